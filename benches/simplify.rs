@@ -0,0 +1,58 @@
+//! Douglas-Peucker simplification of a synthetic 1,000,000-point random walk, with every
+//! split order and from every memory layout of the coordinates.
+//!
+//! Run with `cargo bench --bench simplify`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use trajectory_rs::bench::{synthetic_trajectory, SYNTHETIC_POINTS};
+use trajectory_rs::layout::{BlockedPoints, PackedCoordinates};
+use trajectory_rs::simplify::{self, SplitOrder};
+
+/// Epsilon for simplification (before 1e-6 multiplier), 100 meters precision
+const EPSILON: i64 = 1000;
+
+fn split_orders(c: &mut Criterion) {
+    let trajectory = synthetic_trajectory(SYNTHETIC_POINTS, 0);
+    let (latitudes, longitudes) = (&trajectory.latitudes, &trajectory.longitudes);
+
+    let mut group = c.benchmark_group("simplify");
+    group.throughput(Throughput::Elements(SYNTHETIC_POINTS as u64));
+    group.sample_size(20);
+    for order in SplitOrder::ALL {
+        group.bench_function(order.name(), |b| {
+            b.iter(|| {
+                simplify::simplify_with_order(
+                    black_box(latitudes),
+                    black_box(longitudes),
+                    EPSILON,
+                    order,
+                )
+            })
+        });
+    }
+    group.finish();
+}
+
+fn layouts(c: &mut Criterion) {
+    let trajectory = synthetic_trajectory(SYNTHETIC_POINTS, 0);
+    let (latitudes, longitudes) = (&trajectory.latitudes, &trajectory.longitudes);
+    let blocked = BlockedPoints::new(latitudes, longitudes);
+    let packed = PackedCoordinates::pack(latitudes, longitudes);
+
+    let mut group = c.benchmark_group("layout");
+    group.throughput(Throughput::Elements(SYNTHETIC_POINTS as u64));
+    group.sample_size(20);
+    group.bench_function("separate", |b| {
+        b.iter(|| simplify::simplify(black_box(latitudes), black_box(longitudes), EPSILON))
+    });
+    group.bench_function("blocked", |b| {
+        b.iter(|| black_box(&blocked).simplify(EPSILON))
+    });
+    group.bench_function("packed", |b| {
+        b.iter(|| black_box(&packed).simplify(EPSILON))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, split_orders, layouts);
+criterion_main!(benches);
@@ -0,0 +1,143 @@
+//! Detection of trips deviating from the routes a user usually travels.
+//!
+//! The frequent routes of every user, found by `routes::mine_routes`, are represented by
+//! their first trip. A trip is scored by its Fréchet distance to the closest of these
+//! prototypes, and flagged when the score exceeds a threshold. Users without frequent
+//! routes have no history to deviate from, so their trips are never flagged.
+
+use crate::routes::{mine_routes, RouteParams, Trip};
+use crate::similarity::frechet_m;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// Thresholds of the anomaly detection.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnomalyParams {
+    /// Grouping of trips into routes
+    pub routes: RouteParams,
+    /// Smallest number of trips of a route used as a prototype
+    pub min_route_trips: usize,
+    /// Largest Fréchet distance in meters to the closest prototype of a normal trip
+    pub max_deviation_m: f64,
+}
+
+/// Trip deviating from the routes of its user.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Anomaly {
+    /// Index of the trip
+    pub trip: usize,
+    /// Fréchet distance in meters to the closest prototype
+    pub deviation_m: f64,
+}
+
+/// Flags the anomalous trips, the most deviating first.
+pub fn detect(trips: &[Trip], params: AnomalyParams) -> Vec<Anomaly> {
+    let mut prototypes: HashMap<&str, Vec<usize>> = HashMap::new();
+    for route in mine_routes(trips, params.routes) {
+        if route.trips.len() >= params.min_route_trips {
+            let user = trips[route.trips[0]].user.as_str();
+            prototypes.entry(user).or_default().push(route.trips[0]);
+        }
+    }
+
+    let mut anomalies: Vec<Anomaly> = trips
+        .iter()
+        .enumerate()
+        .filter_map(|(i, trip)| {
+            let deviation_m = prototypes
+                .get(trip.user.as_str())?
+                .iter()
+                .filter_map(|&prototype| frechet_m(&trip.trajectory, &trips[prototype].trajectory))
+                .reduce(f64::min)?;
+            (deviation_m > params.max_deviation_m).then_some(Anomaly {
+                trip: i,
+                deviation_m,
+            })
+        })
+        .collect();
+    anomalies.sort_by(|a, b| b.deviation_m.total_cmp(&a.deviation_m));
+    anomalies
+}
+
+/// Builds the list of flagged trips as JSON, with the thresholds used.
+pub fn anomalies_json(anomalies: &[Anomaly], trips: &[Trip], params: AnomalyParams) -> Value {
+    let anomalies: Vec<Value> = anomalies
+        .iter()
+        .map(|anomaly| {
+            let trip = &trips[anomaly.trip];
+            json!({
+                "trip": trip.name,
+                "user": trip.user,
+                "deviation_m": anomaly.deviation_m,
+            })
+        })
+        .collect();
+    json!({
+        "min_route_trips": params.min_route_trips,
+        "max_deviation_m": params.max_deviation_m,
+        "anomalies": anomalies,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trajectory::CoordinateSystem;
+    use crate::trajectory::Trajectory;
+
+    const PARAMS: AnomalyParams = AnomalyParams {
+        routes: RouteParams {
+            endpoint_radius_m: 200.0,
+            path_tolerance_m: 100.0,
+        },
+        min_route_trips: 2,
+        max_deviation_m: 500.0,
+    };
+
+    fn trip(user: &str, name: &str, points: &[(i64, i64)]) -> Trip {
+        Trip {
+            user: user.to_string(),
+            name: name.to_string(),
+            trajectory: Trajectory {
+                latitudes: points.iter().map(|p| p.0).collect(),
+                longitudes: points.iter().map(|p| p.1).collect(),
+                timestamps: (0..points.len() as u64).collect(),
+                altitudes: Vec::new(),
+                coordinates: CoordinateSystem::Wgs84,
+            },
+        }
+    }
+
+    #[test]
+    fn test_detect() {
+        let home = (39_900_000, 116_400_000);
+        let work = (39_920_000, 116_420_000);
+        let direct = [home, (39_910_000, 116_410_000), work];
+        // About 300 m and 1.4 km off the usual path
+        let slight = [home, (39_912_000, 116_408_000), work];
+        let detour = [home, (39_920_000, 116_400_000), work];
+
+        let trips = vec![
+            trip("a", "a1", &direct),
+            trip("a", "a2", &direct),
+            trip("a", "a3", &slight),
+            trip("a", "a4", &detour),
+            trip("b", "b1", &detour),
+        ];
+        let anomalies = detect(&trips, PARAMS);
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].trip, 3);
+        assert!(anomalies[0].deviation_m > 1_000.0);
+
+        let json = anomalies_json(&anomalies, &trips, PARAMS);
+        assert_eq!(json["anomalies"][0]["trip"], "a4");
+        assert_eq!(json["max_deviation_m"], 500.0);
+
+        // Without frequent routes, nothing is flagged
+        let strict = AnomalyParams {
+            min_route_trips: 3,
+            ..PARAMS
+        };
+        assert!(detect(&trips, strict).is_empty());
+    }
+}
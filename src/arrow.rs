@@ -0,0 +1,75 @@
+//! Simplified trajectories as Apache Arrow record batches, for analytical clients
+//! (pyarrow, DuckDB, Polars, ...), which `flight` serves over Arrow Flight.
+//!
+//! The columns are `file` (utf8), `timestamp` (seconds, UTC), `latitude` and `longitude`
+//! (degrees), none of them nullable.
+
+use crate::geo::to_degrees;
+use crate::trajectory::Trajectory;
+use arrow_array::{ArrayRef, Float64Array, RecordBatch, StringArray, TimestampSecondArray};
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef, TimeUnit};
+use std::sync::Arc;
+
+/// Time zone of the `timestamp` column
+const TIME_ZONE: &str = "UTC";
+
+/// Schema of the record batches.
+pub fn schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("file", DataType::Utf8, false),
+        Field::new(
+            "timestamp",
+            DataType::Timestamp(TimeUnit::Second, Some(TIME_ZONE.into())),
+            false,
+        ),
+        Field::new("latitude", DataType::Float64, false),
+        Field::new("longitude", DataType::Float64, false),
+    ]))
+}
+
+/// Record batch of the points of `trajectory`, read from `file`.
+pub fn record_batch(file: &str, trajectory: &Trajectory) -> Result<RecordBatch, ArrowError> {
+    let degrees =
+        |units: &[i64]| Float64Array::from_iter_values(units.iter().map(|&u| to_degrees(u)));
+    let timestamps = trajectory.timestamps.iter().map(|&ts| ts as i64);
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from_iter_values(std::iter::repeat_n(
+            file,
+            trajectory.timestamps.len(),
+        ))),
+        Arc::new(TimestampSecondArray::from_iter_values(timestamps).with_timezone(TIME_ZONE)),
+        Arc::new(degrees(&trajectory.latitudes)),
+        Arc::new(degrees(&trajectory.longitudes)),
+    ];
+    RecordBatch::try_new(schema(), columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trajectory::CoordinateSystem;
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::TimestampSecondType;
+
+    #[test]
+    fn test_record_batch() {
+        let trajectory = Trajectory {
+            latitudes: vec![39_900_000, 39_910_000, 39_920_000],
+            longitudes: vec![116_400_000, 116_410_000, 116_420_000],
+            timestamps: vec![1_224_730_384, 1_224_730_389, 1_224_730_394],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+        let batch = record_batch("a.plt", &trajectory).unwrap();
+        assert_eq!(batch.schema(), schema());
+        assert_eq!(batch.num_rows(), 3);
+        assert_eq!(batch.column(0).as_string::<i32>().value(2), "a.plt");
+        let timestamps = batch.column(1).as_primitive::<TimestampSecondType>();
+        assert_eq!(timestamps.value(1), 1_224_730_389);
+        let latitudes = batch
+            .column(2)
+            .as_primitive::<arrow_array::types::Float64Type>();
+        assert_eq!(latitudes.value(2), 39.92);
+        assert_eq!(batch.column(3).len(), 3);
+    }
+}
@@ -0,0 +1,445 @@
+//! Comparison of the serialization backends and of the simplification strategies on the
+//! same trajectory, and of the compression obtained at different tolerances.
+
+use crate::encoding::Encoding;
+use crate::layout::{BlockedPoints, PackedCoordinates};
+use crate::simplify::{self, SplitOrder};
+use crate::stays::{self, StayParams};
+use crate::trajectory::{CoordinateSystem, Trajectory};
+use crate::{binary, geo, polyline, proto, quality, AppError};
+use num_format::{Locale, ToFormattedString};
+use prost::Message;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+/// Locale for number formatting
+pub const LOCALE: Locale = Locale::en;
+
+/// Number of times each backend encodes and decodes the trajectory, and each
+/// strategy simplifies it
+const ITERATIONS: u32 = 10;
+
+/// Number of points of the synthetic trajectory used to benchmark simplification
+pub const SYNTHETIC_POINTS: usize = 1_000_000;
+
+/// Measurements of one serialization backend.
+pub struct FormatReport {
+    pub encoding: Encoding,
+    pub bytes: usize,
+    pub encode_time: Duration,
+    pub decode_time: Duration,
+}
+
+/// Decodes the bytes produced by `encoding` and returns the number of points read.
+fn decode(encoding: Encoding, bytes: &[u8]) -> Result<usize, AppError> {
+    Ok(match encoding {
+        Encoding::Proto => Trajectory::from_proto(proto::Trajectory::decode(bytes)?)?
+            .latitudes
+            .len(),
+        Encoding::DeltaProto => Trajectory::from_delta_proto(proto::Trajectory::decode(bytes)?)?
+            .latitudes
+            .len(),
+        Encoding::Polyline => polyline::decode(std::str::from_utf8(bytes)?)?.0.len(),
+        Encoding::Binary => binary::decode(bytes)?.latitudes.len(),
+    })
+}
+
+/// Encodes and decodes the trajectory with every supported backend.
+///
+/// Times are averaged over `ITERATIONS` runs, after checking once that the backend reads
+/// back every point it wrote.
+pub fn bench_formats(trajectory: &Trajectory) -> Result<Vec<FormatReport>, AppError> {
+    Encoding::ALL
+        .into_iter()
+        .map(|encoding| {
+            let points = decode(encoding, &encoding.encode(trajectory)?)?;
+            if points != trajectory.latitudes.len() {
+                return Err(AppError::RoundTrip {
+                    encoding: encoding.name(),
+                    expected: trajectory.latitudes.len(),
+                    found: points,
+                });
+            }
+
+            let start = Instant::now();
+            let mut bytes = Vec::new();
+            for _ in 0..ITERATIONS {
+                bytes = encoding.encode(trajectory)?;
+            }
+            let encode_time = start.elapsed() / ITERATIONS;
+
+            let start = Instant::now();
+            for _ in 0..ITERATIONS {
+                decode(encoding, &bytes)?;
+            }
+            let decode_time = start.elapsed() / ITERATIONS;
+
+            Ok(FormatReport {
+                encoding,
+                bytes: bytes.len(),
+                encode_time,
+                decode_time,
+            })
+        })
+        .collect()
+}
+
+/// Prints the reports as a table.
+pub fn print_reports(reports: &[FormatReport]) {
+    println!(
+        "{:<12} {:>14} {:>14} {:>14}",
+        "format", "bytes", "encode", "decode"
+    );
+    for report in reports {
+        println!(
+            "{:<12} {:>14} {:>14} {:>14}",
+            report.encoding.name(),
+            report.bytes.to_formatted_string(&LOCALE),
+            format!("{:?}", report.encode_time),
+            format!("{:?}", report.decode_time),
+        );
+    }
+}
+
+/// Generates a random walk of `points` points, one per second, moving up to 10 scaled
+/// units per step along each axis.
+pub fn synthetic_trajectory(points: usize, seed: u64) -> Trajectory {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let (mut latitude, mut longitude) = (39_900_000_i64, 116_400_000_i64);
+    let mut trajectory = Trajectory {
+        latitudes: Vec::with_capacity(points),
+        longitudes: Vec::with_capacity(points),
+        timestamps: Vec::with_capacity(points),
+        altitudes: Vec::new(),
+        coordinates: CoordinateSystem::Wgs84,
+    };
+
+    for ts in 0..points as u64 {
+        latitude += rng.gen_range(-10..=10);
+        longitude += rng.gen_range(-10..=10);
+        trajectory.latitudes.push(latitude);
+        trajectory.longitudes.push(longitude);
+        trajectory.timestamps.push(ts);
+    }
+
+    trajectory
+}
+
+/// Measurements of one simplification strategy.
+pub struct SimplifyReport {
+    pub order: SplitOrder,
+    pub kept: usize,
+    pub time: Duration,
+}
+
+/// Simplifies the trajectory with every split order.
+///
+/// Times are averaged over `ITERATIONS` runs.
+pub fn bench_simplify(trajectory: &Trajectory, epsilon: i64) -> Vec<SimplifyReport> {
+    SplitOrder::ALL
+        .into_iter()
+        .map(|order| {
+            let start = Instant::now();
+            let mut keep = Vec::new();
+            for _ in 0..ITERATIONS {
+                keep = simplify::simplify_with_order(
+                    &trajectory.latitudes,
+                    &trajectory.longitudes,
+                    epsilon,
+                    order,
+                );
+            }
+            let time = start.elapsed() / ITERATIONS;
+
+            SimplifyReport {
+                order,
+                kept: keep.iter().filter(|&&keep| keep).count(),
+                time,
+            }
+        })
+        .collect()
+}
+
+/// Prints the simplification reports as a table.
+pub fn print_simplify_reports(reports: &[SimplifyReport]) {
+    println!("{:<16} {:>14} {:>14}", "order", "kept", "time");
+    for report in reports {
+        println!(
+            "{:<16} {:>14} {:>14}",
+            report.order.name(),
+            report.kept.to_formatted_string(&LOCALE),
+            format!("{:?}", report.time),
+        );
+    }
+}
+
+/// Measurements of one memory layout of the coordinates.
+pub struct LayoutReport {
+    pub name: &'static str,
+    pub kept: usize,
+    /// Time to build the layout from the trajectory, zero for the trajectory itself
+    pub build_time: Duration,
+    pub time: Duration,
+}
+
+/// Simplifies the trajectory from its two separate coordinate vectors, from
+/// `layout::BlockedPoints`, and from `layout::PackedCoordinates`, named after the type the
+/// coordinates are packed in.
+///
+/// Times are averaged over `ITERATIONS` runs.
+///
+/// # Panics
+///
+/// This function will panic if the layouts keep different points.
+pub fn bench_layouts(trajectory: &Trajectory, epsilon: i64) -> Vec<LayoutReport> {
+    let (latitudes, longitudes) = (&trajectory.latitudes, &trajectory.longitudes);
+    let time = |simplify: &dyn Fn() -> Vec<bool>| {
+        let start = Instant::now();
+        let mut keep = Vec::new();
+        for _ in 0..ITERATIONS {
+            keep = simplify();
+        }
+        (keep, start.elapsed() / ITERATIONS)
+    };
+
+    let (separate, separate_time) = time(&|| simplify::simplify(latitudes, longitudes, epsilon));
+    let start = Instant::now();
+    let blocked = BlockedPoints::new(latitudes, longitudes);
+    let build_time = start.elapsed();
+    let (blocked_keep, blocked_time) = time(&|| blocked.simplify(epsilon));
+    assert_eq!(separate, blocked_keep, "the layouts keep the same points");
+    let start = Instant::now();
+    let packed = PackedCoordinates::pack(latitudes, longitudes);
+    let packed_build_time = start.elapsed();
+    let (packed_keep, packed_time) = time(&|| packed.simplify(epsilon));
+    assert_eq!(separate, packed_keep, "the layouts keep the same points");
+
+    let kept = separate.iter().filter(|&&keep| keep).count();
+    vec![
+        LayoutReport {
+            name: "separate",
+            kept,
+            build_time: Duration::ZERO,
+            time: separate_time,
+        },
+        LayoutReport {
+            name: "blocked",
+            kept,
+            build_time,
+            time: blocked_time,
+        },
+        LayoutReport {
+            name: match packed {
+                PackedCoordinates::I32 { .. } => "packed-i32",
+                PackedCoordinates::I64 { .. } => "packed-i64",
+            },
+            kept,
+            build_time: packed_build_time,
+            time: packed_time,
+        },
+    ]
+}
+
+/// Prints the layout reports as a table.
+pub fn print_layout_reports(reports: &[LayoutReport]) {
+    println!(
+        "{:<12} {:>14} {:>14} {:>14}",
+        "layout", "kept", "build", "time"
+    );
+    for report in reports {
+        println!(
+            "{:<12} {:>14} {:>14} {:>14}",
+            report.name,
+            report.kept.to_formatted_string(&LOCALE),
+            format!("{:?}", report.build_time),
+            format!("{:?}", report.time),
+        );
+    }
+}
+
+/// Measurements of one stay-point detection strategy.
+pub struct StayReport {
+    pub name: &'static str,
+    pub stays: usize,
+    pub time: Duration,
+}
+
+/// Detects the stay points of every trajectory following each anchor, with the range
+/// tree, and with the range tree on `threads` threads.
+pub fn bench_stay_points(
+    trajectories: &[Trajectory],
+    params: StayParams,
+    threads: usize,
+) -> Vec<StayReport> {
+    let count = |stays: &[Vec<stays::StayPoint>]| stays.iter().map(Vec::len).sum();
+    let mut reports = Vec::new();
+
+    let start = Instant::now();
+    let found: Vec<_> = trajectories
+        .iter()
+        .map(|trajectory| stays::detect_naive(trajectory, params))
+        .collect();
+    reports.push(StayReport {
+        name: "naive",
+        stays: count(&found),
+        time: start.elapsed(),
+    });
+
+    let start = Instant::now();
+    let found: Vec<_> = trajectories
+        .iter()
+        .map(|trajectory| stays::detect(trajectory, params))
+        .collect();
+    reports.push(StayReport {
+        name: "indexed",
+        stays: count(&found),
+        time: start.elapsed(),
+    });
+
+    let start = Instant::now();
+    let found = stays::detect_batch(trajectories, params, threads);
+    reports.push(StayReport {
+        name: "indexed-parallel",
+        stays: count(&found),
+        time: start.elapsed(),
+    });
+
+    reports
+}
+
+/// Prints the stay-point reports as a table.
+pub fn print_stay_reports(reports: &[StayReport]) {
+    println!("{:<16} {:>14} {:>14}", "strategy", "stays", "time");
+    for report in reports {
+        println!(
+            "{:<16} {:>14} {:>14}",
+            report.name,
+            report.stays.to_formatted_string(&LOCALE),
+            format!("{:?}", report.time),
+        );
+    }
+}
+
+/// Compression obtained at one tolerance.
+pub struct SweepRow {
+    pub epsilon_m: f64,
+    pub kept_percent: f64,
+    /// Size of the simplified trajectory in every encoding, in `Encoding::ALL` order
+    pub bytes: Vec<usize>,
+    pub max_error_m: f64,
+}
+
+/// Simplifies the trajectory at every tolerance, in meters, and measures the result.
+pub fn sweep(trajectory: &Trajectory, epsilons_m: &[f64]) -> Result<Vec<SweepRow>, AppError> {
+    epsilons_m
+        .iter()
+        .map(|&epsilon_m| {
+            let keep = simplify::simplify(
+                &trajectory.latitudes,
+                &trajectory.longitudes,
+                geo::meters_to_units(epsilon_m),
+            );
+            let max_error_m = quality::segment_errors(trajectory, &keep)
+                .iter()
+                .map(|segment| segment.max_error_m)
+                .fold(0.0, f64::max);
+            let kept = keep.iter().filter(|&&keep| keep).count();
+            let simplified = trajectory.clone().filter(&keep);
+            let bytes = Encoding::ALL
+                .into_iter()
+                .map(|encoding| Ok(encoding.encode(&simplified)?.len()))
+                .collect::<Result<_, AppError>>()?;
+
+            Ok(SweepRow {
+                epsilon_m,
+                kept_percent: kept as f64 / trajectory.latitudes.len().max(1) as f64 * 100.0,
+                bytes,
+                max_error_m,
+            })
+        })
+        .collect()
+}
+
+/// Writes the sweep as CSV, with one `bytes_<encoding>` column per encoding.
+pub fn write_sweep_csv(writer: &mut impl Write, rows: &[SweepRow]) -> io::Result<()> {
+    write!(writer, "epsilon_m,points_kept_pct")?;
+    for encoding in Encoding::ALL {
+        write!(writer, ",bytes_{}", encoding.name())?;
+    }
+    writeln!(writer, ",max_error_m")?;
+
+    for row in rows {
+        write!(writer, "{},{:.4}", row.epsilon_m, row.kept_percent)?;
+        for bytes in &row.bytes {
+            write!(writer, ",{bytes}")?;
+        }
+        writeln!(writer, ",{:.3}", row.max_error_m)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sweep() {
+        let trajectory = synthetic_trajectory(2_000, 1);
+        let rows = sweep(&trajectory, &[0.0, 5.0, 50.0]).unwrap();
+
+        assert!(rows[0].kept_percent > 50.0);
+        assert!(rows[2].kept_percent < 1.0);
+        for pair in rows.windows(2) {
+            assert!(pair[1].kept_percent <= pair[0].kept_percent);
+            for (larger, smaller) in pair[0].bytes.iter().zip(&pair[1].bytes) {
+                assert!(smaller <= larger);
+            }
+        }
+        for row in &rows {
+            assert_eq!(row.bytes.len(), Encoding::ALL.len());
+            assert!(row.max_error_m <= row.epsilon_m * 1.01 + 0.2);
+        }
+    }
+
+    #[test]
+    fn test_bench_stay_points() {
+        let trajectories: Vec<Trajectory> = (0..3).map(|seed| synthetic_trajectory(2_000, seed)).collect();
+        let params = StayParams {
+            max_distance_m: 20.0,
+            min_duration_s: 60,
+        };
+        let reports = bench_stay_points(&trajectories, params, 2);
+        assert_eq!(reports.len(), 3);
+        assert!(reports[0].stays > 0);
+        assert!(reports.iter().all(|report| report.stays == reports[0].stays));
+    }
+
+    #[test]
+    fn test_bench_layouts() {
+        let trajectory = synthetic_trajectory(5_000, 2);
+        let reports = bench_layouts(&trajectory, 20);
+        let names: Vec<&str> = reports.iter().map(|report| report.name).collect();
+        assert_eq!(names, ["separate", "blocked", "packed-i32"]);
+        assert!(reports[0].kept > 2);
+        assert_eq!(reports[0].build_time, Duration::ZERO);
+    }
+
+    #[test]
+    fn test_write_sweep_csv() {
+        let rows = [SweepRow {
+            epsilon_m: 10.0,
+            kept_percent: 12.5,
+            bytes: vec![100, 50, 40, 30],
+            max_error_m: 9.87654,
+        }];
+        let mut csv = Vec::new();
+        write_sweep_csv(&mut csv, &rows).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "epsilon_m,points_kept_pct,bytes_proto,bytes_proto-delta,bytes_polyline,\
+             bytes_binary,max_error_m\n10,12.5000,100,50,40,30,9.877\n"
+        );
+    }
+}
@@ -0,0 +1,230 @@
+//! Compact custom binary format for trajectories.
+//!
+//! Layout:
+//! - 4 bytes magic `TRJB`
+//! - 1 byte format version
+//! - 8 bytes little-endian seahash of the payload that follows (since version 2)
+//! - varint number of points
+//! - latitudes, longitudes and timestamps as zigzag varint deltas, one column after the other
+//!
+//! Version 1 files, which have no checksum, are still decoded.
+
+use crate::delta::{delta_decode, delta_encode};
+use crate::trajectory::{CoordinateSystem, Trajectory};
+use thiserror::Error;
+
+/// Magic bytes at the start of every binary trajectory
+const MAGIC: &[u8; 4] = b"TRJB";
+
+/// Current version of the binary format
+pub const VERSION: u8 = 2;
+
+/// Last version without a checksum
+const VERSION_WITHOUT_CHECKSUM: u8 = 1;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum BinaryError {
+    #[error("Invalid magic bytes")]
+    InvalidMagic,
+    #[error("Unsupported format version: {0}")]
+    UnsupportedVersion(u8),
+    #[error("Unexpected end of input")]
+    UnexpectedEof,
+    #[error("Varint is too long")]
+    VarintOverflow,
+    #[error("Trailing bytes after the last column")]
+    TrailingBytes,
+    #[error("Checksum mismatch, the file is corrupted")]
+    ChecksumMismatch,
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+fn write_varint(mut value: u64, output: &mut Vec<u8>) {
+    while value >= 0x80 {
+        output.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    output.push(value as u8);
+}
+
+fn read_varint(input: &mut &[u8]) -> Result<u64, BinaryError> {
+    let mut result = 0_u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = input.split_first().ok_or(BinaryError::UnexpectedEof)?;
+        *input = rest;
+        result |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(result);
+        }
+    }
+    Err(BinaryError::VarintOverflow)
+}
+
+fn write_column(values: impl Iterator<Item = i64>, output: &mut Vec<u8>) {
+    for delta in delta_encode(values) {
+        write_varint(zigzag(delta), output);
+    }
+}
+
+fn read_column(len: usize, input: &mut &[u8]) -> Result<Vec<i64>, BinaryError> {
+    let mut deltas = Vec::with_capacity(len.min(input.len()));
+    for _ in 0..len {
+        deltas.push(unzigzag(read_varint(input)?));
+    }
+    Ok(delta_decode(deltas).collect())
+}
+
+/// Encodes a trajectory into the binary format.
+pub fn encode(trajectory: &Trajectory) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(trajectory.latitudes.len() * 6);
+    write_varint(trajectory.latitudes.len() as u64, &mut payload);
+    write_column(trajectory.latitudes.iter().copied(), &mut payload);
+    write_column(trajectory.longitudes.iter().copied(), &mut payload);
+    write_column(trajectory.timestamps.iter().map(|&ts| ts as i64), &mut payload);
+
+    let mut output = Vec::with_capacity(MAGIC.len() + 9 + payload.len());
+    output.extend_from_slice(MAGIC);
+    output.push(VERSION);
+    output.extend_from_slice(&seahash::hash(&payload).to_le_bytes());
+    output.extend_from_slice(&payload);
+    output
+}
+
+/// Returns the version of the format an encoded trajectory was written with.
+pub fn version(input: &[u8]) -> Result<u8, BinaryError> {
+    let magic = input.get(..MAGIC.len()).ok_or(BinaryError::UnexpectedEof)?;
+    if magic != MAGIC {
+        return Err(BinaryError::InvalidMagic);
+    }
+    input
+        .get(MAGIC.len())
+        .copied()
+        .ok_or(BinaryError::UnexpectedEof)
+}
+
+/// Decodes a trajectory from the binary format.
+pub fn decode(mut input: &[u8]) -> Result<Trajectory, BinaryError> {
+    let version = version(input)?;
+    input = &input[MAGIC.len() + 1..];
+    match version {
+        VERSION => {
+            let checksum = input.get(..8).ok_or(BinaryError::UnexpectedEof)?;
+            input = &input[8..];
+            if u64::from_le_bytes(checksum.try_into().unwrap()) != seahash::hash(input) {
+                return Err(BinaryError::ChecksumMismatch);
+            }
+        }
+        VERSION_WITHOUT_CHECKSUM => {}
+        _ => return Err(BinaryError::UnsupportedVersion(version)),
+    }
+
+    let len = read_varint(&mut input)? as usize;
+    let latitudes = read_column(len, &mut input)?;
+    let longitudes = read_column(len, &mut input)?;
+    let timestamps = read_column(len, &mut input)?
+        .into_iter()
+        .map(|ts| ts as u64)
+        .collect();
+
+    if !input.is_empty() {
+        return Err(BinaryError::TrailingBytes);
+    }
+
+    Ok(Trajectory {
+        latitudes,
+        longitudes,
+        timestamps,
+        altitudes: Vec::new(),
+        coordinates: CoordinateSystem::Wgs84,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Trajectory {
+        Trajectory {
+            latitudes: vec![39_984_702, 39_984_683, 39_984_686],
+            longitudes: vec![116_318_417, 116_318_450, -116_318_417],
+            timestamps: vec![1_224_730_384, 1_224_730_390, 1_224_730_385],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        }
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let trajectory = sample();
+        assert_eq!(decode(&encode(&trajectory)).unwrap(), trajectory);
+    }
+
+    #[test]
+    fn test_roundtrip_empty() {
+        let trajectory = Trajectory {
+            latitudes: vec![],
+            longitudes: vec![],
+            timestamps: vec![],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+        let encoded = encode(&trajectory);
+        assert_eq!(encoded.len(), 14);
+        assert!(decode(&encoded).unwrap().latitudes.is_empty());
+    }
+
+    #[test]
+    fn test_zigzag() {
+        for value in [0, -1, 1, i64::MIN, i64::MAX, 123_456_789] {
+            assert_eq!(unzigzag(zigzag(value)), value);
+        }
+        assert_eq!(zigzag(-1), 1);
+        assert_eq!(zigzag(1), 2);
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let encoded = encode(&sample());
+        assert_eq!(decode(b"XXXX\x01\x00").unwrap_err(), BinaryError::InvalidMagic);
+        assert_eq!(decode(b"TRJB\x03\x00").unwrap_err(), BinaryError::UnsupportedVersion(3));
+        assert_eq!(decode(b"TRJB\x02\x00").unwrap_err(), BinaryError::UnexpectedEof);
+        assert_eq!(
+            decode(&encoded[..encoded.len() - 1]).unwrap_err(),
+            BinaryError::ChecksumMismatch
+        );
+        let mut trailing = encoded.clone();
+        trailing.push(0);
+        assert_eq!(decode(&trailing).unwrap_err(), BinaryError::ChecksumMismatch);
+    }
+
+    #[test]
+    fn test_decode_detects_corruption() {
+        let encoded = encode(&sample());
+        for index in [5, 13, 14, encoded.len() - 1] {
+            let mut corrupted = encoded.clone();
+            corrupted[index] ^= 0x10;
+            assert_eq!(decode(&corrupted).unwrap_err(), BinaryError::ChecksumMismatch);
+        }
+    }
+
+    #[test]
+    fn test_decode_version_without_checksum() {
+        let encoded = encode(&sample());
+        let mut version_1 = b"TRJB\x01".to_vec();
+        assert_eq!(version(&version_1), Ok(1));
+        version_1.extend_from_slice(&encoded[13..]);
+        assert_eq!(decode(&version_1).unwrap(), sample());
+
+        version_1.pop();
+        assert_eq!(decode(&version_1).unwrap_err(), BinaryError::UnexpectedEof);
+        version_1.extend_from_slice(&[0, 0]);
+        assert_eq!(decode(&version_1).unwrap_err(), BinaryError::TrailingBytes);
+    }
+}
@@ -0,0 +1,222 @@
+//! Bootstrap confidence intervals on the distribution of the reconstruction error across a
+//! dataset, to validate requirements such as "95% of points within 25 m".
+//!
+//! Errors of points of the same trajectory are correlated, so trajectories are resampled
+//! whole: each resample draws as many trajectories as the dataset holds, with
+//! replacement, and its statistics are computed over all of their points. Intervals are
+//! the percentiles of the statistics of the resamples.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Settings of the bootstrap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BootstrapParams {
+    /// Number of resamples
+    pub resamples: usize,
+    /// Probability covered by the intervals, e.g. 0.95
+    pub confidence: f64,
+    pub seed: u64,
+}
+
+/// Statistic measured on the whole dataset, with its confidence interval.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    pub value: f64,
+    pub low: f64,
+    pub high: f64,
+}
+
+/// Bootstrap estimates of the error distribution.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorReport {
+    /// Error in meters below which each requested fraction of the points lies
+    pub quantiles: Vec<(f64, Estimate)>,
+    /// Fraction of the points within the threshold in meters
+    pub within: (f64, Estimate),
+    pub points: usize,
+    pub trajectories: usize,
+}
+
+/// Smallest error such that at least a fraction `q` of the weight lies at or below it, in
+/// `errors` sorted by error with the trajectory of each one.
+fn weighted_quantiles(errors: &[(f64, usize)], weights: &[u32], quantiles: &[f64]) -> Vec<f64> {
+    let total: u64 = errors.iter().map(|&(_, t)| u64::from(weights[t])).sum();
+    let mut values = vec![errors.last().map_or(0.0, |&(error, _)| error); quantiles.len()];
+    let mut cumulative = 0;
+    let mut next = 0;
+    // Quantiles are answered in increasing order
+    let mut order: Vec<usize> = (0..quantiles.len()).collect();
+    order.sort_by(|&a, &b| quantiles[a].total_cmp(&quantiles[b]));
+
+    for &(error, trajectory) in errors {
+        if weights[trajectory] == 0 {
+            continue;
+        }
+        cumulative += u64::from(weights[trajectory]);
+        while next < order.len() && cumulative as f64 >= quantiles[order[next]] * total as f64 {
+            values[order[next]] = error;
+            next += 1;
+        }
+        if next == order.len() {
+            break;
+        }
+    }
+    values
+}
+
+/// Value of the sorted `values` at fraction `q`, by nearest rank.
+fn percentile(sorted: &[f64], q: f64) -> f64 {
+    let rank = (q * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Estimates the `quantiles` of the point errors and the fraction of points within
+/// `threshold_m`, from the errors of the points of every trajectory.
+///
+/// # Panics
+///
+/// This function will panic if `params.resamples` is zero.
+pub fn error_report(
+    errors: &[Vec<f64>],
+    quantiles: &[f64],
+    threshold_m: f64,
+    params: BootstrapParams,
+) -> ErrorReport {
+    assert!(params.resamples > 0, "resamples > 0");
+
+    let mut sorted: Vec<(f64, usize)> = errors
+        .iter()
+        .enumerate()
+        .flat_map(|(t, errors)| errors.iter().map(move |&error| (error, t)))
+        .collect();
+    sorted.sort_by(|a, b| a.0.total_cmp(&b.0));
+    // Points and points within the threshold of every trajectory
+    let counts: Vec<(u64, u64)> = errors
+        .iter()
+        .map(|errors| {
+            let within = errors.iter().filter(|&&error| error <= threshold_m).count();
+            (errors.len() as u64, within as u64)
+        })
+        .collect();
+    let within_fraction = |weights: &[u32]| {
+        let (points, within) =
+            counts
+                .iter()
+                .zip(weights)
+                .fold((0, 0), |(points, within), (&(p, w), &weight)| {
+                    (
+                        points + p * u64::from(weight),
+                        within + w * u64::from(weight),
+                    )
+                });
+        if points == 0 {
+            1.0
+        } else {
+            within as f64 / points as f64
+        }
+    };
+
+    let ones = vec![1; errors.len()];
+    let values = weighted_quantiles(&sorted, &ones, quantiles);
+    let within = within_fraction(&ones);
+
+    // Statistics of every resample, quantiles first then the fraction within
+    let mut resampled = vec![Vec::with_capacity(params.resamples); quantiles.len() + 1];
+    let mut rng = StdRng::seed_from_u64(params.seed);
+    let mut weights = vec![0; errors.len()];
+    for _ in 0..params.resamples {
+        weights.fill(0);
+        for _ in 0..errors.len() {
+            weights[rng.gen_range(0..errors.len())] += 1;
+        }
+        let values = weighted_quantiles(&sorted, &weights, quantiles);
+        for (statistic, value) in resampled.iter_mut().zip(values) {
+            statistic.push(value);
+        }
+        resampled[quantiles.len()].push(within_fraction(&weights));
+    }
+
+    let tail = (1.0 - params.confidence) / 2.0;
+    let mut estimates = resampled
+        .into_iter()
+        .zip(values.into_iter().chain([within]))
+        .map(|(mut statistic, value)| {
+            statistic.sort_by(f64::total_cmp);
+            Estimate {
+                value,
+                low: percentile(&statistic, tail),
+                high: percentile(&statistic, 1.0 - tail),
+            }
+        });
+
+    ErrorReport {
+        quantiles: quantiles
+            .iter()
+            .map(|&q| (q, estimates.next().unwrap()))
+            .collect(),
+        within: (threshold_m, estimates.next().unwrap()),
+        points: sorted.len(),
+        trajectories: errors.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PARAMS: BootstrapParams = BootstrapParams {
+        resamples: 200,
+        confidence: 0.9,
+        seed: 0,
+    };
+
+    #[test]
+    fn test_weighted_quantiles() {
+        let errors = [(1.0, 0), (2.0, 1), (3.0, 0), (4.0, 1)];
+        assert_eq!(
+            weighted_quantiles(&errors, &[1, 1], &[0.5, 0.25, 1.0]),
+            vec![2.0, 1.0, 4.0]
+        );
+        // Without the first trajectory, then with the second one counted three times
+        assert_eq!(weighted_quantiles(&errors, &[0, 1], &[0.5]), vec![2.0]);
+        assert_eq!(weighted_quantiles(&errors, &[1, 3], &[0.5]), vec![2.0]);
+    }
+
+    #[test]
+    fn test_percentile() {
+        let sorted = [1.0, 2.0, 3.0, 4.0];
+        assert_eq!(percentile(&sorted, 0.0), 1.0);
+        assert_eq!(percentile(&sorted, 0.5), 2.0);
+        assert_eq!(percentile(&sorted, 0.51), 3.0);
+        assert_eq!(percentile(&sorted, 1.0), 4.0);
+    }
+
+    #[test]
+    fn test_error_report() {
+        // 100 trajectories of 10 points, with errors from 0 to 9 m plus an offset of 0 to
+        // 4 m depending on the trajectory
+        let errors: Vec<Vec<f64>> = (0..100)
+            .map(|t| (0..10).map(|i| i as f64 + (t % 5) as f64).collect())
+            .collect();
+        let report = error_report(&errors, &[0.5, 0.95], 10.0, PARAMS);
+
+        assert_eq!(report.points, 1_000);
+        assert_eq!(report.trajectories, 100);
+        let (q, median) = report.quantiles[0];
+        assert_eq!(q, 0.5);
+        assert_eq!(median.value, 6.0);
+        assert!(median.low <= median.value && median.value <= median.high);
+        let (threshold, within) = report.within;
+        assert_eq!(threshold, 10.0);
+        assert_eq!(within.value, 0.88);
+        assert!(within.low < 0.88 && within.high > 0.88 && within.high < 1.0);
+
+        // Identical trajectories leave no uncertainty
+        let errors = vec![vec![1.0, 2.0, 3.0]; 20];
+        let report = error_report(&errors, &[0.95], 2.5, PARAMS);
+        let (_, p95) = report.quantiles[0];
+        assert_eq!((p95.value, p95.low, p95.high), (3.0, 3.0, 3.0));
+        assert_eq!(report.within.1.low, report.within.1.high);
+    }
+}
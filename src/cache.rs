@@ -0,0 +1,140 @@
+//! Cache of simplification results, for servers rendering the same trajectories at a
+//! few levels of detail.
+
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::sync::Arc;
+
+/// Trajectory id and epsilon a mask was computed for
+type Key<K> = (K, i64);
+
+/// Least-recently-used cache of simplification masks keyed by trajectory and epsilon.
+///
+/// Every access stamps the entry with a new generation; when the cache is full, the entry
+/// with the oldest generation is evicted.
+#[derive(Debug)]
+pub struct SimplificationCache<K> {
+    capacity: usize,
+    generation: u64,
+    entries: HashMap<Key<K>, (u64, Arc<[bool]>)>,
+    generations: BTreeMap<u64, Key<K>>,
+    hits: u64,
+    misses: u64,
+}
+
+impl<K: Hash + Eq + Clone> SimplificationCache<K> {
+    /// Creates a cache holding at most `capacity` masks.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity > 0");
+        SimplificationCache {
+            capacity,
+            generation: 0,
+            entries: HashMap::new(),
+            generations: BTreeMap::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Returns the mask of trajectory `id` simplified with `epsilon`, computing it with
+    /// `simplify` if it is not cached.
+    pub fn get_or_insert_with(
+        &mut self,
+        id: K,
+        epsilon: i64,
+        simplify: impl FnOnce() -> Vec<bool>,
+    ) -> Arc<[bool]> {
+        self.generation += 1;
+        let key = (id, epsilon);
+
+        if let Some((generation, mask)) = self.entries.get_mut(&key) {
+            self.hits += 1;
+            self.generations.remove(generation);
+            *generation = self.generation;
+            self.generations.insert(self.generation, key);
+            return mask.clone();
+        }
+
+        self.misses += 1;
+        if self.entries.len() == self.capacity {
+            if let Some((_, oldest)) = self.generations.pop_first() {
+                self.entries.remove(&oldest);
+            }
+        }
+
+        let mask: Arc<[bool]> = simplify().into();
+        self.entries
+            .insert(key.clone(), (self.generation, mask.clone()));
+        self.generations.insert(self.generation, key);
+        mask
+    }
+
+    /// Number of cached masks
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Number of lookups answered from the cache
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Number of lookups that had to simplify
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hit() {
+        let mut cache = SimplificationCache::new(2);
+        let mask = cache.get_or_insert_with("a", 10, || vec![true, false, true]);
+        let cached = cache.get_or_insert_with("a", 10, || unreachable!());
+
+        assert_eq!(mask, cached);
+        assert_eq!((cache.hits(), cache.misses()), (1, 1));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_keyed_by_epsilon() {
+        let mut cache = SimplificationCache::new(4);
+        cache.get_or_insert_with("a", 10, || vec![true, true]);
+        let mask = cache.get_or_insert_with("a", 20, || vec![true, false]);
+
+        assert_eq!(&*mask, &[true, false]);
+        assert_eq!(cache.misses(), 2);
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let mut cache = SimplificationCache::new(2);
+        cache.get_or_insert_with("a", 10, || vec![true]);
+        cache.get_or_insert_with("b", 10, || vec![true]);
+        // Refresh "a", so that "b" is the least recently used
+        cache.get_or_insert_with("a", 10, || unreachable!());
+        cache.get_or_insert_with("c", 10, || vec![true]);
+
+        assert_eq!(cache.len(), 2);
+        cache.get_or_insert_with("a", 10, || unreachable!());
+        cache.get_or_insert_with("c", 10, || unreachable!());
+        let mut recomputed = false;
+        cache.get_or_insert_with("b", 10, || {
+            recomputed = true;
+            vec![true]
+        });
+        assert!(recomputed);
+    }
+}
@@ -0,0 +1,932 @@
+//! Command line of the binary, parsed with `clap`: the options of the default run and the
+//! arguments of every subcommand, each with its own generated `--help`.
+//!
+//! Values are checked as they are parsed (tolerances and radii must be positive, counts
+//! nonzero), so that the subcommands only get arguments they can use.
+
+use clap::builder::RangedU64ValueParser;
+use clap::{value_parser, Args, Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+use trajectory_rs::datum::Datum;
+use trajectory_rs::encoding::Encoding;
+use trajectory_rs::export::ExportFormat;
+use trajectory_rs::merge::ConflictPolicy;
+use trajectory_rs::preset::Preset;
+use trajectory_rs::quality::ErrorMetric;
+use trajectory_rs::tensor::TensorFormat;
+use trajectory_rs::{bench, geohash, matching};
+
+/// Tolerance in meters of `compare-direction` when none is given
+const DIRECTION_TOLERANCE_M: f64 = 50.0;
+
+/// Seconds without points after which `ingest` compacts a trajectory, unless given
+const DEFAULT_IDLE_S: u64 = 300;
+
+/// Number of trips of a route needed by `anomalies` to use it as a prototype, by default
+const ANOMALY_MIN_ROUTE_TRIPS: usize = 3;
+
+/// Shortest GPS outage bridged by `fill-gaps` by default, in seconds
+const FILL_GAPS_MIN_GAP_S: u64 = 10;
+
+/// Reads the GeoLife .plt and GPX .gpx files of a directory, simplifies each of them with
+/// the Douglas-Peucker algorithm, and prints serialization statistics.
+///
+/// Subcommands read the same directory, from $GEOLIFE_DIR or geolife/.
+#[derive(Parser, Debug)]
+#[command(name = "trajectory-rs", args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    #[command(flatten)]
+    pub run: RunArgs,
+    /// Pushes the metrics of the simplification to a Prometheus Pushgateway, with the
+    /// default run or any subcommand
+    #[arg(long, global = true, value_name = "HOST:PORT")]
+    pub push_metrics: Option<String>,
+}
+
+/// Options of the default run.
+#[derive(Args, Debug)]
+pub struct RunArgs {
+    /// Directory to read, by default $GEOLIFE_DIR or geolife/
+    #[arg(long, value_name = "DIR")]
+    pub input: Option<PathBuf>,
+    /// Tolerance in microdegrees (1000 is about 111 m)
+    #[arg(long, default_value_t = crate::EPSILON, value_parser = value_parser!(i64).range(0..))]
+    pub epsilon: i64,
+    /// Writes the delta-encoded protobuf message to the file
+    #[arg(long, value_name = "FILE.pb")]
+    pub output: Option<PathBuf>,
+    /// Tolerances per transportation mode or speed (TOML)
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+    /// Removes GPS jitter with a One-Euro filter first
+    #[arg(long)]
+    pub smooth: bool,
+    /// Anchors loops at their two farthest points
+    #[arg(long)]
+    pub reanchor_loops: bool,
+    /// Writes why each kept point is kept, for debugging
+    #[arg(long, value_name = "FILE.json")]
+    pub explain: Option<PathBuf>,
+    /// Searches the tolerance keeping this ratio of the points, in (0, 1], instead of
+    /// using --epsilon
+    #[arg(long, value_name = "RATIO", value_parser = ratio, conflicts_with = "config")]
+    pub target_ratio: Option<f64>,
+    /// Writes the simplified trajectories as GeoJSON, for geojson.io
+    #[arg(long, value_name = "FILE.geojson")]
+    pub geojson: Option<PathBuf>,
+    /// Prints the report with the projected output sizes without writing any file
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Merges every file into one trajectory sorted by timestamp instead of simplifying
+    /// each file on its own
+    #[arg(long)]
+    pub merge: bool,
+    /// Simplifies each file on its own, the default, kept for earlier scripts
+    #[arg(long, hide = true, conflicts_with = "merge")]
+    pub per_file: bool,
+    /// Splits files into trips at longer gaps between points
+    #[arg(long = "max-gap", value_name = "SECONDS", conflicts_with = "merge")]
+    pub max_gap_s: Option<u64>,
+    /// Splits files into trips at longer jumps between points
+    #[arg(
+        long = "max-jump",
+        value_name = "METERS",
+        value_parser = positive,
+        conflicts_with = "merge"
+    )]
+    pub max_jump_m: Option<f64>,
+    /// Converts points recorded in GCJ-02 or BD-09 to WGS 84 first
+    #[arg(
+        long,
+        value_name = "gcj02|bd09",
+        default_value = "wgs84",
+        hide_default_value = true
+    )]
+    pub datum: Datum,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Encodes the simplified dataset with every serialization backend and compares them
+    BenchFormats,
+    /// Simplifies the dataset with every split order
+    BenchSimplify,
+    /// Simplifies a synthetic random walk from coordinate vectors and from blocks
+    BenchLayout(BenchLayoutArgs),
+    /// Times stay-point detection with and without the range tree
+    BenchStayPoints,
+    /// Writes the compression of the dataset at every tolerance as CSV
+    Sweep(SweepArgs),
+    /// Splits the dataset into train, validation and test subsets
+    Split(SplitArgs),
+    /// Simplifies a trace in local coordinates, such as a warehouse robot log
+    SimplifyLocal(SimplifyLocalArgs),
+    /// Writes every simplified file as a row of a fixed-length tensor
+    ExportTensor(ExportTensorArgs),
+    /// Simplifies every file through the stages of a scheduler
+    Batch(BatchArgs),
+    /// Writes the simplified dataset as GeoJSON segments colored by their error
+    ExportQuality(OutputArgs),
+    /// Prints percentiles of the reconstruction error with confidence intervals
+    ErrorCi(ErrorCiArgs),
+    /// Writes the simplified dataset with geo-indistinguishable noise on every point
+    ExportPrivate(ExportPrivateArgs),
+    /// Writes the convex hull of every file as a GeoJSON polygon
+    ExportHull(OutputArgs),
+    /// Writes the geohash cells visited by the simplified files
+    ExportGeohash(ExportGeohashArgs),
+    /// Writes the geohash cells within a radius of a simplified route
+    CorridorCells(CorridorCellsArgs),
+    /// Writes the area within a radius of a simplified route as a GeoJSON polygon
+    Buffer(BufferArgs),
+    /// Prints the points of interest within a radius of a simplified route
+    PoisAlong(PoisAlongArgs),
+    /// Tells whether two files follow the same path, in the same or opposite directions
+    CompareDirection(CompareDirectionArgs),
+    /// Prints where the simplified routes of two files cross
+    Crossings(CrossingsArgs),
+    /// Writes the routes each user travels, ranked by number of trips
+    Routes(RoutesArgs),
+    /// Writes the distance and time every file spent in each zone, as CSV
+    Zones(ZonesArgs),
+    /// Writes the number of trips between each pair of zones, as CSV
+    OdMatrix(OdMatrixArgs),
+    /// Writes the trips and distance of each user at every hour of the week
+    TemporalReport(TemporalReportArgs),
+    /// Writes the stay points of every file as CSV
+    StayPoints(StayPointsArgs),
+    /// Writes the estimated positional noise of every file as JSON
+    EstimateNoise(OutputArgs),
+    /// Serves the simplified files over Arrow Flight
+    ServeArrow(ServeArrowArgs),
+    /// Serves the simplification over HTTP
+    ServeHttp(ServeHttpArgs),
+    /// Writes the simplified files as Parquet partitioned by user and date
+    ExportParquet(ExportParquetArgs),
+    /// Publishes the latest position of every user to a Redis GEO set
+    PublishRedis(PublishRedisArgs),
+    /// Puts the simplified files into a store, or reads the stored ones
+    Store(StoreArgs),
+    /// Replays the dataset into a raw log, compacted into a store
+    Ingest(IngestArgs),
+    /// Writes every trajectory of a store to a directory
+    Export(ExportArgs),
+    /// Simplifies every file for a zoom level of a web map with a preset
+    Generalize(GeneralizeArgs),
+    /// Imports GPX, CSV, Google Takeout and .plt files into a store
+    Import(ImportArgs),
+    /// Upgrades the trajectories of a store to the current binary format
+    Migrate(MigrateArgs),
+    /// Writes the driving events of one file as GeoJSON
+    Events(EventsArgs),
+    /// Writes the turns of one file as GeoJSON with a turn-by-turn summary
+    Turns(TurnsArgs),
+    /// Writes the Douglas-Peucker split tree of one file as JSON or Graphviz DOT
+    SplitTree(SplitTreeArgs),
+    /// Writes an HTML page comparing every simplification algorithm on one file
+    Explain(ExplainArgs),
+    /// Writes the trips deviating from the frequent routes of their user
+    Anomalies(AnomaliesArgs),
+    /// Writes when two files stayed close to each other, as a labels.txt file
+    CoTravel(CoTravelArgs),
+    /// Prints how many points are drawn at each zoom level of a web map
+    ZoomLevels(ZoomLevelsArgs),
+    /// Replays the dataset as a live feed simplified incrementally
+    Replay(ReplayArgs),
+    /// Broadcasts the files replayed as a live feed to WebSocket clients
+    ServeLive(ServeLiveArgs),
+    /// Merges recordings of the same trip into one trajectory
+    Merge(MergeArgs),
+    /// Resamples one file at a fixed distance along its path
+    Resample(ResampleArgs),
+    /// Inserts points into one file so no segment exceeds a length
+    Densify(DensifyArgs),
+    /// Bridges the GPS outages of one file by dead reckoning from IMU samples
+    FillGaps(FillGapsArgs),
+    /// Map-matches the road-bound parts of every file before simplifying it
+    SnapSimplify(SnapSimplifyArgs),
+    /// Decrypts an output file with the key set in the environment
+    Decrypt(DecryptArgs),
+    /// Prints a random key for TRAJECTORY_KEY
+    Keygen,
+}
+
+/// Arguments of the subcommands only writing one output file.
+#[derive(Args, Debug)]
+pub struct OutputArgs {
+    pub output: String,
+}
+
+/// Arguments of the `bench-layout` subcommand.
+#[derive(Args, Debug)]
+pub struct BenchLayoutArgs {
+    /// Points of the random walk
+    #[arg(default_value_t = bench::SYNTHETIC_POINTS)]
+    pub points: usize,
+}
+
+/// Arguments of the `sweep` subcommand.
+#[derive(Args, Debug)]
+pub struct SweepArgs {
+    pub output: String,
+    /// Tolerances in meters
+    #[arg(required = true)]
+    pub epsilons_m: Vec<f64>,
+}
+
+/// How `split` assigns the files to the subsets.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum SplitBy {
+    User,
+    Time,
+    Random,
+}
+
+/// Arguments of the `split` subcommand.
+#[derive(Args, Debug)]
+pub struct SplitArgs {
+    pub strategy: SplitBy,
+    pub format: Encoding,
+    pub output_dir: PathBuf,
+    /// Seed of the random split
+    #[arg(default_value_t = 0)]
+    pub seed: u64,
+}
+
+/// Arguments of the `simplify-local` subcommand.
+#[derive(Args, Debug)]
+pub struct SimplifyLocalArgs {
+    /// Tolerance in meters
+    #[arg(value_parser = positive)]
+    pub epsilon_m: f64,
+    /// CSV file of timestamps and east and north positions in meters
+    pub trace: PathBuf,
+    pub output: String,
+}
+
+/// Layout of the tensor written by `export-tensor`.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum TensorLayout {
+    Npy,
+    Raw,
+}
+
+impl From<TensorLayout> for TensorFormat {
+    fn from(layout: TensorLayout) -> Self {
+        match layout {
+            TensorLayout::Npy => TensorFormat::Npy,
+            TensorLayout::Raw => TensorFormat::Raw,
+        }
+    }
+}
+
+/// Arguments of the `export-tensor` subcommand.
+#[derive(Args, Debug)]
+pub struct ExportTensorArgs {
+    /// Points of every row
+    #[arg(value_parser = count)]
+    pub points: usize,
+    pub format: TensorLayout,
+    pub output: String,
+    /// Threads reading the files
+    #[arg(long, value_parser = count)]
+    pub readers: Option<usize>,
+    /// Threads simplifying the files
+    #[arg(long, value_parser = count)]
+    pub workers: Option<usize>,
+}
+
+/// Arguments of the `batch` subcommand.
+#[derive(Args, Debug)]
+pub struct BatchArgs {
+    pub format: Encoding,
+    pub output_dir: PathBuf,
+    /// Converts points recorded in GCJ-02 or BD-09 to WGS 84 in a stage after `clean`
+    #[arg(long, value_name = "gcj02|bd09")]
+    pub datum: Option<Datum>,
+    /// Threads of a stage, e.g. simplify=4
+    #[arg(long, value_name = "STAGE=N", value_parser = stage_threads)]
+    pub threads: Vec<(String, usize)>,
+}
+
+/// Distance measuring the error of the points in `error-ci`.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum Metric {
+    /// Perpendicular distance to the simplified segment
+    Ped,
+    /// Distance to the position on the simplified segment at the same time
+    Sed,
+}
+
+impl From<Metric> for ErrorMetric {
+    fn from(metric: Metric) -> Self {
+        match metric {
+            Metric::Ped => ErrorMetric::Perpendicular,
+            Metric::Sed => ErrorMetric::Synchronized,
+        }
+    }
+}
+
+/// Arguments of the `error-ci` subcommand.
+#[derive(Args, Debug)]
+pub struct ErrorCiArgs {
+    pub metric: Metric,
+    /// Tolerance in meters
+    #[arg(value_parser = positive)]
+    pub epsilon_m: f64,
+    /// Error in meters to report the fraction of points within, by default the tolerance
+    pub threshold_m: Option<f64>,
+}
+
+/// Arguments of the `export-private` subcommand.
+#[derive(Args, Debug)]
+pub struct ExportPrivateArgs {
+    /// Privacy level of geo-indistinguishability
+    #[arg(value_parser = positive)]
+    pub level: f64,
+    /// Radius in meters within which points are indistinguishable
+    #[arg(value_parser = positive)]
+    pub radius_m: f64,
+    pub format: Encoding,
+    pub output: String,
+}
+
+/// Arguments of the `export-geohash` subcommand.
+#[derive(Args, Debug)]
+pub struct ExportGeohashArgs {
+    /// Characters of the geohash cells
+    #[arg(value_parser = precision())]
+    pub precision: usize,
+    pub output: String,
+}
+
+/// Arguments of the `corridor-cells` subcommand.
+#[derive(Args, Debug)]
+pub struct CorridorCellsArgs {
+    #[arg(value_parser = non_negative)]
+    pub radius_m: f64,
+    /// Characters of the geohash cells
+    #[arg(value_parser = precision())]
+    pub precision: usize,
+    pub output: String,
+    pub file: PathBuf,
+}
+
+/// Arguments of the `buffer` subcommand.
+#[derive(Args, Debug)]
+pub struct BufferArgs {
+    #[arg(value_parser = positive)]
+    pub radius_m: f64,
+    pub output: String,
+    pub file: PathBuf,
+}
+
+/// Arguments of the `pois-along` subcommand.
+#[derive(Args, Debug)]
+pub struct PoisAlongArgs {
+    #[arg(value_parser = non_negative)]
+    pub radius_m: f64,
+    /// CSV file of latitudes and longitudes in degrees
+    pub pois: PathBuf,
+    pub file: PathBuf,
+}
+
+/// Arguments of the `compare-direction` subcommand.
+#[derive(Args, Debug)]
+pub struct CompareDirectionArgs {
+    pub a: PathBuf,
+    pub b: PathBuf,
+    #[arg(default_value_t = DIRECTION_TOLERANCE_M)]
+    pub tolerance_m: f64,
+}
+
+/// Arguments of the `crossings` subcommand.
+#[derive(Args, Debug)]
+pub struct CrossingsArgs {
+    pub a: PathBuf,
+    pub b: PathBuf,
+    /// Only reports crossings passed by both within this many seconds
+    pub window_s: Option<u64>,
+}
+
+/// Arguments of the `routes` subcommand.
+#[derive(Args, Debug)]
+pub struct RoutesArgs {
+    /// Distance in meters between the endpoints of trips of the same route
+    #[arg(value_parser = positive)]
+    pub endpoint_radius_m: f64,
+    /// Distance in meters between the paths of trips of the same route
+    #[arg(value_parser = positive)]
+    pub tolerance_m: f64,
+    pub output: String,
+}
+
+/// Arguments of the `zones` subcommand.
+#[derive(Args, Debug)]
+pub struct ZonesArgs {
+    pub zones: PathBuf,
+    pub output: String,
+    /// Measures the simplified files densified to this many meters between points
+    #[arg(value_parser = positive)]
+    pub max_gap_m: Option<f64>,
+}
+
+/// Arguments of the `od-matrix` subcommand.
+#[derive(Args, Debug)]
+pub struct OdMatrixArgs {
+    pub zones: PathBuf,
+    pub output: String,
+}
+
+/// Format of the report of `temporal-report`.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportFormat {
+    Csv,
+    Json,
+}
+
+/// Arguments of the `temporal-report` subcommand.
+#[derive(Args, Debug)]
+pub struct TemporalReportArgs {
+    pub format: ReportFormat,
+    pub output: String,
+}
+
+/// Arguments of the `stay-points` subcommand.
+#[derive(Args, Debug)]
+pub struct StayPointsArgs {
+    #[arg(value_parser = positive)]
+    pub max_distance_m: f64,
+    pub min_duration_s: u64,
+    pub output: String,
+}
+
+/// Arguments of the `serve-arrow` subcommand.
+#[derive(Args, Debug)]
+pub struct ServeArrowArgs {
+    pub address: String,
+    /// Tolerance in meters, by default about 111 m
+    #[arg(value_parser = positive)]
+    pub epsilon_m: Option<f64>,
+}
+
+/// Arguments of the `serve-http` subcommand.
+#[derive(Args, Debug)]
+pub struct ServeHttpArgs {
+    pub address: String,
+    #[arg(long, value_name = "N")]
+    pub max_body_bytes: Option<usize>,
+    #[arg(long, value_name = "N")]
+    pub max_points: Option<usize>,
+    /// Requests per minute sustained by a client address
+    #[arg(long, value_name = "N", value_parser = positive)]
+    pub requests_per_minute: Option<f64>,
+    /// Requests a client address can send at once after being idle
+    #[arg(long, value_name = "N")]
+    pub burst: Option<f64>,
+    /// Connections served at once
+    #[arg(long, value_name = "N")]
+    pub max_connections: Option<usize>,
+    /// Only answers the requests with the API key of a tenant
+    #[arg(long, value_name = "TENANTS.toml")]
+    pub tenants: Option<PathBuf>,
+}
+
+/// Arguments of the `export-parquet` subcommand.
+#[derive(Args, Debug)]
+pub struct ExportParquetArgs {
+    pub output_dir: PathBuf,
+    /// Tolerance in meters, by default about 111 m
+    #[arg(value_parser = positive)]
+    pub epsilon_m: Option<f64>,
+}
+
+/// Arguments of the `publish-redis` subcommand.
+#[derive(Args, Debug)]
+pub struct PublishRedisArgs {
+    #[arg(value_name = "HOST:PORT")]
+    pub address: String,
+    pub key: String,
+}
+
+/// Arguments of the `store` subcommand.
+#[derive(Args, Debug)]
+pub struct StoreArgs {
+    /// Directory, sqlite:<path> or s3://<bucket>/<prefix>
+    pub location: String,
+    /// Only reads and writes the trajectories of a tenant
+    #[arg(long)]
+    pub tenant: Option<String>,
+    #[command(subcommand)]
+    pub command: StoreCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum StoreCommand {
+    /// Stores every simplified file, with its user and file name as id
+    Put {
+        /// Tolerance in meters, by default about 111 m
+        epsilon_m: Option<f64>,
+    },
+    /// Lists the stored ids, or those with points between two Unix timestamps
+    List {
+        #[arg(requires = "end")]
+        start: Option<u64>,
+        end: Option<u64>,
+    },
+    /// Writes a stored trajectory in the given format
+    Get(StoredArgs),
+    /// Writes the thumbnail of a stored trajectory in the given format
+    Thumbnail(StoredArgs),
+    /// Deletes a stored trajectory
+    Delete { id: String },
+}
+
+/// Arguments of the `store` subcommands writing a stored trajectory.
+#[derive(Args, Debug)]
+pub struct StoredArgs {
+    pub id: String,
+    pub format: Encoding,
+    pub output: PathBuf,
+}
+
+/// Arguments of the `ingest` subcommand.
+#[derive(Args, Debug)]
+pub struct IngestArgs {
+    pub log: PathBuf,
+    pub store: String,
+    #[arg(value_parser = count)]
+    pub batch_size: usize,
+    /// Seconds without points after which a trajectory is compacted
+    #[arg(default_value_t = DEFAULT_IDLE_S)]
+    pub idle_s: u64,
+}
+
+/// Arguments of the `export` subcommand.
+#[derive(Args, Debug)]
+pub struct ExportArgs {
+    #[arg(long)]
+    pub store: String,
+    #[arg(long)]
+    pub format: ExportFormat,
+    #[arg(long)]
+    pub out: PathBuf,
+}
+
+/// Arguments of the `generalize` subcommand.
+#[derive(Args, Debug)]
+pub struct GeneralizeArgs {
+    #[arg(long, value_name = "web-z6|web-z10|web-z14|web-z18")]
+    pub preset: Preset,
+    #[arg(long)]
+    pub out: PathBuf,
+}
+
+/// Arguments of the `import` subcommand.
+#[derive(Args, Debug)]
+pub struct ImportArgs {
+    pub dir: PathBuf,
+    #[arg(long)]
+    pub store: String,
+    /// Tolerances per transportation mode or speed (TOML)
+    #[arg(long)]
+    pub config: Option<String>,
+}
+
+/// Arguments of the `migrate` subcommand.
+#[derive(Args, Debug)]
+pub struct MigrateArgs {
+    #[arg(long)]
+    pub store: String,
+    /// Directory the trajectories are copied to before being upgraded
+    #[arg(long)]
+    pub backup: PathBuf,
+}
+
+/// Arguments of the `events` subcommand.
+#[derive(Args, Debug)]
+pub struct EventsArgs {
+    #[arg(value_parser = positive)]
+    pub speed_limit_mps: f64,
+    pub output: String,
+    pub file: PathBuf,
+}
+
+/// Arguments of the `turns` subcommand.
+#[derive(Args, Debug)]
+pub struct TurnsArgs {
+    /// Smallest change of heading in degrees, up to 180
+    #[arg(value_parser = angle)]
+    pub min_angle_deg: f64,
+    pub output: String,
+    pub file: PathBuf,
+}
+
+/// Arguments of the `split-tree` subcommand.
+#[derive(Args, Debug)]
+pub struct SplitTreeArgs {
+    /// Tolerance in microdegrees
+    #[arg(value_parser = value_parser!(i64).range(0..))]
+    pub epsilon: i64,
+    /// JSON file, or Graphviz DOT file if it ends in .dot
+    pub output: String,
+    pub file: PathBuf,
+}
+
+/// Arguments of the `explain` subcommand.
+#[derive(Args, Debug)]
+pub struct ExplainArgs {
+    #[arg(value_parser = non_negative)]
+    pub epsilon_m: f64,
+    pub output: String,
+    pub file: String,
+}
+
+/// Arguments of the `anomalies` subcommand.
+#[derive(Args, Debug)]
+pub struct AnomaliesArgs {
+    /// Distance in meters between the endpoints of trips of the same route
+    #[arg(value_parser = positive)]
+    pub endpoint_radius_m: f64,
+    /// Distance in meters between the paths of trips of the same route
+    #[arg(value_parser = positive)]
+    pub tolerance_m: f64,
+    /// Distance in meters from the usual routes above which a trip is flagged
+    #[arg(value_parser = positive)]
+    pub max_deviation_m: f64,
+    pub output: String,
+    /// Trips of a route needed to compare others with it
+    #[arg(default_value_t = ANOMALY_MIN_ROUTE_TRIPS, value_parser = count)]
+    pub min_route_trips: usize,
+}
+
+/// Arguments of the `co-travel` subcommand.
+#[derive(Args, Debug)]
+pub struct CoTravelArgs {
+    #[arg(value_parser = non_negative)]
+    pub max_distance_m: f64,
+    pub min_duration_s: u64,
+    pub output: String,
+    pub a: PathBuf,
+    pub b: PathBuf,
+}
+
+/// Arguments of the `zoom-levels` subcommand.
+#[derive(Args, Debug)]
+pub struct ZoomLevelsArgs {
+    /// Tolerances in pixels, by default 1
+    pub epsilons_px: Vec<f64>,
+}
+
+/// Arguments of the `replay` subcommand.
+#[derive(Args, Debug)]
+pub struct ReplayArgs {
+    #[arg(value_parser = count)]
+    pub batch_size: usize,
+}
+
+/// Arguments of the `serve-live` subcommand.
+#[derive(Args, Debug)]
+pub struct ServeLiveArgs {
+    pub address: String,
+    #[arg(value_parser = count)]
+    pub batch_size: usize,
+    /// Milliseconds between two batches
+    #[arg(default_value_t = 1000)]
+    pub interval_ms: u64,
+    /// Reads the files of each tenant from its own directory
+    #[arg(long, value_name = "TENANTS.toml")]
+    pub tenants: Option<PathBuf>,
+}
+
+/// Arguments of the `merge` subcommand.
+#[derive(Args, Debug)]
+pub struct MergeArgs {
+    #[arg(value_name = "first|average|error")]
+    pub policy: ConflictPolicy,
+    pub format: Encoding,
+    pub output: String,
+    #[arg(required = true)]
+    pub files: Vec<String>,
+    /// Corrects the clock offset of each file relative to the first one, up to this many
+    /// seconds
+    #[arg(long = "align", value_name = "MAX_OFFSET_S")]
+    pub max_offset_s: Option<u32>,
+    /// Averages the positions with these weights, one per file, instead of resolving
+    /// conflicts with the policy
+    #[arg(long = "fuse", value_name = "WEIGHT,...", value_delimiter = ',')]
+    pub weights: Option<Vec<f64>>,
+}
+
+/// Arguments of the `resample` subcommand.
+#[derive(Args, Debug)]
+pub struct ResampleArgs {
+    #[arg(value_parser = positive)]
+    pub interval_m: f64,
+    pub format: Encoding,
+    pub output: String,
+    pub file: PathBuf,
+}
+
+/// Arguments of the `densify` subcommand.
+#[derive(Args, Debug)]
+pub struct DensifyArgs {
+    #[arg(value_parser = positive)]
+    pub max_gap_m: f64,
+    pub format: Encoding,
+    pub output: String,
+    pub file: PathBuf,
+}
+
+/// Arguments of the `fill-gaps` subcommand.
+#[derive(Args, Debug)]
+pub struct FillGapsArgs {
+    /// CSV file of IMU samples
+    pub imu: PathBuf,
+    pub format: Encoding,
+    pub output: String,
+    pub file: PathBuf,
+    /// Shortest outage bridged, in seconds
+    #[arg(default_value_t = FILL_GAPS_MIN_GAP_S)]
+    pub min_gap_s: u64,
+}
+
+/// Arguments of the `snap-simplify` subcommand.
+#[derive(Args, Debug)]
+pub struct SnapSimplifyArgs {
+    /// GeoJSON road network, or with the http-matcher feature osrm:<url> or valhalla:<url>
+    pub roads: String,
+    /// Distance in meters within which points are matched to roads
+    #[arg(default_value_t = matching::SEARCH_RADIUS_M)]
+    pub radius_m: f64,
+    /// Directory the responses of the matching service are cached in
+    #[cfg(feature = "http-matcher")]
+    #[arg(long)]
+    pub cache: Option<PathBuf>,
+    /// Requests in flight to the matching service
+    #[cfg(feature = "http-matcher")]
+    #[arg(long)]
+    pub concurrency: Option<usize>,
+}
+
+/// Arguments of the `decrypt` subcommand.
+#[derive(Args, Debug)]
+pub struct DecryptArgs {
+    pub input: PathBuf,
+    pub output: PathBuf,
+}
+
+/// Parses a number greater than 0.
+fn positive(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(number) if number > 0.0 => Ok(number),
+        _ => Err("expected a number greater than 0".to_string()),
+    }
+}
+
+/// Parses a number greater than or equal to 0.
+fn non_negative(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(number) if number >= 0.0 => Ok(number),
+        _ => Err("expected a number greater than or equal to 0".to_string()),
+    }
+}
+
+/// Parses a ratio in (0, 1].
+fn ratio(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(ratio) if ratio > 0.0 && ratio <= 1.0 => Ok(ratio),
+        _ => Err("expected a number greater than 0 and at most 1".to_string()),
+    }
+}
+
+/// Parses an angle in degrees in [0, 180].
+fn angle(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(angle) if (0.0..=180.0).contains(&angle) => Ok(angle),
+        _ => Err("expected an angle from 0 to 180 degrees".to_string()),
+    }
+}
+
+/// Parses a count greater than 0.
+fn count(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(count) if count > 0 => Ok(count),
+        _ => Err("expected a whole number greater than 0".to_string()),
+    }
+}
+
+/// Parser of geohash precisions, from 1 to `geohash::MAX_PRECISION` characters.
+fn precision() -> RangedU64ValueParser<usize> {
+    RangedU64ValueParser::new().range(1..=geohash::MAX_PRECISION as u64)
+}
+
+/// Parses the `<stage>=<n>` value of `batch --threads`.
+fn stage_threads(value: &str) -> Result<(String, usize), String> {
+    let (stage, threads) = value
+        .split_once('=')
+        .ok_or_else(|| "expected <stage>=<n>".to_string())?;
+    Ok((stage.to_string(), count(threads)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_cli() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_run_options() {
+        let cli = Cli::try_parse_from(["trajectory-rs", "--max-gap", "600", "--smooth"]).unwrap();
+        assert!(cli.command.is_none());
+        assert_eq!(cli.run.max_gap_s, Some(600));
+        assert_eq!(cli.run.epsilon, crate::EPSILON);
+        assert_eq!(cli.run.datum, Datum::Wgs84);
+        assert!(!cli.run.merge);
+        let args = [
+            "trajectory-rs",
+            "--max-jump",
+            "100",
+            "--target-ratio",
+            "0.1",
+            "--explain",
+            "out.json",
+        ];
+        assert!(Cli::try_parse_from(args).is_ok());
+        let cli = Cli::try_parse_from(["trajectory-rs", "--push-metrics", "localhost:9091"]);
+        assert_eq!(cli.unwrap().push_metrics.as_deref(), Some("localhost:9091"));
+
+        for args in [
+            &["--epsilon", "-1"][..],
+            &["--target-ratio", "1.5"],
+            &["--merge", "--max-gap", "600"],
+            &["--merge", "--per-file"],
+            &["--unknown"],
+            &["--smooth", "store", "trips", "list"],
+        ] {
+            let args = std::iter::once("trajectory-rs").chain(args.iter().copied());
+            assert!(Cli::try_parse_from(args).is_err());
+        }
+    }
+
+    #[test]
+    fn test_subcommands() {
+        let parse = |args: &[&str]| {
+            let args = std::iter::once("trajectory-rs").chain(args.iter().copied());
+            Cli::try_parse_from(args).map(|cli| cli.command.unwrap())
+        };
+
+        let Command::Batch(batch) =
+            parse(&["batch", "binary", "out", "--threads", "simplify=2"]).unwrap()
+        else {
+            panic!("expected batch");
+        };
+        assert_eq!(batch.threads, [("simplify".to_string(), 2)]);
+        let args = [
+            "trajectory-rs",
+            "generalize",
+            "--preset",
+            "web-z10",
+            "--out",
+            "out",
+            "--push-metrics",
+            "gw:9091",
+        ];
+        let cli = Cli::try_parse_from(args).unwrap();
+        assert!(matches!(cli.command, Some(Command::Generalize(_))));
+        assert_eq!(cli.push_metrics.as_deref(), Some("gw:9091"));
+        assert!(parse(&["batch", "binary", "out", "--threads", "simplify=0"]).is_err());
+
+        let Command::Merge(merge) = parse(&[
+            "merge", "first", "binary", "out", "a.plt", "b.plt", "--fuse", "1,3",
+        ])
+        .unwrap() else {
+            panic!("expected merge");
+        };
+        assert_eq!(merge.files, ["a.plt", "b.plt"]);
+        assert_eq!(merge.weights, Some(vec![1.0, 3.0]));
+
+        let Command::Store(store) = parse(&["store", "trips", "list", "10", "20"]).unwrap() else {
+            panic!("expected store");
+        };
+        assert!(matches!(
+            store.command,
+            StoreCommand::List {
+                start: Some(10),
+                end: Some(20)
+            }
+        ));
+        assert!(parse(&["store", "trips", "list", "10"]).is_err());
+
+        assert!(parse(&["buffer", "0", "out.geojson", "a.plt"]).is_err());
+        assert!(parse(&["export-geohash", "13", "out.json"]).is_err());
+        assert!(parse(&["split", "weekly", "binary", "out"]).is_err());
+        assert!(parse(&["decrypt", "in", "out", "--force"]).is_err());
+    }
+}
@@ -0,0 +1,225 @@
+//! Trajectories kept apart instead of merged into one.
+//!
+//! Merging the files of a dataset into a single trajectory joins the end of each trip to
+//! the start of the next one with a straight line that was never traveled, and the
+//! simplification keeps or drops points because of these jumps. A `TrajectoryCollection`
+//! keeps each file as its own trajectory, simplified independently, and serialized as the
+//! repeated `trajectories` of a `proto::TrajectoryCollection` message.
+
+use crate::proto;
+use crate::simplify::{self, LoopAnchors, TrajectoryView};
+use crate::trajectory::{DecodeError, EncodeError, Trajectory};
+
+/// Trajectories processed independently of each other.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TrajectoryCollection {
+    pub trajectories: Vec<Trajectory>,
+}
+
+impl TrajectoryCollection {
+    pub fn new(trajectories: Vec<Trajectory>) -> Self {
+        TrajectoryCollection { trajectories }
+    }
+
+    /// Number of trajectories
+    pub fn len(&self) -> usize {
+        self.trajectories.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.trajectories.is_empty()
+    }
+
+    /// Number of points over every trajectory
+    pub fn points(&self) -> usize {
+        self.trajectories
+            .iter()
+            .map(|trajectory| trajectory.latitudes.len())
+            .sum()
+    }
+
+    /// Simplifies every trajectory on its own, anchoring loops as given by `anchors` (see
+    /// `simplify::simplify_anchored`), in parallel with the `rayon` feature.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `epsilon` is negative.
+    pub fn simplify(self, epsilon: i64, anchors: LoopAnchors) -> TrajectoryCollection {
+        let views: Vec<TrajectoryView> =
+            self.trajectories.iter().map(TrajectoryView::from).collect();
+        #[cfg(feature = "rayon")]
+        let masks = simplify::simplify_all_parallel(&views, epsilon, anchors);
+        #[cfg(not(feature = "rayon"))]
+        let masks: Vec<_> = views
+            .iter()
+            .map(|view| {
+                simplify::simplify_anchored(view.positions_x, view.positions_y, epsilon, anchors)
+            })
+            .collect();
+
+        let trajectories = self
+            .trajectories
+            .into_iter()
+            .zip(masks)
+            .map(|(trajectory, keep)| trajectory.filter(&keep))
+            .collect();
+        TrajectoryCollection { trajectories }
+    }
+
+    /// Merges every point of every trajectory into one trajectory sorted by timestamp, as
+    /// `dataset::load_trajectory` reads files, for the statistics of a whole dataset.
+    ///
+    /// Points sharing a timestamp keep the order of the trajectories. Altitudes are kept
+    /// when every trajectory with points has some.
+    pub fn merged(&self) -> Trajectory {
+        let mut order: Vec<(&Trajectory, usize)> = self
+            .trajectories
+            .iter()
+            .flat_map(|trajectory| (0..trajectory.timestamps.len()).map(move |i| (trajectory, i)))
+            .collect();
+        order.sort_by_key(|&(trajectory, i)| trajectory.timestamps[i]);
+        let column = |values: fn(&Trajectory) -> &Vec<i64>| {
+            order
+                .iter()
+                .map(|&(trajectory, i)| values(trajectory)[i])
+                .collect()
+        };
+        let with_altitudes = self
+            .trajectories
+            .iter()
+            .all(|trajectory| trajectory.timestamps.is_empty() || !trajectory.altitudes.is_empty());
+        Trajectory {
+            latitudes: column(|trajectory| &trajectory.latitudes),
+            longitudes: column(|trajectory| &trajectory.longitudes),
+            timestamps: order
+                .iter()
+                .map(|&(trajectory, i)| trajectory.timestamps[i])
+                .collect(),
+            altitudes: if with_altitudes {
+                column(|trajectory| &trajectory.altitudes)
+            } else {
+                Vec::new()
+            },
+            coordinates: self
+                .trajectories
+                .first()
+                .map(|trajectory| trajectory.coordinates)
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Converts the trajectories to a protobuf message using absolute values (see
+    /// `Trajectory::to_proto`).
+    #[allow(clippy::wrong_self_convention)]
+    pub fn to_proto(self) -> proto::TrajectoryCollection {
+        proto::TrajectoryCollection {
+            trajectories: self
+                .trajectories
+                .into_iter()
+                .map(Trajectory::to_proto)
+                .collect(),
+        }
+    }
+
+    /// Converts the trajectories to a protobuf message using delta encoding (see
+    /// `Trajectory::to_delta_proto`). Each trajectory is delta-encoded from zero, so that
+    /// any of them can be decoded without the others.
+    #[allow(clippy::wrong_self_convention)]
+    pub fn to_delta_proto(self) -> Result<proto::TrajectoryCollection, EncodeError> {
+        Ok(proto::TrajectoryCollection {
+            trajectories: self
+                .trajectories
+                .into_iter()
+                .map(Trajectory::to_delta_proto)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Reads the trajectories of a protobuf message with absolute values.
+    pub fn from_proto(
+        message: proto::TrajectoryCollection,
+    ) -> Result<TrajectoryCollection, DecodeError> {
+        message
+            .trajectories
+            .into_iter()
+            .map(Trajectory::from_proto)
+            .collect::<Result<_, _>>()
+            .map(TrajectoryCollection::new)
+    }
+
+    /// Reads the trajectories of a protobuf message with delta-encoded values.
+    pub fn from_delta_proto(
+        message: proto::TrajectoryCollection,
+    ) -> Result<TrajectoryCollection, DecodeError> {
+        message
+            .trajectories
+            .into_iter()
+            .map(Trajectory::from_delta_proto)
+            .collect::<Result<_, _>>()
+            .map(TrajectoryCollection::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trajectory::CoordinateSystem;
+
+    fn trip(start: i64, timestamp: u64) -> Trajectory {
+        Trajectory {
+            latitudes: vec![start, start + 1_000, start + 2_000],
+            longitudes: vec![0, 10, 0],
+            timestamps: vec![timestamp, timestamp + 5, timestamp + 10],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        }
+    }
+
+    #[test]
+    fn test_merged() {
+        let mut late = trip(50_000, 7);
+        late.altitudes = vec![1, 2, 3];
+        let collection = TrajectoryCollection::new(vec![trip(0, 0), late.clone()]);
+        let merged = collection.merged();
+        assert_eq!(merged.timestamps, [0, 5, 7, 10, 12, 17]);
+        assert_eq!(merged.latitudes, [0, 1_000, 50_000, 2_000, 51_000, 52_000]);
+        // The first trip has no altitudes
+        assert!(merged.altitudes.is_empty());
+
+        let collection = TrajectoryCollection::new(vec![late, Trajectory::new(Vec::new())]);
+        assert_eq!(collection.merged().altitudes, [1, 2, 3]);
+        assert!(TrajectoryCollection::default()
+            .merged()
+            .timestamps
+            .is_empty());
+    }
+
+    #[test]
+    fn test_collection_simplify() {
+        // Two straight trips far apart: merged, the jump between them would keep points
+        let collection = TrajectoryCollection::new(vec![trip(0, 0), trip(50_000, 100)]);
+        assert_eq!(collection.len(), 2);
+        assert_eq!(collection.points(), 6);
+
+        let simplified = collection.simplify(100, LoopAnchors::Endpoints);
+        assert_eq!(simplified.len(), 2);
+        assert_eq!(simplified.points(), 4);
+        assert_eq!(simplified.trajectories[1].latitudes, [50_000, 52_000]);
+        assert!(TrajectoryCollection::default().is_empty());
+    }
+
+    #[test]
+    fn test_collection_proto_round_trip() {
+        let collection = TrajectoryCollection::new(vec![trip(0, 0), trip(50_000, 100)]);
+
+        let proto = collection.clone().to_delta_proto().unwrap();
+        // Each trajectory starts from zero
+        assert_eq!(proto.trajectories[1].latitudes, [50_000, 1_000, 1_000]);
+        assert_eq!(
+            TrajectoryCollection::from_delta_proto(proto),
+            Ok(collection.clone())
+        );
+        let proto = collection.clone().to_proto();
+        assert_eq!(TrajectoryCollection::from_proto(proto), Ok(collection));
+    }
+}
@@ -0,0 +1,294 @@
+//! Side-by-side comparison of the simplification algorithms on one trajectory, to help
+//! pick an algorithm and its tolerance: the points each one keeps, its errors and its
+//! running time, rendered as a self-contained HTML page with one map per algorithm.
+
+use crate::geo::{meters_to_units, to_degrees};
+use crate::quality::{point_errors, ErrorMetric};
+use crate::simplify::{self, LoopAnchors};
+use crate::trajectory::Trajectory;
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+/// Width and height in pixels of the maps
+const MAP_SIZE: f64 = 360.0;
+
+/// Simplification algorithm compared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    /// `simplify::simplify`
+    DouglasPeucker,
+    /// `simplify::simplify_anchored`, loops anchored at their two farthest points
+    LoopAnchored,
+    /// `simplify::simplify_tdtr`, with the synchronized distance
+    TimeAware,
+    /// `simplify::simplify_trajectory_3d`, with the altitudes
+    ThreeD,
+    /// `simplify::simplify_visvalingam`, with the square of the tolerance as area
+    Visvalingam,
+}
+
+impl Algorithm {
+    pub const ALL: [Algorithm; 5] = [
+        Algorithm::DouglasPeucker,
+        Algorithm::LoopAnchored,
+        Algorithm::TimeAware,
+        Algorithm::ThreeD,
+        Algorithm::Visvalingam,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::DouglasPeucker => "douglas-peucker",
+            Algorithm::LoopAnchored => "loop-anchored",
+            Algorithm::TimeAware => "td-tr",
+            Algorithm::ThreeD => "3d",
+            Algorithm::Visvalingam => "visvalingam",
+        }
+    }
+
+    /// Simplifies the trajectory with a tolerance in meters, converted to scaled units
+    /// along a meridian for the algorithms working on the coordinates.
+    pub fn simplify(self, trajectory: &Trajectory, epsilon_m: f64) -> Vec<bool> {
+        let (latitudes, longitudes) = (&trajectory.latitudes, &trajectory.longitudes);
+        let epsilon = meters_to_units(epsilon_m);
+        match self {
+            Algorithm::DouglasPeucker => simplify::simplify(latitudes, longitudes, epsilon),
+            Algorithm::LoopAnchored => {
+                simplify::simplify_anchored(latitudes, longitudes, epsilon, LoopAnchors::Diameter)
+            }
+            Algorithm::TimeAware => {
+                simplify::simplify_tdtr(latitudes, longitudes, &trajectory.timestamps, epsilon)
+            }
+            Algorithm::ThreeD => simplify::simplify_trajectory_3d(trajectory, epsilon_m),
+            Algorithm::Visvalingam => {
+                simplify::simplify_visvalingam(latitudes, longitudes, epsilon * epsilon)
+            }
+        }
+    }
+}
+
+/// Result of one algorithm on the trajectory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    pub algorithm: Algorithm,
+    pub keep: Vec<bool>,
+    pub kept: usize,
+    pub duration: Duration,
+    /// Largest and mean distance in meters to the simplified segments
+    pub perpendicular_m: (f64, f64),
+    /// Largest and mean distance in meters to the position on the simplified segments at
+    /// the same time
+    pub synchronized_m: (f64, f64),
+}
+
+/// Largest and mean of the errors, zero without any.
+fn max_and_mean(errors: &[f64]) -> (f64, f64) {
+    if errors.is_empty() {
+        return (0.0, 0.0);
+    }
+    let max = errors.iter().copied().fold(0.0, f64::max);
+    (max, errors.iter().sum::<f64>() / errors.len() as f64)
+}
+
+/// Runs every algorithm on the trajectory with a tolerance in meters.
+pub fn compare(trajectory: &Trajectory, epsilon_m: f64) -> Vec<Comparison> {
+    Algorithm::ALL
+        .iter()
+        .map(|&algorithm| {
+            let start = Instant::now();
+            let keep = algorithm.simplify(trajectory, epsilon_m);
+            let duration = start.elapsed();
+            let errors = |metric| max_and_mean(&point_errors(trajectory, &keep, metric));
+            Comparison {
+                algorithm,
+                kept: keep.iter().filter(|&&kept| kept).count(),
+                perpendicular_m: errors(ErrorMetric::Perpendicular),
+                synchronized_m: errors(ErrorMetric::Synchronized),
+                duration,
+                keep,
+            }
+        })
+        .collect()
+}
+
+/// Projects the points to pixels of a map fitting the trajectory, north up, with
+/// longitudes shortened by the cosine of the latitude of the first point.
+fn project(trajectory: &Trajectory) -> Vec<(f64, f64)> {
+    let points: Vec<(f64, f64)> = trajectory
+        .latitudes
+        .iter()
+        .zip(&trajectory.longitudes)
+        .map(|(&latitude, &longitude)| (to_degrees(longitude), to_degrees(latitude)))
+        .collect();
+    let Some(&(_, first_latitude)) = points.first() else {
+        return points;
+    };
+    let cos_lat = first_latitude.to_radians().cos().max(0.01);
+    let points: Vec<(f64, f64)> = points.iter().map(|&(x, y)| (x * cos_lat, y)).collect();
+
+    let (min_x, max_x, min_y, max_y) = points.iter().fold(
+        (f64::MAX, f64::MIN, f64::MAX, f64::MIN),
+        |(min_x, max_x, min_y, max_y), &(x, y)| {
+            (min_x.min(x), max_x.max(x), min_y.min(y), max_y.max(y))
+        },
+    );
+    let margin = 8.0;
+    let scale = (MAP_SIZE - 2.0 * margin) / (max_x - min_x).max(max_y - min_y).max(f64::EPSILON);
+    points
+        .iter()
+        .map(|&(x, y)| (margin + (x - min_x) * scale, margin + (max_y - y) * scale))
+        .collect()
+}
+
+/// SVG path through the given pixels.
+fn path(pixels: impl Iterator<Item = (f64, f64)>) -> String {
+    let mut path = String::new();
+    for (i, (x, y)) in pixels.enumerate() {
+        let command = if i == 0 { 'M' } else { 'L' };
+        write!(path, "{command}{x:.1},{y:.1} ").unwrap();
+    }
+    path
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Renders the comparisons as a self-contained HTML page: a table of the points kept,
+/// errors and running time of every algorithm, then a map per algorithm with the original
+/// trajectory in grey and the simplified one over it.
+pub fn html(
+    title: &str,
+    trajectory: &Trajectory,
+    epsilon_m: f64,
+    comparisons: &[Comparison],
+) -> String {
+    let points = trajectory.latitudes.len();
+    let pixels = project(trajectory);
+    let original = path(pixels.iter().copied());
+    let title = escape(title);
+
+    let mut html = String::new();
+    write!(
+        html,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+         <style>\n\
+         body {{ font-family: sans-serif; margin: 2em; }}\n\
+         table {{ border-collapse: collapse; }}\n\
+         th, td {{ padding: 0.3em 0.8em; border-bottom: 1px solid #ddd; text-align: right; }}\n\
+         th:first-child, td:first-child {{ text-align: left; }}\n\
+         figure {{ display: inline-block; margin: 1em 1em 0 0; }}\n\
+         svg {{ border: 1px solid #ccc; }}\n\
+         </style>\n</head>\n<body>\n\
+         <h1>{title}</h1>\n<p>{points} points, epsilon {epsilon_m} m</p>\n\
+         <table>\n<tr><th>Algorithm</th><th>Points kept</th><th>Ratio</th>\
+         <th>Max error</th><th>Mean error</th><th>Max sync. error</th>\
+         <th>Mean sync. error</th><th>Time</th></tr>\n"
+    )
+    .unwrap();
+    for comparison in comparisons {
+        let (max_m, mean_m) = comparison.perpendicular_m;
+        let (max_sync_m, mean_sync_m) = comparison.synchronized_m;
+        writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{:.2} %</td><td>{max_m:.1} m</td><td>{mean_m:.1} m</td>\
+             <td>{max_sync_m:.1} m</td><td>{mean_sync_m:.1} m</td><td>{:?}</td></tr>",
+            comparison.algorithm.name(),
+            comparison.kept,
+            comparison.kept as f64 / points.max(1) as f64 * 100.0,
+            comparison.duration,
+        )
+        .unwrap();
+    }
+    html.push_str("</table>\n");
+
+    for comparison in comparisons {
+        let kept = || {
+            (0..points)
+                .filter(|&i| comparison.keep[i])
+                .map(|i| pixels[i])
+        };
+        write!(
+            html,
+            "<figure>\n<svg width=\"{MAP_SIZE}\" height=\"{MAP_SIZE}\">\n\
+             <path d=\"{original}\" fill=\"none\" stroke=\"#bbb\" stroke-width=\"1\"/>\n\
+             <path d=\"{}\" fill=\"none\" stroke=\"#d62728\" stroke-width=\"1.5\"/>\n",
+            path(kept())
+        )
+        .unwrap();
+        for (x, y) in kept() {
+            writeln!(
+                html,
+                "<circle cx=\"{x:.1}\" cy=\"{y:.1}\" r=\"2\" fill=\"#d62728\"/>"
+            )
+            .unwrap();
+        }
+        writeln!(
+            html,
+            "</svg>\n<figcaption>{}: {} points</figcaption>\n</figure>",
+            comparison.algorithm.name(),
+            comparison.kept
+        )
+        .unwrap();
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trajectory::CoordinateSystem;
+
+    /// A straight line north with a stop halfway, then a detour east.
+    fn trip() -> Trajectory {
+        Trajectory {
+            latitudes: vec![0, 1_000, 2_000, 2_000, 3_000, 4_000, 4_000],
+            longitudes: vec![0, 0, 0, 0, 0, 1_000, 0],
+            timestamps: vec![0, 10, 20, 300, 310, 320, 330],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        }
+    }
+
+    #[test]
+    fn test_compare() {
+        let trajectory = trip();
+        let comparisons = compare(&trajectory, 20.0);
+        assert_eq!(comparisons.len(), Algorithm::ALL.len());
+
+        let by_name = |name: &str| {
+            comparisons
+                .iter()
+                .find(|comparison| comparison.algorithm.name() == name)
+                .unwrap()
+        };
+        let douglas_peucker = by_name("douglas-peucker");
+        assert_eq!(
+            douglas_peucker.keep,
+            [true, false, false, false, true, true, true]
+        );
+        assert_eq!(douglas_peucker.perpendicular_m.0, 0.0);
+        // The stop is lost in space, but not in time
+        assert!(douglas_peucker.synchronized_m.0 > 100.0);
+        let time_aware = by_name("td-tr");
+        assert!(time_aware.kept > douglas_peucker.kept);
+        assert!(time_aware.synchronized_m.0 < 20.0);
+        // Without altitudes, the 3D simplification is the planar one, in meters
+        assert_eq!(by_name("3d").keep, douglas_peucker.keep);
+    }
+
+    #[test]
+    fn test_html() {
+        let trajectory = trip();
+        let comparisons = compare(&trajectory, 20.0);
+        let html = html("a <trip>", &trajectory, 20.0, &comparisons);
+        assert!(html.contains("<title>a &lt;trip&gt;</title>"));
+        assert_eq!(html.matches("<svg ").count(), Algorithm::ALL.len());
+        assert!(html.contains("<td>td-tr</td>"));
+        assert!(html.ends_with("</html>\n"));
+    }
+}
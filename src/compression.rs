@@ -0,0 +1,221 @@
+//! Decompression of gzip files and zip archives, so that GPS archives can be read
+//! without extracting them to disk first.
+//!
+//! Decompressed data is bounded, by the size announced in the zip headers and by a maximum
+//! given by the caller, so that a small archive expanding to gigabytes fails instead of
+//! using all the memory.
+
+use flate2::read::MultiGzDecoder;
+use std::io::{self, Read, Seek};
+use thiserror::Error;
+use zip::result::ZipError;
+
+/// Largest file decompressed from a gzip file or a zip archive, far beyond any track
+pub const MAX_DECOMPRESSED_BYTES: u64 = 1 << 30;
+
+#[derive(Error, Debug)]
+pub enum CompressionError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Invalid zip archive: {0}")]
+    Zip(#[from] ZipError),
+    #[error("Decompressed data exceeds the limit of {0} bytes")]
+    TooLarge(u64),
+    #[error("Zip entry {0} does not have the size of its header")]
+    SizeMismatch(String),
+}
+
+/// Reads at most `limit` bytes, failing if there are more.
+fn read_bounded(reader: impl Read, limit: u64) -> Result<Vec<u8>, CompressionError> {
+    let mut data = Vec::new();
+    reader
+        .take(limit.saturating_add(1))
+        .read_to_end(&mut data)?;
+    if data.len() as u64 > limit {
+        return Err(CompressionError::TooLarge(limit));
+    }
+    Ok(data)
+}
+
+/// Decompresses a gzip file (RFC 1952), including files of several concatenated members,
+/// failing beyond `max_bytes`.
+pub fn gunzip(data: &[u8], max_bytes: u64) -> Result<Vec<u8>, CompressionError> {
+    read_bounded(MultiGzDecoder::new(data), max_bytes)
+}
+
+/// Zip archive, whose central directory is read once for all its entries.
+pub struct ZipArchive<R> {
+    archive: zip::ZipArchive<R>,
+}
+
+impl<R: Read + Seek> ZipArchive<R> {
+    /// Reads the central directory of an archive.
+    pub fn new(reader: R) -> Result<Self, CompressionError> {
+        Ok(ZipArchive {
+            archive: zip::ZipArchive::new(reader)?,
+        })
+    }
+
+    /// Paths of the files of the archive, with `/` separators, in the order of the archive.
+    ///
+    /// Directories are omitted.
+    pub fn entries(&self) -> Vec<String> {
+        self.archive
+            .file_names()
+            .filter(|name| !name.ends_with('/'))
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Reads and decompresses the entry `name`, if any, failing beyond `max_bytes` or
+    /// beyond the size announced by its header.
+    pub fn read(
+        &mut self,
+        name: &str,
+        max_bytes: u64,
+    ) -> Result<Option<Vec<u8>>, CompressionError> {
+        let entry = match self.archive.by_name(name) {
+            Ok(entry) => entry,
+            Err(ZipError::FileNotFound) => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+        let size = entry.size();
+        if size > max_bytes {
+            return Err(CompressionError::TooLarge(max_bytes));
+        }
+        // Read to the end of the entry, for its checksum to be verified
+        match read_bounded(entry, size) {
+            Ok(data) if data.len() as u64 == size => Ok(Some(data)),
+            Ok(_) | Err(CompressionError::TooLarge(_)) => {
+                Err(CompressionError::SizeMismatch(name.to_string()))
+            }
+            Err(error) => Err(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::fs;
+    use std::io::{Cursor, Write};
+    use std::path::Path;
+    use zip::write::SimpleFileOptions;
+    use zip::CompressionMethod;
+
+    fn fixture(path: &str) -> Vec<u8> {
+        fs::read(
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("tests/fixtures")
+                .join(path),
+        )
+        .unwrap()
+    }
+
+    /// Zip archive of one deflated entry.
+    fn zip_of(name: &str, data: &[u8]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        writer.start_file(name, options).unwrap();
+        writer.write_all(data).unwrap();
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_gunzip() {
+        // Compressed with dynamic Huffman codes
+        let data = gunzip(
+            &fixture("compressed/20081023125304.plt.gz"),
+            MAX_DECOMPRESSED_BYTES,
+        );
+        assert_eq!(data.unwrap(), fixture("geolife/20081023125304.plt"));
+
+        // Concatenated members
+        let mut members = Vec::new();
+        for part in [&b"hello "[..], b"world"] {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(part).unwrap();
+            members.extend(encoder.finish().unwrap());
+        }
+        assert_eq!(gunzip(&members, 100).unwrap(), b"hello world");
+    }
+
+    #[test]
+    fn test_gunzip_errors() {
+        let mut data = fixture("compressed/20081023125304.plt.gz");
+        assert!(matches!(
+            gunzip(&data[1..], MAX_DECOMPRESSED_BYTES),
+            Err(CompressionError::Io(_))
+        ));
+
+        let last = data.len() - 5;
+        data[last] ^= 0xFF;
+        assert!(matches!(
+            gunzip(&data, MAX_DECOMPRESSED_BYTES),
+            Err(CompressionError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_gunzip_bomb() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(&vec![0; 10 << 20]).unwrap();
+        let bomb = encoder.finish().unwrap();
+        assert!(bomb.len() < 64 * 1024);
+        assert!(matches!(
+            gunzip(&bomb, 1 << 20),
+            Err(CompressionError::TooLarge(limit)) if limit == 1 << 20
+        ));
+    }
+
+    #[test]
+    fn test_zip() {
+        let mut archive = ZipArchive::new(Cursor::new(fixture("compressed/geolife.zip"))).unwrap();
+        assert_eq!(
+            archive.entries(),
+            vec![
+                "Data/000/Trajectory/20081023025304.plt",
+                "Data/000/Trajectory/20081023075304.plt",
+                "Data/readme.txt",
+            ]
+        );
+
+        // Deflated, then stored
+        for plain in ["20081023025304.plt", "20081023075304.plt"] {
+            let name = format!("Data/000/Trajectory/{plain}");
+            let data = archive.read(&name, MAX_DECOMPRESSED_BYTES).unwrap();
+            assert_eq!(data, Some(fixture(&format!("geolife/{plain}"))));
+        }
+        assert!(archive.read("Data/missing.plt", 100).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_zip_invalid() {
+        let archive = ZipArchive::new(Cursor::new(b"not a zip archive".to_vec()));
+        assert!(matches!(archive, Err(CompressionError::Zip(_))));
+    }
+
+    #[test]
+    fn test_zip_bomb() {
+        let mut bomb = zip_of("bomb.plt", &vec![0; 10 << 20]);
+        let mut archive = ZipArchive::new(Cursor::new(bomb.clone())).unwrap();
+        assert!(matches!(
+            archive.read("bomb.plt", 1 << 20),
+            Err(CompressionError::TooLarge(_))
+        ));
+
+        // Announcing less than it holds does not get it past the limit either: the size is
+        // at offset 22 of the local header and 24 of the central directory entry
+        let directory = bomb.len() - 22 - 46 - "bomb.plt".len();
+        for offset in [22, directory + 24] {
+            bomb[offset..offset + 4].copy_from_slice(&1024_u32.to_le_bytes());
+        }
+        let mut archive = ZipArchive::new(Cursor::new(bomb)).unwrap();
+        assert!(matches!(
+            archive.read("bomb.plt", 1 << 20),
+            Err(CompressionError::SizeMismatch(name)) if name == "bomb.plt"
+        ));
+    }
+}
@@ -0,0 +1,410 @@
+//! Configuration file for the simplification pipeline.
+//!
+//! The configuration is a TOML file. All tolerances are in meters:
+//!
+//! ```toml
+//! # Default tolerance
+//! epsilon_m = 100
+//! # or a fraction of the distance between the two points of each trajectory farthest
+//! # apart, 0.1% here, which suits short walks and long drives alike
+//! # epsilon_fraction = 0.001
+//!
+//! # Minimum number of points kept per trajectory, endpoints included
+//! min_points = 4
+//!
+//! # Points kept even within epsilon: the extrema of each coordinate, for the bounding box
+//! # of the simplified trajectory to be the original one, and the sharpest turns
+//! keep_extrema = true
+//! sharpest_turns = 3
+//!
+//! # Tolerance per transportation mode, used for points covered by a GeoLife label
+//! [epsilon_by_mode]
+//! walk = 5
+//! car = 15
+//!
+//! # Tolerance per speed band, used for points without a mode label. Bands are tried
+//! # in order and the first one whose `max_speed_mps` is above the speed applies.
+//! [[epsilon_by_speed]]
+//! max_speed_mps = 2.5
+//! epsilon_m = 5
+//!
+//! [[epsilon_by_speed]]
+//! max_speed_mps = 40
+//! epsilon_m = 15
+//!
+//! # Timestamps of the protobuf output, "seconds" or "milliseconds", "absolute" or "delta".
+//! # By default seconds, delta-encoded only when coordinates are.
+//! [output]
+//! timestamp_unit = "milliseconds"
+//! timestamp_encoding = "delta"
+//! ```
+
+use crate::geo::{meters_to_units, point_speeds};
+use crate::geom;
+use crate::labels::{mode_at, ModeLabel};
+use crate::proto::TimestampUnit;
+use crate::trajectory::{TimestampEncoding, TimestampFormat, Trajectory};
+use std::collections::BTreeMap;
+use std::path::Path;
+use thiserror::Error;
+use toml_edit::{DocumentMut, Item};
+
+/// Half-width, in points, of the window used to estimate the speed at a point
+const SPEED_WINDOW: usize = 5;
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Error while reading config file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid TOML: {0}")]
+    Toml(#[from] toml_edit::TomlError),
+    #[error("Unknown config key: {0}")]
+    UnknownKey(String),
+    #[error("Invalid value for {0}")]
+    InvalidValue(String),
+}
+
+/// Tolerance applied to points moving at most at `max_speed_mps`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpeedBand {
+    pub max_speed_mps: f64,
+    pub epsilon_m: f64,
+}
+
+/// Format of the protobuf output.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct OutputConfig {
+    pub timestamp_unit: Option<TimestampUnit>,
+    pub timestamp_encoding: Option<TimestampEncoding>,
+}
+
+impl OutputConfig {
+    /// Timestamp format to use, with `default_encoding` unless one is configured.
+    pub fn timestamp_format(self, default_encoding: TimestampEncoding) -> TimestampFormat {
+        TimestampFormat {
+            unit: self.timestamp_unit.unwrap_or(TimestampUnit::Seconds),
+            encoding: self.timestamp_encoding.unwrap_or(default_encoding),
+        }
+    }
+}
+
+/// Pipeline configuration.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Config {
+    /// Default tolerance in meters, overriding the built-in one
+    pub epsilon_m: Option<f64>,
+    /// Default tolerance as a fraction of the trajectory diameter, exclusive with `epsilon_m`
+    pub epsilon_fraction: Option<f64>,
+    /// Tolerance in meters per transportation mode
+    pub epsilon_by_mode: BTreeMap<String, f64>,
+    /// Tolerance per speed band, in order
+    pub epsilon_by_speed: Vec<SpeedBand>,
+    /// Minimum number of points kept per trajectory, endpoints included
+    pub min_points: Option<usize>,
+    /// Whether to keep the lowest and highest latitude and longitude
+    pub keep_extrema: bool,
+    /// Number of sharpest turns to keep
+    pub sharpest_turns: usize,
+    /// Format of the protobuf output
+    pub output: OutputConfig,
+}
+
+/// Reads a non-negative number, accepting both TOML integers and floats.
+fn number(item: &Item, key: &str) -> Result<f64, ConfigError> {
+    item.as_float()
+        .or_else(|| item.as_integer().map(|i| i as f64))
+        .filter(|value| *value >= 0.0)
+        .ok_or_else(|| ConfigError::InvalidValue(key.to_string()))
+}
+
+/// Reads a non-negative integer.
+fn count(item: &Item, key: &str) -> Result<usize, ConfigError> {
+    item.as_integer()
+        .and_then(|value| usize::try_from(value).ok())
+        .ok_or_else(|| ConfigError::InvalidValue(key.to_string()))
+}
+
+impl Config {
+    /// Parses a configuration from TOML.
+    pub fn parse(toml: &str) -> Result<Config, ConfigError> {
+        let document: DocumentMut = toml.parse()?;
+        let mut config = Config::default();
+
+        for (key, item) in document.iter() {
+            match key {
+                "epsilon_m" => config.epsilon_m = Some(number(item, key)?),
+                "epsilon_fraction" => {
+                    let fraction = Some(number(item, key)?).filter(|&fraction| fraction <= 1.0);
+                    config.epsilon_fraction =
+                        Some(fraction.ok_or_else(|| ConfigError::InvalidValue(key.to_string()))?);
+                }
+                "min_points" => config.min_points = Some(count(item, key)?),
+                "keep_extrema" => {
+                    config.keep_extrema = item
+                        .as_bool()
+                        .ok_or_else(|| ConfigError::InvalidValue(key.to_string()))?;
+                }
+                "sharpest_turns" => config.sharpest_turns = count(item, key)?,
+                "epsilon_by_mode" => {
+                    let table = item
+                        .as_table_like()
+                        .ok_or_else(|| ConfigError::InvalidValue(key.to_string()))?;
+                    for (mode, item) in table.iter() {
+                        let epsilon = number(item, &format!("{key}.{mode}"))?;
+                        config.epsilon_by_mode.insert(mode.to_string(), epsilon);
+                    }
+                }
+                "epsilon_by_speed" => {
+                    let bands = item
+                        .as_array_of_tables()
+                        .ok_or_else(|| ConfigError::InvalidValue(key.to_string()))?;
+                    for band in bands.iter() {
+                        let field = |name: &str| {
+                            let path = format!("{key}.{name}");
+                            band.get(name)
+                                .ok_or_else(|| ConfigError::InvalidValue(path.clone()))
+                                .and_then(|item| number(item, &path))
+                        };
+                        config.epsilon_by_speed.push(SpeedBand {
+                            max_speed_mps: field("max_speed_mps")?,
+                            epsilon_m: field("epsilon_m")?,
+                        });
+                    }
+                }
+                "output" => {
+                    let table = item
+                        .as_table_like()
+                        .ok_or_else(|| ConfigError::InvalidValue(key.to_string()))?;
+                    for (name, item) in table.iter() {
+                        let path = format!("{key}.{name}");
+                        let value = item.as_str();
+                        match name {
+                            "timestamp_unit" => {
+                                config.output.timestamp_unit = Some(match value {
+                                    Some("seconds") => TimestampUnit::Seconds,
+                                    Some("milliseconds") => TimestampUnit::Milliseconds,
+                                    _ => return Err(ConfigError::InvalidValue(path)),
+                                });
+                            }
+                            "timestamp_encoding" => {
+                                config.output.timestamp_encoding = Some(match value {
+                                    Some("absolute") => TimestampEncoding::Absolute,
+                                    Some("delta") => TimestampEncoding::Delta,
+                                    _ => return Err(ConfigError::InvalidValue(path)),
+                                });
+                            }
+                            _ => return Err(ConfigError::UnknownKey(path)),
+                        }
+                    }
+                }
+                _ => return Err(ConfigError::UnknownKey(key.to_string())),
+            }
+        }
+        if config.epsilon_m.is_some() && config.epsilon_fraction.is_some() {
+            return Err(ConfigError::InvalidValue("epsilon_fraction".to_string()));
+        }
+
+        Ok(config)
+    }
+
+    /// Reads and parses a configuration file.
+    pub fn load(path: &Path) -> Result<Config, ConfigError> {
+        Config::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Returns the tolerance, in scaled units, to use for each point of the trajectory.
+    ///
+    /// The mode label covering the point takes precedence, then the speed band of the
+    /// point, then the default tolerance of the configuration, then `default_epsilon`.
+    pub fn point_epsilons(
+        &self,
+        trajectory: &Trajectory,
+        labels: &[ModeLabel],
+        default_epsilon: i64,
+    ) -> Vec<i64> {
+        let default_epsilon = match (self.epsilon_m, self.epsilon_fraction) {
+            (Some(epsilon_m), _) => meters_to_units(epsilon_m),
+            (None, Some(fraction)) => {
+                meters_to_units(fraction * geom::diameter_m(trajectory).unwrap_or_default())
+            }
+            (None, None) => default_epsilon,
+        };
+        let speeds = point_speeds(trajectory, SPEED_WINDOW);
+
+        trajectory
+            .timestamps
+            .iter()
+            .zip(speeds)
+            .map(|(&ts, speed)| {
+                let by_mode = mode_at(labels, ts).and_then(|mode| self.epsilon_by_mode.get(mode));
+                let by_speed = || {
+                    self.epsilon_by_speed
+                        .iter()
+                        .find(|band| speed <= band.max_speed_mps)
+                        .map(|band| &band.epsilon_m)
+                };
+                by_mode
+                    .or_else(by_speed)
+                    .map_or(default_epsilon, |&epsilon_m| meters_to_units(epsilon_m))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trajectory::CoordinateSystem;
+    use chrono::DateTime;
+
+    const CONFIG: &str = r#"
+epsilon_m = 100
+min_points = 4
+keep_extrema = true
+sharpest_turns = 3
+
+[epsilon_by_mode]
+walk = 5
+car = 15.5
+
+[[epsilon_by_speed]]
+max_speed_mps = 2.5
+epsilon_m = 5
+
+[[epsilon_by_speed]]
+max_speed_mps = 40
+epsilon_m = 15
+
+[output]
+timestamp_unit = "milliseconds"
+"#;
+
+    #[test]
+    fn test_parse() {
+        let config = Config::parse(CONFIG).unwrap();
+
+        assert_eq!(config.epsilon_m, Some(100.0));
+        assert_eq!(config.min_points, Some(4));
+        assert!(config.keep_extrema);
+        assert_eq!(config.sharpest_turns, 3);
+        assert_eq!(config.epsilon_by_mode["walk"], 5.0);
+        assert_eq!(config.epsilon_by_mode["car"], 15.5);
+        assert_eq!(
+            config.epsilon_by_speed,
+            vec![
+                SpeedBand { max_speed_mps: 2.5, epsilon_m: 5.0 },
+                SpeedBand { max_speed_mps: 40.0, epsilon_m: 15.0 },
+            ]
+        );
+        assert_eq!(
+            config.output.timestamp_format(TimestampEncoding::Delta),
+            TimestampFormat {
+                unit: TimestampUnit::Milliseconds,
+                encoding: TimestampEncoding::Delta,
+            }
+        );
+        assert_eq!(
+            Config::default().output.timestamp_format(TimestampEncoding::Absolute),
+            TimestampFormat {
+                unit: TimestampUnit::Seconds,
+                encoding: TimestampEncoding::Absolute,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(Config::parse("epsilon = 1"), Err(ConfigError::UnknownKey(_))));
+        assert!(matches!(Config::parse("epsilon_m = -1"), Err(ConfigError::InvalidValue(_))));
+        assert!(matches!(Config::parse("min_points = 2.5"), Err(ConfigError::InvalidValue(_))));
+        assert!(matches!(
+            Config::parse("keep_extrema = 1"),
+            Err(ConfigError::InvalidValue(_))
+        ));
+        assert!(matches!(Config::parse("epsilon_m = "), Err(ConfigError::Toml(_))));
+        assert!(matches!(
+            Config::parse("epsilon_fraction = 2"),
+            Err(ConfigError::InvalidValue(_))
+        ));
+        assert!(matches!(
+            Config::parse("epsilon_m = 1\nepsilon_fraction = 0.1"),
+            Err(ConfigError::InvalidValue(_))
+        ));
+        assert!(matches!(
+            Config::parse("[[epsilon_by_speed]]\nepsilon_m = 1"),
+            Err(ConfigError::InvalidValue(_))
+        ));
+        assert!(matches!(
+            Config::parse("[output]\ntimestamp_unit = \"minutes\""),
+            Err(ConfigError::InvalidValue(_))
+        ));
+        assert!(matches!(
+            Config::parse("[output]\nunit = \"seconds\""),
+            Err(ConfigError::UnknownKey(_))
+        ));
+    }
+
+    fn straight_line(step: i64, len: usize) -> Trajectory {
+        Trajectory {
+            latitudes: (0..len as i64).map(|i| i * step).collect(),
+            longitudes: vec![0; len],
+            timestamps: (0..len as u64).collect(),
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        }
+    }
+
+    #[test]
+    fn test_point_epsilons_by_mode() {
+        let config = Config {
+            epsilon_by_speed: vec![],
+            ..Config::parse(CONFIG).unwrap()
+        };
+        let labels = vec![ModeLabel {
+            start: DateTime::from_timestamp(0, 0).unwrap(),
+            end: DateTime::from_timestamp(1, 0).unwrap(),
+            mode: "car".to_string(),
+        }];
+
+        let epsilons = config.point_epsilons(&straight_line(10, 4), &labels, 1000);
+        assert_eq!(
+            epsilons,
+            vec![
+                meters_to_units(15.5),
+                meters_to_units(15.5),
+                meters_to_units(100.0),
+                meters_to_units(100.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_point_epsilons_by_speed() {
+        let config = Config::parse(CONFIG).unwrap();
+
+        // About 1.1 m/s
+        let epsilons = config.point_epsilons(&straight_line(10, 20), &[], 1000);
+        assert_eq!(epsilons, vec![meters_to_units(5.0); 20]);
+
+        // About 111 m/s, faster than every band
+        let epsilons = config.point_epsilons(&straight_line(1000, 20), &[], 1000);
+        assert_eq!(epsilons, vec![meters_to_units(100.0); 20]);
+    }
+
+    #[test]
+    fn test_point_epsilons_by_diameter() {
+        let config = Config::parse("epsilon_fraction = 0.01").unwrap();
+
+        // 1 km and 100 km long
+        let epsilons = config.point_epsilons(&straight_line(1000, 10), &[], 1000);
+        assert_eq!(epsilons, vec![90; 10]);
+        let epsilons = config.point_epsilons(&straight_line(100_000, 10), &[], 1000);
+        assert_eq!(epsilons, vec![9000; 10]);
+    }
+
+    #[test]
+    fn test_point_epsilons_default() {
+        let epsilons = Config::default().point_epsilons(&straight_line(10, 8), &[], 1000);
+        assert_eq!(epsilons, vec![1000; 8]);
+    }
+}
@@ -0,0 +1,648 @@
+//! Corridors: the area within a given distance of a route, typically a simplified
+//! trajectory, for geofencing and exposure analysis.
+//!
+//! Distances are measured in an equirectangular projection around the start of the route
+//! (see `LocalProjection`), so routes should span at most a few hundred kilometers.
+
+use crate::geo::{to_degrees, LocalProjection};
+use crate::geohash::{self, Bounds};
+use crate::simplify;
+use crate::trajectory::Trajectory;
+use serde_json::{json, Value};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Number of raster cells per radius when tracing the outline of a corridor
+const CELLS_PER_RADIUS: f64 = 8.0;
+
+/// Smallest cell size in meters of the index used by `Corridor::contains`
+const MIN_INDEX_CELL_M: f64 = 10.0;
+
+/// Outline of an area, with rings of `(latitude, longitude)` points in degrees whose last
+/// point repeats the first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polygon {
+    /// Outer boundary, counter-clockwise
+    pub exterior: Vec<(f64, f64)>,
+    /// Boundaries of the holes, clockwise
+    pub holes: Vec<Vec<(f64, f64)>>,
+    /// Area in square meters, holes excluded
+    pub area_m2: f64,
+}
+
+impl Polygon {
+    /// Builds a GeoJSON `Polygon` geometry.
+    pub fn geojson(&self) -> Value {
+        let ring = |points: &[(f64, f64)]| -> Vec<[f64; 2]> {
+            points
+                .iter()
+                .map(|&(latitude, longitude)| [longitude, latitude])
+                .collect()
+        };
+        let rings: Vec<Vec<[f64; 2]>> = std::iter::once(&self.exterior)
+            .chain(&self.holes)
+            .map(|points| ring(points))
+            .collect();
+        json!({ "type": "Polygon", "coordinates": rings })
+    }
+}
+
+/// Area within `radius_m` meters of a route.
+#[derive(Debug, Clone)]
+pub struct Corridor {
+    projection: LocalProjection,
+    /// Projected route points in meters
+    points: Vec<(f64, f64)>,
+    radius_m: f64,
+    /// Size in meters of the cells of `index`
+    index_cell: f64,
+    /// Segments passing through each cell of a square grid, by the index of their first point
+    index: HashMap<(i64, i64), Vec<usize>>,
+}
+
+/// Distance from a point to the segment between `a` and `b`.
+fn point_segment_distance(p: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length_squared = dx * dx + dy * dy;
+    let t = if length_squared == 0.0 {
+        0.0
+    } else {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / length_squared).clamp(0.0, 1.0)
+    };
+    (p.0 - a.0 - t * dx).hypot(p.1 - a.1 - t * dy)
+}
+
+/// Whether the segment between `a` and `b` crosses the rectangle `(min, max)`, by clipping
+/// it with Liang-Barsky.
+fn segment_crosses_rectangle(
+    a: (f64, f64),
+    b: (f64, f64),
+    min: (f64, f64),
+    max: (f64, f64),
+) -> bool {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let (mut t0, mut t1) = (0.0_f64, 1.0_f64);
+    for (p, q) in [
+        (-dx, a.0 - min.0),
+        (dx, max.0 - a.0),
+        (-dy, a.1 - min.1),
+        (dy, max.1 - a.1),
+    ] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return false;
+            }
+        } else if p < 0.0 {
+            t0 = t0.max(q / p);
+        } else {
+            t1 = t1.min(q / p);
+        }
+    }
+    t0 <= t1
+}
+
+/// Distance from the segment between `a` and `b` to the rectangle `(min, max)`, zero if
+/// they intersect.
+fn segment_rectangle_distance(
+    a: (f64, f64),
+    b: (f64, f64),
+    min: (f64, f64),
+    max: (f64, f64),
+) -> f64 {
+    if segment_crosses_rectangle(a, b, min, max) {
+        return 0.0;
+    }
+    let corners = [min, (max.0, min.1), max, (min.0, max.1)];
+    let to_corners = corners
+        .into_iter()
+        .map(|corner| point_segment_distance(corner, a, b));
+    let to_rectangle = [a, b].into_iter().map(|p| {
+        let dx = (min.0 - p.0).max(p.0 - max.0).max(0.0);
+        let dy = (min.1 - p.1).max(p.1 - max.1).max(0.0);
+        dx.hypot(dy)
+    });
+    to_corners.chain(to_rectangle).fold(f64::INFINITY, f64::min)
+}
+
+/// Interval of `x` where the horizontal line at `y` is within `radius` of the segment
+/// between `a` and `b`, or `None` if they are farther apart.
+///
+/// The points within `radius` of a segment are the union of two disks around its ends and
+/// of a rectangle along it, and since they form a convex set the line crosses them along a
+/// single interval.
+fn capsule_row(a: (f64, f64), b: (f64, f64), radius: f64, y: f64) -> Option<(f64, f64)> {
+    let mut interval: Option<(f64, f64)> = None;
+    let mut include = |min: f64, max: f64| {
+        if min <= max {
+            interval = Some(interval.map_or((min, max), |(lo, hi)| (lo.min(min), hi.max(max))));
+        }
+    };
+
+    for p in [a, b] {
+        let dy = y - p.1;
+        if dy.abs() <= radius {
+            let half_width = (radius * radius - dy * dy).sqrt();
+            include(p.0 - half_width, p.0 + half_width);
+        }
+    }
+
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length = dx.hypot(dy);
+    if length > 0.0 {
+        let (ux, uy) = (dx / length, dy / length);
+        let (mut min, mut max) = (f64::NEG_INFINITY, f64::INFINITY);
+        // Along the segment and across it, each constraint is `low <= slope * (x - a.0) + offset <= high`
+        for (slope, offset, low, high) in [
+            (ux, uy * (y - a.1), 0.0, length),
+            (-uy, ux * (y - a.1), -radius, radius),
+        ] {
+            if slope.abs() < 1e-12 {
+                if offset < low || offset > high {
+                    return interval;
+                }
+            } else {
+                let (x1, x2) = ((low - offset) / slope + a.0, (high - offset) / slope + a.0);
+                min = min.max(x1.min(x2));
+                max = max.min(x1.max(x2));
+            }
+        }
+        include(min, max);
+    }
+
+    interval
+}
+
+/// Traces the boundaries of a set of grid cells as closed rings of cell corners, with the
+/// cells on the left: outer boundaries turn counter-clockwise and holes clockwise. Where two
+/// cells touch only by a corner, the rings going around them are joined.
+fn trace_rings(cells: &HashSet<(i64, i64)>) -> Vec<Vec<(i64, i64)>> {
+    let mut edges: HashMap<(i64, i64), Vec<(i64, i64)>> = HashMap::new();
+    for &(i, j) in cells {
+        let sides = [
+            ((i, j - 1), (i, j), (i + 1, j)),
+            ((i + 1, j), (i + 1, j), (i + 1, j + 1)),
+            ((i, j + 1), (i + 1, j + 1), (i, j + 1)),
+            ((i - 1, j), (i, j + 1), (i, j)),
+        ];
+        for (neighbor, from, to) in sides {
+            if !cells.contains(&neighbor) {
+                edges.entry(from).or_default().push(to);
+            }
+        }
+    }
+
+    let mut rings = Vec::new();
+    while let Some(&start) = edges.keys().next() {
+        let mut ring = vec![start];
+        let mut current = start;
+        let mut direction = (0, 0);
+        while let Some(ends) = edges.get_mut(&current) {
+            // Prefer turning right, then going straight, then turning left
+            let preference = |to: &(i64, i64)| {
+                let turn = (to.0 - current.0, to.1 - current.1);
+                if turn == (direction.1, -direction.0) {
+                    0
+                } else if turn == direction {
+                    1
+                } else {
+                    2
+                }
+            };
+            let index = (0..ends.len())
+                .min_by_key(|&k| preference(&ends[k]))
+                .unwrap();
+            let next = ends.swap_remove(index);
+            if ends.is_empty() {
+                edges.remove(&current);
+            }
+            direction = (next.0 - current.0, next.1 - current.1);
+            ring.push(next);
+            current = next;
+        }
+        rings.push(ring);
+    }
+    rings
+}
+
+/// Twice the signed area of a closed ring, positive if it turns counter-clockwise.
+fn twice_signed_area(ring: &[(i64, i64)]) -> i64 {
+    ring.windows(2)
+        .map(|pair| pair[0].0 * pair[1].1 - pair[1].0 * pair[0].1)
+        .sum()
+}
+
+impl Corridor {
+    /// Creates the corridor of a route.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `radius_m` is negative.
+    pub fn new(route: &Trajectory, radius_m: f64) -> Self {
+        assert!(radius_m >= 0.0, "radius_m must be non-negative");
+
+        let reference = route
+            .latitudes
+            .first()
+            .zip(route.longitudes.first())
+            .map_or((0.0, 0.0), |(&lat, &lon)| {
+                (to_degrees(lat), to_degrees(lon))
+            });
+        let projection = LocalProjection::new(reference.0, reference.1);
+        let points = route
+            .latitudes
+            .iter()
+            .zip(&route.longitudes)
+            .map(|(&lat, &lon)| projection.project(to_degrees(lat), to_degrees(lon)))
+            .collect();
+
+        let mut corridor = Corridor {
+            projection,
+            points,
+            radius_m,
+            index_cell: radius_m.max(MIN_INDEX_CELL_M),
+            index: HashMap::new(),
+        };
+        corridor.build_index();
+        corridor
+    }
+
+    /// Segment starting at the given point, of length zero for the last point.
+    fn segment(&self, start: usize) -> ((f64, f64), (f64, f64)) {
+        let end = self.points.get(start + 1).unwrap_or(&self.points[start]);
+        (self.points[start], *end)
+    }
+
+    /// Cell of the index containing a projected point.
+    fn index_cell_of(&self, (x, y): (f64, f64)) -> (i64, i64) {
+        (
+            (x / self.index_cell).floor() as i64,
+            (y / self.index_cell).floor() as i64,
+        )
+    }
+
+    /// Registers every segment in the cells of points sampled along it every half cell.
+    fn build_index(&mut self) {
+        let segments = self
+            .points
+            .len()
+            .saturating_sub(1)
+            .max(self.points.len().min(1));
+        for start in 0..segments {
+            let (a, b) = self.segment(start);
+            let steps = ((a.0 - b.0).hypot(a.1 - b.1) / (self.index_cell / 2.0)).ceil() as usize;
+            let mut last = None;
+            for step in 0..=steps {
+                let t = if steps == 0 {
+                    0.0
+                } else {
+                    step as f64 / steps as f64
+                };
+                let cell = self.index_cell_of((a.0 + t * (b.0 - a.0), a.1 + t * (b.1 - a.1)));
+                if last != Some(cell) {
+                    let starts = self.index.entry(cell).or_default();
+                    if starts.last() != Some(&start) {
+                        starts.push(start);
+                    }
+                    last = Some(cell);
+                }
+            }
+        }
+    }
+
+    /// Tells whether a point, in degrees, is within the radius of the route.
+    ///
+    /// Only the segments registered near the point are measured. Every point of a segment
+    /// is within a quarter cell of a sampled point, so the segments within the radius are
+    /// registered in the cells within the radius plus a quarter cell.
+    pub fn contains(&self, latitude: f64, longitude: f64) -> bool {
+        let p = self.projection.project(latitude, longitude);
+        let reach = self.radius_m + self.index_cell / 4.0;
+        let (min_x, min_y) = self.index_cell_of((p.0 - reach, p.1 - reach));
+        let (max_x, max_y) = self.index_cell_of((p.0 + reach, p.1 + reach));
+
+        (min_x..=max_x)
+            .flat_map(|x| (min_y..=max_y).map(move |y| (x, y)))
+            .filter_map(|cell| self.index.get(&cell))
+            .flatten()
+            .any(|&start| {
+                let (a, b) = self.segment(start);
+                point_segment_distance(p, a, b) <= self.radius_m
+            })
+    }
+
+    /// Segments of the route, a single point giving one segment of length zero.
+    fn segments(&self) -> impl Iterator<Item = ((f64, f64), (f64, f64))> + '_ {
+        let pairs = self.points.windows(2).map(|pair| (pair[0], pair[1]));
+        let single = (self.points.len() == 1).then(|| (self.points[0], self.points[0]));
+        pairs.chain(single)
+    }
+
+    /// Lists the geohash cells of the given precision intersecting the corridor, sorted.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `precision` is zero or above `geohash::MAX_PRECISION`.
+    pub fn cells(&self, precision: usize) -> Vec<String> {
+        let mut cells = BTreeSet::new();
+        for (a, b) in self.segments() {
+            let (south, west) = self
+                .projection
+                .unproject(a.0.min(b.0) - self.radius_m, a.1.min(b.1) - self.radius_m);
+            let (north, east) = self
+                .projection
+                .unproject(a.0.max(b.0) + self.radius_m, a.1.max(b.1) + self.radius_m);
+            let area = Bounds {
+                min_latitude: south,
+                max_latitude: north,
+                min_longitude: west,
+                max_longitude: east,
+            };
+
+            for cell in geohash::covering(area, precision) {
+                if cells.contains(&cell) {
+                    continue;
+                }
+                let bounds = geohash::bounds(&cell).unwrap();
+                let min = self
+                    .projection
+                    .project(bounds.min_latitude, bounds.min_longitude);
+                let max = self
+                    .projection
+                    .project(bounds.max_latitude, bounds.max_longitude);
+                if segment_rectangle_distance(a, b, min, max) <= self.radius_m {
+                    cells.insert(cell);
+                }
+            }
+        }
+        cells.into_iter().collect()
+    }
+
+    /// Computes the outline of the corridor.
+    ///
+    /// The corridor is rasterized on a grid of `radius_m / 8` meter cells, whose boundary is
+    /// then traced and simplified with a tolerance of one cell. The outline is therefore
+    /// within about a quarter of the radius of the exact one.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the radius is zero.
+    pub fn polygon(&self) -> Polygon {
+        assert!(self.radius_m > 0.0, "radius_m must be positive");
+
+        let cell = self.radius_m / CELLS_PER_RADIUS;
+        let mut cells = HashSet::new();
+        for (a, b) in self.segments() {
+            let rows = ((a.1.min(b.1) - self.radius_m) / cell).floor() as i64
+                ..=((a.1.max(b.1) + self.radius_m) / cell).ceil() as i64;
+            for row in rows {
+                let y = (row as f64 + 0.5) * cell;
+                if let Some((min, max)) = capsule_row(a, b, self.radius_m, y) {
+                    let columns =
+                        (min / cell - 0.5).ceil() as i64..=(max / cell - 0.5).floor() as i64;
+                    cells.extend(columns.map(|column| (column, row)));
+                }
+            }
+        }
+
+        let mut exterior = Vec::new();
+        let mut holes = Vec::new();
+        for ring in trace_rings(&cells) {
+            let area = twice_signed_area(&ring);
+            let (xs, ys): (Vec<i64>, Vec<i64>) = ring.iter().copied().unzip();
+            let keep = simplify::simplify(&xs, &ys, 1);
+            let points: Vec<(f64, f64)> = ring
+                .iter()
+                .zip(keep)
+                .filter(|(_, keep)| *keep)
+                .map(|(&(x, y), _)| self.projection.unproject(x as f64 * cell, y as f64 * cell))
+                .collect();
+            if points.len() < 4 {
+                continue;
+            }
+            // The route is connected, so the corridor has a single outer boundary
+            if area > 0 {
+                exterior = points;
+            } else {
+                holes.push(points);
+            }
+        }
+
+        Polygon {
+            exterior,
+            holes,
+            area_m2: cells.len() as f64 * cell * cell,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trajectory::CoordinateSystem;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_segment_rectangle_distance() {
+        let (min, max) = ((0.0, 0.0), (10.0, 10.0));
+        // Crossing, inside, beside an edge and beside a corner
+        assert_eq!(
+            segment_rectangle_distance((-5.0, 5.0), (15.0, 5.0), min, max),
+            0.0
+        );
+        assert_eq!(
+            segment_rectangle_distance((2.0, 2.0), (3.0, 3.0), min, max),
+            0.0
+        );
+        assert_eq!(
+            segment_rectangle_distance((-5.0, 12.0), (15.0, 12.0), min, max),
+            2.0
+        );
+        assert_eq!(
+            segment_rectangle_distance((13.0, 14.0), (20.0, 20.0), min, max),
+            5.0
+        );
+        // A diagonal passing near a corner
+        let d = segment_rectangle_distance((10.0, 14.0), (14.0, 10.0), min, max);
+        assert!((d - 8.0_f64.sqrt()).abs() < 1e-9, "{d}");
+    }
+
+    #[test]
+    fn test_cells() {
+        // About 1.1 km north along a meridian, at precision 7 (cells of 153 m by 153 m)
+        let route = Trajectory {
+            latitudes: vec![42_600_000, 42_610_000],
+            longitudes: vec![-5_600_000, -5_600_000],
+            timestamps: vec![0, 60],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+        let narrow = Corridor::new(&route, 0.0).cells(7);
+        let wide = Corridor::new(&route, 500.0).cells(7);
+
+        assert!(narrow.contains(&geohash::encode(42_605_000, -5_600_000, 7)));
+        assert!(narrow.len() >= 8 && narrow.len() <= 16, "{}", narrow.len());
+        assert!(narrow.iter().all(|cell| wide.contains(cell)));
+        // About 1,000 m wide and 2,100 m long
+        assert!(wide.len() > 6 * 8 && wide.len() < 9 * 16, "{}", wide.len());
+
+        // A single point gives the cells of a disk
+        let point = Trajectory {
+            latitudes: vec![42_600_000],
+            longitudes: vec![-5_600_000],
+            timestamps: vec![0],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+        assert_eq!(Corridor::new(&point, 0.0).cells(7).len(), 1);
+        assert!(Corridor::new(&point, 200.0).cells(7).len() >= 9);
+    }
+
+    #[test]
+    fn test_capsule_row() {
+        let (a, b) = ((0.0, 0.0), (10.0, 0.0));
+        assert_eq!(capsule_row(a, b, 2.0, 0.0), Some((-2.0, 12.0)));
+        assert_eq!(capsule_row(a, b, 2.0, 2.0), Some((0.0, 10.0)));
+        assert_eq!(capsule_row(a, b, 2.0, 3.0), None);
+
+        // A diagonal segment: the band across the line at 45 degrees is 2 * sqrt(2) wide
+        let (min, max) = capsule_row((0.0, 0.0), (10.0, 10.0), 1.0, 5.0).unwrap();
+        assert!((min - (5.0 - 2_f64.sqrt())).abs() < 1e-9, "{min}");
+        assert!((max - (5.0 + 2_f64.sqrt())).abs() < 1e-9, "{max}");
+    }
+
+    #[test]
+    fn test_trace_rings() {
+        // A 3 by 3 square with a hole in the middle
+        let cells: HashSet<(i64, i64)> = (0..3)
+            .flat_map(|i| (0..3).map(move |j| (i, j)))
+            .filter(|&cell| cell != (1, 1))
+            .collect();
+        let mut areas: Vec<i64> = trace_rings(&cells)
+            .iter()
+            .map(|ring| {
+                assert_eq!(ring.first(), ring.last());
+                twice_signed_area(ring)
+            })
+            .collect();
+        areas.sort();
+        assert_eq!(areas, vec![-2, 18]);
+
+        // Cells touching by a corner are joined into one ring
+        let cells: HashSet<(i64, i64)> = [(0, 0), (1, 1)].into_iter().collect();
+        let rings = trace_rings(&cells);
+        assert_eq!(rings.len(), 1);
+        assert_eq!(twice_signed_area(&rings[0]), 4);
+    }
+
+    #[test]
+    fn test_polygon() {
+        // About 1.1 km along the equator with a 100 m radius: a stadium of about
+        // 1,112 * 200 + pi * 100^2 = 253,800 m²
+        let route = Trajectory {
+            latitudes: vec![0, 0],
+            longitudes: vec![0, 10_000],
+            timestamps: vec![0, 60],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+        let polygon = Corridor::new(&route, 100.0).polygon();
+
+        assert!(
+            (polygon.area_m2 - 253_800.0).abs() < 5_000.0,
+            "{}",
+            polygon.area_m2
+        );
+        assert!(polygon.holes.is_empty());
+        assert_eq!(polygon.exterior.first(), polygon.exterior.last());
+        let max_latitude = polygon
+            .exterior
+            .iter()
+            .map(|p| p.0)
+            .fold(f64::MIN, f64::max);
+        // 100 m north, within a cell
+        assert!(
+            (max_latitude * 111_195.0 - 100.0).abs() < 13.0,
+            "{max_latitude}"
+        );
+
+        let geojson = polygon.geojson();
+        assert_eq!(geojson["type"], "Polygon");
+        assert_eq!(geojson["coordinates"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_polygon_with_hole() {
+        // A square loop of about 1.1 km with a 100 m radius leaves a hole in the middle
+        let route = Trajectory {
+            latitudes: vec![0, 0, 10_000, 10_000, 0],
+            longitudes: vec![0, 10_000, 10_000, 0, 0],
+            timestamps: vec![0, 1, 2, 3, 4],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+        let polygon = Corridor::new(&route, 100.0).polygon();
+        assert_eq!(polygon.holes.len(), 1);
+        // The hole is about 900 m wide
+        let hole_latitudes: Vec<f64> = polygon.holes[0].iter().map(|p| p.0).collect();
+        let height = hole_latitudes.iter().fold(f64::MIN, |a, &b| a.max(b))
+            - hole_latitudes.iter().fold(f64::MAX, |a, &b| a.min(b));
+        assert!((height * 111_195.0 - 912.0).abs() < 30.0, "{height}");
+    }
+
+    #[test]
+    fn test_contains() {
+        // An L-shaped route of about 2 x 1.1 km, with a 50 m radius
+        let route = Trajectory {
+            latitudes: vec![39_900_000, 39_900_000, 39_910_000],
+            longitudes: vec![116_400_000, 116_423_000, 116_423_000],
+            timestamps: vec![0, 1, 2],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+        let corridor = Corridor::new(&route, 50.0);
+        let meters_per_degree = 111_195.0;
+
+        // 40 m and 60 m north of the first leg
+        assert!(corridor.contains(39.9 + 40.0 / meters_per_degree, 116.41));
+        assert!(!corridor.contains(39.9 + 60.0 / meters_per_degree, 116.41));
+        // Beyond the corner and near the end of the second leg
+        assert!(corridor.contains(39.9, 116.423 + 40.0 / (meters_per_degree * 0.766)));
+        assert!(corridor.contains(39.91 + 30.0 / meters_per_degree, 116.423));
+        assert!(!corridor.contains(39.95, 116.5));
+
+        // Same answers as measuring every segment
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+        for _ in 0..2_000 {
+            let latitude = rng.gen_range(39.899..39.911);
+            let longitude = rng.gen_range(116.399..116.424);
+            let p = corridor.projection.project(latitude, longitude);
+            let expected = corridor
+                .points
+                .windows(2)
+                .any(|pair| point_segment_distance(p, pair[0], pair[1]) <= 50.0);
+            assert_eq!(corridor.contains(latitude, longitude), expected);
+        }
+    }
+
+    #[test]
+    fn test_contains_single_point() {
+        let route = Trajectory {
+            latitudes: vec![0],
+            longitudes: vec![0],
+            timestamps: vec![0],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+        let corridor = Corridor::new(&route, 0.0);
+        assert!(corridor.contains(0.0, 0.0));
+        assert!(!corridor.contains(0.0001, 0.0));
+
+        let empty = Trajectory {
+            latitudes: vec![],
+            longitudes: vec![],
+            timestamps: vec![],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+        assert!(!Corridor::new(&empty, 100.0).contains(0.0, 0.0));
+    }
+}
@@ -0,0 +1,191 @@
+//! Detection of the intervals during which two devices traveled together.
+//!
+//! Both trajectories are resampled at common timestamps over the span they share, and
+//! every run of samples closer than a distance threshold that lasts long enough is
+//! reported as an interval.
+
+use crate::geo::haversine_m;
+use crate::labels::ModeLabel;
+use crate::resample::position_at;
+use crate::trajectory::Trajectory;
+use chrono::DateTime;
+
+/// Thresholds of the co-travel detection.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CoTravelParams {
+    /// Largest distance in meters between the devices
+    pub max_distance_m: f64,
+    /// Shortest interval reported, in seconds
+    pub min_duration_s: u64,
+    /// Time in seconds between the common samples
+    pub step_s: u64,
+}
+
+/// Interval during which two devices stayed within the distance threshold.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CoTravel {
+    /// First and last sample of the interval, as Unix timestamps
+    pub start: u64,
+    pub end: u64,
+    /// Mean distance in meters between the devices over the interval
+    pub mean_distance_m: f64,
+}
+
+impl CoTravel {
+    /// Converts the interval to a label named `mode`, to write in a `labels.txt` file.
+    pub fn label(&self, mode: &str) -> ModeLabel {
+        let date = |ts: u64| DateTime::from_timestamp(ts as i64, 0).unwrap_or_default();
+        ModeLabel {
+            start: date(self.start),
+            end: date(self.end),
+            mode: mode.to_string(),
+        }
+    }
+}
+
+/// Finds the intervals during which `a` and `b` traveled together.
+///
+/// Timestamps must be sorted in non-decreasing order. Both trajectories are sampled every
+/// `step_s` seconds from the start of the span they share, positions being interpolated
+/// between points, so simplified trajectories can be compared directly.
+///
+/// # Panics
+///
+/// This function will panic if `step_s` is zero.
+pub fn co_travel(a: &Trajectory, b: &Trajectory, params: CoTravelParams) -> Vec<CoTravel> {
+    assert!(params.step_s > 0, "step_s must be positive");
+
+    let (Some(&start_a), Some(&end_a), Some(&start_b), Some(&end_b)) = (
+        a.timestamps.first(),
+        a.timestamps.last(),
+        b.timestamps.first(),
+        b.timestamps.last(),
+    ) else {
+        return Vec::new();
+    };
+    let (start, end) = (start_a.max(start_b), end_a.min(end_b));
+
+    let mut intervals = Vec::new();
+    // First sample, last sample and summed distance of the current run
+    let mut run: Option<(u64, u64, f64)> = None;
+    let mut close_run = |run: Option<(u64, u64, f64)>| {
+        if let Some((first, last, total)) = run {
+            if last - first >= params.min_duration_s {
+                let samples = (last - first) / params.step_s + 1;
+                intervals.push(CoTravel {
+                    start: first,
+                    end: last,
+                    mean_distance_m: total / samples as f64,
+                });
+            }
+        }
+    };
+
+    for ts in (start..=end).step_by(params.step_s as usize) {
+        let distance = position_at(a, ts as f64)
+            .zip(position_at(b, ts as f64))
+            .map(|((lat_a, lon_a), (lat_b, lon_b))| {
+                let round = |value: f64| value.round() as i64;
+                haversine_m(round(lat_a), round(lon_a), round(lat_b), round(lon_b))
+            });
+        match distance {
+            Some(distance) if distance <= params.max_distance_m => {
+                let (first, _, total) = run.unwrap_or((ts, ts, 0.0));
+                run = Some((first, ts, total + distance));
+            }
+            _ => close_run(run.take()),
+        }
+    }
+    close_run(run);
+
+    intervals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trajectory::CoordinateSystem;
+
+    const PARAMS: CoTravelParams = CoTravelParams {
+        max_distance_m: 50.0,
+        min_duration_s: 60,
+        step_s: 10,
+    };
+
+    #[test]
+    fn test_co_travel() {
+        // A goes east at about 8.5 m/s for 1000 s. B follows 20 m north of it from 100 s
+        // to 300 s, and from 500 s to 550 s, and is far away otherwise.
+        let a = Trajectory {
+            latitudes: vec![0, 0],
+            longitudes: vec![0, 76_000],
+            timestamps: vec![0, 1_000],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+        let north = 180; // about 20 m
+        let b = Trajectory {
+            latitudes: vec![10_000, north, north, 10_000, north, north, 10_000],
+            longitudes: vec![7_600, 7_600, 22_800, 30_400, 38_000, 41_800, 45_600],
+            timestamps: vec![50, 100, 300, 400, 500, 550, 600],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+
+        let intervals = co_travel(&a, &b, PARAMS);
+        assert_eq!(intervals.len(), 1);
+        assert_eq!((intervals[0].start, intervals[0].end), (100, 300));
+        assert!((intervals[0].mean_distance_m - 20.0).abs() < 0.5);
+
+        // The second interval lasts 50 s
+        let intervals = co_travel(
+            &a,
+            &b,
+            CoTravelParams {
+                min_duration_s: 50,
+                ..PARAMS
+            },
+        );
+        let spans: Vec<(u64, u64)> = intervals.iter().map(|i| (i.start, i.end)).collect();
+        assert_eq!(spans, vec![(100, 300), (500, 550)]);
+    }
+
+    #[test]
+    fn test_co_travel_disjoint() {
+        let a = Trajectory {
+            latitudes: vec![0, 0],
+            longitudes: vec![0, 0],
+            timestamps: vec![0, 100],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+        let b = Trajectory {
+            timestamps: vec![200, 300],
+            ..a.clone()
+        };
+        assert!(co_travel(&a, &b, PARAMS).is_empty());
+        assert_eq!(co_travel(&a, &a, PARAMS).len(), 1);
+
+        let empty = Trajectory {
+            latitudes: vec![],
+            longitudes: vec![],
+            timestamps: vec![],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+        assert!(co_travel(&a, &empty, PARAMS).is_empty());
+    }
+
+    #[test]
+    fn test_label() {
+        let label = CoTravel {
+            start: 1_207_135_461,
+            end: 1_207_135_561,
+            mean_distance_m: 0.0,
+        }
+        .label("co-travel");
+        assert_eq!(label.start.timestamp(), 1_207_135_461);
+        assert_eq!(label.end.timestamp(), 1_207_135_561);
+        assert_eq!(label.mode, "co-travel");
+    }
+}
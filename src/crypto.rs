@@ -0,0 +1,216 @@
+//! Authenticated encryption of output files with ChaCha20-Poly1305 (RFC 8439), since
+//! location histories are sensitive and outputs often end up on shared storage.
+//!
+//! An encrypted file is the header (`MAGIC`, `VERSION` and a random 12-byte nonce), the
+//! ciphertext, then the 16-byte tag authenticating both the header and the ciphertext,
+//! as sealed by the `chacha20poly1305` crate. The 32-byte key is read from the
+//! `TRAJECTORY_KEY` environment variable, as hex, or from the file named by
+//! `TRAJECTORY_KEY_FILE`, as hex or raw bytes.
+
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::ChaCha20Poly1305;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use std::io;
+use thiserror::Error;
+
+/// Magic bytes starting every encrypted file
+pub const MAGIC: &[u8; 4] = b"TRJE";
+
+/// Version of the encrypted file format
+pub const VERSION: u8 = 1;
+
+/// Environment variable holding the key in hex
+pub const KEY_ENV: &str = "TRAJECTORY_KEY";
+
+/// Environment variable holding the path of a key file
+pub const KEY_FILE_ENV: &str = "TRAJECTORY_KEY_FILE";
+
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+const HEADER_LEN: usize = MAGIC.len() + 1 + NONCE_LEN;
+
+/// A ChaCha20-Poly1305 key.
+pub type Key = [u8; 32];
+
+#[derive(Error, Debug)]
+pub enum CryptoError {
+    #[error("Error while reading key file: {0}")]
+    Io(#[from] io::Error),
+    #[error("Key must be 32 bytes, or 64 hexadecimal digits")]
+    InvalidKey,
+    #[error("File is not encrypted")]
+    NotEncrypted,
+    #[error("Unsupported encrypted file version {0}")]
+    UnsupportedVersion(u8),
+    #[error("File is truncated")]
+    Truncated,
+    #[error("Wrong key, or the file was modified")]
+    AuthenticationFailed,
+}
+
+/// Encrypts `plaintext` into the encrypted file format with a random nonce.
+pub fn encrypt(key: &Key, plaintext: &[u8]) -> Vec<u8> {
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+    encrypt_with_nonce(key, &nonce, plaintext)
+}
+
+fn encrypt_with_nonce(key: &Key, nonce: &[u8; NONCE_LEN], plaintext: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(HEADER_LEN + plaintext.len() + TAG_LEN);
+    output.extend_from_slice(MAGIC);
+    output.push(VERSION);
+    output.extend_from_slice(nonce);
+
+    let payload = Payload {
+        msg: plaintext,
+        aad: &output,
+    };
+    let sealed = ChaCha20Poly1305::new(key.into())
+        .encrypt(nonce.into(), payload)
+        .expect("Plaintext is too long to encrypt");
+    output.extend_from_slice(&sealed);
+    output
+}
+
+/// Tells whether `data` starts like an encrypted file.
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Decrypts a file produced by `encrypt`, checking that it was not modified.
+pub fn decrypt(key: &Key, data: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    if !is_encrypted(data) {
+        return Err(CryptoError::NotEncrypted);
+    }
+    if data.len() < HEADER_LEN + TAG_LEN {
+        return Err(CryptoError::Truncated);
+    }
+    if data[MAGIC.len()] != VERSION {
+        return Err(CryptoError::UnsupportedVersion(data[MAGIC.len()]));
+    }
+
+    let (header, sealed) = data.split_at(HEADER_LEN);
+    let nonce = &header[MAGIC.len() + 1..];
+    let payload = Payload {
+        msg: sealed,
+        aad: header,
+    };
+    ChaCha20Poly1305::new(key.into())
+        .decrypt(nonce.into(), payload)
+        .map_err(|_| CryptoError::AuthenticationFailed)
+}
+
+/// Parses a key given as 32 raw bytes or 64 hexadecimal digits.
+fn parse_key(bytes: &[u8]) -> Result<Key, CryptoError> {
+    if let Ok(key) = bytes.try_into() {
+        return Ok(key);
+    }
+    let hex = std::str::from_utf8(bytes)
+        .map_err(|_| CryptoError::InvalidKey)?
+        .trim();
+    if hex.len() != 64 || !hex.is_ascii() {
+        return Err(CryptoError::InvalidKey);
+    }
+    let mut key = [0u8; 32];
+    for (i, byte) in key.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).map_err(|_| CryptoError::InvalidKey)?;
+    }
+    Ok(key)
+}
+
+/// Reads the key from `TRAJECTORY_KEY` or `TRAJECTORY_KEY_FILE`, if either is set.
+pub fn key_from_env() -> Result<Option<Key>, CryptoError> {
+    if let Some(hex) = std::env::var_os(KEY_ENV) {
+        return parse_key(hex.as_encoded_bytes()).map(Some);
+    }
+    match std::env::var_os(KEY_FILE_ENV) {
+        Some(path) => parse_key(&std::fs::read(path)?).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Generates a random key, formatted in hex.
+pub fn generate_key() -> String {
+    let mut key = [0u8; 32];
+    OsRng.fill_bytes(&mut key);
+    key.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn key(start: u8) -> Key {
+        std::array::from_fn(|i| start + i as u8)
+    }
+
+    #[test]
+    fn test_decrypt_format() {
+        // Written with key(1) and an all-zero nonce: files written before keep decrypting
+        let encrypted = from_hex(
+            "54524a450100000000000000000000000050b3f92f4bf3980fb5784962f1c7c5aa\
+             bbff49dab0ea19e9768c",
+        );
+        assert_eq!(decrypt(&key(1), &encrypted).unwrap(), b"39.9,116.3");
+        assert_eq!(
+            encrypt_with_nonce(&key(1), &[0; NONCE_LEN], b"39.9,116.3"),
+            encrypted
+        );
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let plaintext: Vec<u8> = (0..1000).map(|i| (i * 7) as u8).collect();
+        let encrypted = encrypt(&key(1), &plaintext);
+
+        assert!(is_encrypted(&encrypted));
+        assert_eq!(encrypted.len(), HEADER_LEN + plaintext.len() + TAG_LEN);
+        assert_eq!(decrypt(&key(1), &encrypted).unwrap(), plaintext);
+        // Nonces are random, so the same plaintext never encrypts the same way twice
+        assert_ne!(encrypt(&key(1), &plaintext), encrypted);
+    }
+
+    #[test]
+    fn test_decrypt_rejects_tampering() {
+        let encrypted = encrypt_with_nonce(&key(1), &[0; NONCE_LEN], b"39.9,116.3");
+
+        assert!(matches!(
+            decrypt(&key(2), &encrypted),
+            Err(CryptoError::AuthenticationFailed)
+        ));
+        for index in [MAGIC.len() + 1, HEADER_LEN, encrypted.len() - 1] {
+            let mut modified = encrypted.clone();
+            modified[index] ^= 1;
+            assert!(matches!(
+                decrypt(&key(1), &modified),
+                Err(CryptoError::AuthenticationFailed)
+            ));
+        }
+        assert!(matches!(
+            decrypt(&key(1), &encrypted[..HEADER_LEN + TAG_LEN - 1]),
+            Err(CryptoError::Truncated)
+        ));
+        assert!(matches!(
+            decrypt(&key(1), b"39.9,116.3"),
+            Err(CryptoError::NotEncrypted)
+        ));
+    }
+
+    #[test]
+    fn test_parse_key() {
+        let hex = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f\n";
+        assert_eq!(parse_key(hex.as_bytes()).unwrap(), key(0));
+        assert_eq!(parse_key(&key(5)).unwrap(), key(5));
+        assert!(matches!(parse_key(b"0011"), Err(CryptoError::InvalidKey)));
+        assert!(matches!(parse_key(&[b'g'; 64]), Err(CryptoError::InvalidKey)));
+        assert_eq!(parse_key(generate_key().as_bytes()).unwrap().len(), 32);
+    }
+}
@@ -0,0 +1,475 @@
+//! Discovery and loading of GeoLife `.plt` files, and of the other formats of `formats`.
+//!
+//! Files may be gzip-compressed (`.plt.gz`) or stored in zip archives, which are read
+//! without extracting them. A file inside an archive is addressed by the path of the
+//! archive followed by its path in the archive, e.g. `geolife.zip/Data/000/Trajectory/x.plt`.
+//!
+//! Datasets are read and parsed in parallel, one file per task, with the `rayon` feature.
+//! Every file is read even when one fails, to report all the files that cannot be read.
+
+use crate::collection::TrajectoryCollection;
+use crate::compression::{self, ZipArchive, MAX_DECOMPRESSED_BYTES};
+use crate::diagnostics::Diagnostics;
+use crate::formats::Format;
+use crate::labels::{parse_labels_file, ModeLabel};
+use crate::point::{parse_local_csv, Point};
+use crate::trajectory::{CoordinateSystem, Trajectory};
+use crate::AppError;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use thiserror::Error;
+
+/// Files of a dataset that could not be read, with the error of each, in the order of the
+/// files.
+#[derive(Error, Debug)]
+#[error("{} files could not be read:{}", .0.len(), list(.0))]
+pub struct FileErrors(pub Vec<(PathBuf, AppError)>);
+
+/// One line per file and its error.
+fn list(errors: &[(PathBuf, AppError)]) -> String {
+    errors
+        .iter()
+        .map(|(path, error)| format!("\n  {}: {error}", path.display()))
+        .collect()
+}
+
+/// Zip archive opened once for all its entries
+type SharedZipArchive = Arc<Mutex<ZipArchive<io::BufReader<fs::File>>>>;
+
+/// Zip archives hold thousands of files, whose central directory is read only once.
+static ZIP_ARCHIVES: OnceLock<Mutex<HashMap<PathBuf, SharedZipArchive>>> = OnceLock::new();
+
+/// Returns the zip archive at `path`, opening it the first time.
+fn zip_archive(path: &Path) -> Result<SharedZipArchive, AppError> {
+    let archives = ZIP_ARCHIVES.get_or_init(Default::default);
+    if let Some(archive) = archives.lock().unwrap().get(path) {
+        return Ok(archive.clone());
+    }
+
+    let reader = io::BufReader::new(fs::File::open(path)?);
+    let archive = Arc::new(Mutex::new(ZipArchive::new(reader)?));
+    archives
+        .lock()
+        .unwrap()
+        .insert(path.to_path_buf(), archive.clone());
+    Ok(archive)
+}
+
+fn is_zip(path: &Path) -> bool {
+    path.extension().and_then(|s| s.to_str()) == Some("zip")
+}
+
+fn is_gzip(path: &Path) -> bool {
+    path.extension().and_then(|s| s.to_str()) == Some("gz")
+}
+
+/// Returns the path without its `.gz` extension, if any.
+fn without_gz(path: &Path) -> &Path {
+    if is_gzip(path) {
+        path.file_stem().map_or(path, Path::new)
+    } else {
+        path
+    }
+}
+
+/// Recursively finds every file under `dir` accepted by `matches`, including the files
+/// of zip archives.
+///
+/// Files are sorted by path, as `fs::read_dir` order varies between platforms and
+/// file systems and would otherwise change the output of runs on the same dataset.
+fn find_files(dir: &Path, matches: &impl Fn(&Path) -> bool) -> Result<Vec<PathBuf>, AppError> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            files.extend(find_files(&path, matches)?);
+        } else if is_zip(&path) {
+            for name in zip_archive(&path)?.lock().unwrap().entries() {
+                let file = path.join(name);
+                if matches(&file) {
+                    files.push(file);
+                }
+            }
+        } else if matches(&path) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Recursively finds every `.plt` or `.plt.gz` file under `dir`, sorted by path.
+pub fn find_plt_files(dir: &Path) -> Result<Vec<PathBuf>, AppError> {
+    find_files(dir, &|path| {
+        without_gz(path).extension().and_then(|s| s.to_str()) == Some("plt")
+    })
+}
+
+/// Recursively finds every GeoLife `.plt` or GPX `.gpx` track file under `dir`, compressed
+/// or not, sorted by path.
+pub fn find_track_files(dir: &Path) -> Result<Vec<PathBuf>, AppError> {
+    find_files(dir, &|path| {
+        matches!(Format::detect(path), Some(Format::Plt | Format::Gpx))
+    })
+}
+
+/// Recursively finds every file under `dir` in a format that can be imported, sorted by
+/// path.
+pub fn find_import_files(dir: &Path) -> Result<Vec<PathBuf>, AppError> {
+    find_files(dir, &|path| Format::detect(path).is_some())
+}
+
+/// Opens a file found by `find_files` or the functions built on it: a track, import or
+/// label file, gzip-compressed or not, or an entry of a `.zip` archive, decompressing it if
+/// needed.
+///
+/// # Returns
+///
+/// A reader of the decompressed content and its size in bytes.
+pub fn open(path: &Path) -> Result<(Box<dyn BufRead + Send>, u64), AppError> {
+    let data = if path.is_file() {
+        if !is_gzip(path) {
+            let size = fs::metadata(path)?.len();
+            return Ok((Box::new(io::BufReader::new(fs::File::open(path)?)), size));
+        }
+        fs::read(path)?
+    } else {
+        let archive = path
+            .ancestors()
+            .skip(1)
+            .find(|ancestor| is_zip(ancestor) && ancestor.is_file())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, path.display().to_string()))?;
+        let name = path
+            .strip_prefix(archive)
+            .unwrap_or(path)
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        zip_archive(archive)?
+            .lock()
+            .unwrap()
+            .read(&name, MAX_DECOMPRESSED_BYTES)?
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, path.display().to_string()))?
+    };
+
+    let data = if is_gzip(path) {
+        compression::gunzip(&data, MAX_DECOMPRESSED_BYTES)?
+    } else {
+        data
+    };
+    let size = data.len() as u64;
+    Ok((Box::new(io::Cursor::new(data)), size))
+}
+
+/// Reads the transportation mode labels of every `labels.txt` file under `dir`.
+pub fn load_labels(dir: &Path) -> Result<Vec<ModeLabel>, AppError> {
+    let mut labels = Vec::new();
+    for path in find_files(dir, &|path| {
+        without_gz(path).file_name() == Some("labels.txt".as_ref())
+    })? {
+        let (reader, _) = open(&path)?;
+        labels.extend(parse_labels_file(reader)?);
+    }
+    Ok(labels)
+}
+
+/// Reads the files into a single trajectory sorted by timestamp.
+///
+/// The format of each file is detected from its extension (see `Format::detect`), GeoLife
+/// `.plt` when unknown. Points sharing a timestamp keep the order of `files`.
+///
+/// # Returns
+///
+/// The trajectory and the total size in bytes of the files read, once decompressed.
+pub fn load_trajectory(files: &[PathBuf]) -> Result<(Trajectory, u64), AppError> {
+    load(files, None)
+}
+
+/// Reads the files into a single trajectory like `load_trajectory`, checking each file with
+/// `diagnostics` as read, before the points are sorted. `Diagnostics::finish` checks the
+/// merged trajectory.
+pub fn load_trajectory_diagnosed(
+    files: &[PathBuf],
+    diagnostics: &mut Diagnostics,
+) -> Result<(Trajectory, u64), AppError> {
+    load(files, Some(diagnostics))
+}
+
+/// Reads and parses each file, in parallel with the `rayon` feature.
+///
+/// # Returns
+///
+/// The points of each file and its size in bytes once decompressed, in the order of
+/// `files`, or the error of every file that could not be read.
+fn parse_files(files: &[PathBuf]) -> Result<Vec<(Vec<Point>, u64)>, FileErrors> {
+    let parse = |path: &PathBuf| -> Result<(Vec<Point>, u64), AppError> {
+        let (reader, file_size) = open(path)?;
+        let format = Format::detect(path).unwrap_or(Format::Plt);
+        Ok((format.parse(reader)?, file_size))
+    };
+    #[cfg(feature = "rayon")]
+    let results: Vec<_> = {
+        use rayon::prelude::*;
+        files.par_iter().map(parse).collect()
+    };
+    #[cfg(not(feature = "rayon"))]
+    let results: Vec<_> = files.iter().map(parse).collect();
+
+    let mut parsed = Vec::with_capacity(files.len());
+    let mut errors = Vec::new();
+    for (path, result) in files.iter().zip(results) {
+        match result {
+            Ok(file) => parsed.push(file),
+            Err(error) => errors.push((path.clone(), error)),
+        }
+    }
+    if errors.is_empty() {
+        Ok(parsed)
+    } else {
+        Err(FileErrors(errors))
+    }
+}
+
+fn load(
+    files: &[PathBuf],
+    mut diagnostics: Option<&mut Diagnostics>,
+) -> Result<(Trajectory, u64), AppError> {
+    let mut total_size = 0;
+    let mut all_points = Vec::new();
+
+    for (path, (points, file_size)) in files.iter().zip(parse_files(files)?) {
+        total_size += file_size;
+        if let Some(diagnostics) = diagnostics.as_mut() {
+            let trajectory = Trajectory::new(points.clone());
+            diagnostics.check_file(&path.display().to_string(), &trajectory);
+        }
+        all_points.extend(points);
+    }
+
+    // Sort all points by timestamp
+    all_points.sort_by_key(|p| p.datetime);
+
+    Ok((Trajectory::new(all_points), total_size))
+}
+
+/// Reads each file into its own trajectory sorted by timestamp, in the order of `files`.
+///
+/// # Returns
+///
+/// The trajectories and the total size in bytes of the files read, once decompressed.
+pub fn load_collection(files: &[PathBuf]) -> Result<(TrajectoryCollection, u64), AppError> {
+    load_files(files, None)
+}
+
+/// Reads each file into its own trajectory like `load_collection`, checking each file with
+/// `diagnostics` as read, before its points are sorted. `Diagnostics::finish` checks
+/// `TrajectoryCollection::merged`.
+pub fn load_collection_diagnosed(
+    files: &[PathBuf],
+    diagnostics: &mut Diagnostics,
+) -> Result<(TrajectoryCollection, u64), AppError> {
+    load_files(files, Some(diagnostics))
+}
+
+fn load_files(
+    files: &[PathBuf],
+    mut diagnostics: Option<&mut Diagnostics>,
+) -> Result<(TrajectoryCollection, u64), AppError> {
+    let mut total_size = 0;
+    let mut trajectories = Vec::with_capacity(files.len());
+    for (path, (mut points, file_size)) in files.iter().zip(parse_files(files)?) {
+        total_size += file_size;
+        if let Some(diagnostics) = diagnostics.as_mut() {
+            let trajectory = Trajectory::new(points.clone());
+            diagnostics.check_file(&path.display().to_string(), &trajectory);
+        }
+        points.sort_by_key(|p| p.datetime);
+        trajectories.push(Trajectory::new(points));
+    }
+    Ok((TrajectoryCollection::new(trajectories), total_size))
+}
+
+/// Reads a CSV trace in local coordinates (see `parse_local_csv`), scaled by 10^`scale`
+/// units per meter and sorted by timestamp.
+pub fn load_local_trace(path: &Path, scale: u32) -> Result<Trajectory, AppError> {
+    let (reader, _) = open(path)?;
+    let mut points = parse_local_csv(reader)?;
+    points.sort_by_key(|p| p.datetime);
+    Ok(Trajectory::with_coordinates(
+        points,
+        CoordinateSystem::Local { scale },
+    ))
+}
+
+/// Returns the GeoLife user a file belongs to.
+///
+/// GeoLife stores files as `<user>/Trajectory/<start time>.plt`; for other layouts the
+/// name of the directory containing the file is used.
+pub fn user_of(path: &Path) -> Option<&str> {
+    let parent = path.parent()?;
+    let dir = if parent.file_name()? == "Trajectory" {
+        parent.parent()?
+    } else {
+        parent
+    };
+    dir.file_name()?.to_str()
+}
+
+/// Id of the trajectory of a file in a store: its user and the name of the file without
+/// extensions, e.g. `000-20081023025304`, with any character other than ASCII letters,
+/// digits and `-` replaced by `_`.
+pub fn trajectory_id(file: &Path) -> String {
+    let name = file
+        .file_name()
+        .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+    let stem = name.split('.').next().unwrap_or_default();
+    let id = match user_of(file) {
+        Some(user) => format!("{user}-{stem}"),
+        None => stem.to_string(),
+    };
+    id.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_plt_files_sorted() {
+        let dir = tempfile::tempdir().unwrap();
+        for user in ["010", "002", "001"] {
+            let trajectory_dir = dir.path().join(user).join("Trajectory");
+            fs::create_dir_all(&trajectory_dir).unwrap();
+            for name in ["20090101.plt", "20080101.plt", "notes.txt"] {
+                fs::write(trajectory_dir.join(name), "").unwrap();
+            }
+        }
+
+        let files: Vec<String> = find_plt_files(dir.path())
+            .unwrap()
+            .iter()
+            .map(|path| {
+                let path = path.strip_prefix(dir.path()).unwrap();
+                path.to_string_lossy().replace('\\', "/")
+            })
+            .collect();
+        assert_eq!(
+            files,
+            vec![
+                "001/Trajectory/20080101.plt",
+                "001/Trajectory/20090101.plt",
+                "002/Trajectory/20080101.plt",
+                "002/Trajectory/20090101.plt",
+                "010/Trajectory/20080101.plt",
+                "010/Trajectory/20090101.plt",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compressed_files() {
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+        let dir = fixtures.join("compressed");
+        let files = find_plt_files(&dir).unwrap();
+
+        assert_eq!(
+            files,
+            vec![
+                dir.join("20081023125304.plt.gz"),
+                dir.join("geolife.zip/Data/000/Trajectory/20081023025304.plt"),
+                dir.join("geolife.zip/Data/000/Trajectory/20081023075304.plt"),
+            ]
+        );
+        assert_eq!(user_of(&files[1]), Some("000"));
+
+        let plain = find_plt_files(&fixtures.join("geolife")).unwrap();
+        assert_eq!(load_trajectory(&files).unwrap(), load_trajectory(&plain).unwrap());
+    }
+
+    #[test]
+    fn test_track_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let plt = "Geolife trajectory\nWGS 84\nAltitude is in Feet\nReserved 3\n\
+                   0,2,255,My Track,0,0,2,8421376\n0\n\
+                   39.98,116.31,0,100,39744.1201851852,2008-10-23,02:53:04\n";
+        let gpx = r#"<gpx><trk><trkseg>
+            <trkpt lat="39.99" lon="116.32"><ele>50</ele><time>2008-10-23T02:53:00Z</time></trkpt>
+            </trkseg></trk></gpx>"#;
+        fs::write(dir.path().join("a.plt"), plt).unwrap();
+        fs::write(dir.path().join("b.gpx"), gpx).unwrap();
+        fs::write(dir.path().join("c.csv"), "lat,lon,time\n").unwrap();
+
+        let files = find_track_files(dir.path()).unwrap();
+        assert_eq!(files, [dir.path().join("a.plt"), dir.path().join("b.gpx")]);
+        // Sorted by time, the GPX point first, with both altitudes in millimeters
+        let (trajectory, _) = load_trajectory(&files).unwrap();
+        assert_eq!(trajectory.latitudes, [39_990_000, 39_980_000]);
+        assert_eq!(trajectory.altitudes, [50_000, 30_480]);
+    }
+
+    #[test]
+    fn test_file_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let header = "Geolife trajectory\nWGS 84\nAltitude is in Feet\nReserved 3\n\
+                      0,2,255,My Track,0,0,2,8421376\n0\n";
+        let point = "39.98,116.31,0,100,39744.1201851852,2008-10-23,02:53:04\n";
+        fs::write(dir.path().join("a.plt"), format!("{header}{point}")).unwrap();
+        fs::write(dir.path().join("b.plt"), format!("{header}not,a,point\n")).unwrap();
+        let files = [
+            dir.path().join("a.plt"),
+            dir.path().join("missing.plt"),
+            dir.path().join("b.plt"),
+        ];
+
+        let Err(AppError::Files(errors)) = load_collection(&files) else {
+            panic!("expected file errors");
+        };
+        assert_eq!(errors.0.len(), 2);
+        assert_eq!(errors.0[0].0, files[1]);
+        assert!(matches!(errors.0[0].1, AppError::Io(_)));
+        assert_eq!(errors.0[1].0, files[2]);
+        assert!(matches!(errors.0[1].1, AppError::Parse(_)));
+        let message = errors.to_string();
+        assert!(message.starts_with("2 files could not be read:"));
+        assert!(message.contains("missing.plt: IO error"));
+
+        let (collection, _) = load_collection(&files[..1]).unwrap();
+        assert_eq!(collection.points(), 1);
+    }
+
+    #[test]
+    fn test_open_missing() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/compressed");
+        assert!(matches!(
+            open(&dir.join("geolife.zip/Data/missing.plt")),
+            Err(AppError::Io(_))
+        ));
+        assert!(matches!(open(&dir.join("missing.plt")), Err(AppError::Io(_))));
+    }
+
+    #[test]
+    fn test_user_of() {
+        assert_eq!(user_of(Path::new("geolife/042/Trajectory/1.plt")), Some("042"));
+        assert_eq!(user_of(Path::new("data/alice/1.plt")), Some("alice"));
+    }
+
+    #[test]
+    fn test_trajectory_id() {
+        let id = trajectory_id(Path::new("geolife/000/Trajectory/20081023025304.plt.gz"));
+        assert_eq!(id, "000-20081023025304");
+        assert_eq!(trajectory_id(Path::new("data/bob smith/a_b.gpx")), "bob_smith-a_b");
+        assert_eq!(trajectory_id(Path::new("1.plt")), "1");
+    }
+}
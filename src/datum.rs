@@ -0,0 +1,264 @@
+//! Conversions between WGS 84 and the datums of Chinese map providers, so that trajectories
+//! recorded by devices or apps using them align with OpenStreetMap basemaps.
+//!
+//! GCJ-02 is WGS 84 shifted by a non-linear offset of a few hundred meters inside China,
+//! and is used by Chinese map services and apps. BD-09 is GCJ-02 shifted again, used by
+//! Baidu. The GCJ-02 offset has no closed-form inverse, so it is inverted iteratively, to
+//! well below the precision of the scaled coordinates. Outside China, GCJ-02 is WGS 84.
+
+use crate::geo::{to_degrees, UNITS_PER_DEGREE};
+use crate::trajectory::{CoordinateSystem, Trajectory};
+use std::f64::consts::PI;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+#[error("Unknown datum: {0} (expected one of wgs84, gcj02, bd09)")]
+pub struct UnknownDatum(pub String);
+
+/// Semi-major axis in meters of the Krasovsky 1940 ellipsoid used by GCJ-02
+const KRASOVSKY_A: f64 = 6_378_245.0;
+
+/// Square of the eccentricity of the Krasovsky 1940 ellipsoid
+const KRASOVSKY_EE: f64 = 0.006_693_421_622_965_943;
+
+/// Angle factor of the BD-09 shift
+const BD_X_PI: f64 = PI * 3000.0 / 180.0;
+
+/// Largest difference in degrees between the GCJ-02 position of the inverse and the
+/// position inverted, about 0.1 mm
+const INVERSE_TOLERANCE_DEG: f64 = 1e-9;
+
+/// Most iterations of the inverse of the GCJ-02 offset, which converges in a few
+const INVERSE_ITERATIONS: usize = 30;
+
+/// Geodetic datum of the coordinates of a trajectory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Datum {
+    Wgs84,
+    /// The datum of Chinese map services
+    Gcj02,
+    /// The datum of Baidu Maps
+    Bd09,
+}
+
+impl Datum {
+    pub const ALL: [Datum; 3] = [Datum::Wgs84, Datum::Gcj02, Datum::Bd09];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Datum::Wgs84 => "wgs84",
+            Datum::Gcj02 => "gcj02",
+            Datum::Bd09 => "bd09",
+        }
+    }
+}
+
+impl FromStr for Datum {
+    type Err = UnknownDatum;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Datum::ALL
+            .into_iter()
+            .find(|datum| datum.name() == s)
+            .ok_or_else(|| UnknownDatum(s.to_string()))
+    }
+}
+
+/// Whether GCJ-02 leaves the point unshifted, outside the rough bounding box of China.
+fn outside_china(latitude: f64, longitude: f64) -> bool {
+    !(72.004..=137.8347).contains(&longitude) || !(0.8293..=55.8271).contains(&latitude)
+}
+
+fn shift_latitude(x: f64, y: f64) -> f64 {
+    -100.0
+        + 2.0 * x
+        + 3.0 * y
+        + 0.2 * y * y
+        + 0.1 * x * y
+        + 0.2 * x.abs().sqrt()
+        + (20.0 * (6.0 * x * PI).sin() + 20.0 * (2.0 * x * PI).sin()) * 2.0 / 3.0
+        + (20.0 * (y * PI).sin() + 40.0 * (y / 3.0 * PI).sin()) * 2.0 / 3.0
+        + (160.0 * (y / 12.0 * PI).sin() + 320.0 * (y * PI / 30.0).sin()) * 2.0 / 3.0
+}
+
+fn shift_longitude(x: f64, y: f64) -> f64 {
+    300.0
+        + x
+        + 2.0 * y
+        + 0.1 * x * x
+        + 0.1 * x * y
+        + 0.1 * x.abs().sqrt()
+        + (20.0 * (6.0 * x * PI).sin() + 20.0 * (2.0 * x * PI).sin()) * 2.0 / 3.0
+        + (20.0 * (x * PI).sin() + 40.0 * (x / 3.0 * PI).sin()) * 2.0 / 3.0
+        + (150.0 * (x / 12.0 * PI).sin() + 300.0 * (x / 30.0 * PI).sin()) * 2.0 / 3.0
+}
+
+/// Converts a WGS 84 position in degrees to GCJ-02.
+pub fn wgs84_to_gcj02(latitude: f64, longitude: f64) -> (f64, f64) {
+    if outside_china(latitude, longitude) {
+        return (latitude, longitude);
+    }
+    let d_latitude = shift_latitude(longitude - 105.0, latitude - 35.0);
+    let d_longitude = shift_longitude(longitude - 105.0, latitude - 35.0);
+    let rad_latitude = latitude.to_radians();
+    let magic = 1.0 - KRASOVSKY_EE * rad_latitude.sin().powi(2);
+    let sqrt_magic = magic.sqrt();
+    (
+        latitude
+            + d_latitude * 180.0 / (KRASOVSKY_A * (1.0 - KRASOVSKY_EE) / (magic * sqrt_magic) * PI),
+        longitude + d_longitude * 180.0 / (KRASOVSKY_A / sqrt_magic * rad_latitude.cos() * PI),
+    )
+}
+
+/// Converts a GCJ-02 position in degrees to WGS 84, inverting `wgs84_to_gcj02` by fixed
+/// point iteration.
+pub fn gcj02_to_wgs84(latitude: f64, longitude: f64) -> (f64, f64) {
+    let (mut wgs_latitude, mut wgs_longitude) = (latitude, longitude);
+    for _ in 0..INVERSE_ITERATIONS {
+        let (gcj_latitude, gcj_longitude) = wgs84_to_gcj02(wgs_latitude, wgs_longitude);
+        let (d_latitude, d_longitude) = (latitude - gcj_latitude, longitude - gcj_longitude);
+        if d_latitude.abs() < INVERSE_TOLERANCE_DEG && d_longitude.abs() < INVERSE_TOLERANCE_DEG {
+            break;
+        }
+        wgs_latitude += d_latitude;
+        wgs_longitude += d_longitude;
+    }
+    (wgs_latitude, wgs_longitude)
+}
+
+/// Converts a GCJ-02 position in degrees to BD-09.
+pub fn gcj02_to_bd09(latitude: f64, longitude: f64) -> (f64, f64) {
+    let (x, y) = (longitude, latitude);
+    let z = (x * x + y * y).sqrt() + 0.00002 * (y * BD_X_PI).sin();
+    let theta = y.atan2(x) + 0.000003 * (x * BD_X_PI).cos();
+    (z * theta.sin() + 0.006, z * theta.cos() + 0.0065)
+}
+
+/// Converts a BD-09 position in degrees to GCJ-02.
+pub fn bd09_to_gcj02(latitude: f64, longitude: f64) -> (f64, f64) {
+    let (x, y) = (longitude - 0.0065, latitude - 0.006);
+    let z = (x * x + y * y).sqrt() - 0.00002 * (y * BD_X_PI).sin();
+    let theta = y.atan2(x) - 0.000003 * (x * BD_X_PI).cos();
+    (z * theta.sin(), z * theta.cos())
+}
+
+/// Converts a position in degrees between two datums, through GCJ-02 between WGS 84 and
+/// BD-09.
+pub fn convert(latitude: f64, longitude: f64, from: Datum, to: Datum) -> (f64, f64) {
+    let (latitude, longitude) = match from {
+        Datum::Wgs84 if to == Datum::Wgs84 => return (latitude, longitude),
+        Datum::Wgs84 => wgs84_to_gcj02(latitude, longitude),
+        Datum::Gcj02 => (latitude, longitude),
+        Datum::Bd09 => bd09_to_gcj02(latitude, longitude),
+    };
+    match to {
+        Datum::Wgs84 => gcj02_to_wgs84(latitude, longitude),
+        Datum::Gcj02 => (latitude, longitude),
+        Datum::Bd09 => gcj02_to_bd09(latitude, longitude),
+    }
+}
+
+/// Converts the points of a trajectory between two datums, rounding to the nearest scaled
+/// unit. Timestamps and altitudes are kept.
+///
+/// # Panics
+///
+/// This function will panic if the trajectory is not in WGS 84 coordinates, the system of
+/// latitudes and longitudes whatever their datum.
+pub fn transform(mut trajectory: Trajectory, from: Datum, to: Datum) -> Trajectory {
+    assert_eq!(
+        trajectory.coordinates,
+        CoordinateSystem::Wgs84,
+        "datums apply to latitudes and longitudes"
+    );
+    if from == to {
+        return trajectory;
+    }
+    for (latitude, longitude) in trajectory
+        .latitudes
+        .iter_mut()
+        .zip(trajectory.longitudes.iter_mut())
+    {
+        let (converted_latitude, converted_longitude) =
+            convert(to_degrees(*latitude), to_degrees(*longitude), from, to);
+        *latitude = (converted_latitude * UNITS_PER_DEGREE).round() as i64;
+        *longitude = (converted_longitude * UNITS_PER_DEGREE).round() as i64;
+    }
+    trajectory
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geo::haversine_m;
+
+    fn distance_m((lat1, lon1): (f64, f64), (lat2, lon2): (f64, f64)) -> f64 {
+        let units = |degrees: f64| (degrees * UNITS_PER_DEGREE).round() as i64;
+        haversine_m(units(lat1), units(lon1), units(lat2), units(lon2))
+    }
+
+    #[test]
+    fn test_gcj02() {
+        // Tiananmen: shifted a few hundred meters north-east
+        let wgs84 = (39.908_72, 116.397_48);
+        let gcj02 = wgs84_to_gcj02(wgs84.0, wgs84.1);
+        assert!(gcj02.0 > wgs84.0 && gcj02.1 > wgs84.1);
+        let shift_m = distance_m(wgs84, gcj02);
+        assert!((400.0..700.0).contains(&shift_m), "{shift_m}");
+
+        let inverse = gcj02_to_wgs84(gcj02.0, gcj02.1);
+        assert!(distance_m(inverse, wgs84) < 0.01);
+
+        // Paris is not shifted
+        assert_eq!(wgs84_to_gcj02(48.85, 2.35), (48.85, 2.35));
+        assert_eq!(gcj02_to_wgs84(48.85, 2.35), (48.85, 2.35));
+    }
+
+    #[test]
+    fn test_bd09() {
+        let gcj02 = (39.910_12, 116.403_73);
+        let bd09 = gcj02_to_bd09(gcj02.0, gcj02.1);
+        let shift_m = distance_m(gcj02, bd09);
+        assert!((500.0..1_200.0).contains(&shift_m), "{shift_m}");
+        assert!(distance_m(bd09_to_gcj02(bd09.0, bd09.1), gcj02) < 0.5);
+    }
+
+    #[test]
+    fn test_convert() {
+        let wgs84 = (31.230_4, 121.473_7);
+        for from in Datum::ALL {
+            for to in Datum::ALL {
+                let (latitude, longitude) = convert(wgs84.0, wgs84.1, Datum::Wgs84, from);
+                let converted = convert(latitude, longitude, from, to);
+                let expected = convert(wgs84.0, wgs84.1, Datum::Wgs84, to);
+                assert!(distance_m(converted, expected) < 0.5, "{from:?} {to:?}");
+            }
+        }
+        assert_eq!("gcj02".parse::<Datum>().unwrap(), Datum::Gcj02);
+        assert!("etrs89".parse::<Datum>().is_err());
+    }
+
+    #[test]
+    fn test_transform() {
+        let trajectory = Trajectory {
+            latitudes: vec![39_908_720, 39_910_000],
+            longitudes: vec![116_397_480, 116_400_000],
+            timestamps: vec![0, 10],
+            altitudes: vec![50_000, 51_000],
+            coordinates: CoordinateSystem::Wgs84,
+        };
+        let gcj02 = transform(trajectory.clone(), Datum::Wgs84, Datum::Gcj02);
+        assert_ne!(gcj02.latitudes, trajectory.latitudes);
+        assert_eq!(gcj02.timestamps, trajectory.timestamps);
+        assert_eq!(gcj02.altitudes, trajectory.altitudes);
+
+        let back = transform(gcj02, Datum::Gcj02, Datum::Wgs84);
+        for (a, b) in back.latitudes.iter().zip(&trajectory.latitudes) {
+            assert!((a - b).abs() <= 1);
+        }
+        for (a, b) in back.longitudes.iter().zip(&trajectory.longitudes) {
+            assert!((a - b).abs() <= 1);
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! Delta coding of numeric columns, shared by the serialization backends.
+//!
+//! Every value is replaced with its difference from the previous one, the first value with
+//! its difference from zero. Differences wrap around, so any column round-trips even when
+//! consecutive values are further apart than the type can represent.
+
+/// A number that can be delta-coded.
+pub trait Delta: Copy + Default {
+    /// `self - previous`, wrapping around on overflow
+    fn delta_from(self, previous: Self) -> Self;
+    /// `previous + self`, wrapping around on overflow
+    fn undelta_from(self, previous: Self) -> Self;
+}
+
+macro_rules! impl_delta {
+    ($($t:ty),*) => {
+        $(
+            impl Delta for $t {
+                fn delta_from(self, previous: Self) -> Self {
+                    self.wrapping_sub(previous)
+                }
+
+                fn undelta_from(self, previous: Self) -> Self {
+                    previous.wrapping_add(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_delta!(i32, i64, u32, u64);
+
+/// Replaces every value with its difference from the previous one.
+pub fn delta_encode<T: Delta>(values: impl IntoIterator<Item = T>) -> impl Iterator<Item = T> {
+    values.into_iter().scan(T::default(), |previous, value| {
+        let delta = value.delta_from(*previous);
+        *previous = value;
+        Some(delta)
+    })
+}
+
+/// Restores the values from their differences, the inverse of [`delta_encode`].
+pub fn delta_decode<T: Delta>(deltas: impl IntoIterator<Item = T>) -> impl Iterator<Item = T> {
+    deltas.into_iter().scan(T::default(), |previous, delta| {
+        *previous = delta.undelta_from(*previous);
+        Some(*previous)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delta_encode() {
+        let deltas: Vec<i64> = delta_encode([10, 12, 11, 11]).collect();
+        assert_eq!(deltas, vec![10, 2, -1, 0]);
+
+        let deltas: Vec<u64> = delta_encode([1000_u64, 1001, 1003]).collect();
+        assert_eq!(deltas, vec![1000, 1, 2]);
+    }
+
+    #[test]
+    fn test_delta_round_trip() {
+        let values = vec![i64::MIN, i64::MAX, 0, -5, i64::MAX];
+        let decoded: Vec<i64> = delta_decode(delta_encode(values.clone())).collect();
+        assert_eq!(decoded, values);
+
+        // Decreasing unsigned values wrap around and still round-trip
+        let values = vec![5_u32, 3, u32::MAX, 0];
+        let decoded: Vec<u32> = delta_decode(delta_encode(values.clone())).collect();
+        assert_eq!(decoded, values);
+    }
+
+    #[test]
+    fn test_delta_empty() {
+        assert_eq!(delta_encode(Vec::<i64>::new()).count(), 0);
+        assert_eq!(delta_decode(Vec::<i64>::new()).count(), 0);
+    }
+}
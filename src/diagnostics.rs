@@ -0,0 +1,343 @@
+//! Warnings about suspicious input data, printed with the report of a run so that data
+//! quality issues do not go unnoticed.
+//!
+//! Files are checked one at a time as they are read, in their own order, before being
+//! merged: for timestamps going back in time, for points reached and left at impossible
+//! speeds, and for files identical to a file read before. Once every file is read, the
+//! points are checked against the region of the dataset: the given one, or the box of the
+//! central 98% of the latitudes and longitudes widened by a margin.
+
+use crate::geo::{haversine_m, to_degrees, UNITS_PER_DEGREE};
+use crate::geohash::Bounds;
+use crate::trajectory::Trajectory;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// Fraction of the points left out on each side when deriving the region of the dataset
+const REGION_QUANTILE: f64 = 0.01;
+
+/// Thresholds of the diagnostics.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiagnosticParams {
+    /// Largest plausible speed in m/s; points reached and left faster are outliers
+    pub max_speed_mps: f64,
+    /// Largest fraction of outliers before warning
+    pub max_outlier_ratio: f64,
+    /// Region the points should be in, derived from the points when `None`
+    pub region: Option<Bounds>,
+    /// Margin in degrees added around the derived region
+    pub region_margin_deg: f64,
+}
+
+impl Default for DiagnosticParams {
+    fn default() -> Self {
+        DiagnosticParams {
+            // Faster than an airliner
+            max_speed_mps: 340.0,
+            max_outlier_ratio: 0.01,
+            region: None,
+            region_margin_deg: 1.0,
+        }
+    }
+}
+
+/// Suspicious pattern found in the input.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Warning {
+    /// More than `max_outlier_ratio` of the points are outliers
+    Outliers { points: usize, total: usize },
+    /// A timestamp of the file is earlier than the one before it
+    NonMonotonicTimestamps { file: String, index: usize },
+    /// Points lie outside the region of the dataset
+    OutsideRegion {
+        points: usize,
+        region: Bounds,
+        /// Files with points outside the region
+        files: Vec<String>,
+    },
+    /// The file holds the same points as a file read before
+    DuplicateFile { file: String, duplicate_of: String },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Warning::Outliers { points, total } => write!(
+                f,
+                "{points} of {total} points ({:.2} %) are outliers moving at impossible speeds",
+                *points as f64 / *total as f64 * 100.0
+            ),
+            Warning::NonMonotonicTimestamps { file, index } => write!(
+                f,
+                "{file}: timestamp at index {index} is earlier than the previous one"
+            ),
+            Warning::OutsideRegion {
+                points,
+                region,
+                files,
+            } => write!(
+                f,
+                "{points} points outside latitudes {:.3}..{:.3} and longitudes {:.3}..{:.3}, \
+                 in {} files such as {}",
+                region.min_latitude,
+                region.max_latitude,
+                region.min_longitude,
+                region.max_longitude,
+                files.len(),
+                files.first().map_or("", String::as_str)
+            ),
+            Warning::DuplicateFile { file, duplicate_of } => {
+                write!(f, "{file} holds the same points as {duplicate_of}")
+            }
+        }
+    }
+}
+
+/// Indices of the points reached from the previous point and left to the next one faster
+/// than `max_speed_mps`, in the order of the trajectory.
+pub fn outliers(trajectory: &Trajectory, max_speed_mps: f64) -> Vec<usize> {
+    let speed = |i: usize, j: usize| {
+        let distance_m = haversine_m(
+            trajectory.latitudes[i],
+            trajectory.longitudes[i],
+            trajectory.latitudes[j],
+            trajectory.longitudes[j],
+        );
+        match trajectory.timestamps[i].abs_diff(trajectory.timestamps[j]) {
+            0 if distance_m > 0.0 => f64::INFINITY,
+            0 => 0.0,
+            dt => distance_m / dt as f64,
+        }
+    };
+    (1..trajectory.timestamps.len().saturating_sub(1))
+        .filter(|&i| speed(i - 1, i) > max_speed_mps && speed(i, i + 1) > max_speed_mps)
+        .collect()
+}
+
+/// Value at the given fraction of the sorted values.
+fn quantile(values: &mut [i64], fraction: f64) -> i64 {
+    let index = ((values.len() - 1) as f64 * fraction).round() as usize;
+    *values.select_nth_unstable(index).1
+}
+
+/// Box of the central points widened by `margin_deg`, `None` without points.
+fn derive_region(trajectory: &Trajectory, margin_deg: f64) -> Option<Bounds> {
+    if trajectory.latitudes.is_empty() {
+        return None;
+    }
+    let (mut latitudes, mut longitudes) =
+        (trajectory.latitudes.clone(), trajectory.longitudes.clone());
+    Some(Bounds {
+        min_latitude: to_degrees(quantile(&mut latitudes, REGION_QUANTILE)) - margin_deg,
+        max_latitude: to_degrees(quantile(&mut latitudes, 1.0 - REGION_QUANTILE)) + margin_deg,
+        min_longitude: to_degrees(quantile(&mut longitudes, REGION_QUANTILE)) - margin_deg,
+        max_longitude: to_degrees(quantile(&mut longitudes, 1.0 - REGION_QUANTILE)) + margin_deg,
+    })
+}
+
+fn contains(region: &Bounds, latitude: i64, longitude: i64) -> bool {
+    let (latitude, longitude) = (
+        latitude as f64 / UNITS_PER_DEGREE,
+        longitude as f64 / UNITS_PER_DEGREE,
+    );
+    (region.min_latitude..=region.max_latitude).contains(&latitude)
+        && (region.min_longitude..=region.max_longitude).contains(&longitude)
+}
+
+/// Smallest and largest latitude and longitude of a file, to tell which files leave the
+/// region without keeping their points.
+type FileBox = (i64, i64, i64, i64);
+
+/// Checks of the files of a run, accumulating warnings.
+#[derive(Debug, Clone)]
+pub struct Diagnostics {
+    params: DiagnosticParams,
+    warnings: Vec<Warning>,
+    /// Name of the first file read with each hash of its points
+    fingerprints: HashMap<u64, String>,
+    /// Name and box of each file with points
+    boxes: Vec<(String, FileBox)>,
+    outliers: usize,
+    points: usize,
+}
+
+impl Diagnostics {
+    pub fn new(params: DiagnosticParams) -> Self {
+        Diagnostics {
+            params,
+            warnings: Vec::new(),
+            fingerprints: HashMap::new(),
+            boxes: Vec::new(),
+            outliers: 0,
+            points: 0,
+        }
+    }
+
+    /// Checks the points of one file, in the order of the file.
+    pub fn check_file(&mut self, name: &str, trajectory: &Trajectory) {
+        let timestamps = &trajectory.timestamps;
+        if let Some(index) = timestamps.windows(2).position(|pair| pair[1] < pair[0]) {
+            self.warnings.push(Warning::NonMonotonicTimestamps {
+                file: name.to_string(),
+                index: index + 1,
+            });
+        }
+        self.outliers += outliers(trajectory, self.params.max_speed_mps).len();
+        self.points += timestamps.len();
+        if timestamps.is_empty() {
+            return;
+        }
+
+        let mut hasher = DefaultHasher::new();
+        (&trajectory.latitudes, &trajectory.longitudes, timestamps).hash(&mut hasher);
+        let fingerprint = hasher.finish();
+        match self.fingerprints.get(&fingerprint) {
+            Some(first) => self.warnings.push(Warning::DuplicateFile {
+                file: name.to_string(),
+                duplicate_of: first.clone(),
+            }),
+            None => {
+                self.fingerprints.insert(fingerprint, name.to_string());
+            }
+        }
+
+        let min_max = |values: &[i64]| {
+            let min = values.iter().copied().min().unwrap_or_default();
+            (min, values.iter().copied().max().unwrap_or_default())
+        };
+        let (min_latitude, max_latitude) = min_max(&trajectory.latitudes);
+        let (min_longitude, max_longitude) = min_max(&trajectory.longitudes);
+        self.boxes.push((
+            name.to_string(),
+            (min_latitude, max_latitude, min_longitude, max_longitude),
+        ));
+    }
+
+    /// Checks the points of every file, merged into `trajectory`, against the region of
+    /// the dataset, and returns the warnings.
+    pub fn finish(mut self, trajectory: &Trajectory) -> Vec<Warning> {
+        if self.points > 0
+            && self.outliers as f64 > self.points as f64 * self.params.max_outlier_ratio
+        {
+            self.warnings.push(Warning::Outliers {
+                points: self.outliers,
+                total: self.points,
+            });
+        }
+
+        let region = self
+            .params
+            .region
+            .or_else(|| derive_region(trajectory, self.params.region_margin_deg));
+        if let Some(region) = region {
+            let points = (0..trajectory.latitudes.len())
+                .filter(|&i| !contains(&region, trajectory.latitudes[i], trajectory.longitudes[i]))
+                .count();
+            if points > 0 {
+                let files = self
+                    .boxes
+                    .into_iter()
+                    .filter(|(_, (min_lat, max_lat, min_lon, max_lon))| {
+                        !contains(&region, *min_lat, *min_lon)
+                            || !contains(&region, *max_lat, *max_lon)
+                    })
+                    .map(|(name, _)| name)
+                    .collect();
+                self.warnings.push(Warning::OutsideRegion {
+                    points,
+                    region,
+                    files,
+                });
+            }
+        }
+        self.warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trajectory::CoordinateSystem;
+
+    /// Points 100 m apart every 10 seconds, north from Beijing.
+    fn walk(len: usize, start: u64) -> Trajectory {
+        Trajectory {
+            latitudes: (0..len as i64).map(|i| 39_900_000 + i * 900).collect(),
+            longitudes: vec![116_400_000; len],
+            timestamps: (0..len as u64).map(|i| start + i * 10).collect(),
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        }
+    }
+
+    #[test]
+    fn test_outliers() {
+        let mut trajectory = walk(10, 0);
+        assert!(outliers(&trajectory, 340.0).is_empty());
+        // A fix 100 km away for one point
+        trajectory.latitudes[4] += 1_000_000;
+        assert_eq!(outliers(&trajectory, 340.0), [4]);
+        assert!(outliers(&walk(0, 0), 340.0).is_empty());
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let mut diagnostics = Diagnostics::new(DiagnosticParams::default());
+        let mut unsorted = walk(200, 1_000);
+        unsorted.timestamps.swap(3, 4);
+        let mut far = walk(200, 10_000);
+        far.latitudes[199] = 10_000_000;
+        diagnostics.check_file("a.plt", &walk(200, 0));
+        diagnostics.check_file("b.plt", &unsorted);
+        diagnostics.check_file("c.plt", &walk(200, 0));
+        diagnostics.check_file("d.plt", &far);
+
+        let mut merged = walk(200, 0);
+        merged.latitudes.extend(&far.latitudes);
+        merged.longitudes.extend(&far.longitudes);
+        merged.timestamps.extend(&far.timestamps);
+        let warnings = diagnostics.finish(&merged);
+        assert_eq!(
+            warnings[..2],
+            [
+                Warning::NonMonotonicTimestamps {
+                    file: "b.plt".to_string(),
+                    index: 4
+                },
+                Warning::DuplicateFile {
+                    file: "c.plt".to_string(),
+                    duplicate_of: "a.plt".to_string()
+                },
+            ]
+        );
+        assert!(matches!(
+            &warnings[2],
+            Warning::OutsideRegion { points: 1, files, .. } if files == &["d.plt"]
+        ));
+        assert_eq!(warnings.len(), 3);
+        assert!(warnings[1]
+            .to_string()
+            .contains("c.plt holds the same points as a.plt"));
+    }
+
+    #[test]
+    fn test_diagnostics_outliers() {
+        let mut diagnostics = Diagnostics::new(DiagnosticParams::default());
+        let mut trajectory = walk(50, 0);
+        trajectory.latitudes[10] += 1_000_000;
+        diagnostics.check_file("a.plt", &trajectory);
+        let warnings = diagnostics.finish(&trajectory);
+        assert_eq!(
+            warnings[0],
+            Warning::Outliers {
+                points: 1,
+                total: 50
+            }
+        );
+        assert!(warnings[0]
+            .to_string()
+            .starts_with("1 of 50 points (2.00 %)"));
+    }
+}
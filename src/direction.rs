@@ -0,0 +1,149 @@
+//! Comparison of the direction of travel of two trajectories.
+//!
+//! Used to recognize the same road driven in both directions, or the outbound and
+//! return legs of a commute.
+
+use crate::geo::to_degrees;
+use crate::trajectory::Trajectory;
+
+/// How a trajectory relates to another one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Both follow the same path in the same direction
+    Same,
+    /// Both follow the same path in opposite directions
+    Reversed,
+    /// The paths differ
+    Different,
+}
+
+/// Initial bearing in degrees, clockwise from north in `[0, 360)`, from the first to
+/// the last point of the trajectory.
+///
+/// Returns `None` for trajectories with less than two points.
+pub fn overall_bearing(trajectory: &Trajectory) -> Option<f64> {
+    let last = trajectory.latitudes.len().checked_sub(1).filter(|&last| last > 0)?;
+
+    let lat1 = to_degrees(trajectory.latitudes[0]).to_radians();
+    let lat2 = to_degrees(trajectory.latitudes[last]).to_radians();
+    let dlon = (to_degrees(trajectory.longitudes[last]) - to_degrees(trajectory.longitudes[0]))
+        .to_radians();
+
+    let y = dlon.sin() * lat2.cos();
+    let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * dlon.cos();
+    Some(y.atan2(x).to_degrees().rem_euclid(360.0))
+}
+
+/// Largest distance in meters from a point of `from` to the path of `to`.
+fn directed_hausdorff_m(from: &Trajectory, to: &Trajectory) -> f64 {
+    let point = |t: &Trajectory, i: usize| (t.latitudes[i], t.longitudes[i]);
+    let len = to.latitudes.len();
+
+    (0..from.latitudes.len())
+        .map(|i| {
+            let p = point(from, i);
+            if len == 1 {
+                return to.coordinates.distance_m(p, point(to, 0));
+            }
+            (1..len)
+                .map(|j| {
+                    to.coordinates
+                        .segment_distance_m(p, point(to, j - 1), point(to, j))
+                })
+                .fold(f64::INFINITY, f64::min)
+        })
+        .fold(0.0, f64::max)
+}
+
+/// Whether `a` and `b` start and end at the same places, and no point of either one is
+/// farther than `tolerance_m` from the path of the other.
+fn same_path(a: &Trajectory, b: &Trajectory, tolerance_m: f64) -> bool {
+    let endpoint_distance = |i: usize, j: usize| {
+        a.coordinates.distance_m(
+            (a.latitudes[i], a.longitudes[i]),
+            (b.latitudes[j], b.longitudes[j]),
+        )
+    };
+    let (last_a, last_b) = (a.latitudes.len() - 1, b.latitudes.len() - 1);
+
+    endpoint_distance(0, 0) <= tolerance_m
+        && endpoint_distance(last_a, last_b) <= tolerance_m
+        && directed_hausdorff_m(a, b) <= tolerance_m
+        && directed_hausdorff_m(b, a) <= tolerance_m
+}
+
+/// Compares the paths of two trajectories, with a tolerance in meters.
+///
+/// A closed loop compares as `Same` with its reverse, as both start and end at the
+/// same place. Trajectories in different coordinate systems are `Different`.
+pub fn compare(a: &Trajectory, b: &Trajectory, tolerance_m: f64) -> Direction {
+    if a.latitudes.is_empty() || b.latitudes.is_empty() || a.coordinates != b.coordinates {
+        return Direction::Different;
+    }
+
+    if same_path(a, b, tolerance_m) {
+        Direction::Same
+    } else if same_path(a, &b.clone().reverse(), tolerance_m) {
+        Direction::Reversed
+    } else {
+        Direction::Different
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trajectory::CoordinateSystem;
+
+    fn trajectory(points: &[(i64, i64)]) -> Trajectory {
+        Trajectory {
+            latitudes: points.iter().map(|p| p.0).collect(),
+            longitudes: points.iter().map(|p| p.1).collect(),
+            timestamps: (0..points.len() as u64).collect(),
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        }
+    }
+
+    #[test]
+    fn test_overall_bearing() {
+        let north = trajectory(&[(0, 0), (1000, 0)]);
+        let east = trajectory(&[(0, 0), (500, 500), (0, 1000)]);
+
+        assert!(overall_bearing(&north).unwrap().abs() < 1e-9);
+        assert!((overall_bearing(&east).unwrap() - 90.0).abs() < 1e-6);
+        assert!((overall_bearing(&north.reverse()).unwrap() - 180.0).abs() < 1e-9);
+        assert_eq!(overall_bearing(&trajectory(&[(0, 0)])), None);
+    }
+
+    #[test]
+    fn test_compare() {
+        let outbound = trajectory(&[(0, 0), (1000, 0), (1000, 1000), (2000, 1000)]);
+        // Same road, sampled at other places and with a few meters of noise
+        let back = trajectory(&[
+            (2000, 1010),
+            (1000, 990),
+            (1000, 500),
+            (1000, 0),
+            (500, 0),
+            (0, 0),
+        ]);
+        let other = trajectory(&[(0, 0), (0, 1000), (1000, 1000), (2000, 1000)]);
+
+        assert_eq!(compare(&outbound, &outbound, 5.0), Direction::Same);
+        assert_eq!(compare(&outbound, &back, 5.0), Direction::Reversed);
+        assert_eq!(compare(&back, &outbound, 5.0), Direction::Reversed);
+        assert_eq!(compare(&outbound, &other, 5.0), Direction::Different);
+        assert_eq!(compare(&outbound, &trajectory(&[]), 5.0), Direction::Different);
+    }
+
+    #[test]
+    fn test_compare_tolerance() {
+        let a = trajectory(&[(0, 0), (0, 1000)]);
+        // About 11 m away
+        let b = trajectory(&[(100, 1000), (100, 0)]);
+
+        assert_eq!(compare(&a, &b, 5.0), Direction::Different);
+        assert_eq!(compare(&a, &b, 15.0), Direction::Reversed);
+    }
+}
@@ -0,0 +1,94 @@
+//! Serialization backends supported for simplified trajectories.
+
+use crate::quality::Reconstruction;
+use crate::trajectory::{EncodeError, Trajectory};
+use crate::{binary, polyline};
+use prost::Message;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+#[error("Unknown encoding: {0} (expected one of proto, proto-delta, polyline, binary)")]
+pub struct UnknownEncoding(pub String);
+
+/// A serialization backend for trajectories.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    /// Protobuf message with absolute values
+    Proto,
+    /// Protobuf message with delta-encoded values
+    DeltaProto,
+    /// Encoded polyline (coordinates only)
+    Polyline,
+    /// Custom binary format with zigzag varint deltas
+    Binary,
+}
+
+impl Encoding {
+    /// All supported encodings
+    pub const ALL: [Encoding; 4] = [
+        Encoding::Proto,
+        Encoding::DeltaProto,
+        Encoding::Polyline,
+        Encoding::Binary,
+    ];
+
+    /// Short name of the encoding, as displayed in reports
+    pub fn name(self) -> &'static str {
+        match self {
+            Encoding::Proto => "proto",
+            Encoding::DeltaProto => "proto-delta",
+            Encoding::Polyline => "polyline",
+            Encoding::Binary => "binary",
+        }
+    }
+
+    /// File extension used when writing this encoding to disk
+    pub fn extension(self) -> &'static str {
+        match self {
+            Encoding::Proto => "pb",
+            Encoding::DeltaProto => "delta.pb",
+            Encoding::Polyline => "polyline",
+            Encoding::Binary => "trjb",
+        }
+    }
+
+    /// Encodes the trajectory with this backend.
+    pub fn encode(self, trajectory: &Trajectory) -> Result<Vec<u8>, EncodeError> {
+        Ok(match self {
+            Encoding::Proto => trajectory.clone().to_proto().encode_to_vec(),
+            Encoding::DeltaProto => trajectory.clone().to_delta_proto()?.encode_to_vec(),
+            Encoding::Polyline => {
+                polyline::encode(&trajectory.latitudes, &trajectory.longitudes).into_bytes()
+            }
+            Encoding::Binary => binary::encode(trajectory),
+        })
+    }
+
+    /// Encodes a simplified trajectory with this backend, along with its reconstruction
+    /// error for the backends that carry metadata: the protobuf ones.
+    pub fn encode_simplified(
+        self,
+        trajectory: &Trajectory,
+        reconstruction: Reconstruction,
+    ) -> Result<Vec<u8>, EncodeError> {
+        let mut message = match self {
+            Encoding::Proto => trajectory.clone().to_proto(),
+            Encoding::DeltaProto => trajectory.clone().to_delta_proto()?,
+            Encoding::Polyline | Encoding::Binary => return self.encode(trajectory),
+        };
+        message.reconstruction = Some(reconstruction.to_proto());
+        Ok(message.encode_to_vec())
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = UnknownEncoding;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Encoding::ALL
+            .into_iter()
+            .find(|encoding| encoding.name() == s)
+            .ok_or_else(|| UnknownEncoding(s.to_string()))
+    }
+}
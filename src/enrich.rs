@@ -0,0 +1,419 @@
+//! Batch HTTP requests to external services (map matching, geocoding, elevation, ...) with
+//! limits on concurrency and request rate, retries, and an on-disk cache of responses.
+//!
+//! Every definitive response (any status except 429 and 5xx) is written to the cache
+//! directory under a hash of the request, so an interrupted run resumes where it stopped
+//! and re-running on the same data sends no requests at all.
+
+use hyper::client::HttpConnector;
+use hyper::{Body, Client, Method};
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::runtime::Runtime;
+use tokio::sync::{Mutex, Semaphore};
+use tokio::task::JoinSet;
+use tokio::time::Instant;
+
+/// Time after which a request is abandoned
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Error, Debug)]
+pub enum EnrichError {
+    #[error("Invalid URL: {0}")]
+    InvalidUrl(String),
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    #[error("HTTP error: {0}")]
+    Http(#[from] hyper::Error),
+    #[error("Request timed out")]
+    Timeout,
+    #[error("Server answered with status {0}")]
+    Status(u16),
+}
+
+/// Limits and cache location of an `EnrichmentClient`.
+#[derive(Debug, Clone)]
+pub struct EnrichOptions {
+    /// Maximum number of requests in flight
+    pub concurrency: usize,
+    /// Minimum delay between the start of two requests
+    pub min_interval: Duration,
+    /// Number of times a failed request is retried
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled after each failed attempt
+    pub retry_delay: Duration,
+    /// Directory responses are cached in, if any
+    pub cache_dir: Option<PathBuf>,
+}
+
+impl Default for EnrichOptions {
+    fn default() -> Self {
+        EnrichOptions {
+            concurrency: 4,
+            min_interval: Duration::ZERO,
+            max_retries: 3,
+            retry_delay: Duration::from_millis(500),
+            cache_dir: None,
+        }
+    }
+}
+
+/// A request to an external service.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: String,
+    /// JSON body, if any
+    pub body: Option<String>,
+}
+
+impl HttpRequest {
+    /// Name of the cache file of the request.
+    fn cache_key(&self) -> String {
+        let mut bytes = Vec::new();
+        for part in [
+            self.method.as_str(),
+            &self.url,
+            self.body.as_deref().unwrap_or(""),
+        ] {
+            bytes.extend_from_slice(part.as_bytes());
+            bytes.push(0);
+        }
+        format!("{:016x}.http", seahash::hash(&bytes))
+    }
+}
+
+/// A definitive response: a success or an error that retrying would not fix.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+impl HttpResponse {
+    /// Parses a cache file: the status on the first line, then the body.
+    fn from_cache(contents: &str) -> Option<Self> {
+        let (status, body) = contents.split_once('\n')?;
+        Some(HttpResponse {
+            status: status.parse().ok()?,
+            body: body.to_string(),
+        })
+    }
+
+    fn to_cache(&self) -> String {
+        format!("{}\n{}", self.status, self.body)
+    }
+}
+
+struct Shared {
+    client: Client<HttpConnector>,
+    options: EnrichOptions,
+    permits: Semaphore,
+    /// Earliest time the next request may start
+    next_start: Mutex<Instant>,
+}
+
+/// Sends batches of requests to external services.
+pub struct EnrichmentClient {
+    shared: Arc<Shared>,
+    runtime: Runtime,
+}
+
+impl Shared {
+    /// Waits for a concurrency permit and for the rate limit, then sends the request once.
+    async fn send(&self, request: &HttpRequest) -> Result<HttpResponse, EnrichError> {
+        let _permit = self
+            .permits
+            .acquire()
+            .await
+            .expect("semaphore is never closed");
+        {
+            let mut next_start = self.next_start.lock().await;
+            tokio::time::sleep_until(*next_start).await;
+            *next_start = Instant::now() + self.options.min_interval;
+        }
+
+        let mut builder = hyper::Request::builder()
+            .method(request.method.clone())
+            .uri(&request.url);
+        if request.body.is_some() {
+            builder = builder.header("content-type", "application/json");
+        }
+        let http_request = builder
+            .body(request.body.clone().map_or_else(Body::empty, Body::from))
+            .map_err(|e| EnrichError::InvalidUrl(e.to_string()))?;
+
+        let response = tokio::time::timeout(REQUEST_TIMEOUT, self.client.request(http_request))
+            .await
+            .map_err(|_| EnrichError::Timeout)??;
+        let status = response.status().as_u16();
+        let body = hyper::body::to_bytes(response.into_body()).await?;
+        if status == 429 || status >= 500 {
+            return Err(EnrichError::Status(status));
+        }
+        Ok(HttpResponse {
+            status,
+            body: String::from_utf8_lossy(&body).into_owned(),
+        })
+    }
+
+    /// Returns the cached response, or sends the request with retries and caches the
+    /// response.
+    async fn fetch(&self, request: &HttpRequest) -> Result<HttpResponse, EnrichError> {
+        let cache_path = self
+            .options
+            .cache_dir
+            .as_ref()
+            .map(|dir| dir.join(request.cache_key()));
+        if let Some(path) = &cache_path {
+            match fs::read_to_string(path) {
+                Ok(contents) => {
+                    if let Some(response) = HttpResponse::from_cache(&contents) {
+                        return Ok(response);
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        let mut delay = self.options.retry_delay;
+        let mut attempt = 0;
+        let response = loop {
+            match self.send(request).await {
+                Err(EnrichError::Timeout | EnrichError::Http(_) | EnrichError::Status(_))
+                    if attempt < self.options.max_retries =>
+                {
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                    attempt += 1;
+                }
+                result => break result?,
+            }
+        };
+
+        if let Some(path) = &cache_path {
+            // Written then renamed, so an interrupted run never leaves a truncated entry
+            let partial = path.with_extension("partial");
+            fs::write(&partial, response.to_cache())?;
+            fs::rename(&partial, path)?;
+        }
+        Ok(response)
+    }
+}
+
+impl EnrichmentClient {
+    /// Creates a client, creating the cache directory if needed.
+    pub fn new(options: EnrichOptions) -> Result<Self, EnrichError> {
+        if let Some(dir) = &options.cache_dir {
+            fs::create_dir_all(dir)?;
+        }
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(options.concurrency.max(1))
+            .enable_all()
+            .build()?;
+
+        Ok(EnrichmentClient {
+            shared: Arc::new(Shared {
+                client: Client::new(),
+                permits: Semaphore::new(options.concurrency.max(1)),
+                next_start: Mutex::new(Instant::now()),
+                options,
+            }),
+            runtime,
+        })
+    }
+
+    /// Fetches every request, returning the results in the same order.
+    pub fn fetch_all(&self, requests: Vec<HttpRequest>) -> Vec<Result<HttpResponse, EnrichError>> {
+        self.runtime.block_on(async {
+            let mut tasks = JoinSet::new();
+            let len = requests.len();
+            for (index, request) in requests.into_iter().enumerate() {
+                let shared = Arc::clone(&self.shared);
+                tasks.spawn(async move { (index, shared.fetch(&request).await) });
+            }
+
+            let mut results: Vec<_> = (0..len).map(|_| None).collect();
+            while let Some(task) = tasks.join_next().await {
+                let (index, result) = task.expect("fetch tasks do not panic");
+                results[index] = Some(result);
+            }
+            results
+                .into_iter()
+                .map(|result| result.expect("every task returned"))
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// Serves the given HTTP responses to successive connections, returning the address
+    /// and a handle yielding the request lines received.
+    pub fn serve(responses: Vec<String>) -> (String, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let mut requests = Vec::new();
+            for response in responses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                requests.push(line.trim_end().to_string());
+                let mut content_length = 0;
+                let mut header = String::new();
+                while reader.read_line(&mut header).unwrap() > 2 {
+                    if let Some(value) = header.to_lowercase().strip_prefix("content-length:") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                    header.clear();
+                }
+                reader.read_exact(&mut vec![0; content_length]).unwrap();
+                let mut stream = reader.into_inner();
+                stream.write_all(response.as_bytes()).unwrap();
+                let _ = stream.read(&mut [0; 1]);
+            }
+            requests
+        });
+        (address, handle)
+    }
+
+    pub fn response(status: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+    }
+
+    pub fn fast_retries() -> EnrichOptions {
+        EnrichOptions {
+            retry_delay: Duration::from_millis(1),
+            ..EnrichOptions::default()
+        }
+    }
+
+    fn get(url: String) -> HttpRequest {
+        HttpRequest {
+            method: Method::GET,
+            url,
+            body: None,
+        }
+    }
+
+    #[test]
+    fn test_retries_transient_failures() {
+        let (address, handle) = serve(vec![
+            response("503 Service Unavailable", ""),
+            response("429 Too Many Requests", ""),
+            response("404 Not Found", "missing"),
+        ]);
+        let client = EnrichmentClient::new(fast_retries()).unwrap();
+
+        let results = client.fetch_all(vec![get(format!("{address}/a"))]);
+        assert_eq!(
+            results[0].as_ref().unwrap(),
+            &HttpResponse {
+                status: 404,
+                body: "missing".to_string()
+            }
+        );
+        assert_eq!(handle.join().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_gives_up_after_max_retries() {
+        let (address, handle) = serve(vec![response("500 Internal Server Error", ""); 2]);
+        let client = EnrichmentClient::new(EnrichOptions {
+            max_retries: 1,
+            ..fast_retries()
+        })
+        .unwrap();
+
+        let results = client.fetch_all(vec![get(address)]);
+        assert!(matches!(results[0], Err(EnrichError::Status(500))));
+        assert_eq!(handle.join().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_cache_resumes_without_requests() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = EnrichOptions {
+            cache_dir: Some(dir.path().join("cache")),
+            concurrency: 1,
+            ..fast_retries()
+        };
+        let (address, handle) = serve(vec![
+            response("200 OK", "first"),
+            response("200 OK", "second"),
+        ]);
+        let requests = vec![
+            get(format!("{address}/1")),
+            HttpRequest {
+                method: Method::POST,
+                url: format!("{address}/2"),
+                body: Some("{}".to_string()),
+            },
+        ];
+
+        let client = EnrichmentClient::new(options.clone()).unwrap();
+        let bodies = |results: Vec<Result<HttpResponse, EnrichError>>| {
+            results
+                .into_iter()
+                .map(|result| result.unwrap().body)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            bodies(client.fetch_all(requests.clone())),
+            ["first", "second"]
+        );
+        assert_eq!(
+            handle.join().unwrap(),
+            [
+                "GET /1 HTTP/1.1".to_string(),
+                "POST /2 HTTP/1.1".to_string()
+            ]
+        );
+
+        // The server is gone: everything must come from the cache
+        let client = EnrichmentClient::new(options).unwrap();
+        assert_eq!(bodies(client.fetch_all(requests)), ["first", "second"]);
+    }
+
+    #[test]
+    fn test_cache_key() {
+        let a = get("http://localhost/a".to_string());
+        let mut b = a.clone();
+        assert_eq!(a.cache_key(), b.cache_key());
+        b.body = Some(String::new());
+        assert_eq!(a.cache_key(), b.cache_key());
+        b.method = Method::POST;
+        assert_ne!(a.cache_key(), b.cache_key());
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let (address, handle) = serve(vec![response("200 OK", ""); 3]);
+        let client = EnrichmentClient::new(EnrichOptions {
+            min_interval: Duration::from_millis(50),
+            ..fast_retries()
+        })
+        .unwrap();
+
+        let start = std::time::Instant::now();
+        let results = client.fetch_all((0..3).map(|i| get(format!("{address}/{i}"))).collect());
+        assert!(results.iter().all(Result::is_ok));
+        assert!(start.elapsed() >= Duration::from_millis(100));
+        handle.join().unwrap();
+    }
+}
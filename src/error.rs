@@ -0,0 +1,100 @@
+//! Error type shared by the dataset readers, the pipeline and the command line.
+
+use crate::binary::BinaryError;
+use crate::compression::CompressionError;
+use crate::config::ConfigError;
+use crate::crypto::CryptoError;
+use crate::dataset::FileErrors;
+use crate::datum::UnknownDatum;
+use crate::encoding::UnknownEncoding;
+use crate::export::UnknownFormat;
+use crate::flight::FlightError;
+use crate::geojson::GeoJsonError;
+use crate::imu::ImuError;
+use crate::labels::LabelError;
+use crate::matching::MatchError;
+use crate::merge::MergeError;
+use crate::metrics::MetricsError;
+use crate::point::ParseError;
+use crate::polyline::PolylineError;
+use crate::preset::UnknownPreset;
+use crate::rawlog::RawLogError;
+use crate::redis::RedisError;
+use crate::store::StoreError;
+use crate::tenants::TenantError;
+use crate::trajectory::{DecodeError, EncodeError};
+use crate::zones;
+use thiserror::Error;
+
+/// Custom error type for the application
+#[derive(Error, Debug)]
+pub enum AppError {
+    #[error("Parse error: {0}")]
+    Parse(#[from] ParseError),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("{0}")]
+    Files(#[from] FileErrors),
+    #[error("Encode error: {0}")]
+    Encode(#[from] EncodeError),
+    #[error("Protobuf decode error: {0}")]
+    ProtoDecode(#[from] prost::DecodeError),
+    #[error("Trajectory decode error: {0}")]
+    Decode(#[from] DecodeError),
+    #[error("Binary decode error: {0}")]
+    BinaryDecode(#[from] BinaryError),
+    #[error("Decompression error: {0}")]
+    Compression(#[from] CompressionError),
+    #[error("Arrow Flight error: {0}")]
+    Flight(#[from] FlightError),
+    #[error("Polyline decode error: {0}")]
+    PolylineDecode(#[from] PolylineError),
+    #[error("Invalid UTF-8: {0}")]
+    Utf8(#[from] std::str::Utf8Error),
+    #[error("{0}")]
+    UnknownEncoding(#[from] UnknownEncoding),
+    #[error("{0}")]
+    UnknownFormat(#[from] UnknownFormat),
+    #[error("{0}")]
+    UnknownDatum(#[from] UnknownDatum),
+    #[error("{0}")]
+    UnknownPreset(#[from] UnknownPreset),
+    #[error("IMU parse error: {0}")]
+    Imu(#[from] ImuError),
+    #[error("Label parse error: {0}")]
+    Label(#[from] LabelError),
+    #[error("Config error: {0}")]
+    Config(#[from] ConfigError),
+    #[error("Merge error: {0}")]
+    Merge(#[from] MergeError),
+    #[error("Map matching error: {0}")]
+    Match(#[from] MatchError),
+    #[error("{0}")]
+    Zone(#[from] zones::ZoneError),
+    #[error("Encryption error: {0}")]
+    Crypto(#[from] CryptoError),
+    #[error("Parquet error: {0}")]
+    Parquet(#[from] parquet::errors::ParquetError),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("GeoJSON error: {0}")]
+    GeoJson(#[from] GeoJsonError),
+    #[error("Redis error: {0}")]
+    Redis(#[from] RedisError),
+    #[error("Tenants error: {0}")]
+    Tenant(#[from] TenantError),
+    #[error("Metrics error: {0}")]
+    Metrics(#[from] MetricsError),
+    #[error("Store error: {0}")]
+    Store(#[from] StoreError),
+    #[error("Raw log error: {0}")]
+    RawLog(#[from] RawLogError),
+    #[error("{encoding} read back {found} of {expected} points")]
+    RoundTrip {
+        encoding: &'static str,
+        expected: usize,
+        found: usize,
+    },
+    #[error("Usage: {0}")]
+    Usage(&'static str),
+}
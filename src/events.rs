@@ -0,0 +1,297 @@
+//! Detection of driving events for fleet use: speeding, harsh acceleration and braking,
+//! and long idling.
+//!
+//! Events are detected on the original points, whose speeds simplification would
+//! flatten, and attached to the segment of the simplified trajectory they happened on.
+
+use crate::geo::{point_speeds, to_degrees};
+use crate::trajectory::Trajectory;
+use serde_json::{json, Value};
+
+/// Thresholds of the event detection.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EventParams {
+    /// Speed limit in m/s
+    pub speed_limit_mps: f64,
+    /// Smallest acceleration in m/s² counted as harsh, positive
+    pub harsh_acceleration_mps2: f64,
+    /// Smallest deceleration in m/s² counted as harsh braking, positive
+    pub harsh_braking_mps2: f64,
+    /// Largest speed in m/s of an idle vehicle
+    pub idle_speed_mps: f64,
+    /// Shortest idling reported, in seconds
+    pub min_idle_s: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    Speeding,
+    HarshAcceleration,
+    HarshBraking,
+    Idling,
+}
+
+impl EventKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            EventKind::Speeding => "speeding",
+            EventKind::HarshAcceleration => "harsh-acceleration",
+            EventKind::HarshBraking => "harsh-braking",
+            EventKind::Idling => "idling",
+        }
+    }
+}
+
+/// Event spanning a run of original points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Event {
+    pub kind: EventKind,
+    /// Indices of the first and last original points of the event
+    pub start: usize,
+    pub end: usize,
+    /// Highest speed in m/s when speeding, highest acceleration or deceleration in m/s²
+    /// when accelerating or braking, duration in seconds when idling
+    pub value: f64,
+}
+
+/// Finds the runs of consecutive indices for which `flags` is set, as inclusive ranges.
+fn runs(flags: impl IntoIterator<Item = bool>) -> Vec<(usize, usize)> {
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+    for (i, flag) in flags.into_iter().enumerate() {
+        if !flag {
+            continue;
+        }
+        match runs.last_mut() {
+            Some((_, end)) if *end + 1 == i => *end = i,
+            _ => runs.push((i, i)),
+        }
+    }
+    runs
+}
+
+/// Detects the events of a trajectory, ordered by start.
+///
+/// Speeds are estimated at each point from its two neighbors, and accelerations between
+/// consecutive points from these speeds. An acceleration event covers the points on both
+/// sides of every harsh step.
+pub fn detect(trajectory: &Trajectory, params: EventParams) -> Vec<Event> {
+    let speeds = point_speeds(trajectory, 1);
+    let timestamps = &trajectory.timestamps;
+    // Acceleration from each point to the next
+    let accelerations: Vec<f64> = (1..speeds.len())
+        .map(|i| match timestamps[i].saturating_sub(timestamps[i - 1]) {
+            0 => 0.0,
+            dt => (speeds[i] - speeds[i - 1]) / dt as f64,
+        })
+        .collect();
+    let max = |values: &[f64]| values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+
+    let mut events = Vec::new();
+    for (start, end) in runs(speeds.iter().map(|&speed| speed > params.speed_limit_mps)) {
+        events.push(Event {
+            kind: EventKind::Speeding,
+            start,
+            end,
+            value: max(&speeds[start..=end]),
+        });
+    }
+    for (start, end) in runs(
+        accelerations
+            .iter()
+            .map(|&acceleration| acceleration >= params.harsh_acceleration_mps2),
+    ) {
+        events.push(Event {
+            kind: EventKind::HarshAcceleration,
+            start,
+            end: end + 1,
+            value: max(&accelerations[start..=end]),
+        });
+    }
+    let decelerations: Vec<f64> = accelerations.iter().map(|a| -a).collect();
+    for (start, end) in runs(
+        decelerations
+            .iter()
+            .map(|&deceleration| deceleration >= params.harsh_braking_mps2),
+    ) {
+        events.push(Event {
+            kind: EventKind::HarshBraking,
+            start,
+            end: end + 1,
+            value: max(&decelerations[start..=end]),
+        });
+    }
+    for (start, end) in runs(speeds.iter().map(|&speed| speed <= params.idle_speed_mps)) {
+        let duration = timestamps[end] - timestamps[start];
+        if duration >= params.min_idle_s {
+            events.push(Event {
+                kind: EventKind::Idling,
+                start,
+                end,
+                value: duration as f64,
+            });
+        }
+    }
+
+    events.sort_by_key(|event| (event.start, event.end));
+    events
+}
+
+/// Builds a GeoJSON `FeatureCollection` with the simplified trajectory as a `LineString`,
+/// followed by every event as a `Point` at its first original point.
+///
+/// Each event has its kind, value, start and end timestamps, and the index of the
+/// simplified segment its first point lies on as `segment`.
+///
+/// # Panics
+///
+/// This function will panic if `keep` does not have one entry per point of `original`.
+pub fn events_geojson(original: &Trajectory, keep: &[bool], events: &[Event]) -> Value {
+    assert_eq!(keep.len(), original.latitudes.len(), "keep.len() == len");
+
+    let position = |i: usize| {
+        json!([
+            to_degrees(original.longitudes[i]),
+            to_degrees(original.latitudes[i])
+        ])
+    };
+    let line: Vec<Value> = (0..keep.len()).filter(|&i| keep[i]).map(position).collect();
+    // Number of kept points up to each original point
+    let kept_before: Vec<usize> = keep
+        .iter()
+        .scan(0, |count, &kept| {
+            *count += kept as usize;
+            Some(*count)
+        })
+        .collect();
+    let segments = line.len().saturating_sub(1);
+
+    let mut features = vec![json!({
+        "type": "Feature",
+        "geometry": { "type": "LineString", "coordinates": line },
+        "properties": {},
+    })];
+    features.extend(events.iter().map(|event| {
+        let segment = kept_before[event.start]
+            .saturating_sub(1)
+            .min(segments.saturating_sub(1));
+        json!({
+            "type": "Feature",
+            "geometry": { "type": "Point", "coordinates": position(event.start) },
+            "properties": {
+                "event": event.kind.name(),
+                "value": event.value,
+                "start": original.timestamps[event.start],
+                "end": original.timestamps[event.end],
+                "segment": segment,
+            },
+        })
+    }));
+    json!({ "type": "FeatureCollection", "features": features })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trajectory::CoordinateSystem;
+    use crate::geo::meters_to_units;
+
+    const PARAMS: EventParams = EventParams {
+        speed_limit_mps: 25.0,
+        harsh_acceleration_mps2: 3.0,
+        harsh_braking_mps2: 4.0,
+        idle_speed_mps: 0.5,
+        min_idle_s: 60,
+    };
+
+    /// Drives north, one point per second, at the given speeds in m/s.
+    fn drive(speeds: &[f64]) -> Trajectory {
+        let mut latitude = 0.0;
+        let mut trajectory = Trajectory {
+            latitudes: vec![0],
+            longitudes: vec![0],
+            timestamps: vec![0],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+        for (i, speed) in speeds.iter().enumerate() {
+            latitude += speed;
+            trajectory.latitudes.push(meters_to_units(latitude));
+            trajectory.longitudes.push(0);
+            trajectory.timestamps.push(i as u64 + 1);
+        }
+        trajectory
+    }
+
+    #[test]
+    fn test_runs() {
+        assert_eq!(runs([false, true, true, false, true]), vec![(1, 2), (4, 4)]);
+        assert!(runs([false, false]).is_empty());
+    }
+
+    #[test]
+    fn test_detect() {
+        // Idle for 100 s, accelerate hard to 30 m/s, cruise, brake hard to 0 and idle for
+        // 30 s
+        let mut speeds = vec![0.0; 100];
+        speeds.extend((1..=6).map(|i| 5.0 * i as f64));
+        speeds.extend([30.0; 20]);
+        speeds.extend((0..6).rev().map(|i| 5.0 * i as f64));
+        speeds.extend([0.0; 30]);
+        let trajectory = drive(&speeds);
+
+        let events = detect(&trajectory, PARAMS);
+        let kinds: Vec<EventKind> = events.iter().map(|event| event.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                EventKind::Idling,
+                EventKind::HarshAcceleration,
+                EventKind::Speeding,
+                EventKind::HarshBraking,
+            ]
+        );
+        assert_eq!(events[0].start, 0);
+        assert!(events[0].value >= 99.0);
+        assert!((events[1].value - 5.0).abs() < 0.1);
+        assert!((events[2].value - 30.0).abs() < 0.1);
+        assert!((events[3].value - 5.0).abs() < 0.1);
+        assert!(events[1].end < events[2].start + 3);
+
+        // Stricter thresholds report nothing
+        let lenient = EventParams {
+            speed_limit_mps: 40.0,
+            harsh_acceleration_mps2: 10.0,
+            harsh_braking_mps2: 10.0,
+            idle_speed_mps: 0.0,
+            min_idle_s: 1_000,
+        };
+        assert!(detect(&trajectory, lenient).is_empty());
+    }
+
+    #[test]
+    fn test_events_geojson() {
+        let trajectory = drive(&[30.0, 30.0, 30.0, 30.0]);
+        let keep = [true, false, true, false, true];
+        let events = [Event {
+            kind: EventKind::Speeding,
+            start: 3,
+            end: 4,
+            value: 30.0,
+        }];
+
+        let geojson = events_geojson(&trajectory, &keep, &events);
+        let features = geojson["features"].as_array().unwrap();
+        assert_eq!(features.len(), 2);
+        assert_eq!(
+            features[0]["geometry"]["coordinates"]
+                .as_array()
+                .unwrap()
+                .len(),
+            3
+        );
+        assert_eq!(features[1]["properties"]["event"], "speeding");
+        assert_eq!(features[1]["properties"]["start"], 3);
+        assert_eq!(features[1]["properties"]["end"], 4);
+        assert_eq!(features[1]["properties"]["segment"], 1);
+    }
+}
@@ -0,0 +1,94 @@
+//! Formats stored trajectories are exported to: GeoJSON, for tools that cannot read the
+//! internal formats, or any serialization backend.
+
+use crate::encoding::Encoding;
+use crate::trajectory::{EncodeError, Trajectory};
+use serde_json::{json, Value};
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+#[error("Unknown export format: {0} (expected geojson, proto, proto-delta, polyline or binary)")]
+pub struct UnknownFormat(pub String);
+
+/// Format of exported trajectories.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// GeoJSON `Feature` with the trajectory as a `LineString`
+    GeoJson,
+    Encoded(Encoding),
+}
+
+impl ExportFormat {
+    /// File extension of the exported trajectories
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::GeoJson => "geojson",
+            ExportFormat::Encoded(encoding) => encoding.extension(),
+        }
+    }
+
+    /// Encodes the trajectory stored as `id`.
+    pub fn encode(self, id: &str, trajectory: &Trajectory) -> Result<Vec<u8>, EncodeError> {
+        match self {
+            ExportFormat::GeoJson => Ok(feature(id, trajectory).to_string().into_bytes()),
+            ExportFormat::Encoded(encoding) => encoding.encode(trajectory),
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = UnknownFormat;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "geojson" => Ok(ExportFormat::GeoJson),
+            _ => s
+                .parse()
+                .map(ExportFormat::Encoded)
+                .map_err(|_| UnknownFormat(s.to_string())),
+        }
+    }
+}
+
+/// Builds a GeoJSON `Feature` with the trajectory as a `LineString`, its id and the
+/// timestamp of every point.
+fn feature(id: &str, trajectory: &Trajectory) -> Value {
+    let mut feature = trajectory.to_geojson();
+    feature["properties"]["id"] = json!(id);
+    feature
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trajectory::CoordinateSystem;
+
+    #[test]
+    fn test_export_format() {
+        let trajectory = Trajectory {
+            latitudes: vec![39_900_000, 39_910_000],
+            longitudes: vec![116_400_000, 116_410_000],
+            timestamps: vec![100, 160],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+
+        let format: ExportFormat = "geojson".parse().unwrap();
+        let geojson: Value =
+            serde_json::from_slice(&format.encode("000-a", &trajectory).unwrap()).unwrap();
+        assert_eq!(
+            geojson["geometry"]["coordinates"][1],
+            json!([116.41, 39.91])
+        );
+        assert_eq!(
+            geojson["properties"],
+            json!({ "id": "000-a", "timestamps": [100, 160] })
+        );
+
+        let format: ExportFormat = "binary".parse().unwrap();
+        assert_eq!(format, ExportFormat::Encoded(Encoding::Binary));
+        assert_eq!(format.extension(), "trjb");
+        assert!(matches!("gpx".parse::<ExportFormat>(), Err(UnknownFormat(name)) if name == "gpx"));
+    }
+}
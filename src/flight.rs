@@ -0,0 +1,304 @@
+//! Arrow Flight service of simplified trajectories, on the tonic gRPC stack, for any Flight
+//! client (pyarrow.flight, DuckDB, the Rust `FlightClient`, ...) to read them.
+//!
+//! Every file is a flight described by the path `[<file>]` and read with the ticket
+//! `<file>`, as one record batch with the columns of `arrow`. The empty path and ticket
+//! stand for every file at once.
+
+use crate::arrow;
+use crate::trajectory::Trajectory;
+use arrow_array::RecordBatch;
+use arrow_flight::encode::FlightDataEncoderBuilder;
+use arrow_flight::error::FlightError as EncodeError;
+use arrow_flight::flight_service_server::{FlightService, FlightServiceServer};
+use arrow_flight::{
+    Action, ActionType, Criteria, Empty, FlightData, FlightDescriptor, FlightEndpoint, FlightInfo,
+    HandshakeRequest, HandshakeResponse, PutResult, SchemaAsIpc, SchemaResult, Ticket,
+};
+use arrow_schema::{ArrowError, SchemaRef};
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
+use std::net::SocketAddr;
+use thiserror::Error;
+use tonic::{Request, Response, Status, Streaming};
+
+#[derive(Error, Debug)]
+pub enum FlightError {
+    #[error("Arrow error: {0}")]
+    Arrow(#[from] ArrowError),
+    #[error("gRPC transport error: {0}")]
+    Transport(#[from] tonic::transport::Error),
+}
+
+/// Flight service of a fixed set of simplified trajectories.
+pub struct TrajectoryFlights {
+    schema: SchemaRef,
+    /// Schema as sent to clients, encoded once
+    schema_result: SchemaResult,
+    /// Description of every flight before its descriptor, ticket and size
+    info: FlightInfo,
+    /// Record batch of every file, by file name
+    batches: Vec<(String, RecordBatch)>,
+}
+
+impl TrajectoryFlights {
+    /// Service of the `(file, trajectory)` pairs, converted to record batches once for
+    /// every client.
+    pub fn new(
+        trajectories: impl IntoIterator<Item = (String, Trajectory)>,
+    ) -> Result<Self, FlightError> {
+        let batches = trajectories
+            .into_iter()
+            .map(|(file, trajectory)| Ok((file.clone(), arrow::record_batch(&file, &trajectory)?)))
+            .collect::<Result<_, ArrowError>>()?;
+        let schema = arrow::schema();
+        Ok(TrajectoryFlights {
+            schema_result: SchemaAsIpc::new(&schema, &Default::default()).try_into()?,
+            info: FlightInfo::new().try_with_schema(&schema)?,
+            schema,
+            batches,
+        })
+    }
+
+    /// Record batches of `file`, or of every file if empty, or `None` if unknown.
+    fn batches(&self, file: &str) -> Option<Vec<RecordBatch>> {
+        let batches: Vec<RecordBatch> = self
+            .batches
+            .iter()
+            .filter(|(name, _)| file.is_empty() || name == file)
+            .map(|(_, batch)| batch.clone())
+            .collect();
+        (file.is_empty() || !batches.is_empty()).then_some(batches)
+    }
+
+    /// Description of the flight of `file`, or of every file if empty, or `None` if
+    /// unknown.
+    fn flight_info(&self, file: &str) -> Option<FlightInfo> {
+        let rows = self
+            .batches(file)?
+            .iter()
+            .map(RecordBatch::num_rows)
+            .sum::<usize>();
+        let path = if file.is_empty() {
+            Vec::new()
+        } else {
+            vec![file.to_string()]
+        };
+        Some(
+            self.info
+                .clone()
+                .with_descriptor(FlightDescriptor::new_path(path))
+                .with_endpoint(FlightEndpoint::new().with_ticket(Ticket::new(file.to_string())))
+                .with_total_records(rows as i64)
+                .with_ordered(true),
+        )
+    }
+}
+
+fn not_found(file: &str) -> Status {
+    Status::not_found(format!("No trajectory for {file}"))
+}
+
+/// File named by the path of a descriptor, empty for every file, or `None` for paths of
+/// several parts.
+fn descriptor_file(descriptor: &FlightDescriptor) -> Option<&str> {
+    match descriptor.path.as_slice() {
+        [] => Some(""),
+        [file] => Some(file),
+        _ => None,
+    }
+}
+
+fn invalid_descriptor() -> Status {
+    Status::invalid_argument("Descriptor paths have one file at most")
+}
+
+#[tonic::async_trait]
+impl FlightService for TrajectoryFlights {
+    type HandshakeStream = BoxStream<'static, Result<HandshakeResponse, Status>>;
+    type ListFlightsStream = BoxStream<'static, Result<FlightInfo, Status>>;
+    type DoGetStream = BoxStream<'static, Result<FlightData, Status>>;
+    type DoPutStream = BoxStream<'static, Result<PutResult, Status>>;
+    type DoActionStream = BoxStream<'static, Result<arrow_flight::Result, Status>>;
+    type ListActionsStream = BoxStream<'static, Result<ActionType, Status>>;
+    type DoExchangeStream = BoxStream<'static, Result<FlightData, Status>>;
+
+    async fn handshake(
+        &self,
+        _request: Request<Streaming<HandshakeRequest>>,
+    ) -> Result<Response<Self::HandshakeStream>, Status> {
+        Err(Status::unimplemented("No authentication"))
+    }
+
+    async fn list_flights(
+        &self,
+        _request: Request<Criteria>,
+    ) -> Result<Response<Self::ListFlightsStream>, Status> {
+        let infos = self
+            .batches
+            .iter()
+            .filter_map(|(file, _)| self.flight_info(file))
+            .map(Ok)
+            .collect::<Vec<_>>();
+        Ok(Response::new(stream::iter(infos).boxed()))
+    }
+
+    async fn get_flight_info(
+        &self,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        let file = descriptor_file(request.get_ref()).ok_or_else(invalid_descriptor)?;
+        let info = self.flight_info(file).ok_or_else(|| not_found(file))?;
+        Ok(Response::new(info))
+    }
+
+    async fn get_schema(
+        &self,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<SchemaResult>, Status> {
+        let file = descriptor_file(request.get_ref()).ok_or_else(invalid_descriptor)?;
+        self.batches(file).ok_or_else(|| not_found(file))?;
+        Ok(Response::new(self.schema_result.clone()))
+    }
+
+    async fn do_get(
+        &self,
+        request: Request<Ticket>,
+    ) -> Result<Response<Self::DoGetStream>, Status> {
+        let file = std::str::from_utf8(&request.get_ref().ticket)
+            .map_err(|_| Status::invalid_argument("Tickets are file names"))?;
+        let batches = self.batches(file).ok_or_else(|| not_found(file))?;
+        let data = FlightDataEncoderBuilder::new()
+            .with_schema(self.schema.clone())
+            .build(stream::iter(batches.into_iter().map(Ok::<_, EncodeError>)))
+            .map_err(|error| Status::internal(error.to_string()));
+        Ok(Response::new(data.boxed()))
+    }
+
+    async fn do_put(
+        &self,
+        _request: Request<Streaming<FlightData>>,
+    ) -> Result<Response<Self::DoPutStream>, Status> {
+        Err(Status::unimplemented("Trajectories are read-only"))
+    }
+
+    async fn do_action(
+        &self,
+        _request: Request<Action>,
+    ) -> Result<Response<Self::DoActionStream>, Status> {
+        Err(Status::unimplemented("No actions"))
+    }
+
+    async fn list_actions(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<Self::ListActionsStream>, Status> {
+        Ok(Response::new(stream::empty().boxed()))
+    }
+
+    async fn do_exchange(
+        &self,
+        _request: Request<Streaming<FlightData>>,
+    ) -> Result<Response<Self::DoExchangeStream>, Status> {
+        Err(Status::unimplemented("Trajectories are read-only"))
+    }
+}
+
+/// Serves the flights on `address` until the process ends.
+pub async fn serve(address: SocketAddr, flights: TrajectoryFlights) -> Result<(), FlightError> {
+    tonic::transport::Server::builder()
+        .add_service(FlightServiceServer::new(flights))
+        .serve(address)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_flight::decode::FlightRecordBatchStream;
+    use arrow_flight::FlightClient;
+    use tokio::net::TcpListener;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::transport::Channel;
+
+    fn trajectory(timestamps: Vec<u64>) -> Trajectory {
+        Trajectory {
+            latitudes: timestamps
+                .iter()
+                .map(|&ts| 39_900_000 + ts as i64)
+                .collect(),
+            longitudes: timestamps
+                .iter()
+                .map(|&ts| 116_400_000 - ts as i64)
+                .collect(),
+            timestamps,
+            altitudes: Vec::new(),
+            coordinates: crate::trajectory::CoordinateSystem::Wgs84,
+        }
+    }
+
+    async fn collect(stream: FlightRecordBatchStream) -> Vec<RecordBatch> {
+        stream.try_collect().await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_flight_client() {
+        let flights = TrajectoryFlights::new([
+            ("a.plt".to_string(), trajectory(vec![1, 2, 3])),
+            ("b.plt".to_string(), trajectory(vec![10, 20])),
+        ])
+        .unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(FlightServiceServer::new(flights))
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+        let channel = Channel::from_shared(format!("http://{address}"))
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        let mut client = FlightClient::new(channel);
+
+        let infos: Vec<FlightInfo> = client
+            .list_flights(Vec::new())
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(infos.len(), 2);
+        assert_eq!(infos[1].total_records, 2);
+        let ticket = infos[1].endpoint[0].ticket.clone().unwrap();
+        let batches = collect(client.do_get(ticket).await.unwrap()).await;
+        assert_eq!(
+            batches,
+            [arrow::record_batch("b.plt", &trajectory(vec![10, 20])).unwrap()]
+        );
+
+        let all = collect(client.do_get(Ticket::new("")).await.unwrap()).await;
+        assert_eq!(
+            all.iter().map(RecordBatch::num_rows).collect::<Vec<_>>(),
+            [3, 2]
+        );
+        let schema = client
+            .get_schema(FlightDescriptor::new_path(vec!["a.plt".to_string()]))
+            .await
+            .unwrap();
+        assert_eq!(schema, *arrow::schema());
+        let info = client
+            .get_flight_info(FlightDescriptor::new_path(Vec::new()))
+            .await
+            .unwrap();
+        assert_eq!(info.total_records, 5);
+
+        let missing = client.do_get(Ticket::new("c.plt")).await;
+        assert!(matches!(
+            missing,
+            Err(arrow_flight::error::FlightError::Tonic(status))
+                if status.code() == tonic::Code::NotFound
+        ));
+    }
+}
@@ -0,0 +1,327 @@
+//! Readers of the file formats trajectories are imported from, besides GeoLife `.plt`:
+//! GPX tracks, CSV files with WGS 84 coordinates, and Google Takeout location history.
+//!
+//! The format of a file is detected from its extension, ignoring a `.gz` suffix.
+
+use crate::point::{parse_plt_file, ParseError, Point};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde_json::Value;
+use std::io::BufRead;
+use std::path::Path;
+
+/// Format of a file to import.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// GeoLife `.plt`
+    Plt,
+    /// GPX track points, with their time
+    Gpx,
+    /// CSV with a header naming the latitude, longitude and time columns
+    Csv,
+    /// Google Takeout `Records.json` location history
+    Takeout,
+}
+
+impl Format {
+    /// Detects the format of a file from its extension.
+    pub fn detect(path: &Path) -> Option<Format> {
+        let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+        let name = name.strip_suffix(".gz").unwrap_or(&name);
+        match name.rsplit_once('.')?.1 {
+            "plt" => Some(Format::Plt),
+            "gpx" => Some(Format::Gpx),
+            "csv" => Some(Format::Csv),
+            "json" => Some(Format::Takeout),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Format::Plt => "plt",
+            Format::Gpx => "gpx",
+            Format::Csv => "csv",
+            Format::Takeout => "takeout",
+        }
+    }
+
+    /// Parses the points of a file in this format.
+    pub fn parse(self, reader: impl BufRead) -> Result<Vec<Point>, ParseError> {
+        match self {
+            Format::Plt => parse_plt_file(reader),
+            Format::Gpx => parse_gpx_file(reader),
+            Format::Csv => parse_csv_file(reader),
+            Format::Takeout => parse_takeout_file(reader),
+        }
+    }
+}
+
+fn parse_time(value: &str) -> Result<DateTime<Utc>, ParseError> {
+    let value = value.trim();
+    match value.parse::<i64>() {
+        Ok(seconds) => DateTime::from_timestamp(seconds, 0).ok_or(ParseError::InvalidTimestamp),
+        Err(_) => DateTime::parse_from_rfc3339(value)
+            .map(|datetime| datetime.with_timezone(&Utc))
+            .map_err(|e| ParseError::DateParse(e.to_string())),
+    }
+}
+
+fn parse_latitude(value: &str) -> Result<Decimal, ParseError> {
+    value
+        .trim()
+        .parse()
+        .map_err(|e: rust_decimal::Error| ParseError::LatitudeParse(e.to_string()))
+}
+
+fn parse_longitude(value: &str) -> Result<Decimal, ParseError> {
+    value
+        .trim()
+        .parse()
+        .map_err(|e: rust_decimal::Error| ParseError::LongitudeParse(e.to_string()))
+}
+
+fn parse_elevation(value: &str) -> Result<Decimal, ParseError> {
+    value
+        .trim()
+        .parse()
+        .map_err(|e: rust_decimal::Error| ParseError::AltitudeParse(e.to_string()))
+}
+
+/// Value of the attribute `name` in the start tag `tag`.
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let mut offset = 0;
+    while let Some(found) = tag[offset..].find(name) {
+        let start = offset + found;
+        offset = start + name.len();
+        if !tag[..start].ends_with(char::is_whitespace) {
+            continue;
+        }
+        let Some(value) = tag[offset..].trim_start().strip_prefix('=') else {
+            continue;
+        };
+        let value = value.trim_start();
+        let quote = value.chars().next().filter(|&c| c == '"' || c == '\'')?;
+        return value[1..].split(quote).next();
+    }
+    None
+}
+
+/// Text of the first `name` element in `content`.
+fn element<'a>(content: &'a str, name: &str) -> Option<&'a str> {
+    let start = content.find(&format!("<{name}>"))? + name.len() + 2;
+    let end = content[start..].find(&format!("</{name}>"))?;
+    Some(&content[start..start + end])
+}
+
+/// Parses the track points of a GPX file, in the order of the file, with their elevation
+/// in meters if any. Every point must have a `time`; waypoints and routes are ignored.
+pub fn parse_gpx_file(mut reader: impl BufRead) -> Result<Vec<Point>, ParseError> {
+    let mut gpx = String::new();
+    reader.read_to_string(&mut gpx)?;
+
+    let mut points = Vec::new();
+    let mut rest = gpx.as_str();
+    while let Some(start) = rest.find("<trkpt") {
+        rest = &rest[start..];
+        let tag_end = rest.find('>').ok_or(ParseError::InvalidFieldCount)?;
+        let tag = &rest[..tag_end];
+        // A self-closing point has no time
+        let content = if tag.ends_with('/') {
+            ""
+        } else {
+            let end = rest.find("</trkpt>").ok_or(ParseError::InvalidFieldCount)?;
+            &rest[tag_end + 1..end]
+        };
+
+        let latitude = attribute(tag, "lat").ok_or(ParseError::InvalidFieldCount)?;
+        let longitude = attribute(tag, "lon").ok_or(ParseError::InvalidFieldCount)?;
+        let time = element(content, "time").ok_or(ParseError::InvalidTimestamp)?;
+        points.push(Point {
+            latitude: parse_latitude(latitude)?,
+            longitude: parse_longitude(longitude)?,
+            datetime: parse_time(time)?,
+            altitude: element(content, "ele").map(parse_elevation).transpose()?,
+        });
+        rest = &rest[tag_end..];
+    }
+
+    Ok(points)
+}
+
+/// Parses a CSV file with a header line naming its latitude (`lat` or `latitude`),
+/// longitude (`lon`, `lng` or `longitude`) and time (`time`, `timestamp` or `datetime`)
+/// columns, in any order among others. Times are Unix seconds or RFC 3339. Blank lines
+/// are ignored.
+pub fn parse_csv_file(reader: impl BufRead) -> Result<Vec<Point>, ParseError> {
+    let mut lines = reader.lines();
+    let header = lines.next().transpose()?.unwrap_or_default();
+    let columns: Vec<String> = header
+        .split(',')
+        .map(|name| name.trim().to_ascii_lowercase())
+        .collect();
+    let column = |names: &[&str]| {
+        columns
+            .iter()
+            .position(|column| names.contains(&column.as_str()))
+            .ok_or(ParseError::InvalidFieldCount)
+    };
+    let latitude_column = column(&["lat", "latitude"])?;
+    let longitude_column = column(&["lon", "lng", "longitude"])?;
+    let time_column = column(&["time", "timestamp", "datetime"])?;
+
+    let mut points = Vec::new();
+    for line in lines {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').collect();
+        if fields.len() != columns.len() {
+            return Err(ParseError::InvalidFieldCount);
+        }
+        points.push(Point {
+            latitude: parse_latitude(fields[latitude_column])?,
+            longitude: parse_longitude(fields[longitude_column])?,
+            datetime: parse_time(fields[time_column])?,
+            altitude: None,
+        });
+    }
+
+    Ok(points)
+}
+
+/// Parses a Google Takeout location history, `{"locations": [...]}` with coordinates in
+/// 10^-7 degrees and the time as RFC 3339 `timestamp` or, in older exports, as Unix
+/// milliseconds in `timestampMs`.
+pub fn parse_takeout_file(reader: impl BufRead) -> Result<Vec<Point>, ParseError> {
+    let history: Value =
+        serde_json::from_reader(reader).map_err(|e| ParseError::Json(e.to_string()))?;
+    let locations = history["locations"]
+        .as_array()
+        .ok_or(ParseError::InvalidFieldCount)?;
+
+    let mut points = Vec::with_capacity(locations.len());
+    for location in locations {
+        let e7 = |name: &str| location[name].as_i64().map(|value| Decimal::new(value, 7));
+        let datetime = match (
+            location["timestamp"].as_str(),
+            location["timestampMs"].as_str(),
+        ) {
+            (Some(timestamp), _) => parse_time(timestamp)?,
+            (None, Some(milliseconds)) => milliseconds
+                .parse()
+                .ok()
+                .and_then(DateTime::from_timestamp_millis)
+                .ok_or(ParseError::InvalidTimestamp)?,
+            (None, None) => return Err(ParseError::InvalidTimestamp),
+        };
+        points.push(Point {
+            latitude: e7("latitudeE7")
+                .ok_or_else(|| ParseError::LatitudeParse(location["latitudeE7"].to_string()))?,
+            longitude: e7("longitudeE7")
+                .ok_or_else(|| ParseError::LongitudeParse(location["longitudeE7"].to_string()))?,
+            datetime,
+            altitude: None,
+        });
+    }
+
+    Ok(points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(points: &[Point]) -> Vec<(String, String, i64)> {
+        points
+            .iter()
+            .map(|point| {
+                (
+                    point.latitude.normalize().to_string(),
+                    point.longitude.normalize().to_string(),
+                    point.datetime.timestamp(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(Format::detect(Path::new("a/b.GPX")), Some(Format::Gpx));
+        assert_eq!(Format::detect(Path::new("b.plt.gz")), Some(Format::Plt));
+        assert_eq!(
+            Format::detect(Path::new("Records.json")),
+            Some(Format::Takeout)
+        );
+        assert_eq!(Format::detect(Path::new("notes.txt")), None);
+        assert_eq!(Format::detect(Path::new("csv")), None);
+    }
+
+    #[test]
+    fn test_parse_gpx_file() {
+        let gpx = r#"<?xml version="1.0"?>
+            <gpx version="1.1" creator="test">
+              <wpt lat="1" lon="1"><time>2020-01-01T00:00:00Z</time></wpt>
+              <trk><trkseg>
+                <trkpt lat="39.9" lon="116.4"><ele>44</ele><time>2020-01-01T00:00:00Z</time></trkpt>
+                <trkpt lon='116.41' lat='39.91'>
+                  <time>2020-01-01T01:00:10+01:00</time>
+                </trkpt>
+              </trkseg></trk>
+            </gpx>"#;
+        let points = parse_gpx_file(gpx.as_bytes()).unwrap();
+        assert_eq!(
+            summary(&points),
+            [
+                ("39.9".to_string(), "116.4".to_string(), 1577836800),
+                ("39.91".to_string(), "116.41".to_string(), 1577836810),
+            ]
+        );
+        assert_eq!(points[0].altitude, Some(Decimal::from(44)));
+        assert_eq!(points[1].altitude, None);
+
+        let without_time = r#"<trk><trkseg><trkpt lat="1" lon="2"/></trkseg></trk>"#;
+        assert!(matches!(
+            parse_gpx_file(without_time.as_bytes()),
+            Err(ParseError::InvalidTimestamp)
+        ));
+        let invalid_ele = r#"<trkpt lat="1" lon="2"><ele>high</ele><time>0</time></trkpt>"#;
+        assert!(matches!(
+            parse_gpx_file(invalid_ele.as_bytes()),
+            Err(ParseError::AltitudeParse(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_csv_file() {
+        let csv =
+            "id,time,lon,lat\n1,1577836800,116.4,39.9\n\n2,2020-01-01T00:00:10Z,116.41,39.91\n";
+        assert_eq!(
+            summary(&parse_csv_file(csv.as_bytes()).unwrap()),
+            [
+                ("39.9".to_string(), "116.4".to_string(), 1577836800),
+                ("39.91".to_string(), "116.41".to_string(), 1577836810),
+            ]
+        );
+        assert!(matches!(
+            parse_csv_file("x,y\n1,2\n".as_bytes()),
+            Err(ParseError::InvalidFieldCount)
+        ));
+    }
+
+    #[test]
+    fn test_parse_takeout_file() {
+        let takeout = r#"{"locations": [
+            {"latitudeE7": 399000000, "longitudeE7": 1164000000, "timestampMs": "1577836800000"},
+            {"latitudeE7": -339000000, "longitudeE7": 1512000000, "timestamp": "2020-01-01T00:00:10.500Z"}
+        ]}"#;
+        assert_eq!(
+            summary(&parse_takeout_file(takeout.as_bytes()).unwrap()),
+            [
+                ("39.9".to_string(), "116.4".to_string(), 1577836800),
+                ("-33.9".to_string(), "151.2".to_string(), 1577836810),
+            ]
+        );
+    }
+}
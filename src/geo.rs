@@ -0,0 +1,202 @@
+//! Geodesic helpers working on the scaled integer coordinates of `Trajectory`.
+
+use crate::trajectory::{Trajectory, SCALE};
+
+/// Mean radius of the Earth in meters
+pub const EARTH_RADIUS_M: f64 = 6_371_008.8;
+
+/// Number of scaled units per degree
+pub const UNITS_PER_DEGREE: f64 = 1_000_000.0;
+
+const _: () = assert!(SCALE == 6, "UNITS_PER_DEGREE must match SCALE");
+
+/// Length in meters of one scaled unit along a meridian (about 11 cm)
+pub const METERS_PER_UNIT: f64 = EARTH_RADIUS_M * std::f64::consts::PI / 180.0 / UNITS_PER_DEGREE;
+
+/// Converts a scaled coordinate to degrees.
+pub fn to_degrees(value: i64) -> f64 {
+    value as f64 / UNITS_PER_DEGREE
+}
+
+/// Converts a distance in meters to scaled units, measured along a meridian.
+pub fn meters_to_units(meters: f64) -> i64 {
+    (meters / METERS_PER_UNIT).round() as i64
+}
+
+/// Great-circle distance in meters between two scaled coordinates.
+pub fn haversine_m(lat1: i64, lon1: i64, lat2: i64, lon2: i64) -> f64 {
+    let lat1 = to_degrees(lat1).to_radians();
+    let lat2 = to_degrees(lat2).to_radians();
+    let dlat = lat2 - lat1;
+    let dlon = (to_degrees(lon2) - to_degrees(lon1)).to_radians();
+
+    let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_M * a.sqrt().asin()
+}
+
+/// Distance in meters from a point to the segment between two other points.
+///
+/// Uses an equirectangular projection centered on the point, which is accurate
+/// for the short distances found between consecutive GPS fixes.
+pub fn segment_distance_m(
+    (lat, lon): (i64, i64),
+    (lat1, lon1): (i64, i64),
+    (lat2, lon2): (i64, i64),
+) -> f64 {
+    let cos_lat = to_degrees(lat).to_radians().cos();
+    let project = |p_lat: i64, p_lon: i64| {
+        (
+            (p_lon - lon) as f64 * cos_lat * METERS_PER_UNIT,
+            (p_lat - lat) as f64 * METERS_PER_UNIT,
+        )
+    };
+    let (x1, y1) = project(lat1, lon1);
+    let (x2, y2) = project(lat2, lon2);
+
+    let (dx, dy) = (x2 - x1, y2 - y1);
+    let length_squared = dx * dx + dy * dy;
+    let t = if length_squared == 0.0 {
+        0.0
+    } else {
+        (-(x1 * dx + y1 * dy) / length_squared).clamp(0.0, 1.0)
+    };
+    let (x, y) = (x1 + t * dx, y1 + t * dy);
+    (x * x + y * y).sqrt()
+}
+
+/// Estimates the speed in m/s at each point, averaged over `window` points on each side.
+pub fn point_speeds(trajectory: &Trajectory, window: usize) -> Vec<f64> {
+    let len = trajectory.timestamps.len();
+    let mut cumulative = vec![0.0; len];
+    for i in 1..len {
+        cumulative[i] = cumulative[i - 1]
+            + trajectory.coordinates.distance_m(
+                (trajectory.latitudes[i - 1], trajectory.longitudes[i - 1]),
+                (trajectory.latitudes[i], trajectory.longitudes[i]),
+            );
+    }
+
+    (0..len)
+        .map(|i| {
+            let first = i.saturating_sub(window);
+            let last = (i + window).min(len - 1);
+            let dt = trajectory.timestamps[last].saturating_sub(trajectory.timestamps[first]);
+            if dt == 0 {
+                0.0
+            } else {
+                (cumulative[last] - cumulative[first]) / dt as f64
+            }
+        })
+        .collect()
+}
+
+/// Equirectangular projection to meters around a reference point, with `x` east and `y`
+/// north, accurate within a few hundred kilometers of the reference.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LocalProjection {
+    latitude: f64,
+    longitude: f64,
+    cos_lat: f64,
+}
+
+impl LocalProjection {
+    /// Creates a projection around a point given in degrees.
+    pub fn new(latitude: f64, longitude: f64) -> Self {
+        LocalProjection {
+            latitude,
+            longitude,
+            cos_lat: latitude.to_radians().cos(),
+        }
+    }
+
+    /// Projects a point given in degrees.
+    pub fn project(&self, latitude: f64, longitude: f64) -> (f64, f64) {
+        let meters_per_degree = METERS_PER_UNIT * UNITS_PER_DEGREE;
+        (
+            (longitude - self.longitude) * self.cos_lat * meters_per_degree,
+            (latitude - self.latitude) * meters_per_degree,
+        )
+    }
+
+    /// Returns the latitude and longitude in degrees of a projected point.
+    pub fn unproject(&self, x: f64, y: f64) -> (f64, f64) {
+        let meters_per_degree = METERS_PER_UNIT * UNITS_PER_DEGREE;
+        (
+            self.latitude + y / meters_per_degree,
+            self.longitude + x / (self.cos_lat * meters_per_degree),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trajectory::CoordinateSystem;
+
+    #[test]
+    fn test_haversine_one_degree_of_latitude() {
+        let d = haversine_m(0, 0, 1_000_000, 0);
+        assert!((d - 111_195.0).abs() < 1.0, "{d}");
+    }
+
+    #[test]
+    fn test_haversine_beijing() {
+        // Tiananmen to the Summer Palace, about 14.5 km
+        let d = haversine_m(39_908_823, 116_397_470, 39_999_982, 116_275_461);
+        assert!((d - 14_522.0).abs() < 1.0, "{d}");
+    }
+
+    #[test]
+    fn test_segment_distance_m() {
+        // 100 units north of the middle of an east-west segment
+        let d = segment_distance_m((100, 50), (0, 0), (0, 100));
+        assert!((d - 100.0 * METERS_PER_UNIT).abs() < 1e-9);
+
+        // Beyond the end of the segment: distance to the endpoint
+        let d = segment_distance_m((0, 130), (0, 0), (0, 100));
+        assert!((d - 30.0 * METERS_PER_UNIT).abs() < 1e-9);
+
+        // Degenerate segment
+        let d = segment_distance_m((40, 30), (0, 0), (0, 0));
+        assert!((d - 50.0 * METERS_PER_UNIT).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_meters_to_units() {
+        assert_eq!(meters_to_units(0.0), 0);
+        assert_eq!(meters_to_units(111.195), 1000);
+        assert_eq!(meters_to_units(METERS_PER_UNIT * 42.0), 42);
+    }
+
+    #[test]
+    fn test_point_speeds() {
+        let trajectory = Trajectory {
+            latitudes: vec![0, 100, 200, 300],
+            longitudes: vec![0; 4],
+            timestamps: vec![0, 10, 20, 20],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+        let expected = 300.0 * METERS_PER_UNIT / 20.0;
+        assert!(point_speeds(&trajectory, 5)
+            .iter()
+            .all(|speed| (speed - expected).abs() < 0.01));
+
+        // The last point shares its timestamp with its only neighbor
+        let speeds = point_speeds(&trajectory, 1);
+        assert!((speeds[1] - 200.0 * METERS_PER_UNIT / 20.0).abs() < 0.01);
+        assert_eq!(speeds[3], 0.0);
+    }
+
+    #[test]
+    fn test_local_projection() {
+        let projection = LocalProjection::new(60.0, 10.0);
+        // A degree of longitude is half as long at 60 degrees of latitude
+        let (x, y) = projection.project(61.0, 12.0);
+        assert!((x - 111_195.0).abs() < 1.0, "{x}");
+        assert!((y - 111_195.0).abs() < 1.0, "{y}");
+
+        let (latitude, longitude) = projection.unproject(x, y);
+        assert!((latitude - 61.0).abs() < 1e-9 && (longitude - 12.0).abs() < 1e-9);
+    }
+}
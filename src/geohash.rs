@@ -0,0 +1,319 @@
+//! Geohash cells and the coarse visit aggregation exported next to simplified tracks.
+//!
+//! A geohash of precision `n` names a cell by interleaving `5n` bits of longitude and
+//! latitude bisections, starting with longitude, written in base 32. Longer hashes name
+//! smaller cells nested in the shorter ones: about 5 km wide at precision 5, 150 m at 7.
+
+use crate::geo::to_degrees;
+use crate::trajectory::Trajectory;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+/// Digits of the geohash base 32 alphabet, which leaves out `a`, `i`, `l` and `o`
+const BASE32: &[u8; 32] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+
+/// Longest supported geohash, whose cells are a few centimeters wide
+pub const MAX_PRECISION: usize = 12;
+
+/// Bounds of a geohash cell in degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub min_latitude: f64,
+    pub max_latitude: f64,
+    pub min_longitude: f64,
+    pub max_longitude: f64,
+}
+
+/// Computes the geohash of the cell containing a point.
+///
+/// # Panics
+///
+/// This function will panic if `precision` is zero or above `MAX_PRECISION`.
+pub fn encode(latitude: i64, longitude: i64, precision: usize) -> String {
+    encode_degrees(to_degrees(latitude), to_degrees(longitude), precision)
+}
+
+/// Computes the geohash of the cell containing a point given in degrees.
+///
+/// # Panics
+///
+/// This function will panic if `precision` is zero or above `MAX_PRECISION`.
+fn encode_degrees(latitude: f64, longitude: f64, precision: usize) -> String {
+    assert!(
+        (1..=MAX_PRECISION).contains(&precision),
+        "precision must be between 1 and {MAX_PRECISION}"
+    );
+
+    let mut latitude_range = (-90.0, 90.0);
+    let mut longitude_range = (-180.0, 180.0);
+    let mut hash = String::with_capacity(precision);
+    let mut bit = 0;
+    let mut digit = 0;
+
+    while hash.len() < precision {
+        let (range, value) = if bit % 2 == 0 {
+            (&mut longitude_range, longitude)
+        } else {
+            (&mut latitude_range, latitude)
+        };
+        let middle = (range.0 + range.1) / 2.0;
+        digit <<= 1;
+        if value >= middle {
+            digit |= 1;
+            range.0 = middle;
+        } else {
+            range.1 = middle;
+        }
+
+        bit += 1;
+        if bit % 5 == 0 {
+            hash.push(BASE32[digit] as char);
+            digit = 0;
+        }
+    }
+
+    hash
+}
+
+/// Computes the bounds of a geohash cell, or returns `None` if it is not a valid geohash.
+pub fn bounds(hash: &str) -> Option<Bounds> {
+    if hash.is_empty() || hash.len() > MAX_PRECISION {
+        return None;
+    }
+
+    let mut latitude_range = (-90.0, 90.0);
+    let mut longitude_range = (-180.0, 180.0);
+    let mut even = true;
+    for c in hash.bytes() {
+        let digit = BASE32.iter().position(|&d| d == c)?;
+        for shift in (0..5).rev() {
+            let range = if even {
+                &mut longitude_range
+            } else {
+                &mut latitude_range
+            };
+            let middle = (range.0 + range.1) / 2.0;
+            if digit >> shift & 1 == 1 {
+                range.0 = middle;
+            } else {
+                range.1 = middle;
+            }
+            even = !even;
+        }
+    }
+
+    Some(Bounds {
+        min_latitude: latitude_range.0,
+        max_latitude: latitude_range.1,
+        min_longitude: longitude_range.0,
+        max_longitude: longitude_range.1,
+    })
+}
+
+/// Lists the cells intersecting or touching a box given in degrees, row by row from the
+/// south-west.
+///
+/// # Panics
+///
+/// This function will panic if `precision` is zero or above `MAX_PRECISION`.
+pub fn covering(bounds: Bounds, precision: usize) -> Vec<String> {
+    // Longitude takes the first of every two bits
+    let latitude_bits = 5 * precision as i32 / 2;
+    let longitude_bits = 5 * precision as i32 - latitude_bits;
+    let height = 180.0 / 2_f64.powi(latitude_bits);
+    let width = 360.0 / 2_f64.powi(longitude_bits);
+    let index = |value: f64, min: f64, size: f64, bits: i32| {
+        (((value - min) / size).floor() as i64).clamp(0, (1_i64 << bits) - 1)
+    };
+
+    let rows = index(bounds.min_latitude, -90.0, height, latitude_bits)
+        ..=index(bounds.max_latitude, -90.0, height, latitude_bits);
+    let columns = index(bounds.min_longitude, -180.0, width, longitude_bits)
+        ..=index(bounds.max_longitude, -180.0, width, longitude_bits);
+    rows.flat_map(|row| {
+        columns.clone().map(move |column| {
+            let latitude = -90.0 + (row as f64 + 0.5) * height;
+            let longitude = -180.0 + (column as f64 + 0.5) * width;
+            encode_degrees(latitude, longitude, precision)
+        })
+    })
+    .collect()
+}
+
+/// Consecutive points of a trajectory in the same cell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Visit {
+    pub cell: String,
+    pub points: usize,
+}
+
+/// Maps the points of a trajectory to cells, merging consecutive points in the same cell.
+///
+/// # Panics
+///
+/// This function will panic if `precision` is zero or above `MAX_PRECISION`.
+pub fn visits(trajectory: &Trajectory, precision: usize) -> Vec<Visit> {
+    let mut visits: Vec<Visit> = Vec::new();
+    for (&latitude, &longitude) in trajectory.latitudes.iter().zip(&trajectory.longitudes) {
+        let cell = encode(latitude, longitude, precision);
+        match visits.last_mut() {
+            Some(visit) if visit.cell == cell => visit.points += 1,
+            _ => visits.push(Visit { cell, points: 1 }),
+        }
+    }
+    visits
+}
+
+/// Builds the aggregation of named trajectories: the sequence of cells visited by each one,
+/// and for every cell the number of points and of separate visits over all of them.
+pub fn aggregate_json(trajectories: &[(String, Vec<Visit>)], precision: usize) -> Value {
+    let mut cells: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+    for (_, visits) in trajectories {
+        for visit in visits {
+            let (points, count) = cells.entry(&visit.cell).or_default();
+            *points += visit.points;
+            *count += 1;
+        }
+    }
+
+    let cells: Map<String, Value> = cells
+        .into_iter()
+        .map(|(cell, (points, visits))| {
+            (
+                cell.to_string(),
+                json!({ "points": points, "visits": visits }),
+            )
+        })
+        .collect();
+    let trajectories: Vec<Value> = trajectories
+        .iter()
+        .map(|(name, visits)| {
+            let sequence: Vec<&str> = visits.iter().map(|visit| visit.cell.as_str()).collect();
+            json!({ "name": name, "sequence": sequence })
+        })
+        .collect();
+
+    json!({
+        "precision": precision,
+        "cells": cells,
+        "trajectories": trajectories,
+    })
+}
+
+/// Builds a GeoJSON `FeatureCollection` with one rectangle per cell, named by a `geohash`
+/// property. Invalid geohashes are skipped.
+pub fn cells_geojson(cells: &[String]) -> Value {
+    let features: Vec<Value> = cells
+        .iter()
+        .filter_map(|cell| Some((cell, bounds(cell)?)))
+        .map(|(cell, b)| {
+            let ring = json!([
+                [b.min_longitude, b.min_latitude],
+                [b.max_longitude, b.min_latitude],
+                [b.max_longitude, b.max_latitude],
+                [b.min_longitude, b.max_latitude],
+                [b.min_longitude, b.min_latitude],
+            ]);
+            json!({
+                "type": "Feature",
+                "geometry": { "type": "Polygon", "coordinates": [ring] },
+                "properties": { "geohash": cell },
+            })
+        })
+        .collect();
+    json!({ "type": "FeatureCollection", "features": features })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trajectory::CoordinateSystem;
+
+    #[test]
+    fn test_encode() {
+        // Reference values from the original geohash.org implementation
+        assert_eq!(encode(42_600_000, -5_600_000, 5), "ezs42");
+        assert_eq!(encode(57_649_110, 10_407_440, 11), "u4pruydqqvj");
+        assert_eq!(encode(39_984_702, 116_318_417, 1), "w");
+        assert_eq!(encode(-90_000_000, -180_000_000, 3), "000");
+    }
+
+    #[test]
+    #[should_panic(expected = "precision must be between 1 and 12")]
+    fn test_encode_invalid_precision() {
+        encode(0, 0, 13);
+    }
+
+    #[test]
+    fn test_bounds() {
+        let bounds = bounds("ezs42").unwrap();
+        assert!(bounds.min_latitude <= 42.6 && 42.6 < bounds.max_latitude);
+        assert!(bounds.min_longitude <= -5.6 && -5.6 < bounds.max_longitude);
+        assert!((bounds.max_longitude - bounds.min_longitude - 360.0 / 8192.0).abs() < 1e-12);
+        assert!((bounds.max_latitude - bounds.min_latitude - 180.0 / 4096.0).abs() < 1e-12);
+
+        assert_eq!(super::bounds(""), None);
+        assert_eq!(super::bounds("ezs4a"), None);
+    }
+
+    #[test]
+    fn test_covering() {
+        let cell = bounds("ezs42").unwrap();
+        let inside = Bounds {
+            min_latitude: cell.min_latitude + 0.001,
+            max_latitude: cell.max_latitude - 0.001,
+            min_longitude: cell.min_longitude + 0.001,
+            max_longitude: cell.max_longitude - 0.001,
+        };
+        assert_eq!(covering(inside, 5), vec!["ezs42"]);
+
+        // The cell and its eastern and northern neighbors
+        let area = Bounds {
+            max_latitude: cell.max_latitude + 0.001,
+            max_longitude: cell.max_longitude + 0.001,
+            ..cell
+        };
+        assert_eq!(covering(area, 5), vec!["ezs42", "ezs43", "ezs48", "ezs49"]);
+    }
+
+    #[test]
+    fn test_cells_geojson() {
+        let geojson = cells_geojson(&["s".to_string(), "invalid".to_string()]);
+        let features = geojson["features"].as_array().unwrap();
+        assert_eq!(features.len(), 1);
+        assert_eq!(features[0]["properties"]["geohash"], "s");
+        assert_eq!(
+            features[0]["geometry"]["coordinates"][0],
+            json!([[0.0, 0.0], [45.0, 0.0], [45.0, 45.0], [0.0, 45.0], [0.0, 0.0]])
+        );
+    }
+
+    #[test]
+    fn test_visits_and_aggregate() {
+        // Two points in one cell, one in another, then back to the first
+        let trajectory = Trajectory {
+            latitudes: vec![42_600_000, 42_600_001, 43_000_000, 42_600_000],
+            longitudes: vec![-5_600_000, -5_600_001, -5_600_000, -5_600_000],
+            timestamps: vec![0, 1, 2, 3],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+        let visits = visits(&trajectory, 5);
+        let cells: Vec<(&str, usize)> = visits
+            .iter()
+            .map(|visit| (visit.cell.as_str(), visit.points))
+            .collect();
+        assert_eq!(cells, vec![("ezs42", 2), ("ezsh8", 1), ("ezs42", 1)]);
+
+        let aggregate = aggregate_json(&[("a".to_string(), visits)], 5);
+        assert_eq!(aggregate["precision"], 5);
+        assert_eq!(
+            aggregate["cells"]["ezs42"],
+            json!({ "points": 3, "visits": 2 })
+        );
+        assert_eq!(
+            aggregate["trajectories"][0]["sequence"],
+            json!(["ezs42", "ezsh8", "ezs42"])
+        );
+    }
+}
@@ -0,0 +1,258 @@
+//! GeoJSON input and output of trajectories, to inspect them in geojson.io or QGIS.
+//!
+//! A trajectory is a `Feature` with a `LineString` geometry of `[longitude, latitude]`
+//! positions in degrees, with the altitude in meters as third value when known, and the
+//! Unix timestamps of its points as its `timestamps` property. Several trajectories are one
+//! `Feature` with a `MultiLineString` geometry and one array of timestamps per line.
+
+use crate::geo::{to_degrees, UNITS_PER_DEGREE};
+use crate::trajectory::{CoordinateSystem, Trajectory, ALTITUDE_SCALE};
+use serde_json::{json, Value};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum GeoJsonError {
+    #[error("Unsupported GeoJSON type: {0} (expected LineString or MultiLineString)")]
+    UnsupportedType(String),
+    #[error("Missing or invalid GeoJSON member: {0}")]
+    InvalidMember(&'static str),
+    #[error("Invalid GeoJSON position at index {index}")]
+    InvalidPosition { index: usize },
+    #[error("Expected {expected} timestamps, found {found}")]
+    TimestampCount { expected: usize, found: usize },
+}
+
+/// Altitudes are scaled by 10^`ALTITUDE_SCALE` units per meter
+const ALTITUDE_UNITS_PER_METER: f64 = 1000.0;
+
+const _: () = assert!(ALTITUDE_SCALE == 3, "ALTITUDE_UNITS_PER_METER must match");
+
+/// Positions of the points of the trajectory.
+fn positions(trajectory: &Trajectory) -> Vec<Value> {
+    (0..trajectory.latitudes.len())
+        .map(|i| {
+            let (longitude, latitude) = (
+                to_degrees(trajectory.longitudes[i]),
+                to_degrees(trajectory.latitudes[i]),
+            );
+            match trajectory.altitudes.get(i) {
+                Some(&altitude) => {
+                    json!([
+                        longitude,
+                        latitude,
+                        altitude as f64 / ALTITUDE_UNITS_PER_METER
+                    ])
+                }
+                None => json!([longitude, latitude]),
+            }
+        })
+        .collect()
+}
+
+/// Builds a GeoJSON `Feature` with the trajectory as a `LineString`.
+pub fn to_geojson(trajectory: &Trajectory) -> Value {
+    json!({
+        "type": "Feature",
+        "geometry": { "type": "LineString", "coordinates": positions(trajectory) },
+        "properties": { "timestamps": trajectory.timestamps },
+    })
+}
+
+/// Builds a GeoJSON `Feature` with the trajectories as the lines of a `MultiLineString`.
+pub fn collection_to_geojson(trajectories: &[Trajectory]) -> Value {
+    let lines: Vec<Vec<Value>> = trajectories.iter().map(positions).collect();
+    let timestamps: Vec<&Vec<u64>> = trajectories
+        .iter()
+        .map(|trajectory| &trajectory.timestamps)
+        .collect();
+    json!({
+        "type": "Feature",
+        "geometry": { "type": "MultiLineString", "coordinates": lines },
+        "properties": { "timestamps": timestamps },
+    })
+}
+
+/// Reads one line of positions, with the given timestamps or the index of each point.
+fn line(positions: &Value, timestamps: Option<&Value>) -> Result<Trajectory, GeoJsonError> {
+    let positions = positions
+        .as_array()
+        .ok_or(GeoJsonError::InvalidMember("coordinates"))?;
+    let mut trajectory = Trajectory {
+        latitudes: Vec::with_capacity(positions.len()),
+        longitudes: Vec::with_capacity(positions.len()),
+        timestamps: Vec::with_capacity(positions.len()),
+        altitudes: Vec::with_capacity(positions.len()),
+        coordinates: CoordinateSystem::Wgs84,
+    };
+    for (index, position) in positions.iter().enumerate() {
+        let values: Option<Vec<f64>> = position
+            .as_array()
+            .and_then(|values| values.iter().map(Value::as_f64).collect());
+        let (longitude, latitude, altitude) = match values.as_deref() {
+            Some(&[longitude, latitude]) => (longitude, latitude, None),
+            Some(&[longitude, latitude, altitude, ..]) => (longitude, latitude, Some(altitude)),
+            _ => return Err(GeoJsonError::InvalidPosition { index }),
+        };
+        trajectory
+            .longitudes
+            .push((longitude * UNITS_PER_DEGREE).round() as i64);
+        trajectory
+            .latitudes
+            .push((latitude * UNITS_PER_DEGREE).round() as i64);
+        if let Some(altitude) = altitude {
+            trajectory
+                .altitudes
+                .push((altitude * ALTITUDE_UNITS_PER_METER).round() as i64);
+        }
+    }
+    // Altitudes are kept only when every point has one
+    if trajectory.altitudes.len() != positions.len() {
+        trajectory.altitudes.clear();
+    }
+
+    trajectory.timestamps = match timestamps {
+        Some(timestamps) => timestamps
+            .as_array()
+            .and_then(|timestamps| timestamps.iter().map(Value::as_u64).collect())
+            .ok_or(GeoJsonError::InvalidMember("timestamps"))?,
+        None => (0..positions.len() as u64).collect(),
+    };
+    if trajectory.timestamps.len() != positions.len() {
+        return Err(GeoJsonError::TimestampCount {
+            expected: positions.len(),
+            found: trajectory.timestamps.len(),
+        });
+    }
+    Ok(trajectory)
+}
+
+/// Reads the trajectories of a GeoJSON `LineString` or `MultiLineString` geometry, of a
+/// `Feature` with one, or of every feature of a `FeatureCollection`.
+///
+/// The timestamps of the points are read from the `timestamps` property of the feature,
+/// as written by `to_geojson` and `collection_to_geojson`. Without it, such as for a line
+/// drawn in geojson.io, the points are one second apart from 0.
+pub fn from_geojson(value: &Value) -> Result<Vec<Trajectory>, GeoJsonError> {
+    let kind = value["type"]
+        .as_str()
+        .ok_or(GeoJsonError::InvalidMember("type"))?;
+    let (geometry, properties) = match kind {
+        "FeatureCollection" => {
+            let features = value["features"]
+                .as_array()
+                .ok_or(GeoJsonError::InvalidMember("features"))?;
+            let mut trajectories = Vec::new();
+            for feature in features {
+                trajectories.extend(from_geojson(feature)?);
+            }
+            return Ok(trajectories);
+        }
+        "Feature" => (&value["geometry"], &value["properties"]),
+        _ => (value, &Value::Null),
+    };
+    let timestamps = properties.get("timestamps");
+    let coordinates = &geometry["coordinates"];
+
+    match geometry["type"].as_str() {
+        Some("LineString") => Ok(vec![line(coordinates, timestamps)?]),
+        Some("MultiLineString") => {
+            let lines = coordinates
+                .as_array()
+                .ok_or(GeoJsonError::InvalidMember("coordinates"))?;
+            lines
+                .iter()
+                .enumerate()
+                .map(|(i, positions)| line(positions, timestamps.map(|timestamps| &timestamps[i])))
+                .collect()
+        }
+        Some(kind) => Err(GeoJsonError::UnsupportedType(kind.to_string())),
+        None => Err(GeoJsonError::InvalidMember("type")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trajectory(altitudes: Vec<i64>) -> Trajectory {
+        Trajectory {
+            latitudes: vec![39_900_000, 39_910_000, 39_920_000],
+            longitudes: vec![116_400_000, 116_410_000, 116_420_000],
+            timestamps: vec![100, 160, 220],
+            altitudes,
+            coordinates: CoordinateSystem::Wgs84,
+        }
+    }
+
+    #[test]
+    fn test_to_geojson() {
+        let geojson = to_geojson(&trajectory(Vec::new()));
+        assert_eq!(geojson["geometry"]["type"], "LineString");
+        assert_eq!(
+            geojson["geometry"]["coordinates"][1],
+            json!([116.41, 39.91])
+        );
+        assert_eq!(geojson["properties"]["timestamps"], json!([100, 160, 220]));
+        let geojson = to_geojson(&trajectory(vec![1_500, 2_000, 2_250]));
+        assert_eq!(
+            geojson["geometry"]["coordinates"][2],
+            json!([116.42, 39.92, 2.25])
+        );
+
+        let collection = collection_to_geojson(&[trajectory(Vec::new()), trajectory(Vec::new())]);
+        assert_eq!(collection["geometry"]["type"], "MultiLineString");
+        assert_eq!(
+            collection["properties"]["timestamps"][1],
+            json!([100, 160, 220])
+        );
+    }
+
+    #[test]
+    fn test_from_geojson() {
+        for original in [
+            trajectory(Vec::new()),
+            trajectory(vec![1_500, 2_000, 2_250]),
+        ] {
+            let trajectories = from_geojson(&to_geojson(&original)).unwrap();
+            assert_eq!(trajectories, std::slice::from_ref(&original));
+            let collection = collection_to_geojson(&[original.clone(), original.clone()]);
+            assert_eq!(
+                from_geojson(&collection).unwrap(),
+                [original.clone(), original]
+            );
+        }
+
+        // A line drawn in geojson.io, without timestamps, in a collection
+        let drawn = json!({
+            "type": "FeatureCollection",
+            "features": [{
+                "type": "Feature",
+                "properties": {},
+                "geometry": { "type": "LineString", "coordinates": [[2.35, 48.85], [2.36, 48.86]] },
+            }],
+        });
+        let trajectories = from_geojson(&drawn).unwrap();
+        assert_eq!(trajectories[0].latitudes, [48_850_000, 48_860_000]);
+        assert_eq!(trajectories[0].timestamps, [0, 1]);
+
+        let point = json!({ "type": "Point", "coordinates": [2.35, 48.85] });
+        assert_eq!(
+            from_geojson(&point),
+            Err(GeoJsonError::UnsupportedType("Point".to_string()))
+        );
+        let invalid = json!({ "type": "LineString", "coordinates": [[2.35, 48.85], [2.36]] });
+        assert_eq!(
+            from_geojson(&invalid),
+            Err(GeoJsonError::InvalidPosition { index: 1 })
+        );
+        let mut short = to_geojson(&trajectory(Vec::new()));
+        short["properties"]["timestamps"] = json!([1]);
+        assert_eq!(
+            from_geojson(&short),
+            Err(GeoJsonError::TimestampCount {
+                expected: 3,
+                found: 1
+            })
+        );
+    }
+}
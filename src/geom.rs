@@ -0,0 +1,439 @@
+//! Computational geometry on sequences of points: convex hull, farthest pair and minimal
+//! enclosing circle.
+//!
+//! Like `simplify`, the functions take the coordinates as two slices and return indices
+//! into them. Hull and farthest pair are computed exactly on the integer coordinates.
+
+use crate::geo::{to_degrees, METERS_PER_UNIT};
+use crate::trajectory::Trajectory;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use serde_json::{json, Value};
+
+/// Twice the signed area of the triangle `(a, b, c)`, positive if it turns counter-clockwise.
+fn cross(a: (i64, i64), b: (i64, i64), c: (i64, i64)) -> i128 {
+    let (abx, aby) = ((b.0 - a.0) as i128, (b.1 - a.1) as i128);
+    let (acx, acy) = ((c.0 - a.0) as i128, (c.1 - a.1) as i128);
+    abx * acy - aby * acx
+}
+
+fn distance_squared(a: (i64, i64), b: (i64, i64)) -> i128 {
+    let (dx, dy) = ((b.0 - a.0) as i128, (b.1 - a.1) as i128);
+    dx * dx + dy * dy
+}
+
+/// Computes the convex hull with Andrew's monotone chain.
+///
+/// # Returns
+///
+/// The indices of the hull vertices in counter-clockwise order, starting from the lowest
+/// `x` (then lowest `y`). Points on the edges of the hull and repeated positions are left
+/// out, so a single position gives one vertex and collinear points give two.
+///
+/// # Panics
+///
+/// This function will panic if `positions_x` and `positions_y` have different lengths.
+pub fn convex_hull(positions_x: &[i64], positions_y: &[i64]) -> Vec<usize> {
+    assert_eq!(
+        positions_x.len(),
+        positions_y.len(),
+        "positions_x.len() == positions_y.len()"
+    );
+
+    let point = |i: usize| (positions_x[i], positions_y[i]);
+    let mut order: Vec<usize> = (0..positions_x.len()).collect();
+    order.sort_by_key(|&i| point(i));
+    order.dedup_by_key(|i| point(*i));
+    if order.len() <= 2 {
+        return order;
+    }
+
+    // Lower chain from left to right, then upper chain from right to left. The last point
+    // of each chain is the first of the other one.
+    let mut hull = half_hull(order.iter().copied(), point);
+    hull.pop();
+    let mut upper = half_hull(order.iter().rev().copied(), point);
+    upper.pop();
+    hull.append(&mut upper);
+    hull
+}
+
+/// Keeps the points of a sorted sequence that make the chain turn left.
+fn half_hull(
+    points: impl Iterator<Item = usize>,
+    point: impl Fn(usize) -> (i64, i64),
+) -> Vec<usize> {
+    let mut chain: Vec<usize> = Vec::new();
+    for i in points {
+        while let [.., a, b] = chain[..] {
+            if cross(point(a), point(b), point(i)) > 0 {
+                break;
+            }
+            chain.pop();
+        }
+        chain.push(i);
+    }
+    chain
+}
+
+/// Finds the two points farthest apart with rotating calipers on the convex hull.
+///
+/// # Returns
+///
+/// Their indices in increasing order, or `None` if there are no points. A single position
+/// gives the first point twice.
+///
+/// # Panics
+///
+/// This function will panic if `positions_x` and `positions_y` have different lengths.
+pub fn farthest_pair(positions_x: &[i64], positions_y: &[i64]) -> Option<(usize, usize)> {
+    let hull = convex_hull(positions_x, positions_y);
+    let point = |i: usize| (positions_x[hull[i]], positions_y[hull[i]]);
+    let ordered = |a: usize, b: usize| (a.min(b), a.max(b));
+
+    match hull.len() {
+        0 => return None,
+        1 => return Some((hull[0], hull[0])),
+        2 => return Some(ordered(hull[0], hull[1])),
+        _ => {}
+    }
+
+    let len = hull.len();
+    let mut best = (0, hull[0], hull[1]);
+    let mut j = 1;
+    for i in 0..len {
+        let next = (i + 1) % len;
+        // Advance the opposite caliper while it moves away from the edge (i, next)
+        while cross(point(i), point(next), point((j + 1) % len))
+            > cross(point(i), point(next), point(j))
+        {
+            j = (j + 1) % len;
+        }
+        for candidate in [i, next] {
+            let distance = distance_squared(point(candidate), point(j));
+            if distance > best.0 {
+                best = (distance, hull[candidate], hull[j]);
+            }
+        }
+    }
+    Some(ordered(best.1, best.2))
+}
+
+/// A circle in the coordinate space of the points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Circle {
+    pub center_x: f64,
+    pub center_y: f64,
+    pub radius: f64,
+}
+
+impl Circle {
+    fn through_two(a: (f64, f64), b: (f64, f64)) -> Circle {
+        let (center_x, center_y) = ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0);
+        Circle {
+            center_x,
+            center_y,
+            radius: (a.0 - center_x).hypot(a.1 - center_y),
+        }
+    }
+
+    fn through_three(a: (f64, f64), b: (f64, f64), c: (f64, f64)) -> Circle {
+        let (bx, by) = (b.0 - a.0, b.1 - a.1);
+        let (cx, cy) = (c.0 - a.0, c.1 - a.1);
+        let d = 2.0 * (bx * cy - by * cx);
+        if d == 0.0 {
+            // Collinear, which only happens through rounding: the farthest two points define it
+            return [(a, b), (a, c), (b, c)]
+                .into_iter()
+                .map(|(p, q)| Circle::through_two(p, q))
+                .max_by(|p, q| p.radius.total_cmp(&q.radius))
+                .unwrap();
+        }
+        let b_squared = bx * bx + by * by;
+        let c_squared = cx * cx + cy * cy;
+        let ux = (cy * b_squared - by * c_squared) / d;
+        let uy = (bx * c_squared - cx * b_squared) / d;
+        Circle {
+            center_x: a.0 + ux,
+            center_y: a.1 + uy,
+            radius: ux.hypot(uy),
+        }
+    }
+
+    fn contains(&self, (x, y): (f64, f64)) -> bool {
+        (x - self.center_x).hypot(y - self.center_y) <= self.radius * (1.0 + 1e-12) + 1e-9
+    }
+}
+
+/// Computes the smallest circle containing every point, with Welzl's algorithm.
+///
+/// Only hull vertices are considered, in a shuffled order drawn from a fixed seed so the
+/// result is reproducible.
+///
+/// # Returns
+///
+/// The circle, or `None` if there are no points.
+///
+/// # Panics
+///
+/// This function will panic if `positions_x` and `positions_y` have different lengths.
+pub fn enclosing_circle(positions_x: &[i64], positions_y: &[i64]) -> Option<Circle> {
+    let mut points: Vec<(f64, f64)> = convex_hull(positions_x, positions_y)
+        .into_iter()
+        .map(|i| (positions_x[i] as f64, positions_y[i] as f64))
+        .collect();
+    points.shuffle(&mut StdRng::seed_from_u64(0));
+
+    let &first = points.first()?;
+    let mut circle = Circle {
+        center_x: first.0,
+        center_y: first.1,
+        radius: 0.0,
+    };
+    for i in 1..points.len() {
+        if circle.contains(points[i]) {
+            continue;
+        }
+        // points[i] is on the boundary of the circle enclosing points[..=i]
+        circle = Circle {
+            center_x: points[i].0,
+            center_y: points[i].1,
+            radius: 0.0,
+        };
+        for j in 0..i {
+            if circle.contains(points[j]) {
+                continue;
+            }
+            circle = Circle::through_two(points[i], points[j]);
+            for k in 0..j {
+                if !circle.contains(points[k]) {
+                    circle = Circle::through_three(points[i], points[j], points[k]);
+                }
+            }
+        }
+    }
+    Some(circle)
+}
+
+/// Spatial extent of a trajectory.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Extent {
+    /// Distance in meters between the two points farthest apart
+    pub diameter_m: f64,
+    /// Radius in meters of the smallest circle containing every point
+    pub radius_m: f64,
+}
+
+/// Projects the points to scaled units along a meridian, with `x` east and `y` north.
+///
+/// Longitudes are scaled by the cosine of the mean latitude, an equirectangular projection
+/// accurate for trajectories spanning up to a few hundred kilometers.
+fn project(trajectory: &Trajectory) -> (Vec<i64>, &[i64]) {
+    let len = trajectory.latitudes.len().max(1);
+    let mean_latitude = trajectory
+        .latitudes
+        .iter()
+        .map(|&lat| lat as f64)
+        .sum::<f64>()
+        / len as f64;
+    let cos_lat = to_degrees(mean_latitude as i64).to_radians().cos();
+    let xs = trajectory
+        .longitudes
+        .iter()
+        .map(|&lon| (lon as f64 * cos_lat).round() as i64)
+        .collect();
+    (xs, &trajectory.latitudes)
+}
+
+/// Distance in meters between the two points of a trajectory farthest apart, or `None` if
+/// it has no points.
+pub fn diameter_m(trajectory: &Trajectory) -> Option<f64> {
+    let (xs, ys) = project(trajectory);
+    let (a, b) = farthest_pair(&xs, ys)?;
+    Some((distance_squared((xs[a], ys[a]), (xs[b], ys[b])) as f64).sqrt() * METERS_PER_UNIT)
+}
+
+/// Measures the extent of a trajectory, or returns `None` if it has no points.
+pub fn extent(trajectory: &Trajectory) -> Option<Extent> {
+    let (xs, ys) = project(trajectory);
+    let (a, b) = farthest_pair(&xs, ys)?;
+    let diameter = distance_squared((xs[a], ys[a]), (xs[b], ys[b])) as f64;
+    let circle = enclosing_circle(&xs, ys)?;
+    Some(Extent {
+        diameter_m: diameter.sqrt() * METERS_PER_UNIT,
+        radius_m: circle.radius * METERS_PER_UNIT,
+    })
+}
+
+/// Area in square meters of the convex hull of a trajectory.
+pub fn hull_area_m2(trajectory: &Trajectory) -> f64 {
+    let (xs, ys) = project(trajectory);
+    let hull = trajectory.convex_hull();
+    let twice_area: i128 = (0..hull.len())
+        .map(|i| {
+            let (a, b) = (hull[i], hull[(i + 1) % hull.len()]);
+            xs[a] as i128 * ys[b] as i128 - xs[b] as i128 * ys[a] as i128
+        })
+        .sum();
+    twice_area.abs() as f64 / 2.0 * METERS_PER_UNIT * METERS_PER_UNIT
+}
+
+/// Builds a GeoJSON `Feature` with the convex hull of a trajectory as a `Polygon`, and its
+/// number of points and area as properties.
+///
+/// The ring is counter-clockwise and closed, as RFC 7946 requires. Returns `None` if the
+/// hull has less than three vertices, which do not make a polygon.
+pub fn hull_geojson(trajectory: &Trajectory) -> Option<Value> {
+    let hull = trajectory.convex_hull();
+    if hull.len() < 3 {
+        return None;
+    }
+    let ring: Vec<Value> = hull
+        .iter()
+        .chain(hull.first())
+        .map(|&i| {
+            json!([
+                to_degrees(trajectory.longitudes[i]),
+                to_degrees(trajectory.latitudes[i])
+            ])
+        })
+        .collect();
+
+    Some(json!({
+        "type": "Feature",
+        "geometry": { "type": "Polygon", "coordinates": [ring] },
+        "properties": {
+            "points": trajectory.latitudes.len(),
+            "hull_vertices": hull.len(),
+            "area_m2": hull_area_m2(trajectory),
+        },
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trajectory::CoordinateSystem;
+    use rand::Rng;
+
+    fn random_points(len: usize, seed: u64) -> (Vec<i64>, Vec<i64>) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..len)
+            .map(|_| (rng.gen_range(-1000..1000), rng.gen_range(-1000..1000)))
+            .unzip()
+    }
+
+    #[test]
+    fn test_convex_hull() {
+        // Square with an interior point, a point on an edge and a repeated corner
+        let xs = [0, 10, 10, 0, 5, 5, 10];
+        let ys = [0, 0, 10, 10, 5, 0, 10];
+        assert_eq!(convex_hull(&xs, &ys), vec![0, 1, 2, 3]);
+
+        assert_eq!(convex_hull(&[], &[]), Vec::<usize>::new());
+        assert_eq!(convex_hull(&[3, 3], &[4, 4]), vec![0]);
+        assert_eq!(convex_hull(&[0, 2, 1], &[0, 2, 1]), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_farthest_pair() {
+        for seed in 0..20 {
+            let (xs, ys) = random_points(200, seed);
+            let mut expected = 0;
+            for i in 0..xs.len() {
+                for j in i + 1..xs.len() {
+                    expected = expected.max(distance_squared((xs[i], ys[i]), (xs[j], ys[j])));
+                }
+            }
+
+            let (a, b) = farthest_pair(&xs, &ys).unwrap();
+            assert!(a < b);
+            assert_eq!(distance_squared((xs[a], ys[a]), (xs[b], ys[b])), expected);
+        }
+
+        assert_eq!(farthest_pair(&[], &[]), None);
+        assert_eq!(farthest_pair(&[7], &[7]), Some((0, 0)));
+        assert_eq!(farthest_pair(&[0, 1, 5], &[0, 0, 0]), Some((0, 2)));
+    }
+
+    #[test]
+    fn test_enclosing_circle() {
+        let circle = enclosing_circle(&[0, 10, 10, 0, 5], &[0, 0, 10, 10, 3]).unwrap();
+        assert!((circle.center_x - 5.0).abs() < 1e-9);
+        assert!((circle.center_y - 5.0).abs() < 1e-9);
+        assert!((circle.radius - 50_f64.sqrt()).abs() < 1e-9);
+
+        for seed in 0..20 {
+            let (xs, ys) = random_points(200, seed);
+            let circle = enclosing_circle(&xs, &ys).unwrap();
+            let (a, b) = farthest_pair(&xs, &ys).unwrap();
+            let diameter = (distance_squared((xs[a], ys[a]), (xs[b], ys[b])) as f64).sqrt();
+
+            assert!(xs
+                .iter()
+                .zip(&ys)
+                .all(|(&x, &y)| circle.contains((x as f64, y as f64))));
+            // Jung's theorem bounds the radius by the diameter
+            assert!(circle.radius >= diameter / 2.0 - 1e-9);
+            assert!(circle.radius <= diameter / 3_f64.sqrt() + 1e-9);
+        }
+
+        assert_eq!(enclosing_circle(&[], &[]), None);
+        assert_eq!(enclosing_circle(&[4], &[2]).unwrap().radius, 0.0);
+    }
+
+    #[test]
+    fn test_extent() {
+        // 0.01 degree of latitude, about 1.1 km
+        let trajectory = Trajectory {
+            latitudes: vec![40_000_000, 40_005_000, 40_010_000],
+            longitudes: vec![116_000_000; 3],
+            timestamps: vec![0, 1, 2],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+        let extent = extent(&trajectory).unwrap();
+        assert!((extent.diameter_m - 1112.0).abs() < 1.0, "{extent:?}");
+        assert!((extent.radius_m - 556.0).abs() < 1.0, "{extent:?}");
+    }
+
+    #[test]
+    fn test_hull_geojson() {
+        // A square of 0.01 degree at the equator, about 1.1 km wide, with its center
+        let trajectory = Trajectory {
+            latitudes: vec![0, 0, 10_000, 10_000, 5_000],
+            longitudes: vec![0, 10_000, 10_000, 0, 5_000],
+            timestamps: vec![0, 1, 2, 3, 4],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+        let feature = hull_geojson(&trajectory).unwrap();
+
+        assert_eq!(feature["geometry"]["type"], "Polygon");
+        assert_eq!(
+            feature["geometry"]["coordinates"],
+            json!([[
+                [0.0, 0.0],
+                [0.01, 0.0],
+                [0.01, 0.01],
+                [0.0, 0.01],
+                [0.0, 0.0]
+            ]])
+        );
+        assert_eq!(feature["properties"]["points"], 5);
+        assert_eq!(feature["properties"]["hull_vertices"], 4);
+        let area = feature["properties"]["area_m2"].as_f64().unwrap();
+        assert!((area - 1111.95_f64.powi(2)).abs() < 100.0, "{area}");
+
+        let line = Trajectory {
+            latitudes: vec![0, 1, 2],
+            longitudes: vec![0, 1, 2],
+            timestamps: vec![0, 1, 2],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+        assert_eq!(hull_geojson(&line), None);
+        assert_eq!(hull_area_m2(&line), 0.0);
+    }
+}
@@ -0,0 +1,510 @@
+//! Map matching through an OSRM (`/match`) or Valhalla (`/trace_route`) server, as an
+//! alternative to loading a `RoadNetwork` for areas too large to fit in memory.
+//!
+//! Snappable points are sent in batches of at most `batch_size` points through an
+//! `EnrichmentClient`, which retries and caches requests. Only plain `http://` servers are
+//! supported.
+
+use crate::enrich::{EnrichOptions, EnrichmentClient, HttpRequest};
+use crate::geo::to_degrees;
+use crate::matching::{timestamps_along, MatchError, Matcher};
+use crate::polyline;
+use crate::trajectory::{CoordinateSystem, Trajectory};
+use hyper::Method;
+use serde_json::{json, Value};
+
+/// Number of points sent per request, the default limit of OSRM's `/match`
+pub const DEFAULT_BATCH_SIZE: usize = 100;
+
+/// A map matching server API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Service {
+    Osrm,
+    Valhalla,
+}
+
+/// A `Matcher` sending trajectories to a map matching server.
+pub struct HttpMatcher {
+    pub service: Service,
+    /// URL of the server, such as `http://localhost:5000`
+    pub base_url: String,
+    pub batch_size: usize,
+    client: EnrichmentClient,
+}
+
+/// Appends the points of `batch` in `range` to `output`.
+fn push_raw(output: &mut Trajectory, batch: &Trajectory, range: std::ops::Range<usize>) {
+    output
+        .latitudes
+        .extend_from_slice(&batch.latitudes[range.clone()]);
+    output
+        .longitudes
+        .extend_from_slice(&batch.longitudes[range.clone()]);
+    output
+        .timestamps
+        .extend_from_slice(&batch.timestamps[range]);
+}
+
+/// Appends a matched path driven from `start_ts` to `end_ts` to `output`.
+fn push_path(output: &mut Trajectory, path: &[(i64, i64)], start_ts: u64, end_ts: u64) {
+    for (&(latitude, longitude), ts) in path.iter().zip(timestamps_along(path, start_ts, end_ts)) {
+        output.latitudes.push(latitude);
+        output.longitudes.push(longitude);
+        output.timestamps.push(ts);
+    }
+}
+
+/// Decodes a polyline6 geometry into `(latitude, longitude)` pairs.
+fn decode_path(value: &Value) -> Result<Vec<(i64, i64)>, MatchError> {
+    let encoded = value
+        .as_str()
+        .ok_or(MatchError::InvalidResponse("geometry is not a polyline"))?;
+    let (latitudes, longitudes) = polyline::decode(encoded)
+        .map_err(|_| MatchError::InvalidResponse("geometry is not a polyline"))?;
+    Ok(latitudes.into_iter().zip(longitudes).collect())
+}
+
+impl HttpMatcher {
+    /// Creates a matcher for the server at `base_url`, with the default batch size.
+    pub fn new(
+        service: Service,
+        base_url: &str,
+        options: EnrichOptions,
+    ) -> Result<Self, MatchError> {
+        if !base_url.starts_with("http://") {
+            return Err(MatchError::InvalidService(format!(
+                "{base_url} is not an http:// URL"
+            )));
+        }
+
+        Ok(HttpMatcher {
+            service,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            batch_size: DEFAULT_BATCH_SIZE,
+            client: EnrichmentClient::new(options)?,
+        })
+    }
+
+    /// Parses an `osrm:<url>` or `valhalla:<url>` command line argument, returning `None`
+    /// for anything else.
+    pub fn from_spec(spec: &str, options: EnrichOptions) -> Result<Option<Self>, MatchError> {
+        let service = match spec.split_once(':') {
+            Some(("osrm", url)) => (Service::Osrm, url),
+            Some(("valhalla", url)) => (Service::Valhalla, url),
+            _ => return Ok(None),
+        };
+        Self::new(service.0, service.1, options).map(Some)
+    }
+
+    /// Builds the request matching `batch`.
+    fn request(&self, batch: &Trajectory, radius_m: f64) -> HttpRequest {
+        let points = batch.latitudes.iter().zip(&batch.longitudes);
+        match self.service {
+            Service::Osrm => {
+                let join = |values: Vec<String>| values.join(";");
+                let coordinates = join(
+                    points
+                        .map(|(&lat, &lon)| {
+                            format!("{:.6},{:.6}", to_degrees(lon), to_degrees(lat))
+                        })
+                        .collect(),
+                );
+                let timestamps = join(batch.timestamps.iter().map(u64::to_string).collect());
+                let radiuses = join(vec![radius_m.to_string(); batch.timestamps.len()]);
+                HttpRequest {
+                    method: Method::GET,
+                    url: format!(
+                        "{}/match/v1/driving/{coordinates}?geometries=polyline6&overview=full\
+                         &timestamps={timestamps}&radiuses={radiuses}",
+                        self.base_url
+                    ),
+                    body: None,
+                }
+            }
+            Service::Valhalla => {
+                let shape: Vec<Value> = points
+                    .zip(&batch.timestamps)
+                    .map(|((&lat, &lon), &ts)| {
+                        json!({"lat": to_degrees(lat), "lon": to_degrees(lon), "time": ts})
+                    })
+                    .collect();
+                let body = json!({
+                    "shape": shape,
+                    "costing": "auto",
+                    "shape_match": "map_snap",
+                    "trace_options": {"search_radius": radius_m},
+                });
+                HttpRequest {
+                    method: Method::POST,
+                    url: format!("{}/trace_route", self.base_url),
+                    body: Some(body.to_string()),
+                }
+            }
+        }
+    }
+
+    /// Converts a response to the matched batch.
+    fn parse_response(
+        &self,
+        batch: &Trajectory,
+        response: &Value,
+    ) -> Result<Trajectory, MatchError> {
+        let mut matched = Trajectory {
+            latitudes: Vec::new(),
+            longitudes: Vec::new(),
+            timestamps: Vec::new(),
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+        let len = batch.timestamps.len();
+
+        match self.service {
+            Service::Osrm => {
+                if response["code"] == "NoMatch" {
+                    return Ok(batch.clone());
+                }
+                let matchings = response["matchings"]
+                    .as_array()
+                    .ok_or(MatchError::InvalidResponse("missing matchings"))?;
+                let tracepoints = response["tracepoints"]
+                    .as_array()
+                    .filter(|tracepoints| tracepoints.len() == len)
+                    .ok_or(MatchError::InvalidResponse("missing tracepoints"))?;
+
+                // First and last point covered by each matching
+                let mut ranges = vec![None; matchings.len()];
+                let matching_of = |i: usize| {
+                    tracepoints[i]["matchings_index"]
+                        .as_u64()
+                        .map(|index| index as usize)
+                        .filter(|&index| index < matchings.len())
+                };
+                for i in 0..len {
+                    if let Some(index) = matching_of(i) {
+                        let range: &mut Option<(usize, usize)> = &mut ranges[index];
+                        *range = Some(range.map_or((i, i), |(first, _)| (first, i)));
+                    }
+                }
+
+                let mut i = 0;
+                while i < len {
+                    match matching_of(i).and_then(|index| Some((index, ranges[index]?))) {
+                        Some((index, (first, last))) if first == i => {
+                            let path = decode_path(&matchings[index]["geometry"])?;
+                            push_path(
+                                &mut matched,
+                                &path,
+                                batch.timestamps[first],
+                                batch.timestamps[last],
+                            );
+                            i = last + 1;
+                        }
+                        _ => {
+                            push_raw(&mut matched, batch, i..i + 1);
+                            i += 1;
+                        }
+                    }
+                }
+            }
+            Service::Valhalla => {
+                let legs = response["trip"]["legs"]
+                    .as_array()
+                    .ok_or(MatchError::InvalidResponse("missing trip legs"))?;
+                let mut path: Vec<(i64, i64)> = Vec::new();
+                for leg in legs {
+                    let shape = decode_path(&leg["shape"])?;
+                    // Consecutive legs share their junction point
+                    let skip = usize::from(path.last().is_some() && path.last() == shape.first());
+                    path.extend(&shape[skip..]);
+                }
+                push_path(
+                    &mut matched,
+                    &path,
+                    batch.timestamps[0],
+                    batch.timestamps[len - 1],
+                );
+            }
+        }
+
+        Ok(matched)
+    }
+}
+
+impl Matcher for HttpMatcher {
+    /// Sends each run of snappable points in batches, and replaces every batch with the
+    /// matched geometry, its timestamps interpolated along the road between the first and
+    /// last points matched.
+    fn match_trajectory(
+        &self,
+        trajectory: &Trajectory,
+        snappable: &[bool],
+        radius_m: f64,
+    ) -> Result<Trajectory, MatchError> {
+        let len = trajectory.timestamps.len();
+        assert_eq!(snappable.len(), len);
+        let mut matched = Trajectory {
+            latitudes: Vec::with_capacity(len),
+            longitudes: Vec::with_capacity(len),
+            timestamps: Vec::with_capacity(len),
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+
+        // Runs of points that are all snappable or all not, cut to the batch size
+        let mut batches = Vec::new();
+        let mut start = 0;
+        while start < len {
+            let run = snappable[start..]
+                .iter()
+                .take_while(|&&value| value == snappable[start])
+                .count();
+            let end = (start + run).min(start + self.batch_size.max(2));
+            let batch = Trajectory {
+                latitudes: trajectory.latitudes[start..end].to_vec(),
+                longitudes: trajectory.longitudes[start..end].to_vec(),
+                timestamps: trajectory.timestamps[start..end].to_vec(),
+                altitudes: Vec::new(),
+                coordinates: trajectory.coordinates,
+            };
+            // Matching needs at least two points
+            batches.push((snappable[start] && end - start >= 2, batch));
+            start = end;
+        }
+
+        let sent: Vec<usize> = (0..batches.len())
+            .filter(|&index| batches[index].0)
+            .collect();
+        let requests = sent
+            .iter()
+            .map(|&index| self.request(&batches[index].1, radius_m))
+            .collect();
+        let mut responses = sent
+            .into_iter()
+            .zip(self.client.fetch_all(requests))
+            .peekable();
+        for (index, (_, batch)) in batches.iter().enumerate() {
+            match responses.next_if(|(sent, _)| *sent == index) {
+                Some((_, response)) => {
+                    let response = response?;
+                    let body: Option<Value> = serde_json::from_str(&response.body).ok();
+                    let matched_batch = match (response.status, body) {
+                        (200, Some(body)) => self.parse_response(batch, &body)?,
+                        // OSRM answers 400 with code `NoMatch` when no road fits the points
+                        (400, Some(body))
+                            if self.service == Service::Osrm && body["code"] == "NoMatch" =>
+                        {
+                            batch.clone()
+                        }
+                        (200, None) => {
+                            return Err(MatchError::InvalidResponse("body is not JSON"));
+                        }
+                        (status, _) => return Err(MatchError::Status(status)),
+                    };
+                    push_raw(
+                        &mut matched,
+                        &matched_batch,
+                        0..matched_batch.timestamps.len(),
+                    );
+                }
+                None => push_raw(&mut matched, batch, 0..batch.timestamps.len()),
+            }
+        }
+
+        Ok(matched)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enrich::tests::{fast_retries, response, serve};
+
+    fn trajectory(points: &[(i64, i64, u64)]) -> Trajectory {
+        Trajectory {
+            latitudes: points.iter().map(|p| p.0).collect(),
+            longitudes: points.iter().map(|p| p.1).collect(),
+            timestamps: points.iter().map(|p| p.2).collect(),
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        }
+    }
+
+    fn batch() -> Trajectory {
+        trajectory(&[
+            (40_000_100, 116_000_000, 0),
+            (40_000_100, 116_001_000, 10),
+            (40_000_100, 116_002_000, 20),
+            (40_050_000, 116_050_000, 30),
+        ])
+    }
+
+    /// An OSRM response matching the first three points of `batch` onto latitude 40.
+    fn osrm_response() -> Value {
+        let geometry = polyline::encode(&[40_000_000; 3], &[116_000_000, 116_001_000, 116_002_000]);
+        json!({
+            "code": "Ok",
+            "matchings": [{"geometry": geometry}],
+            "tracepoints": [
+                {"matchings_index": 0, "waypoint_index": 0},
+                {"matchings_index": 0, "waypoint_index": 1},
+                {"matchings_index": 0, "waypoint_index": 2},
+                null
+            ]
+        })
+    }
+
+    #[test]
+    fn test_from_spec() {
+        let matcher = HttpMatcher::from_spec("osrm:http://localhost:5000/", fast_retries())
+            .unwrap()
+            .unwrap();
+        assert_eq!(matcher.service, Service::Osrm);
+        assert_eq!(matcher.base_url, "http://localhost:5000");
+        let matcher = HttpMatcher::from_spec("valhalla:http://localhost:8002", fast_retries())
+            .unwrap()
+            .unwrap();
+        assert_eq!(matcher.service, Service::Valhalla);
+
+        assert!(HttpMatcher::from_spec("roads.geojson", fast_retries())
+            .unwrap()
+            .is_none());
+        assert!(matches!(
+            HttpMatcher::from_spec("osrm:https://router.example", fast_retries()),
+            Err(MatchError::InvalidService(_))
+        ));
+    }
+
+    #[test]
+    fn test_osrm_request() {
+        let matcher =
+            HttpMatcher::new(Service::Osrm, "http://localhost:5000", fast_retries()).unwrap();
+        let request = matcher.request(&batch(), 25.0);
+        assert_eq!(request.method, Method::GET);
+        assert_eq!(
+            request.url,
+            "http://localhost:5000/match/v1/driving/\
+             116.000000,40.000100;116.001000,40.000100;116.002000,40.000100;116.050000,40.050000\
+             ?geometries=polyline6&overview=full&timestamps=0;10;20;30&radiuses=25;25;25;25"
+        );
+    }
+
+    #[test]
+    fn test_parse_osrm_response() {
+        let matcher =
+            HttpMatcher::new(Service::Osrm, "http://localhost:5000", fast_retries()).unwrap();
+        let matched = matcher.parse_response(&batch(), &osrm_response()).unwrap();
+        assert_eq!(
+            matched,
+            trajectory(&[
+                (40_000_000, 116_000_000, 0),
+                (40_000_000, 116_001_000, 10),
+                (40_000_000, 116_002_000, 20),
+                (40_050_000, 116_050_000, 30),
+            ])
+        );
+
+        let no_match = json!({"code": "NoMatch"});
+        assert_eq!(
+            matcher.parse_response(&batch(), &no_match).unwrap(),
+            batch()
+        );
+        assert!(matches!(
+            matcher.parse_response(&batch(), &json!({"code": "Ok"})),
+            Err(MatchError::InvalidResponse(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_valhalla_response() {
+        let matcher =
+            HttpMatcher::new(Service::Valhalla, "http://localhost:8002", fast_retries()).unwrap();
+        let response = json!({"trip": {"legs": [
+            {"shape": polyline::encode(&[40_000_000, 40_000_000], &[116_000_000, 116_001_000])},
+            {"shape": polyline::encode(&[40_000_000, 40_000_000], &[116_001_000, 116_003_000])},
+        ]}});
+        let matched = matcher.parse_response(&batch(), &response).unwrap();
+        assert_eq!(
+            matched.longitudes,
+            vec![116_000_000, 116_001_000, 116_003_000]
+        );
+        assert_eq!(matched.timestamps, vec![0, 10, 30]);
+    }
+
+    #[test]
+    fn test_retries_server_errors() {
+        let (address, handle) = serve(vec![
+            response("503 Service Unavailable", ""),
+            response("200 OK", &osrm_response().to_string()),
+        ]);
+        let matcher = HttpMatcher::new(Service::Osrm, &address, fast_retries()).unwrap();
+
+        let input = batch();
+        let matched = matcher.match_trajectory(&input, &[true; 4], 25.0).unwrap();
+        assert_eq!(
+            matched.latitudes,
+            vec![40_000_000, 40_000_000, 40_000_000, 40_050_000]
+        );
+        assert_eq!(handle.join().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_does_not_retry_client_errors() {
+        let (address, handle) = serve(vec![response("404 Not Found", "")]);
+        let matcher = HttpMatcher::new(Service::Osrm, &address, fast_retries()).unwrap();
+
+        let result = matcher.match_trajectory(&batch(), &[true; 4], 25.0);
+        assert!(matches!(result, Err(MatchError::Status(404))));
+        assert_eq!(handle.join().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_no_match_keeps_points() {
+        let body = json!({"code": "NoMatch"}).to_string();
+        let (address, handle) = serve(vec![response("400 Bad Request", &body)]);
+        let matcher = HttpMatcher::new(Service::Osrm, &address, fast_retries()).unwrap();
+
+        let matched = matcher
+            .match_trajectory(&batch(), &[true; 4], 25.0)
+            .unwrap();
+        assert_eq!(matched, batch());
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_batches_and_skips_unsnappable_points() {
+        let ok = |body: Value| response("200 OK", &body.to_string());
+        let one_matching = |geometry: (&[i64], &[i64])| {
+            json!({
+                "code": "Ok",
+                "matchings": [{"geometry": polyline::encode(geometry.0, geometry.1)}],
+                "tracepoints": [{"matchings_index": 0}, {"matchings_index": 0}]
+            })
+        };
+        let (address, handle) = serve(vec![
+            ok(one_matching((&[1, 1], &[0, 10]))),
+            ok(one_matching((&[1, 1], &[20, 30]))),
+        ]);
+        let mut matcher = HttpMatcher::new(
+            Service::Osrm,
+            &address,
+            EnrichOptions {
+                concurrency: 1,
+                ..fast_retries()
+            },
+        )
+        .unwrap();
+        matcher.batch_size = 2;
+
+        let input = trajectory(&[(0, 0, 0), (0, 10, 1), (0, 20, 2), (0, 30, 3), (5, 40, 4)]);
+        let matched = matcher
+            .match_trajectory(&input, &[true, true, true, true, false], 25.0)
+            .unwrap();
+        assert_eq!(
+            matched,
+            trajectory(&[(1, 0, 0), (1, 10, 1), (1, 20, 2), (1, 30, 3), (5, 40, 4)])
+        );
+        let requests = handle.join().unwrap();
+        assert!(requests[0].contains("/match/v1/driving/0.000000,0.000000;0.000010,0.000000?"));
+        assert!(requests[1].contains("/match/v1/driving/0.000020,0.000000;0.000030,0.000000?"));
+    }
+}
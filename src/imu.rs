@@ -0,0 +1,372 @@
+//! Dead reckoning through GPS outages, such as tunnels, from inertial samples.
+//!
+//! Inside a gap between two fixes, the speed is integrated from the forward acceleration
+//! starting at the speed before the gap, and the position from the speed along the
+//! heading. The drift accumulated by the end of the gap is then spread linearly over
+//! time, so the reckoned path ends on the fix after the gap.
+
+use crate::geo::{to_degrees, LocalProjection, UNITS_PER_DEGREE};
+use crate::trajectory::{CoordinateSystem, Trajectory};
+use std::io::{self, BufRead};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ImuError {
+    #[error("Error while reading line from file: {0}")]
+    Io(#[from] io::Error),
+    #[error("Invalid number of fields in line")]
+    InvalidFieldCount,
+    #[error("Failed to parse IMU sample: {0}")]
+    Parse(String),
+}
+
+/// Inertial sample, held until the next one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImuSample {
+    /// Unix timestamp in milliseconds
+    pub timestamp_ms: u64,
+    /// Acceleration along the direction of travel in m/s², negative when braking
+    pub acceleration_mps2: f64,
+    /// Heading in degrees, clockwise from north
+    pub heading_deg: f64,
+}
+
+/// Parses a CSV file of IMU samples, with a header line then one
+/// `timestamp_ms,acceleration_mps2,heading_deg` line per sample.
+///
+/// Samples are returned sorted by timestamp. Blank lines are ignored.
+pub fn parse_imu_csv(reader: impl BufRead) -> Result<Vec<ImuSample>, ImuError> {
+    let mut samples = Vec::new();
+
+    for line in reader.lines().skip(1) {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let [timestamp, acceleration, heading] = line.split(',').collect::<Vec<_>>()[..] else {
+            return Err(ImuError::InvalidFieldCount);
+        };
+        let parse_error = |e: &dyn std::fmt::Display| ImuError::Parse(e.to_string());
+
+        samples.push(ImuSample {
+            timestamp_ms: timestamp.trim().parse().map_err(|e| parse_error(&e))?,
+            acceleration_mps2: acceleration.trim().parse().map_err(|e| parse_error(&e))?,
+            heading_deg: heading.trim().parse().map_err(|e| parse_error(&e))?,
+        });
+    }
+
+    samples.sort_by_key(|sample| sample.timestamp_ms);
+    Ok(samples)
+}
+
+/// Thresholds of the gap filling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GapParams {
+    /// Shortest time between two fixes considered a gap, in seconds
+    pub min_gap_s: u64,
+    /// Time between reckoned points, in seconds
+    pub step_s: u64,
+}
+
+/// Conversion between scaled coordinates and meters around a reference point.
+enum Frame {
+    Geographic(LocalProjection),
+    Local {
+        origin: (i64, i64),
+        units_per_meter: f64,
+    },
+}
+
+impl Frame {
+    fn new(coordinates: CoordinateSystem, (latitude, longitude): (i64, i64)) -> Self {
+        match coordinates {
+            CoordinateSystem::Wgs84 => Frame::Geographic(LocalProjection::new(
+                to_degrees(latitude),
+                to_degrees(longitude),
+            )),
+            CoordinateSystem::Local { scale } => Frame::Local {
+                origin: (latitude, longitude),
+                units_per_meter: 10f64.powi(scale as i32),
+            },
+        }
+    }
+
+    /// Meters east and north of the reference point.
+    fn to_meters(&self, (latitude, longitude): (i64, i64)) -> (f64, f64) {
+        match self {
+            Frame::Geographic(projection) => {
+                projection.project(to_degrees(latitude), to_degrees(longitude))
+            }
+            Frame::Local {
+                origin,
+                units_per_meter,
+            } => (
+                (longitude - origin.1) as f64 / units_per_meter,
+                (latitude - origin.0) as f64 / units_per_meter,
+            ),
+        }
+    }
+
+    fn to_scaled(&self, (x, y): (f64, f64)) -> (i64, i64) {
+        match self {
+            Frame::Geographic(projection) => {
+                let (latitude, longitude) = projection.unproject(x, y);
+                (
+                    (latitude * UNITS_PER_DEGREE).round() as i64,
+                    (longitude * UNITS_PER_DEGREE).round() as i64,
+                )
+            }
+            Frame::Local {
+                origin,
+                units_per_meter,
+            } => (
+                origin.0 + (y * units_per_meter).round() as i64,
+                origin.1 + (x * units_per_meter).round() as i64,
+            ),
+        }
+    }
+}
+
+/// Dead-reckons the positions every `step_s` seconds strictly between the points `i` and
+/// `i + 1`, in meters from point `i`, before drift correction.
+fn reckon(
+    trajectory: &Trajectory,
+    frame: &Frame,
+    i: usize,
+    samples: &[ImuSample],
+    step_s: u64,
+) -> Vec<(u64, (f64, f64))> {
+    let point = |j: usize| (trajectory.latitudes[j], trajectory.longitudes[j]);
+    let (t0, t1) = (trajectory.timestamps[i], trajectory.timestamps[i + 1]);
+
+    // Speed over the segment before the gap
+    let mut speed = match i.checked_sub(1) {
+        Some(previous) if trajectory.timestamps[i] > trajectory.timestamps[previous] => {
+            trajectory.coordinates.distance_m(point(previous), point(i))
+                / (trajectory.timestamps[i] - trajectory.timestamps[previous]) as f64
+        }
+        _ => 0.0,
+    };
+    let (mut x, mut y) = (0.0, 0.0);
+    let mut now_ms = t0 * 1000;
+    let mut next_sample = 0;
+    let mut reckoned = Vec::new();
+
+    // Every step, then the end of the gap to measure the drift
+    for ts in (t0 + step_s..t1).step_by(step_s as usize).chain([t1]) {
+        while now_ms < ts * 1000 {
+            // The latest sample at or before now, or the first one before any
+            while next_sample + 1 < samples.len() && samples[next_sample + 1].timestamp_ms <= now_ms
+            {
+                next_sample += 1;
+            }
+            let sample = samples[next_sample];
+            let until_ms = samples
+                .get(next_sample + 1)
+                .map_or(ts * 1000, |next| next.timestamp_ms.min(ts * 1000));
+            let dt = (until_ms - now_ms) as f64 / 1000.0;
+
+            let new_speed = (speed + sample.acceleration_mps2 * dt).max(0.0);
+            let distance = (speed + new_speed) / 2.0 * dt;
+            let heading = sample.heading_deg.to_radians();
+            x += distance * heading.sin();
+            y += distance * heading.cos();
+            speed = new_speed;
+            now_ms = until_ms;
+        }
+        reckoned.push((ts, (x, y)));
+    }
+
+    let reckoned_end = reckoned.pop().map_or((0.0, 0.0), |(_, position)| position);
+    let end = frame.to_meters(point(i + 1));
+    let drift = (end.0 - reckoned_end.0, end.1 - reckoned_end.1);
+
+    reckoned
+        .into_iter()
+        .map(|(ts, (x, y))| {
+            let fraction = (ts - t0) as f64 / (t1 - t0) as f64;
+            (ts, (x + fraction * drift.0, y + fraction * drift.1))
+        })
+        .collect()
+}
+
+/// Inserts dead-reckoned points every `params.step_s` seconds into every gap of at least
+/// `params.min_gap_s` seconds with IMU samples, so the simplified track follows the path
+/// taken instead of cutting straight across.
+///
+/// Gaps without samples are left as they are. Timestamps must be sorted in non-decreasing
+/// order, and `samples` by timestamp.
+///
+/// # Returns
+///
+/// The filled trajectory and the number of inserted points.
+///
+/// # Panics
+///
+/// This function will panic if `params.step_s` is zero.
+pub fn fill_gaps(
+    trajectory: &Trajectory,
+    samples: &[ImuSample],
+    params: GapParams,
+) -> (Trajectory, usize) {
+    assert!(params.step_s > 0, "step_s > 0");
+
+    let len = trajectory.timestamps.len();
+    let mut filled = Trajectory {
+        latitudes: Vec::with_capacity(len),
+        longitudes: Vec::with_capacity(len),
+        timestamps: Vec::with_capacity(len),
+        altitudes: Vec::new(),
+        coordinates: trajectory.coordinates,
+    };
+    let mut inserted = 0;
+
+    for i in 0..len {
+        filled.latitudes.push(trajectory.latitudes[i]);
+        filled.longitudes.push(trajectory.longitudes[i]);
+        filled.timestamps.push(trajectory.timestamps[i]);
+        if i + 1 == len {
+            break;
+        }
+
+        let (t0, t1) = (trajectory.timestamps[i], trajectory.timestamps[i + 1]);
+        if t1.saturating_sub(t0) < params.min_gap_s {
+            continue;
+        }
+        let first = samples.partition_point(|sample| sample.timestamp_ms < t0 * 1000);
+        let last = samples.partition_point(|sample| sample.timestamp_ms < t1 * 1000);
+        if first == last {
+            continue;
+        }
+
+        let frame = Frame::new(
+            trajectory.coordinates,
+            (trajectory.latitudes[i], trajectory.longitudes[i]),
+        );
+        for (ts, position) in reckon(trajectory, &frame, i, &samples[first..last], params.step_s) {
+            let (latitude, longitude) = frame.to_scaled(position);
+            filled.latitudes.push(latitude);
+            filled.longitudes.push(longitude);
+            filled.timestamps.push(ts);
+            inserted += 1;
+        }
+    }
+
+    (filled, inserted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geo::{haversine_m, meters_to_units};
+
+    const PARAMS: GapParams = GapParams {
+        min_gap_s: 10,
+        step_s: 1,
+    };
+
+    #[test]
+    fn test_parse_imu_csv() {
+        let data = "timestamp_ms,acceleration,heading\n2000,0.5,90\n\n1000,-1,180.5\n";
+        let samples = parse_imu_csv(data.as_bytes()).unwrap();
+        assert_eq!(
+            samples,
+            vec![
+                ImuSample {
+                    timestamp_ms: 1000,
+                    acceleration_mps2: -1.0,
+                    heading_deg: 180.5,
+                },
+                ImuSample {
+                    timestamp_ms: 2000,
+                    acceleration_mps2: 0.5,
+                    heading_deg: 90.0,
+                },
+            ]
+        );
+        assert!(matches!(
+            parse_imu_csv("header\n1,2\n".as_bytes()),
+            Err(ImuError::InvalidFieldCount)
+        ));
+        assert!(matches!(
+            parse_imu_csv("header\n1,x,2\n".as_bytes()),
+            Err(ImuError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_fill_gaps() {
+        // 10 m/s north, then a 20 s tunnel going east for 10 s and north again for 10 s,
+        // which comes out 100 m east and 100 m north of its entrance
+        let north = meters_to_units(100.0);
+        let east = meters_to_units(100.0);
+        let trajectory = Trajectory {
+            latitudes: vec![0, north, 2 * north, 2 * north + north],
+            longitudes: vec![0, 0, east, east],
+            timestamps: vec![0, 10, 30, 40],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+        let samples = [
+            ImuSample {
+                timestamp_ms: 10_000,
+                acceleration_mps2: 0.0,
+                heading_deg: 90.0,
+            },
+            ImuSample {
+                timestamp_ms: 20_000,
+                acceleration_mps2: 0.0,
+                heading_deg: 0.0,
+            },
+        ];
+
+        let (filled, inserted) = fill_gaps(&trajectory, &samples, PARAMS);
+        assert_eq!(inserted, 19);
+        assert_eq!(filled.timestamps.len(), 23);
+        assert_eq!(filled.timestamps[2..21], (11..30).collect::<Vec<_>>()[..]);
+        // The corner of the tunnel, instead of the middle of a straight line across
+        let corner = 2 + 9;
+        assert_eq!(filled.timestamps[corner], 20);
+        let distance = haversine_m(
+            filled.latitudes[corner],
+            filled.longitudes[corner],
+            north,
+            east,
+        );
+        assert!(distance < 1.0, "{distance}");
+
+        // Without samples in the gap, or with a longer minimum gap, nothing is inserted
+        assert_eq!(fill_gaps(&trajectory, &[], PARAMS), (trajectory.clone(), 0));
+        let long = GapParams {
+            min_gap_s: 30,
+            ..PARAMS
+        };
+        assert_eq!(fill_gaps(&trajectory, &samples, long).1, 0);
+    }
+
+    #[test]
+    fn test_fill_gaps_drift() {
+        // The IMU underestimates the speed: the reckoned points are stretched to end on the
+        // fix after the gap, in a local frame in millimeters
+        let trajectory = Trajectory {
+            latitudes: vec![0, 10_000, 30_000],
+            longitudes: vec![0, 0, 0],
+            timestamps: vec![0, 10, 20],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Local { scale: 3 },
+        };
+        let samples = [ImuSample {
+            timestamp_ms: 10_000,
+            acceleration_mps2: 0.0,
+            heading_deg: 0.0,
+        }];
+        let (filled, inserted) = fill_gaps(&trajectory, &samples, PARAMS);
+        assert_eq!(inserted, 9);
+        // 1 m/s from the IMU, 2 m/s with the drift spread over the gap
+        assert_eq!(
+            filled.latitudes[2..11],
+            (1..10).map(|i| 10_000 + 2_000 * i).collect::<Vec<_>>()[..]
+        );
+        assert!(filled.longitudes.iter().all(|&longitude| longitude == 0));
+    }
+}
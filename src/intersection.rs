@@ -0,0 +1,174 @@
+//! Places where two trajectories cross, for meeting-point and co-location analyses.
+//!
+//! Both trajectories are projected around the first point of the first one, and the
+//! segments of the second are indexed in a `SegmentTree` so that each segment of the
+//! first is only tested against the segments near it.
+
+use crate::geo::{to_degrees, LocalProjection};
+use crate::rtree::{Rect, SegmentTree};
+use crate::trajectory::Trajectory;
+
+/// Point where a segment of trajectory `a` crosses a segment of trajectory `b`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Crossing {
+    /// Position in degrees
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Segments of `a` and `b` crossing, segment `i` joining points `i` and `i + 1`
+    pub segment_a: usize,
+    pub segment_b: usize,
+    /// Times at which `a` and `b` pass the crossing, interpolated along their segments
+    pub time_a: u64,
+    pub time_b: u64,
+}
+
+fn cross((ax, ay): (f64, f64), (bx, by): (f64, f64)) -> f64 {
+    ax * by - ay * bx
+}
+
+/// Finds where the segment `p -> p + r` crosses the segment `q -> q + s`.
+///
+/// # Returns
+///
+/// The fractions `(t, u)` of each segment at the crossing, or `None` if the segments do
+/// not cross or are parallel. Crossings at the end of a segment are only reported when
+/// `p_last` or `q_last` is set, so that a crossing at a shared vertex is found once.
+fn segment_crossing(
+    (p, r, p_last): ((f64, f64), (f64, f64), bool),
+    (q, s, q_last): ((f64, f64), (f64, f64), bool),
+) -> Option<(f64, f64)> {
+    let denominator = cross(r, s);
+    if denominator == 0.0 {
+        return None;
+    }
+    let qp = (q.0 - p.0, q.1 - p.1);
+    let t = cross(qp, s) / denominator;
+    let u = cross(qp, r) / denominator;
+    let within =
+        |fraction: f64, last: bool| fraction >= 0.0 && (fraction < 1.0 || last && fraction <= 1.0);
+    (within(t, p_last) && within(u, q_last)).then_some((t, u))
+}
+
+/// Interpolates the time at a fraction of the segment starting at point `i`.
+fn time_at(timestamps: &[u64], i: usize, fraction: f64) -> u64 {
+    let (start, end) = (timestamps[i] as f64, timestamps[i + 1] as f64);
+    (start + fraction * (end - start)).round() as u64
+}
+
+/// Finds every place where trajectories `a` and `b` cross, ordered along `a`.
+///
+/// With a `time_window_s`, only crossings that both trajectories pass within that many
+/// seconds of each other are kept. Overlapping collinear segments, as when both follow
+/// the same straight road, are not reported as crossings.
+pub fn crossings(a: &Trajectory, b: &Trajectory, time_window_s: Option<u64>) -> Vec<Crossing> {
+    let (Some(&latitude), Some(&longitude)) = (a.latitudes.first(), a.longitudes.first()) else {
+        return Vec::new();
+    };
+    let projection = LocalProjection::new(to_degrees(latitude), to_degrees(longitude));
+    let project = |t: &Trajectory| -> Vec<(f64, f64)> {
+        t.latitudes
+            .iter()
+            .zip(&t.longitudes)
+            .map(|(&lat, &lon)| projection.project(to_degrees(lat), to_degrees(lon)))
+            .collect()
+    };
+    let (points_a, points_b) = (project(a), project(b));
+    let tree = SegmentTree::new(&points_b);
+    let (last_a, last_b) = (
+        points_a.len().saturating_sub(2),
+        points_b.len().saturating_sub(2),
+    );
+
+    let mut crossings = Vec::new();
+    for (i, pair) in points_a.windows(2).enumerate() {
+        let (p, r) = (pair[0], (pair[1].0 - pair[0].0, pair[1].1 - pair[0].1));
+        let mut found: Vec<(f64, Crossing)> = Vec::new();
+        for j in tree.query(&Rect::of_segment(pair[0], pair[1])) {
+            let q = points_b[j];
+            let s = (points_b[j + 1].0 - q.0, points_b[j + 1].1 - q.1);
+            let Some((t, u)) = segment_crossing((p, r, i == last_a), (q, s, j == last_b)) else {
+                continue;
+            };
+            let (time_a, time_b) = (time_at(&a.timestamps, i, t), time_at(&b.timestamps, j, u));
+            if time_window_s.is_some_and(|window| time_a.abs_diff(time_b) > window) {
+                continue;
+            }
+            let (latitude, longitude) = projection.unproject(p.0 + t * r.0, p.1 + t * r.1);
+            let crossing = Crossing {
+                latitude,
+                longitude,
+                segment_a: i,
+                segment_b: j,
+                time_a,
+                time_b,
+            };
+            found.push((t, crossing));
+        }
+        found.sort_by(|x, y| x.0.total_cmp(&y.0).then(x.1.segment_b.cmp(&y.1.segment_b)));
+        crossings.extend(found.into_iter().map(|(_, crossing)| crossing));
+    }
+    crossings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trajectory::CoordinateSystem;
+
+    fn trajectory(points: &[(i64, i64, u64)]) -> Trajectory {
+        Trajectory {
+            latitudes: points.iter().map(|p| p.0).collect(),
+            longitudes: points.iter().map(|p| p.1).collect(),
+            timestamps: points.iter().map(|p| p.2).collect(),
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        }
+    }
+
+    #[test]
+    fn test_crossings() {
+        // A goes east then north, B goes north across the first leg then east across the
+        // second one
+        let a = trajectory(&[(0, 0, 0), (0, 20_000, 100), (20_000, 20_000, 200)]);
+        let b = trajectory(&[
+            (-10_000, 5_000, 1_000),
+            (10_000, 5_000, 1_100),
+            (10_000, 30_000, 1_200),
+        ]);
+
+        let found = crossings(&a, &b, None);
+        assert_eq!(found.len(), 2);
+        assert_eq!((found[0].segment_a, found[0].segment_b), (0, 0));
+        assert!((found[0].latitude - 0.0).abs() < 1e-9);
+        assert!((found[0].longitude - 0.005).abs() < 1e-9);
+        assert_eq!((found[0].time_a, found[0].time_b), (25, 1_050));
+        assert_eq!((found[1].segment_a, found[1].segment_b), (1, 1));
+        assert!((found[1].latitude - 0.01).abs() < 1e-9);
+        assert!((found[1].longitude - 0.02).abs() < 1e-9);
+        assert_eq!((found[1].time_a, found[1].time_b), (150, 1_160));
+
+        // Both crossings are more than 900 s apart
+        assert_eq!(crossings(&a, &b, Some(1_010)).len(), 1);
+        assert!(crossings(&a, &b, Some(900)).is_empty());
+    }
+
+    #[test]
+    fn test_crossing_at_vertex() {
+        // B crosses A exactly at the shared vertex of two segments of both
+        let a = trajectory(&[(0, -10, 0), (0, 0, 1), (0, 10, 2)]);
+        let b = trajectory(&[(-10, 0, 0), (0, 0, 1), (10, 0, 2)]);
+        let found = crossings(&a, &b, None);
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].segment_a, found[0].segment_b), (1, 1));
+    }
+
+    #[test]
+    fn test_no_crossings() {
+        let a = trajectory(&[(0, 0, 0), (0, 10, 1)]);
+        // Parallel and collinear overlapping segments
+        assert!(crossings(&a, &trajectory(&[(5, 0, 0), (5, 10, 1)]), None).is_empty());
+        assert!(crossings(&a, &trajectory(&[(0, 5, 0), (0, 15, 1)]), None).is_empty());
+        assert!(crossings(&a, &trajectory(&[]), None).is_empty());
+        assert!(crossings(&trajectory(&[]), &a, None).is_empty());
+    }
+}
@@ -0,0 +1,128 @@
+//! Parsing of GeoLife transportation mode labels (`labels.txt`).
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use std::io::{self, BufRead, Write};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum LabelError {
+    #[error("Error while reading line from file: {0}")]
+    Io(#[from] io::Error),
+    #[error("Invalid number of fields in line")]
+    InvalidFieldCount,
+    #[error("Failed to parse date: {0}")]
+    DateParse(String),
+}
+
+/// A time interval during which the user reported a transportation mode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModeLabel {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub mode: String,
+}
+
+/// Format of the dates in `labels.txt`
+const DATE_FORMAT: &str = "%Y/%m/%d %H:%M:%S";
+
+fn parse_date(value: &str) -> Result<DateTime<Utc>, LabelError> {
+    NaiveDateTime::parse_from_str(value.trim(), DATE_FORMAT)
+        .map(|date| date.and_utc())
+        .map_err(|e| LabelError::DateParse(e.to_string()))
+}
+
+/// Parses a GeoLife `labels.txt` file.
+///
+/// The file has a header line followed by tab-separated start time, end time and mode.
+pub fn parse_labels_file(reader: impl BufRead) -> Result<Vec<ModeLabel>, LabelError> {
+    let mut labels = Vec::new();
+
+    for line in reader.lines().skip(1) {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let parts: Vec<&str> = line.split('\t').collect();
+        if parts.len() != 3 {
+            return Err(LabelError::InvalidFieldCount);
+        }
+
+        labels.push(ModeLabel {
+            start: parse_date(parts[0])?,
+            end: parse_date(parts[1])?,
+            mode: parts[2].trim().to_string(),
+        });
+    }
+
+    Ok(labels)
+}
+
+/// Writes labels in the GeoLife `labels.txt` format read by [`parse_labels_file`].
+pub fn write_labels_file(mut writer: impl Write, labels: &[ModeLabel]) -> io::Result<()> {
+    writeln!(writer, "Start Time\tEnd Time\tTransportation Mode")?;
+    for label in labels {
+        writeln!(
+            writer,
+            "{}\t{}\t{}",
+            label.start.format(DATE_FORMAT),
+            label.end.format(DATE_FORMAT),
+            label.mode
+        )?;
+    }
+    Ok(())
+}
+
+/// Returns the mode reported at the given Unix timestamp, if any.
+pub fn mode_at(labels: &[ModeLabel], timestamp: u64) -> Option<&str> {
+    labels
+        .iter()
+        .find(|label| {
+            (label.start.timestamp()..=label.end.timestamp()).contains(&(timestamp as i64))
+        })
+        .map(|label| label.mode.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LABELS: &str = "Start Time\tEnd Time\tTransportation Mode\n\
+        2008/04/02 11:24:21\t2008/04/02 11:50:45\tbus\n\
+        2008/04/03 01:07:03\t2008/04/03 11:31:55\ttrain\n";
+
+    #[test]
+    fn test_parse_labels_file() {
+        let labels = parse_labels_file(LABELS.as_bytes()).unwrap();
+
+        assert_eq!(labels.len(), 2);
+        assert_eq!(labels[0].mode, "bus");
+        assert_eq!(labels[0].start.timestamp(), 1_207_135_461);
+        assert_eq!(labels[1].mode, "train");
+    }
+
+    #[test]
+    fn test_write_labels_file() {
+        let labels = parse_labels_file(LABELS.as_bytes()).unwrap();
+        let mut written = Vec::new();
+        write_labels_file(&mut written, &labels).unwrap();
+        assert_eq!(String::from_utf8(written).unwrap(), LABELS);
+    }
+
+    #[test]
+    fn test_mode_at() {
+        let labels = parse_labels_file(LABELS.as_bytes()).unwrap();
+
+        assert_eq!(mode_at(&labels, 1_207_135_461), Some("bus"));
+        assert_eq!(mode_at(&labels, 1_207_135_460), None);
+        assert_eq!(mode_at(&labels, 1_207_184_823), Some("train"));
+    }
+
+    #[test]
+    fn test_parse_labels_file_invalid() {
+        let result = parse_labels_file("header\n2008/04/02 11:24:21\tbus\n".as_bytes());
+        assert!(matches!(result, Err(LabelError::InvalidFieldCount)));
+
+        let result = parse_labels_file("header\nnope\t2008/04/02 11:50:45\tbus\n".as_bytes());
+        assert!(matches!(result, Err(LabelError::DateParse(_))));
+    }
+}
@@ -0,0 +1,256 @@
+//! Experimental memory layouts for huge single trajectories.
+//!
+//! `simplify::simplify` scans the latitudes and longitudes of each segment from two
+//! separate vectors, two streams far apart in memory. `BlockedPoints` tiles them into
+//! blocks of `BLOCK_POINTS` latitudes followed by their longitudes, so that the scan of a
+//! segment reads one contiguous region. `PackedCoordinates` stores them as `i32` when they
+//! fit, which they do for WGS 84 coordinates in microdegrees, halving the memory read by
+//! the scan. Whether this pays off on trajectories of tens of millions of points is
+//! measured by `bench::bench_layouts` before changing the default.
+
+use crate::simplify::{self, Coordinate};
+
+/// Number of points per block: 64 latitudes then 64 longitudes, 1 KiB
+pub const BLOCK_POINTS: usize = 64;
+
+#[derive(Debug, Clone)]
+struct Block {
+    x: [i64; BLOCK_POINTS],
+    y: [i64; BLOCK_POINTS],
+}
+
+/// Points stored in blocks of `BLOCK_POINTS` x coordinates followed by their y
+/// coordinates. The last block is padded.
+#[derive(Debug, Clone)]
+pub struct BlockedPoints {
+    len: usize,
+    blocks: Vec<Block>,
+}
+
+impl BlockedPoints {
+    /// Tiles the coordinates into blocks.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `positions_x` and `positions_y` have different lengths.
+    pub fn new(positions_x: &[i64], positions_y: &[i64]) -> BlockedPoints {
+        assert_eq!(
+            positions_x.len(),
+            positions_y.len(),
+            "positions_x.len() == positions_y.len()"
+        );
+        let blocks = positions_x
+            .chunks(BLOCK_POINTS)
+            .zip(positions_y.chunks(BLOCK_POINTS))
+            .map(|(xs, ys)| {
+                let mut block = Block {
+                    x: [0; BLOCK_POINTS],
+                    y: [0; BLOCK_POINTS],
+                };
+                block.x[..xs.len()].copy_from_slice(xs);
+                block.y[..ys.len()].copy_from_slice(ys);
+                block
+            })
+            .collect();
+        BlockedPoints {
+            len: positions_x.len(),
+            blocks,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Coordinates of the point at `index`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `index` is out of bounds.
+    pub fn get(&self, index: usize) -> (i64, i64) {
+        assert!(index < self.len, "index out of bounds");
+        let block = &self.blocks[index / BLOCK_POINTS];
+        let offset = index % BLOCK_POINTS;
+        (block.x[offset], block.y[offset])
+    }
+
+    /// Largest squared distance of the points strictly between `start` and `end` to the
+    /// line through them, and the first point at that distance.
+    fn max_distance(&self, start: usize, end: usize) -> (i64, usize) {
+        let line = i64::line(self.get(start), self.get(end));
+        let mut max = (0, start);
+        let mut i = start + 1;
+        while i < end {
+            let block = &self.blocks[i / BLOCK_POINTS];
+            let offset = i % BLOCK_POINTS;
+            let stop = BLOCK_POINTS.min(offset + end - i);
+            let xs = &block.x[offset..stop];
+            let ys = &block.y[offset..stop];
+            for (k, (&x, &y)) in xs.iter().zip(ys).enumerate() {
+                let d = i64::line_distance_squared(&line, (x, y));
+                if d > max.0 {
+                    max = (d, i + k);
+                }
+            }
+            i += stop - offset;
+        }
+        max
+    }
+
+    /// Simplifies the points like `simplify::simplify`, with the same result.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `epsilon` is negative.
+    pub fn simplify(&self, epsilon: i64) -> Vec<bool> {
+        assert!(epsilon >= 0, "epsilon must be non-negative");
+        if self.len <= 2 {
+            return vec![true; self.len];
+        }
+
+        let mut keep = vec![false; self.len];
+        keep[0] = true;
+        keep[self.len - 1] = true;
+        let mut stack = vec![(0, self.len - 1)];
+        while let Some((start, end)) = stack.pop() {
+            if end - start <= 1 {
+                continue;
+            }
+            let (distance, index) = self.max_distance(start, end);
+            if distance > epsilon * epsilon {
+                keep[index] = true;
+                stack.push((start, index));
+                stack.push((index, end));
+            }
+        }
+        keep
+    }
+}
+
+/// Coordinates stored as `i32` when every one of them fits, as `i64` otherwise.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackedCoordinates {
+    I32 { x: Vec<i32>, y: Vec<i32> },
+    I64 { x: Vec<i64>, y: Vec<i64> },
+}
+
+impl PackedCoordinates {
+    /// Packs the coordinates as `i32`, falling back to `i64` when one of them does not fit.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `positions_x` and `positions_y` have different lengths.
+    pub fn pack(positions_x: &[i64], positions_y: &[i64]) -> PackedCoordinates {
+        assert_eq!(
+            positions_x.len(),
+            positions_y.len(),
+            "positions_x.len() == positions_y.len()"
+        );
+        let narrow = |values: &[i64]| -> Option<Vec<i32>> {
+            values
+                .iter()
+                .map(|&value| i32::try_from(value).ok())
+                .collect()
+        };
+        match (narrow(positions_x), narrow(positions_y)) {
+            (Some(x), Some(y)) => PackedCoordinates::I32 { x, y },
+            _ => PackedCoordinates::I64 {
+                x: positions_x.to_vec(),
+                y: positions_y.to_vec(),
+            },
+        }
+    }
+
+    /// Name of the storage type, for reports
+    pub fn name(&self) -> &'static str {
+        match self {
+            PackedCoordinates::I32 { .. } => "i32",
+            PackedCoordinates::I64 { .. } => "i64",
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            PackedCoordinates::I32 { x, .. } => x.len(),
+            PackedCoordinates::I64 { x, .. } => x.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Simplifies the points like `simplify::simplify`, with the same result.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `epsilon` is negative.
+    pub fn simplify(&self, epsilon: i64) -> Vec<bool> {
+        match self {
+            PackedCoordinates::I32 { x, y } => match i32::try_from(epsilon) {
+                Ok(epsilon) => simplify::simplify(x, y, epsilon),
+                // Beyond the range of the coordinates, widened for the rare huge tolerance
+                Err(_) => {
+                    let widen = |values: &[i32]| values.iter().map(|&v| v as i64).collect();
+                    let (x, y): (Vec<i64>, Vec<i64>) = (widen(x), widen(y));
+                    simplify::simplify(&x, &y, epsilon)
+                }
+            },
+            PackedCoordinates::I64 { x, y } => simplify::simplify(x, y, epsilon),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bench::synthetic_trajectory;
+    use crate::simplify::simplify;
+
+    #[test]
+    fn test_blocked_points() {
+        let x: Vec<i64> = (0..200).collect();
+        let y: Vec<i64> = x.iter().map(|&x| x * 3).collect();
+        let points = BlockedPoints::new(&x, &y);
+        assert_eq!(points.len(), 200);
+        assert_eq!(points.get(0), (0, 0));
+        assert_eq!(points.get(BLOCK_POINTS), (64, 192));
+        assert_eq!(points.get(199), (199, 597));
+        assert!(BlockedPoints::new(&[], &[]).is_empty());
+    }
+
+    #[test]
+    fn test_packed_coordinates() {
+        let trajectory = synthetic_trajectory(1_000, 3);
+        let (x, y) = (&trajectory.latitudes, &trajectory.longitudes);
+        let packed = PackedCoordinates::pack(x, y);
+        assert_eq!(packed.name(), "i32");
+        assert_eq!(packed.len(), 1_000);
+        for epsilon in [0, 5, 30, 3_000_000_000] {
+            assert_eq!(packed.simplify(epsilon), simplify(x, y, epsilon));
+        }
+
+        let wide = [0, i32::MAX as i64 + 1, 5];
+        let packed = PackedCoordinates::pack(&wide, &[0, 1, 2]);
+        assert_eq!(packed.name(), "i64");
+        assert_eq!(packed.simplify(0), simplify(&wide, &[0, 1, 2], 0));
+        assert!(PackedCoordinates::pack(&[], &[]).is_empty());
+    }
+
+    #[test]
+    fn test_blocked_simplify() {
+        // Lengths around block boundaries
+        for len in [0, 1, 2, 3, 63, 64, 65, 128, 129, 1_000, 10_000] {
+            let trajectory = synthetic_trajectory(len, len as u64);
+            let (x, y) = (&trajectory.latitudes, &trajectory.longitudes);
+            let points = BlockedPoints::new(x, y);
+            for epsilon in [0, 5, 30] {
+                assert_eq!(points.simplify(epsilon), simplify(x, y, epsilon), "{len}");
+            }
+        }
+    }
+}
@@ -0,0 +1,111 @@
+//! Processing and compression of GPS trajectories: parsing of GeoLife and other formats,
+//! Douglas-Peucker simplification, and serialization to protobuf, encoded polylines or a
+//! compact binary format. The `trajectory-rs` binary is a command line over this library.
+//!
+//! ```
+//! use trajectory_rs::{parse_plt_file, simplify, Encoding, Trajectory};
+//!
+//! let plt = "Geolife trajectory\nWGS 84\nAltitude is in Feet\nReserved 3\n0,2,255,My Track,0,0,2,8421376\n0\n\
+//!            39.9,116.3,0,492,39745.0,2008-10-24,00:00:00\n\
+//!            39.9001,116.3001,0,492,39745.0,2008-10-24,00:00:05\n\
+//!            39.9002,116.3002,0,492,39745.0,2008-10-24,00:00:10\n";
+//! let trajectory = Trajectory::new(parse_plt_file(plt.as_bytes())?);
+//!
+//! // 1,000 microdegrees, about 100 m
+//! let keep = simplify(&trajectory.latitudes, &trajectory.longitudes, 1000);
+//! let simplified = trajectory.filter(&keep);
+//! assert_eq!(simplified.latitudes, [39_900_000, 39_900_200]);
+//!
+//! let bytes = Encoding::Binary.encode(&simplified)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+pub mod anomaly;
+pub mod arrow;
+pub mod bench;
+pub mod binary;
+pub mod bootstrap;
+pub mod cache;
+pub mod collection;
+pub mod compare;
+pub mod compression;
+pub mod config;
+pub mod corridor;
+pub mod cotravel;
+pub mod crypto;
+pub mod dataset;
+pub mod datum;
+pub mod delta;
+pub mod diagnostics;
+pub mod direction;
+pub mod encoding;
+#[cfg(feature = "http-matcher")]
+pub mod enrich;
+pub mod error;
+pub mod events;
+pub mod export;
+pub mod flight;
+pub mod formats;
+pub mod geo;
+pub mod geohash;
+pub mod geojson;
+pub mod geom;
+#[cfg(feature = "http-matcher")]
+pub mod http_matcher;
+pub mod imu;
+pub mod intersection;
+pub mod labels;
+pub mod layout;
+pub mod matching;
+pub mod merge;
+pub mod metrics;
+pub mod noise;
+pub mod od;
+pub mod parquet;
+pub mod pipeline;
+pub mod point;
+pub mod preset;
+pub mod polyline;
+pub mod privacy;
+pub mod quality;
+pub mod rawlog;
+pub mod recommend;
+pub mod redis;
+pub mod resample;
+pub mod routes;
+pub mod rtree;
+pub mod run;
+#[cfg(feature = "s3")]
+pub mod s3_store;
+pub mod segment;
+pub mod server;
+pub mod similarity;
+pub mod simplify;
+pub mod smoothing;
+pub mod split;
+pub mod split_tree;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_store;
+pub mod stays;
+pub mod store;
+pub mod streaming;
+pub mod temporal;
+pub mod tenants;
+pub mod tensor;
+pub mod thumbnail;
+pub mod tile;
+pub mod trajectory;
+pub mod turns;
+pub mod websocket;
+pub mod zones;
+
+/// Generated protobuf messages
+pub mod proto {
+    include!(concat!(env!("OUT_DIR"), "/trajectory.rs"));
+}
+
+pub use encoding::Encoding;
+pub use error::AppError;
+pub use point::{parse_plt_file, ParseError, Point};
+pub use simplify::simplify;
+pub use trajectory::Trajectory;
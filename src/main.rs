@@ -1,181 +1,2130 @@
 //! A proof of concept for trajectory data processing and compression.
 //! This program processes GPS trajectory data, simplifies it using the Douglas-Peucker algorithm,
-//! and demonstrates different serialization approaches.
+//! and demonstrates different serialization approaches. The processing itself lives in the
+//! `trajectory_rs` library.
 
-mod point;
-mod simplify;
-mod trajectory;
+mod cli;
 
-use num_format::{Locale, ToFormattedString};
-use point::{parse_plt_file, ParseError};
+use trajectory_rs::{
+    anomaly, bench, binary, bootstrap, cache, compare, config, corridor, cotravel, crypto, dataset,
+    datum, direction, events, flight, formats, geo, geohash, geom, imu, intersection, labels,
+    matching, merge, metrics, noise, od, parquet, pipeline, privacy, quality, rawlog, redis,
+    resample, routes, run, server, simplify, split, split_tree, stays, store, streaming, temporal,
+    tenants, tensor, tile, trajectory, turns, websocket, zones, AppError,
+};
+#[cfg(feature = "http-matcher")]
+use trajectory_rs::{enrich, http_matcher};
+
+use bench::LOCALE;
+use clap::Parser;
+use cli::{Cli, Command};
+use config::Config;
+use corridor::Corridor;
+use datum::Datum;
+use matching::{Matcher, RoadNetwork};
+use metrics::Metrics;
+use noise::NoiseParams;
+use num_format::ToFormattedString;
+use pipeline::PipelineConfig;
 use prost::Message;
+use run::write_output;
+use simplify::{BatchOptions, LoopAnchors, TrajectoryView};
+use split::{SplitRatios, SplitStrategy};
+use split_tree::SplitTree;
+use std::collections::BTreeMap;
 use std::fs;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
-use thiserror::Error;
+use streaming::StreamingOptions;
+use trajectory::Trajectory;
 
-// Include the generated protobuf code
-pub mod proto {
-    include!(concat!(env!("OUT_DIR"), "/trajectory.rs"));
-}
+/// Epsilon for simplification (before 1e-6 multiplier), 100 meters precision:
+const EPSILON: i64 = 1000;
 
-use crate::trajectory::Trajectory;
+/// Directory the GeoLife `.plt` files are read from, unless `GEOLIFE_DIR` is set
+const DIR_PATH: &str = "geolife/";
 
-/// Locale for number formatting
-const LOCALE: Locale = Locale::en;
+/// Precision of local traces: 10^3 units per meter, i.e. millimeters
+const LOCAL_SCALE: u32 = 3;
 
-/// Epsilon for simplification (before 1e-6 multiplier), 100 meters precision:
-const EPSILON: i64 = 1000;
+/// Fractions of the points whose error `error-ci` estimates
+const ERROR_CI_QUANTILES: [f64; 4] = [0.5, 0.9, 0.95, 0.99];
+
+/// Bootstrap of `error-ci`: 95% intervals from 1000 resamples
+const ERROR_CI_BOOTSTRAP: bootstrap::BootstrapParams = bootstrap::BootstrapParams {
+    resamples: 1000,
+    confidence: 0.95,
+    seed: 0,
+};
+
+/// Thresholds of `bench-stay-points`: 50 m for 5 minutes
+const BENCH_STAY_PARAMS: stays::StayParams = stays::StayParams {
+    max_distance_m: 50.0,
+    min_duration_s: 300,
+};
 
-/// Custom error type for the application
-#[derive(Error, Debug)]
-pub enum AppError {
-    #[error("Parse error: {0}")]
-    Parse(#[from] ParseError),
-    #[error("IO error: {0}")]
-    Io(#[from] std::io::Error),
+/// Number of points of the synthetic trajectory used to benchmark stay-point detection,
+/// smaller than for simplification as the naive detection is much slower
+const BENCH_STAY_SYNTHETIC_POINTS: usize = 100_000;
+
+/// Thresholds of `events` other than the speed limit
+const EVENT_THRESHOLDS: events::EventParams = events::EventParams {
+    speed_limit_mps: f64::INFINITY,
+    harsh_acceleration_mps2: 3.0,
+    harsh_braking_mps2: 4.0,
+    idle_speed_mps: 0.5,
+    min_idle_s: 300,
+};
+
+/// Distance in meters the heading must hold around a turn, well above the GPS noise
+const TURN_WINDOW_M: f64 = 50.0;
+
+/// Time in seconds between the samples compared by `co-travel`
+const CO_TRAVEL_STEP_S: u64 = 10;
+
+/// Number of simplification masks `zoom-levels` keeps in memory
+const ZOOM_CACHE_CAPACITY: usize = 1024;
+
+/// Returns the input directory, taken from the `GEOLIFE_DIR` environment variable if set.
+fn dir_path() -> PathBuf {
+    std::env::var_os("GEOLIFE_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DIR_PATH))
 }
 
+/// Simplifies the trajectory using the Douglas-Peucker algorithm and drops the discarded points.
+fn simplify_trajectory(trajectory: Trajectory, epsilon: i64) -> Trajectory {
+    let keep_points = simplify::simplify(&trajectory.latitudes, &trajectory.longitudes, epsilon);
+    trajectory.filter(&keep_points)
+}
 
 /// Main entry point for the trajectory processing application.
 ///
+/// Runs the subcommand given as first argument (see the README), the default pipeline
+/// otherwise. The default pipeline accepts `--smooth` to apply a One-Euro filter before
+/// simplification, `--reanchor-loops` to anchor loops at their two farthest points and
+/// `--config <path>` to read per-mode and per-speed tolerances.
+///
 /// # Returns
 ///
 /// Returns `Result<(), AppError>` where:
 /// - `Ok(())` indicates successful processing
 /// - `Err(AppError)` contains details about any errors encountered
 fn main() -> Result<(), AppError> {
-    let dir_path = "geolife/";
-    let mut total_size = 0;
+    let cli = Cli::parse();
+    run::with_metrics(cli.push_metrics.as_deref(), |metrics| match cli.command {
+        Some(Command::BenchFormats) => run_bench_formats(),
+        Some(Command::BenchSimplify) => run_bench_simplify(),
+        Some(Command::BenchLayout(args)) => run_bench_layout(args),
+        Some(Command::BenchStayPoints) => run_bench_stay_points(),
+        Some(Command::Sweep(args)) => run_sweep(args),
+        Some(Command::Split(args)) => run_split(args),
+        Some(Command::SimplifyLocal(args)) => run_simplify_local(args, metrics),
+        Some(Command::ExportTensor(args)) => run_export_tensor(args, metrics),
+        Some(Command::Batch(args)) => run_batch(args, metrics),
+        Some(Command::ExportQuality(args)) => run_export_quality(args),
+        Some(Command::ErrorCi(args)) => run_error_ci(args),
+        Some(Command::ExportPrivate(args)) => run_export_private(args),
+        Some(Command::ExportHull(args)) => run_export_hull(args),
+        Some(Command::ExportGeohash(args)) => run_export_geohash(args),
+        Some(Command::CorridorCells(args)) => run_corridor_cells(args),
+        Some(Command::Buffer(args)) => run_buffer(args),
+        Some(Command::PoisAlong(args)) => run_pois_along(args),
+        Some(Command::CompareDirection(args)) => run_compare_direction(args),
+        Some(Command::Crossings(args)) => run_crossings(args),
+        Some(Command::Routes(args)) => run_routes(args),
+        Some(Command::Zones(args)) => run_zones(args),
+        Some(Command::OdMatrix(args)) => run_od_matrix(args),
+        Some(Command::TemporalReport(args)) => run_temporal_report(args),
+        Some(Command::StayPoints(args)) => run_stay_points(args),
+        Some(Command::EstimateNoise(args)) => run_estimate_noise(args),
+        Some(Command::ServeArrow(args)) => run_serve_arrow(args),
+        Some(Command::ServeHttp(args)) => run_serve_http(args),
+        Some(Command::ExportParquet(args)) => run_export_parquet(args, metrics),
+        Some(Command::PublishRedis(args)) => run_publish_redis(args),
+        Some(Command::Store(args)) => run_store(args, metrics),
+        Some(Command::Ingest(args)) => run_ingest(args),
+        Some(Command::Export(args)) => run_export(args),
+        Some(Command::Generalize(args)) => run_generalize(args, metrics),
+        Some(Command::Import(args)) => run_import(args, metrics),
+        Some(Command::Migrate(args)) => run_migrate(args),
+        Some(Command::Events(args)) => run_events(args),
+        Some(Command::Turns(args)) => run_turns(args),
+        Some(Command::SplitTree(args)) => run_split_tree(args),
+        Some(Command::Explain(args)) => run_explain(args),
+        Some(Command::Anomalies(args)) => run_anomalies(args),
+        Some(Command::CoTravel(args)) => run_co_travel(args),
+        Some(Command::ZoomLevels(args)) => run_zoom_levels(args),
+        Some(Command::Replay(args)) => run_replay(args),
+        Some(Command::ServeLive(args)) => run_serve_live(args),
+        Some(Command::Merge(args)) => run_merge(args),
+        Some(Command::Resample(args)) => run_resample(args),
+        Some(Command::Densify(args)) => run_densify(args),
+        Some(Command::FillGaps(args)) => run_fill_gaps(args),
+        Some(Command::SnapSimplify(args)) => run_snap_simplify(args),
+        Some(Command::Decrypt(args)) => run_decrypt(args),
+        Some(Command::Keygen) => {
+            println!("{}", crypto::generate_key());
+            Ok(())
+        }
+        None => run::run(run_options(cli.run)?, metrics),
+    })
+}
+
+/// Encodes the simplified dataset with every serialization backend and prints a comparison table.
+fn run_bench_formats() -> Result<(), AppError> {
+    let files = dataset::find_plt_files(&dir_path())?;
+    let (trajectory, _) = dataset::load_trajectory(&files)?;
+    let simplified_trajectory = simplify_trajectory(trajectory, EPSILON);
+
+    println!(
+        "Benchmarking {} simplified points",
+        simplified_trajectory
+            .latitudes
+            .len()
+            .to_formatted_string(&LOCALE)
+    );
+    println!();
+
+    let reports = bench::bench_formats(&simplified_trajectory)?;
+    bench::print_reports(&reports);
+
+    Ok(())
+}
+
+/// Simplifies the dataset with every split order and prints a comparison table.
+///
+/// The simplification of a synthetic 1,000,000-point trajectory is measured by the
+/// criterion benches (`cargo bench --bench simplify`).
+fn run_bench_simplify() -> Result<(), AppError> {
+    let files = dataset::find_plt_files(&dir_path())?;
+    let (trajectory, _) = dataset::load_trajectory(&files)?;
+
+    println!(
+        "Benchmarking simplification of {} points",
+        trajectory.latitudes.len().to_formatted_string(&LOCALE)
+    );
+    println!();
+
+    let reports = bench::bench_simplify(&trajectory, EPSILON);
+    bench::print_simplify_reports(&reports);
+
+    Ok(())
+}
+
+/// Simplifies a synthetic random walk, of 1,000,000 points unless given, from separate
+/// coordinate vectors and from blocks, and prints a comparison table.
+fn run_bench_layout(args: cli::BenchLayoutArgs) -> Result<(), AppError> {
+    let cli::BenchLayoutArgs { points } = args;
+    let trajectory = bench::synthetic_trajectory(points, 0);
+    println!(
+        "Benchmarking the memory layouts on {} synthetic points",
+        points.to_formatted_string(&LOCALE)
+    );
+    println!();
+
+    let reports = bench::bench_layouts(&trajectory, EPSILON);
+    bench::print_layout_reports(&reports);
+    Ok(())
+}
+
+/// Prints the stay-point detection time of every file of the dataset, and of a synthetic
+/// random walk, with and without the range tree and in parallel.
+fn run_bench_stay_points() -> Result<(), AppError> {
+    let files = dataset::find_plt_files(&dir_path())?;
+    let mut trajectories = Vec::with_capacity(files.len());
+    for file in &files {
+        let (trajectory, _) = dataset::load_trajectory(std::slice::from_ref(file))?;
+        trajectories.push(trajectory);
+    }
+    let synthetic = vec![bench::synthetic_trajectory(BENCH_STAY_SYNTHETIC_POINTS, 0)];
+    let threads = std::thread::available_parallelism().map_or(1, usize::from);
+
+    for (name, trajectories) in [("dataset", trajectories), ("synthetic", synthetic)] {
+        println!(
+            "Benchmarking stay-point detection on {} {name} points",
+            trajectories
+                .iter()
+                .map(|trajectory| trajectory.latitudes.len())
+                .sum::<usize>()
+                .to_formatted_string(&LOCALE)
+        );
+        println!();
+
+        let reports = bench::bench_stay_points(&trajectories, BENCH_STAY_PARAMS, threads);
+        bench::print_stay_reports(&reports);
+        println!();
+    }
+
+    Ok(())
+}
+
+/// Simplifies the dataset at every tolerance given in meters and writes the compression
+/// obtained as CSV.
+fn run_sweep(args: cli::SweepArgs) -> Result<(), AppError> {
+    let cli::SweepArgs { output, epsilons_m } = args;
+
+    let files = dataset::find_plt_files(&dir_path())?;
+    let (trajectory, _) = dataset::load_trajectory(&files)?;
+    let rows = bench::sweep(&trajectory, &epsilons_m)?;
+
+    let mut csv = Vec::new();
+    bench::write_sweep_csv(&mut csv, &rows)?;
+    write_output(&output, csv)?;
+    println!(
+        "Swept {} tolerances over {} points -> {output}",
+        rows.len(),
+        trajectory.latitudes.len().to_formatted_string(&LOCALE)
+    );
+
+    Ok(())
+}
+
+/// Splits the dataset into train, validation and test subsets and writes each one,
+/// simplified, in the requested format.
+fn run_split(args: cli::SplitArgs) -> Result<(), AppError> {
+    let cli::SplitArgs {
+        strategy,
+        format: encoding,
+        output_dir,
+        seed,
+    } = args;
+    let strategy = match strategy {
+        cli::SplitBy::User => SplitStrategy::User,
+        cli::SplitBy::Time => SplitStrategy::Time,
+        cli::SplitBy::Random => SplitStrategy::Random { seed },
+    };
+
+    let files = dataset::find_plt_files(&dir_path())?;
+    let split = split::split_files(files, strategy, SplitRatios::default());
+
+    fs::create_dir_all(&output_dir)?;
+    let subsets = [
+        ("train", split.train),
+        ("validation", split.validation),
+        ("test", split.test),
+    ];
+    let trajectories = subsets
+        .iter()
+        .map(|(_, files)| Ok(dataset::load_trajectory(files)?.0))
+        .collect::<Result<Vec<_>, AppError>>()?;
+    let views: Vec<TrajectoryView> = trajectories.iter().map(TrajectoryView::from).collect();
+    let masks = simplify::simplify_batch(&views, BatchOptions::new(EPSILON));
+
+    for (((name, files), trajectory), keep_points) in subsets.iter().zip(trajectories).zip(masks) {
+        let reconstruction = quality::Reconstruction::measure(
+            &trajectory,
+            &keep_points,
+            EPSILON as f64 * geo::METERS_PER_UNIT,
+        );
+        let simplified_trajectory = trajectory.filter(&keep_points);
+        let path = output_dir.join(format!("{name}.{}", encoding.extension()));
+        write_output(
+            &path,
+            encoding.encode_simplified(&simplified_trajectory, reconstruction)?,
+        )?;
+
+        println!(
+            "{name:<10} {:>6} files {:>12} points -> {}",
+            files.len().to_formatted_string(&LOCALE),
+            simplified_trajectory
+                .latitudes
+                .len()
+                .to_formatted_string(&LOCALE),
+            path.display()
+        );
+    }
+
+    Ok(())
+}
+
+/// Simplifies every file as a separate trajectory and writes them as a fixed-length tensor.
+fn run_export_tensor(args: cli::ExportTensorArgs, metrics: &Metrics) -> Result<(), AppError> {
+    let cli::ExportTensorArgs {
+        points,
+        format,
+        output,
+        readers,
+        workers,
+    } = args;
+    let defaults = PipelineConfig::default();
+    let pipeline_config = PipelineConfig {
+        readers: readers.unwrap_or(defaults.readers),
+        workers: workers.unwrap_or(defaults.workers),
+        ..defaults
+    };
+
+    let files = dataset::find_plt_files(&dir_path())?;
+    let trajectories = pipeline::simplify_files(&files, EPSILON, pipeline_config, metrics)?;
+
+    let mut bytes = Vec::new();
+    tensor::write_tensor(&mut bytes, &trajectories, points, format.into())?;
+    write_output(&output, bytes)?;
 
+    println!(
+        "Wrote {} trajectories of {} points to {}",
+        trajectories.len().to_formatted_string(&LOCALE),
+        points.to_formatted_string(&LOCALE),
+        output
+    );
+
+    Ok(())
+}
+
+/// Simplifies every file as its own trajectory and writes it to the output directory in
+/// the given format, through the parse, clean, simplify, encode and write stages of a
+/// scheduler, with the thread count of any stage set by `--threads <stage>=<n>`. With
+/// `--datum`, a `datum` stage after `clean` converts the points to WGS 84.
+fn run_batch(args: cli::BatchArgs, metrics: &Metrics) -> Result<(), AppError> {
+    let cli::BatchArgs {
+        format: encoding,
+        output_dir,
+        datum,
+        threads,
+    } = args;
+
+    let config = PipelineConfig::default();
+    let mut scheduler = pipeline::Scheduler::new(config.capacity)
+        .stage(pipeline::parse_stage(config.readers))
+        .stage(pipeline::clean_stage(1))
+        .stage(pipeline::simplify_stage(config.workers, EPSILON, metrics))
+        .stage(pipeline::encode_stage(config.workers, encoding))
+        .stage(pipeline::write_stage(1, &output_dir, encoding));
+    if let Some(datum) = datum {
+        let stage = pipeline::datum_stage(config.workers, datum, Datum::Wgs84);
+        scheduler.insert_after("clean", stage);
+    }
+    for (stage, count) in threads {
+        if !scheduler.set_threads(&stage, count) {
+            return Err(AppError::Usage(
+                "batch --threads takes a stage of the pipeline",
+            ));
+        }
+    }
+
+    fs::create_dir_all(&output_dir)?;
+    let files = dataset::find_track_files(&dir_path())?;
     let start = Instant::now();
-    let all_points = {
-        let mut all_points = Vec::new();
-
-        // Read all files in the directory
-        for entry in fs::read_dir(dir_path)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.extension().and_then(|s| s.to_str()) == Some("plt") {
-                let file_size = fs::metadata(&path)?.len();
-                total_size += file_size;
-
-                let file = fs::File::open(&path)?;
-                let reader = std::io::BufReader::new(file);
-                let points = parse_plt_file(reader)?;
-                all_points.extend(points);
+    let jobs = scheduler.run(pipeline::discover(&files))?;
+    let duration = start.elapsed();
+
+    let stages: Vec<String> = scheduler
+        .stages()
+        .iter()
+        .map(|(name, threads)| format!("{name} ({threads})"))
+        .collect();
+    println!("Stages: {}", stages.join(", "));
+    println!(
+        "Wrote {} trajectories ({} bytes) to {} in {duration:?}",
+        jobs.len().to_formatted_string(&LOCALE),
+        jobs.iter()
+            .map(|job| job.bytes.len())
+            .sum::<usize>()
+            .to_formatted_string(&LOCALE),
+        output_dir.display()
+    );
+
+    Ok(())
+}
+
+/// Writes the simplified dataset as a GeoJSON overlay coloring each segment by its error.
+fn run_export_quality(args: cli::OutputArgs) -> Result<(), AppError> {
+    let cli::OutputArgs { output } = args;
+
+    let files = dataset::find_plt_files(&dir_path())?;
+    let (trajectory, _) = dataset::load_trajectory(&files)?;
+    let keep_points = simplify::simplify(&trajectory.latitudes, &trajectory.longitudes, EPSILON);
+
+    let epsilon_m = EPSILON as f64 * geo::METERS_PER_UNIT;
+    let geojson = quality::heat_geojson(&trajectory, &keep_points, epsilon_m);
+    write_output(&output, serde_json::to_vec(&geojson)?)?;
+
+    println!(
+        "Wrote {} segments to {}",
+        geojson["features"]
+            .as_array()
+            .map_or(0, Vec::len)
+            .to_formatted_string(&LOCALE),
+        output
+    );
+
+    Ok(())
+}
+
+/// Simplifies every file with a tolerance in meters and prints percentiles of the
+/// reconstruction error of its points, and the fraction within `threshold_m` meters
+/// (`epsilon_m` by default), with bootstrap confidence intervals.
+fn run_error_ci(args: cli::ErrorCiArgs) -> Result<(), AppError> {
+    let cli::ErrorCiArgs {
+        metric,
+        epsilon_m,
+        threshold_m,
+    } = args;
+    let metric = quality::ErrorMetric::from(metric);
+    let threshold_m = threshold_m.unwrap_or(epsilon_m);
+
+    let epsilon = geo::meters_to_units(epsilon_m);
+    let files = dataset::find_plt_files(&dir_path())?;
+    let mut errors = Vec::with_capacity(files.len());
+    for file in &files {
+        let (trajectory, _) = dataset::load_trajectory(std::slice::from_ref(file))?;
+        let keep = simplify::simplify(&trajectory.latitudes, &trajectory.longitudes, epsilon);
+        errors.push(quality::point_errors(&trajectory, &keep, metric));
+    }
+    let report =
+        bootstrap::error_report(&errors, &ERROR_CI_QUANTILES, threshold_m, ERROR_CI_BOOTSTRAP);
+
+    println!(
+        "Error of {} points of {} files simplified at {epsilon_m} m, with {}% intervals",
+        report.points.to_formatted_string(&LOCALE),
+        report.trajectories.to_formatted_string(&LOCALE),
+        ERROR_CI_BOOTSTRAP.confidence * 100.0
+    );
+    println!();
+    println!("{:<18} {:>10} {:>22}", "statistic", "value", "interval");
+    for (q, estimate) in &report.quantiles {
+        println!(
+            "{:<18} {:>10} {:>22}",
+            format!("p{} (m)", q * 100.0),
+            format!("{:.2}", estimate.value),
+            format!("[{:.2}, {:.2}]", estimate.low, estimate.high)
+        );
+    }
+    let (threshold_m, within) = report.within;
+    println!(
+        "{:<18} {:>10} {:>22}",
+        format!("within {threshold_m} m"),
+        format!("{:.2}%", within.value * 100.0),
+        format!("[{:.2}%, {:.2}%]", within.low * 100.0, within.high * 100.0)
+    );
+
+    Ok(())
+}
+
+/// Writes the convex hull of every file as a GeoJSON polygon with its area, and prints the
+/// area covered by the whole dataset.
+fn run_export_hull(args: cli::OutputArgs) -> Result<(), AppError> {
+    let cli::OutputArgs { output } = args;
+
+    let files = dataset::find_plt_files(&dir_path())?;
+    let mut features = Vec::new();
+    for file in &files {
+        let (trajectory, _) = dataset::load_trajectory(std::slice::from_ref(file))?;
+        if let Some(mut feature) = geom::hull_geojson(&trajectory) {
+            feature["properties"]["file"] = file.display().to_string().into();
+            features.push(feature);
+        }
+    }
+    let geojson = serde_json::json!({ "type": "FeatureCollection", "features": features });
+    write_output(&output, serde_json::to_vec(&geojson)?)?;
+
+    let (trajectory, _) = dataset::load_trajectory(&files)?;
+    println!(
+        "Wrote the hulls of {} of {} files to {output}",
+        features.len().to_formatted_string(&LOCALE),
+        files.len().to_formatted_string(&LOCALE)
+    );
+    println!(
+        "Area covered by the dataset: {:.3} km²",
+        geom::hull_area_m2(&trajectory) / 1e6
+    );
+
+    Ok(())
+}
+
+/// Simplifies every file and writes the geohash cells its kept points fall in, with visit
+/// counts per cell, as a coarse alternative to the exact tracks.
+fn run_export_geohash(args: cli::ExportGeohashArgs) -> Result<(), AppError> {
+    let cli::ExportGeohashArgs { precision, output } = args;
+
+    let files = dataset::find_plt_files(&dir_path())?;
+    let mut trajectories = Vec::with_capacity(files.len());
+    for file in &files {
+        let (trajectory, _) = dataset::load_trajectory(std::slice::from_ref(file))?;
+        let simplified_trajectory = simplify_trajectory(trajectory, EPSILON);
+        let visits = geohash::visits(&simplified_trajectory, precision);
+        trajectories.push((file.display().to_string(), visits));
+    }
+    let aggregate = geohash::aggregate_json(&trajectories, precision);
+    write_output(&output, serde_json::to_vec(&aggregate)?)?;
+
+    println!(
+        "Wrote {} cells visited by {} files to {output}",
+        aggregate["cells"]
+            .as_object()
+            .map_or(0, |cells| cells.len())
+            .to_formatted_string(&LOCALE),
+        files.len().to_formatted_string(&LOCALE)
+    );
+
+    Ok(())
+}
+
+/// Simplifies one file and writes the geohash cells within `radius_m` meters of the
+/// simplified route as GeoJSON rectangles.
+fn run_corridor_cells(args: cli::CorridorCellsArgs) -> Result<(), AppError> {
+    let cli::CorridorCellsArgs {
+        radius_m,
+        precision,
+        output,
+        file,
+    } = args;
+
+    let (trajectory, _) = dataset::load_trajectory(&[file])?;
+    let route = simplify_trajectory(trajectory, EPSILON);
+    let cells = Corridor::new(&route, radius_m).cells(precision);
+    write_output(
+        &output,
+        serde_json::to_vec(&geohash::cells_geojson(&cells))?,
+    )?;
+
+    println!(
+        "Wrote {} cells within {radius_m} m of {} simplified points to {output}",
+        cells.len().to_formatted_string(&LOCALE),
+        route.latitudes.len().to_formatted_string(&LOCALE)
+    );
+
+    Ok(())
+}
+
+/// Simplifies one file and prints the points of interest within `radius_m` meters of the
+/// simplified route. Every line of the CSV file starts with a latitude and a longitude in
+/// degrees; lines that do not, such as a header, are skipped.
+fn run_pois_along(args: cli::PoisAlongArgs) -> Result<(), AppError> {
+    let cli::PoisAlongArgs {
+        radius_m,
+        pois,
+        file,
+    } = args;
+
+    let (trajectory, _) = dataset::load_trajectory(&[file])?;
+    let corridor = Corridor::new(&simplify_trajectory(trajectory, EPSILON), radius_m);
+
+    let pois = fs::read_to_string(pois)?;
+    let mut candidates = 0;
+    let mut matches = 0;
+    for line in pois.lines() {
+        let mut fields = line.split(',').map(|field| field.trim().parse::<f64>());
+        let (Some(Ok(latitude)), Some(Ok(longitude))) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        candidates += 1;
+        if corridor.contains(latitude, longitude) {
+            matches += 1;
+            println!("{line}");
+        }
+    }
+
+    println!(
+        "{} of {} points of interest within {radius_m} m of the route",
+        matches.to_formatted_string(&LOCALE),
+        candidates.to_formatted_string(&LOCALE)
+    );
+
+    Ok(())
+}
+
+/// Simplifies one file and writes the area within `radius_m` meters of the simplified route
+/// as a GeoJSON polygon.
+fn run_buffer(args: cli::BufferArgs) -> Result<(), AppError> {
+    let cli::BufferArgs {
+        radius_m,
+        output,
+        file,
+    } = args;
+
+    let (trajectory, _) = dataset::load_trajectory(&[file])?;
+    let polygon = simplify_trajectory(trajectory, EPSILON).buffer(radius_m);
+    let feature = serde_json::json!({
+        "type": "Feature",
+        "geometry": polygon.geojson(),
+        "properties": { "radius_m": radius_m, "area_m2": polygon.area_m2 },
+    });
+    write_output(&output, serde_json::to_vec(&feature)?)?;
+
+    println!(
+        "Wrote the {:.3} km² within {radius_m} m of the route to {output}",
+        polygon.area_m2 / 1e6
+    );
+
+    Ok(())
+}
+
+/// Writes the simplified dataset with geo-indistinguishable noise on every point, and the
+/// privacy parameters next to it in `<output>.privacy.json`.
+fn run_export_private(args: cli::ExportPrivateArgs) -> Result<(), AppError> {
+    let cli::ExportPrivateArgs {
+        level,
+        radius_m,
+        format: encoding,
+        output,
+    } = args;
+    let params = privacy::GeoIndistinguishability { level, radius_m };
+
+    let files = dataset::find_plt_files(&dir_path())?;
+    let (trajectory, _) = dataset::load_trajectory(&files)?;
+    let simplified_trajectory = simplify_trajectory(trajectory, EPSILON);
+    let noisy = privacy::add_noise(&simplified_trajectory, params, &mut rand::rngs::OsRng);
+
+    let metadata = params.metadata(noisy.timestamps.len());
+    write_output(&output, encoding.encode(&noisy)?)?;
+    write_output(
+        format!("{output}.privacy.json"),
+        serde_json::to_vec_pretty(&metadata)?,
+    )?;
+
+    println!(
+        "Wrote {} points with an expected displacement of {:.0} m to {output}",
+        noisy.timestamps.len().to_formatted_string(&LOCALE),
+        metadata["expected_displacement_m"].as_f64().unwrap_or_default()
+    );
+
+    Ok(())
+}
+
+/// Tells whether two `.plt` files follow the same path, in the same or opposite directions.
+fn run_compare_direction(args: cli::CompareDirectionArgs) -> Result<(), AppError> {
+    let cli::CompareDirectionArgs { a, b, tolerance_m } = args;
+
+    let (a, _) = dataset::load_trajectory(&[a])?;
+    let (b, _) = dataset::load_trajectory(&[b])?;
+
+    for (name, trajectory) in [("A", &a), ("B", &b)] {
+        match direction::overall_bearing(trajectory) {
+            Some(bearing) => println!("Bearing {name}: {bearing:>6.1}°"),
+            None => println!("Bearing {name}:    n/a"),
+        }
+    }
+    println!("Direction: {:?}", direction::compare(&a, &b, tolerance_m));
+
+    Ok(())
+}
+
+/// Simplifies two files and prints where their routes cross, optionally only where both
+/// pass within `window_s` seconds of each other.
+fn run_crossings(args: cli::CrossingsArgs) -> Result<(), AppError> {
+    let cli::CrossingsArgs {
+        a,
+        b,
+        window_s: time_window_s,
+    } = args;
+
+    let (a, _) = dataset::load_trajectory(&[a])?;
+    let (b, _) = dataset::load_trajectory(&[b])?;
+    let a = simplify_trajectory(a, EPSILON);
+    let b = simplify_trajectory(b, EPSILON);
+
+    let crossings = intersection::crossings(&a, &b, time_window_s);
+    for crossing in &crossings {
+        println!(
+            "{:.6},{:.6}: A at {}, B at {} ({} s apart)",
+            crossing.latitude,
+            crossing.longitude,
+            crossing.time_a,
+            crossing.time_b,
+            crossing.time_a.abs_diff(crossing.time_b)
+        );
+    }
+    println!("{} crossings", crossings.len().to_formatted_string(&LOCALE));
+
+    Ok(())
+}
+
+/// Simplifies every file as a trip and writes the routes each user travels, ranked by
+/// number of trips.
+fn run_routes(args: cli::RoutesArgs) -> Result<(), AppError> {
+    let cli::RoutesArgs {
+        endpoint_radius_m,
+        tolerance_m,
+        output,
+    } = args;
+    let params = routes::RouteParams {
+        endpoint_radius_m,
+        path_tolerance_m: tolerance_m,
+    };
+
+    let trips = load_trips()?;
+    let ranked = routes::mine_routes(&trips, params);
+    write_output(
+        &output,
+        serde_json::to_vec(&routes::routes_json(&ranked, &trips))?,
+    )?;
+
+    for (rank, route) in ranked.iter().take(10).enumerate() {
+        println!(
+            "{:>2}. user {}: {} trips",
+            rank + 1,
+            route.user,
+            route.trips.len().to_formatted_string(&LOCALE)
+        );
+    }
+    println!(
+        "Wrote {} routes of {} trips to {output}",
+        ranked.len().to_formatted_string(&LOCALE),
+        trips.len().to_formatted_string(&LOCALE)
+    );
+
+    Ok(())
+}
+
+/// Writes the distance and time every file spent in each zone of a GeoJSON file, as CSV.
+///
+/// Original points are measured, unless `max_gap_m` is given: the simplified trajectory is
+/// then densified so that no segment exceeds `max_gap_m` meters, and measured instead.
+fn run_zones(args: cli::ZonesArgs) -> Result<(), AppError> {
+    let cli::ZonesArgs {
+        zones,
+        output,
+        max_gap_m,
+    } = args;
+    let zones = zones::Zone::from_geojson(&fs::read_to_string(zones)?)?;
+
+    let files = dataset::find_plt_files(&dir_path())?;
+    let mut csv = String::from("file,zone,distance_m,duration_s\n");
+    let mut totals = vec![zones::ZoneUsage::default(); zones.len()];
+    for file in &files {
+        let (trajectory, _) = dataset::load_trajectory(std::slice::from_ref(file))?;
+        let trajectory = match max_gap_m {
+            Some(max_gap_m) => {
+                resample::densify(&simplify_trajectory(trajectory, EPSILON), max_gap_m)
+            }
+            None => trajectory,
+        };
+        for (zone, total) in zones.iter().zip(&mut totals) {
+            let usage = zone.usage(&trajectory);
+            if usage.distance_m > 0.0 || usage.duration_s > 0.0 {
+                csv.push_str(&format!(
+                    "{},{},{:.1},{:.0}\n",
+                    file.display(),
+                    zone.name,
+                    usage.distance_m,
+                    usage.duration_s
+                ));
+                total.distance_m += usage.distance_m;
+                total.duration_s += usage.duration_s;
             }
         }
+    }
+    write_output(&output, csv.into_bytes())?;
+
+    for (zone, total) in zones.iter().zip(&totals) {
+        println!(
+            "{:<20}{:>12.1} km{:>10.1} h",
+            zone.name,
+            total.distance_m / 1000.0,
+            total.duration_s / 3600.0
+        );
+    }
+    println!(
+        "Wrote the usage of {} zones by {} files to {output}",
+        zones.len().to_formatted_string(&LOCALE),
+        files.len().to_formatted_string(&LOCALE)
+    );
+
+    Ok(())
+}
+
+/// Writes the number of trips between each pair of zones of a GeoJSON file and their mean
+/// duration as CSV, every file being a trip.
+fn run_od_matrix(args: cli::OdMatrixArgs) -> Result<(), AppError> {
+    let cli::OdMatrixArgs { zones, output } = args;
+    let zones = zones::Zone::from_geojson(&fs::read_to_string(zones)?)?;
+
+    let files = dataset::find_plt_files(&dir_path())?;
+    let mut trips = Vec::with_capacity(files.len());
+    for file in &files {
+        let (trajectory, _) = dataset::load_trajectory(std::slice::from_ref(file))?;
+        trips.push(trajectory);
+    }
+    let matrix = od::od_matrix(&trips, &zones);
+    write_output(&output, od::od_csv(&matrix, &zones).into_bytes())?;
+
+    let within: usize = matrix
+        .iter()
+        .filter(|((origin, destination), _)| origin.is_some() && destination.is_some())
+        .map(|(_, cell)| cell.trips)
+        .sum();
+    println!(
+        "Wrote {} origin-destination pairs to {output}, {} of {} trips between zones",
+        matrix.len().to_formatted_string(&LOCALE),
+        within.to_formatted_string(&LOCALE),
+        trips.len().to_formatted_string(&LOCALE)
+    );
+
+    Ok(())
+}
 
-        // Sort all points by timestamp
-        all_points.sort_by_key(|p| p.datetime);
-        all_points
+/// Writes the number of trips and the distance traveled by each user at every hour of the
+/// week, every file being a trip.
+fn run_temporal_report(args: cli::TemporalReportArgs) -> Result<(), AppError> {
+    let cli::TemporalReportArgs { format, output } = args;
+
+    let files = dataset::find_plt_files(&dir_path())?;
+    let mut report = temporal::TemporalReport::default();
+    for file in &files {
+        let (trajectory, _) = dataset::load_trajectory(std::slice::from_ref(file))?;
+        report.add_trip(dataset::user_of(file).unwrap_or_default(), &trajectory);
+    }
+    let bytes = if format == cli::ReportFormat::Csv {
+        report.csv().into_bytes()
+    } else {
+        serde_json::to_vec(&report.json())?
     };
-    let total_points = all_points.len();
-    let duration = start.elapsed();
+    write_output(&output, bytes)?;
 
     println!(
-        "Read {} points in {duration:?}",
-        all_points.len().to_formatted_string(&LOCALE),
-        duration = duration
+        "Wrote the trips of {} users in {} files to {output}",
+        report.users.len().to_formatted_string(&LOCALE),
+        files.len().to_formatted_string(&LOCALE)
     );
 
-    let trajectory = Trajectory::new(all_points);
+    Ok(())
+}
 
-    // Simplify the points using Douglas-Peucker algorithm
-    let start = Instant::now();
+/// Writes the stay points of every file, places where it remained within
+/// `max_distance_m` meters for `min_duration_s` seconds or more, as CSV. Files are
+/// processed in parallel.
+fn run_stay_points(args: cli::StayPointsArgs) -> Result<(), AppError> {
+    let cli::StayPointsArgs {
+        max_distance_m,
+        min_duration_s,
+        output,
+    } = args;
+    let params = stays::StayParams {
+        max_distance_m,
+        min_duration_s,
+    };
+
+    let files = dataset::find_plt_files(&dir_path())?;
+    let mut trajectories = Vec::with_capacity(files.len());
+    for file in &files {
+        let (trajectory, _) = dataset::load_trajectory(std::slice::from_ref(file))?;
+        trajectories.push(trajectory);
+    }
+    let threads = std::thread::available_parallelism().map_or(1, usize::from);
+    let found = stays::detect_batch(&trajectories, params, threads);
+
+    let mut csv = String::from("file,latitude,longitude,arrival,departure,duration_s\n");
+    for (file, stays) in files.iter().zip(&found) {
+        for stay in stays {
+            csv.push_str(&format!(
+                "{},{:.6},{:.6},{},{},{}\n",
+                file.display(),
+                geo::to_degrees(stay.latitude),
+                geo::to_degrees(stay.longitude),
+                stay.arrival,
+                stay.departure,
+                stay.departure - stay.arrival
+            ));
+        }
+    }
+    write_output(&output, csv.into_bytes())?;
+
+    println!(
+        "Wrote {} stay points of {} files to {output}",
+        found.iter().map(Vec::len).sum::<usize>().to_formatted_string(&LOCALE),
+        files.len().to_formatted_string(&LOCALE)
+    );
+
+    Ok(())
+}
+
+/// Estimates the positional noise of every file and writes it as JSON metadata, one entry
+/// per file.
+fn run_estimate_noise(args: cli::OutputArgs) -> Result<(), AppError> {
+    let cli::OutputArgs { output } = args;
+
+    let files = dataset::find_plt_files(&dir_path())?;
+    let mut entries = Vec::with_capacity(files.len());
+    let mut sigmas = Vec::new();
+    for file in &files {
+        let (trajectory, _) = dataset::load_trajectory(std::slice::from_ref(file))?;
+        let estimate = noise::estimate(&trajectory, NoiseParams::default());
+        sigmas.extend(estimate.sigma_m());
+        let mut entry = estimate.metadata();
+        entry["file"] = serde_json::Value::from(file.display().to_string());
+        entries.push(entry);
+    }
+    let json = serde_json::to_vec(&entries)?;
+    write_output(&output, json)?;
+
+    sigmas.sort_by(f64::total_cmp);
+    println!(
+        "Wrote the noise of {} files to {output}, {} with an estimate",
+        files.len().to_formatted_string(&LOCALE),
+        sigmas.len().to_formatted_string(&LOCALE)
+    );
+    if !sigmas.is_empty() {
+        println!(
+            "Median noise: {:.1} m per axis",
+            sigmas[(sigmas.len() - 1) / 2]
+        );
+    }
+
+    Ok(())
+}
+
+/// Serves the simplification over HTTP, with the limits given as flags.
+fn run_serve_http(args: cli::ServeHttpArgs) -> Result<(), AppError> {
+    let cli::ServeHttpArgs {
+        address,
+        max_body_bytes,
+        max_points,
+        requests_per_minute,
+        burst,
+        max_connections,
+        tenants,
+    } = args;
+    let tenants = tenants
+        .map(|path| tenants::Tenants::load(&path))
+        .transpose()?;
+    let defaults = server::ServerLimits::default();
+    let limits = server::ServerLimits {
+        max_body_bytes: max_body_bytes.unwrap_or(defaults.max_body_bytes),
+        max_points: max_points.unwrap_or(defaults.max_points),
+        requests_per_minute: requests_per_minute.unwrap_or(defaults.requests_per_minute),
+        burst: burst.unwrap_or(defaults.burst),
+        max_connections: max_connections.unwrap_or(defaults.max_connections),
+    };
+
+    let listener = std::net::TcpListener::bind(address)?;
+    println!(
+        "Serving POST /simplify and GET /metrics on http://{} (bodies up to {} bytes, {} points, {} requests per minute, {} connections)",
+        listener.local_addr()?,
+        limits.max_body_bytes.to_formatted_string(&LOCALE),
+        limits.max_points.to_formatted_string(&LOCALE),
+        limits.requests_per_minute,
+        limits.max_connections
+    );
+    server::serve(&listener, &limits, tenants.as_ref(), &Metrics::default());
+
+    Ok(())
+}
+
+/// Simplifies every file and serves them over Arrow Flight, one flight per file, on
+/// `address`.
+fn run_serve_arrow(args: cli::ServeArrowArgs) -> Result<(), AppError> {
+    let cli::ServeArrowArgs { address, epsilon_m } = args;
+    let epsilon = epsilon_m.map_or(EPSILON, geo::meters_to_units);
+    let address: std::net::SocketAddr = address
+        .parse()
+        .map_err(|_| AppError::Usage("serve-arrow needs an address such as 127.0.0.1:8815"))?;
+
+    let files = dataset::find_plt_files(&dir_path())?;
+    let mut simplified = Vec::with_capacity(files.len());
+    for file in &files {
+        let (trajectory, _) = dataset::load_trajectory(std::slice::from_ref(file))?;
+        let name = file
+            .file_name()
+            .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+        simplified.push((name, simplify_trajectory(trajectory, epsilon)));
+    }
+    let points = simplified
+        .iter()
+        .map(|(_, trajectory)| trajectory.timestamps.len())
+        .sum::<usize>();
+    let flights = flight::TrajectoryFlights::new(simplified)?;
+
+    println!(
+        "Serving {} simplified points of {} files over Arrow Flight on grpc://{address}",
+        points.to_formatted_string(&LOCALE),
+        files.len().to_formatted_string(&LOCALE),
+    );
+    tokio::runtime::Runtime::new()?.block_on(flight::serve(address, flights))?;
+
+    Ok(())
+}
+
+/// Simplifies every file and writes them as Parquet partitioned by user and date, with the
+/// SQL of DuckDB views over them.
+fn run_export_parquet(args: cli::ExportParquetArgs, metrics: &Metrics) -> Result<(), AppError> {
+    let cli::ExportParquetArgs {
+        output_dir,
+        epsilon_m,
+    } = args;
+    let epsilon = epsilon_m.map_or(EPSILON, geo::meters_to_units);
+
+    let files = dataset::find_plt_files(&dir_path())?;
+    let mut partitions: BTreeMap<PathBuf, Vec<(String, Trajectory)>> = BTreeMap::new();
+    for file in &files {
+        let (trajectory, _) = dataset::load_trajectory(std::slice::from_ref(file))?;
+        let Some(&start) = trajectory.timestamps.first() else {
+            continue;
+        };
+        let partition = parquet::partition(dataset::user_of(file).unwrap_or_default(), start);
+        let name = file
+            .file_name()
+            .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+        let keep_points = metrics.simplify(&trajectory, epsilon);
+        partitions
+            .entry(partition)
+            .or_default()
+            .push((name, trajectory.filter(&keep_points)));
+    }
+
+    for (partition, trajectories) in &partitions {
+        let dir = output_dir.join(partition);
+        fs::create_dir_all(&dir)?;
+        let file = parquet::encode(
+            trajectories
+                .iter()
+                .map(|(name, trajectory)| (name.as_str(), trajectory)),
+        )?;
+        write_output(dir.join("part-0.parquet"), file)?;
+    }
+    let views = output_dir.join("views.sql");
+    write_output(
+        &views,
+        parquet::duckdb_views(&output_dir.to_string_lossy()).into_bytes(),
+    )?;
+
+    println!(
+        "Wrote {} files in {} partitions to {}",
+        files.len().to_formatted_string(&LOCALE),
+        partitions.len().to_formatted_string(&LOCALE),
+        output_dir.display()
+    );
+    println!("Explore them with: duckdb -init {}", views.display());
+
+    Ok(())
+}
+
+/// Publishes the latest position of every user to a Redis GEO set.
+fn run_publish_redis(args: cli::PublishRedisArgs) -> Result<(), AppError> {
+    let cli::PublishRedisArgs { address, key } = args;
+
+    let mut latest = redis::LatestPositions::default();
+    for file in dataset::find_plt_files(&dir_path())? {
+        let (trajectory, _) = dataset::load_trajectory(std::slice::from_ref(&file))?;
+        latest.update(dataset::user_of(&file).unwrap_or_default(), &trajectory);
+    }
+
+    let positions = latest.into_positions();
+    let mut publisher = redis::GeoPublisher::connect(&address)?;
+    let (added, skipped) = publisher.publish(&key, &positions)?;
+    println!(
+        "Published {} positions to {key} ({} new devices, {} beyond the poles limit)",
+        (positions.len() - skipped).to_formatted_string(&LOCALE),
+        added.to_formatted_string(&LOCALE),
+        skipped.to_formatted_string(&LOCALE)
+    );
+
+    Ok(())
+}
+
+/// Puts the simplified files into a store, or lists, reads, previews or deletes the stored
+/// ones.
+fn run_store(args: cli::StoreArgs, metrics: &Metrics) -> Result<(), AppError> {
+    let cli::StoreArgs {
+        location,
+        tenant,
+        command,
+    } = args;
+    let mut store = store::open(&location)?;
+    if let Some(tenant) = tenant {
+        store = Box::new(store::NamespacedStore::new(store, &tenant)?);
+    }
+    match command {
+        cli::StoreCommand::Put { epsilon_m } => {
+            let epsilon = epsilon_m.map_or(EPSILON, geo::meters_to_units);
+            let files = dataset::find_plt_files(&dir_path())?;
+            for file in &files {
+                let (trajectory, _) = dataset::load_trajectory(std::slice::from_ref(file))?;
+                let keep_points = metrics.simplify(&trajectory, epsilon);
+                let reconstruction = quality::Reconstruction::measure(
+                    &trajectory,
+                    &keep_points,
+                    epsilon as f64 * geo::METERS_PER_UNIT,
+                );
+                let id = dataset::trajectory_id(file);
+                store.put(&id, &trajectory.filter(&keep_points))?;
+                store.put_metadata(
+                    &id,
+                    &serde_json::json!({ "reconstruction": reconstruction.metadata() }),
+                )?;
+            }
+            println!(
+                "Stored {} trajectories in {location}",
+                files.len().to_formatted_string(&LOCALE)
+            );
+        }
+        cli::StoreCommand::List { start, end } => {
+            let ids = match start.zip(end) {
+                Some((start, end)) => store.list_between(start, end)?,
+                None => store.list()?,
+            };
+            for id in ids {
+                println!("{id}");
+            }
+        }
+        cli::StoreCommand::Get(ref args) | cli::StoreCommand::Thumbnail(ref args) => {
+            let trajectory = match command {
+                cli::StoreCommand::Get(_) => store.get(&args.id)?,
+                _ => store.thumbnail(&args.id)?,
+            };
+            let trajectory = trajectory.ok_or_else(|| {
+                std::io::Error::new(std::io::ErrorKind::NotFound, args.id.clone())
+            })?;
+            write_output(&args.output, args.format.encode(&trajectory)?)?;
+        }
+        cli::StoreCommand::Delete { id } => {
+            if !store.delete(&id)? {
+                println!("No trajectory {id} in {location}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes every trajectory of a store to a directory in the given format, one file per
+/// trajectory named after its id.
+fn run_export(args: cli::ExportArgs) -> Result<(), AppError> {
+    let cli::ExportArgs {
+        store: location,
+        format,
+        out,
+    } = args;
+    let store = store::open(&location)?;
+
+    fs::create_dir_all(&out)?;
+    let (mut trajectories, mut points) = (0, 0);
+    for id in store.list()? {
+        // Deleted since listed
+        let Some(trajectory) = store.get(&id)? else {
+            continue;
+        };
+        trajectories += 1;
+        points += trajectory.latitudes.len();
+        let path = out.join(format!("{id}.{}", format.extension()));
+        write_output(&path, format.encode(&id, &trajectory)?)?;
+        if let Some(metadata) = store.metadata(&id)? {
+            write_output(
+                out.join(format!("{id}.json")),
+                serde_json::to_vec(&metadata)?,
+            )?;
+        }
+    }
+    println!(
+        "Exported {} trajectories of {} points to {}",
+        trajectories.to_formatted_string(&LOCALE),
+        points.to_formatted_string(&LOCALE),
+        out.display()
+    );
+
+    Ok(())
+}
+
+/// Generalizes every file of the dataset as its own trajectory for a zoom level of a web
+/// map with a preset, and writes those kept to a directory in the format of the preset.
+fn run_generalize(args: cli::GeneralizeArgs, metrics: &Metrics) -> Result<(), AppError> {
+    let cli::GeneralizeArgs { preset, out } = args;
+
+    fs::create_dir_all(&out)?;
+    let files = dataset::find_track_files(&dir_path())?;
+    let (collection, _) = dataset::load_collection(&files)?;
+    let mut points = 0;
+    let total_points = collection.points();
+    for (file, trajectory) in files.iter().zip(collection.trajectories) {
+        let (start, file_points) = (Instant::now(), trajectory.latitudes.len());
+        let trajectory = preset.generalize(trajectory);
+        metrics.record_simplification(file_points, trajectory.latitudes.len(), start.elapsed());
+        points += trajectory.latitudes.len();
+        let id = dataset::trajectory_id(file);
+        let path = out.join(format!("{id}.{}", preset.format.extension()));
+        write_output(&path, preset.format.encode(&id, &trajectory)?)?;
+    }
+    println!(
+        "Generalized {} points to {} for zoom {} ({} px)",
+        total_points.to_formatted_string(&LOCALE),
+        points.to_formatted_string(&LOCALE),
+        preset.zoom,
+        preset.epsilon_px
+    );
+    println!(
+        "Wrote {} trajectories, keeping up to {} points, to {}",
+        files.len().to_formatted_string(&LOCALE),
+        preset.min_points,
+        out.display()
+    );
+
+    Ok(())
+}
+
+/// Imports every GPX, CSV, Google Takeout or `.plt` file of a directory into a store, one
+/// trajectory per file simplified with the configuration if given, with where it comes
+/// from and how it was simplified as metadata.
+fn run_import(args: cli::ImportArgs, metrics: &Metrics) -> Result<(), AppError> {
+    let cli::ImportArgs {
+        dir,
+        store: location,
+        config: config_path,
+    } = args;
+    let config = config_path
+        .as_deref()
+        .map(|path| Config::load(Path::new(path)))
+        .transpose()?;
+    let policy = match config_path {
+        Some(path) => serde_json::json!({ "config": path }),
+        None => serde_json::json!({ "epsilon_m": EPSILON as f64 * geo::METERS_PER_UNIT }),
+    };
+    let store = store::open(&location)?;
+
+    let (mut imported, mut points, mut kept_points) = (0, 0, 0);
+    for file in dataset::find_import_files(&dir)? {
+        let Some(format) = formats::Format::detect(&file) else {
+            continue;
+        };
+        let (reader, _) = dataset::open(&file)?;
+        let mut file_points = format.parse(reader)?;
+        file_points.sort_by_key(|point| point.datetime);
+        let trajectory = Trajectory::new(file_points);
+        let Some(&start) = trajectory.timestamps.first() else {
+            continue;
+        };
+
+        let (keep_points, epsilon) = match &config {
+            Some(config) => {
+                let start = Instant::now();
+                let (keep_points, epsilon) =
+                    run::simplify_with_config(&trajectory, config, &[], EPSILON, None);
+                let kept = keep_points.iter().filter(|&&keep| keep).count();
+                metrics.record_simplification(keep_points.len(), kept, start.elapsed());
+                (keep_points, epsilon)
+            }
+            None => (metrics.simplify(&trajectory, EPSILON), EPSILON),
+        };
+        let reconstruction = quality::Reconstruction::measure(
+            &trajectory,
+            &keep_points,
+            epsilon as f64 * geo::METERS_PER_UNIT,
+        );
+        let file_points = trajectory.latitudes.len();
+        let simplified = trajectory.filter(&keep_points);
+        let id = format!("{}-{start}", dataset::trajectory_id(&file));
+        store.put(&id, &simplified)?;
+        store.put_metadata(
+            &id,
+            &serde_json::json!({
+                "source": file.display().to_string(),
+                "format": format.name(),
+                "points": file_points,
+                "kept_points": simplified.latitudes.len(),
+                "simplification": policy,
+                "reconstruction": reconstruction.metadata(),
+                "imported_at": chrono::Utc::now().to_rfc3339(),
+            }),
+        )?;
+
+        imported += 1;
+        points += file_points;
+        kept_points += simplified.latitudes.len();
+    }
+    println!(
+        "Imported {} trajectories into {location}, simplified from {} to {} points",
+        imported.to_formatted_string(&LOCALE),
+        points.to_formatted_string(&LOCALE),
+        kept_points.to_formatted_string(&LOCALE)
+    );
+
+    Ok(())
+}
+
+/// Upgrades the trajectories of a store written with older versions of the binary format,
+/// keeping a copy of each as it was in the backup directory.
+fn run_migrate(args: cli::MigrateArgs) -> Result<(), AppError> {
+    let cli::MigrateArgs {
+        store: location,
+        backup,
+    } = args;
+    let store = store::open(&location)?;
+    let migration = store::migrate(store.as_ref(), &backup)?;
+    println!(
+        "Migrated {} trajectories to version {} of the binary format ({} already were), backed up to {}",
+        migration.migrated.to_formatted_string(&LOCALE),
+        binary::VERSION,
+        migration.current.to_formatted_string(&LOCALE),
+        backup.display()
+    );
+
+    Ok(())
+}
+
+/// Replays the points of every file, interleaved by time as if the users were tracked live,
+/// into the append-only raw log in batches, and compacts the log into the store whenever
+/// the trajectories idle for `idle_s` seconds may have changed.
+fn run_ingest(args: cli::IngestArgs) -> Result<(), AppError> {
+    let cli::IngestArgs {
+        log,
+        store: location,
+        batch_size,
+        idle_s,
+    } = args;
+
+    let mut points = Vec::new();
+    for file in dataset::find_plt_files(&dir_path())? {
+        let (trajectory, _) = dataset::load_trajectory(std::slice::from_ref(&file))?;
+        let id = dataset::trajectory_id(&file);
+        for i in 0..trajectory.timestamps.len() {
+            points.push(rawlog::RawPoint {
+                trajectory: id.clone(),
+                timestamp: trajectory.timestamps[i],
+                latitude: trajectory.latitudes[i],
+                longitude: trajectory.longitudes[i],
+            });
+        }
+    }
+    points.sort_by_key(|point| point.timestamp);
+
+    let log = rawlog::RawLog::open(&log)?;
+    let store = store::open(&location)?;
+    let totals = rawlog::replay(&log, store.as_ref(), &points, batch_size, EPSILON, idle_s)?;
+
+    println!(
+        "Ingested {} points, compacted {} into {} segments of {} points in {location}",
+        points.len().to_formatted_string(&LOCALE),
+        totals.points.to_formatted_string(&LOCALE),
+        totals.segments.to_formatted_string(&LOCALE),
+        totals.kept_points.to_formatted_string(&LOCALE)
+    );
+
+    Ok(())
+}
+
+/// Simplifies a trace in local coordinates, such as a warehouse robot log, with a
+/// tolerance in meters and writes the kept points in the same CSV format.
+fn run_simplify_local(args: cli::SimplifyLocalArgs, metrics: &Metrics) -> Result<(), AppError> {
+    let cli::SimplifyLocalArgs {
+        epsilon_m,
+        trace,
+        output,
+    } = args;
+    let units_per_meter = 10f64.powi(LOCAL_SCALE as i32);
+
+    let trajectory = dataset::load_local_trace(&trace, LOCAL_SCALE)?;
+    let length_m = trajectory.length_m();
+    let points = trajectory.timestamps.len();
+    let keep_points = metrics.simplify(&trajectory, (epsilon_m * units_per_meter).round() as i64);
+    let simplified = trajectory.filter(&keep_points);
+
+    let mut csv = String::from("timestamp,east,north\n");
+    for ((ts, east), north) in simplified
+        .timestamps
+        .iter()
+        .zip(&simplified.longitudes)
+        .zip(&simplified.latitudes)
+    {
+        csv.push_str(&format!(
+            "{ts},{},{}\n",
+            *east as f64 / units_per_meter,
+            *north as f64 / units_per_meter
+        ));
+    }
+    write_output(&output, csv.into_bytes())?;
+
+    println!(
+        "Simplified {} points ({length_m:.1} m) into {} points ({:.1} m) -> {output}",
+        points.to_formatted_string(&LOCALE),
+        simplified.timestamps.len().to_formatted_string(&LOCALE),
+        simplified.length_m()
+    );
+
+    Ok(())
+}
+
+/// Detects the driving events of one file on its original points and writes them as
+/// GeoJSON points, next to the simplified trajectory they are attached to.
+fn run_events(args: cli::EventsArgs) -> Result<(), AppError> {
+    let cli::EventsArgs {
+        speed_limit_mps,
+        output,
+        file,
+    } = args;
+    let params = events::EventParams {
+        speed_limit_mps,
+        ..EVENT_THRESHOLDS
+    };
+
+    let (trajectory, _) = dataset::load_trajectory(&[file])?;
     let keep_points = simplify::simplify(&trajectory.latitudes, &trajectory.longitudes, EPSILON);
-    let duration = start.elapsed();
+    let events = events::detect(&trajectory, params);
+    let geojson = events::events_geojson(&trajectory, &keep_points, &events);
+    write_output(&output, serde_json::to_vec(&geojson)?)?;
 
-    println!("Ran simplification in {duration:?}");
+    for kind in [
+        events::EventKind::Speeding,
+        events::EventKind::HarshAcceleration,
+        events::EventKind::HarshBraking,
+        events::EventKind::Idling,
+    ] {
+        let count = events.iter().filter(|event| event.kind == kind).count();
+        println!("{:<20}{}", kind.name(), count.to_formatted_string(&LOCALE));
+    }
+    println!(
+        "Wrote {} events to {output}",
+        events.len().to_formatted_string(&LOCALE)
+    );
 
-    let start = Instant::now();
-    let simplified_trajectory = {
-        let mut trajectory = trajectory;
-        let mut i = 0;
-        
-        // Filter all three vectors in a single pass
-        trajectory.latitudes.retain(|_| {
-            let keep = keep_points[i];
-            i += 1;
-            keep
-        });
-        
-        i = 0;
-        trajectory.longitudes.retain(|_| {
-            let keep = keep_points[i];
-            i += 1;
-            keep
-        });
-        
-        i = 0;
-        trajectory.timestamps.retain(|_| {
-            let keep = keep_points[i];
-            i += 1;
-            keep
+    Ok(())
+}
+
+/// Detects the turns of one file, keeps their points through simplification, and writes
+/// them with the simplified trajectory, printing a turn-by-turn summary.
+fn run_turns(args: cli::TurnsArgs) -> Result<(), AppError> {
+    let cli::TurnsArgs {
+        min_angle_deg,
+        output,
+        file,
+    } = args;
+    let params = turns::TurnParams {
+        min_angle_deg,
+        window_m: TURN_WINDOW_M,
+    };
+
+    let (trajectory, _) = dataset::load_trajectory(&[file])?;
+    let turns = turns::detect(&trajectory, params);
+    let mut keep_points =
+        simplify::simplify(&trajectory.latitudes, &trajectory.longitudes, EPSILON);
+    turns::keep_turns(&turns, &mut keep_points);
+    let geojson = turns::turns_geojson(&trajectory, &keep_points, &turns);
+    write_output(&output, serde_json::to_vec(&geojson)?)?;
+
+    let mut distance_m = 0.0;
+    for turn in &turns {
+        println!(
+            "after {:>8} m  {:<13}{:>5.0}°",
+            ((turn.distance_m - distance_m).round() as u64).to_formatted_string(&LOCALE),
+            turn.label.name(),
+            turn.angle_deg
+        );
+        distance_m = turn.distance_m;
+    }
+    println!(
+        "Wrote {} turns and {} points to {output}",
+        turns.len().to_formatted_string(&LOCALE),
+        keep_points
+            .iter()
+            .filter(|&&kept| kept)
+            .count()
+            .to_formatted_string(&LOCALE)
+    );
+
+    Ok(())
+}
+
+/// Writes the Douglas-Peucker split tree of one file as JSON, or as Graphviz DOT for an
+/// output ending in `.dot`, and prints the number of points kept at each depth.
+fn run_split_tree(args: cli::SplitTreeArgs) -> Result<(), AppError> {
+    let cli::SplitTreeArgs {
+        epsilon,
+        output,
+        file,
+    } = args;
+
+    let (trajectory, _) = dataset::load_trajectory(&[file])?;
+    let tree = SplitTree::build(&trajectory.latitudes, &trajectory.longitudes, epsilon);
+    let contents = if output.ends_with(".dot") {
+        tree.to_dot().into_bytes()
+    } else {
+        serde_json::to_vec(&tree.to_json())?
+    };
+    write_output(&output, contents)?;
+
+    for depth in 1..=tree.depth() {
+        let keep = tree.keep_to_depth(depth);
+        let kept = keep.iter().filter(|&&kept| kept).count();
+        println!(
+            "depth {depth:>3}: {:>10} points",
+            kept.to_formatted_string(&LOCALE)
+        );
+    }
+    println!(
+        "Wrote {} splits of {} points to {output}",
+        tree.splits.len().to_formatted_string(&LOCALE),
+        tree.len.to_formatted_string(&LOCALE)
+    );
+
+    Ok(())
+}
+
+/// Runs every simplification algorithm on one file and writes an HTML page comparing
+/// their maps and errors, printing the comparison table.
+fn run_explain(args: cli::ExplainArgs) -> Result<(), AppError> {
+    let cli::ExplainArgs {
+        epsilon_m,
+        output,
+        file,
+    } = args;
+
+    let (trajectory, _) = dataset::load_trajectory(&[PathBuf::from(&file)])?;
+    let comparisons = compare::compare(&trajectory, epsilon_m);
+    let html = compare::html(&file, &trajectory, epsilon_m, &comparisons);
+    write_output(&output, html.into_bytes())?;
+
+    println!(
+        "{:<16}{:>10}{:>12}{:>12}{:>12}",
+        "algorithm", "points", "max error", "mean error", "sync. max"
+    );
+    for comparison in &comparisons {
+        println!(
+            "{:<16}{:>10}{:>10.1} m{:>10.1} m{:>10.1} m",
+            comparison.algorithm.name(),
+            comparison.kept.to_formatted_string(&LOCALE),
+            comparison.perpendicular_m.0,
+            comparison.perpendicular_m.1,
+            comparison.synchronized_m.0
+        );
+    }
+    println!("Wrote the comparison to {output}");
+
+    Ok(())
+}
+
+/// Loads every file of the dataset as a simplified trip of its user.
+fn load_trips() -> Result<Vec<routes::Trip>, AppError> {
+    let files = dataset::find_plt_files(&dir_path())?;
+    let mut trips = Vec::with_capacity(files.len());
+    for file in &files {
+        let (trajectory, _) = dataset::load_trajectory(std::slice::from_ref(file))?;
+        trips.push(routes::Trip {
+            user: dataset::user_of(file).unwrap_or_default().to_string(),
+            name: file.display().to_string(),
+            trajectory: simplify_trajectory(trajectory, EPSILON),
         });
-        
-        trajectory
+    }
+    Ok(trips)
+}
+
+/// Simplifies every file as a trip and writes the trips deviating by more than
+/// `max_deviation_m` meters from the frequent routes of their user, with their deviation.
+fn run_anomalies(args: cli::AnomaliesArgs) -> Result<(), AppError> {
+    let cli::AnomaliesArgs {
+        endpoint_radius_m,
+        tolerance_m,
+        max_deviation_m,
+        output,
+        min_route_trips,
+    } = args;
+    let params = anomaly::AnomalyParams {
+        routes: routes::RouteParams {
+            endpoint_radius_m,
+            path_tolerance_m: tolerance_m,
+        },
+        min_route_trips,
+        max_deviation_m,
     };
-    let duration = start.elapsed();
 
+    let trips = load_trips()?;
+    let anomalies = anomaly::detect(&trips, params);
+    write_output(
+        &output,
+        serde_json::to_vec(&anomaly::anomalies_json(&anomalies, &trips, params))?,
+    )?;
+
+    for anomaly in anomalies.iter().take(10) {
+        println!(
+            "{}: {:.0} m from the usual routes",
+            trips[anomaly.trip].name, anomaly.deviation_m
+        );
+    }
     println!(
-        "Filtered {} points in {duration:?}",
-        simplified_trajectory
-            .latitudes
-            .len()
-            .to_formatted_string(&LOCALE),
-        duration = duration
+        "Flagged {} of {} trips -> {output}",
+        anomalies.len().to_formatted_string(&LOCALE),
+        trips.len().to_formatted_string(&LOCALE)
     );
 
-    // Get the length before consuming the trajectory
-    let simplified_points = simplified_trajectory.latitudes.len();
+    Ok(())
+}
 
-    // Clone the trajectory since we need to use it twice
-    let protobuf_value = simplified_trajectory.clone().to_delta_proto();
-    let serialized_delta = protobuf_value.encode_to_vec();
+/// Simplifies two files and writes the intervals during which they stayed within
+/// `max_distance_m` meters of each other for at least `min_duration_s` seconds, as a
+/// `labels.txt` file with the mode `co-travel`.
+fn run_co_travel(args: cli::CoTravelArgs) -> Result<(), AppError> {
+    let cli::CoTravelArgs {
+        max_distance_m,
+        min_duration_s,
+        output,
+        a,
+        b,
+    } = args;
+    let params = cotravel::CoTravelParams {
+        max_distance_m,
+        min_duration_s,
+        step_s: CO_TRAVEL_STEP_S,
+    };
 
-    let protobuf_value = simplified_trajectory.to_proto();
-    let serialized = protobuf_value.encode_to_vec();
+    let (a, _) = dataset::load_trajectory(&[a])?;
+    let (b, _) = dataset::load_trajectory(&[b])?;
+    let a = simplify_trajectory(a, EPSILON);
+    let b = simplify_trajectory(b, EPSILON);
 
-    println!();
+    let intervals = cotravel::co_travel(&a, &b, params);
+    let labels: Vec<_> = intervals
+        .iter()
+        .map(|interval| interval.label("co-travel"))
+        .collect();
+    let mut bytes = Vec::new();
+    labels::write_labels_file(&mut bytes, &labels)?;
+    write_output(&output, bytes)?;
 
+    for interval in &intervals {
+        println!(
+            "{} to {}: {} s, {:.1} m apart on average",
+            interval.start,
+            interval.end,
+            interval.end - interval.start,
+            interval.mean_distance_m
+        );
+    }
     println!(
-        "Original size:        {:>21} bytes",
-        total_size.to_formatted_string(&LOCALE)
+        "Wrote {} co-travel intervals to {output}",
+        intervals.len().to_formatted_string(&LOCALE)
     );
 
+    Ok(())
+}
+
+/// Prints how many points of the dataset are drawn at each zoom level of a web map,
+/// for each of the given tolerances in pixels.
+///
+/// Each file is simplified as its own trajectory. A tolerance at one zoom level is
+/// equivalent to twice that tolerance at the next lower level, so masks are cached and
+/// shared between tolerances.
+fn run_zoom_levels(args: cli::ZoomLevelsArgs) -> Result<(), AppError> {
+    let cli::ZoomLevelsArgs { mut epsilons_px } = args;
+    if epsilons_px.is_empty() {
+        epsilons_px.push(1.0);
+    }
+
+    let files = dataset::find_plt_files(&dir_path())?;
+    let trajectories = dataset::load_collection(&files)?.0.trajectories;
+
+    let mut cache = cache::SimplificationCache::new(ZOOM_CACHE_CAPACITY);
+    for epsilon_px in epsilons_px {
+        println!("Tolerance {epsilon_px} px:");
+        for zoom in 0..=tile::MAX_ZOOM {
+            let epsilon = tile::zoom_epsilon(zoom, epsilon_px);
+            let points: usize = trajectories
+                .iter()
+                .enumerate()
+                .map(|(id, trajectory)| {
+                    let keep_points = cache.get_or_insert_with(id, epsilon, || {
+                        tile::simplify_for_zoom(
+                            &trajectory.latitudes,
+                            &trajectory.longitudes,
+                            zoom,
+                            epsilon_px,
+                        )
+                    });
+                    keep_points.iter().filter(|&&keep| keep).count()
+                })
+                .sum();
+            println!(
+                "  Zoom {zoom:>2}: {:>12} points",
+                points.to_formatted_string(&LOCALE)
+            );
+        }
+    }
+    println!(
+        "Cache: {} masks, {} hits, {} misses",
+        cache.len().to_formatted_string(&LOCALE),
+        cache.hits().to_formatted_string(&LOCALE),
+        cache.misses().to_formatted_string(&LOCALE)
+    );
+
+    Ok(())
+}
+
+/// Replays the dataset as a live feed arriving in batches, updating the simplification
+/// incrementally after each batch, and compares it with simplifying the whole dataset once.
+fn run_replay(args: cli::ReplayArgs) -> Result<(), AppError> {
+    let cli::ReplayArgs { batch_size } = args;
+
+    let files = dataset::find_plt_files(&dir_path())?;
+    let (trajectory, _) = dataset::load_trajectory(&files)?;
+    let total_points = trajectory.latitudes.len();
+
+    // GeoLife files do not report the accuracy of their fixes
+    let accuracies = vec![None; total_points];
+    let options = StreamingOptions::new(EPSILON);
+
+    let start = Instant::now();
+    let mut keep_points = Vec::new();
+    let mut batches = 0;
+    for len in (batch_size..total_points + batch_size).step_by(batch_size) {
+        let len = len.min(total_points);
+        keep_points = options.update(
+            &trajectory.latitudes[..len],
+            &trajectory.longitudes[..len],
+            &accuracies[..len],
+            &keep_points,
+        );
+        batches += 1;
+    }
+    let incremental_duration = start.elapsed();
+    let incremental_points = keep_points.iter().filter(|&&keep| keep).count();
+
+    let start = Instant::now();
+    let keep_points = simplify::simplify(&trajectory.latitudes, &trajectory.longitudes, EPSILON);
+    let full_duration = start.elapsed();
+    let full_points = keep_points.iter().filter(|&&keep| keep).count();
+
+    println!(
+        "Replayed {} points in {} batches",
+        total_points.to_formatted_string(&LOCALE),
+        batches.to_formatted_string(&LOCALE)
+    );
+    println!(
+        "Incremental: {:>12} points in {incremental_duration:?}",
+        incremental_points.to_formatted_string(&LOCALE)
+    );
+    println!(
+        "Full:        {:>12} points in {full_duration:?}",
+        full_points.to_formatted_string(&LOCALE)
+    );
+
+    Ok(())
+}
+
+/// Replays every file in batches through the streaming simplifier and broadcasts the
+/// newly committed points of each batch to the WebSocket clients following the file.
+///
+/// With `--tenants`, the files of each tenant are read from its namespace, the directory
+/// named after it, and only broadcast to the clients authenticated as that tenant.
+fn run_serve_live(args: cli::ServeLiveArgs) -> Result<(), AppError> {
+    let cli::ServeLiveArgs {
+        address,
+        batch_size,
+        interval_ms,
+        tenants,
+    } = args;
+    let tenants = tenants
+        .map(|path| tenants::Tenants::load(&path))
+        .transpose()?;
+    let interval = std::time::Duration::from_millis(interval_ms);
+
+    let sources: Vec<(Option<String>, PathBuf)> = match &tenants {
+        Some(tenants) => tenants
+            .names()
+            .iter()
+            .map(|tenant| {
+                let dir = tenants::namespace(&dir_path(), tenant)?;
+                Ok((Some(tenant.clone()), dir))
+            })
+            .collect::<Result<_, tenants::TenantError>>()?,
+        None => vec![(None, dir_path())],
+    };
+    let feed = std::sync::Arc::new(match tenants {
+        Some(tenants) => websocket::LiveFeed::with_tenants(tenants),
+        None => websocket::LiveFeed::default(),
+    });
+    let listener = std::net::TcpListener::bind(address)?;
+    println!("Live feed on ws://{}/", listener.local_addr()?);
+    feed.serve(listener);
+
+    let options = StreamingOptions::new(EPSILON);
+    let (mut deliveries, mut points) = (0, 0);
+    let mut files = Vec::new();
+    for (tenant, dir) in &sources {
+        for file in dataset::find_plt_files(dir)? {
+            files.push((tenant.as_deref(), file));
+        }
+    }
+    for (tenant, file) in files {
+        let (trajectory, _) = dataset::load_trajectory(std::slice::from_ref(&file))?;
+        let name = file
+            .file_name()
+            .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+        let (committed, messages) =
+            feed.replay(tenant, &name, &trajectory, options, batch_size, interval);
+        points += committed;
+        deliveries += messages;
+    }
+
     println!(
-        "Size after simplification: {:>16} bytes",
-        serialized.len().to_formatted_string(&LOCALE)
+        "Broadcast {} committed points in {} messages to clients",
+        points.to_formatted_string(&LOCALE),
+        deliveries.to_formatted_string(&LOCALE)
     );
 
+    Ok(())
+}
+
+/// Merges recordings of the same trip into one trajectory, written unsimplified in the
+/// requested format.
+///
+/// With `--align`, the clock offset of each file relative to the first one is estimated
+/// and corrected first. With `--fuse`, positions are averaged with the given weights
+/// instead of resolving conflicts with the policy.
+fn run_merge(args: cli::MergeArgs) -> Result<(), AppError> {
+    let cli::MergeArgs {
+        policy,
+        format: encoding,
+        output,
+        files,
+        max_offset_s: max_offset,
+        weights,
+    } = args;
+    if weights
+        .as_ref()
+        .is_some_and(|weights| weights.len() != files.len())
+    {
+        return Err(AppError::Usage("merge --fuse takes one weight per file"));
+    }
+
+    let mut trajectories = files
+        .iter()
+        .map(|file| Ok(dataset::load_trajectory(&[PathBuf::from(file)])?.0))
+        .collect::<Result<Vec<_>, AppError>>()?;
+
+    if let Some(max_offset) = max_offset {
+        let (reference, others) = trajectories.split_at_mut(1);
+        for (file, other) in files[1..].iter().zip(others) {
+            match merge::estimate_offset(&reference[0], other, max_offset) {
+                Some(offset) => {
+                    println!("Offset of {file}: {offset:+} s");
+                    merge::shift(other, offset);
+                }
+                None => println!("Offset of {file}: not enough overlap, left unchanged"),
+            }
+        }
+    }
+
+    let merged = match weights {
+        Some(weights) => merge::fuse(&trajectories, &weights),
+        None => merge::merge(&trajectories, policy)?,
+    };
+    write_output(&output, encoding.encode(&merged)?)?;
+
     println!(
-        "Serialized DELTA size: {:>20} bytes",
-        serialized_delta.len().to_formatted_string(&LOCALE)
+        "Merged {} points from {} files into {} points -> {}",
+        trajectories
+            .iter()
+            .map(|trajectory| trajectory.timestamps.len())
+            .sum::<usize>()
+            .to_formatted_string(&LOCALE),
+        files.len(),
+        merged.timestamps.len().to_formatted_string(&LOCALE),
+        output
     );
+
+    Ok(())
+}
+
+/// Resamples one file every `interval_m` meters along its path and writes it in the given format.
+fn run_resample(args: cli::ResampleArgs) -> Result<(), AppError> {
+    let cli::ResampleArgs {
+        interval_m,
+        format: encoding,
+        output,
+        file,
+    } = args;
+
+    let (trajectory, _) = dataset::load_trajectory(&[file])?;
+    let resampled = resample::resample_by_distance(&trajectory, interval_m);
+    write_output(&output, encoding.encode(&resampled)?)?;
+
     println!(
-        "Total points: {:>29} points",
-        total_points.to_formatted_string(&LOCALE)
+        "Resampled {} points into {} points every {interval_m} m -> {output}",
+        trajectory.timestamps.len().to_formatted_string(&LOCALE),
+        resampled.timestamps.len().to_formatted_string(&LOCALE),
     );
+
+    Ok(())
+}
+
+/// Inserts points into one file so no segment exceeds `max_gap_m` meters and writes it in the
+/// given format.
+fn run_densify(args: cli::DensifyArgs) -> Result<(), AppError> {
+    let cli::DensifyArgs {
+        max_gap_m,
+        format: encoding,
+        output,
+        file,
+    } = args;
+
+    let (trajectory, _) = dataset::load_trajectory(&[file])?;
+    let densified = resample::densify(&trajectory, max_gap_m);
+    write_output(&output, encoding.encode(&densified)?)?;
+
     println!(
-        "simplified points: {:>24} points",
-        simplified_points.to_formatted_string(&LOCALE)
+        "Densified {} points into {} points at most {max_gap_m} m apart -> {output}",
+        trajectory.timestamps.len().to_formatted_string(&LOCALE),
+        densified.timestamps.len().to_formatted_string(&LOCALE),
     );
 
+    Ok(())
+}
+
+/// Bridges the GPS outages of one file by dead reckoning from IMU samples, then simplifies
+/// it and writes it in the given format.
+fn run_fill_gaps(args: cli::FillGapsArgs) -> Result<(), AppError> {
+    let cli::FillGapsArgs {
+        imu: imu_file,
+        format: encoding,
+        output,
+        file,
+        min_gap_s,
+    } = args;
+
+    let (reader, _) = dataset::open(&imu_file)?;
+    let samples = imu::parse_imu_csv(reader)?;
+    let (trajectory, _) = dataset::load_trajectory(&[file])?;
+    let params = imu::GapParams {
+        min_gap_s,
+        step_s: 1,
+    };
+    let (filled, inserted) = imu::fill_gaps(&trajectory, &samples, params);
+    let simplified = simplify_trajectory(filled, EPSILON);
+    write_output(&output, encoding.encode(&simplified)?)?;
+
     println!(
-        "Ratio points: {:>29.2} %",
-        (simplified_points as f64 / total_points as f64) * 100.0
+        "Inserted {} dead-reckoned points into {} points, simplified to {} points -> {output}",
+        inserted.to_formatted_string(&LOCALE),
+        trajectory.timestamps.len().to_formatted_string(&LOCALE),
+        simplified.timestamps.len().to_formatted_string(&LOCALE),
     );
 
+    Ok(())
+}
+
+/// Builds the matcher given on the command line: a GeoJSON road network, or with the
+/// `http-matcher` feature an `osrm:<url>` or `valhalla:<url>` service configured by the
+/// `--cache` and `--concurrency` flags.
+fn matcher(args: &cli::SnapSimplifyArgs) -> Result<Box<dyn Matcher>, AppError> {
+    #[cfg(feature = "http-matcher")]
+    {
+        let mut options = enrich::EnrichOptions {
+            cache_dir: args.cache.clone(),
+            ..Default::default()
+        };
+        if let Some(concurrency) = args.concurrency {
+            options.concurrency = concurrency;
+        }
+        if let Some(matcher) = http_matcher::HttpMatcher::from_spec(&args.roads, options)? {
+            return Ok(Box::new(matcher));
+        }
+        // Flags only configure matching services
+        if args.cache.is_some() || args.concurrency.is_some() {
+            return Err(AppError::Usage(
+                "--cache and --concurrency only apply to osrm: and valhalla: matchers",
+            ));
+        }
+    }
+
+    let start = Instant::now();
+    let network = RoadNetwork::from_geojson(&fs::read_to_string(&args.roads)?)?;
     println!(
-        "Ratio bytes delta vs non-delta: {:>11.2} %",
-        (serialized_delta.len() as f64 / serialized.len() as f64) * 100.0
+        "Read {} roads in {:?}",
+        network.len().to_formatted_string(&LOCALE),
+        start.elapsed()
     );
+    Ok(Box::new(network))
+}
+
+/// Map-matches the road-bound parts of every file before simplifying it, and compares the
+/// result with simplifying the raw points.
+///
+/// Points labeled with a mode other than car, taxi or bus are left unmatched; if the dataset
+/// has no labels, every point is matched.
+fn run_snap_simplify(args: cli::SnapSimplifyArgs) -> Result<(), AppError> {
+    let matcher = matcher(&args)?;
+    let radius_m = args.radius_m;
+
+    let labels = dataset::load_labels(&dir_path())?;
+    let files = dataset::find_plt_files(&dir_path())?;
+    let (mut points, mut raw_points, mut matched_points) = (0, 0, 0);
+    let (mut raw_bytes, mut matched_bytes) = (0, 0);
+    for file in &files {
+        let (trajectory, _) = dataset::load_trajectory(std::slice::from_ref(file))?;
+        let snappable: Vec<bool> = trajectory
+            .timestamps
+            .iter()
+            .map(|&ts| {
+                labels.is_empty()
+                    || labels::mode_at(&labels, ts)
+                        .is_some_and(|mode| matching::ROAD_MODES.contains(&mode))
+            })
+            .collect();
+        points += trajectory.timestamps.len();
+
+        let matched = matching::snap_then_simplify(
+            matcher.as_ref(),
+            &trajectory,
+            &snappable,
+            radius_m,
+            EPSILON,
+        )?;
+        let raw = simplify_trajectory(trajectory, EPSILON);
+        raw_points += raw.timestamps.len();
+        matched_points += matched.timestamps.len();
+        raw_bytes += raw.to_delta_proto()?.encode_to_vec().len();
+        matched_bytes += matched.to_delta_proto()?.encode_to_vec().len();
+    }
 
+    println!("Total points: {:>21}", points.to_formatted_string(&LOCALE));
     println!(
-        "Ratio bytes delta vs original: {:>12.2} %",
-        (serialized_delta.len() as f64 / total_size as f64) * 100.0
+        "Simplified raw: {:>19} points {:>14} bytes",
+        raw_points.to_formatted_string(&LOCALE),
+        raw_bytes.to_formatted_string(&LOCALE)
+    );
+    println!(
+        "Snapped then simplified: {:>10} points {:>14} bytes",
+        matched_points.to_formatted_string(&LOCALE),
+        matched_bytes.to_formatted_string(&LOCALE)
     );
 
     Ok(())
 }
+
+/// Decrypts an output file with the key set in the environment.
+fn run_decrypt(args: cli::DecryptArgs) -> Result<(), AppError> {
+    let cli::DecryptArgs { input, output } = args;
+    let key = crypto::key_from_env()?.ok_or(AppError::Usage(
+        "decrypt needs a key in TRAJECTORY_KEY or TRAJECTORY_KEY_FILE",
+    ))?;
+    fs::write(output, crypto::decrypt(&key, &fs::read(input)?)?)?;
+    Ok(())
+}
+
+/// Builds the options of the default run from the command line, loading the configuration
+/// if given.
+fn run_options(args: cli::RunArgs) -> Result<run::RunOptions, AppError> {
+    let cli::RunArgs {
+        input,
+        epsilon,
+        output,
+        config,
+        smooth,
+        reanchor_loops,
+        explain,
+        target_ratio,
+        geojson,
+        dry_run,
+        merge,
+        per_file: _,
+        max_gap_s,
+        max_jump_m,
+        datum,
+    } = args;
+    Ok(run::RunOptions {
+        input: input.unwrap_or_else(dir_path),
+        epsilon,
+        output,
+        config: config.map(|path| Config::load(&path)).transpose()?,
+        smooth,
+        loop_anchors: if reanchor_loops {
+            LoopAnchors::Diameter
+        } else {
+            LoopAnchors::Endpoints
+        },
+        explain,
+        target_ratio,
+        geojson,
+        dry_run,
+        merge,
+        max_gap_s,
+        max_jump_m,
+        datum,
+    })
+}
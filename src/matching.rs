@@ -0,0 +1,487 @@
+//! Map matching of trajectories onto a road network, so that road-bound trips can be stored
+//! as the road geometry they followed rather than as noisy GPS fixes.
+//!
+//! The network is read from a GeoJSON `FeatureCollection` of `LineString` or
+//! `MultiLineString` features, such as an OpenStreetMap extract converted with `osmium` or
+//! `ogr2ogr`. Each point is snapped to the closest road within a search radius, preferring
+//! the road of the previous point, and the road vertices passed between two snapped points
+//! are inserted so the result follows the road.
+
+use crate::geo::{self, haversine_m, meters_to_units, METERS_PER_UNIT};
+use crate::simplify;
+use crate::trajectory::{CoordinateSystem, Trajectory};
+use serde_json::Value;
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Side of the square cells of the spatial index, in scaled units (about 111 m)
+const CELL_SIZE: i64 = 1000;
+
+/// Distance in meters a road change must save for a point to leave the road of the
+/// previous point, so that noise does not make matches jump between parallel roads
+const SWITCH_PENALTY_M: f64 = 10.0;
+
+/// Default search radius in meters around each point
+pub const SEARCH_RADIUS_M: f64 = 30.0;
+
+/// GeoLife transportation modes whose points are matched to roads
+pub const ROAD_MODES: [&str; 3] = ["car", "taxi", "bus"];
+
+#[derive(Error, Debug)]
+pub enum MatchError {
+    #[error("Invalid road network: {0}")]
+    InvalidNetwork(&'static str),
+    #[error("Invalid road network: {0}")]
+    Json(#[from] serde_json::Error),
+    #[cfg(feature = "http-matcher")]
+    #[error("Invalid matching service: {0}")]
+    InvalidService(String),
+    #[cfg(feature = "http-matcher")]
+    #[error("{0}")]
+    Enrich(#[from] crate::enrich::EnrichError),
+    #[cfg(feature = "http-matcher")]
+    #[error("Matching service answered with status {0}")]
+    Status(u16),
+    #[cfg(feature = "http-matcher")]
+    #[error("Invalid response from the matching service: {0}")]
+    InvalidResponse(&'static str),
+}
+
+/// A polyline of the road network, in scaled coordinates.
+#[derive(Debug, Clone, PartialEq)]
+struct Road {
+    latitudes: Vec<i64>,
+    longitudes: Vec<i64>,
+}
+
+/// The position of a point snapped onto a road segment.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Snap {
+    road: usize,
+    /// Index of the first vertex of the segment
+    segment: usize,
+    latitude: i64,
+    longitude: i64,
+    distance_m: f64,
+}
+
+/// Road polylines with a grid index of their segments.
+pub struct RoadNetwork {
+    roads: Vec<Road>,
+    cells: HashMap<(i64, i64), Vec<(usize, usize)>>,
+}
+
+/// Parses a `[longitude, latitude]` GeoJSON position into scaled `(latitude, longitude)`.
+fn parse_position(value: &Value) -> Result<(i64, i64), MatchError> {
+    let coordinate = |index: usize| {
+        value
+            .get(index)
+            .and_then(Value::as_f64)
+            .map(|degrees| (degrees * geo::UNITS_PER_DEGREE).round() as i64)
+            .ok_or(MatchError::InvalidNetwork(
+                "position is not [longitude, latitude]",
+            ))
+    };
+    Ok((coordinate(1)?, coordinate(0)?))
+}
+
+fn parse_line(value: &Value) -> Result<Road, MatchError> {
+    let positions = value
+        .as_array()
+        .ok_or(MatchError::InvalidNetwork("coordinates are not an array"))?;
+    let (latitudes, longitudes) = positions
+        .iter()
+        .map(parse_position)
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .unzip();
+    Ok(Road {
+        latitudes,
+        longitudes,
+    })
+}
+
+impl RoadNetwork {
+    fn new(roads: Vec<Road>) -> Self {
+        let mut cells: HashMap<_, Vec<_>> = HashMap::new();
+        for (road_index, road) in roads.iter().enumerate() {
+            for segment in 0..road.latitudes.len().saturating_sub(1) {
+                let (lat1, lat2) = (road.latitudes[segment], road.latitudes[segment + 1]);
+                let (lon1, lon2) = (road.longitudes[segment], road.longitudes[segment + 1]);
+                for cell_lat in
+                    lat1.min(lat2).div_euclid(CELL_SIZE)..=lat1.max(lat2).div_euclid(CELL_SIZE)
+                {
+                    for cell_lon in
+                        lon1.min(lon2).div_euclid(CELL_SIZE)..=lon1.max(lon2).div_euclid(CELL_SIZE)
+                    {
+                        cells
+                            .entry((cell_lat, cell_lon))
+                            .or_default()
+                            .push((road_index, segment));
+                    }
+                }
+            }
+        }
+        RoadNetwork { roads, cells }
+    }
+
+    /// Parses a GeoJSON `FeatureCollection`, keeping its `LineString` and `MultiLineString`
+    /// geometries and ignoring the others.
+    pub fn from_geojson(geojson: &str) -> Result<Self, MatchError> {
+        let value: Value = serde_json::from_str(geojson)?;
+        let features = value
+            .get("features")
+            .and_then(Value::as_array)
+            .ok_or(MatchError::InvalidNetwork("not a FeatureCollection"))?;
+
+        let mut roads = Vec::new();
+        for feature in features {
+            let Some(geometry) = feature.get("geometry") else {
+                continue;
+            };
+            let coordinates = geometry
+                .get("coordinates")
+                .ok_or(MatchError::InvalidNetwork("geometry has no coordinates"))?;
+            match geometry.get("type").and_then(Value::as_str) {
+                Some("LineString") => roads.push(parse_line(coordinates)?),
+                Some("MultiLineString") => {
+                    let lines = coordinates
+                        .as_array()
+                        .ok_or(MatchError::InvalidNetwork("coordinates are not an array"))?;
+                    for line in lines {
+                        roads.push(parse_line(line)?);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self::new(roads))
+    }
+
+    /// Number of roads in the network.
+    pub fn len(&self) -> usize {
+        self.roads.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.roads.is_empty()
+    }
+
+    /// Projects the point onto one segment, returning the projected point and its distance.
+    fn project(&self, road: usize, segment: usize, (lat, lon): (i64, i64)) -> Snap {
+        let road_ref = &self.roads[road];
+        let (lat1, lon1) = (road_ref.latitudes[segment], road_ref.longitudes[segment]);
+        let (lat2, lon2) = (
+            road_ref.latitudes[segment + 1],
+            road_ref.longitudes[segment + 1],
+        );
+
+        // Equirectangular projection centered on the point, as in `geo::segment_distance_m`
+        let cos_lat = geo::to_degrees(lat).to_radians().cos();
+        let (x1, y1) = ((lon1 - lon) as f64 * cos_lat, (lat1 - lat) as f64);
+        let (dx, dy) = ((lon2 - lon1) as f64 * cos_lat, (lat2 - lat1) as f64);
+        let length_squared = dx * dx + dy * dy;
+        let t = if length_squared == 0.0 {
+            0.0
+        } else {
+            (-(x1 * dx + y1 * dy) / length_squared).clamp(0.0, 1.0)
+        };
+        let (x, y) = (x1 + t * dx, y1 + t * dy);
+
+        Snap {
+            road,
+            segment,
+            latitude: lat1 + ((lat2 - lat1) as f64 * t).round() as i64,
+            longitude: lon1 + ((lon2 - lon1) as f64 * t).round() as i64,
+            distance_m: (x * x + y * y).sqrt() * METERS_PER_UNIT,
+        }
+    }
+
+    /// Snaps the point to the closest segment within `radius_m` meters.
+    ///
+    /// Segments of `previous_road` are considered `SWITCH_PENALTY_M` meters closer than
+    /// they are.
+    fn snap(&self, point: (i64, i64), radius_m: f64, previous_road: Option<usize>) -> Option<Snap> {
+        let (lat, lon) = point;
+        let radius_lat = meters_to_units(radius_m);
+        let cos_lat = geo::to_degrees(lat).to_radians().cos().max(0.01);
+        let radius_lon = (radius_lat as f64 / cos_lat).ceil() as i64;
+
+        let score = |snap: &Snap| {
+            if Some(snap.road) == previous_road {
+                snap.distance_m - SWITCH_PENALTY_M
+            } else {
+                snap.distance_m
+            }
+        };
+
+        let mut best: Option<Snap> = None;
+        for cell_lat in
+            (lat - radius_lat).div_euclid(CELL_SIZE)..=(lat + radius_lat).div_euclid(CELL_SIZE)
+        {
+            for cell_lon in
+                (lon - radius_lon).div_euclid(CELL_SIZE)..=(lon + radius_lon).div_euclid(CELL_SIZE)
+            {
+                let Some(segments) = self.cells.get(&(cell_lat, cell_lon)) else {
+                    continue;
+                };
+                for &(road, segment) in segments {
+                    let snap = self.project(road, segment, point);
+                    if snap.distance_m <= radius_m
+                        && best.is_none_or(|best| score(&snap) < score(&best))
+                    {
+                        best = Some(snap);
+                    }
+                }
+            }
+        }
+        best
+    }
+
+    /// Vertices of the road passed when moving from `from` to `to` along the same road.
+    fn vertices_between(&self, from: &Snap, to: &Snap) -> Vec<(i64, i64)> {
+        let road = &self.roads[from.road];
+        let vertex = |index: usize| (road.latitudes[index], road.longitudes[index]);
+        if from.segment < to.segment {
+            (from.segment + 1..=to.segment).map(vertex).collect()
+        } else {
+            (to.segment + 1..=from.segment).rev().map(vertex).collect()
+        }
+    }
+}
+
+/// Timestamps of the vertices of a path driven from `start_ts` to `end_ts`, interpolated by
+/// the distance traveled along it.
+pub(crate) fn timestamps_along(path: &[(i64, i64)], start_ts: u64, end_ts: u64) -> Vec<u64> {
+    let lengths: Vec<f64> = path
+        .windows(2)
+        .map(|pair| haversine_m(pair[0].0, pair[0].1, pair[1].0, pair[1].1))
+        .collect();
+    let total: f64 = lengths.iter().sum();
+
+    let mut timestamps = Vec::with_capacity(path.len());
+    timestamps.push(start_ts);
+    let mut traveled = 0.0;
+    for length in lengths {
+        traveled += length;
+        let t = if total > 0.0 { traveled / total } else { 0.0 };
+        timestamps.push(start_ts + ((end_ts - start_ts) as f64 * t).round() as u64);
+    }
+    if let Some(last) = timestamps.last_mut().filter(|_| path.len() > 1) {
+        *last = end_ts;
+    }
+    timestamps
+}
+
+/// A way of snapping trajectories to roads.
+pub trait Matcher {
+    /// Snaps the points flagged in `snappable` to roads within `radius_m` meters, replacing
+    /// them with the road geometry driven through.
+    ///
+    /// Points that are not snappable or have no road in range are kept as they are.
+    fn match_trajectory(
+        &self,
+        trajectory: &Trajectory,
+        snappable: &[bool],
+        radius_m: f64,
+    ) -> Result<Trajectory, MatchError>;
+}
+
+impl Matcher for RoadNetwork {
+    /// Snaps each point to the closest road, and inserts the road vertices passed between
+    /// consecutive points snapped to the same road with timestamps interpolated along it.
+    fn match_trajectory(
+        &self,
+        trajectory: &Trajectory,
+        snappable: &[bool],
+        radius_m: f64,
+    ) -> Result<Trajectory, MatchError> {
+        let len = trajectory.timestamps.len();
+        assert_eq!(snappable.len(), len);
+        let mut matched = Trajectory {
+            latitudes: Vec::with_capacity(len),
+            longitudes: Vec::with_capacity(len),
+            timestamps: Vec::with_capacity(len),
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+
+        let mut previous: Option<(Snap, u64)> = None;
+        for (i, &snappable) in snappable.iter().enumerate() {
+            let point = (trajectory.latitudes[i], trajectory.longitudes[i]);
+            let ts = trajectory.timestamps[i];
+            let snap = snappable
+                .then(|| self.snap(point, radius_m, previous.map(|(snap, _)| snap.road)))
+                .flatten();
+
+            if let (Some((from, from_ts)), Some(to)) = (previous, snap) {
+                if from.road == to.road && from.segment != to.segment {
+                    let mut path = vec![(from.latitude, from.longitude)];
+                    path.extend(self.vertices_between(&from, &to));
+                    path.push((to.latitude, to.longitude));
+
+                    let timestamps = timestamps_along(&path, from_ts, ts);
+                    for (&(latitude, longitude), &ts) in
+                        path.iter().zip(&timestamps).take(path.len() - 1).skip(1)
+                    {
+                        matched.latitudes.push(latitude);
+                        matched.longitudes.push(longitude);
+                        matched.timestamps.push(ts);
+                    }
+                }
+            }
+
+            let (latitude, longitude) = snap.map_or(point, |snap| (snap.latitude, snap.longitude));
+            matched.latitudes.push(latitude);
+            matched.longitudes.push(longitude);
+            matched.timestamps.push(ts);
+            previous = snap.map(|snap| (snap, ts));
+        }
+
+        Ok(matched)
+    }
+}
+
+/// Map-matches the trajectory, then simplifies the matched geometry with tolerance `epsilon`.
+///
+/// Once points lie exactly on the road, Douglas-Peucker only keeps the road's own turns
+/// rather than the GPS noise around them.
+pub fn snap_then_simplify(
+    matcher: &dyn Matcher,
+    trajectory: &Trajectory,
+    snappable: &[bool],
+    radius_m: f64,
+    epsilon: i64,
+) -> Result<Trajectory, MatchError> {
+    let matched = matcher.match_trajectory(trajectory, snappable, radius_m)?;
+    let keep = simplify::simplify(&matched.latitudes, &matched.longitudes, epsilon);
+    Ok(matched.filter(&keep))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An L-shaped road going east then north, and a parallel road 200 m south.
+    const NETWORK: &str = r#"{
+        "type": "FeatureCollection",
+        "features": [
+            {"type": "Feature", "properties": {}, "geometry": {"type": "LineString",
+                "coordinates": [[116.0, 40.0], [116.01, 40.0], [116.01, 40.01]]}},
+            {"type": "Feature", "properties": {}, "geometry": {"type": "MultiLineString",
+                "coordinates": [[[116.0, 39.998], [116.01, 39.998]]]}},
+            {"type": "Feature", "properties": {}, "geometry": {"type": "Point",
+                "coordinates": [116.0, 40.0]}}
+        ]
+    }"#;
+
+    fn trajectory(points: &[(i64, i64, u64)]) -> Trajectory {
+        Trajectory {
+            latitudes: points.iter().map(|p| p.0).collect(),
+            longitudes: points.iter().map(|p| p.1).collect(),
+            timestamps: points.iter().map(|p| p.2).collect(),
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        }
+    }
+
+    #[test]
+    fn test_from_geojson() {
+        let network = RoadNetwork::from_geojson(NETWORK).unwrap();
+        assert_eq!(network.len(), 2);
+        assert_eq!(
+            network.roads[0].longitudes,
+            vec![116_000_000, 116_010_000, 116_010_000]
+        );
+        assert_eq!(network.roads[1].latitudes, vec![39_998_000, 39_998_000]);
+
+        assert!(matches!(
+            RoadNetwork::from_geojson("{}"),
+            Err(MatchError::InvalidNetwork(_))
+        ));
+        assert!(matches!(
+            RoadNetwork::from_geojson("{"),
+            Err(MatchError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn test_match_follows_the_road() {
+        let network = RoadNetwork::from_geojson(NETWORK).unwrap();
+        // Noisy fixes before and after the corner, about 10 m off the road
+        let input = trajectory(&[(40_000_090, 116_005_000, 0), (40_005_000, 116_010_100, 100)]);
+        let matched = network
+            .match_trajectory(&input, &[true, true], SEARCH_RADIUS_M)
+            .unwrap();
+
+        assert_eq!(matched.latitudes, vec![40_000_000, 40_000_000, 40_005_000]);
+        assert_eq!(
+            matched.longitudes,
+            vec![116_005_000, 116_010_000, 116_010_000]
+        );
+        // The corner is 426 m into the 982 m driven along the road between both fixes
+        assert_eq!(matched.timestamps, vec![0, 43, 100]);
+    }
+
+    #[test]
+    fn test_match_keeps_unmatched_points() {
+        let network = RoadNetwork::from_geojson(NETWORK).unwrap();
+        let input = trajectory(&[
+            (40_000_090, 116_005_000, 0),
+            (40_005_000, 116_005_000, 100),
+            (40_000_090, 116_006_000, 200),
+        ]);
+        let matched = network
+            .match_trajectory(&input, &[true, true, false], SEARCH_RADIUS_M)
+            .unwrap();
+
+        assert_eq!(matched.latitudes, vec![40_000_000, 40_005_000, 40_000_090]);
+        assert_eq!(
+            matched.longitudes,
+            vec![116_005_000, 116_005_000, 116_006_000]
+        );
+    }
+
+    #[test]
+    fn test_match_prefers_previous_road() {
+        let network = RoadNetwork::from_geojson(NETWORK).unwrap();
+        // The second fix is slightly closer to the southern road
+        let input = trajectory(&[(39_998_100, 116_002_000, 0), (39_999_010, 116_004_000, 10)]);
+        let matched = network.match_trajectory(&input, &[true, true], 150.0).unwrap();
+        assert_eq!(matched.latitudes, vec![39_998_000, 39_998_000]);
+
+        // Without the previous point, it goes to the closest road
+        let matched = network.match_trajectory(&input, &[false, true], 150.0).unwrap();
+        assert_eq!(matched.latitudes, vec![39_998_100, 40_000_000]);
+    }
+
+    #[test]
+    fn test_timestamps_along() {
+        let path = [(0, 0), (0, 300), (0, 400), (0, 400)];
+        assert_eq!(timestamps_along(&path, 10, 20), vec![10, 17, 20, 20]);
+        assert_eq!(timestamps_along(&[(0, 0), (0, 0)], 10, 20), vec![10, 20]);
+        assert_eq!(timestamps_along(&[(0, 0)], 10, 10), vec![10]);
+    }
+
+    #[test]
+    fn test_snap_then_simplify() {
+        let network = RoadNetwork::from_geojson(NETWORK).unwrap();
+        // Points zig-zagging around the road
+        let points: Vec<_> = (0..20)
+            .map(|i| {
+                let offset = if i % 2 == 0 { 100 } else { -100 };
+                (40_000_000 + offset, 116_000_000 + i * 500, i as u64)
+            })
+            .collect();
+        let input = trajectory(&points);
+        let snappable = vec![true; points.len()];
+
+        let raw = simplify::simplify(&input.latitudes, &input.longitudes, 50);
+        let simplified = snap_then_simplify(&network, &input, &snappable, SEARCH_RADIUS_M, 50).unwrap();
+
+        assert_eq!(raw.iter().filter(|&&keep| keep).count(), 20);
+        assert_eq!(simplified.latitudes, vec![40_000_000, 40_000_000]);
+        assert_eq!(simplified.longitudes, vec![116_000_000, 116_009_500]);
+    }
+}
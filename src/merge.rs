@@ -0,0 +1,340 @@
+//! Merging of several trajectories of the same trip, e.g. recorded by several devices.
+//!
+//! Devices rarely agree on the time, so their recordings can first be aligned by
+//! estimating the clock offset between them, then fused into a single trajectory
+//! weighted by the quality of each device.
+
+use crate::resample::position_at;
+use crate::trajectory::{CoordinateSystem, Trajectory};
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum MergeError {
+    #[error("Conflicting positions at timestamp {timestamp}")]
+    Conflict { timestamp: u64 },
+    #[error("Unknown conflict policy: {0} (expected one of first, average, error)")]
+    UnknownPolicy(String),
+    #[error("Trajectories are in different coordinate systems")]
+    MixedCoordinateSystems,
+}
+
+/// How to resolve points of different trajectories sharing a timestamp but not a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Keep the position of the trajectory listed first. Trajectories carry no accuracy,
+    /// so callers list the most accurate source first.
+    PreferFirst,
+    /// Average the conflicting positions
+    Average,
+    /// Fail with `MergeError::Conflict`
+    Error,
+}
+
+impl FromStr for ConflictPolicy {
+    type Err = MergeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "first" => Ok(ConflictPolicy::PreferFirst),
+            "average" => Ok(ConflictPolicy::Average),
+            "error" => Ok(ConflictPolicy::Error),
+            _ => Err(MergeError::UnknownPolicy(s.to_string())),
+        }
+    }
+}
+
+/// Returns the coordinate system of the trajectories, `Wgs84` if there are none, or `None`
+/// if they differ.
+fn shared_coordinates(trajectories: &[Trajectory]) -> Option<CoordinateSystem> {
+    let coordinates = trajectories
+        .first()
+        .map_or(CoordinateSystem::Wgs84, |trajectory| trajectory.coordinates);
+    trajectories
+        .iter()
+        .all(|trajectory| trajectory.coordinates == coordinates)
+        .then_some(coordinates)
+}
+
+/// Interleaves the points of the trajectories by timestamp.
+///
+/// Points sharing a timestamp are merged into one: identical positions are kept once,
+/// different ones are resolved with `policy`. Trajectories must share their coordinate
+/// system.
+pub fn merge(trajectories: &[Trajectory], policy: ConflictPolicy) -> Result<Trajectory, MergeError> {
+    let coordinates =
+        shared_coordinates(trajectories).ok_or(MergeError::MixedCoordinateSystems)?;
+    // Sorting is stable, so points sharing a timestamp stay in the order of `trajectories`
+    let mut points: Vec<(u64, i64, i64)> = trajectories
+        .iter()
+        .flat_map(|trajectory| {
+            trajectory
+                .timestamps
+                .iter()
+                .zip(&trajectory.latitudes)
+                .zip(&trajectory.longitudes)
+                .map(|((&ts, &lat), &lon)| (ts, lat, lon))
+        })
+        .collect();
+    points.sort_by_key(|&(ts, _, _)| ts);
+
+    let mut merged = Trajectory {
+        latitudes: Vec::with_capacity(points.len()),
+        longitudes: Vec::with_capacity(points.len()),
+        timestamps: Vec::with_capacity(points.len()),
+        altitudes: Vec::new(),
+        coordinates,
+    };
+
+    for group in points.chunk_by(|a, b| a.0 == b.0) {
+        let (timestamp, latitude, longitude) = group[0];
+        let conflict = group
+            .iter()
+            .any(|&(_, lat, lon)| (lat, lon) != (latitude, longitude));
+
+        let (latitude, longitude) = match policy {
+            ConflictPolicy::PreferFirst => (latitude, longitude),
+            _ if !conflict => (latitude, longitude),
+            ConflictPolicy::Average => {
+                let len = group.len() as i128;
+                let sum = |coordinate: fn(&(u64, i64, i64)) -> i64| {
+                    group.iter().map(|point| coordinate(point) as i128).sum::<i128>()
+                };
+                (
+                    (sum(|point| point.1) / len) as i64,
+                    (sum(|point| point.2) / len) as i64,
+                )
+            }
+            ConflictPolicy::Error => return Err(MergeError::Conflict { timestamp }),
+        };
+
+        merged.latitudes.push(latitude);
+        merged.longitudes.push(longitude);
+        merged.timestamps.push(timestamp);
+    }
+
+    Ok(merged)
+}
+
+/// Minimum number of points two recordings must share for their offset to be estimated
+const MIN_OVERLAP: usize = 10;
+
+/// Estimates the clock offset, in seconds, to add to the timestamps of `other` so that it
+/// matches `reference`, searching offsets up to `max_offset` seconds in both directions.
+///
+/// The offset minimizing the mean squared distance between the points of `reference`
+/// and the positions of `other` at the same time is chosen, which maximizes the
+/// correlation of the two position series.
+///
+/// Returns `None` if no offset makes at least `MIN_OVERLAP` points overlap.
+pub fn estimate_offset(reference: &Trajectory, other: &Trajectory, max_offset: u32) -> Option<i64> {
+    let max_offset = i64::from(max_offset);
+    let mut best: Option<(f64, i64)> = None;
+
+    for offset in -max_offset..=max_offset {
+        let (mut sum, mut count) = (0.0, 0);
+        for ((&ts, &lat), &lon) in reference
+            .timestamps
+            .iter()
+            .zip(&reference.latitudes)
+            .zip(&reference.longitudes)
+        {
+            if let Some((other_lat, other_lon)) = position_at(other, (ts as i64 - offset) as f64) {
+                sum += (lat as f64 - other_lat).powi(2) + (lon as f64 - other_lon).powi(2);
+                count += 1;
+            }
+        }
+
+        // On ties, prefer the offset closest to zero
+        if count >= MIN_OVERLAP {
+            let score = sum / count as f64;
+            let better = best.is_none_or(|(best_score, best_offset)| {
+                score < best_score || (score == best_score && offset.abs() < best_offset.abs())
+            });
+            if better {
+                best = Some((score, offset));
+            }
+        }
+    }
+
+    best.map(|(_, offset)| offset)
+}
+
+/// Adds `offset` seconds to every timestamp, saturating at zero.
+pub fn shift(trajectory: &mut Trajectory, offset: i64) {
+    for ts in &mut trajectory.timestamps {
+        *ts = ts.saturating_add_signed(offset);
+    }
+}
+
+/// Fuses aligned recordings of the same trip into one trajectory.
+///
+/// The fused trajectory has a point at every timestamp of any recording. Its position is
+/// the average of the positions of the recordings covering that time, interpolated
+/// between their points and weighted by `weights`, e.g. higher for a dedicated GPS
+/// logger than for a phone.
+///
+/// # Panics
+///
+/// This function will panic if there is not one weight per trajectory, or if the
+/// trajectories are in different coordinate systems.
+pub fn fuse(trajectories: &[Trajectory], weights: &[f64]) -> Trajectory {
+    assert_eq!(trajectories.len(), weights.len(), "one weight per trajectory");
+    let coordinates = shared_coordinates(trajectories).expect("same coordinate system");
+
+    let mut timestamps: Vec<u64> = trajectories
+        .iter()
+        .flat_map(|trajectory| trajectory.timestamps.iter().copied())
+        .collect();
+    timestamps.sort_unstable();
+    timestamps.dedup();
+
+    let mut fused = Trajectory {
+        latitudes: Vec::with_capacity(timestamps.len()),
+        longitudes: Vec::with_capacity(timestamps.len()),
+        timestamps: Vec::with_capacity(timestamps.len()),
+        altitudes: Vec::new(),
+        coordinates,
+    };
+    for ts in timestamps {
+        let (mut lat, mut lon, mut total) = (0.0, 0.0, 0.0);
+        for (trajectory, &weight) in trajectories.iter().zip(weights) {
+            if let Some((latitude, longitude)) = position_at(trajectory, ts as f64) {
+                lat += latitude * weight;
+                lon += longitude * weight;
+                total += weight;
+            }
+        }
+        if total > 0.0 {
+            fused.latitudes.push((lat / total).round() as i64);
+            fused.longitudes.push((lon / total).round() as i64);
+            fused.timestamps.push(ts);
+        }
+    }
+
+    fused
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trajectory(points: &[(u64, i64, i64)]) -> Trajectory {
+        Trajectory {
+            latitudes: points.iter().map(|p| p.1).collect(),
+            longitudes: points.iter().map(|p| p.2).collect(),
+            timestamps: points.iter().map(|p| p.0).collect(),
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        }
+    }
+
+    #[test]
+    fn test_merge_interleaves() {
+        let a = trajectory(&[(0, 0, 0), (2, 20, 20), (4, 40, 40)]);
+        let b = trajectory(&[(1, 10, 10), (2, 20, 20), (3, 30, 30)]);
+
+        let merged = merge(&[a, b], ConflictPolicy::Error).unwrap();
+        assert_eq!(
+            merged,
+            trajectory(&[(0, 0, 0), (1, 10, 10), (2, 20, 20), (3, 30, 30), (4, 40, 40)])
+        );
+    }
+
+    #[test]
+    fn test_merge_conflicts() {
+        let a = trajectory(&[(0, 0, 0), (1, 10, 10)]);
+        let b = trajectory(&[(1, 20, 13)]);
+        let c = trajectory(&[(1, 30, 10)]);
+        let trajectories = [a, b, c];
+
+        let merged = merge(&trajectories, ConflictPolicy::PreferFirst).unwrap();
+        assert_eq!(merged, trajectory(&[(0, 0, 0), (1, 10, 10)]));
+
+        let merged = merge(&trajectories, ConflictPolicy::Average).unwrap();
+        assert_eq!(merged, trajectory(&[(0, 0, 0), (1, 20, 11)]));
+
+        assert_eq!(
+            merge(&trajectories, ConflictPolicy::Error),
+            Err(MergeError::Conflict { timestamp: 1 })
+        );
+    }
+
+    /// A trip with a turn every 20 seconds, one point per second.
+    fn zigzag(start: u64, len: u64) -> Trajectory {
+        Trajectory {
+            latitudes: (0..len).map(|i| (i * 10) as i64).collect(),
+            longitudes: (0..len)
+                .map(|i| if (i / 20) % 2 == 0 { (i % 20) * 15 } else { 300 - (i % 20) * 15 } as i64)
+                .collect(),
+            timestamps: (start..start + len).collect(),
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        }
+    }
+
+    #[test]
+    fn test_estimate_offset() {
+        let reference = zigzag(1000, 120);
+        // Same trip recorded by a device whose clock is 7 seconds late
+        let mut late = reference.clone();
+        shift(&mut late, 7);
+        let mut disjoint = reference.clone();
+        shift(&mut disjoint, 500);
+
+        assert_eq!(estimate_offset(&reference, &late, 30), Some(-7));
+        assert_eq!(estimate_offset(&late, &reference, 30), Some(7));
+        assert_eq!(estimate_offset(&reference, &reference, 30), Some(0));
+        assert_eq!(estimate_offset(&reference, &disjoint, 30), None);
+    }
+
+    #[test]
+    fn test_shift() {
+        let mut shifted = trajectory(&[(5, 0, 0), (10, 1, 1)]);
+        shift(&mut shifted, -7);
+        assert_eq!(shifted.timestamps, vec![0, 3]);
+    }
+
+    #[test]
+    fn test_fuse() {
+        let a = trajectory(&[(0, 0, 0), (10, 100, 100)]);
+        let b = trajectory(&[(5, 80, 40), (10, 130, 130), (20, 200, 200)]);
+
+        let fused = fuse(&[a, b], &[3.0, 1.0]);
+        assert_eq!(fused.timestamps, vec![0, 5, 10, 20]);
+        // At 5 s, a is interpolated to (50, 50)
+        assert_eq!(fused.latitudes, vec![0, 58, 108, 200]);
+        assert_eq!(fused.longitudes, vec![0, 48, 108, 200]);
+    }
+
+    #[test]
+    fn test_merge_empty() {
+        assert_eq!(merge(&[], ConflictPolicy::Error).unwrap(), trajectory(&[]));
+    }
+
+    #[test]
+    fn test_merge_mixed_coordinate_systems() {
+        let local = Trajectory {
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Local { scale: 3 },
+            ..trajectory(&[(1, 10, 10)])
+        };
+        assert_eq!(
+            merge(&[trajectory(&[(0, 0, 0)]), local.clone()], ConflictPolicy::Error),
+            Err(MergeError::MixedCoordinateSystems)
+        );
+        assert_eq!(
+            merge(std::slice::from_ref(&local), ConflictPolicy::Error).unwrap(),
+            local
+        );
+    }
+
+    #[test]
+    fn test_conflict_policy_from_str() {
+        assert_eq!("average".parse(), Ok(ConflictPolicy::Average));
+        assert_eq!(
+            "best".parse::<ConflictPolicy>(),
+            Err(MergeError::UnknownPolicy("best".to_string()))
+        );
+    }
+}
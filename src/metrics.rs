@@ -0,0 +1,273 @@
+//! Prometheus metrics of the simplification: points processed and kept, simplification
+//! latency, compression ratio and parse errors.
+//!
+//! Metrics are rendered in the Prometheus text exposition format, scraped from
+//! `GET /metrics` in server mode or pushed to a Pushgateway at the end of a command (see
+//! `run::with_metrics`).
+
+use crate::simplify;
+use crate::trajectory::Trajectory;
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// Upper bounds of the simplification latency buckets, in seconds
+const LATENCY_BUCKETS: [f64; 9] = [0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 10.0];
+
+/// Upper bounds of the compression ratio buckets, the share of points kept
+const RATIO_BUCKETS: [f64; 7] = [0.01, 0.02, 0.05, 0.1, 0.2, 0.5, 1.0];
+
+#[derive(Error, Debug)]
+pub enum MetricsError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Pushgateway answered: {0}")]
+    Rejected(String),
+}
+
+/// Monotonic counter.
+#[derive(Debug, Default)]
+struct Counter(AtomicU64);
+
+impl Counter {
+    fn add(&self, value: u64) {
+        self.0.fetch_add(value, Ordering::Relaxed);
+    }
+
+    fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Observations of a histogram, with one count per bucket and a last one for `+Inf`.
+#[derive(Debug, Clone, PartialEq)]
+struct Observations {
+    counts: Vec<u64>,
+    sum: f64,
+}
+
+/// Histogram with fixed buckets.
+#[derive(Debug)]
+struct Histogram {
+    bounds: &'static [f64],
+    observations: Mutex<Observations>,
+}
+
+impl Histogram {
+    fn new(bounds: &'static [f64]) -> Self {
+        Histogram {
+            bounds,
+            observations: Mutex::new(Observations {
+                counts: vec![0; bounds.len() + 1],
+                sum: 0.0,
+            }),
+        }
+    }
+
+    fn observe(&self, value: f64) {
+        let bucket = self.bounds.partition_point(|&bound| bound < value);
+        let mut observations = self.observations.lock().unwrap();
+        observations.counts[bucket] += 1;
+        observations.sum += value;
+    }
+
+    /// Appends the cumulative buckets, sum and count of the histogram.
+    fn render(&self, name: &str, out: &mut String) {
+        let observations = self.observations.lock().unwrap().clone();
+        let mut cumulative = 0;
+        for (bound, count) in self.bounds.iter().zip(&observations.counts) {
+            cumulative += count;
+            let _ = writeln!(out, "{name}_bucket{{le=\"{bound}\"}} {cumulative}");
+        }
+        cumulative += observations.counts[self.bounds.len()];
+        let _ = writeln!(out, "{name}_bucket{{le=\"+Inf\"}} {cumulative}");
+        let _ = writeln!(out, "{name}_sum {}", observations.sum);
+        let _ = writeln!(out, "{name}_count {cumulative}");
+    }
+}
+
+/// Metrics of a process, shared between threads.
+#[derive(Debug)]
+pub struct Metrics {
+    points_processed: Counter,
+    points_kept: Counter,
+    parse_errors: Counter,
+    simplification_seconds: Histogram,
+    compression_ratio: Histogram,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Metrics {
+            points_processed: Counter::default(),
+            points_kept: Counter::default(),
+            parse_errors: Counter::default(),
+            simplification_seconds: Histogram::new(&LATENCY_BUCKETS),
+            compression_ratio: Histogram::new(&RATIO_BUCKETS),
+        }
+    }
+}
+
+/// Appends the `HELP` and `TYPE` lines of a metric.
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+impl Metrics {
+    /// Records the simplification of `points` points to `kept` points, which took `elapsed`.
+    pub fn record_simplification(&self, points: usize, kept: usize, elapsed: Duration) {
+        self.points_processed.add(points as u64);
+        self.points_kept.add(kept as u64);
+        self.simplification_seconds.observe(elapsed.as_secs_f64());
+        if points > 0 {
+            self.compression_ratio.observe(kept as f64 / points as f64);
+        }
+    }
+
+    /// Simplifies `trajectory` with `epsilon` and records the simplification.
+    pub fn simplify(&self, trajectory: &Trajectory, epsilon: i64) -> Vec<bool> {
+        let start = Instant::now();
+        let keep = simplify::simplify(&trajectory.latitudes, &trajectory.longitudes, epsilon);
+        let kept = keep.iter().filter(|&&keep| keep).count();
+        self.record_simplification(keep.len(), kept, start.elapsed());
+        keep
+    }
+
+    /// Records an input that could not be parsed.
+    pub fn record_parse_error(&self) {
+        self.parse_errors.add(1);
+    }
+
+    /// Renders the metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for (name, help, counter) in [
+            (
+                "trajectory_points_processed_total",
+                "Points given to the simplification.",
+                &self.points_processed,
+            ),
+            (
+                "trajectory_points_kept_total",
+                "Points kept by the simplification.",
+                &self.points_kept,
+            ),
+            (
+                "trajectory_parse_errors_total",
+                "Inputs that could not be parsed.",
+                &self.parse_errors,
+            ),
+        ] {
+            header(&mut out, name, "counter", help);
+            let _ = writeln!(out, "{name} {}", counter.get());
+        }
+        for (name, help, histogram) in [
+            (
+                "trajectory_simplification_seconds",
+                "Time spent simplifying a trajectory.",
+                &self.simplification_seconds,
+            ),
+            (
+                "trajectory_compression_ratio",
+                "Share of the points of a trajectory kept by the simplification.",
+                &self.compression_ratio,
+            ),
+        ] {
+            header(&mut out, name, "histogram", help);
+            histogram.render(name, &mut out);
+        }
+        out
+    }
+}
+
+/// Pushes the metrics to the Pushgateway at `address`, as `host:port`, replacing the ones
+/// previously pushed for `job`.
+pub fn push(address: &str, job: &str, metrics: &Metrics) -> Result<(), MetricsError> {
+    let body = metrics.render();
+    let mut stream = TcpStream::connect(address)?;
+    write!(
+        stream,
+        "PUT /metrics/job/{job} HTTP/1.1\r\nHost: {address}\r\n\
+         Content-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    let mut status_line = String::new();
+    BufReader::new(stream).read_line(&mut status_line)?;
+    let status_line = status_line.trim_end();
+    match status_line.split_whitespace().nth(1) {
+        Some(status) if status.starts_with('2') => Ok(()),
+        _ => Err(MetricsError::Rejected(status_line.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::net::TcpListener;
+    use std::thread;
+
+    #[test]
+    fn test_render() {
+        let metrics = Metrics::default();
+        metrics.record_simplification(100, 10, Duration::from_millis(3));
+        metrics.record_simplification(50, 50, Duration::from_secs(20));
+        metrics.record_parse_error();
+
+        let text = metrics.render();
+        assert!(text.contains("# TYPE trajectory_points_processed_total counter\n"));
+        assert!(text.contains("\ntrajectory_points_processed_total 150\n"));
+        assert!(text.contains("\ntrajectory_points_kept_total 60\n"));
+        assert!(text.contains("\ntrajectory_parse_errors_total 1\n"));
+        assert!(text.contains("trajectory_simplification_seconds_bucket{le=\"0.001\"} 0\n"));
+        assert!(text.contains("trajectory_simplification_seconds_bucket{le=\"0.005\"} 1\n"));
+        assert!(text.contains("trajectory_simplification_seconds_bucket{le=\"10\"} 1\n"));
+        assert!(text.contains("trajectory_simplification_seconds_bucket{le=\"+Inf\"} 2\n"));
+        assert!(text.contains("trajectory_simplification_seconds_count 2\n"));
+        // A ratio on a bound falls in its bucket
+        assert!(text.contains("trajectory_compression_ratio_bucket{le=\"0.1\"} 1\n"));
+        assert!(text.contains("trajectory_compression_ratio_bucket{le=\"0.5\"} 1\n"));
+        assert!(text.contains("trajectory_compression_ratio_bucket{le=\"1\"} 2\n"));
+        assert!(text.contains("trajectory_compression_ratio_sum 1.1\n"));
+    }
+
+    #[test]
+    fn test_push() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let handle = thread::spawn(move || {
+            let mut requests = Vec::new();
+            for reply in [
+                "HTTP/1.1 200 OK\r\n\r\n",
+                "HTTP/1.1 400 Bad Request\r\n\r\n",
+            ] {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = String::new();
+                while !request.ends_with("_count 0\n") {
+                    let mut buffer = [0; 4096];
+                    let len = stream.read(&mut buffer).unwrap();
+                    request.push_str(std::str::from_utf8(&buffer[..len]).unwrap());
+                }
+                stream.write_all(reply.as_bytes()).unwrap();
+                requests.push(request);
+            }
+            requests
+        });
+
+        let metrics = Metrics::default();
+        push(&address, "batch", &metrics).unwrap();
+        assert!(matches!(
+            push(&address, "batch", &metrics),
+            Err(MetricsError::Rejected(status)) if status == "HTTP/1.1 400 Bad Request"
+        ));
+        let requests = handle.join().unwrap();
+        assert!(requests[0].starts_with("PUT /metrics/job/batch HTTP/1.1\r\n"));
+        assert!(requests[0].ends_with(&metrics.render()));
+    }
+}
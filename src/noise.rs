@@ -0,0 +1,244 @@
+//! Estimation of the positional noise of a trajectory, as the standard deviation in meters
+//! of the error of its fixes along each axis.
+//!
+//! Two estimators are combined:
+//! - while stationary, every fix is the true position plus noise, so the spread of the
+//!   fixes of stationary runs around their mean measures the noise directly;
+//! - while moving, the second differences `x[i+1] - 2 x[i] + x[i-1]` of positions sampled
+//!   at a regular interval cancel any constant velocity, and have a variance of 6 σ² for
+//!   white noise of deviation σ. This is the Allan variance of the deltas between fixes,
+//!   `3 σ²`, doubled. Turns and accelerations also contribute, so the median is used.
+
+use crate::geo::point_speeds;
+use crate::trajectory::Trajectory;
+use serde_json::{json, Value};
+
+/// Median of the absolute value of a standard normal variable
+const NORMAL_MEDIAN_ABSOLUTE: f64 = 0.674_489_75;
+
+/// Radius containing 68% of the fixes for a unit deviation along each axis,
+/// `sqrt(-2 ln(0.32))`, the convention of the accuracy reported by Android and iOS
+const ACCURACY_PER_SIGMA: f64 = 1.509_6;
+
+/// Thresholds of the noise estimation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NoiseParams {
+    /// Largest speed in m/s of a stationary fix
+    pub max_stationary_speed_mps: f64,
+    /// Fewest fixes of a stationary run taken into account
+    pub min_stationary_points: usize,
+    /// Longest interval between fixes in seconds for second differences
+    pub max_interval_s: u64,
+}
+
+impl Default for NoiseParams {
+    fn default() -> Self {
+        NoiseParams {
+            max_stationary_speed_mps: 0.5,
+            min_stationary_points: 5,
+            max_interval_s: 5,
+        }
+    }
+}
+
+/// Noise of one trajectory, by each estimator.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct NoiseEstimate {
+    /// Pooled deviation in meters of the fixes of stationary runs around their mean
+    pub stationary_m: Option<f64>,
+    /// Fixes in stationary runs
+    pub stationary_points: usize,
+    /// Deviation in meters from the second differences of regularly sampled fixes
+    pub second_difference_m: Option<f64>,
+    /// Second differences measured
+    pub second_differences: usize,
+}
+
+impl NoiseEstimate {
+    /// Deviation in meters along each axis: the stationary estimate if any, which measures
+    /// the noise alone, or the second difference one.
+    pub fn sigma_m(&self) -> Option<f64> {
+        self.stationary_m.or(self.second_difference_m)
+    }
+
+    /// Radius in meters containing 68% of the fixes, comparable to a reported accuracy.
+    pub fn accuracy_m(&self) -> Option<f64> {
+        self.sigma_m().map(|sigma_m| sigma_m * ACCURACY_PER_SIGMA)
+    }
+
+    /// Describes the estimate, to store next to the trajectory.
+    pub fn metadata(&self) -> Value {
+        json!({
+            "sigma_m": self.sigma_m(),
+            "accuracy_m": self.accuracy_m(),
+            "stationary_m": self.stationary_m,
+            "stationary_points": self.stationary_points,
+            "second_difference_m": self.second_difference_m,
+            "second_differences": self.second_differences,
+        })
+    }
+}
+
+/// Pooled deviation along each axis of the fixes of the stationary runs, with the number of
+/// fixes in those runs.
+fn stationary_noise(trajectory: &Trajectory, params: NoiseParams) -> (Option<f64>, usize) {
+    let speeds = point_speeds(trajectory, 1);
+    let indices: Vec<usize> = (0..speeds.len()).collect();
+    let point = |i: usize| (trajectory.latitudes[i], trajectory.longitudes[i]);
+
+    let (mut squares, mut points, mut runs) = (0.0, 0, 0);
+    for run in indices.chunk_by(|&a, &b| {
+        (speeds[a] <= params.max_stationary_speed_mps)
+            == (speeds[b] <= params.max_stationary_speed_mps)
+    }) {
+        if speeds[run[0]] > params.max_stationary_speed_mps
+            || run.len() < params.min_stationary_points
+        {
+            continue;
+        }
+        let offsets: Vec<(f64, f64)> = run
+            .iter()
+            .map(|&i| trajectory.coordinates.offset_m(point(run[0]), point(i)))
+            .collect();
+        let n = offsets.len() as f64;
+        let mean = offsets
+            .iter()
+            .fold((0.0, 0.0), |(x, y), &(dx, dy)| (x + dx / n, y + dy / n));
+        squares += offsets
+            .iter()
+            .map(|&(x, y)| (x - mean.0).powi(2) + (y - mean.1).powi(2))
+            .sum::<f64>();
+        points += run.len();
+        runs += 1;
+    }
+
+    // Each run loses one degree of freedom per axis to its mean
+    let sigma_m = (points > runs).then(|| (squares / (2 * (points - runs)) as f64).sqrt());
+    (sigma_m, points)
+}
+
+/// Deviation along each axis from the median absolute second difference, with the number
+/// of second differences measured.
+fn second_difference_noise(trajectory: &Trajectory, params: NoiseParams) -> (Option<f64>, usize) {
+    let timestamps = &trajectory.timestamps;
+    let point = |i: usize| (trajectory.latitudes[i], trajectory.longitudes[i]);
+
+    let mut differences = Vec::new();
+    for i in 1..timestamps.len().saturating_sub(1) {
+        let interval = timestamps[i] - timestamps[i - 1];
+        if interval == 0
+            || interval > params.max_interval_s
+            || timestamps[i + 1] - timestamps[i] != interval
+        {
+            continue;
+        }
+        let before = trajectory.coordinates.offset_m(point(i), point(i - 1));
+        let after = trajectory.coordinates.offset_m(point(i), point(i + 1));
+        differences.push((before.0 + after.0).abs());
+        differences.push((before.1 + after.1).abs());
+    }
+    if differences.is_empty() {
+        return (None, 0);
+    }
+
+    differences.sort_by(f64::total_cmp);
+    let median = differences[differences.len() / 2];
+    let sigma_m = median / NORMAL_MEDIAN_ABSOLUTE / 6f64.sqrt();
+    (Some(sigma_m), differences.len() / 2)
+}
+
+/// Estimates the positional noise of a trajectory.
+///
+/// Timestamps must be sorted in non-decreasing order.
+pub fn estimate(trajectory: &Trajectory, params: NoiseParams) -> NoiseEstimate {
+    let (stationary_m, stationary_points) = stationary_noise(trajectory, params);
+    let (second_difference_m, second_differences) = second_difference_noise(trajectory, params);
+    NoiseEstimate {
+        stationary_m,
+        stationary_points,
+        second_difference_m,
+        second_differences,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trajectory::CoordinateSystem;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::f64::consts::PI;
+
+    /// Fixes every second moving north at `speed_mps`, with gaussian noise of deviation
+    /// `sigma_m` along each axis, in millimeters so that rounding adds no noise.
+    fn noisy(len: usize, speed_mps: f64, sigma_m: f64, seed: u64) -> Trajectory {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut gaussian = || {
+            // Box-Muller; 1 - u is in (0, 1], so the logarithm is finite
+            let (u, v): (f64, f64) = (rng.gen(), rng.gen());
+            (-2.0 * (1.0 - u).ln()).sqrt() * (2.0 * PI * v).cos() * sigma_m
+        };
+        let mut trajectory = Trajectory {
+            latitudes: Vec::new(),
+            longitudes: Vec::new(),
+            timestamps: Vec::new(),
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Local { scale: 3 },
+        };
+        let millimeters = |meters: f64| (meters * 1_000.0).round() as i64;
+        for i in 0..len {
+            trajectory
+                .latitudes
+                .push(millimeters(i as f64 * speed_mps + gaussian()));
+            trajectory.longitudes.push(millimeters(gaussian()));
+            trajectory.timestamps.push(i as u64);
+        }
+        trajectory
+    }
+
+    #[test]
+    fn test_estimate_stationary() {
+        // Stationary fixes 0.2 m apart on average, mostly below the speed threshold
+        let estimate = estimate(&noisy(2_000, 0.0, 0.1, 0), NoiseParams::default());
+        assert!(estimate.stationary_points > 1_900);
+        let sigma_m = estimate.stationary_m.unwrap();
+        assert!((sigma_m - 0.1).abs() < 0.01, "{sigma_m}");
+        let second_difference_m = estimate.second_difference_m.unwrap();
+        assert!(
+            (second_difference_m - 0.1).abs() < 0.01,
+            "{second_difference_m}"
+        );
+        assert_eq!(estimate.sigma_m(), Some(sigma_m));
+    }
+
+    #[test]
+    fn test_estimate_moving() {
+        // Driving at 15 m/s with 3 m of noise: never stationary
+        let estimate = estimate(&noisy(2_000, 15.0, 3.0, 1), NoiseParams::default());
+        assert_eq!(estimate.stationary_m, None);
+        assert_eq!(estimate.stationary_points, 0);
+        assert_eq!(estimate.second_differences, 1_998);
+        let sigma_m = estimate.sigma_m().unwrap();
+        assert!((sigma_m - 3.0).abs() < 0.2, "{sigma_m}");
+        assert!((estimate.accuracy_m().unwrap() - 3.0 * ACCURACY_PER_SIGMA).abs() < 0.3);
+
+        let metadata = estimate.metadata();
+        assert_eq!(metadata["sigma_m"], sigma_m);
+        assert_eq!(metadata["stationary_m"], Value::Null);
+    }
+
+    #[test]
+    fn test_estimate_irregular() {
+        // Without two equal intervals in a row, nor a stationary run, nothing is measured
+        let trajectory = Trajectory {
+            latitudes: vec![0, 1_000, 2_000, 3_000],
+            longitudes: vec![0, 0, 0, 0],
+            timestamps: vec![0, 1, 3, 6],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+        let estimate = estimate(&trajectory, NoiseParams::default());
+        assert_eq!(estimate, NoiseEstimate::default());
+        assert_eq!(estimate.accuracy_m(), None);
+    }
+}
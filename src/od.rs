@@ -0,0 +1,144 @@
+//! Origin-destination matrix: the number of trips between each pair of zones and their
+//! mean duration, a standard mobility-analysis deliverable.
+
+use crate::trajectory::Trajectory;
+use crate::zones::Zone;
+use std::collections::BTreeMap;
+
+/// Name of the origin or destination of trips starting or ending outside every zone
+pub const OUTSIDE: &str = "outside";
+
+/// Trips between two zones.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct OdCell {
+    pub trips: usize,
+    pub total_duration_s: u64,
+}
+
+impl OdCell {
+    pub fn mean_duration_s(&self) -> f64 {
+        self.total_duration_s as f64 / self.trips as f64
+    }
+}
+
+/// Trips by origin and destination zone, by index in the zone list. `None` stands for
+/// outside every zone.
+pub type OdMatrix = BTreeMap<(Option<usize>, Option<usize>), OdCell>;
+
+/// Index of the first zone containing a point.
+fn zone_of(zones: &[Zone], latitude: i64, longitude: i64) -> Option<usize> {
+    zones
+        .iter()
+        .position(|zone| zone.contains_point(latitude, longitude))
+}
+
+/// Classifies the first and last point of every trip into zones and counts the trips
+/// between each pair. Trips without points are ignored.
+pub fn od_matrix<'a>(trips: impl IntoIterator<Item = &'a Trajectory>, zones: &[Zone]) -> OdMatrix {
+    let mut matrix = OdMatrix::new();
+    for trip in trips {
+        let (Some(&first), Some(&last)) = (trip.timestamps.first(), trip.timestamps.last()) else {
+            continue;
+        };
+        let end = trip.latitudes.len() - 1;
+        let origin = zone_of(zones, trip.latitudes[0], trip.longitudes[0]);
+        let destination = zone_of(zones, trip.latitudes[end], trip.longitudes[end]);
+
+        let cell = matrix.entry((origin, destination)).or_default();
+        cell.trips += 1;
+        cell.total_duration_s += last.saturating_sub(first);
+    }
+    matrix
+}
+
+/// Writes the matrix as CSV, one line per pair of zones with trips between them.
+pub fn od_csv(matrix: &OdMatrix, zones: &[Zone]) -> String {
+    let name = |zone: Option<usize>| zone.map_or(OUTSIDE, |i| zones[i].name.as_str());
+    let mut csv = String::from("origin,destination,trips,mean_duration_s\n");
+    for (&(origin, destination), cell) in matrix {
+        csv.push_str(&format!(
+            "{},{},{},{:.0}\n",
+            name(origin),
+            name(destination),
+            cell.trips,
+            cell.mean_duration_s()
+        ));
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trajectory::CoordinateSystem;
+
+    const ZONES: &str = r#"{
+        "type": "FeatureCollection",
+        "features": [
+            {
+                "type": "Feature",
+                "properties": { "name": "home" },
+                "geometry": {
+                    "type": "Polygon",
+                    "coordinates": [[[0, 0], [0.01, 0], [0.01, 0.01], [0, 0.01], [0, 0]]]
+                }
+            },
+            {
+                "type": "Feature",
+                "properties": { "name": "work" },
+                "geometry": {
+                    "type": "Polygon",
+                    "coordinates": [[[1, 1], [1.01, 1], [1.01, 1.01], [1, 1.01], [1, 1]]]
+                }
+            }
+        ]
+    }"#;
+
+    fn trip(from: (i64, i64), to: (i64, i64), duration_s: u64) -> Trajectory {
+        Trajectory {
+            latitudes: vec![from.0, to.0],
+            longitudes: vec![from.1, to.1],
+            timestamps: vec![1_000, 1_000 + duration_s],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        }
+    }
+
+    #[test]
+    fn test_od_matrix() {
+        let zones = Zone::from_geojson(ZONES).unwrap();
+        let (home, work, park) = ((5_000, 5_000), (1_005_000, 1_005_000), (-5_000, 0));
+        let trips = [
+            trip(home, work, 1_800),
+            trip(home, work, 2_400),
+            trip(work, home, 2_000),
+            trip(home, park, 600),
+            Trajectory {
+                latitudes: vec![],
+                longitudes: vec![],
+                timestamps: vec![],
+                altitudes: Vec::new(),
+                coordinates: CoordinateSystem::Wgs84,
+            },
+        ];
+
+        let matrix = od_matrix(&trips, &zones);
+        assert_eq!(matrix.len(), 3);
+        assert_eq!(
+            matrix[&(Some(0), Some(1))],
+            OdCell {
+                trips: 2,
+                total_duration_s: 4_200
+            }
+        );
+        assert_eq!(matrix[&(Some(0), None)].trips, 1);
+
+        assert_eq!(
+            od_csv(&matrix, &zones),
+            "origin,destination,trips,mean_duration_s\n\
+             home,outside,1,600\n\
+             home,work,2,2100\n\
+             work,home,1,2000\n"
+        );
+    }
+}
@@ -0,0 +1,156 @@
+//! Apache Parquet export of simplified trajectories, partitioned by user and date as
+//! `user=<user>/date=<YYYY-MM-DD>/` directories, and the DuckDB views reading them.
+//!
+//! Files are written by the `parquet` crate, uncompressed, one row group per file.
+//! The columns are `trajectory` (utf8 file name), `timestamp` (milliseconds, UTC),
+//! `latitude` and `longitude` (degrees), none of them nullable.
+
+use crate::geo::to_degrees;
+use crate::trajectory::Trajectory;
+use ::parquet::arrow::ArrowWriter;
+use ::parquet::errors::ParquetError;
+use arrow_array::{ArrayRef, Float64Array, RecordBatch, StringArray, TimestampMillisecondArray};
+use arrow_schema::{DataType, Field, Schema, TimeUnit};
+use chrono::DateTime;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Time zone of the `timestamp` column
+const TIME_ZONE: &str = "UTC";
+
+/// Encodes the points of the `(name, trajectory)` pairs as one Parquet file.
+pub fn encode<'a>(
+    trajectories: impl IntoIterator<Item = (&'a str, &'a Trajectory)>,
+) -> Result<Vec<u8>, ParquetError> {
+    let mut names = Vec::new();
+    let mut timestamps = Vec::new();
+    let mut latitudes = Vec::new();
+    let mut longitudes = Vec::new();
+    for (name, trajectory) in trajectories {
+        for ((&ts, &latitude), &longitude) in trajectory
+            .timestamps
+            .iter()
+            .zip(&trajectory.latitudes)
+            .zip(&trajectory.longitudes)
+        {
+            names.push(name);
+            timestamps.push(ts as i64 * 1_000);
+            latitudes.push(to_degrees(latitude));
+            longitudes.push(to_degrees(longitude));
+        }
+    }
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("trajectory", DataType::Utf8, false),
+        Field::new(
+            "timestamp",
+            DataType::Timestamp(TimeUnit::Millisecond, Some(TIME_ZONE.into())),
+            false,
+        ),
+        Field::new("latitude", DataType::Float64, false),
+        Field::new("longitude", DataType::Float64, false),
+    ]));
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from(names)),
+        Arc::new(TimestampMillisecondArray::from(timestamps).with_timezone(TIME_ZONE)),
+        Arc::new(Float64Array::from(latitudes)),
+        Arc::new(Float64Array::from(longitudes)),
+    ];
+    let batch = RecordBatch::try_new(schema.clone(), columns)?;
+    let mut writer = ArrowWriter::try_new(Vec::new(), schema, None)?;
+    writer.write(&batch)?;
+    writer.into_inner()
+}
+
+/// Directory of the partition of a trip of `user` starting at `start`, as seconds since
+/// the epoch; trips are dated by their start in UTC.
+pub fn partition(user: &str, start: u64) -> PathBuf {
+    let date = DateTime::from_timestamp(start as i64, 0).unwrap_or_default();
+    PathBuf::from(format!("user={user}")).join(format!("date={}", date.format("%Y-%m-%d")))
+}
+
+/// DuckDB statements creating a `points` view over the partitioned files under `root`,
+/// and a `trajectories` view with one row per trip.
+pub fn duckdb_views(root: &str) -> String {
+    let root = root.trim_end_matches('/').replace('\'', "''");
+    format!(
+        "CREATE OR REPLACE VIEW points AS
+SELECT * FROM read_parquet('{root}/*/*/*.parquet', hive_partitioning = true,
+    hive_types = {{'user': VARCHAR, 'date': DATE}});
+
+CREATE OR REPLACE VIEW trajectories AS
+SELECT user, date, trajectory, count(*) AS points,
+    min(timestamp) AS start_time, max(timestamp) AS end_time
+FROM points
+GROUP BY user, date, trajectory;
+"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trajectory::CoordinateSystem;
+    use ::parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use ::parquet::file::reader::{FileReader, SerializedFileReader};
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{Float64Type, TimestampMillisecondType};
+    use std::io::Write;
+
+    #[test]
+    fn test_encode() {
+        let trajectory = Trajectory {
+            latitudes: vec![39_900_000, 39_910_000],
+            longitudes: vec![116_400_000, 116_410_000],
+            timestamps: vec![1_224_730_384, 1_224_730_389],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+        let mut file = tempfile::tempfile().unwrap();
+        file.write_all(&encode([("a", &trajectory), ("b", &trajectory)]).unwrap())
+            .unwrap();
+
+        // Read back by the Parquet reader, column by column
+        let reader = SerializedFileReader::new(file.try_clone().unwrap()).unwrap();
+        let metadata = reader.metadata();
+        assert_eq!(metadata.num_row_groups(), 1);
+        assert_eq!(metadata.file_metadata().num_rows(), 4);
+        let columns = metadata.file_metadata().schema_descr().columns().to_vec();
+        let names: Vec<&str> = columns.iter().map(|column| column.name()).collect();
+        assert_eq!(names, ["trajectory", "timestamp", "latitude", "longitude"]);
+
+        // And as Arrow record batches
+        let batches: Vec<RecordBatch> = ParquetRecordBatchReaderBuilder::try_new(file)
+            .unwrap()
+            .build()
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(batches.len(), 1);
+        let batch = &batches[0];
+        let trajectories = batch.column(0).as_string::<i32>();
+        assert_eq!(
+            trajectories.iter().flatten().collect::<Vec<_>>(),
+            ["a", "a", "b", "b"]
+        );
+        let timestamps = batch.column(1).as_primitive::<TimestampMillisecondType>();
+        assert_eq!(timestamps.value(3), 1_224_730_389_000);
+        assert_eq!(
+            batch.schema().field(1).data_type(),
+            &DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into()))
+        );
+        let latitudes = batch.column(2).as_primitive::<Float64Type>();
+        assert_eq!(latitudes.values()[..2], [39.9, 39.91]);
+        let longitudes = batch.column(3).as_primitive::<Float64Type>();
+        assert_eq!(longitudes.value(1), 116.41);
+    }
+
+    #[test]
+    fn test_partition() {
+        assert_eq!(
+            partition("042", 1_224_730_384),
+            PathBuf::from("user=042/date=2008-10-23")
+        );
+        assert!(duckdb_views("out/").contains("read_parquet('out/*/*/*.parquet'"));
+    }
+}
@@ -0,0 +1,464 @@
+//! Multi-threaded pipeline reading, assembling and simplifying one trajectory per file.
+//!
+//! A `Scheduler` runs jobs through a sequence of named stages (parse, clean, simplify,
+//! encode, write, or any other), each on its own number of threads. Stages are connected by
+//! bounded channels, so a slow stage holds back the ones before it instead of letting work
+//! pile up in memory, and I/O, parsing and simplification of different files overlap. The
+//! first error stops every stage.
+//!
+//! A job carries everything the stages produce for one file, so a new stage, such as an
+//! enrichment before encoding, is a function from a job to the updated job inserted at its
+//! place with `Scheduler::insert_after`.
+
+use crate::dataset;
+use crate::datum::{self, Datum};
+use crate::encoding::Encoding;
+use crate::formats::Format;
+use crate::metrics::Metrics;
+use crate::point::Point;
+use crate::trajectory::Trajectory;
+use crate::AppError;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Thread counts and channel capacity of the pipeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PipelineConfig {
+    /// Number of threads reading and parsing files
+    pub readers: usize,
+    /// Number of threads simplifying trajectories
+    pub workers: usize,
+    /// Number of items each channel holds before blocking its senders
+    pub capacity: usize,
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        let threads = thread::available_parallelism().map_or(1, usize::from);
+        PipelineConfig {
+            readers: threads,
+            workers: threads,
+            capacity: 16,
+        }
+    }
+}
+
+/// Everything the stages produce for one file, filled in as the job goes through them.
+#[derive(Debug, Default)]
+pub struct Job {
+    /// Position of the job among the jobs run, by which results are ordered
+    pub index: usize,
+    /// File the job processes
+    pub path: PathBuf,
+    /// Points parsed from the file, until they are assembled into the trajectory
+    pub points: Vec<Point>,
+    pub trajectory: Option<Trajectory>,
+    /// Encoded trajectory
+    pub bytes: Vec<u8>,
+}
+
+type StageFn<'a> = dyn Fn(Job) -> Result<Job, AppError> + Sync + 'a;
+
+/// Named step of a pipeline, applied to every job by `threads` threads.
+pub struct Stage<'a> {
+    pub name: &'static str,
+    pub threads: usize,
+    run: Box<StageFn<'a>>,
+}
+
+impl<'a> Stage<'a> {
+    /// # Panics
+    ///
+    /// This function will panic if `threads` is zero.
+    pub fn new(
+        name: &'static str,
+        threads: usize,
+        run: impl Fn(Job) -> Result<Job, AppError> + Sync + 'a,
+    ) -> Stage<'a> {
+        assert!(threads > 0, "threads > 0");
+        Stage {
+            name,
+            threads,
+            run: Box::new(run),
+        }
+    }
+}
+
+/// Sequence of stages every job goes through, in order.
+pub struct Scheduler<'a> {
+    stages: Vec<Stage<'a>>,
+    /// Number of jobs each channel between two stages holds before blocking
+    capacity: usize,
+}
+
+impl<'a> Scheduler<'a> {
+    /// # Panics
+    ///
+    /// This function will panic if `capacity` is zero.
+    pub fn new(capacity: usize) -> Scheduler<'a> {
+        assert!(capacity > 0, "capacity > 0");
+        Scheduler {
+            stages: Vec::new(),
+            capacity,
+        }
+    }
+
+    /// Appends a stage.
+    pub fn stage(mut self, stage: Stage<'a>) -> Scheduler<'a> {
+        self.stages.push(stage);
+        self
+    }
+
+    /// Inserts a stage right after the stage named `name`, returning `false` without one.
+    pub fn insert_after(&mut self, name: &str, stage: Stage<'a>) -> bool {
+        match self.stages.iter().position(|stage| stage.name == name) {
+            Some(position) => {
+                self.stages.insert(position + 1, stage);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Sets the number of threads of the stage named `name`, returning `false` without one.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `threads` is zero.
+    pub fn set_threads(&mut self, name: &str, threads: usize) -> bool {
+        assert!(threads > 0, "threads > 0");
+        match self.stages.iter_mut().find(|stage| stage.name == name) {
+            Some(stage) => {
+                stage.threads = threads;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Names and thread counts of the stages, in order.
+    pub fn stages(&self) -> Vec<(&'static str, usize)> {
+        self.stages
+            .iter()
+            .map(|stage| (stage.name, stage.threads))
+            .collect()
+    }
+
+    /// Runs every job through the stages.
+    ///
+    /// # Returns
+    ///
+    /// The jobs in the order given, with their `index` set to their position, or the first
+    /// error encountered, which stops every stage.
+    pub fn run(&self, jobs: Vec<Job>) -> Result<Vec<Job>, AppError> {
+        let count = jobs.len();
+        let (sender, receiver) = sync_channel(self.capacity);
+
+        thread::scope(|scope| {
+            scope.spawn(move || {
+                for (index, job) in jobs.into_iter().enumerate() {
+                    if sender.send(Ok(Job { index, ..job })).is_err() {
+                        break;
+                    }
+                }
+            });
+
+            let mut receiver: Receiver<Result<Job, AppError>> = receiver;
+            for stage in &self.stages {
+                let (sender, next_receiver) = sync_channel(self.capacity);
+                // Shared by the threads of the stage and dropped with the last one, so that
+                // the stage before stops if every thread of this one has stopped
+                let shared = Arc::new(Mutex::new(receiver));
+                for _ in 0..stage.threads {
+                    let sender = sender.clone();
+                    let shared = Arc::clone(&shared);
+                    scope.spawn(move || {
+                        while let Some(job) = recv_shared(&shared) {
+                            // Failed jobs go through untouched, to be reported at the end
+                            if sender.send(job.and_then(&stage.run)).is_err() {
+                                break;
+                            }
+                        }
+                    });
+                }
+                receiver = next_receiver;
+            }
+
+            // Returning drops the receiver, which stops every stage on the first error
+            let mut done: Vec<Option<Job>> = (0..count).map(|_| None).collect();
+            for job in receiver {
+                let job = job?;
+                let index = job.index;
+                done[index] = Some(job);
+            }
+            Ok(done.into_iter().flatten().collect())
+        })
+    }
+}
+
+/// Receives the next item from a receiver shared by several threads.
+fn recv_shared<T>(receiver: &Mutex<Receiver<T>>) -> Option<T> {
+    receiver.lock().ok()?.recv().ok()
+}
+
+/// One job per file, in order.
+pub fn discover(files: &[PathBuf]) -> Vec<Job> {
+    files
+        .iter()
+        .map(|path| Job {
+            path: path.clone(),
+            ..Job::default()
+        })
+        .collect()
+}
+
+/// Stage reading and parsing the file of each job, in the format detected from its
+/// extension, GeoLife `.plt` when unknown.
+pub fn parse_stage<'a>(threads: usize) -> Stage<'a> {
+    Stage::new("parse", threads, |mut job| {
+        let (reader, _) = dataset::open(&job.path)?;
+        let format = Format::detect(&job.path).unwrap_or(Format::Plt);
+        job.points = format.parse(reader)?;
+        Ok(job)
+    })
+}
+
+/// Stage sorting the points of each job by time and assembling them into its trajectory.
+pub fn clean_stage<'a>(threads: usize) -> Stage<'a> {
+    Stage::new("clean", threads, |mut job| {
+        let mut points = std::mem::take(&mut job.points);
+        points.sort_by_key(|p| p.datetime);
+        job.trajectory = Some(Trajectory::new(points));
+        Ok(job)
+    })
+}
+
+/// Stage converting the trajectory of each job from the datum `from` to `to`, inserted
+/// after `clean` for data recorded in GCJ-02 or BD-09 (see `datum`).
+pub fn datum_stage<'a>(threads: usize, from: Datum, to: Datum) -> Stage<'a> {
+    Stage::new("datum", threads, move |mut job| {
+        job.trajectory = job
+            .trajectory
+            .map(|trajectory| datum::transform(trajectory, from, to));
+        Ok(job)
+    })
+}
+
+/// Stage simplifying the trajectory of each job with `epsilon`, recorded in `metrics`.
+pub fn simplify_stage(threads: usize, epsilon: i64, metrics: &Metrics) -> Stage<'_> {
+    Stage::new("simplify", threads, move |mut job| {
+        job.trajectory = job.trajectory.map(|trajectory| {
+            let keep_points = metrics.simplify(&trajectory, epsilon);
+            trajectory.filter(&keep_points)
+        });
+        Ok(job)
+    })
+}
+
+/// Stage encoding the trajectory of each job.
+pub fn encode_stage<'a>(threads: usize, encoding: Encoding) -> Stage<'a> {
+    Stage::new("encode", threads, move |mut job| {
+        if let Some(trajectory) = &job.trajectory {
+            job.bytes = encoding.encode(trajectory)?;
+        }
+        Ok(job)
+    })
+}
+
+/// Stage writing the encoded trajectory of each job to `dir` as
+/// `<user>-<file name>.<extension>` (see `dataset::user_of`).
+pub fn write_stage(threads: usize, dir: &Path, encoding: Encoding) -> Stage<'_> {
+    Stage::new("write", threads, move |job| {
+        let stem = job
+            .path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map(|name| name.split('.').next().unwrap_or(name))
+            .unwrap_or("trajectory");
+        let name = match dataset::user_of(&job.path) {
+            Some(user) => format!("{user}-{stem}.{}", encoding.extension()),
+            None => format!("{stem}.{}", encoding.extension()),
+        };
+        fs::write(dir.join(name), &job.bytes)?;
+        Ok(job)
+    })
+}
+
+/// Reads every file as its own trajectory and simplifies it with `epsilon`, recorded in
+/// `metrics`.
+///
+/// # Returns
+///
+/// The simplified trajectories, in the order of `files`, or the first error encountered.
+///
+/// # Panics
+///
+/// This function will panic if a thread count or the channel capacity is zero.
+pub fn simplify_files(
+    files: &[PathBuf],
+    epsilon: i64,
+    config: PipelineConfig,
+    metrics: &Metrics,
+) -> Result<Vec<Trajectory>, AppError> {
+    let scheduler = Scheduler::new(config.capacity)
+        .stage(parse_stage(config.readers))
+        .stage(clean_stage(1))
+        .stage(simplify_stage(config.workers, epsilon, metrics));
+    let jobs = scheduler.run(discover(files))?;
+    Ok(jobs.into_iter().filter_map(|job| job.trajectory).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simplify;
+
+    fn fixture_files() -> Vec<PathBuf> {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/geolife");
+        dataset::find_plt_files(&dir).unwrap()
+    }
+
+    #[test]
+    fn test_simplify_files_matches_sequential() {
+        let files = fixture_files();
+        let expected: Vec<Trajectory> = files
+            .iter()
+            .map(|file| {
+                let (trajectory, _) = dataset::load_trajectory(std::slice::from_ref(file)).unwrap();
+                let keep = simplify::simplify(&trajectory.latitudes, &trajectory.longitudes, 1000);
+                trajectory.filter(&keep)
+            })
+            .collect();
+
+        for (readers, workers, capacity) in [(1, 1, 1), (2, 3, 1), (4, 4, 16)] {
+            let config = PipelineConfig {
+                readers,
+                workers,
+                capacity,
+            };
+            let metrics = Metrics::default();
+            assert_eq!(
+                simplify_files(&files, 1000, config, &metrics).unwrap(),
+                expected
+            );
+            let kept: usize = expected
+                .iter()
+                .map(|trajectory| trajectory.latitudes.len())
+                .sum();
+            assert!(metrics
+                .render()
+                .contains(&format!("trajectory_points_kept_total {kept}\n")));
+        }
+    }
+
+    #[test]
+    fn test_simplify_files_error() {
+        let mut files = fixture_files();
+        files.insert(1, PathBuf::from("does/not/exist.plt"));
+
+        let result = simplify_files(&files, 1000, PipelineConfig::default(), &Metrics::default());
+        assert!(matches!(result, Err(AppError::Io(_))));
+    }
+
+    #[test]
+    fn test_simplify_files_error_with_full_channels() {
+        let mut files = vec![PathBuf::from("does/not/exist.plt")];
+        files.extend(fixture_files().into_iter().cycle().take(50));
+
+        let config = PipelineConfig {
+            readers: 2,
+            workers: 2,
+            capacity: 1,
+        };
+        let result = simplify_files(&files, 1000, config, &Metrics::default());
+        assert!(matches!(result, Err(AppError::Io(_))));
+    }
+
+    #[test]
+    fn test_scheduler() {
+        let tag = |name: &'static str, threads| {
+            Stage::new(name, threads, move |mut job: Job| {
+                job.bytes.extend_from_slice(name.as_bytes());
+                Ok(job)
+            })
+        };
+        let mut scheduler = Scheduler::new(1).stage(tag("a", 3)).stage(tag("c", 2));
+        assert!(scheduler.insert_after("a", tag("b", 1)));
+        assert!(!scheduler.insert_after("missing", tag("d", 1)));
+        assert!(scheduler.set_threads("c", 4));
+        assert_eq!(scheduler.stages(), [("a", 3), ("b", 1), ("c", 4)]);
+
+        let jobs = scheduler
+            .run((0..50).map(|_| Job::default()).collect())
+            .unwrap();
+        assert_eq!(jobs.len(), 50);
+        assert!(jobs.iter().enumerate().all(|(i, job)| job.index == i));
+        assert!(jobs.iter().all(|job| job.bytes == b"abc"));
+
+        // A failing stage stops the run, even with every channel full
+        let failing = Stage::new("fail", 1, |job: Job| {
+            if job.index == 3 {
+                return Err(AppError::Usage("failed"));
+            }
+            Ok(job)
+        });
+        scheduler.insert_after("b", failing);
+        let result = scheduler.run((0..50).map(|_| Job::default()).collect());
+        assert!(matches!(result, Err(AppError::Usage("failed"))));
+    }
+
+    #[test]
+    fn test_write_stages() {
+        let files = fixture_files();
+        let dir = tempfile::tempdir().unwrap();
+        let metrics = Metrics::default();
+        let scheduler = Scheduler::new(2)
+            .stage(parse_stage(2))
+            .stage(clean_stage(1))
+            .stage(simplify_stage(2, 1000, &metrics))
+            .stage(encode_stage(2, Encoding::Binary))
+            .stage(write_stage(1, dir.path(), Encoding::Binary));
+        let jobs = scheduler.run(discover(&files)).unwrap();
+
+        let written = fs::read_dir(dir.path()).unwrap().count();
+        assert_eq!(written, files.len());
+        let job = &jobs[0];
+        let name = format!(
+            "{}-{}.trjb",
+            dataset::user_of(&job.path).unwrap(),
+            job.path.file_stem().unwrap().to_str().unwrap()
+        );
+        assert_eq!(fs::read(dir.path().join(name)).unwrap(), job.bytes);
+        assert!(job.points.is_empty());
+    }
+
+    #[test]
+    fn test_datum_stage() {
+        let files = fixture_files();
+        let mut scheduler = Scheduler::new(2)
+            .stage(parse_stage(1))
+            .stage(clean_stage(1));
+        let wgs84 = scheduler.run(discover(&files)).unwrap();
+        assert!(scheduler.insert_after("clean", datum_stage(2, Datum::Gcj02, Datum::Wgs84)));
+        let shifted = scheduler.run(discover(&files)).unwrap();
+
+        let (wgs84, shifted) = (
+            wgs84[0].trajectory.as_ref().unwrap(),
+            shifted[0].trajectory.as_ref().unwrap(),
+        );
+        assert_eq!(shifted.timestamps, wgs84.timestamps);
+        // GeoLife is in Beijing, shifted south-west back to WGS 84
+        assert!(shifted.latitudes[0] < wgs84.latitudes[0]);
+        assert!(shifted.longitudes[0] < wgs84.longitudes[0]);
+    }
+
+    #[test]
+    fn test_simplify_files_empty() {
+        let result =
+            simplify_files(&[], 1000, PipelineConfig::default(), &Metrics::default()).unwrap();
+        assert!(result.is_empty());
+    }
+}
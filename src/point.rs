@@ -17,15 +17,45 @@ pub enum ParseError {
     LongitudeParse(String),
     #[error("Invalid timestamp")]
     InvalidTimestamp,
+    #[error("Failed to parse coordinate: {0}")]
+    CoordinateParse(String),
+    #[error("Failed to parse altitude: {0}")]
+    AltitudeParse(String),
+    #[error("Invalid JSON: {0}")]
+    Json(String),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Point {
     pub latitude: Decimal,
     pub longitude: Decimal,
     pub datetime: DateTime<Utc>,
+    /// Altitude in meters, when the source has one
+    pub altitude: Option<Decimal>,
 }
 
+/// Altitude marking a GeoLife point without a valid altitude
+const INVALID_ALTITUDE_FEET: i64 = -777;
+
+/// Meters per foot, as 0.3048
+const METERS_PER_FOOT: Decimal = Decimal::from_parts(3048, 0, 0, false, 4);
+
+/// Parses a GeoLife altitude in feet into meters, `None` for the invalid altitude marker.
+fn parse_altitude(value: &str) -> Result<Option<Decimal>, ParseError> {
+    let feet: Decimal = value
+        .trim()
+        .parse()
+        .map_err(|e: rust_decimal::Error| ParseError::AltitudeParse(e.to_string()))?;
+    if feet == Decimal::from(INVALID_ALTITUDE_FEET) {
+        return Ok(None);
+    }
+    Ok(Some(feet * METERS_PER_FOOT))
+}
+
+/// Parses a GeoLife `.plt` file.
+///
+/// The 6 header lines are skipped; files without any point (including empty files)
+/// produce an empty vector. Blank lines are ignored.
 pub fn parse_plt_file(reader: impl BufRead) -> Result<Vec<Point>, ParseError> {
     let lines = reader.lines();
     let mut points = Vec::new();
@@ -34,6 +64,9 @@ pub fn parse_plt_file(reader: impl BufRead) -> Result<Vec<Point>, ParseError> {
 
     for line in line_iter {
         let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
         let parts: Vec<&str> = line.split(',').collect();
 
         if parts.len() != 7 {
@@ -59,6 +92,7 @@ pub fn parse_plt_file(reader: impl BufRead) -> Result<Vec<Point>, ParseError> {
                 .parse()
                 .map_err(|e: rust_decimal::Error| ParseError::LongitudeParse(e.to_string()))?,
             datetime,
+            altitude: parse_altitude(parts[3])?,
         };
 
         points.push(point);
@@ -66,3 +100,117 @@ pub fn parse_plt_file(reader: impl BufRead) -> Result<Vec<Point>, ParseError> {
 
     Ok(points)
 }
+
+/// Parses a CSV trace in local coordinates, such as an indoor positioning or robot log,
+/// with a header line then one `timestamp,east,north` line per point: Unix seconds and
+/// meters from the origin of the site.
+///
+/// Points hold the north coordinate as `latitude` and the east one as `longitude`, to be
+/// read with `CoordinateSystem::Local`. Blank lines are ignored.
+pub fn parse_local_csv(reader: impl BufRead) -> Result<Vec<Point>, ParseError> {
+    let mut points = Vec::new();
+
+    for line in reader.lines().skip(1) {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let [timestamp, east, north] = line.split(',').collect::<Vec<_>>()[..] else {
+            return Err(ParseError::InvalidFieldCount);
+        };
+
+        let datetime = timestamp
+            .trim()
+            .parse()
+            .ok()
+            .and_then(|ts| DateTime::from_timestamp(ts, 0))
+            .ok_or(ParseError::InvalidTimestamp)?;
+        let coordinate = |value: &str| {
+            value
+                .trim()
+                .parse()
+                .map_err(|e: rust_decimal::Error| ParseError::CoordinateParse(e.to_string()))
+        };
+
+        points.push(Point {
+            latitude: coordinate(north)?,
+            longitude: coordinate(east)?,
+            datetime,
+            altitude: None,
+        });
+    }
+
+    Ok(points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "Geolife trajectory\nWGS 84\nAltitude is in Feet\nReserved 3\n\
+        0,2,255,My Track,0,0,2,8421376\n0\n";
+
+    #[test]
+    fn test_parse_empty_file() {
+        assert!(parse_plt_file("".as_bytes()).unwrap().is_empty());
+        assert!(parse_plt_file(HEADER.as_bytes()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_single_point_with_blank_lines() {
+        let data = format!(
+            "{HEADER}39.984702,116.318417,0,492,39744.1201851852,2008-10-23,02:53:04\r\n\r\n"
+        );
+        let points = parse_plt_file(data.as_bytes()).unwrap();
+
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[0].latitude.to_string(), "39.984702");
+        assert_eq!(points[0].longitude.to_string(), "116.318417");
+        assert_eq!(points[0].datetime.timestamp(), 1_224_730_384);
+    }
+
+    #[test]
+    fn test_parse_altitude() {
+        let data = format!(
+            "{HEADER}39.98,116.31,0,100,39744.12,2008-10-23,02:52:48\n\
+             39.98,116.31,0,-777,39744.12,2008-10-23,02:52:48\n"
+        );
+        let points = parse_plt_file(data.as_bytes()).unwrap();
+        assert_eq!(points[0].altitude, Some(Decimal::new(3048, 2)));
+        assert_eq!(points[1].altitude, None);
+
+        let data = format!("{HEADER}39.98,116.31,0,high,39744.12,2008-10-23,02:52:48\n");
+        assert!(matches!(
+            parse_plt_file(data.as_bytes()),
+            Err(ParseError::AltitudeParse(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_invalid_line() {
+        let data = format!("{HEADER}39.984702,116.318417,0,492\n");
+        assert!(matches!(
+            parse_plt_file(data.as_bytes()),
+            Err(ParseError::InvalidFieldCount)
+        ));
+    }
+
+    #[test]
+    fn test_parse_local_csv() {
+        let data = "timestamp,east,north\n1700000000,1.5,-2.25\n\n1700000001,2,-2.5\n";
+        let points = parse_local_csv(data.as_bytes()).unwrap();
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].longitude, Decimal::new(15, 1));
+        assert_eq!(points[0].latitude, Decimal::new(-225, 2));
+        assert_eq!(points[1].datetime.timestamp(), 1_700_000_001);
+
+        assert!(matches!(
+            parse_local_csv("header\n1,2\n".as_bytes()),
+            Err(ParseError::InvalidFieldCount)
+        ));
+        assert!(matches!(
+            parse_local_csv("header\n1,x,2\n".as_bytes()),
+            Err(ParseError::CoordinateParse(_))
+        ));
+    }
+}
@@ -0,0 +1,149 @@
+//! Encoded polyline format (as used by Google Maps, OSRM and Valhalla).
+//! Coordinates are written with the same 10^6 precision as `Trajectory`
+//! ("polyline6"), so encoding is lossless. Timestamps are not part of the format.
+
+use crate::delta::delta_encode;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum PolylineError {
+    #[error("Invalid character in polyline: {0:?}")]
+    InvalidCharacter(char),
+    #[error("Polyline ended in the middle of a value")]
+    UnexpectedEnd,
+    #[error("Polyline contains a latitude without a longitude")]
+    MissingLongitude,
+    #[error("Polyline coordinate at index {0} is out of range")]
+    Overflow(usize),
+}
+
+fn encode_value(value: i64, output: &mut String) {
+    let mut value = if value < 0 { !(value << 1) } else { value << 1 } as u64;
+    while value >= 0x20 {
+        output.push(char::from((0x20 | (value & 0x1f)) as u8 + 63));
+        value >>= 5;
+    }
+    output.push(char::from(value as u8 + 63));
+}
+
+fn decode_value(chars: &mut impl Iterator<Item = char>) -> Result<Option<i64>, PolylineError> {
+    let mut result: u64 = 0;
+    let mut shift = 0;
+    let mut started = false;
+
+    for c in chars.by_ref() {
+        let byte = (c as u32)
+            .checked_sub(63)
+            .filter(|&b| b < 64 && shift < 64)
+            .ok_or(PolylineError::InvalidCharacter(c))? as u64;
+        started = true;
+        result |= (byte & 0x1f) << shift;
+        shift += 5;
+        if byte < 0x20 {
+            let value = (result >> 1) as i64;
+            return Ok(Some(if result & 1 == 1 { !value } else { value }));
+        }
+    }
+
+    if started {
+        Err(PolylineError::UnexpectedEnd)
+    } else {
+        Ok(None)
+    }
+}
+
+/// Encodes scaled coordinates into a polyline string.
+///
+/// # Panics
+///
+/// This function will panic if `latitudes` and `longitudes` have different lengths.
+pub fn encode(latitudes: &[i64], longitudes: &[i64]) -> String {
+    assert_eq!(
+        latitudes.len(),
+        longitudes.len(),
+        "latitudes.len() == longitudes.len()"
+    );
+
+    let mut output = String::new();
+    let latitudes = delta_encode(latitudes.iter().copied());
+    let longitudes = delta_encode(longitudes.iter().copied());
+    for (delta_lat, delta_lon) in latitudes.zip(longitudes) {
+        encode_value(delta_lat, &mut output);
+        encode_value(delta_lon, &mut output);
+    }
+    output
+}
+
+/// Decodes a polyline string back into scaled latitudes and longitudes.
+///
+/// Unlike `delta::delta_decode`, sums of deltas do not wrap around: a polyline whose
+/// coordinates leave the range of `i64` is invalid.
+pub fn decode(polyline: &str) -> Result<(Vec<i64>, Vec<i64>), PolylineError> {
+    let mut chars = polyline.chars();
+    let mut latitudes = Vec::new();
+    let mut longitudes = Vec::new();
+    let (mut lat, mut lon) = (0_i64, 0_i64);
+
+    while let Some(delta_lat) = decode_value(&mut chars)? {
+        let delta_lon = decode_value(&mut chars)?.ok_or(PolylineError::MissingLongitude)?;
+        let overflow = || PolylineError::Overflow(latitudes.len());
+        lat = lat.checked_add(delta_lat).ok_or_else(overflow)?;
+        lon = lon.checked_add(delta_lon).ok_or_else(overflow)?;
+        latitudes.push(lat);
+        longitudes.push(lon);
+    }
+
+    Ok((latitudes, longitudes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_reference_polyline() {
+        // Reference example from the polyline algorithm documentation, at 10^5 precision.
+        let latitudes = vec![3_850_000, 4_070_000, 4_325_200];
+        let longitudes = vec![-12_020_000, -12_095_000, -12_645_300];
+        assert_eq!(encode(&latitudes, &longitudes), "_p~iF~ps|U_ulLnnqC_mqNvxq`@");
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let latitudes = vec![39_984_702, 39_984_683, -39_984_686, 0];
+        let longitudes = vec![116_318_417, 116_318_450, 116_318_417, -1];
+        let encoded = encode(&latitudes, &longitudes);
+        assert_eq!(decode(&encoded).unwrap(), (latitudes, longitudes));
+    }
+
+    #[test]
+    fn test_decode_empty() {
+        assert_eq!(decode("").unwrap(), (vec![], vec![]));
+    }
+
+    #[test]
+    fn test_decode_truncated() {
+        assert_eq!(decode("_p~iF~ps|U_"), Err(PolylineError::UnexpectedEnd));
+        assert_eq!(decode("_p~iF"), Err(PolylineError::MissingLongitude));
+    }
+
+    #[test]
+    fn test_decode_overflow() {
+        let mut polyline = String::new();
+        for delta in [i64::MAX, 0, 1, 0] {
+            encode_value(delta, &mut polyline);
+        }
+        assert_eq!(decode(&polyline), Err(PolylineError::Overflow(1)));
+
+        polyline.clear();
+        for delta in [0, i64::MIN, 0, -1] {
+            encode_value(delta, &mut polyline);
+        }
+        assert_eq!(decode(&polyline), Err(PolylineError::Overflow(1)));
+    }
+
+    #[test]
+    fn test_decode_invalid_character() {
+        assert_eq!(decode(" "), Err(PolylineError::InvalidCharacter(' ')));
+    }
+}
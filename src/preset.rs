@@ -0,0 +1,135 @@
+//! Named presets for generalizing trajectories to draw on web maps at a zoom level.
+//!
+//! A preset bundles the settings of `tile`: the points are projected to Web Mercator
+//! pixels and simplified with a tolerance in pixels at the zoom level of the preset, then
+//! points are added back up to its minimum (see `simplify::ensure_min_points`), so that at
+//! low zoom levels a trip keeps its shape instead of being drawn as a single segment. The
+//! output format suits the use: GeoJSON layers for overview maps, encoded polylines for
+//! the street-level trips of a list or a detail view.
+
+use crate::encoding::Encoding;
+use crate::export::ExportFormat;
+use crate::simplify;
+use crate::tile;
+use crate::trajectory::Trajectory;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+#[error("Unknown preset: {0} (expected one of web-z6, web-z10, web-z14, web-z18)")]
+pub struct UnknownPreset(pub String);
+
+/// Settings of generalizing trajectories for one zoom level of a web map.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Preset {
+    pub name: &'static str,
+    /// Zoom level of the Web Mercator projection the tolerance applies at
+    pub zoom: u8,
+    /// Tolerance in pixels at `zoom`
+    pub epsilon_px: f64,
+    /// Fewest points a simplified trajectory keeps, if it has that many
+    pub min_points: usize,
+    pub format: ExportFormat,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 4] = [
+        // Countries and regions
+        Preset {
+            name: "web-z6",
+            zoom: 6,
+            epsilon_px: 1.0,
+            min_points: 3,
+            format: ExportFormat::GeoJson,
+        },
+        // Cities
+        Preset {
+            name: "web-z10",
+            zoom: 10,
+            epsilon_px: 1.0,
+            min_points: 3,
+            format: ExportFormat::GeoJson,
+        },
+        // Streets
+        Preset {
+            name: "web-z14",
+            zoom: 14,
+            epsilon_px: 0.5,
+            min_points: 2,
+            format: ExportFormat::Encoded(Encoding::Polyline),
+        },
+        // Buildings
+        Preset {
+            name: "web-z18",
+            zoom: tile::MAX_ZOOM,
+            epsilon_px: 0.25,
+            min_points: 2,
+            format: ExportFormat::Encoded(Encoding::Polyline),
+        },
+    ];
+
+    /// Simplifies a trajectory in Web Mercator pixels at the zoom level of the preset,
+    /// keeping at least `min_points` points.
+    pub fn generalize(&self, trajectory: Trajectory) -> Trajectory {
+        let (xs, ys) = tile::project_trajectory(&trajectory.latitudes, &trajectory.longitudes);
+        let epsilon = tile::zoom_epsilon(self.zoom, self.epsilon_px);
+        let mut keep_points = simplify::simplify(&xs, &ys, epsilon);
+        simplify::ensure_min_points(&xs, &ys, &mut keep_points, self.min_points);
+        trajectory.filter(&keep_points)
+    }
+}
+
+impl FromStr for Preset {
+    type Err = UnknownPreset;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Preset::ALL
+            .into_iter()
+            .find(|preset| preset.name == s)
+            .ok_or_else(|| UnknownPreset(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trajectory::CoordinateSystem;
+
+    /// A 200 m walk north in Beijing with a 20 m detour east halfway.
+    fn walk() -> Trajectory {
+        Trajectory {
+            latitudes: vec![39_900_000, 39_900_900, 39_901_800],
+            longitudes: vec![116_400_000, 116_400_235, 116_400_000],
+            timestamps: vec![0, 60, 120],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        }
+    }
+
+    #[test]
+    fn test_from_str() {
+        for preset in Preset::ALL {
+            assert_eq!(preset.name.parse::<Preset>().unwrap(), preset);
+        }
+        assert_eq!("web-z14".parse::<Preset>().unwrap().zoom, 14);
+        assert!("web-z20".parse::<Preset>().is_err());
+    }
+
+    #[test]
+    fn test_generalize() {
+        // The detour is below a pixel at zoom 10, but kept as the third point
+        assert_eq!(Preset::ALL[1].generalize(walk()), walk());
+        assert_eq!(Preset::ALL[2].generalize(walk()), walk());
+
+        let mut straight = walk();
+        straight.longitudes[1] = 116_400_000;
+        let street = Preset::ALL[2].generalize(straight.clone());
+        assert_eq!(street.timestamps, [0, 120]);
+        let overview = Preset::ALL[1].generalize(straight.clone());
+        assert_eq!(overview, straight);
+
+        // Shorter trajectories are kept whole
+        let point = walk().filter(&[true, false, false]);
+        assert_eq!(Preset::ALL[0].generalize(point.clone()), point);
+    }
+}
@@ -0,0 +1,138 @@
+//! Geo-indistinguishability: planar Laplace noise added to coordinates before a dataset is
+//! shared, so that any two locations within `radius_m` of each other produce the same
+//! output with probabilities differing by at most a factor `e^level`.
+//!
+//! Noise is drawn independently for every point, so the guarantee holds per point: an
+//! adversary averaging many points recorded at the same place learns more. Simplifying
+//! first reduces the number of points published.
+//!
+//! See Andrés et al., "Geo-Indistinguishability: Differential Privacy for Location-Based
+//! Systems", CCS 2013.
+
+use crate::geo::{to_degrees, METERS_PER_UNIT};
+use crate::trajectory::Trajectory;
+use rand::Rng;
+use serde_json::{json, Value};
+use std::f64::consts::PI;
+
+/// Privacy parameters: `level` of privacy (the ε·r of the paper) within `radius_m` meters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GeoIndistinguishability {
+    pub level: f64,
+    pub radius_m: f64,
+}
+
+impl GeoIndistinguishability {
+    /// Privacy budget per meter, the ε of the planar Laplace distribution.
+    pub fn epsilon_per_m(self) -> f64 {
+        self.level / self.radius_m
+    }
+
+    /// Describes the mechanism and its parameters, to publish next to the noisy dataset.
+    pub fn metadata(self, points: usize) -> Value {
+        json!({
+            "mechanism": "planar-laplace",
+            "guarantee": "geo-indistinguishability, per point",
+            "level": self.level,
+            "radius_m": self.radius_m,
+            "epsilon_per_m": self.epsilon_per_m(),
+            "expected_displacement_m": 2.0 / self.epsilon_per_m(),
+            "points": points,
+        })
+    }
+}
+
+/// Draws a planar Laplace displacement `(east, north)` in meters.
+///
+/// The angle is uniform, and the distance follows a Gamma(2, ε) distribution, drawn as the
+/// sum of two exponential variables.
+pub fn planar_laplace(rng: &mut impl Rng, epsilon_per_m: f64) -> (f64, f64) {
+    let theta = rng.gen_range(0.0..2.0 * PI);
+    // 1 - u is in (0, 1], so the logarithms are finite
+    let radius = -((1.0 - rng.gen::<f64>()).ln() + (1.0 - rng.gen::<f64>()).ln()) / epsilon_per_m;
+    (radius * theta.cos(), radius * theta.sin())
+}
+
+/// Returns a copy of the trajectory with planar Laplace noise added to every point.
+/// Timestamps are unchanged.
+pub fn add_noise(
+    trajectory: &Trajectory,
+    params: GeoIndistinguishability,
+    rng: &mut impl Rng,
+) -> Trajectory {
+    let epsilon_per_m = params.epsilon_per_m();
+    let mut noisy = trajectory.clone();
+    for (latitude, longitude) in noisy.latitudes.iter_mut().zip(&mut noisy.longitudes) {
+        let (east_m, north_m) = planar_laplace(rng, epsilon_per_m);
+        let cos_lat = to_degrees(*latitude).to_radians().cos().max(0.01);
+        *latitude += (north_m / METERS_PER_UNIT).round() as i64;
+        *longitude += (east_m / (METERS_PER_UNIT * cos_lat)).round() as i64;
+    }
+    noisy
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trajectory::CoordinateSystem;
+    use crate::geo::haversine_m;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const PARAMS: GeoIndistinguishability = GeoIndistinguishability {
+        level: std::f64::consts::LN_2,
+        radius_m: 100.0,
+    };
+
+    #[test]
+    fn test_planar_laplace_distribution() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let samples: Vec<(f64, f64)> = (0..100_000)
+            .map(|_| planar_laplace(&mut rng, PARAMS.epsilon_per_m()))
+            .collect();
+        let n = samples.len() as f64;
+
+        // The mean distance of Gamma(2, ε) is 2/ε, about 289 m here
+        let mean_radius = samples.iter().map(|(x, y)| x.hypot(*y)).sum::<f64>() / n;
+        let expected = 2.0 / PARAMS.epsilon_per_m();
+        assert!((mean_radius - expected).abs() < expected * 0.01, "{mean_radius}");
+
+        // Directions are uniform, so the displacements cancel out on average
+        let mean_x = samples.iter().map(|(x, _)| x).sum::<f64>() / n;
+        let mean_y = samples.iter().map(|(_, y)| y).sum::<f64>() / n;
+        assert!(mean_x.abs() < 5.0 && mean_y.abs() < 5.0, "{mean_x} {mean_y}");
+    }
+
+    #[test]
+    fn test_add_noise() {
+        let trajectory = Trajectory {
+            latitudes: vec![39_984_702; 1000],
+            longitudes: vec![116_318_417; 1000],
+            timestamps: (0..1000).collect(),
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+        let noisy = add_noise(&trajectory, PARAMS, &mut StdRng::seed_from_u64(1));
+
+        assert_eq!(noisy.timestamps, trajectory.timestamps);
+        let distances: Vec<f64> = noisy
+            .latitudes
+            .iter()
+            .zip(&noisy.longitudes)
+            .map(|(&lat, &lon)| haversine_m(39_984_702, 116_318_417, lat, lon))
+            .collect();
+        let mean = distances.iter().sum::<f64>() / distances.len() as f64;
+        let expected = 2.0 / PARAMS.epsilon_per_m();
+        assert!((mean - expected).abs() < expected * 0.1, "{mean}");
+        assert!(distances.iter().all(|&distance| distance > 0.0));
+    }
+
+    #[test]
+    fn test_metadata() {
+        let metadata = PARAMS.metadata(42);
+        assert_eq!(metadata["mechanism"], "planar-laplace");
+        assert_eq!(metadata["radius_m"], 100.0);
+        assert_eq!(metadata["points"], 42);
+        assert_eq!(metadata["epsilon_per_m"], PARAMS.level / 100.0);
+    }
+}
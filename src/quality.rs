@@ -0,0 +1,285 @@
+//! Measurement of the simplification error along a trajectory.
+//!
+//! Each segment of the simplified trajectory replaces a run of original points. Its
+//! error is the largest distance between one of those points and the segment, which
+//! can be exported as a GeoJSON heat overlay to spot under- and over-simplified regions.
+
+use crate::geo::to_degrees;
+use crate::proto;
+use crate::trajectory::Trajectory;
+use serde_json::{json, Value};
+
+/// Error of one segment of the simplified trajectory.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SegmentError {
+    /// Index in the original trajectory of the first point of the segment
+    pub start: usize,
+    /// Index in the original trajectory of the last point of the segment
+    pub end: usize,
+    /// Largest distance in meters between a dropped point and the segment
+    pub max_error_m: f64,
+}
+
+/// Computes the error of every segment of the simplified trajectory.
+///
+/// # Panics
+///
+/// This function will panic if `keep` does not have one entry per point of `original`.
+pub fn segment_errors(original: &Trajectory, keep: &[bool]) -> Vec<SegmentError> {
+    assert_eq!(keep.len(), original.latitudes.len(), "keep.len() == len");
+
+    let point = |i: usize| (original.latitudes[i], original.longitudes[i]);
+    let kept: Vec<usize> = (0..keep.len()).filter(|&i| keep[i]).collect();
+
+    kept.windows(2)
+        .map(|pair| {
+            let (start, end) = (pair[0], pair[1]);
+            let max_error_m = (start + 1..end)
+                .map(|i| {
+                    original
+                        .coordinates
+                        .segment_distance_m(point(i), point(start), point(end))
+                })
+                .fold(0.0, f64::max);
+            SegmentError {
+                start,
+                end,
+                max_error_m,
+            }
+        })
+        .collect()
+}
+
+/// Distance between an original point and its reconstruction from the simplified
+/// trajectory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorMetric {
+    /// Perpendicular Euclidean distance: distance to the simplified segment
+    Perpendicular,
+    /// Synchronized Euclidean distance: distance to the position on the simplified segment
+    /// at the same time, interpolated between its endpoints
+    Synchronized,
+}
+
+/// Computes the reconstruction error in meters of every original point, zero for the kept
+/// ones.
+///
+/// # Panics
+///
+/// This function will panic if `keep` does not have one entry per point of `original`.
+pub fn point_errors(original: &Trajectory, keep: &[bool], metric: ErrorMetric) -> Vec<f64> {
+    let mut errors = vec![0.0; keep.len()];
+    let point = |i: usize| (original.latitudes[i], original.longitudes[i]);
+
+    for segment in segment_errors(original, keep) {
+        let (start, end) = (segment.start, segment.end);
+        for (i, error) in errors.iter_mut().enumerate().take(end).skip(start + 1) {
+            *error = match metric {
+                ErrorMetric::Perpendicular => original
+                    .coordinates
+                    .segment_distance_m(point(i), point(start), point(end)),
+                ErrorMetric::Synchronized => {
+                    let (t0, t1) = (original.timestamps[start], original.timestamps[end]);
+                    let t = if t1 > t0 {
+                        original.timestamps[i].saturating_sub(t0) as f64 / (t1 - t0) as f64
+                    } else {
+                        0.0
+                    };
+                    let interpolate = |values: &[i64]| {
+                        values[start] + ((values[end] - values[start]) as f64 * t).round() as i64
+                    };
+                    let synchronized = (
+                        interpolate(&original.latitudes),
+                        interpolate(&original.longitudes),
+                    );
+                    original.coordinates.distance_m(point(i), synchronized)
+                }
+            };
+        }
+    }
+    errors
+}
+
+/// How far a simplified trajectory is from its original, to be stored with it so that
+/// readers can tell whether it is precise enough without the original.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Reconstruction {
+    /// Largest tolerance of the simplification
+    pub epsilon_m: f64,
+    /// Largest perpendicular distance between an original point and the simplified path
+    pub max_error_m: f64,
+    /// Mean perpendicular distance over every original point, kept ones included
+    pub mean_error_m: f64,
+}
+
+impl Reconstruction {
+    /// Measures the simplification of `original` to its `keep` points with a tolerance of
+    /// at most `epsilon_m` meters.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `keep` does not have one entry per point of `original`.
+    pub fn measure(original: &Trajectory, keep: &[bool], epsilon_m: f64) -> Self {
+        let errors = point_errors(original, keep, ErrorMetric::Perpendicular);
+        Reconstruction {
+            epsilon_m,
+            max_error_m: errors.iter().copied().fold(0.0, f64::max),
+            mean_error_m: if errors.is_empty() {
+                0.0
+            } else {
+                errors.iter().sum::<f64>() / errors.len() as f64
+            },
+        }
+    }
+
+    pub fn to_proto(self) -> proto::Reconstruction {
+        proto::Reconstruction {
+            epsilon_m: self.epsilon_m,
+            max_error_m: self.max_error_m,
+            mean_error_m: self.mean_error_m,
+        }
+    }
+
+    /// Describes the reconstruction, to store next to the trajectory.
+    pub fn metadata(&self) -> Value {
+        json!({
+            "epsilon_m": self.epsilon_m,
+            "max_error_m": self.max_error_m,
+            "mean_error_m": self.mean_error_m,
+        })
+    }
+}
+
+/// Color from green (no error) to red (error at or above `max_error_m`), as `#rrggbb`.
+fn heat_color(error_m: f64, max_error_m: f64) -> String {
+    let ratio = if max_error_m > 0.0 {
+        (error_m / max_error_m).clamp(0.0, 1.0)
+    } else {
+        1.0
+    };
+    let red = (255.0 * (2.0 * ratio).min(1.0)).round() as u8;
+    let green = (255.0 * (2.0 - 2.0 * ratio).min(1.0)).round() as u8;
+    format!("#{red:02x}{green:02x}00")
+}
+
+/// Builds a GeoJSON `FeatureCollection` with one `LineString` per simplified segment.
+///
+/// Each feature carries its `max_error_m` and a `stroke` color (understood by
+/// geojson.io and other simplestyle renderers) scaled against `max_color_error_m`.
+pub fn heat_geojson(original: &Trajectory, keep: &[bool], max_color_error_m: f64) -> Value {
+    let features: Vec<Value> = segment_errors(original, keep)
+        .into_iter()
+        .map(|segment| {
+            let coordinates: Vec<Value> = [segment.start, segment.end]
+                .into_iter()
+                .map(|i| {
+                    json!([
+                        to_degrees(original.longitudes[i]),
+                        to_degrees(original.latitudes[i])
+                    ])
+                })
+                .collect();
+            json!({
+                "type": "Feature",
+                "geometry": { "type": "LineString", "coordinates": coordinates },
+                "properties": {
+                    "max_error_m": segment.max_error_m,
+                    "dropped_points": segment.end - segment.start - 1,
+                    "start_time": original.timestamps[segment.start],
+                    "end_time": original.timestamps[segment.end],
+                    "stroke": heat_color(segment.max_error_m, max_color_error_m),
+                },
+            })
+        })
+        .collect();
+
+    json!({ "type": "FeatureCollection", "features": features })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geo::{haversine_m, METERS_PER_UNIT};
+    use crate::trajectory::CoordinateSystem;
+
+    fn sample() -> Trajectory {
+        Trajectory {
+            latitudes: vec![0, 10, 0, 0, 0],
+            longitudes: vec![0, 50, 100, 150, 200],
+            timestamps: vec![0, 1, 2, 3, 4],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        }
+    }
+
+    #[test]
+    fn test_segment_errors() {
+        let errors = segment_errors(&sample(), &[true, false, true, false, true]);
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!((errors[0].start, errors[0].end), (0, 2));
+        assert!((errors[0].max_error_m - 10.0 * METERS_PER_UNIT).abs() < 1e-9);
+        assert_eq!((errors[1].start, errors[1].end), (2, 4));
+        assert_eq!(errors[1].max_error_m, 0.0);
+    }
+
+    #[test]
+    fn test_segment_errors_all_kept() {
+        let errors = segment_errors(&sample(), &[true; 5]);
+        assert_eq!(errors.len(), 4);
+        assert!(errors.iter().all(|e| e.max_error_m == 0.0));
+    }
+
+    #[test]
+    fn test_point_errors() {
+        let keep = [true, false, true, false, true];
+        let errors = point_errors(&sample(), &keep, ErrorMetric::Perpendicular);
+        assert_eq!(errors.len(), 5);
+        assert!((errors[1] - 10.0 * METERS_PER_UNIT).abs() < 1e-9);
+        assert_eq!(errors.iter().filter(|&&error| error > 0.0).count(), 1);
+
+        // The second point is recorded late: halfway in space but a quarter of the way in
+        // time, so the synchronized position is 25 units behind it
+        let mut late = sample();
+        late.timestamps = vec![0, 1, 4, 5, 6];
+        let errors = point_errors(&late, &keep, ErrorMetric::Synchronized);
+        let expected = haversine_m(10, 50, 0, 25);
+        assert!((errors[1] - expected).abs() < 1e-9, "{errors:?}");
+        assert_eq!(errors[3], 0.0);
+    }
+
+    #[test]
+    fn test_reconstruction() {
+        let reconstruction =
+            Reconstruction::measure(&sample(), &[true, false, true, true, true], 2.0);
+        let error = 10.0 * METERS_PER_UNIT;
+        assert_eq!(reconstruction.epsilon_m, 2.0);
+        assert!((reconstruction.max_error_m - error).abs() < 1e-9);
+        assert!((reconstruction.mean_error_m - error / 5.0).abs() < 1e-9);
+        assert_eq!(
+            reconstruction.to_proto().max_error_m,
+            reconstruction.max_error_m
+        );
+        assert_eq!(reconstruction.metadata()["epsilon_m"], 2.0);
+    }
+
+    #[test]
+    fn test_heat_color() {
+        assert_eq!(heat_color(0.0, 10.0), "#00ff00");
+        assert_eq!(heat_color(5.0, 10.0), "#ffff00");
+        assert_eq!(heat_color(20.0, 10.0), "#ff0000");
+    }
+
+    #[test]
+    fn test_heat_geojson() {
+        let geojson = heat_geojson(&sample(), &[true, false, true, false, true], 1.0);
+        let features = geojson["features"].as_array().unwrap();
+
+        assert_eq!(geojson["type"], "FeatureCollection");
+        assert_eq!(features.len(), 2);
+        assert_eq!(features[0]["geometry"]["coordinates"], json!([[0.0, 0.0], [0.0001, 0.0]]));
+        assert_eq!(features[0]["properties"]["dropped_points"], 1);
+        assert_eq!(features[0]["properties"]["stroke"], "#ff0000");
+        assert_eq!(features[1]["properties"]["stroke"], "#00ff00");
+    }
+}
@@ -0,0 +1,363 @@
+//! Append-only log of the raw points received by the streaming ingestion, and its
+//! compaction into a store.
+//!
+//! Points are appended as `trajectory,timestamp,latitude,longitude` lines, in coordinate
+//! units, and synced before `append` returns, so that an acknowledged point survives a
+//! crash. Compaction simplifies the trajectories that stopped receiving points, writes
+//! them to the store, and rewrites the log with the remaining points.
+//!
+//! Compaction is crash-safe: the log is only replaced, by a rename, once the segments are
+//! in the store, and segments are stored under an id derived from their first point, so
+//! compacting again after a crash overwrites them with the same content.
+
+use crate::geo;
+use crate::quality::Reconstruction;
+use crate::simplify;
+use crate::store::{StoreError, TrajectoryStore};
+use crate::trajectory::{CoordinateSystem, Trajectory};
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum RawLogError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Store error: {0}")]
+    Store(#[from] StoreError),
+    #[error("Malformed line {0} in the raw log")]
+    Malformed(usize),
+}
+
+/// Point as received, before simplification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawPoint {
+    pub trajectory: String,
+    pub timestamp: u64,
+    pub latitude: i64,
+    pub longitude: i64,
+}
+
+/// Outcome of a compaction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Compaction {
+    /// Closed segments written to the store
+    pub segments: usize,
+    /// Points of the closed segments
+    pub points: usize,
+    /// Points kept by the simplification of the closed segments
+    pub kept_points: usize,
+    /// Points of the open segments, left in the log
+    pub remaining_points: usize,
+}
+
+/// Append-only log file, shared by the writers and the compaction.
+#[derive(Debug)]
+pub struct RawLog {
+    path: PathBuf,
+    /// Appending handle, locked by writers and by the compaction for the whole rewrite
+    file: Mutex<File>,
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+fn parse_line(line: &str) -> Option<RawPoint> {
+    let mut fields = line.split(',');
+    let point = RawPoint {
+        trajectory: fields.next()?.to_string(),
+        timestamp: fields.next()?.parse().ok()?,
+        latitude: fields.next()?.parse().ok()?,
+        longitude: fields.next()?.parse().ok()?,
+    };
+    fields.next().is_none().then_some(point)
+}
+
+/// Reads the points of a log. A last line without its newline was being written during a
+/// crash and was never acknowledged, so it is ignored.
+fn read_points(path: &Path) -> Result<Vec<RawPoint>, RawLogError> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(error) => return Err(error.into()),
+    };
+    let complete = content.rfind('\n').map_or("", |end| &content[..end]);
+    complete
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(index, line)| parse_line(line).ok_or(RawLogError::Malformed(index + 1)))
+        .collect()
+}
+
+fn write_points(output: &mut impl Write, points: &[RawPoint]) -> io::Result<()> {
+    for point in points {
+        writeln!(
+            output,
+            "{},{},{},{}",
+            point.trajectory, point.timestamp, point.latitude, point.longitude
+        )?;
+    }
+    Ok(())
+}
+
+impl RawLog {
+    /// Opens the log at `path`, creating it if needed. A line cut by a crash is removed,
+    /// so that the next points are not appended to it.
+    pub fn open(path: &Path) -> Result<Self, RawLogError> {
+        let file = open_append(path)?;
+        let content = fs::read(path)?;
+        if content.last().is_some_and(|&byte| byte != b'\n') {
+            let complete = content
+                .iter()
+                .rposition(|&byte| byte == b'\n')
+                .map_or(0, |end| end + 1);
+            file.set_len(complete as u64)?;
+        }
+        Ok(RawLog {
+            path: path.to_path_buf(),
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Appends points and syncs them to disk.
+    pub fn append(&self, points: &[RawPoint]) -> Result<(), RawLogError> {
+        let mut file = self.file.lock().unwrap();
+        let mut output = BufWriter::new(&mut *file);
+        write_points(&mut output, points)?;
+        output.flush()?;
+        drop(output);
+        file.sync_data()?;
+        Ok(())
+    }
+
+    /// Simplifies the segments whose last point is older than `closed_before`, writes each
+    /// to the store as `<trajectory>-<first timestamp>`, and truncates the log to the
+    /// points of the other segments.
+    pub fn compact(
+        &self,
+        store: &dyn TrajectoryStore,
+        epsilon: i64,
+        closed_before: u64,
+    ) -> Result<Compaction, RawLogError> {
+        let mut file = self.file.lock().unwrap();
+
+        let mut segments: BTreeMap<String, Vec<RawPoint>> = BTreeMap::new();
+        for point in read_points(&self.path)? {
+            segments
+                .entry(point.trajectory.clone())
+                .or_default()
+                .push(point);
+        }
+
+        let mut compaction = Compaction::default();
+        let mut remaining = Vec::new();
+        for (trajectory, mut points) in segments {
+            points.sort_by_key(|point| point.timestamp);
+            if points.last().unwrap().timestamp >= closed_before {
+                compaction.remaining_points += points.len();
+                remaining.extend(points);
+                continue;
+            }
+
+            let segment = Trajectory {
+                latitudes: points.iter().map(|point| point.latitude).collect(),
+                longitudes: points.iter().map(|point| point.longitude).collect(),
+                timestamps: points.iter().map(|point| point.timestamp).collect(),
+                altitudes: Vec::new(),
+                coordinates: CoordinateSystem::Wgs84,
+            };
+            let keep = simplify::simplify(&segment.latitudes, &segment.longitudes, epsilon);
+            let reconstruction =
+                Reconstruction::measure(&segment, &keep, epsilon as f64 * geo::METERS_PER_UNIT);
+            let simplified = segment.filter(&keep);
+            let id = format!("{trajectory}-{}", points[0].timestamp);
+            store.put(&id, &simplified)?;
+            store.put_metadata(
+                &id,
+                &serde_json::json!({ "reconstruction": reconstruction.metadata() }),
+            )?;
+            compaction.segments += 1;
+            compaction.points += points.len();
+            compaction.kept_points += simplified.latitudes.len();
+        }
+
+        if compaction.segments > 0 {
+            // The open segments are written aside, then replace the log at once
+            let partial = self.path.with_extension("partial");
+            let mut output = BufWriter::new(File::create(&partial)?);
+            write_points(&mut output, &remaining)?;
+            output
+                .into_inner()
+                .map_err(|error| error.into_error())?
+                .sync_all()?;
+            fs::rename(&partial, &self.path)?;
+            *file = open_append(&self.path)?;
+        }
+        Ok(compaction)
+    }
+}
+
+impl Compaction {
+    /// Adds the segments compacted by `other`, whose remaining points are the latest ones.
+    fn add(&mut self, other: Compaction) {
+        self.segments += other.segments;
+        self.points += other.points;
+        self.kept_points += other.kept_points;
+        self.remaining_points = other.remaining_points;
+    }
+}
+
+/// Replays `points`, sorted by timestamp, as if received live: appends them to `log` in
+/// batches of `batch_size`, compacts into `store` the segments idle for `idle_s` seconds
+/// whenever they may have changed, and every segment once the replay is over.
+///
+/// # Returns
+///
+/// The segments compacted over the whole replay.
+///
+/// # Panics
+///
+/// This function will panic if `batch_size` is zero.
+pub fn replay(
+    log: &RawLog,
+    store: &dyn TrajectoryStore,
+    points: &[RawPoint],
+    batch_size: usize,
+    epsilon: i64,
+    idle_s: u64,
+) -> Result<Compaction, RawLogError> {
+    let mut totals = Compaction::default();
+    let mut compacted_at = 0;
+    for batch in points.chunks(batch_size) {
+        log.append(batch)?;
+        // Time of the replay, as given by the latest point received
+        let now = batch.last().unwrap().timestamp;
+        if now.saturating_sub(compacted_at) >= idle_s {
+            totals.add(log.compact(store, epsilon, now - idle_s)?);
+            compacted_at = now;
+        }
+    }
+    // The replay is over, so every trajectory is closed
+    totals.add(log.compact(store, epsilon, u64::MAX)?);
+    Ok(totals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::FsStore;
+
+    fn point(trajectory: &str, timestamp: u64, latitude: i64) -> RawPoint {
+        RawPoint {
+            trajectory: trajectory.to_string(),
+            timestamp,
+            latitude,
+            longitude: 0,
+        }
+    }
+
+    #[test]
+    fn test_compact() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FsStore::open(&dir.path().join("store")).unwrap();
+        let path = dir.path().join("raw.log");
+        let log = RawLog::open(&path).unwrap();
+
+        // "bike" goes straight, "car" is still moving
+        log.append(&[point("bike", 10, 0), point("car", 10, 0)])
+            .unwrap();
+        log.append(&[
+            point("bike", 20, 5),
+            point("bike", 30, 10),
+            point("car", 40, 5),
+        ])
+        .unwrap();
+
+        let compaction = log.compact(&store, 1, 35).unwrap();
+        assert_eq!(
+            compaction,
+            Compaction {
+                segments: 1,
+                points: 3,
+                kept_points: 2,
+                remaining_points: 2,
+            }
+        );
+        let bike = store.get("bike-10").unwrap().unwrap();
+        assert_eq!(bike.timestamps, [10, 30]);
+        let metadata = store.metadata("bike-10").unwrap().unwrap();
+        assert_eq!(metadata["reconstruction"]["max_error_m"], 0.0);
+        assert_eq!(
+            read_points(&path).unwrap(),
+            [point("car", 10, 0), point("car", 40, 5)]
+        );
+
+        // Appends go to the truncated log
+        log.append(&[point("car", 50, 10)]).unwrap();
+        let compaction = log.compact(&store, 1, u64::MAX).unwrap();
+        assert_eq!((compaction.segments, compaction.points), (1, 3));
+        assert_eq!(store.list().unwrap(), ["bike-10", "car-10"]);
+        assert!(read_points(&path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_replay() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FsStore::open(&dir.path().join("store")).unwrap();
+        let log = RawLog::open(&dir.path().join("raw.log")).unwrap();
+
+        // "bike" stops at 30, long before "car" does
+        let mut points: Vec<RawPoint> = [10, 20, 30]
+            .into_iter()
+            .map(|timestamp| point("bike", timestamp, timestamp as i64))
+            .chain(
+                (10..=100)
+                    .step_by(10)
+                    .map(|timestamp| point("car", timestamp, 0)),
+            )
+            .collect();
+        points.sort_by_key(|point| point.timestamp);
+
+        let totals = replay(&log, &store, &points, 2, 1, 30).unwrap();
+        assert_eq!(
+            totals,
+            Compaction {
+                segments: 2,
+                points: 13,
+                kept_points: 4,
+                remaining_points: 0,
+            }
+        );
+        assert_eq!(store.list().unwrap(), ["bike-10", "car-10"]);
+    }
+
+    #[test]
+    fn test_read_points() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("raw.log");
+        assert!(read_points(&path).unwrap().is_empty());
+
+        // The last line was cut by a crash
+        fs::write(&path, "bike,10,1,2\nbike,20,3").unwrap();
+        assert_eq!(
+            read_points(&path).unwrap(),
+            [RawPoint {
+                longitude: 2,
+                ..point("bike", 10, 1)
+            }]
+        );
+
+        // and is removed before appending
+        let log = RawLog::open(&path).unwrap();
+        log.append(&[point("car", 30, 5)]).unwrap();
+        assert_eq!(read_points(&path).unwrap().len(), 2);
+
+        fs::write(&path, "bike,10,1,2\nbike,x,3,4\n").unwrap();
+        assert!(matches!(read_points(&path), Err(RawLogError::Malformed(2))));
+    }
+}
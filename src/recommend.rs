@@ -0,0 +1,134 @@
+//! Recommendation of simplification tolerances from the reported accuracy of the fixes.
+//!
+//! Detail finer than the GPS error is noise: a tolerance below the error of the fixes only
+//! keeps points to follow that noise. Fixes are grouped into accuracy classes, and each
+//! class is recommended the median accuracy of its fixes as tolerance, so the
+//! simplification error stays below the error of a typical fix.
+
+/// Accuracy assumed for fixes without a reported accuracy, that of a consumer GPS receiver
+/// under open sky
+const ASSUMED_ACCURACY_M: f64 = 10.0;
+
+/// Fixes whose reported accuracy is at most `max_accuracy_m` meters, and more than that of
+/// the previous class.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct AccuracyClass {
+    name: &'static str,
+    max_accuracy_m: f64,
+}
+
+/// Classes from the most to the least accurate fixes
+const ACCURACY_CLASSES: [AccuracyClass; 4] = [
+    AccuracyClass {
+        name: "high",
+        max_accuracy_m: 5.0,
+    },
+    AccuracyClass {
+        name: "standard",
+        max_accuracy_m: 15.0,
+    },
+    AccuracyClass {
+        name: "degraded",
+        max_accuracy_m: 50.0,
+    },
+    AccuracyClass {
+        name: "poor",
+        max_accuracy_m: f64::INFINITY,
+    },
+];
+
+/// Tolerance recommended for the fixes of one class.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Recommendation {
+    /// Name of the class, or `unreported` for fixes without accuracy
+    pub class: &'static str,
+    pub points: usize,
+    /// Median reported accuracy in meters, or the assumed one for unreported fixes
+    pub median_accuracy_m: f64,
+    /// Recommended tolerance in meters, the median accuracy rounded up to the meter
+    pub epsilon_m: f64,
+}
+
+fn recommendation(class: &'static str, accuracies: &mut [f64]) -> Recommendation {
+    accuracies.sort_by(f64::total_cmp);
+    let median_accuracy_m = accuracies[(accuracies.len() - 1) / 2];
+    Recommendation {
+        class,
+        points: accuracies.len(),
+        median_accuracy_m,
+        epsilon_m: median_accuracy_m.ceil().max(1.0),
+    }
+}
+
+/// Recommends a tolerance for every accuracy class with fixes, from the most to the least
+/// accurate, followed by one for the fixes without a reported accuracy, if any.
+pub fn recommend(accuracies: &[Option<f64>]) -> Vec<Recommendation> {
+    let mut classes = vec![Vec::new(); ACCURACY_CLASSES.len()];
+    let mut unreported = 0;
+    for accuracy_m in accuracies {
+        match accuracy_m {
+            Some(accuracy_m) => {
+                let class = ACCURACY_CLASSES
+                    .iter()
+                    .position(|class| *accuracy_m <= class.max_accuracy_m)
+                    .unwrap_or(ACCURACY_CLASSES.len() - 1);
+                classes[class].push(*accuracy_m);
+            }
+            None => unreported += 1,
+        }
+    }
+
+    let mut recommendations: Vec<Recommendation> = ACCURACY_CLASSES
+        .iter()
+        .zip(&mut classes)
+        .filter(|(_, accuracies)| !accuracies.is_empty())
+        .map(|(class, accuracies)| recommendation(class.name, accuracies))
+        .collect();
+    if unreported > 0 {
+        recommendations.push(Recommendation {
+            class: "unreported",
+            points: unreported,
+            median_accuracy_m: ASSUMED_ACCURACY_M,
+            epsilon_m: ASSUMED_ACCURACY_M,
+        });
+    }
+    recommendations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recommend() {
+        let accuracies = [
+            Some(3.0),
+            Some(4.2),
+            Some(1.0),
+            Some(12.0),
+            None,
+            Some(80.0),
+            Some(60.0),
+            None,
+        ];
+        let recommendations = recommend(&accuracies);
+        let summary: Vec<(&str, usize, f64)> = recommendations
+            .iter()
+            .map(|r| (r.class, r.points, r.epsilon_m))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("high", 3, 3.0),
+                ("standard", 1, 12.0),
+                ("poor", 2, 60.0),
+                ("unreported", 2, ASSUMED_ACCURACY_M),
+            ]
+        );
+        assert_eq!(recommendations[0].median_accuracy_m, 3.0);
+
+        // Sub-meter fixes still get a 1 m tolerance
+        assert_eq!(recommend(&[Some(0.3)])[0].epsilon_m, 1.0);
+        assert!(recommend(&[]).is_empty());
+    }
+}
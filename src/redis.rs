@@ -0,0 +1,208 @@
+//! Publishing of the latest position of every device to a Redis (or Valkey) GEO set, the
+//! backend of a live map answering `GEOSEARCH` queries.
+//!
+//! Commands are sent with the `redis` crate, with every member of a batch in a single
+//! `GEOADD` and every batch in one pipeline.
+
+use crate::geo;
+use crate::trajectory::Trajectory;
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// Members sent per `GEOADD`, to keep commands small
+const BATCH_SIZE: usize = 1_000;
+
+/// Largest latitude Redis accepts, the limit of Web Mercator
+const MAX_LATITUDE: f64 = 85.051_128_78;
+
+#[derive(Error, Debug)]
+pub enum RedisError {
+    #[error("Redis error: {0}")]
+    Redis(#[from] ::redis::RedisError),
+}
+
+/// Position of a device, in degrees.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Position {
+    pub device: String,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+/// Latest position of every device, over the trajectories read.
+#[derive(Debug, Default)]
+pub struct LatestPositions {
+    /// Timestamp of the latest fix of every device, and its position
+    latest: BTreeMap<String, (u64, Position)>,
+}
+
+impl LatestPositions {
+    /// Keeps the last fix of `trajectory` as the position of `device`, unless the device
+    /// has a later one.
+    ///
+    /// Simplification always keeps the last point, so it is also the latest simplified
+    /// position.
+    pub fn update(&mut self, device: &str, trajectory: &Trajectory) {
+        let Some(&timestamp) = trajectory.timestamps.last() else {
+            return;
+        };
+        if self
+            .latest
+            .get(device)
+            .is_some_and(|(seen, _)| *seen >= timestamp)
+        {
+            return;
+        }
+        let last = trajectory.timestamps.len() - 1;
+        let position = Position {
+            device: device.to_string(),
+            latitude: geo::to_degrees(trajectory.latitudes[last]),
+            longitude: geo::to_degrees(trajectory.longitudes[last]),
+        };
+        self.latest
+            .insert(device.to_string(), (timestamp, position));
+    }
+
+    /// Positions of every device, by device.
+    pub fn into_positions(self) -> Vec<Position> {
+        self.latest
+            .into_values()
+            .map(|(_, position)| position)
+            .collect()
+    }
+}
+
+/// Connection to a Redis server.
+pub struct GeoPublisher {
+    connection: ::redis::Connection,
+}
+
+impl GeoPublisher {
+    /// Connects to the server at `address`, as `host:port`.
+    pub fn connect(address: &str) -> Result<Self, RedisError> {
+        let client = ::redis::Client::open(format!("redis://{address}/"))?;
+        Ok(GeoPublisher {
+            connection: client.get_connection()?,
+        })
+    }
+
+    /// Adds or moves the devices in the GEO set `key`. Positions Redis cannot index, beyond
+    /// 85.05° of latitude, are skipped.
+    ///
+    /// # Returns
+    ///
+    /// The number of devices added to the set, not counting the ones that moved, and the
+    /// number of positions skipped.
+    pub fn publish(
+        &mut self,
+        key: &str,
+        positions: &[Position],
+    ) -> Result<(usize, usize), RedisError> {
+        let valid: Vec<&Position> = positions
+            .iter()
+            .filter(|position| position.latitude.abs() <= MAX_LATITUDE)
+            .collect();
+        let mut pipeline = ::redis::pipe();
+        for batch in valid.chunks(BATCH_SIZE) {
+            let command = pipeline.cmd("GEOADD").arg(key);
+            for position in batch {
+                command
+                    .arg(position.longitude)
+                    .arg(position.latitude)
+                    .arg(&position.device);
+            }
+        }
+        let added: Vec<usize> = pipeline.query(&mut self.connection)?;
+        Ok((added.iter().sum(), positions.len() - valid.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// Answers every command of one connection with the given replies, returning the
+    /// address and a handle yielding the bytes received.
+    fn serve(replies: Vec<&'static str>) -> (String, thread::JoinHandle<Vec<u8>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut received = Vec::new();
+            for reply in replies {
+                let mut buffer = [0; 4096];
+                let len = stream.read(&mut buffer).unwrap();
+                received.extend_from_slice(&buffer[..len]);
+                stream.write_all(reply.as_bytes()).unwrap();
+            }
+            received
+        });
+        (address, handle)
+    }
+
+    fn position(device: &str, latitude: f64, longitude: f64) -> Position {
+        Position {
+            device: device.to_string(),
+            latitude,
+            longitude,
+        }
+    }
+
+    #[test]
+    fn test_latest_positions() {
+        let trajectory = |timestamps: Vec<u64>, latitude: i64| Trajectory {
+            latitudes: vec![latitude; timestamps.len()],
+            longitudes: vec![116_400_000; timestamps.len()],
+            timestamps,
+            altitudes: Vec::new(),
+            coordinates: crate::trajectory::CoordinateSystem::Wgs84,
+        };
+        let mut latest = LatestPositions::default();
+        latest.update("001", &trajectory(vec![10, 20], 39_000_000));
+        latest.update("000", &trajectory(vec![30, 40], 39_900_000));
+        // Earlier than the trajectory already read, and empty
+        latest.update("000", &trajectory(vec![5, 35], 40_000_000));
+        latest.update("002", &trajectory(Vec::new(), 0));
+        assert_eq!(
+            latest.into_positions(),
+            [position("000", 39.9, 116.4), position("001", 39.0, 116.4)]
+        );
+    }
+
+    #[test]
+    fn test_publish() {
+        // The client names itself on connection, in two commands whose replies it ignores
+        let (address, handle) = serve(vec![
+            "+OK\r\n+OK\r\n",
+            ":1\r\n",
+            "-WRONGTYPE wrong kind of value\r\n",
+        ]);
+        let mut publisher = GeoPublisher::connect(&address).unwrap();
+
+        let positions = [
+            position("000", 39.9, 116.4),
+            position("001", 89.0, 0.0),
+            position("002", -33.9, 151.2),
+        ];
+        assert_eq!(publisher.publish("devices", &positions).unwrap(), (1, 1));
+        assert_eq!(
+            publisher.publish("devices", &positions[1..2]).unwrap(),
+            (0, 1)
+        );
+        let error = publisher.publish("devices", &positions[..1]).unwrap_err();
+        assert!(matches!(error, RedisError::Redis(error) if error.code() == Some("WRONGTYPE")));
+
+        let received = String::from_utf8(handle.join().unwrap()).unwrap();
+        let expected = ::redis::cmd("GEOADD")
+            .arg("devices")
+            .arg(&["116.4", "39.9", "000", "151.2", "-33.9", "002"])
+            .get_packed_command();
+        assert!(
+            received.contains(std::str::from_utf8(&expected).unwrap()),
+            "{received}"
+        );
+    }
+}
@@ -0,0 +1,346 @@
+//! Resampling of trajectories to a fixed number of points or at fixed distances.
+
+use crate::trajectory::Trajectory;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ResampleError {
+    #[error("Timestamp at index {0} is earlier than the previous one")]
+    UnsortedTimestamps(usize),
+}
+
+/// Linearly interpolates between two scaled values.
+fn lerp(a: i64, b: i64, t: f64) -> i64 {
+    a + ((b - a) as f64 * t).round() as i64
+}
+
+/// Resamples the trajectory to `n` points equally spaced in time between its first
+/// and last timestamp, linearly interpolating the coordinates.
+///
+/// A trajectory with a single point is resampled by repeating that point.
+///
+/// # Errors
+///
+/// Returns `ResampleError::UnsortedTimestamps` if a timestamp is earlier than the one
+/// before it.
+pub fn resample_by_time(trajectory: &Trajectory, n: usize) -> Result<Trajectory, ResampleError> {
+    let timestamps = &trajectory.timestamps;
+    if let Some(index) = timestamps.windows(2).position(|pair| pair[1] < pair[0]) {
+        return Err(ResampleError::UnsortedTimestamps(index + 1));
+    }
+    let len = timestamps.len();
+    let mut resampled = Trajectory {
+        latitudes: Vec::with_capacity(n),
+        longitudes: Vec::with_capacity(n),
+        timestamps: Vec::with_capacity(n),
+        altitudes: Vec::new(),
+        coordinates: trajectory.coordinates,
+    };
+    if len == 0 {
+        return Ok(resampled);
+    }
+
+    let first = trajectory.timestamps[0];
+    let duration = trajectory.timestamps[len - 1] - first;
+    let mut segment = 0;
+
+    for i in 0..n {
+        let ts = if n > 1 {
+            first as f64 + duration as f64 * i as f64 / (n - 1) as f64
+        } else {
+            first as f64
+        };
+
+        // Advance to the segment [segment, segment + 1] containing ts
+        while segment + 2 < len && (trajectory.timestamps[segment + 1] as f64) < ts {
+            segment += 1;
+        }
+
+        let (latitude, longitude) = if len == 1 {
+            (trajectory.latitudes[0], trajectory.longitudes[0])
+        } else {
+            let t0 = trajectory.timestamps[segment] as f64;
+            let t1 = trajectory.timestamps[segment + 1] as f64;
+            let t = if t1 > t0 {
+                ((ts - t0) / (t1 - t0)).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            (
+                lerp(trajectory.latitudes[segment], trajectory.latitudes[segment + 1], t),
+                lerp(trajectory.longitudes[segment], trajectory.longitudes[segment + 1], t),
+            )
+        };
+
+        resampled.latitudes.push(latitude);
+        resampled.longitudes.push(longitude);
+        resampled.timestamps.push(ts.round() as u64);
+    }
+
+    Ok(resampled)
+}
+
+/// Resamples the trajectory every `interval_m` meters along its path, linearly
+/// interpolating coordinates and timestamps.
+///
+/// The first point is kept, then a point is placed every `interval_m` meters of travel,
+/// and the last point is kept if it is not already one of them. Stationary stretches
+/// produce no points.
+///
+/// # Panics
+///
+/// This function will panic if `interval_m` is not positive.
+pub fn resample_by_distance(trajectory: &Trajectory, interval_m: f64) -> Trajectory {
+    assert!(interval_m > 0.0, "interval_m must be positive");
+
+    let len = trajectory.timestamps.len();
+    let mut resampled = Trajectory {
+        latitudes: Vec::new(),
+        longitudes: Vec::new(),
+        timestamps: Vec::new(),
+        altitudes: Vec::new(),
+        coordinates: trajectory.coordinates,
+    };
+    if len == 0 {
+        return resampled;
+    }
+
+    let mut push = |latitude: i64, longitude: i64, ts: u64| {
+        resampled.latitudes.push(latitude);
+        resampled.longitudes.push(longitude);
+        resampled.timestamps.push(ts);
+    };
+    push(trajectory.latitudes[0], trajectory.longitudes[0], trajectory.timestamps[0]);
+
+    // Distance traveled since the last resampled point
+    let mut traveled = 0.0;
+    let mut last_on_point = true;
+    for i in 1..len {
+        let (lat0, lon0) = (trajectory.latitudes[i - 1], trajectory.longitudes[i - 1]);
+        let (lat1, lon1) = (trajectory.latitudes[i], trajectory.longitudes[i]);
+        let (t0, t1) = (trajectory.timestamps[i - 1], trajectory.timestamps[i]);
+        let length = trajectory.coordinates.distance_m((lat0, lon0), (lat1, lon1));
+
+        // Distance along this segment of the next resampled point
+        let mut next = interval_m - traveled;
+        last_on_point = false;
+        while next <= length {
+            let t = next / length;
+            push(
+                lerp(lat0, lat1, t),
+                lerp(lon0, lon1, t),
+                t0 + ((t1 - t0) as f64 * t).round() as u64,
+            );
+            last_on_point = next == length;
+            next += interval_m;
+        }
+        traveled = length - (next - interval_m);
+    }
+
+    if !last_on_point && len > 1 {
+        push(
+            trajectory.latitudes[len - 1],
+            trajectory.longitudes[len - 1],
+            trajectory.timestamps[len - 1],
+        );
+    }
+
+    resampled
+}
+
+/// Inserts linearly interpolated points so that no segment is longer than `max_gap_m`
+/// meters.
+///
+/// Every original point is kept; a segment of length `l` is cut into `ceil(l / max_gap_m)`
+/// equal parts.
+///
+/// # Panics
+///
+/// This function will panic if `max_gap_m` is not positive.
+pub fn densify(trajectory: &Trajectory, max_gap_m: f64) -> Trajectory {
+    assert!(max_gap_m > 0.0, "max_gap_m must be positive");
+
+    let len = trajectory.timestamps.len();
+    let mut densified = Trajectory {
+        latitudes: Vec::with_capacity(len),
+        longitudes: Vec::with_capacity(len),
+        timestamps: Vec::with_capacity(len),
+        altitudes: Vec::new(),
+        coordinates: trajectory.coordinates,
+    };
+
+    for i in 0..len {
+        if i > 0 {
+            let (lat0, lon0) = (trajectory.latitudes[i - 1], trajectory.longitudes[i - 1]);
+            let (lat1, lon1) = (trajectory.latitudes[i], trajectory.longitudes[i]);
+            let (t0, t1) = (trajectory.timestamps[i - 1], trajectory.timestamps[i]);
+            let parts = (trajectory.coordinates.distance_m((lat0, lon0), (lat1, lon1)) / max_gap_m)
+                .ceil() as u64;
+            for part in 1..parts {
+                let t = part as f64 / parts as f64;
+                densified.latitudes.push(lerp(lat0, lat1, t));
+                densified.longitudes.push(lerp(lon0, lon1, t));
+                densified
+                    .timestamps
+                    .push(t0 + ((t1 - t0) as f64 * t).round() as u64);
+            }
+        }
+        densified.latitudes.push(trajectory.latitudes[i]);
+        densified.longitudes.push(trajectory.longitudes[i]);
+        densified.timestamps.push(trajectory.timestamps[i]);
+    }
+
+    densified
+}
+
+/// Interpolates the position of the trajectory at `timestamp`, in scaled units.
+///
+/// Timestamps must be sorted in non-decreasing order. Returns `None` outside of the time
+/// span of the trajectory.
+pub fn position_at(trajectory: &Trajectory, timestamp: f64) -> Option<(f64, f64)> {
+    let timestamps = &trajectory.timestamps;
+    let first = *timestamps.first()? as f64;
+    let last = *timestamps.last()? as f64;
+    if timestamp < first || timestamp > last {
+        return None;
+    }
+
+    // First point at or after the timestamp
+    let next = timestamps
+        .partition_point(|&ts| (ts as f64) < timestamp)
+        .min(timestamps.len() - 1);
+    let previous = next.saturating_sub(1);
+    let (t0, t1) = (timestamps[previous] as f64, timestamps[next] as f64);
+    let t = if t1 > t0 { (timestamp - t0) / (t1 - t0) } else { 1.0 };
+
+    let interpolate = |values: &[i64]| {
+        values[previous] as f64 + (values[next] - values[previous]) as f64 * t
+    };
+    Some((
+        interpolate(&trajectory.latitudes),
+        interpolate(&trajectory.longitudes),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geo::haversine_m;
+    use crate::trajectory::CoordinateSystem;
+
+    fn trajectory(points: &[(i64, i64, u64)]) -> Trajectory {
+        Trajectory {
+            latitudes: points.iter().map(|p| p.0).collect(),
+            longitudes: points.iter().map(|p| p.1).collect(),
+            timestamps: points.iter().map(|p| p.2).collect(),
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        }
+    }
+
+    #[test]
+    fn test_resample_by_time_interpolates() {
+        let input = trajectory(&[(0, 0, 0), (100, 0, 10), (100, 300, 40)]);
+        let resampled = resample_by_time(&input, 5).unwrap();
+
+        assert_eq!(resampled.timestamps, vec![0, 10, 20, 30, 40]);
+        assert_eq!(resampled.latitudes, vec![0, 100, 100, 100, 100]);
+        assert_eq!(resampled.longitudes, vec![0, 0, 100, 200, 300]);
+    }
+
+    #[test]
+    fn test_resample_by_time_keeps_endpoints() {
+        let input = trajectory(&[(1, 2, 100), (5, 7, 103), (9, 4, 111)]);
+        let resampled = resample_by_time(&input, 2).unwrap();
+
+        assert_eq!(resampled, trajectory(&[(1, 2, 100), (9, 4, 111)]));
+    }
+
+    #[test]
+    fn test_resample_by_distance() {
+        // Two segments along the equator, 1000 and 500 units long (about 111 m and 56 m)
+        let input = trajectory(&[(0, 0, 0), (0, 1000, 100), (0, 1500, 200)]);
+        let interval_m = haversine_m(0, 0, 0, 400);
+        let resampled = resample_by_distance(&input, interval_m);
+
+        assert_eq!(resampled.longitudes, vec![0, 400, 800, 1200, 1500]);
+        assert_eq!(resampled.latitudes, vec![0; 5]);
+        assert_eq!(resampled.timestamps, vec![0, 40, 80, 140, 200]);
+    }
+
+    #[test]
+    fn test_resample_by_distance_ends_on_a_point() {
+        let input = trajectory(&[(0, 0, 0), (0, 1000, 10)]);
+        let resampled = resample_by_distance(&input, haversine_m(0, 0, 0, 500));
+
+        assert_eq!(resampled.longitudes, vec![0, 500, 1000]);
+    }
+
+    #[test]
+    fn test_resample_by_distance_degenerate() {
+        assert_eq!(resample_by_distance(&trajectory(&[]), 10.0), trajectory(&[]));
+        assert_eq!(
+            resample_by_distance(&trajectory(&[(1, 2, 100)]), 10.0),
+            trajectory(&[(1, 2, 100)])
+        );
+        // Stationary points collapse to the first and last
+        assert_eq!(
+            resample_by_distance(&trajectory(&[(1, 2, 100), (1, 2, 110), (1, 2, 120)]), 10.0),
+            trajectory(&[(1, 2, 100), (1, 2, 120)])
+        );
+    }
+
+    #[test]
+    fn test_densify() {
+        let input = trajectory(&[(0, 0, 0), (0, 1000, 100), (0, 1100, 110)]);
+        let densified = densify(&input, haversine_m(0, 0, 0, 300));
+
+        // 1000 units are cut in 4 parts of 250, the last 100 units are left as they are
+        assert_eq!(densified.longitudes, vec![0, 250, 500, 750, 1000, 1100]);
+        assert_eq!(densified.latitudes, vec![0; 6]);
+        assert_eq!(densified.timestamps, vec![0, 25, 50, 75, 100, 110]);
+    }
+
+    #[test]
+    fn test_densify_keeps_short_segments() {
+        let input = trajectory(&[(0, 0, 0), (5, 5, 1), (5, 5, 2)]);
+        assert_eq!(densify(&input, 100.0), input);
+        assert_eq!(densify(&trajectory(&[]), 100.0), trajectory(&[]));
+    }
+
+    #[test]
+    fn test_position_at() {
+        let input = trajectory(&[(0, 0, 10), (100, 0, 20), (100, 300, 50)]);
+
+        assert_eq!(position_at(&input, 10.0), Some((0.0, 0.0)));
+        assert_eq!(position_at(&input, 15.0), Some((50.0, 0.0)));
+        assert_eq!(position_at(&input, 20.0), Some((100.0, 0.0)));
+        assert_eq!(position_at(&input, 40.0), Some((100.0, 200.0)));
+        assert_eq!(position_at(&input, 50.0), Some((100.0, 300.0)));
+        assert_eq!(position_at(&input, 9.9), None);
+        assert_eq!(position_at(&input, 50.1), None);
+        assert_eq!(position_at(&trajectory(&[]), 0.0), None);
+    }
+
+    #[test]
+    fn test_resample_by_time_degenerate() {
+        assert_eq!(resample_by_time(&trajectory(&[]), 3), Ok(trajectory(&[])));
+        assert_eq!(
+            resample_by_time(&trajectory(&[(1, 2, 100)]), 3),
+            Ok(trajectory(&[(1, 2, 100), (1, 2, 100), (1, 2, 100)]))
+        );
+        assert_eq!(
+            resample_by_time(&trajectory(&[(1, 2, 100), (3, 4, 100)]), 2),
+            Ok(trajectory(&[(1, 2, 100), (1, 2, 100)]))
+        );
+    }
+
+    #[test]
+    fn test_resample_by_time_unsorted() {
+        let input = trajectory(&[(0, 0, 100), (1, 1, 110), (2, 2, 50)]);
+        assert_eq!(
+            resample_by_time(&input, 3),
+            Err(ResampleError::UnsortedTimestamps(2))
+        );
+    }
+}
@@ -0,0 +1,253 @@
+//! Mining of the routes each user travels repeatedly, such as commutes.
+//!
+//! Trips are normalized to the places where they start and end, found by clustering the
+//! endpoints of every trip of a user, and trips between the same places are grouped when
+//! their paths match in both directions within a tolerance (see `direction::compare`).
+//! Each group is a route, named by the geohash cells its first trip goes through.
+
+use crate::direction::{self, Direction};
+use crate::geo::{haversine_m, to_degrees};
+use crate::geohash;
+use crate::trajectory::Trajectory;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+/// Precision of the geohash cells, about 1 km wide, naming the path of a route
+const SIGNATURE_PRECISION: usize = 6;
+
+/// Tolerances of the route mining.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RouteParams {
+    /// Largest distance in meters from an endpoint to the center of its place
+    pub endpoint_radius_m: f64,
+    /// Largest distance in meters between the paths of trips on the same route
+    pub path_tolerance_m: f64,
+}
+
+/// Trip of a user, such as the simplified content of one GeoLife file.
+#[derive(Debug, Clone)]
+pub struct Trip {
+    pub user: String,
+    pub name: String,
+    pub trajectory: Trajectory,
+}
+
+/// Route traveled by a user, with the trips following it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Route {
+    pub user: String,
+    /// Centers of the start and end places, as scaled `(latitude, longitude)`
+    pub start: (i64, i64),
+    pub end: (i64, i64),
+    /// Geohash cells traversed by the first trip, joined by `-`
+    pub signature: String,
+    /// Indices of the trips following the route, in input order
+    pub trips: Vec<usize>,
+}
+
+/// Groups points into places: each point joins the first place whose center, the first
+/// point that joined it, is within `radius_m` meters, or starts a new place.
+///
+/// # Returns
+///
+/// The centers of the places and the place of every point.
+fn cluster(points: &[(i64, i64)], radius_m: f64) -> (Vec<(i64, i64)>, Vec<usize>) {
+    let mut centers: Vec<(i64, i64)> = Vec::new();
+    let places = points
+        .iter()
+        .map(|&(lat, lon)| {
+            centers
+                .iter()
+                .position(|&(c_lat, c_lon)| haversine_m(lat, lon, c_lat, c_lon) <= radius_m)
+                .unwrap_or_else(|| {
+                    centers.push((lat, lon));
+                    centers.len() - 1
+                })
+        })
+        .collect();
+    (centers, places)
+}
+
+/// Names the path of a trajectory by the sequence of geohash cells it goes through.
+fn signature(trajectory: &Trajectory) -> String {
+    geohash::visits(trajectory, SIGNATURE_PRECISION)
+        .into_iter()
+        .map(|visit| visit.cell)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Finds the routes of every user, ranked by number of trips, then by user and first trip.
+/// Trips without points are ignored.
+pub fn mine_routes(trips: &[Trip], params: RouteParams) -> Vec<Route> {
+    let mut by_user: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (i, trip) in trips.iter().enumerate() {
+        if !trip.trajectory.latitudes.is_empty() {
+            by_user.entry(&trip.user).or_default().push(i);
+        }
+    }
+
+    let mut routes = Vec::new();
+    for (user, indices) in by_user {
+        let endpoint = |i: usize, last: bool| {
+            let t = &trips[i].trajectory;
+            let j = if last { t.latitudes.len() - 1 } else { 0 };
+            (t.latitudes[j], t.longitudes[j])
+        };
+        // Starts and ends are clustered together, as the end of a commute is the start of
+        // the return trip
+        let endpoints: Vec<(i64, i64)> = indices
+            .iter()
+            .flat_map(|&i| [endpoint(i, false), endpoint(i, true)])
+            .collect();
+        let (centers, places) = cluster(&endpoints, params.endpoint_radius_m);
+
+        let mut user_routes: Vec<(usize, usize, Route)> = Vec::new();
+        for (k, &i) in indices.iter().enumerate() {
+            let (start, end) = (places[2 * k], places[2 * k + 1]);
+            let trajectory = &trips[i].trajectory;
+            let existing = user_routes.iter_mut().find(|(s, e, route)| {
+                (*s, *e) == (start, end)
+                    && direction::compare(
+                        &trips[route.trips[0]].trajectory,
+                        trajectory,
+                        params.path_tolerance_m,
+                    ) == Direction::Same
+            });
+            match existing {
+                Some((_, _, route)) => route.trips.push(i),
+                None => user_routes.push((
+                    start,
+                    end,
+                    Route {
+                        user: user.to_string(),
+                        start: centers[start],
+                        end: centers[end],
+                        signature: signature(trajectory),
+                        trips: vec![i],
+                    },
+                )),
+            }
+        }
+        routes.extend(user_routes.into_iter().map(|(_, _, route)| route));
+    }
+
+    // The sort is stable, so routes with as many trips keep the order of their first trip
+    routes.sort_by_key(|route| std::cmp::Reverse(route.trips.len()));
+    routes
+}
+
+/// Builds the ranked route list as JSON, with the names of the trips of every route.
+pub fn routes_json(routes: &[Route], trips: &[Trip]) -> Value {
+    let degrees = |(lat, lon): (i64, i64)| json!([to_degrees(lat), to_degrees(lon)]);
+    let routes: Vec<Value> = routes
+        .iter()
+        .enumerate()
+        .map(|(rank, route)| {
+            let names: Vec<&str> = route
+                .trips
+                .iter()
+                .map(|&i| trips[i].name.as_str())
+                .collect();
+            json!({
+                "rank": rank + 1,
+                "user": route.user,
+                "count": route.trips.len(),
+                "start": degrees(route.start),
+                "end": degrees(route.end),
+                "signature": route.signature,
+                "trips": names,
+            })
+        })
+        .collect();
+    json!({ "routes": routes })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trajectory::CoordinateSystem;
+
+    const PARAMS: RouteParams = RouteParams {
+        endpoint_radius_m: 200.0,
+        path_tolerance_m: 100.0,
+    };
+
+    fn trip(user: &str, points: &[(i64, i64)]) -> Trip {
+        Trip {
+            user: user.to_string(),
+            name: format!("{user}/{}", points.len()),
+            trajectory: Trajectory {
+                latitudes: points.iter().map(|p| p.0).collect(),
+                longitudes: points.iter().map(|p| p.1).collect(),
+                timestamps: (0..points.len() as u64).collect(),
+                altitudes: Vec::new(),
+                coordinates: CoordinateSystem::Wgs84,
+            },
+        }
+    }
+
+    #[test]
+    fn test_cluster() {
+        // The second and fourth points are 55 m from the previous one, the last one 110 m
+        // from the first
+        let points = [(0, 0), (500, 0), (10_000, 0), (10_000, 500), (0, 1_000)];
+        let (centers, places) = cluster(&points, 100.0);
+        assert_eq!(centers, vec![(0, 0), (10_000, 0), (0, 1_000)]);
+        assert_eq!(places, vec![0, 0, 1, 1, 2]);
+    }
+
+    #[test]
+    fn test_mine_routes() {
+        let home = (39_900_000, 116_400_000);
+        let work = (39_920_000, 116_420_000);
+        // Along the diagonal, or via the north-west corner
+        let direct = [home, (39_910_000, 116_410_000), work];
+        let detour = [home, (39_920_000, 116_400_000), work];
+        let back = [work, (39_910_000, 116_410_000), home];
+        let shifted = [(39_900_500, 116_400_000), (39_910_000, 116_410_000), work];
+
+        let trips = vec![
+            trip("a", &direct),
+            trip("a", &detour),
+            trip("a", &shifted),
+            trip("a", &back),
+            trip("b", &direct),
+            trip("a", &direct),
+        ];
+        let routes = mine_routes(&trips, PARAMS);
+
+        let summary: Vec<(&str, Vec<usize>)> = routes
+            .iter()
+            .map(|route| (route.user.as_str(), route.trips.clone()))
+            .collect();
+        // The shifted start is 55 m from home, within both tolerances
+        assert_eq!(
+            summary,
+            vec![
+                ("a", vec![0, 2, 5]),
+                ("a", vec![1]),
+                ("a", vec![3]),
+                ("b", vec![4])
+            ]
+        );
+        assert_eq!((routes[0].start, routes[0].end), (home, work));
+        assert_eq!((routes[2].start, routes[2].end), (work, home));
+        assert_eq!(routes[0].signature, signature(&trips[0].trajectory));
+        let cells: Vec<&str> = routes[0].signature.split('-').collect();
+        assert_eq!(cells.len(), 3);
+        assert_eq!(cells[0], geohash::encode(home.0, home.1, SIGNATURE_PRECISION));
+
+        let json = routes_json(&routes, &trips);
+        assert_eq!(json["routes"][0]["rank"], 1);
+        assert_eq!(json["routes"][0]["count"], 3);
+        assert_eq!(json["routes"][0]["trips"], json!(["a/3", "a/3", "a/3"]));
+        assert_eq!(json["routes"][3]["user"], "b");
+    }
+
+    #[test]
+    fn test_mine_routes_empty() {
+        let trips = vec![trip("a", &[])];
+        assert!(mine_routes(&trips, PARAMS).is_empty());
+    }
+}
@@ -0,0 +1,192 @@
+//! Packed R-tree over the segments of a polyline, for queries by bounding box.
+//!
+//! The tree is built once with the Sort-Tile-Recursive algorithm: segments are sorted into
+//! vertical slices by the center of their box, then by `y` within each slice, and grouped
+//! into nodes of `NODE_CAPACITY` entries, level by level up to a single root.
+
+/// Number of children of every node but the last of each level
+const NODE_CAPACITY: usize = 16;
+
+/// Axis-aligned rectangle in projected coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub min_x: f64,
+    pub min_y: f64,
+    pub max_x: f64,
+    pub max_y: f64,
+}
+
+impl Rect {
+    /// Smallest rectangle containing the segment between `a` and `b`.
+    pub fn of_segment(a: (f64, f64), b: (f64, f64)) -> Self {
+        Rect {
+            min_x: a.0.min(b.0),
+            min_y: a.1.min(b.1),
+            max_x: a.0.max(b.0),
+            max_y: a.1.max(b.1),
+        }
+    }
+
+    /// Smallest rectangle containing both.
+    pub fn union(self, other: Rect) -> Rect {
+        Rect {
+            min_x: self.min_x.min(other.min_x),
+            min_y: self.min_y.min(other.min_y),
+            max_x: self.max_x.max(other.max_x),
+            max_y: self.max_y.max(other.max_y),
+        }
+    }
+
+    /// Tells whether the rectangles share at least one point, edges included.
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.min_x <= other.max_x
+            && other.min_x <= self.max_x
+            && self.min_y <= other.max_y
+            && other.min_y <= self.max_y
+    }
+
+    fn center(&self) -> (f64, f64) {
+        (
+            (self.min_x + self.max_x) / 2.0,
+            (self.min_y + self.max_y) / 2.0,
+        )
+    }
+}
+
+/// R-tree of the segments between consecutive points of a polyline, segment `i` joining
+/// points `i` and `i + 1`.
+#[derive(Debug, Clone)]
+pub struct SegmentTree {
+    /// Boxes of every level, from the segments up to the root. Entry `i` of a level covers
+    /// the entries `NODE_CAPACITY * i ..` of the level below; leaves are sorted segments.
+    levels: Vec<Vec<Rect>>,
+    /// Segment of every leaf
+    segments: Vec<usize>,
+}
+
+/// Sorts the entries in place by Sort-Tile-Recursive order.
+fn sort_tile(entries: &mut [(Rect, usize)]) {
+    let nodes = entries.len().div_ceil(NODE_CAPACITY);
+    let slices = (nodes as f64).sqrt().ceil() as usize;
+    let slice_len = slices.max(1) * NODE_CAPACITY;
+
+    entries.sort_by(|a, b| a.0.center().0.total_cmp(&b.0.center().0));
+    for slice in entries.chunks_mut(slice_len) {
+        slice.sort_by(|a, b| a.0.center().1.total_cmp(&b.0.center().1));
+    }
+}
+
+impl SegmentTree {
+    /// Builds the tree of the segments of a polyline. A polyline with less than two points
+    /// has no segments.
+    pub fn new(points: &[(f64, f64)]) -> Self {
+        let mut leaves: Vec<(Rect, usize)> = points
+            .windows(2)
+            .enumerate()
+            .map(|(i, pair)| (Rect::of_segment(pair[0], pair[1]), i))
+            .collect();
+        sort_tile(&mut leaves);
+
+        let (boxes, segments) = leaves.into_iter().unzip();
+        let mut levels: Vec<Vec<Rect>> = vec![boxes];
+        while levels.last().is_some_and(|level| level.len() > 1) {
+            let parents = levels
+                .last()
+                .unwrap()
+                .chunks(NODE_CAPACITY)
+                .map(|children| {
+                    children
+                        .iter()
+                        .copied()
+                        .reduce(Rect::union)
+                        .expect("chunks are not empty")
+                })
+                .collect();
+            levels.push(parents);
+        }
+
+        SegmentTree { levels, segments }
+    }
+
+    /// Lists the segments whose bounding box intersects `rect`, in no particular order.
+    pub fn query(&self, rect: &Rect) -> Vec<usize> {
+        let mut found = Vec::new();
+        let Some(top) = self.levels.len().checked_sub(1) else {
+            return found;
+        };
+
+        // Pending nodes as (level, index)
+        let mut stack: Vec<(usize, usize)> =
+            (0..self.levels[top].len()).map(|i| (top, i)).collect();
+        while let Some((level, index)) = stack.pop() {
+            if !self.levels[level][index].intersects(rect) {
+                continue;
+            }
+            if level == 0 {
+                found.push(self.segments[index]);
+            } else {
+                let first = index * NODE_CAPACITY;
+                let last = (first + NODE_CAPACITY).min(self.levels[level - 1].len());
+                stack.extend((first..last).map(|child| (level - 1, child)));
+            }
+        }
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_query() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+        let points: Vec<(f64, f64)> = (0..1_000)
+            .map(|_| (rng.gen_range(0.0..1_000.0), rng.gen_range(0.0..1_000.0)))
+            .collect();
+        let tree = SegmentTree::new(&points);
+        assert_eq!(tree.levels.len(), 4);
+
+        // Same segments as checking every box
+        for _ in 0..100 {
+            let (x, y) = (rng.gen_range(0.0..1_000.0), rng.gen_range(0.0..1_000.0));
+            let rect = Rect {
+                min_x: x,
+                min_y: y,
+                max_x: x + 50.0,
+                max_y: y + 20.0,
+            };
+            let mut found = tree.query(&rect);
+            found.sort_unstable();
+            let expected: Vec<usize> = (0..points.len() - 1)
+                .filter(|&i| Rect::of_segment(points[i], points[i + 1]).intersects(&rect))
+                .collect();
+            assert_eq!(found, expected);
+        }
+    }
+
+    #[test]
+    fn test_query_small() {
+        let everywhere = Rect {
+            min_x: f64::MIN,
+            min_y: f64::MIN,
+            max_x: f64::MAX,
+            max_y: f64::MAX,
+        };
+        assert!(SegmentTree::new(&[]).query(&everywhere).is_empty());
+        assert!(SegmentTree::new(&[(1.0, 1.0)])
+            .query(&everywhere)
+            .is_empty());
+
+        let tree = SegmentTree::new(&[(0.0, 0.0), (1.0, 1.0)]);
+        assert_eq!(tree.query(&everywhere), vec![0]);
+        let touching = Rect {
+            min_x: 1.0,
+            min_y: 1.0,
+            max_x: 2.0,
+            max_y: 2.0,
+        };
+        assert_eq!(tree.query(&touching), vec![0]);
+    }
+}
@@ -0,0 +1,763 @@
+//! The default run of the command line: reads a dataset, simplifies each of its files on
+//! its own or all of them merged into one trajectory, writes the outputs requested and
+//! prints statistics about the dataset, the simplification and the size of its
+//! serializations.
+
+use crate::bench::LOCALE;
+use crate::config::Config;
+use crate::datum::{self, Datum};
+use crate::diagnostics::{DiagnosticParams, Diagnostics};
+use crate::labels::ModeLabel;
+use crate::metrics::{self, Metrics};
+use crate::noise::{self, NoiseParams};
+use crate::simplify::{self, Constraint, KeepReason, LoopAnchors, Shape};
+use crate::smoothing::{self, OneEuroParams};
+use crate::trajectory::{TimestampEncoding, Trajectory};
+use crate::{crypto, dataset, geo, geojson, geom, proto, quality, recommend, segment, AppError};
+use num_format::ToFormattedString;
+use prost::Message;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Writes an output file, encrypted if a key is set in the environment (see `crypto`).
+pub fn write_output(path: impl AsRef<Path>, bytes: Vec<u8>) -> Result<(), AppError> {
+    let bytes = match crypto::key_from_env()? {
+        Some(key) => crypto::encrypt(&key, &bytes),
+        None => bytes,
+    };
+    fs::write(path, bytes)?;
+    Ok(())
+}
+
+/// Runs a command with fresh metrics, recording its parse errors, then pushes the metrics
+/// to the Pushgateway at `push_to` if given, even if the command failed, for the parse
+/// errors to be seen.
+pub fn with_metrics(
+    push_to: Option<&str>,
+    command: impl FnOnce(&Metrics) -> Result<(), AppError>,
+) -> Result<(), AppError> {
+    let metrics = Metrics::default();
+    let result = command(&metrics);
+    match &result {
+        Err(AppError::Parse(_)) => metrics.record_parse_error(),
+        Err(AppError::Files(errors)) => {
+            for (_, error) in &errors.0 {
+                if let AppError::Parse(_) = error {
+                    metrics.record_parse_error();
+                }
+            }
+        }
+        _ => {}
+    }
+    if let Some(address) = push_to {
+        metrics::push(address, "trajectory-rs", &metrics)?;
+    }
+    result
+}
+
+/// Options of the default run.
+pub struct RunOptions {
+    /// Directory of the track files
+    pub input: PathBuf,
+    /// Tolerance, unless the configuration sets one
+    pub epsilon: i64,
+    /// File the delta-encoded message is written to
+    pub output: Option<PathBuf>,
+    /// Tolerances per transportation mode or speed band
+    pub config: Option<Config>,
+    /// Whether to remove GPS jitter with a One-Euro filter before simplification
+    pub smooth: bool,
+    /// Points loops are anchored at
+    pub loop_anchors: LoopAnchors,
+    /// File the reason each kept point is kept for is written to
+    pub explain: Option<PathBuf>,
+    /// Ratio of points to keep, searching the tolerance instead of using `epsilon`
+    pub target_ratio: Option<f64>,
+    /// File the simplified trajectories are written to as GeoJSON
+    pub geojson: Option<PathBuf>,
+    /// Whether to report the output sizes without encoding or writing the outputs
+    pub dry_run: bool,
+    /// Whether to merge every file into one trajectory instead of keeping each on its own
+    pub merge: bool,
+    /// Longest time between two points of a trip, splitting files into trips when set
+    pub max_gap_s: Option<u64>,
+    /// Longest distance between two points of a trip, splitting files into trips when set
+    pub max_jump_m: Option<f64>,
+    /// Datum the points are recorded in, converted to WGS 84 after reading
+    pub datum: Datum,
+}
+
+/// Simplifies the trajectory with the tolerances of the configuration, then keeps its
+/// minimum number of points and its characteristic points. Returns the points to keep and
+/// the largest tolerance used.
+///
+/// When `reasons` is given, it is filled with the reason each point is kept for.
+pub fn simplify_with_config(
+    trajectory: &Trajectory,
+    config: &Config,
+    labels: &[ModeLabel],
+    epsilon: i64,
+    mut reasons: Option<&mut Vec<Option<KeepReason>>>,
+) -> (Vec<bool>, i64) {
+    let epsilons = config.point_epsilons(trajectory, labels, epsilon);
+    let max_epsilon = epsilons.iter().copied().max().unwrap_or(epsilon);
+    let mut keep_points = match reasons.as_deref_mut() {
+        Some(reasons) => {
+            *reasons = simplify::explain_piecewise(
+                &trajectory.latitudes,
+                &trajectory.longitudes,
+                &epsilons,
+            );
+            reasons.iter().map(Option::is_some).collect()
+        }
+        None => {
+            simplify::simplify_piecewise(&trajectory.latitudes, &trajectory.longitudes, &epsilons)
+        }
+    };
+    let mut record = |keep_points: &[bool], constraint| {
+        if let Some(reasons) = reasons.as_deref_mut() {
+            simplify::record_constraint(reasons, keep_points, constraint);
+        }
+    };
+    if let Some(min_points) = config.min_points {
+        simplify::ensure_min_points(
+            &trajectory.latitudes,
+            &trajectory.longitudes,
+            &mut keep_points,
+            min_points,
+        );
+        record(&keep_points, Constraint::MinPoints);
+    }
+    if config.keep_extrema {
+        simplify::keep_extrema(
+            &trajectory.latitudes,
+            &trajectory.longitudes,
+            &mut keep_points,
+        );
+        record(&keep_points, Constraint::Extrema);
+    }
+    simplify::keep_sharpest_turns(
+        &trajectory.latitudes,
+        &trajectory.longitudes,
+        &mut keep_points,
+        config.sharpest_turns,
+    );
+    record(&keep_points, Constraint::SharpestTurn);
+    (keep_points, max_epsilon)
+}
+
+/// Builds the JSON report of `--explain`: the mask of the points kept, and the reason each
+/// kept point is kept for, with the depth of the recursion for splits.
+pub fn keep_reasons_json(reasons: &[Option<KeepReason>]) -> serde_json::Value {
+    let keep: Vec<bool> = reasons.iter().map(Option::is_some).collect();
+    let points: Vec<serde_json::Value> = reasons
+        .iter()
+        .enumerate()
+        .filter_map(|(index, reason)| {
+            let reason = (*reason)?;
+            let mut point = serde_json::json!({ "index": index, "reason": reason.name() });
+            if let KeepReason::Split { depth } = reason {
+                point["depth"] = depth.into();
+            }
+            Some(point)
+        })
+        .collect();
+    serde_json::json!({ "keep": keep, "points": points })
+}
+
+/// Applies `f` to every item, in parallel with the `rayon` feature.
+fn map_parallel<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync + Send) -> Vec<R> {
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        items.par_iter().map(f).collect()
+    }
+    #[cfg(not(feature = "rayon"))]
+    items.iter().map(f).collect()
+}
+
+/// Simplifies one trajectory of the default run, with the tolerances of `config` if
+/// given, or with `epsilon` and loops anchored as given by `loop_anchors`.
+///
+/// Returns the points to keep, the largest tolerance used, and with `explain`, the reason
+/// each point is kept for.
+pub fn simplify_trajectory(
+    trajectory: &Trajectory,
+    config: Option<&Config>,
+    labels: &[ModeLabel],
+    epsilon: i64,
+    loop_anchors: LoopAnchors,
+    explain: bool,
+) -> (Vec<bool>, i64, Option<Vec<Option<KeepReason>>>) {
+    let mut reasons = explain.then(Vec::new);
+    let (keep_points, epsilon) = match config {
+        Some(config) => simplify_with_config(trajectory, config, labels, epsilon, reasons.as_mut()),
+        None => {
+            let keep_points = match reasons.as_mut() {
+                Some(reasons) => {
+                    *reasons = simplify::explain_anchored(
+                        &trajectory.latitudes,
+                        &trajectory.longitudes,
+                        epsilon,
+                        loop_anchors,
+                    );
+                    reasons.iter().map(Option::is_some).collect()
+                }
+                None => simplify::simplify_anchored(
+                    &trajectory.latitudes,
+                    &trajectory.longitudes,
+                    epsilon,
+                    loop_anchors,
+                ),
+            };
+            (keep_points, epsilon)
+        }
+    };
+    (keep_points, epsilon, reasons)
+}
+
+/// Reads, simplifies and serializes the dataset, printing statistics along the way.
+///
+/// Each file is simplified as its own trajectory, or, with `max_gap_s` or `max_jump_m`,
+/// each trip of a file (see `segment::split_by_gap`). With `merge`, every file is merged
+/// into one trajectory sorted by timestamp instead.
+///
+/// When `smooth` is set, GPS jitter is removed with a One-Euro filter before simplification.
+/// When a configuration is given, the tolerance of each point follows its transportation
+/// mode label or speed band. Otherwise, loops are anchored as given by `loop_anchors`, and
+/// with a `target_ratio`, the tolerance of each trajectory is searched to keep that ratio
+/// of its points.
+///
+/// With `dry_run`, the sizes of the messages are computed without encoding them, and no
+/// file is written, for exploring parameters on large datasets.
+pub fn run(options: RunOptions, metrics: &Metrics) -> Result<(), AppError> {
+    let RunOptions {
+        input,
+        epsilon,
+        output: output_path,
+        config,
+        smooth,
+        loop_anchors,
+        explain,
+        target_ratio,
+        geojson,
+        dry_run,
+        merge,
+        max_gap_s,
+        max_jump_m,
+        datum,
+    } = options;
+    let start = Instant::now();
+    let files = dataset::find_track_files(&input)?;
+    let mut diagnostics = Diagnostics::new(DiagnosticParams::default());
+    let (collection, total_size) = dataset::load_collection_diagnosed(&files, &mut diagnostics)?;
+    let total_points = collection.points();
+    let duration = start.elapsed();
+
+    println!(
+        "Read {} points in {} files in {duration:?}",
+        total_points.to_formatted_string(&LOCALE),
+        collection.len().to_formatted_string(&LOCALE),
+    );
+    // The dataset is checked and measured as a whole
+    let merged = collection.merged();
+    for warning in diagnostics.finish(&merged) {
+        println!("Warning: {warning}");
+    }
+    if datum != Datum::Wgs84 {
+        println!("Converting points from {} to WGS 84", datum.name());
+    }
+    let merged = datum::transform(merged, datum, Datum::Wgs84);
+    if let Some(extent) = geom::extent(&merged) {
+        println!(
+            "Extent: {:.0} m across, within {:.0} m of its center",
+            extent.diameter_m, extent.radius_m
+        );
+    }
+    // Estimated before smoothing, which would hide the noise
+    let noise = noise::estimate(&merged, NoiseParams::default());
+    if let Some(sigma_m) = noise.sigma_m() {
+        println!("Noise: {sigma_m:.1} m per axis");
+    }
+    if total_points == 0 {
+        println!("No points to simplify");
+        return Ok(());
+    }
+
+    let files = collection.len();
+    let trajectories: Vec<Trajectory> = if merge {
+        vec![merged]
+    } else {
+        let trajectories = collection
+            .trajectories
+            .into_iter()
+            .map(|trajectory| datum::transform(trajectory, datum, Datum::Wgs84));
+        if max_gap_s.is_some() || max_jump_m.is_some() {
+            let (max_gap_s, max_jump_m) = (
+                max_gap_s.unwrap_or(u64::MAX),
+                max_jump_m.unwrap_or(f64::INFINITY),
+            );
+            let trips: Vec<Trajectory> = trajectories
+                .flat_map(|trajectory| segment::split_by_gap(&trajectory, max_gap_s, max_jump_m))
+                .collect();
+            println!(
+                "Split {} files into {} trips",
+                files.to_formatted_string(&LOCALE),
+                trips.len().to_formatted_string(&LOCALE)
+            );
+            trips
+        } else {
+            trajectories.collect()
+        }
+    };
+
+    let degenerate = trajectories
+        .iter()
+        .filter(|trajectory| {
+            matches!(
+                simplify::classify(&trajectory.latitudes, &trajectory.longitudes),
+                Shape::SinglePoint | Shape::Stationary
+            )
+        })
+        .count();
+    if degenerate > 0 {
+        println!(
+            "Warning: {degenerate} of {} trajectories are degenerate (a single point or \
+             stationary), nothing to simplify",
+            trajectories.len()
+        );
+    }
+
+    let trajectories = if smooth {
+        let start = Instant::now();
+        let trajectories = map_parallel(&trajectories, |trajectory| {
+            smoothing::smooth_one_euro(trajectory, OneEuroParams::default())
+        });
+        println!("Smoothed points in {:?}", start.elapsed());
+        trajectories
+    } else {
+        trajectories
+    };
+
+    let epsilons = match target_ratio {
+        Some(ratio) => {
+            let start = Instant::now();
+            let epsilons = map_parallel(&trajectories, |trajectory| {
+                simplify::find_epsilon_for_ratio(trajectory, ratio)
+            });
+            let min = epsilons.iter().copied().min().unwrap_or_default();
+            let max = epsilons.iter().copied().max().unwrap_or_default();
+            let range = if min == max {
+                min.to_string()
+            } else {
+                format!("{min} to {max}")
+            };
+            println!(
+                "Epsilon for a ratio of {ratio}: {range} (found in {:?})",
+                start.elapsed()
+            );
+            epsilons
+        }
+        None => vec![epsilon; trajectories.len()],
+    };
+
+    let output = config
+        .as_ref()
+        .map(|config| config.output)
+        .unwrap_or_default();
+    let labels = match config {
+        Some(_) => dataset::load_labels(&input)?,
+        None => Vec::new(),
+    };
+
+    // Simplify the points using Douglas-Peucker algorithm
+    let start = Instant::now();
+    let jobs: Vec<(&Trajectory, i64)> = trajectories.iter().zip(epsilons).collect();
+    let simplifications = map_parallel(&jobs, |&(trajectory, epsilon)| {
+        simplify_trajectory(
+            trajectory,
+            config.as_ref(),
+            &labels,
+            epsilon,
+            loop_anchors,
+            explain.is_some(),
+        )
+    });
+    let duration = start.elapsed();
+
+    let mut explanations = Vec::new();
+    let mut reconstructions = Vec::with_capacity(trajectories.len());
+    let mut simplified = Vec::with_capacity(trajectories.len());
+    for (trajectory, (keep_points, epsilon, reasons)) in
+        trajectories.into_iter().zip(simplifications)
+    {
+        if let Some(reasons) = reasons {
+            explanations.push(keep_reasons_json(&reasons));
+        }
+        let reconstruction = quality::Reconstruction::measure(
+            &trajectory,
+            &keep_points,
+            epsilon as f64 * geo::METERS_PER_UNIT,
+        );
+        reconstructions.push((reconstruction, trajectory.latitudes.len()));
+        simplified.push(trajectory.filter(&keep_points));
+    }
+    let simplified_points: usize = simplified
+        .iter()
+        .map(|trajectory| trajectory.latitudes.len())
+        .sum();
+    metrics.record_simplification(total_points, simplified_points, duration);
+
+    println!(
+        "Simplified to {} points in {duration:?}",
+        simplified_points.to_formatted_string(&LOCALE),
+        duration = duration
+    );
+
+    if let Some(path) = &explain {
+        if dry_run {
+            println!(
+                "Dry run: not writing the reasons of the kept points to {}",
+                path.display()
+            );
+        } else {
+            let value = serde_json::json!({ "trajectories": explanations });
+            fs::write(path, value.to_string())?;
+            println!("Wrote the reasons of the kept points to {}", path.display());
+        }
+    }
+    if let Some(path) = &geojson {
+        if dry_run {
+            println!(
+                "Dry run: not writing the simplified trajectories to {}",
+                path.display()
+            );
+        } else {
+            let value = if merge {
+                simplified[0].to_geojson()
+            } else {
+                geojson::collection_to_geojson(&simplified)
+            };
+            fs::write(path, value.to_string())?;
+            println!("Wrote the simplified trajectories to {}", path.display());
+        }
+    }
+
+    let mut delta_messages = Vec::with_capacity(simplified.len());
+    let mut absolute_messages = Vec::with_capacity(simplified.len());
+    for (trajectory, (reconstruction, _)) in simplified.into_iter().zip(&reconstructions) {
+        // Clone the trajectory since we need to use it twice
+        let mut delta_message = trajectory
+            .clone()
+            .to_proto_with(true, output.timestamp_format(TimestampEncoding::Delta))?;
+        delta_message.reconstruction = Some(reconstruction.to_proto());
+        let mut protobuf_value = trajectory
+            .to_proto_with(false, output.timestamp_format(TimestampEncoding::Absolute))?;
+        protobuf_value.reconstruction = Some(reconstruction.to_proto());
+        delta_messages.push(delta_message);
+        absolute_messages.push(protobuf_value);
+    }
+    // Each trajectory is delta-encoded from zero, so that any of them can be decoded
+    // without the others. Merged, the trajectory is written as a message of its own.
+    let delta_message = proto::TrajectoryCollection {
+        trajectories: delta_messages,
+    };
+    let protobuf_value = proto::TrajectoryCollection {
+        trajectories: absolute_messages,
+    };
+    let encoded_len = |message: &proto::TrajectoryCollection| {
+        if merge {
+            message.trajectories[0].encoded_len()
+        } else {
+            message.encoded_len()
+        }
+    };
+    // Sizes are computed from the messages, which are only encoded to be written
+    let serialized_len = encoded_len(&protobuf_value);
+    let serialized_delta_len = encoded_len(&delta_message);
+
+    println!();
+
+    println!(
+        "Original size:        {:>21} bytes",
+        total_size.to_formatted_string(&LOCALE)
+    );
+
+    println!(
+        "Size after simplification: {:>16} bytes",
+        serialized_len.to_formatted_string(&LOCALE)
+    );
+
+    println!(
+        "Serialized DELTA size: {:>20} bytes",
+        serialized_delta_len.to_formatted_string(&LOCALE)
+    );
+    println!(
+        "Total points: {:>29} points",
+        total_points.to_formatted_string(&LOCALE)
+    );
+    println!(
+        "simplified points: {:>24} points",
+        simplified_points.to_formatted_string(&LOCALE)
+    );
+
+    println!(
+        "Ratio points: {:>29.2} %",
+        (simplified_points as f64 / total_points as f64) * 100.0
+    );
+
+    println!(
+        "Ratio bytes delta vs non-delta: {:>11.2} %",
+        (serialized_delta_len as f64 / serialized_len as f64) * 100.0
+    );
+
+    println!(
+        "Ratio bytes delta vs original: {:>12.2} %",
+        (serialized_delta_len as f64 / total_size as f64) * 100.0
+    );
+
+    // The errors of every trajectory, the mean one weighted by their points
+    let (epsilon_m, max_error_m, mean_error_m) = reconstructions.iter().fold(
+        (0.0, 0.0, 0.0),
+        |(epsilon_m, max_error_m, mean_error_m), (reconstruction, points)| {
+            (
+                f64::max(epsilon_m, reconstruction.epsilon_m),
+                f64::max(max_error_m, reconstruction.max_error_m),
+                mean_error_m + reconstruction.mean_error_m * *points as f64 / total_points as f64,
+            )
+        },
+    );
+    // GeoLife files do not report the accuracy of their fixes, so the one of the estimated
+    // noise stands for it
+    let accuracies = vec![noise.accuracy_m(); total_points];
+    println!();
+    println!("Epsilon: {epsilon_m:>34.1} m");
+    println!("Reconstruction error: {max_error_m:>21.1} m max, {mean_error_m:.1} m mean");
+    for recommendation in recommend::recommend(&accuracies) {
+        println!(
+            "Recommended for {:<10} {:>9} points, accuracy {:>5.1} m: epsilon {:>5.1} m",
+            format!("{}:", recommendation.class),
+            recommendation.points.to_formatted_string(&LOCALE),
+            recommendation.median_accuracy_m,
+            recommendation.epsilon_m
+        );
+    }
+
+    if let Some(path) = output_path {
+        println!();
+        let what = if merge { "message" } else { "collection" };
+        if dry_run {
+            println!(
+                "Dry run: not writing the delta-encoded {what} to {}",
+                path.display()
+            );
+        } else {
+            let bytes = if merge {
+                delta_message.trajectories[0].encode_to_vec()
+            } else {
+                delta_message.encode_to_vec()
+            };
+            write_output(&path, bytes)?;
+            println!("Wrote the delta-encoded {what} to {}", path.display());
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collection::TrajectoryCollection;
+    use crate::trajectory::CoordinateSystem;
+
+    fn fixtures() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/geolife")
+    }
+
+    fn options(input: PathBuf) -> RunOptions {
+        RunOptions {
+            input,
+            epsilon: 1000,
+            output: None,
+            config: None,
+            smooth: false,
+            loop_anchors: LoopAnchors::Endpoints,
+            explain: None,
+            target_ratio: None,
+            geojson: None,
+            dry_run: false,
+            merge: false,
+            max_gap_s: None,
+            max_jump_m: None,
+            datum: Datum::Wgs84,
+        }
+    }
+
+    /// Zigzag of `len` points 100 units apart, with a turn at every point.
+    fn zigzag(len: usize) -> Trajectory {
+        Trajectory {
+            latitudes: (0..len).map(|i| i as i64 * 100).collect(),
+            longitudes: (0..len).map(|i| (i % 2) as i64 * 10).collect(),
+            timestamps: (0..len as u64).collect(),
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        }
+    }
+
+    #[test]
+    fn test_simplify_with_config() {
+        let trajectory = zigzag(20);
+        let config = Config::parse("epsilon_m = 100\nmin_points = 5\nsharpest_turns = 1").unwrap();
+        let mut reasons = Vec::new();
+        let (keep, epsilon) =
+            simplify_with_config(&trajectory, &config, &[], 1000, Some(&mut reasons));
+        assert_eq!(epsilon, geo::meters_to_units(100.0));
+        assert!(keep.iter().filter(|&&kept| kept).count() >= 5);
+        assert_eq!(
+            reasons.iter().map(Option::is_some).collect::<Vec<_>>(),
+            keep
+        );
+        assert!(reasons.contains(&Some(KeepReason::Constraint(Constraint::MinPoints))));
+
+        let (without_reasons, _) = simplify_with_config(&trajectory, &config, &[], 1000, None);
+        assert_eq!(without_reasons, keep);
+    }
+
+    #[test]
+    fn test_keep_reasons_json() {
+        let reasons = [
+            Some(KeepReason::Endpoint),
+            None,
+            Some(KeepReason::Split { depth: 2 }),
+            Some(KeepReason::Endpoint),
+        ];
+        assert_eq!(
+            keep_reasons_json(&reasons),
+            serde_json::json!({
+                "keep": [true, false, true, true],
+                "points": [
+                    { "index": 0, "reason": "endpoint" },
+                    { "index": 2, "reason": "split", "depth": 2 },
+                    { "index": 3, "reason": "endpoint" },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn test_simplify_trajectory() {
+        let trajectory = zigzag(10);
+        let (keep, epsilon, reasons) =
+            simplify_trajectory(&trajectory, None, &[], 1000, LoopAnchors::Endpoints, false);
+        assert_eq!(keep.iter().filter(|&&kept| kept).count(), 2);
+        assert_eq!((epsilon, reasons), (1000, None));
+
+        let (explained, _, reasons) =
+            simplify_trajectory(&trajectory, None, &[], 1000, LoopAnchors::Endpoints, true);
+        assert_eq!(explained, keep);
+        assert_eq!(reasons.unwrap()[0], Some(KeepReason::Endpoint));
+    }
+
+    #[test]
+    fn test_with_metrics() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let gateway = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = String::new();
+            while !request.ends_with("trajectory_compression_ratio_count 1\n") {
+                let mut buffer = [0; 4096];
+                let len = std::io::Read::read(&mut stream, &mut buffer).unwrap();
+                request.push_str(std::str::from_utf8(&buffer[..len]).unwrap());
+            }
+            std::io::Write::write_all(&mut stream, b"HTTP/1.1 200 OK\r\n\r\n").unwrap();
+            request
+        });
+
+        // Pushed although the command failed, with its parse error
+        let result = with_metrics(Some(&address), |metrics| {
+            metrics.simplify(&zigzag(10), 1000);
+            Err(crate::point::ParseError::InvalidFieldCount.into())
+        });
+        assert!(matches!(result, Err(AppError::Parse(_))));
+        let request = gateway.join().unwrap();
+        assert!(request.contains("trajectory_points_processed_total 10\n"));
+        assert!(request.contains("trajectory_points_kept_total 2\n"));
+        assert!(request.contains("trajectory_parse_errors_total 1\n"));
+
+        assert!(with_metrics(None, |_| Ok(())).is_ok());
+    }
+
+    #[test]
+    fn test_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let (output, geojson, explain) = (
+            dir.path().join("out.pb"),
+            dir.path().join("out.geojson"),
+            dir.path().join("reasons.json"),
+        );
+        let metrics = Metrics::default();
+        run(
+            RunOptions {
+                output: Some(output.clone()),
+                geojson: Some(geojson.clone()),
+                explain: Some(explain.clone()),
+                target_ratio: Some(0.1),
+                ..options(fixtures())
+            },
+            &metrics,
+        )
+        .unwrap();
+
+        let message = proto::TrajectoryCollection::decode(fs::read(&output).unwrap().as_slice());
+        let collection = TrajectoryCollection::from_delta_proto(message.unwrap()).unwrap();
+        assert_eq!(collection.len(), 3);
+        // The files keep about a tenth of their 1,200 points
+        let kept: usize = collection
+            .trajectories
+            .iter()
+            .map(|trajectory| trajectory.latitudes.len())
+            .sum();
+        assert!((108..=132).contains(&kept), "kept {kept}");
+        let value: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&explain).unwrap()).unwrap();
+        assert_eq!(value["trajectories"].as_array().unwrap().len(), 3);
+        let value: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&geojson).unwrap()).unwrap();
+        assert_eq!(value["geometry"]["type"], "MultiLineString");
+        assert!(metrics.render().contains("points_kept"));
+
+        // Merged, the message is a single trajectory
+        run(
+            RunOptions {
+                output: Some(output.clone()),
+                merge: true,
+                ..options(fixtures())
+            },
+            &metrics,
+        )
+        .unwrap();
+        let message = proto::Trajectory::decode(fs::read(&output).unwrap().as_slice()).unwrap();
+        let merged = Trajectory::from_delta_proto(message).unwrap();
+        assert!(merged.timestamps.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    #[test]
+    fn test_run_dry_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("out.pb");
+        let dry_run = RunOptions {
+            output: Some(output.clone()),
+            dry_run: true,
+            ..options(fixtures())
+        };
+        run(dry_run, &Metrics::default()).unwrap();
+        assert!(!output.exists());
+
+        // An empty directory has nothing to simplify
+        let empty = tempfile::tempdir().unwrap();
+        run(options(empty.path().to_path_buf()), &Metrics::default()).unwrap();
+    }
+}
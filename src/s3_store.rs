@@ -0,0 +1,346 @@
+//! `TrajectoryStore` backend keeping trajectories as objects of an S3 bucket
+//! (`s3://<bucket>/<prefix>` locations), laid out as the files of the filesystem backend:
+//! `<prefix>/<id>.trjb`, `<prefix>/<id>.json` and `<prefix>/<id>.thumb`.
+//!
+//! Credentials, region and endpoint come from the usual AWS configuration: environment
+//! variables (`AWS_ACCESS_KEY_ID`, `AWS_REGION`, `AWS_ENDPOINT_URL`, ...), profiles or
+//! instance roles. Requests are sent on a runtime of the store, one at a time.
+
+use crate::binary;
+use crate::store::{validate_id, StoreError, TrajectoryStore};
+use crate::thumbnail::{self, MAX_POINTS};
+use crate::trajectory::Trajectory;
+use aws_config::BehaviorVersion;
+use aws_sdk_s3::error::DisplayErrorContext;
+use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::Client;
+use serde_json::Value;
+use tokio::runtime::Runtime;
+
+const EXTENSION: &str = "trjb";
+const METADATA_EXTENSION: &str = "json";
+const THUMBNAIL_EXTENSION: &str = "thumb";
+
+/// Store keeping one object per trajectory under a prefix of an S3 bucket.
+#[derive(Debug)]
+pub struct S3Store {
+    client: Client,
+    bucket: String,
+    /// Empty, or ending with `/`
+    prefix: String,
+    runtime: Runtime,
+}
+
+fn s3_error(error: impl std::error::Error) -> StoreError {
+    StoreError::S3(DisplayErrorContext(error).to_string())
+}
+
+/// Splits `<bucket>/<prefix>` into the bucket and the prefix keys start with.
+fn parse_location(location: &str) -> Result<(String, String), StoreError> {
+    let (bucket, prefix) = location.split_once('/').unwrap_or((location, ""));
+    if bucket.is_empty() {
+        return Err(StoreError::S3(format!("No bucket in s3://{location}")));
+    }
+    let prefix = prefix.trim_matches('/');
+    let prefix = if prefix.is_empty() {
+        String::new()
+    } else {
+        format!("{prefix}/")
+    };
+    Ok((bucket.to_string(), prefix))
+}
+
+impl S3Store {
+    /// Opens the store at `<bucket>/<prefix>` with the AWS configuration of the
+    /// environment.
+    pub fn open(location: &str) -> Result<Self, StoreError> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        let config = runtime.block_on(aws_config::load_defaults(BehaviorVersion::latest()));
+        Self::with_client(Client::new(&config), location, runtime)
+    }
+
+    /// Opens the store at `<bucket>/<prefix>` with a client configured by the caller, e.g.
+    /// for an S3-compatible service needing path-style addressing.
+    pub fn with_config(config: aws_sdk_s3::Config, location: &str) -> Result<Self, StoreError> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        Self::with_client(Client::from_conf(config), location, runtime)
+    }
+
+    fn with_client(client: Client, location: &str, runtime: Runtime) -> Result<Self, StoreError> {
+        let (bucket, prefix) = parse_location(location)?;
+        Ok(S3Store {
+            client,
+            bucket,
+            prefix,
+            runtime,
+        })
+    }
+
+    fn key(&self, id: &str, extension: &str) -> Result<String, StoreError> {
+        validate_id(id)?;
+        Ok(format!("{}{id}.{extension}", self.prefix))
+    }
+
+    fn put_object(&self, key: String, bytes: Vec<u8>) -> Result<(), StoreError> {
+        let request = self
+            .client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .body(ByteStream::from(bytes));
+        self.runtime.block_on(request.send()).map_err(s3_error)?;
+        Ok(())
+    }
+
+    /// Reads an object, or returns `None` if it does not exist.
+    fn get_object(&self, key: String) -> Result<Option<Vec<u8>>, StoreError> {
+        let request = self.client.get_object().bucket(&self.bucket).key(key);
+        self.runtime.block_on(async {
+            match request.send().await {
+                Ok(output) => {
+                    let body = output.body.collect().await.map_err(s3_error)?;
+                    Ok(Some(body.into_bytes().to_vec()))
+                }
+                Err(error) if error.as_service_error().is_some_and(|e| e.is_no_such_key()) => {
+                    Ok(None)
+                }
+                Err(error) => Err(s3_error(error)),
+            }
+        })
+    }
+
+    fn exists(&self, key: String) -> Result<bool, StoreError> {
+        let request = self.client.head_object().bucket(&self.bucket).key(key);
+        match self.runtime.block_on(request.send()) {
+            Ok(_) => Ok(true),
+            Err(error) if error.as_service_error().is_some_and(|e| e.is_not_found()) => Ok(false),
+            Err(error) => Err(s3_error(error)),
+        }
+    }
+
+    fn delete_object(&self, key: String) -> Result<(), StoreError> {
+        let request = self.client.delete_object().bucket(&self.bucket).key(key);
+        self.runtime.block_on(request.send()).map_err(s3_error)?;
+        Ok(())
+    }
+}
+
+impl TrajectoryStore for S3Store {
+    fn put(&self, id: &str, trajectory: &Trajectory) -> Result<(), StoreError> {
+        // The previous thumbnail goes first, for a failed write to never leave it next to the
+        // new trajectory
+        self.delete_object(self.key(id, THUMBNAIL_EXTENSION)?)?;
+        self.put_object(self.key(id, EXTENSION)?, binary::encode(trajectory))?;
+        // After the trajectory, for a thumbnail to never be left without it
+        let thumbnail = thumbnail::thumbnail(trajectory, MAX_POINTS);
+        self.put_object(
+            self.key(id, THUMBNAIL_EXTENSION)?,
+            binary::encode(&thumbnail),
+        )
+    }
+
+    fn get_encoded(&self, id: &str) -> Result<Option<Vec<u8>>, StoreError> {
+        self.get_object(self.key(id, EXTENSION)?)
+    }
+
+    fn list(&self) -> Result<Vec<String>, StoreError> {
+        let mut pages = self
+            .client
+            .list_objects_v2()
+            .bucket(&self.bucket)
+            .prefix(&self.prefix)
+            .into_paginator()
+            .send();
+        let suffix = format!(".{EXTENSION}");
+        let mut ids = Vec::new();
+        while let Some(page) = self.runtime.block_on(pages.next()) {
+            for object in page.map_err(s3_error)?.contents() {
+                let id = object
+                    .key()
+                    .and_then(|key| key.strip_prefix(&self.prefix))
+                    .and_then(|name| name.strip_suffix(&suffix));
+                // Objects under nested prefixes are not trajectories of this store
+                if let Some(id) = id.filter(|id| validate_id(id).is_ok()) {
+                    ids.push(id.to_string());
+                }
+            }
+        }
+        ids.sort();
+        Ok(ids)
+    }
+
+    fn delete(&self, id: &str) -> Result<bool, StoreError> {
+        let key = self.key(id, EXTENSION)?;
+        if !self.exists(key.clone())? {
+            return Ok(false);
+        }
+        // The metadata and thumbnail go first, for a trajectory to never be left without them
+        self.delete_object(self.key(id, METADATA_EXTENSION)?)?;
+        self.delete_object(self.key(id, THUMBNAIL_EXTENSION)?)?;
+        self.delete_object(key)?;
+        Ok(true)
+    }
+
+    fn put_metadata(&self, id: &str, metadata: &Value) -> Result<(), StoreError> {
+        let key = self.key(id, METADATA_EXTENSION)?;
+        self.put_object(key, serde_json::to_vec(metadata)?)
+    }
+
+    fn metadata(&self, id: &str) -> Result<Option<Value>, StoreError> {
+        self.get_object(self.key(id, METADATA_EXTENSION)?)?
+            .map(|bytes| serde_json::from_slice(&bytes))
+            .transpose()
+            .map_err(StoreError::from)
+    }
+
+    fn thumbnail(&self, id: &str) -> Result<Option<Trajectory>, StoreError> {
+        match self.get_object(self.key(id, THUMBNAIL_EXTENSION)?)? {
+            Some(bytes) => Ok(Some(binary::decode(&bytes)?)),
+            // Stored before thumbnails were
+            None => Ok(self
+                .get(id)?
+                .map(|trajectory| thumbnail::thumbnail(&trajectory, MAX_POINTS))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_sdk_s3::config::{Credentials, Region};
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// Answers successive requests with the given status and body, returning the address
+    /// and a handle yielding the request lines received.
+    fn serve(
+        responses: Vec<(&'static str, &'static str)>,
+    ) -> (String, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let mut requests = Vec::new();
+            for (status, body) in responses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                requests.push(line.trim_end().to_string());
+                let mut content_length = 0;
+                let mut header = String::new();
+                while reader.read_line(&mut header).unwrap() > 2 {
+                    if let Some(value) = header.to_lowercase().strip_prefix("content-length:") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                    header.clear();
+                }
+                reader.read_exact(&mut vec![0; content_length]).unwrap();
+                let response = format!(
+                    "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                reader.into_inner().write_all(response.as_bytes()).unwrap();
+            }
+            requests
+        });
+        (address, handle)
+    }
+
+    fn store(address: &str, location: &str) -> S3Store {
+        let config = aws_sdk_s3::Config::builder()
+            .behavior_version(BehaviorVersion::latest())
+            .endpoint_url(address)
+            .region(Region::new("us-east-1"))
+            .credentials_provider(Credentials::new("key", "secret", None, None, "test"))
+            .force_path_style(true)
+            .build();
+        S3Store::with_config(config, location).unwrap()
+    }
+
+    #[test]
+    fn test_parse_location() {
+        let parse = |location| parse_location(location).unwrap();
+        assert_eq!(parse("trips"), ("trips".to_string(), String::new()));
+        assert_eq!(parse("trips/"), ("trips".to_string(), String::new()));
+        assert_eq!(
+            parse("trips/beijing/2008/"),
+            ("trips".to_string(), "beijing/2008/".to_string())
+        );
+        assert!(matches!(parse_location("/beijing"), Err(StoreError::S3(_))));
+    }
+
+    #[test]
+    fn test_s3_store() {
+        let listing = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+            <ListBucketResult><Name>trips</Name><Prefix>beijing/</Prefix><KeyCount>4</KeyCount>\
+            <IsTruncated>false</IsTruncated>\
+            <Contents><Key>beijing/b.trjb</Key></Contents>\
+            <Contents><Key>beijing/a.json</Key></Contents>\
+            <Contents><Key>beijing/a.trjb</Key></Contents>\
+            <Contents><Key>beijing/old/c.trjb</Key></Contents>\
+            </ListBucketResult>";
+        let missing = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+            <Error><Code>NoSuchKey</Code><Message>Not found</Message></Error>";
+        let denied = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+            <Error><Code>AccessDenied</Code><Message>Access denied</Message></Error>";
+        let (address, handle) = serve(vec![
+            ("200 OK", listing),
+            ("200 OK", "{\"source\":\"a.gpx\"}"),
+            ("404 Not Found", missing),
+            ("404 Not Found", ""),
+            ("200 OK", ""),
+            ("204 No Content", ""),
+            ("204 No Content", ""),
+            ("204 No Content", ""),
+            ("204 No Content", ""),
+            ("200 OK", ""),
+            ("403 Forbidden", denied),
+        ]);
+        let store = store(&address, "trips/beijing");
+
+        assert_eq!(store.list().unwrap(), ["a", "b"]);
+        assert_eq!(
+            store.metadata("a").unwrap(),
+            Some(serde_json::json!({ "source": "a.gpx" }))
+        );
+        assert_eq!(store.get("missing").unwrap(), None);
+        assert!(!store.delete("missing").unwrap());
+        assert!(store.delete("a").unwrap());
+        assert!(matches!(store.get("../a"), Err(StoreError::InvalidId(_))));
+        // The thumbnail fails to be written after the previous one was removed
+        let trajectory = crate::bench::synthetic_trajectory(10, 0);
+        assert!(matches!(
+            store.put("c", &trajectory),
+            Err(StoreError::S3(_))
+        ));
+
+        let requests: Vec<String> = handle
+            .join()
+            .unwrap()
+            .into_iter()
+            // Method and path, without the query and the HTTP version
+            .map(|line| line.split(['?', ' ']).take(2).collect::<Vec<_>>().join(" "))
+            .collect();
+        assert_eq!(
+            requests,
+            [
+                "GET /trips/",
+                "GET /trips/beijing/a.json",
+                "GET /trips/beijing/missing.trjb",
+                "HEAD /trips/beijing/missing.trjb",
+                "HEAD /trips/beijing/a.trjb",
+                "DELETE /trips/beijing/a.json",
+                "DELETE /trips/beijing/a.thumb",
+                "DELETE /trips/beijing/a.trjb",
+                "DELETE /trips/beijing/c.thumb",
+                "PUT /trips/beijing/c.trjb",
+                "PUT /trips/beijing/c.thumb",
+            ]
+        );
+    }
+}
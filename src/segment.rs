@@ -0,0 +1,110 @@
+//! Segmentation of a stream of points into trips.
+//!
+//! A GeoLife file may log several days, with the device off at night or between trips.
+//! Simplified as one trajectory, the end of a trip is joined to the start of the next with a
+//! straight line that was never traveled. A new trip starts wherever the time between two
+//! consecutive points or the distance between them exceeds a threshold.
+
+use crate::trajectory::Trajectory;
+use std::ops::Range;
+
+/// Indices of the points of each trip, in order: a trip ends before the first point more
+/// than `max_gap_seconds` after the previous point or more than `max_jump_meters` away
+/// from it.
+pub fn gap_ranges(
+    trajectory: &Trajectory,
+    max_gap_seconds: u64,
+    max_jump_meters: f64,
+) -> Vec<Range<usize>> {
+    let len = trajectory.timestamps.len();
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    for i in 1..len {
+        let gap = trajectory.timestamps[i].saturating_sub(trajectory.timestamps[i - 1]);
+        let jump_m = trajectory.coordinates.distance_m(
+            (trajectory.latitudes[i - 1], trajectory.longitudes[i - 1]),
+            (trajectory.latitudes[i], trajectory.longitudes[i]),
+        );
+        if gap > max_gap_seconds || jump_m > max_jump_meters {
+            ranges.push(start..i);
+            start = i;
+        }
+    }
+    if len > 0 {
+        ranges.push(start..len);
+    }
+    ranges
+}
+
+/// Points of the trajectory in `range`.
+fn slice(trajectory: &Trajectory, range: Range<usize>) -> Trajectory {
+    Trajectory {
+        latitudes: trajectory.latitudes[range.clone()].to_vec(),
+        longitudes: trajectory.longitudes[range.clone()].to_vec(),
+        timestamps: trajectory.timestamps[range.clone()].to_vec(),
+        altitudes: if trajectory.altitudes.is_empty() {
+            Vec::new()
+        } else {
+            trajectory.altitudes[range].to_vec()
+        },
+        coordinates: trajectory.coordinates,
+    }
+}
+
+/// Splits a trajectory sorted by time into trips at the gaps found by `gap_ranges`.
+///
+/// # Returns
+///
+/// The trips in order, none for an empty trajectory.
+pub fn split_by_gap(
+    trajectory: &Trajectory,
+    max_gap_seconds: u64,
+    max_jump_meters: f64,
+) -> Vec<Trajectory> {
+    gap_ranges(trajectory, max_gap_seconds, max_jump_meters)
+        .into_iter()
+        .map(|range| slice(trajectory, range))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trajectory::CoordinateSystem;
+
+    /// Points about 11 m apart every 5 seconds, with a night between the third and fourth
+    /// and a 1 km jump between the fifth and sixth.
+    fn day_logs() -> Trajectory {
+        Trajectory {
+            latitudes: vec![0, 100, 200, 300, 400, 9_400, 9_500],
+            longitudes: vec![0; 7],
+            timestamps: vec![0, 5, 10, 40_000, 40_005, 40_010, 40_015],
+            altitudes: vec![1, 2, 3, 4, 5, 6, 7],
+            coordinates: CoordinateSystem::Wgs84,
+        }
+    }
+
+    #[test]
+    fn test_gap_ranges() {
+        let trajectory = day_logs();
+        assert_eq!(gap_ranges(&trajectory, 600, 500.0), [0..3, 3..5, 5..7]);
+        assert_eq!(gap_ranges(&trajectory, 600, f64::INFINITY), [0..3, 3..7]);
+        assert_eq!(gap_ranges(&trajectory, u64::MAX, f64::INFINITY), vec![0..7]);
+        assert!(gap_ranges(&day_logs().filter(&[false; 7]), 600, 500.0).is_empty());
+    }
+
+    #[test]
+    fn test_split_by_gap() {
+        let trips = split_by_gap(&day_logs(), 600, 500.0);
+        assert_eq!(trips.len(), 3);
+        assert_eq!(trips[1].timestamps, [40_000, 40_005]);
+        assert_eq!(trips[1].altitudes, [4, 5]);
+        assert_eq!(trips[2].latitudes, [9_400, 9_500]);
+
+        let mut trajectory = day_logs();
+        trajectory.altitudes.clear();
+        assert!(split_by_gap(&trajectory, 600, 500.0)[0]
+            .altitudes
+            .is_empty());
+    }
+}
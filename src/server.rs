@@ -0,0 +1,645 @@
+//! HTTP server exposing the simplification to other services, hardened for public
+//! deployments: request bodies and point counts are bounded, and every client address is
+//! rate limited with a token bucket.
+//!
+//! `POST /simplify?epsilon_m=<meters>` takes a JSON array of `[latitude, longitude,
+//! timestamp]` points in degrees and answers `{"points": [...]}` with the points kept.
+//! With `&budget_ms=<milliseconds>`, the simplification stops when the budget runs out
+//! (see `simplify::simplify_anytime`) and the answer tells whether it is `complete`.
+//! Errors are answered as `{"error": {"code": "...", "message": "..."}}` with the matching
+//! status. `GET /metrics` answers the Prometheus metrics of the server. Every connection
+//! serves one request, and a fixed pool of workers serves the connections.
+//!
+//! When the server has tenants, requests carry the API key of their tenant, as an
+//! `Authorization: Bearer <key>` header or an `api_key` query parameter.
+
+use crate::geo::{meters_to_units, EARTH_RADIUS_M, UNITS_PER_DEGREE};
+use crate::metrics::Metrics;
+use crate::simplify;
+use crate::tenants::Tenants;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, TcpListener, TcpStream};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// Time a client has to send its request
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Time a client has to read its response
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Pause after failing to accept a connection, for errors such as running out of file
+/// descriptors to clear up instead of spinning
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// Longest request line or header accepted
+const MAX_LINE_BYTES: usize = 8 * 1024;
+
+/// Most headers accepted in a request
+const MAX_HEADERS: usize = 100;
+
+/// Largest tolerance accepted, half the circumference of the Earth: every point is within
+/// it, and larger ones would overflow the squared distances
+const MAX_EPSILON_M: f64 = EARTH_RADIUS_M * std::f64::consts::PI;
+
+/// Limits protecting the server.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ServerLimits {
+    pub max_body_bytes: usize,
+    pub max_points: usize,
+    /// Requests per minute sustained by a client address
+    pub requests_per_minute: f64,
+    /// Requests a client address can send at once after being idle
+    pub burst: f64,
+    /// Connections served at once, the others waiting to be accepted
+    pub max_connections: usize,
+}
+
+impl Default for ServerLimits {
+    fn default() -> Self {
+        ServerLimits {
+            max_body_bytes: 4 * 1024 * 1024,
+            max_points: 100_000,
+            requests_per_minute: 60.0,
+            burst: 10.0,
+            max_connections: 64,
+        }
+    }
+}
+
+/// Error answered to a request.
+#[derive(Error, Debug, PartialEq)]
+pub enum ApiError {
+    #[error("Malformed request: {0}")]
+    BadRequest(String),
+    #[error("Missing or unknown API key")]
+    Unauthorized,
+    #[error("No route for {0}")]
+    NotFound(String),
+    #[error("Body of {0} bytes exceeds the limit of {1} bytes")]
+    BodyTooLarge(usize, usize),
+    #[error("{0} points exceed the limit of {1} points")]
+    TooManyPoints(usize, usize),
+    #[error("Rate limit exceeded, retry in {0} s")]
+    RateLimited(u64),
+}
+
+impl ApiError {
+    pub fn status(&self) -> (u16, &'static str) {
+        match self {
+            ApiError::BadRequest(_) => (400, "Bad Request"),
+            ApiError::Unauthorized => (401, "Unauthorized"),
+            ApiError::NotFound(_) => (404, "Not Found"),
+            ApiError::BodyTooLarge(_, _) | ApiError::TooManyPoints(_, _) => {
+                (413, "Payload Too Large")
+            }
+            ApiError::RateLimited(_) => (429, "Too Many Requests"),
+        }
+    }
+
+    /// Stable identifier of the error, for clients to match on.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::BadRequest(_) => "bad_request",
+            ApiError::Unauthorized => "unauthorized",
+            ApiError::NotFound(_) => "not_found",
+            ApiError::BodyTooLarge(_, _) => "body_too_large",
+            ApiError::TooManyPoints(_, _) => "too_many_points",
+            ApiError::RateLimited(_) => "rate_limited",
+        }
+    }
+
+    fn body(&self) -> Value {
+        json!({ "error": { "code": self.code(), "message": self.to_string() } })
+    }
+}
+
+/// Token bucket of one client address.
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Token bucket rate limiter per client address.
+#[derive(Debug)]
+pub struct RateLimiter {
+    per_second: f64,
+    burst: f64,
+    buckets: Mutex<HashMap<IpAddr, Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_minute: f64, burst: f64) -> Self {
+        RateLimiter {
+            per_second: requests_per_minute / 60.0,
+            burst: burst.max(1.0),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Takes a token for a request of `client` at `now`, or returns the error telling when
+    /// one will be available.
+    pub fn check(&self, client: IpAddr, now: Instant) -> Result<(), ApiError> {
+        let mut buckets = self.buckets.lock().unwrap();
+        // Buckets refilled to the burst are the same as absent ones
+        if buckets.len() > 10_000 {
+            let (per_second, burst) = (self.per_second, self.burst);
+            buckets.retain(|_, bucket| {
+                bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() * per_second
+                    < burst
+            });
+        }
+        let bucket = buckets.entry(client).or_insert(Bucket {
+            tokens: self.burst,
+            updated: now,
+        });
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.per_second).min(self.burst);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            let wait_s = (1.0 - bucket.tokens) / self.per_second;
+            Err(ApiError::RateLimited(wait_s.ceil() as u64))
+        }
+    }
+}
+
+/// Body of a successful response.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Response {
+    Json(Value),
+    /// Prometheus text exposition format
+    Metrics(String),
+}
+
+/// Request as parsed from the connection.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub query: String,
+    /// Bearer token of the `Authorization` header
+    pub api_key: Option<String>,
+    pub body: Vec<u8>,
+}
+
+/// Reads a line, failing beyond `MAX_LINE_BYTES`.
+fn read_line(reader: &mut impl BufRead) -> Result<String, ApiError> {
+    let mut line = Vec::new();
+    reader
+        .take(MAX_LINE_BYTES as u64 + 1)
+        .read_until(b'\n', &mut line)
+        .map_err(|error| ApiError::BadRequest(error.to_string()))?;
+    if line.len() > MAX_LINE_BYTES {
+        return Err(ApiError::BadRequest("line too long".to_string()));
+    }
+    String::from_utf8(line)
+        .map(|line| line.trim_end().to_string())
+        .map_err(|_| ApiError::BadRequest("invalid UTF-8".to_string()))
+}
+
+/// Reads a request, refusing a body larger than the limit before reading it.
+pub fn read_request(reader: &mut impl BufRead, limits: &ServerLimits) -> Result<Request, ApiError> {
+    let request_line = read_line(reader)?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(ApiError::BadRequest("invalid request line".to_string()));
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    let (mut content_length, mut api_key) = (0, None);
+    for count in 0.. {
+        let header = read_line(reader)?;
+        if header.is_empty() {
+            break;
+        }
+        if count == MAX_HEADERS {
+            return Err(ApiError::BadRequest("too many headers".to_string()));
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| ApiError::BadRequest("invalid Content-Length".to_string()))?;
+            } else if name.trim().eq_ignore_ascii_case("authorization") {
+                if let Some(key) = value.trim().strip_prefix("Bearer ") {
+                    api_key = Some(key.trim().to_string());
+                }
+            }
+        }
+    }
+    if content_length > limits.max_body_bytes {
+        return Err(ApiError::BodyTooLarge(
+            content_length,
+            limits.max_body_bytes,
+        ));
+    }
+    let mut body = vec![0; content_length];
+    reader
+        .read_exact(&mut body)
+        .map_err(|error| ApiError::BadRequest(error.to_string()))?;
+
+    Ok(Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        query: query.to_string(),
+        api_key,
+        body,
+    })
+}
+
+/// Value of the parameter `name` in a query string.
+fn query_param<'a>(query: &'a str, name: &str) -> Option<&'a str> {
+    query.split('&').find_map(|param| {
+        param
+            .split_once('=')
+            .filter(|(key, _)| *key == name)
+            .map(|(_, value)| value)
+    })
+}
+
+/// Checks that a request carries the API key of one of `tenants`, if the server has any.
+pub fn authenticate(request: &Request, tenants: Option<&Tenants>) -> Result<(), ApiError> {
+    let Some(tenants) = tenants else {
+        return Ok(());
+    };
+    let api_key = request
+        .api_key
+        .as_deref()
+        .or_else(|| query_param(&request.query, "api_key"));
+    match api_key.and_then(|key| tenants.authenticate(key)) {
+        Some(_) => Ok(()),
+        None => Err(ApiError::Unauthorized),
+    }
+}
+
+/// Simplifies the points of a `POST /simplify` request.
+fn simplify_points(
+    request: &Request,
+    limits: &ServerLimits,
+    metrics: &Metrics,
+) -> Result<Value, ApiError> {
+    let epsilon_m: f64 = query_param(&request.query, "epsilon_m")
+        .ok_or_else(|| ApiError::BadRequest("missing epsilon_m".to_string()))?
+        .parse()
+        .ok()
+        .filter(|epsilon_m| (0.0..=MAX_EPSILON_M).contains(epsilon_m))
+        .ok_or_else(|| {
+            ApiError::BadRequest(format!(
+                "epsilon_m must be a number of meters from 0 to {MAX_EPSILON_M:.0}"
+            ))
+        })?;
+    let points: Vec<(f64, f64, u64)> = serde_json::from_slice(&request.body).map_err(|error| {
+        metrics.record_parse_error();
+        ApiError::BadRequest(error.to_string())
+    })?;
+    if points.len() > limits.max_points {
+        return Err(ApiError::TooManyPoints(points.len(), limits.max_points));
+    }
+    // Checked before scaling, which saturates out of range
+    let invalid = points.iter().position(|&(latitude, longitude, _)| {
+        !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude)
+    });
+    if let Some(index) = invalid {
+        return Err(ApiError::BadRequest(format!(
+            "point {index} is not a latitude from -90 to 90 and a longitude from -180 to 180"
+        )));
+    }
+
+    let units = |degrees: f64| (degrees * UNITS_PER_DEGREE).round() as i64;
+    let latitudes: Vec<i64> = points
+        .iter()
+        .map(|&(latitude, _, _)| units(latitude))
+        .collect();
+    let longitudes: Vec<i64> = points
+        .iter()
+        .map(|&(_, longitude, _)| units(longitude))
+        .collect();
+    let budget = query_param(&request.query, "budget_ms")
+        .map(|budget_ms| {
+            budget_ms
+                .parse()
+                .map(Duration::from_millis)
+                .map_err(|_| ApiError::BadRequest("invalid budget_ms".to_string()))
+        })
+        .transpose()?;
+    let epsilon = meters_to_units(epsilon_m);
+    let start = Instant::now();
+    let (keep, complete) = match budget {
+        Some(budget) => {
+            let simplification =
+                simplify::simplify_anytime(&latitudes, &longitudes, epsilon, budget);
+            (simplification.keep, Some(simplification.complete))
+        }
+        None => (simplify::simplify(&latitudes, &longitudes, epsilon), None),
+    };
+    let kept_points = keep.iter().filter(|keep| **keep).count();
+    metrics.record_simplification(points.len(), kept_points, start.elapsed());
+    let kept: Vec<Value> = points
+        .iter()
+        .zip(keep)
+        .filter(|(_, keep)| *keep)
+        .map(|(&(latitude, longitude, timestamp), _)| json!([latitude, longitude, timestamp]))
+        .collect();
+    Ok(match complete {
+        Some(complete) => json!({ "points": kept, "complete": complete }),
+        None => json!({ "points": kept }),
+    })
+}
+
+/// Answers a request.
+pub fn handle(
+    request: &Request,
+    limits: &ServerLimits,
+    metrics: &Metrics,
+) -> Result<Response, ApiError> {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("POST", "/simplify") => simplify_points(request, limits, metrics).map(Response::Json),
+        ("GET", "/metrics") => Ok(Response::Metrics(metrics.render())),
+        (method, path) => Err(ApiError::NotFound(format!("{method} {path}"))),
+    }
+}
+
+/// Writes a response.
+fn write_response(
+    stream: &mut impl Write,
+    status: (u16, &str),
+    headers: &str,
+    response: &Response,
+) -> io::Result<()> {
+    let (content_type, body) = match response {
+        Response::Json(body) => ("application/json", body.to_string()),
+        Response::Metrics(body) => ("text/plain; version=0.0.4", body.clone()),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n\
+         {headers}Connection: close\r\n\r\n{body}",
+        status.0,
+        status.1,
+        body.len()
+    )
+}
+
+/// Serves one connection: checks the rate limit of its address, then reads, authenticates
+/// and answers its request.
+pub fn serve_connection(
+    stream: TcpStream,
+    limits: &ServerLimits,
+    limiter: &RateLimiter,
+    tenants: Option<&Tenants>,
+    metrics: &Metrics,
+) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    let client = stream.peer_addr()?.ip();
+    let mut reader = BufReader::new(stream);
+    let result = limiter
+        .check(client, Instant::now())
+        .and_then(|()| read_request(&mut reader, limits))
+        .and_then(|request| authenticate(&request, tenants).map(|()| request))
+        .and_then(|request| handle(&request, limits, metrics));
+    let mut stream = reader.into_inner();
+    match result {
+        Ok(response) => write_response(&mut stream, (200, "OK"), "", &response),
+        Err(error @ ApiError::RateLimited(wait_s)) => write_response(
+            &mut stream,
+            error.status(),
+            &format!("Retry-After: {wait_s}\r\n"),
+            &Response::Json(error.body()),
+        ),
+        Err(error) => write_response(
+            &mut stream,
+            error.status(),
+            "",
+            &Response::Json(error.body()),
+        ),
+    }
+}
+
+/// Serves the connections of `listener` until the process ends, with
+/// `limits.max_connections` workers, only to the clients of `tenants` if any.
+pub fn serve(
+    listener: &TcpListener,
+    limits: &ServerLimits,
+    tenants: Option<&Tenants>,
+    metrics: &Metrics,
+) {
+    let limiter = RateLimiter::new(limits.requests_per_minute, limits.burst);
+    std::thread::scope(|scope| {
+        for _ in 0..limits.max_connections.max(1) {
+            scope.spawn(|| loop {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        let result = serve_connection(stream, limits, &limiter, tenants, metrics);
+                        if let Err(error) = result {
+                            eprintln!("Failed to answer a request: {error}");
+                        }
+                    }
+                    // Failing to accept one connection does not stop serving the others
+                    Err(error) => {
+                        eprintln!("Failed to accept a connection: {error}");
+                        std::thread::sleep(ACCEPT_RETRY_DELAY);
+                    }
+                }
+            });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    fn post(query: &str, body: &str) -> Request {
+        Request {
+            method: "POST".to_string(),
+            path: "/simplify".to_string(),
+            query: query.to_string(),
+            api_key: None,
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_handle() {
+        let limits = ServerLimits::default();
+        let metrics = Metrics::default();
+        let body = "[[39.9, 116.4, 0], [39.90001, 116.41, 10], [39.9, 116.42, 20]]";
+        let response = handle(&post("epsilon_m=10", body), &limits, &metrics).unwrap();
+        assert_eq!(
+            response,
+            Response::Json(json!({ "points": [[39.9, 116.4, 0], [39.9, 116.42, 20]] }))
+        );
+
+        let small = ServerLimits {
+            max_points: 2,
+            ..limits
+        };
+        let error = handle(&post("epsilon_m=10", body), &small, &metrics).unwrap_err();
+        assert_eq!(error, ApiError::TooManyPoints(3, 2));
+        assert_eq!(error.status().0, 413);
+        assert_eq!(error.body()["error"]["code"], "too_many_points");
+
+        let error = handle(&post("", body), &limits, &metrics).unwrap_err();
+        assert_eq!(error.status().0, 400);
+        let error = handle(&post("epsilon_m=10", "[[39.9]]"), &limits, &metrics).unwrap_err();
+        assert_eq!(error.code(), "bad_request");
+        let mut get = post("epsilon_m=10", body);
+        get.method = "GET".to_string();
+        assert_eq!(
+            handle(&get, &limits, &metrics).unwrap_err().code(),
+            "not_found"
+        );
+
+        get.path = "/metrics".to_string();
+        let Response::Metrics(text) = handle(&get, &limits, &metrics).unwrap() else {
+            panic!("expected metrics");
+        };
+        assert!(text.contains("\ntrajectory_points_processed_total 3\n"));
+        assert!(text.contains("\ntrajectory_points_kept_total 2\n"));
+        assert!(text.contains("\ntrajectory_parse_errors_total 1\n"));
+    }
+
+    #[test]
+    fn test_handle_invalid_values() {
+        let (limits, metrics) = (ServerLimits::default(), Metrics::default());
+        let body = "[[39.9, 116.4, 0], [39.9, 116.42, 20]]";
+        for epsilon_m in ["-1", "inf", "NaN", "1e30"] {
+            let request = post(&format!("epsilon_m={epsilon_m}"), body);
+            let error = handle(&request, &limits, &metrics).unwrap_err();
+            assert_eq!(error.status().0, 400, "epsilon_m={epsilon_m}");
+        }
+        let request = post(&format!("epsilon_m={MAX_EPSILON_M}"), body);
+        assert!(handle(&request, &limits, &metrics).is_ok());
+
+        for point in ["[90.5, 116.4, 0]", "[39.9, -180.5, 0]", "[1e300, 116.4, 0]"] {
+            let request = post("epsilon_m=10", &format!("[[39.9, 116.4, 0], {point}]"));
+            let error = handle(&request, &limits, &metrics).unwrap_err();
+            assert_eq!(
+                error,
+                ApiError::BadRequest(
+                    "point 1 is not a latitude from -90 to 90 and a longitude from -180 to 180"
+                        .to_string()
+                )
+            );
+            assert_eq!(error.status().0, 400);
+        }
+        let poles = "[[90, -180, 0], [-90, 180, 10], [0, 0, 20]]";
+        assert!(handle(&post("epsilon_m=0", poles), &limits, &metrics).is_ok());
+    }
+
+    #[test]
+    fn test_handle_budget() {
+        let (limits, metrics) = (ServerLimits::default(), Metrics::default());
+        let body = "[[39.9, 116.4, 0], [39.90001, 116.41, 10], [39.9, 116.42, 20]]";
+        let request = post("epsilon_m=10&budget_ms=1000", body);
+        let Response::Json(answer) = handle(&request, &limits, &metrics).unwrap() else {
+            panic!("expected JSON");
+        };
+        assert_eq!(answer["complete"], true);
+        assert_eq!(answer["points"].as_array().unwrap().len(), 2);
+
+        let request = post("epsilon_m=10&budget_ms=-1", body);
+        let error = handle(&request, &limits, &metrics).unwrap_err();
+        assert_eq!(error.code(), "bad_request");
+    }
+
+    #[test]
+    fn test_serve() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let address = listener.local_addr().unwrap();
+        let limits = ServerLimits {
+            max_connections: 2,
+            ..ServerLimits::default()
+        };
+        std::thread::spawn(move || serve(&listener, &limits, None, &Metrics::default()));
+
+        // A client sending nothing holds one worker, the other one still answers
+        let _idle = TcpStream::connect(address).unwrap();
+        for _ in 0..2 {
+            let mut stream = TcpStream::connect(address).unwrap();
+            stream.write_all(b"GET /unknown HTTP/1.1\r\n\r\n").unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            assert!(
+                response.starts_with("HTTP/1.1 404 Not Found\r\n"),
+                "{response}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_authenticate() {
+        let tenants =
+            Tenants::parse("[fleet]\napi_keys = [\"k1\"]\n[bikes]\napi_keys = [\"k2\"]").unwrap();
+        let request = post("epsilon_m=10", "[]");
+        assert_eq!(authenticate(&request, None), Ok(()));
+        assert_eq!(
+            authenticate(&request, Some(&tenants)),
+            Err(ApiError::Unauthorized)
+        );
+        let with_header = Request {
+            api_key: Some("k2".to_string()),
+            ..request.clone()
+        };
+        assert_eq!(authenticate(&with_header, Some(&tenants)), Ok(()));
+        let with_query = post("epsilon_m=10&api_key=k1", "[]");
+        assert_eq!(authenticate(&with_query, Some(&tenants)), Ok(()));
+        let unknown = post("epsilon_m=10&api_key=k3", "[]");
+        let error = authenticate(&unknown, Some(&tenants)).unwrap_err();
+        assert_eq!(error.status(), (401, "Unauthorized"));
+        assert_eq!(error.code(), "unauthorized");
+
+        let mut input: &[u8] = b"GET /metrics HTTP/1.1\r\nAuthorization: Bearer k1\r\n\r\n";
+        let request = read_request(&mut input, &ServerLimits::default()).unwrap();
+        assert_eq!(request.api_key.as_deref(), Some("k1"));
+        assert_eq!(authenticate(&request, Some(&tenants)), Ok(()));
+    }
+
+    #[test]
+    fn test_read_request() {
+        let limits = ServerLimits {
+            max_body_bytes: 8,
+            ..ServerLimits::default()
+        };
+        let mut input: &[u8] =
+            b"POST /simplify?epsilon_m=5 HTTP/1.1\r\nContent-Length: 2\r\n\r\n[]";
+        assert_eq!(
+            read_request(&mut input, &limits).unwrap(),
+            post("epsilon_m=5", "[]")
+        );
+
+        // The body is refused from its announced length, without reading it
+        let mut input: &[u8] = b"POST /simplify HTTP/1.1\r\nContent-Length: 1000000\r\n\r\n";
+        assert_eq!(
+            read_request(&mut input, &limits).unwrap_err(),
+            ApiError::BodyTooLarge(1_000_000, 8)
+        );
+    }
+
+    #[test]
+    fn test_rate_limiter() {
+        // 2 requests at once, then one every 30 seconds
+        let limiter = RateLimiter::new(2.0, 2.0);
+        let client = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let other = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        let start = Instant::now();
+
+        assert!(limiter.check(client, start).is_ok());
+        assert!(limiter.check(client, start).is_ok());
+        assert_eq!(limiter.check(client, start), Err(ApiError::RateLimited(30)));
+        assert!(limiter.check(other, start).is_ok());
+        assert!(limiter
+            .check(client, start + Duration::from_secs(31))
+            .is_ok());
+    }
+}
@@ -0,0 +1,85 @@
+//! Similarity metrics between the paths of two trajectories.
+
+use crate::trajectory::Trajectory;
+
+/// Discrete Fréchet distance in meters between two trajectories.
+///
+/// This is the shortest leash that lets two walkers go through the points of each
+/// trajectory in order, each one either staying or moving to its next point at every step.
+/// Unlike the Hausdorff distance, it tells a path from its reverse and detects loops taken
+/// by only one of the trajectories. Only the points are matched, so the distance between
+/// sparse trajectories, such as simplified ones, can exceed the distance between the paths.
+///
+/// Returns `None` if either trajectory has no points, or if they are in different
+/// coordinate systems.
+pub fn frechet_m(a: &Trajectory, b: &Trajectory) -> Option<f64> {
+    if a.latitudes.is_empty() || b.latitudes.is_empty() || a.coordinates != b.coordinates {
+        return None;
+    }
+    let distance = |i: usize, j: usize| {
+        a.coordinates.distance_m(
+            (a.latitudes[i], a.longitudes[i]),
+            (b.latitudes[j], b.longitudes[j]),
+        )
+    };
+
+    // Distances of the couplings ending at (i, j), for the previous and current `i`
+    let mut previous: Vec<f64> = Vec::with_capacity(b.latitudes.len());
+    let mut current: Vec<f64> = Vec::with_capacity(b.latitudes.len());
+    for i in 0..a.latitudes.len() {
+        current.clear();
+        for j in 0..b.latitudes.len() {
+            let best = match (i, j) {
+                (0, 0) => 0.0,
+                (0, _) => current[j - 1],
+                (_, 0) => previous[0],
+                _ => previous[j].min(previous[j - 1]).min(current[j - 1]),
+            };
+            current.push(best.max(distance(i, j)));
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous.last().copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trajectory::CoordinateSystem;
+
+    fn trajectory(points: &[(i64, i64)]) -> Trajectory {
+        Trajectory {
+            latitudes: points.iter().map(|p| p.0).collect(),
+            longitudes: points.iter().map(|p| p.1).collect(),
+            timestamps: (0..points.len() as u64).collect(),
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        }
+    }
+
+    #[test]
+    fn test_frechet() {
+        let a = trajectory(&[(0, 0), (0, 1_000), (0, 2_000)]);
+        assert_eq!(frechet_m(&a, &a), Some(0.0));
+
+        // A parallel path about 111 m north. Its extra point halfway between two points of
+        // `a` is matched to one of them, about 124 m away.
+        let b = trajectory(&[(1_000, 0), (1_000, 500), (1_000, 1_000), (1_000, 2_000)]);
+        let distance = frechet_m(&a, &b).unwrap();
+        assert!((distance - 124.3).abs() < 0.1, "{distance}");
+        assert_eq!(frechet_m(&a, &b), frechet_m(&b, &a));
+
+        // The reverse path is as far as its endpoints, unlike with the Hausdorff distance
+        let reversed = trajectory(&[(0, 2_000), (0, 1_000), (0, 0)]);
+        let distance = frechet_m(&a, &reversed).unwrap();
+        assert!((distance - 222.4).abs() < 0.1, "{distance}");
+
+        assert_eq!(frechet_m(&a, &trajectory(&[])), None);
+        let local = Trajectory {
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Local { scale: 3 },
+            ..a.clone()
+        };
+        assert_eq!(frechet_m(&a, &local), None);
+    }
+}
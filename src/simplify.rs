@@ -2,8 +2,133 @@
 //! This module provides functions to reduce the number of points in a trajectory
 //! while maintaining its essential shape.
 
+use crate::geom;
+use crate::trajectory::{Trajectory, ALTITUDE_SCALE};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Geometric degeneracy of a sequence of points, which determines what `simplify` keeps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+    /// No points: nothing is kept
+    Empty,
+    /// A single point: it is kept
+    SinglePoint,
+    /// Several points, all at the same position: only the first and last are kept
+    Stationary,
+    /// Distinct points starting and ending at the same position: distances are
+    /// measured to that position instead of to a line
+    Closed,
+    /// Starts and ends at different positions
+    Open,
+}
+
+/// Classify a sequence of points by its degeneracy.
+///
+/// # Panics
+///
+/// This function will panic if `positions_x` and `positions_y` have different lengths.
+pub fn classify(positions_x: &[i64], positions_y: &[i64]) -> Shape {
+    assert_eq!(
+        positions_x.len(),
+        positions_y.len(),
+        "positions_x.len() == positions_y.len()"
+    );
+
+    let len = positions_x.len();
+    if len == 0 {
+        return Shape::Empty;
+    }
+    if len == 1 {
+        return Shape::SinglePoint;
+    }
+
+    let (x0, y0) = (positions_x[0], positions_y[0]);
+    if positions_x[len - 1] != x0 || positions_y[len - 1] != y0 {
+        Shape::Open
+    } else if positions_x.iter().zip(positions_y).all(|(&x, &y)| x == x0 && y == y0) {
+        Shape::Stationary
+    } else {
+        Shape::Closed
+    }
+}
+
+/// Order in which the segments left to split are processed.
+///
+/// Every order produces the same result; they differ in memory access patterns and in
+/// how large the worklist grows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SplitOrder {
+    /// Depth-first, second half of each split first
+    #[default]
+    LastHalf,
+    /// Depth-first, shorter half of each split first, which bounds the stack to
+    /// a logarithmic number of entries on skewed splits
+    ShorterHalf,
+    /// Longest segment first, from a worklist sorted by segment length
+    LongestSegment,
+}
+
+impl SplitOrder {
+    /// All orders, for benchmarks
+    pub const ALL: [SplitOrder; 3] = [
+        SplitOrder::LastHalf,
+        SplitOrder::ShorterHalf,
+        SplitOrder::LongestSegment,
+    ];
+
+    /// Name used on the command line and in reports
+    pub fn name(self) -> &'static str {
+        match self {
+            SplitOrder::LastHalf => "last-half-first",
+            SplitOrder::ShorterHalf => "shorter-first",
+            SplitOrder::LongestSegment => "longest-first",
+        }
+    }
+}
+
+/// Segments left to split, as `(start, end)` index pairs.
+enum Worklist {
+    Stack(Vec<(usize, usize)>),
+    Sorted(BinaryHeap<(usize, usize, usize)>),
+}
+
+impl Worklist {
+    fn new(order: SplitOrder) -> Self {
+        match order {
+            SplitOrder::LastHalf | SplitOrder::ShorterHalf => {
+                Worklist::Stack(Vec::with_capacity(64))
+            }
+            SplitOrder::LongestSegment => Worklist::Sorted(BinaryHeap::with_capacity(64)),
+        }
+    }
+
+    #[inline(always)]
+    fn push(&mut self, start: usize, end: usize) {
+        match self {
+            Worklist::Stack(stack) => stack.push((start, end)),
+            Worklist::Sorted(heap) => heap.push((end - start, start, end)),
+        }
+    }
+
+    #[inline(always)]
+    fn pop(&mut self) -> Option<(usize, usize)> {
+        match self {
+            Worklist::Stack(stack) => stack.pop(),
+            Worklist::Sorted(heap) => heap.pop().map(|(_, start, end)| (start, end)),
+        }
+    }
+}
+
 /// Calculate the squared perpendicular distance from a point to a line segment.
 /// This is an optimized version that avoids unnecessary calculations.
+///
+/// When both ends of the segment are at the same position, the squared distance
+/// to that position is returned.
 #[inline(always)]
 #[allow(clippy::too_many_arguments)]
 fn perpendicular_distance_squared(
@@ -24,52 +149,191 @@ fn perpendicular_distance_squared(
     }
 
     let area = ((x2 - x1) as i128) * ((y1 - y) as i128) - ((x1 - x) as i128) * ((y2 - y1) as i128);
-    if line_length_squared == 0 {
-        0
-    } else {
-        ((area * area) / line_length_squared) as i64
+    ((area * area) / line_length_squared) as i64
+}
+
+/// Line through two points, precomputed by `Coordinate::line` for the distances to it.
+#[derive(Debug, Clone, Copy)]
+pub struct Line<C: Coordinate> {
+    start: (C, C),
+    end: (C, C),
+    dx: C::Wide,
+    dy: C::Wide,
+    length_squared: C::Wide,
+}
+
+/// Numeric type of the coordinates `simplify` accepts, with the tolerance in the same type.
+///
+/// Integer distances are computed exactly in a wider integer type, float ones in `f64`.
+pub trait Coordinate: Copy {
+    /// Type the line is computed in
+    type Wide: Copy;
+    /// Type squared distances are compared in
+    type Squared: Copy + PartialOrd + Default;
+
+    fn line(start: (Self, Self), end: (Self, Self)) -> Line<Self>;
+
+    /// Squared distance from a point to the line, or to its start when both of its ends
+    /// are at the same position.
+    fn line_distance_squared(line: &Line<Self>, point: (Self, Self)) -> Self::Squared;
+
+    fn squared(self) -> Self::Squared;
+
+    fn is_negative(self) -> bool;
+}
+
+impl Coordinate for i64 {
+    type Wide = i128;
+    type Squared = i64;
+
+    #[inline(always)]
+    fn line(start: (i64, i64), end: (i64, i64)) -> Line<i64> {
+        let dx = (end.0 as i128) - (start.0 as i128);
+        let dy = (end.1 as i128) - (start.1 as i128);
+        Line { start, end, dx, dy, length_squared: dx * dx + dy * dy }
+    }
+
+    #[inline(always)]
+    fn line_distance_squared(line: &Line<i64>, (x, y): (i64, i64)) -> i64 {
+        let ((sx, sy), (ex, ey)) = (line.start, line.end);
+        perpendicular_distance_squared(x, y, sx, sy, ex, ey, line.dx, line.dy, line.length_squared)
+    }
+
+    fn squared(self) -> i64 {
+        self * self
+    }
+
+    fn is_negative(self) -> bool {
+        self < 0
+    }
+}
+
+impl Coordinate for i32 {
+    type Wide = i128;
+    type Squared = i128;
+
+    #[inline(always)]
+    fn line(start: (i32, i32), end: (i32, i32)) -> Line<i32> {
+        let dx = (end.0 as i128) - (start.0 as i128);
+        let dy = (end.1 as i128) - (start.1 as i128);
+        Line { start, end, dx, dy, length_squared: dx * dx + dy * dy }
+    }
+
+    #[inline(always)]
+    fn line_distance_squared(line: &Line<i32>, (x, y): (i32, i32)) -> i128 {
+        let (px, py) = ((x as i128) - (line.start.0 as i128), (y as i128) - (line.start.1 as i128));
+        if line.length_squared == 0 {
+            return px * px + py * py;
+        }
+        let area = line.dx * py - line.dy * px;
+        (area * area) / line.length_squared
+    }
+
+    fn squared(self) -> i128 {
+        (self as i128) * (self as i128)
+    }
+
+    fn is_negative(self) -> bool {
+        self < 0
+    }
+}
+
+/// Squared distance from a point to a line in `f64`, or to its start when both of its ends
+/// are at the same position.
+#[inline(always)]
+fn float_line_distance_squared(start: (f64, f64), (dx, dy, length_squared): (f64, f64, f64), (x, y): (f64, f64)) -> f64 {
+    let (px, py) = (x - start.0, y - start.1);
+    if length_squared == 0.0 {
+        return px * px + py * py;
+    }
+    let area = dx * py - dy * px;
+    (area * area) / length_squared
+}
+
+impl Coordinate for f64 {
+    type Wide = f64;
+    type Squared = f64;
+
+    #[inline(always)]
+    fn line(start: (f64, f64), end: (f64, f64)) -> Line<f64> {
+        let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+        Line { start, end, dx, dy, length_squared: dx * dx + dy * dy }
+    }
+
+    #[inline(always)]
+    fn line_distance_squared(line: &Line<f64>, point: (f64, f64)) -> f64 {
+        float_line_distance_squared(line.start, (line.dx, line.dy, line.length_squared), point)
+    }
+
+    fn squared(self) -> f64 {
+        self * self
+    }
+
+    fn is_negative(self) -> bool {
+        self < 0.0
+    }
+}
+
+impl Coordinate for f32 {
+    type Wide = f64;
+    type Squared = f64;
+
+    #[inline(always)]
+    fn line(start: (f32, f32), end: (f32, f32)) -> Line<f32> {
+        let (dx, dy) = (end.0 as f64 - start.0 as f64, end.1 as f64 - start.1 as f64);
+        Line { start, end, dx, dy, length_squared: dx * dx + dy * dy }
+    }
+
+    #[inline(always)]
+    fn line_distance_squared(line: &Line<f32>, (x, y): (f32, f32)) -> f64 {
+        let start = (line.start.0 as f64, line.start.1 as f64);
+        float_line_distance_squared(start, (line.dx, line.dy, line.length_squared), (x as f64, y as f64))
+    }
+
+    fn squared(self) -> f64 {
+        (self as f64) * (self as f64)
+    }
+
+    fn is_negative(self) -> bool {
+        self < 0.0
     }
 }
 
 /// Iterative implementation of the Douglas-Peucker algorithm using a stack.
 /// This version is optimized for performance and avoids recursion.
 #[inline(always)]
-fn douglas_peucker_iterative(
-    positions_x: &[i64],
-    positions_y: &[i64],
-    epsilon: i64,
+fn douglas_peucker_iterative<C: Coordinate>(
+    positions_x: &[C],
+    positions_y: &[C],
+    epsilon: C,
+    order: SplitOrder,
+    stack: &mut Worklist,
     result: &mut [bool],
 ) {
     assert_eq!(positions_x.len(), positions_y.len());
     assert_eq!(positions_x.len(), result.len());
 
-    let mut stack = Vec::with_capacity(64);
     let len = positions_x.len();
-    stack.push((0, len - 1));
-    let epsilon_squared = epsilon * epsilon;
+    stack.push(0, len - 1);
+    let epsilon_squared = epsilon.squared();
 
     while let Some((start, end)) = stack.pop() {
         if end - start <= 1 {
             continue;
         }
         // Inline find_max_distance
-        let mut max_distance = 0;
+        let mut max_distance = C::Squared::default();
         let mut max_index = start;
-        let sx = positions_x[start];
-        let sy = positions_y[start];
-        let ex = positions_x[end];
-        let ey = positions_y[end];
-        let dx = (ex as i128) - (sx as i128);
-        let dy = (ey as i128) - (sy as i128);
-        let llsq = dx * dx + dy * dy;
+        let line = C::line(
+            (positions_x[start], positions_y[start]),
+            (positions_x[end], positions_y[end]),
+        );
         let mut i = start + 1;
         while i + 7 < end {
             let xs = &positions_x[i..i+8];
             let ys = &positions_y[i..i+8];
-            let ds: Vec<i64> = xs.iter().zip(ys.iter())
-                .map(|(&x, &y)| perpendicular_distance_squared(x, y, sx, sy, ex, ey, dx, dy, llsq))
-                .collect();
-            for (k, &d) in ds.iter().enumerate() {
+            for k in 0..8 {
+                let d = C::line_distance_squared(&line, (xs[k], ys[k]));
                 if d > max_distance { max_distance = d; max_index = i + k; }
             }
             i += 8;
@@ -77,7 +341,7 @@ fn douglas_peucker_iterative(
         let rem = end - i;
         if rem >= 4 {
             for (k, (&x, &y)) in positions_x[i..i+4].iter().zip(&positions_y[i..i+4]).enumerate() {
-                let d = perpendicular_distance_squared(x, y, sx, sy, ex, ey, dx, dy, llsq);
+                let d = C::line_distance_squared(&line, (x, y));
                 if d > max_distance { max_distance = d; max_index = i + k; }
             }
             i += 4;
@@ -87,7 +351,7 @@ fn douglas_peucker_iterative(
             .zip(&positions_y[i..end])
             .enumerate()
             .for_each(|(offset, (&x, &y))| {
-                let d = perpendicular_distance_squared(x, y, sx, sy, ex, ey, dx, dy, llsq);
+                let d = C::line_distance_squared(&line, (x, y));
                 if d > max_distance {
                     max_distance = d;
                     max_index = i + offset;
@@ -96,14 +360,22 @@ fn douglas_peucker_iterative(
         // End inlined find_max_distance
         if max_distance > epsilon_squared {
             result[max_index] = true;
-            stack.push((start, max_index));
-            stack.push((max_index, end));
+            if order == SplitOrder::ShorterHalf && max_index - start < end - max_index {
+                stack.push(max_index, end);
+                stack.push(start, max_index);
+            } else {
+                stack.push(start, max_index);
+                stack.push(max_index, end);
+            }
         }
     }
 }
 
 /// Simplify a sequence of points using the Douglas-Peucker algorithm.
 ///
+/// The coordinates can be of any `Coordinate` type: `i64` like the trajectories, but also
+/// `i32`, `f32` or `f64`.
+///
 /// # Arguments
 ///
 /// * `positions_x` - A slice of x coordinates
@@ -112,7 +384,22 @@ fn douglas_peucker_iterative(
 ///
 /// # Returns
 ///
-/// A vector of booleans indicating which points to keep in the simplified path
+/// A vector of booleans indicating which points to keep in the simplified path.
+/// The first and last points are always kept; see `Shape` for degenerate inputs.
+///
+/// # Panics
+///
+/// This function will panic if:
+/// * `positions_x` and `positions_y` have different lengths
+/// * `epsilon` is negative
+#[inline(always)]
+pub fn simplify<C: Coordinate>(positions_x: &[C], positions_y: &[C], epsilon: C) -> Vec<bool> {
+    simplify_with_order(positions_x, positions_y, epsilon, SplitOrder::default())
+}
+
+/// Simplify a sequence of points, processing the segments left to split in the given order.
+///
+/// The result is the same as `simplify` for every order.
 ///
 /// # Panics
 ///
@@ -120,13 +407,30 @@ fn douglas_peucker_iterative(
 /// * `positions_x` and `positions_y` have different lengths
 /// * `epsilon` is negative
 #[inline(always)]
-pub fn simplify(positions_x: &[i64], positions_y: &[i64], epsilon: i64) -> Vec<bool> {
+pub fn simplify_with_order<C: Coordinate>(
+    positions_x: &[C],
+    positions_y: &[C],
+    epsilon: C,
+    order: SplitOrder,
+) -> Vec<bool> {
+    simplify_with_worklist(positions_x, positions_y, epsilon, order, &mut Worklist::new(order))
+}
+
+/// Simplify a sequence of points with a worklist reused across calls, which must be empty
+/// and created for `order`. It is left empty on return.
+fn simplify_with_worklist<C: Coordinate>(
+    positions_x: &[C],
+    positions_y: &[C],
+    epsilon: C,
+    order: SplitOrder,
+    worklist: &mut Worklist,
+) -> Vec<bool> {
     assert_eq!(
         positions_x.len(),
         positions_y.len(),
         "positions_x.len() == positions_y.len()"
     );
-    assert!(epsilon >= 0, "epsilon must be non-negative");
+    assert!(!epsilon.is_negative(), "epsilon must be non-negative");
 
     if positions_x.len() <= 2 {
         return vec![true; positions_x.len()];
@@ -136,62 +440,1913 @@ pub fn simplify(positions_x: &[i64], positions_y: &[i64], epsilon: i64) -> Vec<b
     result[0] = true;
     result[positions_x.len() - 1] = true;
 
-    douglas_peucker_iterative(positions_x, positions_y, epsilon, &mut result);
+    douglas_peucker_iterative(positions_x, positions_y, epsilon, order, worklist, &mut result);
 
     result
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Why a point is kept by a simplification, for debugging surprising results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeepReason {
+    /// First or last point
+    Endpoint,
+    /// Boundary between runs of points with different tolerances (see `simplify_piecewise`)
+    ToleranceChange,
+    /// One of the two farthest points a loop is anchored at (see `LoopAnchors::Diameter`)
+    LoopAnchor,
+    /// Farthest point from a segment beyond the tolerance, splitting it at the given depth
+    /// of the recursion, 1 for the split of the whole sequence
+    Split { depth: usize },
+    /// Added after the simplification by a constraint
+    Constraint(Constraint),
+}
 
-    #[test]
-    fn test_simplify_empty() {
-        let result = simplify(&[], &[], 1);
-        assert_eq!(result, vec![]);
+/// Constraint keeping points after the simplification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Constraint {
+    /// See `ensure_min_points`
+    MinPoints,
+    /// See `keep_extrema`
+    Extrema,
+    /// See `keep_sharpest_turns`
+    SharpestTurn,
+}
+
+impl KeepReason {
+    /// Name used in reports
+    pub fn name(self) -> &'static str {
+        match self {
+            KeepReason::Endpoint => "endpoint",
+            KeepReason::ToleranceChange => "tolerance-change",
+            KeepReason::LoopAnchor => "loop-anchor",
+            KeepReason::Split { .. } => "split",
+            KeepReason::Constraint(Constraint::MinPoints) => "min-points",
+            KeepReason::Constraint(Constraint::Extrema) => "extrema",
+            KeepReason::Constraint(Constraint::SharpestTurn) => "sharpest-turn",
+        }
     }
+}
 
-    #[test]
-    fn test_simplify_single_point() {
-        let result = simplify(&[1], &[1], 1);
-        assert_eq!(result, vec![true]);
+/// Simplify a sequence of points like `simplify`, recording why each point is kept.
+///
+/// The points kept are those of `simplify`, with `None` for the points dropped. This is
+/// slower than `simplify`, and meant for debugging.
+///
+/// # Panics
+///
+/// This function will panic if:
+/// * `positions_x` and `positions_y` have different lengths
+/// * `epsilon` is negative
+pub fn explain<C: Coordinate>(
+    positions_x: &[C],
+    positions_y: &[C],
+    epsilon: C,
+) -> Vec<Option<KeepReason>> {
+    assert_eq!(
+        positions_x.len(),
+        positions_y.len(),
+        "positions_x.len() == positions_y.len()"
+    );
+    assert!(!epsilon.is_negative(), "epsilon must be non-negative");
+
+    let len = positions_x.len();
+    let mut reasons = vec![None; len];
+    if len == 0 {
+        return reasons;
     }
+    reasons[0] = Some(KeepReason::Endpoint);
+    reasons[len - 1] = Some(KeepReason::Endpoint);
 
-    #[test]
-    fn test_simplify_two_points() {
-        let result = simplify(&[1, 2], &[1, 2], 1);
-        assert_eq!(result, vec![true, true]);
+    let epsilon_squared = epsilon.squared();
+    let mut stack = vec![(0, len - 1, 1)];
+    while let Some((start, end, depth)) = stack.pop() {
+        if end - start <= 1 {
+            continue;
+        }
+        let line = C::line(
+            (positions_x[start], positions_y[start]),
+            (positions_x[end], positions_y[end]),
+        );
+        let mut max_distance = C::Squared::default();
+        let mut max_index = start;
+        for i in start + 1..end {
+            let d = C::line_distance_squared(&line, (positions_x[i], positions_y[i]));
+            if d > max_distance {
+                max_distance = d;
+                max_index = i;
+            }
+        }
+        if max_distance > epsilon_squared {
+            reasons[max_index] = Some(KeepReason::Split { depth });
+            stack.push((start, max_index, depth + 1));
+            stack.push((max_index, end, depth + 1));
+        }
     }
 
-    #[test]
-    fn test_simplify_straight_line() {
-        // A straight line of 5 points
-        let x = vec![0, 1, 2, 3, 4];
-        let y = vec![0, 1, 2, 3, 4];
-        let result = simplify(&x, &y, 1);
-        // Should only keep first and last points
-        assert_eq!(result, vec![true, false, false, false, true]);
+    reasons
+}
+
+/// Records `constraint` as the reason of the points kept in `keep` without a reason yet,
+/// after a constraint such as `keep_extrema` added them.
+///
+/// # Panics
+///
+/// This function will panic if `reasons` and `keep` have different lengths.
+pub fn record_constraint(
+    reasons: &mut [Option<KeepReason>],
+    keep: &[bool],
+    constraint: Constraint,
+) {
+    assert_eq!(reasons.len(), keep.len(), "reasons.len() == keep.len()");
+    for (reason, &keep) in reasons.iter_mut().zip(keep) {
+        if keep && reason.is_none() {
+            *reason = Some(KeepReason::Constraint(constraint));
+        }
     }
+}
 
-    #[test]
-    fn test_simplify_zigzag() {
-        // A zigzag pattern with more pronounced changes
-        let x = vec![0, 1, 2, 3, 4];
-        let y = vec![0, 5, 0, 5, 0]; // Increased amplitude for more significant changes
-        let result = simplify(&x, &y, 1);
-        // With a small epsilon, we should keep all points due to the significant changes
-        assert_eq!(result, vec![true, true, true, true, true]);
+/// Copies the reasons of a part of the sequence starting at `start`, explained on its own,
+/// keeping the reasons already recorded for its ends, which it shares with its neighbors.
+fn merge_reasons(reasons: &mut [Option<KeepReason>], start: usize, part: Vec<Option<KeepReason>>) {
+    for (offset, reason) in part.into_iter().enumerate() {
+        if reasons[start + offset].is_none() {
+            reasons[start + offset] = reason;
+        }
     }
+}
 
-    #[test]
-    #[should_panic(expected = "epsilon must be non-negative")]
-    fn test_simplify_negative_epsilon() {
-        simplify(&[1, 2], &[1, 2], -1);
+/// Find the point of `start + 1..end` farthest from the line between `start` and `end`.
+///
+/// Returns the squared distance and the index of that point.
+pub(crate) fn max_distance(positions_x: &[i64], positions_y: &[i64], start: usize, end: usize) -> (i64, usize) {
+    let (sx, sy) = (positions_x[start], positions_y[start]);
+    let (ex, ey) = (positions_x[end], positions_y[end]);
+    let dx = (ex as i128) - (sx as i128);
+    let dy = (ey as i128) - (sy as i128);
+    let llsq = dx * dx + dy * dy;
+
+    let mut max = (0, start + 1);
+    for i in start + 1..end {
+        let d = perpendicular_distance_squared(positions_x[i], positions_y[i], sx, sy, ex, ey, dx, dy, llsq);
+        if d > max.0 {
+            max = (d, i);
+        }
     }
+    max
+}
 
-    #[test]
-    #[should_panic(expected = "positions_x.len() == positions_y.len()")]
-    fn test_simplify_mismatched_lengths() {
-        simplify(&[1, 2], &[1], 1);
+/// Keep additional points until at least `min_points` points are kept.
+///
+/// Points are added in Douglas-Peucker priority order: the point farthest from the
+/// segment between its two kept neighbors is added first. This prevents long trips
+/// from being reduced to a straight line when the epsilon is large.
+///
+/// # Panics
+///
+/// This function will panic if `positions_x`, `positions_y` and `keep` have different lengths.
+pub fn ensure_min_points(positions_x: &[i64], positions_y: &[i64], keep: &mut [bool], min_points: usize) {
+    assert_eq!(
+        positions_x.len(),
+        positions_y.len(),
+        "positions_x.len() == positions_y.len()"
+    );
+    assert_eq!(positions_x.len(), keep.len(), "positions_x.len() == keep.len()");
+
+    let mut kept = keep.iter().filter(|&&k| k).count();
+    if kept >= min_points {
+        return;
+    }
+
+    // Candidate splits, largest distance first, earliest index on ties
+    let mut heap = BinaryHeap::new();
+    let push = |heap: &mut BinaryHeap<_>, start: usize, end: usize| {
+        if end - start > 1 {
+            let (distance, index) = max_distance(positions_x, positions_y, start, end);
+            heap.push((distance, Reverse(index), start, end));
+        }
+    };
+
+    let kept_indices: Vec<usize> = (0..keep.len()).filter(|&i| keep[i]).collect();
+    for pair in kept_indices.windows(2) {
+        push(&mut heap, pair[0], pair[1]);
+    }
+
+    while kept < min_points {
+        let Some((_, Reverse(index), start, end)) = heap.pop() else {
+            break;
+        };
+        keep[index] = true;
+        kept += 1;
+        push(&mut heap, start, index);
+        push(&mut heap, index, end);
+    }
+}
+
+/// Simplify a sequence of points with the Visvalingam-Whyatt algorithm.
+///
+/// Points are removed one at a time, the one whose triangle with its two remaining
+/// neighbors has the smallest area first, until every remaining point has an effective area
+/// of at least `min_area` squared coordinate units. The effective area of a point never
+/// drops below the area of a point removed before it, so that points are removed in order
+/// of significance. The result is a keep mask, like `simplify`; `min_area` of `epsilon`
+/// squared gives a comparable level of detail.
+///
+/// Removing by area instead of distance drops small spikes and keeps broad curves, which
+/// often gives smoother lines on maps.
+///
+/// # Panics
+///
+/// This function will panic if:
+/// * `positions_x` and `positions_y` have different lengths
+/// * `min_area` is negative
+pub fn simplify_visvalingam(positions_x: &[i64], positions_y: &[i64], min_area: i64) -> Vec<bool> {
+    assert_eq!(
+        positions_x.len(),
+        positions_y.len(),
+        "positions_x.len() == positions_y.len()"
+    );
+    assert!(min_area >= 0, "min_area must be non-negative");
+
+    let len = positions_x.len();
+    let mut keep = vec![true; len];
+    if len <= 2 {
+        return keep;
+    }
+
+    // Twice the area of the triangle of a point with its neighbors
+    let double_area = |previous: usize, i: usize, next: usize| {
+        let (ax, ay) = (positions_x[previous] as i128, positions_y[previous] as i128);
+        let (bx, by) = (positions_x[i] as i128, positions_y[i] as i128);
+        let (cx, cy) = (positions_x[next] as i128, positions_y[next] as i128);
+        ((bx - ax) * (cy - ay) - (by - ay) * (cx - ax)).abs()
+    };
+    let threshold = 2 * min_area as i128;
+
+    // Remaining neighbors of every point, and its current effective area
+    let mut previous: Vec<usize> = (0..len).map(|i| i.saturating_sub(1)).collect();
+    let mut next: Vec<usize> = (0..len).map(|i| i + 1).collect();
+    let mut areas: Vec<i128> = (0..len)
+        .map(|i| match i {
+            0 => 0,
+            i if i == len - 1 => 0,
+            i => double_area(i - 1, i, i + 1),
+        })
+        .collect();
+    let mut heap: BinaryHeap<_> = (1..len - 1).map(|i| Reverse((areas[i], i))).collect();
+
+    while let Some(Reverse((area, i))) = heap.pop() {
+        // Skip the entries of removed points and outdated areas
+        if !keep[i] || area != areas[i] {
+            continue;
+        }
+        if area >= threshold {
+            break;
+        }
+        keep[i] = false;
+        let (before, after) = (previous[i], next[i]);
+        next[before] = after;
+        previous[after] = before;
+        for neighbor in [before, after] {
+            if neighbor == 0 || neighbor == len - 1 {
+                continue;
+            }
+            areas[neighbor] = double_area(previous[neighbor], neighbor, next[neighbor]).max(area);
+            heap.push(Reverse((areas[neighbor], neighbor)));
+        }
+    }
+
+    keep
+}
+
+/// Result of `simplify_anytime`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnytimeSimplification {
+    pub keep: Vec<bool>,
+    /// Whether every split was made, so that `keep` is the result of `simplify`
+    pub complete: bool,
+    /// Largest distance in coordinate units of a dropped point to its simplified segment,
+    /// at most `epsilon` when complete
+    pub error: f64,
+}
+
+/// Simplify a sequence of points within a time budget, for interactive uses with strict
+/// latency requirements.
+///
+/// Segments are split in priority order, the farthest point from its segment first, so
+/// that stopping early still keeps the points that matter the most. When the budget runs
+/// out, the points kept so far are returned with the error left; otherwise, the result is
+/// that of `simplify`.
+///
+/// # Panics
+///
+/// This function will panic if:
+/// * `positions_x` and `positions_y` have different lengths
+/// * `epsilon` is negative
+pub fn simplify_anytime(
+    positions_x: &[i64],
+    positions_y: &[i64],
+    epsilon: i64,
+    budget: Duration,
+) -> AnytimeSimplification {
+    let deadline = Instant::now().checked_add(budget);
+    simplify_prioritized(positions_x, positions_y, epsilon, || {
+        deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    })
+}
+
+/// Keep the `k` most important points of a sequence, the endpoints included.
+///
+/// Unlike `simplify`, which splits every segment beyond a tolerance, the segments are
+/// split in global priority order, the farthest point from its segment first, and the
+/// first `k - 2` splits are made. With as many points as `simplify` keeps for a tolerance,
+/// the result is the same.
+///
+/// The endpoints are kept even when `k` is lower than 2, and fewer than `k` points are
+/// kept when the others all lie on their simplified segments.
+///
+/// # Panics
+///
+/// This function will panic if `positions_x` and `positions_y` have different lengths.
+pub fn simplify_top_k(positions_x: &[i64], positions_y: &[i64], k: usize) -> Vec<bool> {
+    let mut kept = positions_x.len().min(2);
+    simplify_prioritized(positions_x, positions_y, 0, || {
+        kept += 1;
+        kept > k
+    })
+    .keep
+}
+
+/// Simplify a sequence of points to exactly `n` points, or every point when there are
+/// fewer, for storage with a fixed number of points per trajectory.
+///
+/// The points are those of `simplify_top_k`, in order of importance. When the points left
+/// all lie on their simplified segments, evenly spaced ones among them are added to reach
+/// the count. The first point is kept when `n` is 1, and the last one too from 2 on.
+///
+/// # Panics
+///
+/// This function will panic if `positions_x` and `positions_y` have different lengths.
+pub fn simplify_to_count(positions_x: &[i64], positions_y: &[i64], n: usize) -> Vec<bool> {
+    let len = positions_x.len();
+    let mut keep = simplify_top_k(positions_x, positions_y, n);
+    match n {
+        0 => keep.fill(false),
+        1 => keep.iter_mut().skip(1).for_each(|kept| *kept = false),
+        _ => {
+            let dropped: Vec<usize> = (0..len).filter(|&i| !keep[i]).collect();
+            let missing = n.min(len) - (len - dropped.len());
+            for j in 0..missing {
+                keep[dropped[(j + 1) * dropped.len() / (missing + 1)]] = true;
+            }
+        }
+    }
+    keep
+}
+
+/// Relative tolerance of `find_epsilon_for_ratio` on the ratio of points kept
+pub const RATIO_TOLERANCE: f64 = 0.05;
+
+/// Find the tolerance with which `simplify` keeps a given ratio of the points of a
+/// trajectory, in `(0, 1]`.
+///
+/// Binary-searches the tolerance until the ratio of points kept is within
+/// `RATIO_TOLERANCE` of `target_ratio`, relatively. When no tolerance gets that close,
+/// such as for a ratio below that of the endpoints alone, the tolerance with the closest
+/// ratio is returned, the smallest one on ties.
+///
+/// # Panics
+///
+/// This function will panic if `target_ratio` is not in `(0, 1]`.
+pub fn find_epsilon_for_ratio(trajectory: &Trajectory, target_ratio: f64) -> i64 {
+    assert!(
+        target_ratio > 0.0 && target_ratio <= 1.0,
+        "target_ratio must be in (0, 1]"
+    );
+    let (latitudes, longitudes) = (&trajectory.latitudes, &trajectory.longitudes);
+    let len = latitudes.len();
+    if len == 0 {
+        return 0;
+    }
+    let ratio = |epsilon: i64| {
+        let kept = simplify(latitudes, longitudes, epsilon);
+        kept.iter().filter(|&&kept| kept).count() as f64 / len as f64
+    };
+    let range = |values: &[i64]| values.iter().max().unwrap() - values.iter().min().unwrap();
+
+    // No point is farther from a segment than the diagonal of their bounding box
+    let (mut low, mut high) = (0, 2 * range(latitudes).max(range(longitudes)) + 1);
+    let mut best = (f64::INFINITY, 0);
+    while low <= high {
+        let epsilon = low + (high - low) / 2;
+        let kept = ratio(epsilon);
+        let error = (kept - target_ratio).abs();
+        if error < best.0 || (error == best.0 && epsilon < best.1) {
+            best = (error, epsilon);
+        }
+        if error <= target_ratio * RATIO_TOLERANCE {
+            break;
+        }
+        // Fewer points are kept with larger tolerances
+        if kept > target_ratio {
+            low = epsilon + 1;
+        } else {
+            high = epsilon - 1;
+        }
+    }
+    best.1
+}
+
+/// Splits segments in priority order until no point is farther than `epsilon` from its
+/// segment, or until `stop` returns `true`, which is called before each split.
+fn simplify_prioritized(
+    positions_x: &[i64],
+    positions_y: &[i64],
+    epsilon: i64,
+    mut stop: impl FnMut() -> bool,
+) -> AnytimeSimplification {
+    assert_eq!(
+        positions_x.len(),
+        positions_y.len(),
+        "positions_x.len() == positions_y.len()"
+    );
+    assert!(epsilon >= 0, "epsilon must be non-negative");
+
+    let len = positions_x.len();
+    let mut keep = vec![false; len];
+    if let Some(last) = len.checked_sub(1) {
+        keep[0] = true;
+        keep[last] = true;
+    }
+
+    // Candidate splits beyond epsilon, largest distance first, earliest index on ties,
+    // and the largest squared distance of the segments within epsilon
+    let mut heap = BinaryHeap::new();
+    let mut settled = 0;
+    let mut push = |heap: &mut BinaryHeap<_>, start: usize, end: usize| {
+        if end - start > 1 {
+            let (distance, index) = max_distance(positions_x, positions_y, start, end);
+            if distance > epsilon * epsilon {
+                heap.push((distance, Reverse(index), start, end));
+            } else {
+                settled = settled.max(distance);
+            }
+        }
+    };
+    if len > 2 {
+        push(&mut heap, 0, len - 1);
+    }
+
+    while let Some(&(distance, Reverse(index), start, end)) = heap.peek() {
+        if stop() {
+            return AnytimeSimplification {
+                keep,
+                complete: false,
+                error: (distance as f64).sqrt(),
+            };
+        }
+        heap.pop();
+        keep[index] = true;
+        push(&mut heap, start, index);
+        push(&mut heap, index, end);
+    }
+
+    AnytimeSimplification {
+        keep,
+        complete: true,
+        error: (settled as f64).sqrt(),
+    }
+}
+
+/// Keep the extrema of the sequence: the points with the lowest and the highest value of
+/// each coordinate, the first one on ties.
+///
+/// The simplified sequence then has the bounding box of the original one, its northernmost,
+/// southernmost, easternmost and westernmost points.
+///
+/// # Panics
+///
+/// This function will panic if `positions_x`, `positions_y` and `keep` have different lengths.
+pub fn keep_extrema(positions_x: &[i64], positions_y: &[i64], keep: &mut [bool]) {
+    assert_eq!(
+        positions_x.len(),
+        positions_y.len(),
+        "positions_x.len() == positions_y.len()"
+    );
+    assert_eq!(positions_x.len(), keep.len(), "positions_x.len() == keep.len()");
+
+    for positions in [positions_x, positions_y] {
+        let indices = 0..positions.len();
+        let lowest = indices.clone().min_by_key(|&i| (positions[i], i));
+        let highest = indices.max_by_key(|&i| (positions[i], Reverse(i)));
+        for i in lowest.into_iter().chain(highest) {
+            keep[i] = true;
+        }
+    }
+}
+
+/// Keep the `count` points where the sequence turns the most, measured by the angle between
+/// the segments from the previous point and to the next one. Points repeating the position
+/// of a neighbor do not turn.
+///
+/// # Panics
+///
+/// This function will panic if `positions_x`, `positions_y` and `keep` have different lengths.
+pub fn keep_sharpest_turns(positions_x: &[i64], positions_y: &[i64], keep: &mut [bool], count: usize) {
+    assert_eq!(
+        positions_x.len(),
+        positions_y.len(),
+        "positions_x.len() == positions_y.len()"
+    );
+    assert_eq!(positions_x.len(), keep.len(), "positions_x.len() == keep.len()");
+
+    let vector = |from: usize, to: usize| {
+        (
+            (positions_x[to] - positions_x[from]) as f64,
+            (positions_y[to] - positions_y[from]) as f64,
+        )
+    };
+    let mut turns: Vec<(f64, usize)> = (1..positions_x.len().saturating_sub(1))
+        .filter_map(|i| {
+            let (ax, ay) = vector(i - 1, i);
+            let (bx, by) = vector(i, i + 1);
+            let turn = (ax * by - ay * bx).atan2(ax * bx + ay * by).abs();
+            (turn > 0.0).then_some((turn, i))
+        })
+        .collect();
+    // Sharpest first, earliest index on ties
+    turns.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+    for &(_, i) in turns.iter().take(count) {
+        keep[i] = true;
+    }
+}
+
+/// Simplify a sequence of points with a tolerance that can change along the sequence.
+///
+/// Consecutive points sharing the same epsilon form a run, which is simplified on its own
+/// together with the first point of the next run. The boundaries between runs are always kept.
+///
+/// # Panics
+///
+/// This function will panic if:
+/// * `positions_x`, `positions_y` and `epsilons` have different lengths
+/// * an epsilon is negative
+pub fn simplify_piecewise(positions_x: &[i64], positions_y: &[i64], epsilons: &[i64]) -> Vec<bool> {
+    assert_eq!(
+        positions_x.len(),
+        epsilons.len(),
+        "positions_x.len() == epsilons.len()"
+    );
+
+    let len = positions_x.len();
+    let mut result = vec![false; len];
+    let mut start = 0;
+
+    while start < len {
+        let mut end = start;
+        while end + 1 < len && epsilons[end + 1] == epsilons[start] {
+            end += 1;
+        }
+        let stop = (end + 1).min(len - 1);
+
+        let keep = simplify(
+            &positions_x[start..=stop],
+            &positions_y[start..=stop],
+            epsilons[start],
+        );
+        for (offset, keep) in keep.into_iter().enumerate() {
+            result[start + offset] |= keep;
+        }
+
+        start = end + 1;
+    }
+
+    result
+}
+
+/// Simplify a sequence of points like `simplify_piecewise`, recording why each point is
+/// kept (see `explain`). Split depths are counted within each run.
+///
+/// # Panics
+///
+/// This function will panic if:
+/// * `positions_x`, `positions_y` and `epsilons` have different lengths
+/// * an epsilon is negative
+pub fn explain_piecewise(
+    positions_x: &[i64],
+    positions_y: &[i64],
+    epsilons: &[i64],
+) -> Vec<Option<KeepReason>> {
+    assert_eq!(
+        positions_x.len(),
+        epsilons.len(),
+        "positions_x.len() == epsilons.len()"
+    );
+
+    let len = positions_x.len();
+    let mut reasons = vec![None; len];
+    if len == 0 {
+        return reasons;
+    }
+    reasons[0] = Some(KeepReason::Endpoint);
+    reasons[len - 1] = Some(KeepReason::Endpoint);
+    let mut start = 0;
+
+    while start < len {
+        let mut end = start;
+        while end + 1 < len && epsilons[end + 1] == epsilons[start] {
+            end += 1;
+        }
+        let stop = (end + 1).min(len - 1);
+
+        if stop > start {
+            reasons[stop] = reasons[stop].or(Some(KeepReason::ToleranceChange));
+        }
+        let part = explain(
+            &positions_x[start..=stop],
+            &positions_y[start..=stop],
+            epsilons[start],
+        );
+        merge_reasons(&mut reasons, start, part);
+
+        start = end + 1;
+    }
+
+    reasons
+}
+
+/// Points a loop is anchored at before simplifying.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LoopAnchors {
+    /// The first and last points, like any other trajectory
+    #[default]
+    Endpoints,
+    /// The two points farthest apart, in addition to the first and last points. Anchoring
+    /// a loop at its own start and end measures the first split against a single position,
+    /// which keeps points on only one side of the loop.
+    Diameter,
+}
+
+/// Whether the trajectory ends within `tolerance` of where it starts, with at least one
+/// point farther than `tolerance` from the start.
+///
+/// # Panics
+///
+/// This function will panic if `positions_x` and `positions_y` have different lengths.
+pub fn is_loop(positions_x: &[i64], positions_y: &[i64], tolerance: i64) -> bool {
+    assert_eq!(
+        positions_x.len(),
+        positions_y.len(),
+        "positions_x.len() == positions_y.len()"
+    );
+
+    let (Some(&x0), Some(&y0)) = (positions_x.first(), positions_y.first()) else {
+        return false;
+    };
+    let tolerance_squared = (tolerance as i128) * (tolerance as i128);
+    let distance_squared = |x: i64, y: i64| {
+        let (dx, dy) = ((x - x0) as i128, (y - y0) as i128);
+        dx * dx + dy * dy
+    };
+
+    let len = positions_x.len();
+    distance_squared(positions_x[len - 1], positions_y[len - 1]) <= tolerance_squared
+        && positions_x
+            .iter()
+            .zip(positions_y)
+            .any(|(&x, &y)| distance_squared(x, y) > tolerance_squared)
+}
+
+/// Simplify a sequence of points, anchoring loops as given by `anchors`.
+///
+/// A trajectory is a loop when it ends within `epsilon` of where it starts (see `is_loop`).
+/// Other trajectories are simplified as by `simplify`.
+///
+/// # Panics
+///
+/// This function will panic if:
+/// * `positions_x` and `positions_y` have different lengths
+/// * `epsilon` is negative
+pub fn simplify_anchored(
+    positions_x: &[i64],
+    positions_y: &[i64],
+    epsilon: i64,
+    anchors: LoopAnchors,
+) -> Vec<bool> {
+    if anchors == LoopAnchors::Endpoints || !is_loop(positions_x, positions_y, epsilon) {
+        return simplify(positions_x, positions_y, epsilon);
+    }
+
+    let len = positions_x.len();
+    // A loop has at least two distinct positions
+    let (a, b) = geom::farthest_pair(positions_x, positions_y).unwrap();
+    let mut result = vec![false; len];
+    let mut worklist = Worklist::new(SplitOrder::default());
+    for (start, end) in [(0, a), (a, b), (b, len - 1)] {
+        let keep = simplify_with_worklist(
+            &positions_x[start..=end],
+            &positions_y[start..=end],
+            epsilon,
+            SplitOrder::default(),
+            &mut worklist,
+        );
+        for (offset, keep) in keep.into_iter().enumerate() {
+            result[start + offset] |= keep;
+        }
+    }
+
+    result
+}
+
+/// Simplify a sequence of points like `simplify_anchored`, recording why each point is
+/// kept (see `explain`). Split depths are counted between anchors.
+///
+/// # Panics
+///
+/// This function will panic if:
+/// * `positions_x` and `positions_y` have different lengths
+/// * `epsilon` is negative
+pub fn explain_anchored(
+    positions_x: &[i64],
+    positions_y: &[i64],
+    epsilon: i64,
+    anchors: LoopAnchors,
+) -> Vec<Option<KeepReason>> {
+    if anchors == LoopAnchors::Endpoints || !is_loop(positions_x, positions_y, epsilon) {
+        return explain(positions_x, positions_y, epsilon);
+    }
+
+    let len = positions_x.len();
+    let (a, b) = geom::farthest_pair(positions_x, positions_y).unwrap();
+    let mut reasons = vec![None; len];
+    reasons[0] = Some(KeepReason::Endpoint);
+    reasons[len - 1] = Some(KeepReason::Endpoint);
+    for anchor in [a, b] {
+        reasons[anchor] = reasons[anchor].or(Some(KeepReason::LoopAnchor));
+    }
+    for (start, end) in [(0, a), (a, b), (b, len - 1)] {
+        let part = explain(
+            &positions_x[start..=end],
+            &positions_y[start..=end],
+            epsilon,
+        );
+        merge_reasons(&mut reasons, start, part);
+    }
+
+    reasons
+}
+
+/// Simplify a sequence of points, each with its own tolerance.
+///
+/// A segment is split at the farthest of its points that is farther from it than its own
+/// epsilon. With the same epsilon for every point, this is the same as `simplify`.
+///
+/// # Panics
+///
+/// This function will panic if:
+/// * `positions_x`, `positions_y` and `epsilons` have different lengths
+/// * an epsilon is negative
+pub fn simplify_weighted(positions_x: &[i64], positions_y: &[i64], epsilons: &[i64]) -> Vec<bool> {
+    assert_eq!(
+        positions_x.len(),
+        positions_y.len(),
+        "positions_x.len() == positions_y.len()"
+    );
+    assert_eq!(
+        positions_x.len(),
+        epsilons.len(),
+        "positions_x.len() == epsilons.len()"
+    );
+    assert!(epsilons.iter().all(|&epsilon| epsilon >= 0), "epsilon must be non-negative");
+
+    let len = positions_x.len();
+    if len <= 2 {
+        return vec![true; len];
+    }
+
+    let mut result = vec![false; len];
+    result[0] = true;
+    result[len - 1] = true;
+
+    let mut stack = vec![(0, len - 1)];
+    while let Some((start, end)) = stack.pop() {
+        let (sx, sy) = (positions_x[start], positions_y[start]);
+        let (ex, ey) = (positions_x[end], positions_y[end]);
+        let dx = (ex as i128) - (sx as i128);
+        let dy = (ey as i128) - (sy as i128);
+        let llsq = dx * dx + dy * dy;
+
+        let farthest = (start + 1..end)
+            .map(|i| {
+                let d = perpendicular_distance_squared(positions_x[i], positions_y[i], sx, sy, ex, ey, dx, dy, llsq);
+                (d, i)
+            })
+            .filter(|&(d, i)| d > epsilons[i] * epsilons[i])
+            .max_by_key(|&(d, i)| (d, Reverse(i)));
+
+        if let Some((_, index)) = farthest {
+            result[index] = true;
+            stack.push((start, index));
+            stack.push((index, end));
+        }
+    }
+
+    result
+}
+
+/// Simplify a sequence of points in 3D using the Douglas-Peucker algorithm.
+///
+/// Distances are measured in 3D to the line through the ends of each segment, so that
+/// points where only the altitude changes, such as on a climb or a flight, are kept like
+/// any other turn. With all `positions_z` equal, this is the same as `simplify`.
+///
+/// # Panics
+///
+/// This function will panic if:
+/// * `positions_x`, `positions_y` and `positions_z` have different lengths
+/// * `epsilon` is negative
+pub fn simplify_3d(
+    positions_x: &[f64],
+    positions_y: &[f64],
+    positions_z: &[f64],
+    epsilon: f64,
+) -> Vec<bool> {
+    assert_eq!(
+        positions_x.len(),
+        positions_y.len(),
+        "positions_x.len() == positions_y.len()"
+    );
+    assert_eq!(
+        positions_x.len(),
+        positions_z.len(),
+        "positions_x.len() == positions_z.len()"
+    );
+    assert!(epsilon >= 0.0, "epsilon must be non-negative");
+
+    let len = positions_x.len();
+    if len <= 2 {
+        return vec![true; len];
+    }
+
+    let position = |i: usize| [positions_x[i], positions_y[i], positions_z[i]];
+    let mut result = vec![false; len];
+    result[0] = true;
+    result[len - 1] = true;
+    let epsilon_squared = epsilon * epsilon;
+    let mut stack = vec![(0, len - 1)];
+
+    while let Some((start, end)) = stack.pop() {
+        if end - start <= 1 {
+            continue;
+        }
+        let (a, b) = (position(start), position(end));
+        let direction = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+        let length_squared = dot(direction, direction);
+
+        let mut max_distance = 0.0;
+        let mut max_index = start;
+        for i in start + 1..end {
+            let p = position(i);
+            let offset = [p[0] - a[0], p[1] - a[1], p[2] - a[2]];
+            // Squared distance to the line, or to the start when both ends coincide
+            let d = if length_squared == 0.0 {
+                dot(offset, offset)
+            } else {
+                let cross = cross(offset, direction);
+                dot(cross, cross) / length_squared
+            };
+            if d > max_distance {
+                max_distance = d;
+                max_index = i;
+            }
+        }
+
+        if max_distance > epsilon_squared {
+            result[max_index] = true;
+            stack.push((start, max_index));
+            stack.push((max_index, end));
+        }
+    }
+
+    result
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+/// Simplify a trajectory in 3D with its altitudes, with a tolerance in meters.
+///
+/// Points are projected to east-north offsets in meters from the first point, with their
+/// altitude as the third coordinate (see `simplify_3d`). A trajectory without altitudes is
+/// simplified as if flat.
+///
+/// # Panics
+///
+/// This function will panic if `epsilon_m` is negative.
+pub fn simplify_trajectory_3d(trajectory: &Trajectory, epsilon_m: f64) -> Vec<bool> {
+    let len = trajectory.latitudes.len();
+    let origin = (
+        trajectory.latitudes.first().copied().unwrap_or_default(),
+        trajectory.longitudes.first().copied().unwrap_or_default(),
+    );
+    let (east, north): (Vec<f64>, Vec<f64>) = (0..len)
+        .map(|i| {
+            let point = (trajectory.latitudes[i], trajectory.longitudes[i]);
+            trajectory.coordinates.offset_m(origin, point)
+        })
+        .unzip();
+    let meters_per_unit = 10f64.powi(-(ALTITUDE_SCALE as i32));
+    let up: Vec<f64> = if trajectory.altitudes.is_empty() {
+        vec![0.0; len]
+    } else {
+        trajectory
+            .altitudes
+            .iter()
+            .map(|&altitude| altitude as f64 * meters_per_unit)
+            .collect()
+    };
+    simplify_3d(&east, &north, &up, epsilon_m)
+}
+
+/// Simplify a trajectory with the Top-Down Time-Ratio algorithm (TD-TR), a Douglas-Peucker
+/// variant measuring the Synchronized Euclidean Distance.
+///
+/// The distance of a point to a segment is measured to where the object would be at the
+/// time of the point if it moved along the segment at constant speed, instead of to the
+/// closest position on the line. Stops and changes of speed on a straight line are then
+/// kept, so that the simplified trajectory still tells where the object was at any time
+/// within `epsilon`.
+///
+/// Timestamps should be non-decreasing. Points of a segment whose ends share a timestamp
+/// are measured to its start.
+///
+/// # Panics
+///
+/// This function will panic if:
+/// * `latitudes`, `longitudes` and `timestamps` have different lengths
+/// * `epsilon` is negative
+pub fn simplify_tdtr(
+    latitudes: &[i64],
+    longitudes: &[i64],
+    timestamps: &[u64],
+    epsilon: i64,
+) -> Vec<bool> {
+    assert_eq!(
+        latitudes.len(),
+        longitudes.len(),
+        "latitudes.len() == longitudes.len()"
+    );
+    assert_eq!(
+        latitudes.len(),
+        timestamps.len(),
+        "latitudes.len() == timestamps.len()"
+    );
+    assert!(epsilon >= 0, "epsilon must be non-negative");
+
+    let len = latitudes.len();
+    if len <= 2 {
+        return vec![true; len];
+    }
+
+    let mut result = vec![false; len];
+    result[0] = true;
+    result[len - 1] = true;
+    let epsilon_squared = (epsilon as f64) * (epsilon as f64);
+    let mut stack = vec![(0, len - 1)];
+
+    while let Some((start, end)) = stack.pop() {
+        if end - start <= 1 {
+            continue;
+        }
+        let duration = timestamps[end].saturating_sub(timestamps[start]) as f64;
+        let d_lat = (latitudes[end] - latitudes[start]) as f64;
+        let d_lon = (longitudes[end] - longitudes[start]) as f64;
+
+        let mut max_distance = 0.0;
+        let mut max_index = start;
+        for i in start + 1..end {
+            let ratio = if duration > 0.0 {
+                timestamps[i].saturating_sub(timestamps[start]) as f64 / duration
+            } else {
+                0.0
+            };
+            let lat = (latitudes[i] - latitudes[start]) as f64 - d_lat * ratio;
+            let lon = (longitudes[i] - longitudes[start]) as f64 - d_lon * ratio;
+            let d = lat * lat + lon * lon;
+            if d > max_distance {
+                max_distance = d;
+                max_index = i;
+            }
+        }
+
+        if max_distance > epsilon_squared {
+            result[max_index] = true;
+            stack.push((start, max_index));
+            stack.push((max_index, end));
+        }
+    }
+
+    result
+}
+
+/// Index from which a simplification must be computed again after points were appended.
+///
+/// The previous last point was only kept for being last, so the segment ending there
+/// must be simplified again, from the second-to-last kept point.
+fn append_anchor(previous: &[bool]) -> usize {
+    previous
+        .iter()
+        .rposition(|&keep| keep)
+        .and_then(|last| previous[..last].iter().rposition(|&keep| keep))
+        .unwrap_or(0)
+}
+
+/// Update a simplification after points were appended to the sequence.
+///
+/// `previous` is the result of simplifying the points before the append. Only the tail
+/// from the second-to-last kept point onward is simplified again; the decisions before it
+/// are kept as is. The result may keep slightly more points than simplifying the whole
+/// sequence, but every dropped point is still within `epsilon` of the simplified path.
+///
+/// # Panics
+///
+/// This function will panic if:
+/// * `positions_x` and `positions_y` have different lengths
+/// * `previous` is longer than the sequence
+/// * `epsilon` is negative
+pub fn simplify_appended(
+    positions_x: &[i64],
+    positions_y: &[i64],
+    previous: &[bool],
+    epsilon: i64,
+) -> Vec<bool> {
+    assert_eq!(
+        positions_x.len(),
+        positions_y.len(),
+        "positions_x.len() == positions_y.len()"
+    );
+    assert!(previous.len() <= positions_x.len(), "previous.len() <= len");
+
+    let anchor = append_anchor(previous);
+    let mut result = previous[..anchor].to_vec();
+    result.extend(simplify(
+        &positions_x[anchor..],
+        &positions_y[anchor..],
+        epsilon,
+    ));
+    result
+}
+
+/// Update a simplification with one tolerance per point after points were appended.
+///
+/// This is `simplify_appended` with the splitting rule of `simplify_weighted`.
+///
+/// # Panics
+///
+/// This function will panic if:
+/// * `positions_x`, `positions_y` and `epsilons` have different lengths
+/// * `previous` is longer than the sequence
+/// * an epsilon is negative
+pub fn simplify_appended_weighted(
+    positions_x: &[i64],
+    positions_y: &[i64],
+    previous: &[bool],
+    epsilons: &[i64],
+) -> Vec<bool> {
+    assert!(previous.len() <= positions_x.len(), "previous.len() <= len");
+
+    let anchor = append_anchor(previous);
+    let mut result = previous[..anchor].to_vec();
+    result.extend(simplify_weighted(
+        &positions_x[anchor..],
+        &positions_y[anchor..],
+        &epsilons[anchor..],
+    ));
+    result
+}
+
+/// Borrowed coordinates of one trajectory, as given to `simplify_batch`.
+#[derive(Debug, Clone, Copy)]
+pub struct TrajectoryView<'a> {
+    pub positions_x: &'a [i64],
+    pub positions_y: &'a [i64],
+}
+
+impl<'a> From<&'a Trajectory> for TrajectoryView<'a> {
+    fn from(trajectory: &'a Trajectory) -> Self {
+        TrajectoryView {
+            positions_x: &trajectory.latitudes,
+            positions_y: &trajectory.longitudes,
+        }
+    }
+}
+
+/// Points to keep, one entry per point of a trajectory.
+pub type Mask = Vec<bool>;
+
+/// Options of `simplify_batch`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchOptions {
+    /// The maximum allowed distance between the original line and the simplified line
+    pub epsilon: i64,
+    /// Order in which segments are split
+    pub order: SplitOrder,
+    /// Number of threads simplifying trajectories
+    pub threads: usize,
+}
+
+impl BatchOptions {
+    /// Options using every available core with the default split order.
+    pub fn new(epsilon: i64) -> Self {
+        BatchOptions {
+            epsilon,
+            order: SplitOrder::default(),
+            threads: thread::available_parallelism().map_or(1, usize::from),
+        }
+    }
+}
+
+/// Simplifies many trajectories in parallel.
+///
+/// Threads take the next trajectory from a shared counter, so long and short trajectories
+/// balance out, and each thread reuses its worklist from one trajectory to the next.
+///
+/// # Returns
+///
+/// One mask per trajectory, in the order of `trajectories`, equal to what `simplify_with_order`
+/// returns for it.
+///
+/// # Panics
+///
+/// This function will panic if `options.threads` is zero, or for the reasons
+/// `simplify_with_order` does.
+pub fn simplify_batch(trajectories: &[TrajectoryView], options: BatchOptions) -> Vec<Mask> {
+    assert!(options.threads > 0, "threads > 0");
+
+    let next = AtomicUsize::new(0);
+    let threads = options.threads.min(trajectories.len());
+    let mut masks = vec![Vec::new(); trajectories.len()];
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads)
+            .map(|_| {
+                let next = &next;
+                scope.spawn(move || {
+                    let mut worklist = Worklist::new(options.order);
+                    let mut masks = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(view) = trajectories.get(index) else {
+                            break;
+                        };
+                        let mask = simplify_with_worklist(
+                            view.positions_x,
+                            view.positions_y,
+                            options.epsilon,
+                            options.order,
+                            &mut worklist,
+                        );
+                        masks.push((index, mask));
+                    }
+                    masks
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            // Propagate the panic of a thread, e.g. on mismatched lengths
+            let thread_masks = handle.join().unwrap_or_else(|panic| panic::resume_unwind(panic));
+            for (index, mask) in thread_masks {
+                masks[index] = mask;
+            }
+        }
+    });
+
+    masks
+}
+
+/// Simplifies many trajectories in parallel on the rayon thread pool, which uses every core,
+/// anchoring loops as given by `anchors`.
+///
+/// Unlike `simplify_batch`, the work is shared with any other rayon task of the process,
+/// such as the parsing of the next files.
+///
+/// # Returns
+///
+/// One mask per trajectory, in the order of `trajectories`, equal to what
+/// `simplify_anchored` returns for it.
+///
+/// # Panics
+///
+/// This function will panic for the reasons `simplify_anchored` does.
+#[cfg(feature = "rayon")]
+pub fn simplify_all_parallel(
+    trajectories: &[TrajectoryView],
+    epsilon: i64,
+    anchors: LoopAnchors,
+) -> Vec<Mask> {
+    use rayon::prelude::*;
+
+    trajectories
+        .par_iter()
+        .map(|view| simplify_anchored(view.positions_x, view.positions_y, epsilon, anchors))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trajectory::CoordinateSystem;
+
+    #[test]
+    fn test_simplify_empty() {
+        let result = simplify(&[], &[], 1);
+        assert_eq!(result, Vec::<bool>::new());
+    }
+
+    #[test]
+    fn test_simplify_single_point() {
+        let result = simplify(&[1], &[1], 1);
+        assert_eq!(result, vec![true]);
+    }
+
+    #[test]
+    fn test_simplify_two_points() {
+        let result = simplify(&[1, 2], &[1, 2], 1);
+        assert_eq!(result, vec![true, true]);
+    }
+
+    #[test]
+    fn test_simplify_straight_line() {
+        // A straight line of 5 points
+        let x = vec![0, 1, 2, 3, 4];
+        let y = vec![0, 1, 2, 3, 4];
+        let result = simplify(&x, &y, 1);
+        // Should only keep first and last points
+        assert_eq!(result, vec![true, false, false, false, true]);
+    }
+
+    #[test]
+    fn test_simplify_zigzag() {
+        // A zigzag pattern with more pronounced changes
+        let x = vec![0, 1, 2, 3, 4];
+        let y = vec![0, 5, 0, 5, 0]; // Increased amplitude for more significant changes
+        let result = simplify(&x, &y, 1);
+        // With a small epsilon, we should keep all points due to the significant changes
+        assert_eq!(result, vec![true, true, true, true, true]);
+    }
+
+    #[test]
+    fn test_simplify_coordinate_types() {
+        let x: Vec<i64> = (0..200).collect();
+        let y: Vec<i64> = x.iter().map(|&x| (x * x * 7) % 101 - 50).collect();
+        let expected = simplify(&x, &y, 20);
+
+        let as_i32 = |values: &[i64]| values.iter().map(|&v| v as i32).collect::<Vec<_>>();
+        assert_eq!(simplify(&as_i32(&x), &as_i32(&y), 20), expected);
+        let as_f64 = |values: &[i64]| values.iter().map(|&v| v as f64).collect::<Vec<_>>();
+        assert_eq!(simplify(&as_f64(&x), &as_f64(&y), 20.0), expected);
+        let as_f32 = |values: &[i64]| values.iter().map(|&v| v as f32).collect::<Vec<_>>();
+        assert_eq!(simplify(&as_f32(&x), &as_f32(&y), 20.0), expected);
+
+        // Degrees, with a tolerance finer than any integer one
+        let latitudes = [39.9, 39.90005, 39.9001, 39.9];
+        let longitudes = [116.3, 116.30001, 116.3, 116.3];
+        assert_eq!(
+            simplify(&latitudes, &longitudes, 0.00002),
+            vec![true, false, true, true]
+        );
+        assert_eq!(simplify(&latitudes, &longitudes, 0.000001), vec![true; 4]);
+
+        // Degrees scaled by 10^7, as in Google Takeout, across the world
+        let latitudes = [-899_999_999, 0, 899_999_999];
+        let longitudes = [-1_799_999_999, 1_799_999_999, -1_799_999_999];
+        assert_eq!(simplify(&latitudes, &longitudes, 1), vec![true; 3]);
+    }
+
+    #[test]
+    fn test_simplify_3d() {
+        let x: Vec<i64> = (0..200).collect();
+        let y: Vec<i64> = x.iter().map(|&x| (x * x * 7) % 101 - 50).collect();
+        let as_f64 = |values: &[i64]| values.iter().map(|&v| v as f64).collect::<Vec<_>>();
+        assert_eq!(
+            simplify_3d(&as_f64(&x), &as_f64(&y), &[3.0; 200], 20.0),
+            simplify(&x, &y, 20)
+        );
+
+        // A straight climb then a straight descent, flat on the map
+        let z = [0.0, 50.0, 100.0, 50.0, 0.0];
+        let flat = [0.0; 5];
+        let x = [0.0, 10.0, 20.0, 30.0, 40.0];
+        assert_eq!(
+            simplify(&x, &flat, 5.0),
+            vec![true, false, false, false, true]
+        );
+        assert_eq!(
+            simplify_3d(&x, &flat, &z, 5.0),
+            vec![true, false, true, false, true]
+        );
+        assert_eq!(simplify_3d(&[1.0], &[2.0], &[3.0], 0.0), vec![true]);
+    }
+
+    #[test]
+    fn test_simplify_trajectory_3d() {
+        let mut trajectory = Trajectory {
+            latitudes: vec![0; 5],
+            longitudes: vec![0, 10_000, 20_000, 30_000, 40_000],
+            timestamps: (0..5).collect(),
+            altitudes: vec![0, 50_000, 100_000, 50_000, 0],
+            coordinates: CoordinateSystem::Local { scale: 3 },
+        };
+        assert_eq!(
+            simplify_trajectory_3d(&trajectory, 5.0),
+            vec![true, false, true, false, true]
+        );
+        trajectory.altitudes.clear();
+        assert_eq!(
+            simplify_trajectory_3d(&trajectory, 5.0),
+            vec![true, false, false, false, true]
+        );
+    }
+
+    #[test]
+    fn test_simplify_tdtr() {
+        // A straight line, with a stop at 20 then a faster second half
+        let latitudes = [0, 10, 20, 20, 30, 40];
+        let longitudes = [0; 6];
+        let timestamps = [0, 10, 20, 80, 85, 90];
+        assert_eq!(
+            simplify(&latitudes, &longitudes, 1),
+            vec![true, false, false, false, false, true]
+        );
+        assert_eq!(
+            simplify_tdtr(&latitudes, &longitudes, &timestamps, 1),
+            vec![true, false, true, true, false, true]
+        );
+
+        // At constant speed on a line, nothing is kept but the endpoints
+        let timestamps = [0, 10, 20, 30, 40, 50];
+        let latitudes = [0, 10, 20, 30, 40, 50];
+        assert_eq!(
+            simplify_tdtr(&latitudes, &longitudes, &timestamps, 0),
+            vec![true, false, false, false, false, true]
+        );
+        assert_eq!(simplify_tdtr(&[1], &[2], &[3], 0), vec![true]);
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify(&[], &[]), Shape::Empty);
+        assert_eq!(classify(&[1], &[1]), Shape::SinglePoint);
+        assert_eq!(classify(&[1, 1], &[2, 2]), Shape::Stationary);
+        assert_eq!(classify(&[1, 1, 1], &[2, 2, 2]), Shape::Stationary);
+        assert_eq!(classify(&[0, 5, 0], &[0, 5, 0]), Shape::Closed);
+        assert_eq!(classify(&[0, 0, 1], &[0, 0, 1]), Shape::Open);
+    }
+
+    #[test]
+    fn test_simplify_stationary() {
+        let result = simplify(&[7; 6], &[3; 6], 0);
+        assert_eq!(result, vec![true, false, false, false, false, true]);
+    }
+
+    #[test]
+    fn test_simplify_two_identical_points() {
+        assert_eq!(simplify(&[7, 7], &[3, 3], 0), vec![true, true]);
+    }
+
+    #[test]
+    fn test_simplify_closed_loop() {
+        // A square loop: the farthest corner from the start is kept, then the
+        // two remaining corners split the sides.
+        let x = vec![0, 5, 10, 10, 10, 5, 0, 0, 0];
+        let y = vec![0, 0, 0, 5, 10, 10, 10, 5, 0];
+        let result = simplify(&x, &y, 1);
+        assert_eq!(
+            result,
+            vec![true, false, true, false, true, false, true, false, true]
+        );
+    }
+
+    #[test]
+    fn test_simplify_closed_loop_within_epsilon() {
+        let x = vec![0, 1, 0];
+        let y = vec![0, 1, 0];
+        assert_eq!(simplify(&x, &y, 2), vec![true, false, true]);
+        assert_eq!(simplify(&x, &y, 1), vec![true, true, true]);
+    }
+
+    #[test]
+    fn test_ensure_min_points() {
+        // A gentle arc, reduced to a straight line by a large epsilon
+        let x = vec![0, 1, 2, 3, 4, 5, 6];
+        let y = vec![0, 2, 3, 4, 3, 1, 0];
+        let mut keep = simplify(&x, &y, 10);
+        assert_eq!(keep.iter().filter(|&&k| k).count(), 2);
+
+        ensure_min_points(&x, &y, &mut keep, 3);
+        assert_eq!(keep, vec![true, false, false, true, false, false, true]);
+
+        ensure_min_points(&x, &y, &mut keep, 5);
+        assert_eq!(keep.iter().filter(|&&k| k).count(), 5);
+        assert!(keep[3]);
+    }
+
+    #[test]
+    fn test_ensure_min_points_more_than_available() {
+        let x = vec![0, 1, 2];
+        let y = vec![0, 0, 0];
+        let mut keep = simplify(&x, &y, 10);
+        ensure_min_points(&x, &y, &mut keep, 10);
+        assert_eq!(keep, vec![true, true, true]);
+    }
+
+    #[test]
+    fn test_ensure_min_points_already_satisfied() {
+        let x = vec![0, 1, 2, 3, 4];
+        let y = vec![0, 5, 0, 5, 0];
+        let mut keep = simplify(&x, &y, 1);
+        let expected = keep.clone();
+        ensure_min_points(&x, &y, &mut keep, 2);
+        assert_eq!(keep, expected);
+    }
+
+    #[test]
+    fn test_simplify_anytime() {
+        let x: Vec<i64> = (0..200).collect();
+        let y: Vec<i64> = x.iter().map(|&x| (x * x * 7) % 101 - 50).collect();
+        let complete = simplify_anytime(&x, &y, 20, Duration::MAX);
+        assert!(complete.complete);
+        assert_eq!(complete.keep, simplify(&x, &y, 20));
+        assert!(complete.error <= 20.0);
+
+        let none = simplify_anytime(&x, &y, 20, Duration::ZERO);
+        assert!(!none.complete);
+        assert_eq!(none.keep.iter().filter(|&&k| k).count(), 2);
+        assert!(none.error > 20.0);
+
+        // The peak, then the foot of its climb; the foot of its descent would come next
+        let x = [0, 10, 20, 30, 40, 50, 60];
+        let y = [0, -3, 0, 50, 0, 10, 0];
+        let mut splits = 0;
+        let partial = simplify_prioritized(&x, &y, 1, || {
+            splits += 1;
+            splits > 2
+        });
+        assert_eq!(partial.keep, [true, false, true, true, false, false, true]);
+        assert!((partial.error - 17.1).abs() < 0.1, "{partial:?}");
+        assert!(simplify_anytime(&[], &[], 1, Duration::MAX).keep.is_empty());
+    }
+
+    #[test]
+    fn test_simplify_top_k() {
+        let x = [0, 10, 20, 30, 40, 50, 60];
+        let y = [0, -3, 0, 50, 0, 10, 0];
+        assert_eq!(
+            simplify_top_k(&x, &y, 4),
+            [true, false, true, true, false, false, true]
+        );
+        assert_eq!(simplify_top_k(&x, &y, 0), simplify_top_k(&x, &y, 2));
+        assert_eq!(
+            simplify_top_k(&x, &y, 2),
+            [true, false, false, false, false, false, true]
+        );
+        assert_eq!(simplify_top_k(&x, &y, 100), [true; 7]);
+        // Points on their segments are never worth keeping
+        assert_eq!(
+            simplify_top_k(&[0, 1, 2], &[0, 1, 2], 3),
+            [true, false, true]
+        );
+        assert!(simplify_top_k(&[], &[], 3).is_empty());
+
+        // Every point kept with a tolerance is among the top ones with as many points
+        let x: Vec<i64> = (0..200).collect();
+        let y: Vec<i64> = x.iter().map(|&x| (x * x * 7) % 101 - 50).collect();
+        let keep = simplify(&x, &y, 20);
+        let count = keep.iter().filter(|&&k| k).count();
+        assert_eq!(simplify_top_k(&x, &y, count), keep);
+    }
+
+    #[test]
+    fn test_simplify_to_count() {
+        let x = [0, 10, 20, 30, 40, 50, 60];
+        let y = [0, -3, 0, 50, 0, 10, 0];
+        assert_eq!(simplify_to_count(&x, &y, 4), simplify_top_k(&x, &y, 4));
+        assert_eq!(
+            simplify_to_count(&x, &y, 1),
+            [true, false, false, false, false, false, false]
+        );
+        assert_eq!(simplify_to_count(&x, &y, 0), [false; 7]);
+        assert_eq!(simplify_to_count(&x, &y, 10), [true; 7]);
+
+        // A straight line still gets its count, evenly spread
+        let x: Vec<i64> = (0..9).collect();
+        assert_eq!(
+            simplify_to_count(&x, &x, 4),
+            [true, false, false, true, false, true, false, false, true]
+        );
+        for n in 0..12 {
+            let kept = simplify_to_count(&x, &x, n).iter().filter(|&&k| k).count();
+            assert_eq!(kept, n.min(9));
+        }
+        assert!(simplify_to_count(&[], &[], 3).is_empty());
+    }
+
+    #[test]
+    fn test_find_epsilon_for_ratio() {
+        let x: Vec<i64> = (0..1000).collect();
+        let y: Vec<i64> = x.iter().map(|&x| (x * x * 7) % 1001 - 500).collect();
+        let trajectory = Trajectory {
+            latitudes: x,
+            longitudes: y,
+            timestamps: (0..1000).collect(),
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+        let ratio = |epsilon| {
+            let keep = simplify(&trajectory.latitudes, &trajectory.longitudes, epsilon);
+            keep.iter().filter(|&&k| k).count() as f64 / 1000.0
+        };
+        for target in [0.5, 0.2, 0.05] {
+            let epsilon = find_epsilon_for_ratio(&trajectory, target);
+            assert!(
+                (ratio(epsilon) - target).abs() <= target * RATIO_TOLERANCE,
+                "{target}: {epsilon} keeps {}",
+                ratio(epsilon)
+            );
+        }
+        assert_eq!(find_epsilon_for_ratio(&trajectory, 1.0), 0);
+        // The endpoints are always kept
+        assert_eq!(ratio(find_epsilon_for_ratio(&trajectory, 0.0001)), 0.002);
+    }
+
+    #[test]
+    fn test_simplify_visvalingam() {
+        // A narrow spike at 2, then a broad bump from 4 to 6
+        let x = vec![0, 10, 11, 12, 30, 50, 70, 80];
+        let y = vec![0, 0, 30, 0, 20, 25, 20, 0];
+        assert_eq!(
+            simplify_visvalingam(&x, &y, 100),
+            vec![true, false, false, true, true, true, true, true]
+        );
+        assert_eq!(simplify_visvalingam(&x, &y, 0), vec![true; 8]);
+        assert_eq!(
+            simplify_visvalingam(&x, &y, 1_000_000),
+            [true, false, false, false, false, false, false, true]
+        );
+        // Douglas-Peucker keeps the spike instead
+        assert!(simplify(&x, &y, 10)[2]);
+        assert_eq!(simplify_visvalingam(&[1, 2], &[1, 2], 10), vec![true; 2]);
+    }
+
+    #[test]
+    fn test_keep_extrema() {
+        // A detour to the south, within epsilon
+        let x = vec![0, 10, 20, 30, 40];
+        let y = vec![0, -3, 0, 0, 0];
+        let mut keep = simplify(&x, &y, 5);
+        assert_eq!(keep, vec![true, false, false, false, true]);
+
+        keep_extrema(&x, &y, &mut keep);
+        assert_eq!(keep, vec![true, true, false, false, true]);
+
+        // The first point of a tie is kept
+        let mut keep = vec![false; 3];
+        keep_extrema(&[5, 5, 5], &[1, 1, 0], &mut keep);
+        assert_eq!(keep, vec![true, false, true]);
+    }
+
+    #[test]
+    fn test_keep_sharpest_turns() {
+        // A right angle at 2, a slight bend at 4, and a stop at 5
+        let x = vec![0, 10, 20, 20, 20, 21, 21, 22];
+        let y = vec![0, 0, 0, 10, 20, 30, 30, 40];
+        let mut keep = simplify(&x, &y, 50);
+        assert_eq!(keep.iter().filter(|&&k| k).count(), 2);
+
+        keep_sharpest_turns(&x, &y, &mut keep, 1);
+        assert!(keep[2]);
+        keep_sharpest_turns(&x, &y, &mut keep, 2);
+        assert!(keep[4]);
+        assert_eq!(keep.iter().filter(|&&k| k).count(), 4);
+
+        keep_sharpest_turns(&x, &y, &mut keep, 100);
+        assert!(!keep[1] && !keep[3]);
+        assert!(!keep[5] && !keep[6]);
+    }
+
+    #[test]
+    fn test_simplify_appended() {
+        let x: Vec<i64> = (0..40).collect();
+        let y: Vec<i64> = x.iter().map(|&x| (x * x) % 17).collect();
+        let epsilon = 3;
+
+        let mut keep = Vec::new();
+        for len in (5..=40).step_by(5) {
+            let previous = keep.clone();
+            keep = simplify_appended(&x[..len], &y[..len], &previous, epsilon);
+            assert_eq!(keep.len(), len);
+
+            // Decisions before the second-to-last kept point are unchanged
+            let kept: Vec<usize> = (0..previous.len()).filter(|&i| previous[i]).collect();
+            if kept.len() >= 2 {
+                let anchor = kept[kept.len() - 2];
+                assert_eq!(keep[..anchor], previous[..anchor]);
+            }
+        }
+
+        // Every dropped point is within epsilon of its simplified segment
+        let kept: Vec<usize> = (0..keep.len()).filter(|&i| keep[i]).collect();
+        for pair in kept.windows(2) {
+            let (distance, _) = max_distance(&x, &y, pair[0], pair[1]);
+            assert!(pair[1] - pair[0] < 2 || distance <= epsilon * epsilon);
+        }
+    }
+
+    #[test]
+    fn test_simplify_weighted() {
+        let x: Vec<i64> = (0..200).collect();
+        let y: Vec<i64> = x.iter().map(|&x| (x * x * 7) % 101 + x / 3).collect();
+
+        // Uniform tolerances behave like simplify
+        for epsilon in [0, 5, 20, 1000] {
+            assert_eq!(
+                simplify_weighted(&x, &y, &vec![epsilon; x.len()]),
+                simplify(&x, &y, epsilon)
+            );
+        }
+
+        // A large tolerance on the peak drops it, but not the foot of the bump, whose
+        // tolerance is small; the other foot then lies close enough to the new segment
+        let x = vec![0, 1, 2, 3, 4, 5, 6];
+        let y = vec![0, 0, 3, 9, 3, 0, 0];
+        assert_eq!(
+            simplify_weighted(&x, &y, &[1, 1, 1, 20, 1, 1, 1]),
+            vec![true, false, true, false, false, false, true]
+        );
+        assert_eq!(
+            simplify_weighted(&x, &y, &[20; 7]),
+            vec![true, false, false, false, false, false, true]
+        );
+    }
+
+    #[test]
+    fn test_simplify_appended_weighted() {
+        let x: Vec<i64> = (0..40).collect();
+        let y: Vec<i64> = x.iter().map(|&x| (x * x) % 17).collect();
+        let epsilons = vec![3; 40];
+
+        let mut weighted = Vec::new();
+        let mut uniform = Vec::new();
+        for len in (5..=40).step_by(5) {
+            weighted = simplify_appended_weighted(&x[..len], &y[..len], &weighted, &epsilons[..len]);
+            uniform = simplify_appended(&x[..len], &y[..len], &uniform, 3);
+            assert_eq!(weighted, uniform);
+        }
+    }
+
+    #[test]
+    fn test_simplify_appended_from_scratch() {
+        let x = vec![0, 1, 2, 3, 4];
+        let y = vec![0, 5, 0, 5, 0];
+        assert_eq!(simplify_appended(&x, &y, &[], 1), simplify(&x, &y, 1));
+    }
+
+    #[test]
+    fn test_simplify_with_order() {
+        let x: Vec<i64> = (0..200).collect();
+        let y: Vec<i64> = x.iter().map(|&x| (x * x * 7) % 101 + x / 3).collect();
+
+        for epsilon in [0, 5, 20, 1000] {
+            let expected = simplify(&x, &y, epsilon);
+            for order in SplitOrder::ALL {
+                assert_eq!(
+                    simplify_with_order(&x, &y, epsilon, order),
+                    expected,
+                    "{order:?} with epsilon {epsilon}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_simplify_piecewise() {
+        // Small bumps at every other point, tolerated only by the second run
+        let x = vec![0, 1, 2, 3, 4, 5, 6, 7, 8];
+        let y = vec![0, 3, 0, 3, 0, 3, 0, 3, 0];
+        let epsilons = vec![1, 1, 1, 1, 10, 10, 10, 10, 10];
+        let result = simplify_piecewise(&x, &y, &epsilons);
+        assert_eq!(
+            result,
+            vec![true, true, true, true, true, false, false, false, true]
+        );
+    }
+
+    #[test]
+    fn test_simplify_piecewise_uniform() {
+        let x = vec![0, 1, 2, 3, 4];
+        let y = vec![0, 5, 0, 5, 0];
+        assert_eq!(simplify_piecewise(&x, &y, &[1; 5]), simplify(&x, &y, 1));
+        assert_eq!(simplify_piecewise(&[], &[], &[]), Vec::<bool>::new());
+    }
+
+    #[test]
+    #[should_panic(expected = "epsilon must be non-negative")]
+    fn test_simplify_negative_epsilon() {
+        simplify(&[1, 2], &[1, 2], -1);
+    }
+
+    #[test]
+    #[should_panic(expected = "positions_x.len() == positions_y.len()")]
+    fn test_simplify_mismatched_lengths() {
+        simplify(&[1, 2], &[1], 1);
+    }
+
+    #[test]
+    fn test_simplify_batch() {
+        let x: Vec<Vec<i64>> = (0..20).map(|n| (0..n * 10).collect()).collect();
+        let y: Vec<Vec<i64>> = x
+            .iter()
+            .map(|x| x.iter().map(|&i| (i * 37) % 11).collect())
+            .collect();
+        let views: Vec<TrajectoryView> = x
+            .iter()
+            .zip(&y)
+            .map(|(positions_x, positions_y)| TrajectoryView { positions_x, positions_y })
+            .collect();
+
+        for order in SplitOrder::ALL {
+            let options = BatchOptions { epsilon: 3, order, threads: 3 };
+            let masks = simplify_batch(&views, options);
+            assert_eq!(masks.len(), views.len());
+            for (mask, view) in masks.iter().zip(&views) {
+                assert_eq!(*mask, simplify(view.positions_x, view.positions_y, 3));
+            }
+        }
+        assert!(simplify_batch(&[], BatchOptions::new(3)).is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn test_simplify_all_parallel() {
+        let (loop_x, loop_y) = square_loop();
+        let x: Vec<Vec<i64>> = (0..20).map(|n| (0..n * 10).collect()).collect();
+        let y: Vec<Vec<i64>> = x
+            .iter()
+            .map(|x| x.iter().map(|&i| (i * 37) % 11).collect())
+            .collect();
+        let mut views: Vec<TrajectoryView> = x
+            .iter()
+            .zip(&y)
+            .map(|(positions_x, positions_y)| TrajectoryView {
+                positions_x,
+                positions_y,
+            })
+            .collect();
+        views.push(TrajectoryView {
+            positions_x: &loop_x,
+            positions_y: &loop_y,
+        });
+
+        for anchors in [LoopAnchors::Endpoints, LoopAnchors::Diameter] {
+            let masks = simplify_all_parallel(&views, 3, anchors);
+            assert_eq!(masks.len(), views.len());
+            for (mask, view) in masks.iter().zip(&views) {
+                assert_eq!(
+                    *mask,
+                    simplify_anchored(view.positions_x, view.positions_y, 3, anchors)
+                );
+            }
+        }
+        assert!(simplify_all_parallel(&[], 3, LoopAnchors::Endpoints).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "positions_x.len() == positions_y.len()")]
+    fn test_simplify_batch_mismatched_lengths() {
+        let view = TrajectoryView { positions_x: &[1, 2], positions_y: &[1] };
+        simplify_batch(&[view], BatchOptions::new(1));
+    }
+
+    /// A square loop of side 100 starting and ending at the middle of its bottom side.
+    fn square_loop() -> (Vec<i64>, Vec<i64>) {
+        let mut x = vec![];
+        let mut y = vec![];
+        let corners = [(50, 0), (100, 0), (100, 100), (0, 100), (0, 0), (50, 0)];
+        for pair in corners.windows(2) {
+            let ((x1, y1), (x2, y2)) = (pair[0], pair[1]);
+            for step in 0..10 {
+                x.push(x1 + (x2 - x1) * step / 10);
+                y.push(y1 + (y2 - y1) * step / 10);
+            }
+        }
+        x.push(50);
+        y.push(0);
+        (x, y)
+    }
+
+    #[test]
+    fn test_is_loop() {
+        let (x, y) = square_loop();
+        assert!(is_loop(&x, &y, 1));
+        assert!(!is_loop(&x[..30], &y[..30], 1));
+        assert!(!is_loop(&[0, 0, 1], &[0, 0, 1], 5));
+        assert!(!is_loop(&[], &[], 1));
+    }
+
+    #[test]
+    fn test_simplify_anchored() {
+        let (x, y) = square_loop();
+        let keep = simplify_anchored(&x, &y, 1, LoopAnchors::Diameter);
+        let kept: Vec<(i64, i64)> = (0..x.len())
+            .filter(|&i| keep[i])
+            .map(|i| (x[i], y[i]))
+            .collect();
+        // Every corner is kept, with the endpoints
+        assert_eq!(
+            kept,
+            vec![(50, 0), (100, 0), (100, 100), (0, 100), (0, 0), (50, 0)]
+        );
+
+        assert_eq!(
+            simplify_anchored(&x, &y, 1, LoopAnchors::Endpoints),
+            simplify(&x, &y, 1)
+        );
+        let (open_x, open_y) = (&x[..30], &y[..30]);
+        assert_eq!(
+            simplify_anchored(open_x, open_y, 1, LoopAnchors::Diameter),
+            simplify(open_x, open_y, 1)
+        );
+    }
+
+    #[test]
+    fn test_explain() {
+        let is_kept = |reasons: &[Option<KeepReason>]| -> Vec<bool> {
+            reasons.iter().map(Option::is_some).collect()
+        };
+        let x: Vec<i64> = (0..200).collect();
+        let y: Vec<i64> = x.iter().map(|&x| (x * x * 7) % 101 - 50).collect();
+        assert_eq!(is_kept(&explain(&x, &y, 20)), simplify(&x, &y, 20));
+
+        let reasons = explain(&[0, 10, 20, 30, 40], &[0, 9, 10, 4, 0], 2);
+        assert_eq!(
+            reasons,
+            [
+                Some(KeepReason::Endpoint),
+                Some(KeepReason::Split { depth: 2 }),
+                Some(KeepReason::Split { depth: 1 }),
+                None,
+                Some(KeepReason::Endpoint),
+            ]
+        );
+        assert!(explain::<i64>(&[], &[], 1).is_empty());
+
+        let x = vec![0, 1, 2, 3, 4, 5, 6, 7, 8];
+        let y = vec![0, 3, 0, 3, 0, 3, 0, 3, 0];
+        let epsilons = vec![1, 1, 1, 1, 10, 10, 10, 10, 10];
+        let reasons = explain_piecewise(&x, &y, &epsilons);
+        assert_eq!(is_kept(&reasons), simplify_piecewise(&x, &y, &epsilons));
+        assert_eq!(reasons[4], Some(KeepReason::ToleranceChange));
+
+        let (x, y) = square_loop();
+        let reasons = explain_anchored(&x, &y, 1, LoopAnchors::Diameter);
+        assert_eq!(
+            is_kept(&reasons),
+            simplify_anchored(&x, &y, 1, LoopAnchors::Diameter)
+        );
+        let anchors = reasons
+            .iter()
+            .filter(|&&reason| reason == Some(KeepReason::LoopAnchor))
+            .count();
+        assert_eq!(anchors, 2);
+    }
+
+    #[test]
+    fn test_record_constraint() {
+        let x = vec![0, 10, 20, 30, 40];
+        let y = vec![0, -3, 0, 0, 0];
+        let mut reasons = explain(&x, &y, 5);
+        let mut keep: Vec<bool> = reasons.iter().map(Option::is_some).collect();
+        keep_extrema(&x, &y, &mut keep);
+        record_constraint(&mut reasons, &keep, Constraint::Extrema);
+        assert_eq!(
+            reasons,
+            [
+                Some(KeepReason::Endpoint),
+                Some(KeepReason::Constraint(Constraint::Extrema)),
+                None,
+                None,
+                Some(KeepReason::Endpoint),
+            ]
+        );
+        assert_eq!(reasons[1].unwrap().name(), "extrema");
     }
 }
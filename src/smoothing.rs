@@ -0,0 +1,167 @@
+//! One-Euro filter for smoothing GPS jitter before simplification.
+//!
+//! The One-Euro filter is a low-pass filter whose cutoff frequency increases with
+//! speed: slow movements are smoothed heavily (removing jitter while standing still),
+//! while fast movements are followed closely (avoiding lag when driving). It only
+//! needs the previous sample, so it can run point by point on a live feed.
+//!
+//! See Casiez et al., "1€ Filter: A Simple Speed-based Low-pass Filter for Noisy
+//! Input in Interactive Systems", CHI 2012.
+
+use crate::trajectory::Trajectory;
+use std::f64::consts::PI;
+
+/// Tuning parameters of the One-Euro filter.
+///
+/// Values are in the scaled units of `Trajectory` (microdegrees) and seconds.
+#[derive(Clone, Copy, Debug)]
+pub struct OneEuroParams {
+    /// Cutoff frequency in Hz when not moving; lower values remove more jitter
+    pub min_cutoff: f64,
+    /// Increase of the cutoff frequency per microdegree/second of speed; higher values reduce lag
+    pub beta: f64,
+    /// Cutoff frequency in Hz used to smooth the speed estimate
+    pub derivative_cutoff: f64,
+}
+
+impl Default for OneEuroParams {
+    fn default() -> Self {
+        OneEuroParams {
+            min_cutoff: 0.05,
+            beta: 0.001,
+            derivative_cutoff: 1.0,
+        }
+    }
+}
+
+/// Smoothing factor of an exponential low-pass filter with the given cutoff.
+fn alpha(cutoff: f64, dt: f64) -> f64 {
+    let tau = 1.0 / (2.0 * PI * cutoff);
+    1.0 / (1.0 + tau / dt)
+}
+
+/// One-Euro filter over a single scalar signal.
+#[derive(Clone, Debug)]
+pub struct OneEuroFilter {
+    params: OneEuroParams,
+    /// Last filtered value, filtered derivative and timestamp
+    previous: Option<(f64, f64, u64)>,
+}
+
+impl OneEuroFilter {
+    pub fn new(params: OneEuroParams) -> Self {
+        OneEuroFilter {
+            params,
+            previous: None,
+        }
+    }
+
+    /// Filters a new sample and returns the smoothed value.
+    ///
+    /// Samples whose timestamp is not after the previous one are ignored and the
+    /// previous smoothed value is returned.
+    pub fn filter(&mut self, value: f64, timestamp: u64) -> f64 {
+        let Some((previous, previous_derivative, previous_timestamp)) = self.previous else {
+            self.previous = Some((value, 0.0, timestamp));
+            return value;
+        };
+        if timestamp <= previous_timestamp {
+            return previous;
+        }
+
+        let dt = (timestamp - previous_timestamp) as f64;
+        let derivative = (value - previous) / dt;
+        let a = alpha(self.params.derivative_cutoff, dt);
+        let derivative = previous_derivative + a * (derivative - previous_derivative);
+
+        let cutoff = self.params.min_cutoff + self.params.beta * derivative.abs();
+        let a = alpha(cutoff, dt);
+        let smoothed = previous + a * (value - previous);
+
+        self.previous = Some((smoothed, derivative, timestamp));
+        smoothed
+    }
+}
+
+/// Smooths the latitudes and longitudes of a trajectory with two independent One-Euro filters.
+///
+/// Timestamps are kept unchanged.
+pub fn smooth_one_euro(trajectory: &Trajectory, params: OneEuroParams) -> Trajectory {
+    let mut latitude_filter = OneEuroFilter::new(params);
+    let mut longitude_filter = OneEuroFilter::new(params);
+    let mut smoothed = trajectory.clone();
+
+    for i in 0..trajectory.timestamps.len() {
+        let ts = trajectory.timestamps[i];
+        smoothed.latitudes[i] = latitude_filter
+            .filter(trajectory.latitudes[i] as f64, ts)
+            .round() as i64;
+        smoothed.longitudes[i] = longitude_filter
+            .filter(trajectory.longitudes[i] as f64, ts)
+            .round() as i64;
+    }
+
+    smoothed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trajectory::CoordinateSystem;
+
+    #[test]
+    fn test_filter_first_sample_passes_through() {
+        let mut filter = OneEuroFilter::new(OneEuroParams::default());
+        assert_eq!(filter.filter(42.0, 10), 42.0);
+    }
+
+    #[test]
+    fn test_filter_reduces_jitter_when_stationary() {
+        let mut filter = OneEuroFilter::new(OneEuroParams::default());
+        let outputs: Vec<f64> = (0..100)
+            .map(|i| filter.filter(if i % 2 == 0 { 50.0 } else { -50.0 }, i))
+            .collect();
+
+        assert!(outputs[10..].iter().all(|v| v.abs() < 25.0));
+    }
+
+    #[test]
+    fn test_filter_follows_fast_movement() {
+        let params = OneEuroParams::default();
+        let mut adaptive = OneEuroFilter::new(params);
+        let mut fixed = OneEuroFilter::new(OneEuroParams { beta: 0.0, ..params });
+
+        let mut last = (0.0, 0.0);
+        for i in 0..30 {
+            // 150 microdegrees per second, about 15 m/s
+            let value = 150.0 * i as f64;
+            last = (adaptive.filter(value, i), fixed.filter(value, i));
+        }
+        let target = 150.0 * 29.0;
+        assert!((target - last.0).abs() < (target - last.1).abs());
+    }
+
+    #[test]
+    fn test_filter_ignores_non_increasing_timestamps() {
+        let mut filter = OneEuroFilter::new(OneEuroParams::default());
+        filter.filter(0.0, 10);
+        assert_eq!(filter.filter(100.0, 10), 0.0);
+        assert_eq!(filter.filter(100.0, 5), 0.0);
+    }
+
+    #[test]
+    fn test_smooth_one_euro_keeps_timestamps() {
+        let trajectory = Trajectory {
+            latitudes: vec![0, 10, -10, 10],
+            longitudes: vec![0, 0, 0, 0],
+            timestamps: vec![1, 2, 3, 4],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+        let smoothed = smooth_one_euro(&trajectory, OneEuroParams::default());
+
+        assert_eq!(smoothed.timestamps, trajectory.timestamps);
+        assert_eq!(smoothed.longitudes, trajectory.longitudes);
+        assert!(smoothed.latitudes.iter().all(|lat| lat.abs() < 10));
+    }
+}
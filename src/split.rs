@@ -0,0 +1,184 @@
+//! Splitting of a dataset into train/validation/test subsets.
+//! Files are assigned as a whole, so the points of one trip never end up in two subsets.
+
+use crate::dataset::user_of;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// How files are assigned to the subsets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SplitStrategy {
+    /// All the files of a user go to the same subset
+    User,
+    /// Earliest files go to train, latest to test
+    Time,
+    /// Files are shuffled with the given seed
+    Random { seed: u64 },
+}
+
+/// Share of the files going to the train and validation subsets; the rest goes to test.
+#[derive(Clone, Copy, Debug)]
+pub struct SplitRatios {
+    pub train: f64,
+    pub validation: f64,
+}
+
+impl Default for SplitRatios {
+    fn default() -> Self {
+        SplitRatios {
+            train: 0.8,
+            validation: 0.1,
+        }
+    }
+}
+
+/// Files of each subset.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct DatasetSplit {
+    pub train: Vec<PathBuf>,
+    pub validation: Vec<PathBuf>,
+    pub test: Vec<PathBuf>,
+}
+
+/// Cuts the groups into three consecutive subsets following the ratios.
+fn assign(groups: Vec<Vec<PathBuf>>, ratios: SplitRatios) -> DatasetSplit {
+    let len = groups.len() as f64;
+    let train_end = (len * ratios.train).round() as usize;
+    let validation_end = (len * (ratios.train + ratios.validation)).round() as usize;
+
+    let mut split = DatasetSplit::default();
+    for (i, group) in groups.into_iter().enumerate() {
+        let subset = if i < train_end {
+            &mut split.train
+        } else if i < validation_end {
+            &mut split.validation
+        } else {
+            &mut split.test
+        };
+        subset.extend(group);
+    }
+    split
+}
+
+/// Splits the files into train, validation and test subsets.
+///
+/// # Panics
+///
+/// This function will panic if the ratios are negative or add up to more than 1.
+pub fn split_files(
+    mut files: Vec<PathBuf>,
+    strategy: SplitStrategy,
+    ratios: SplitRatios,
+) -> DatasetSplit {
+    assert!(
+        ratios.train >= 0.0 && ratios.validation >= 0.0,
+        "ratios must be non-negative"
+    );
+    assert!(
+        ratios.train + ratios.validation <= 1.0,
+        "ratios must not add up to more than 1"
+    );
+
+    files.sort();
+    let groups = match strategy {
+        SplitStrategy::User => {
+            let mut users: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+            for file in files {
+                let user = user_of(&file).unwrap_or_default().to_string();
+                users.entry(user).or_default().push(file);
+            }
+            users.into_values().collect()
+        }
+        SplitStrategy::Time => {
+            // GeoLife file names are the start time of the trip
+            files.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
+            files.into_iter().map(|file| vec![file]).collect()
+        }
+        SplitStrategy::Random { seed } => {
+            files.shuffle(&mut StdRng::seed_from_u64(seed));
+            files.into_iter().map(|file| vec![file]).collect()
+        }
+    };
+
+    assign(groups, ratios)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    fn files() -> Vec<PathBuf> {
+        [
+            "Data/001/Trajectory/20081024020959.plt",
+            "Data/000/Trajectory/20081023025304.plt",
+            "Data/000/Trajectory/20081026134407.plt",
+            "Data/002/Trajectory/20081023055305.plt",
+            "Data/001/Trajectory/20081028003826.plt",
+            "Data/003/Trajectory/20081029092138.plt",
+            "Data/004/Trajectory/20081102004236.plt",
+            "Data/005/Trajectory/20081103013515.plt",
+            "Data/006/Trajectory/20081104092019.plt",
+            "Data/007/Trajectory/20081105121745.plt",
+        ]
+        .into_iter()
+        .map(PathBuf::from)
+        .collect()
+    }
+
+    fn count(split: &DatasetSplit) -> usize {
+        split.train.len() + split.validation.len() + split.test.len()
+    }
+
+    #[test]
+    fn test_split_by_time() {
+        let split = split_files(files(), SplitStrategy::Time, SplitRatios::default());
+
+        assert_eq!(split.train.len(), 8);
+        assert_eq!(split.validation, vec![PathBuf::from("Data/006/Trajectory/20081104092019.plt")]);
+        assert_eq!(split.test, vec![PathBuf::from("Data/007/Trajectory/20081105121745.plt")]);
+    }
+
+    #[test]
+    fn test_split_by_user_keeps_users_together() {
+        let ratios = SplitRatios {
+            train: 0.5,
+            validation: 0.25,
+        };
+        let split = split_files(files(), SplitStrategy::User, ratios);
+
+        assert_eq!(count(&split), 10);
+        let users = |files: &[PathBuf]| -> BTreeSet<String> {
+            files.iter().map(|f| user_of(f).unwrap().to_string()).collect()
+        };
+        assert!(users(&split.train).is_disjoint(&users(&split.validation)));
+        assert!(users(&split.train).is_disjoint(&users(&split.test)));
+        assert!(users(&split.validation).is_disjoint(&users(&split.test)));
+        // 8 users: 4 in train, 2 in validation, 2 in test
+        assert_eq!(split.train.len(), 6);
+    }
+
+    #[test]
+    fn test_split_random_is_deterministic() {
+        let a = split_files(files(), SplitStrategy::Random { seed: 42 }, SplitRatios::default());
+        let mut shuffled = files();
+        shuffled.reverse();
+        let b = split_files(shuffled, SplitStrategy::Random { seed: 42 }, SplitRatios::default());
+
+        assert_eq!(a, b);
+        assert_eq!(count(&a), 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "ratios must not add up to more than 1")]
+    fn test_split_invalid_ratios() {
+        let ratios = SplitRatios {
+            train: 0.9,
+            validation: 0.2,
+        };
+        split_files(files(), SplitStrategy::Time, ratios);
+    }
+}
@@ -0,0 +1,193 @@
+//! The split tree of the Douglas-Peucker algorithm: which point split which segment, at
+//! what distance, exported as JSON or Graphviz DOT for teaching and debugging.
+//!
+//! Splits deeper in the tree refine the shape at a finer scale, so keeping the splits down
+//! to a depth gives coarser levels of detail of the same simplification.
+
+use crate::simplify::max_distance;
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::fmt::Write;
+
+/// Split of a segment at its farthest point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Split {
+    /// First point of the segment
+    pub start: usize,
+    /// Last point of the segment
+    pub end: usize,
+    /// Farthest point from the segment, which splits it
+    pub index: usize,
+    /// Distance of `index` to the segment, in coordinate units
+    pub distance: f64,
+    /// 1 for the split of the whole sequence, 2 for the splits of its halves, and so on
+    pub depth: usize,
+    /// Index in `SplitTree::splits` of the split the segment comes from, `None` for the
+    /// whole sequence
+    pub parent: Option<usize>,
+}
+
+/// Every split made by `simplify::simplify` on a sequence of points.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SplitTree {
+    /// Number of points of the sequence
+    pub len: usize,
+    pub epsilon: i64,
+    /// Splits in breadth-first order, so that parents come before their children
+    pub splits: Vec<Split>,
+}
+
+impl SplitTree {
+    /// Builds the split tree of the simplification of a sequence of points with `epsilon`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if:
+    /// * `positions_x` and `positions_y` have different lengths
+    /// * `epsilon` is negative
+    pub fn build(positions_x: &[i64], positions_y: &[i64], epsilon: i64) -> SplitTree {
+        assert_eq!(
+            positions_x.len(),
+            positions_y.len(),
+            "positions_x.len() == positions_y.len()"
+        );
+        assert!(epsilon >= 0, "epsilon must be non-negative");
+
+        let len = positions_x.len();
+        let mut splits = Vec::new();
+        let mut segments = VecDeque::new();
+        if len > 2 {
+            segments.push_back((0, len - 1, 1, None));
+        }
+        while let Some((start, end, depth, parent)) = segments.pop_front() {
+            if end - start <= 1 {
+                continue;
+            }
+            let (distance_squared, index) = max_distance(positions_x, positions_y, start, end);
+            if distance_squared <= epsilon * epsilon {
+                continue;
+            }
+            let split = Some(splits.len());
+            splits.push(Split {
+                start,
+                end,
+                index,
+                distance: (distance_squared as f64).sqrt(),
+                depth,
+                parent,
+            });
+            segments.push_back((start, index, depth + 1, split));
+            segments.push_back((index, end, depth + 1, split));
+        }
+
+        SplitTree {
+            len,
+            epsilon,
+            splits,
+        }
+    }
+
+    /// Depth of the deepest split, 0 without any split.
+    pub fn depth(&self) -> usize {
+        self.splits.last().map_or(0, |split| split.depth)
+    }
+
+    /// Points kept with the splits down to `depth`, the endpoints included. With the depth
+    /// of the tree, these are the points kept by `simplify::simplify`.
+    pub fn keep_to_depth(&self, depth: usize) -> Vec<bool> {
+        let mut keep = vec![false; self.len];
+        if let (Some(first), Some(last)) = (keep.first_mut(), self.len.checked_sub(1)) {
+            *first = true;
+            keep[last] = true;
+        }
+        for split in self.splits.iter().take_while(|split| split.depth <= depth) {
+            keep[split.index] = true;
+        }
+        keep
+    }
+
+    pub fn to_json(&self) -> Value {
+        let splits: Vec<Value> = self
+            .splits
+            .iter()
+            .map(|split| {
+                json!({
+                    "segment": [split.start, split.end],
+                    "point": split.index,
+                    "distance": split.distance,
+                    "depth": split.depth,
+                    "parent": split.parent,
+                })
+            })
+            .collect();
+        json!({ "points": self.len, "epsilon": self.epsilon, "splits": splits })
+    }
+
+    /// Graphviz DOT graph of the tree, with one node per split labeled with its point, its
+    /// segment and its distance, under a root node for the whole sequence.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph split_tree {\n  node [shape=box];\n");
+        let last = self.len.saturating_sub(1);
+        writeln!(
+            dot,
+            "  root [label=\"points 0..{last}\\nepsilon {}\"];",
+            self.epsilon
+        )
+        .unwrap();
+        for (i, split) in self.splits.iter().enumerate() {
+            writeln!(
+                dot,
+                "  s{i} [label=\"point {}\\n{}..{}\\n{:.1}\"];",
+                split.index, split.start, split.end, split.distance
+            )
+            .unwrap();
+            match split.parent {
+                Some(parent) => writeln!(dot, "  s{parent} -> s{i};").unwrap(),
+                None => writeln!(dot, "  root -> s{i};").unwrap(),
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simplify::simplify;
+
+    #[test]
+    fn test_split_tree() {
+        let tree = SplitTree::build(&[0, 10, 20, 30, 40], &[0, 9, 10, 4, 0], 2);
+        let summary: Vec<(usize, usize, usize, Option<usize>)> = tree
+            .splits
+            .iter()
+            .map(|split| (split.index, split.start, split.depth, split.parent))
+            .collect();
+        assert_eq!(summary, [(2, 0, 1, None), (1, 0, 2, Some(0))]);
+        assert_eq!(tree.splits[0].distance, 10.0);
+        assert_eq!(tree.depth(), 2);
+        assert_eq!(tree.keep_to_depth(1), [true, false, true, false, true]);
+
+        let x: Vec<i64> = (0..200).collect();
+        let y: Vec<i64> = x.iter().map(|&x| (x * x * 7) % 101 - 50).collect();
+        let tree = SplitTree::build(&x, &y, 20);
+        assert_eq!(tree.keep_to_depth(tree.depth()), simplify(&x, &y, 20));
+        assert!(SplitTree::build(&[], &[], 1).keep_to_depth(1).is_empty());
+    }
+
+    #[test]
+    fn test_split_tree_export() {
+        let tree = SplitTree::build(&[0, 10, 20, 30, 40], &[0, 9, 10, 4, 0], 2);
+        let json = tree.to_json();
+        assert_eq!(json["splits"][1]["segment"], json!([0, 2]));
+        assert_eq!(json["splits"][1]["parent"], 0);
+        assert_eq!(json["splits"][0]["parent"], Value::Null);
+
+        let dot = tree.to_dot();
+        assert!(dot.starts_with("digraph split_tree {"));
+        assert!(dot.contains("root -> s0;"));
+        assert!(dot.contains("s0 -> s1;"));
+        assert!(dot.contains("s1 [label=\"point 1\\n0..2\\n"));
+    }
+}
@@ -0,0 +1,240 @@
+//! `TrajectoryStore` backend keeping every trajectory in one SQLite database file
+//! (`sqlite:<path>` locations), for stores too large to be one file per trajectory.
+//!
+//! Each row holds a trajectory and its thumbnail in the binary format, with its first and
+//! last timestamps indexed so that `list_between` does not read the trajectories.
+//! Metadata has its own table, as with the filesystem backend it may be stored before
+//! the trajectory.
+
+use crate::binary;
+use crate::store::{validate_id, StoreError, TrajectoryStore};
+use crate::thumbnail::{self, MAX_POINTS};
+use crate::trajectory::Trajectory;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::Value;
+use std::path::Path;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS trajectories (
+        id TEXT PRIMARY KEY,
+        trajectory BLOB NOT NULL,
+        thumbnail BLOB NOT NULL,
+        first_timestamp INTEGER,
+        last_timestamp INTEGER
+    );
+    CREATE INDEX IF NOT EXISTS trajectories_time
+        ON trajectories (first_timestamp, last_timestamp);
+    CREATE TABLE IF NOT EXISTS metadata (
+        id TEXT PRIMARY KEY,
+        metadata TEXT NOT NULL
+    );
+";
+
+/// Store keeping every trajectory in an SQLite database.
+#[derive(Debug)]
+pub struct SqliteStore {
+    connection: Connection,
+}
+
+impl SqliteStore {
+    /// Opens the database at `path`, creating it and its tables if needed.
+    pub fn open(path: &Path) -> Result<Self, StoreError> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    /// Opens a database held in memory, lost when the store is dropped.
+    pub fn open_in_memory() -> Result<Self, StoreError> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(connection: Connection) -> Result<Self, StoreError> {
+        connection.execute_batch(SCHEMA)?;
+        Ok(SqliteStore { connection })
+    }
+}
+
+/// SQLite integers are signed: timestamps past `i64::MAX` are stored as `i64::MAX`.
+fn sql_timestamp(timestamp: u64) -> i64 {
+    i64::try_from(timestamp).unwrap_or(i64::MAX)
+}
+
+impl TrajectoryStore for SqliteStore {
+    fn put(&self, id: &str, trajectory: &Trajectory) -> Result<(), StoreError> {
+        validate_id(id)?;
+        let thumbnail = thumbnail::thumbnail(trajectory, MAX_POINTS);
+        let first = trajectory
+            .timestamps
+            .iter()
+            .min()
+            .copied()
+            .map(sql_timestamp);
+        let last = trajectory
+            .timestamps
+            .iter()
+            .max()
+            .copied()
+            .map(sql_timestamp);
+        self.connection.execute(
+            "INSERT OR REPLACE INTO trajectories VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                id,
+                binary::encode(trajectory),
+                binary::encode(&thumbnail),
+                first,
+                last
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn get_encoded(&self, id: &str) -> Result<Option<Vec<u8>>, StoreError> {
+        validate_id(id)?;
+        Ok(self
+            .connection
+            .query_row(
+                "SELECT trajectory FROM trajectories WHERE id = ?1",
+                [id],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    fn list(&self) -> Result<Vec<String>, StoreError> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT id FROM trajectories ORDER BY id")?;
+        let ids = statement.query_map([], |row| row.get(0))?;
+        Ok(ids.collect::<Result<_, _>>()?)
+    }
+
+    fn delete(&self, id: &str) -> Result<bool, StoreError> {
+        validate_id(id)?;
+        self.connection
+            .execute("DELETE FROM metadata WHERE id = ?1", [id])?;
+        let removed = self
+            .connection
+            .execute("DELETE FROM trajectories WHERE id = ?1", [id])?;
+        Ok(removed > 0)
+    }
+
+    fn put_metadata(&self, id: &str, metadata: &Value) -> Result<(), StoreError> {
+        validate_id(id)?;
+        self.connection.execute(
+            "INSERT OR REPLACE INTO metadata VALUES (?1, ?2)",
+            params![id, serde_json::to_string(metadata)?],
+        )?;
+        Ok(())
+    }
+
+    fn metadata(&self, id: &str) -> Result<Option<Value>, StoreError> {
+        validate_id(id)?;
+        let metadata: Option<String> = self
+            .connection
+            .query_row("SELECT metadata FROM metadata WHERE id = ?1", [id], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        metadata
+            .map(|json| serde_json::from_str(&json))
+            .transpose()
+            .map_err(StoreError::from)
+    }
+
+    fn thumbnail(&self, id: &str) -> Result<Option<Trajectory>, StoreError> {
+        validate_id(id)?;
+        let thumbnail: Option<Vec<u8>> = self
+            .connection
+            .query_row(
+                "SELECT thumbnail FROM trajectories WHERE id = ?1",
+                [id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(thumbnail.map(|bytes| binary::decode(&bytes)).transpose()?)
+    }
+
+    fn list_between(&self, start: u64, end: u64) -> Result<Vec<String>, StoreError> {
+        let mut statement = self.connection.prepare(
+            "SELECT id FROM trajectories
+             WHERE first_timestamp <= ?1 AND last_timestamp >= ?2
+             ORDER BY id",
+        )?;
+        let ids =
+            statement.query_map([sql_timestamp(end), sql_timestamp(start)], |row| row.get(0))?;
+        Ok(ids.collect::<Result<_, _>>()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trajectory::CoordinateSystem;
+    use serde_json::json;
+
+    fn trajectory(timestamps: Vec<u64>) -> Trajectory {
+        Trajectory {
+            latitudes: timestamps.iter().map(|&ts| ts as i64 * 10).collect(),
+            longitudes: timestamps.iter().map(|&ts| -(ts as i64)).collect(),
+            timestamps,
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        }
+    }
+
+    #[test]
+    fn test_sqlite_store() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let morning = trajectory(vec![100, 200, 300]);
+        let evening = trajectory(vec![1000, 1100]);
+
+        store.put("b-evening", &evening).unwrap();
+        store.put("a-morning", &trajectory(vec![1])).unwrap();
+        store.put("a-morning", &morning).unwrap();
+        store.put("empty", &trajectory(Vec::new())).unwrap();
+        assert_eq!(store.list().unwrap(), ["a-morning", "b-evening", "empty"]);
+        assert_eq!(store.get("a-morning").unwrap(), Some(morning));
+        assert_eq!(store.get("missing").unwrap(), None);
+
+        assert_eq!(store.list_between(250, 999).unwrap(), ["a-morning"]);
+        assert_eq!(store.list_between(300, 1000).unwrap().len(), 2);
+        assert!(store.list_between(301, 999).unwrap().is_empty());
+        assert_eq!(store.list_between(0, u64::MAX).unwrap().len(), 2);
+
+        store
+            .put_metadata("a-morning", &json!({ "source": "a.gpx" }))
+            .unwrap();
+        assert_eq!(
+            store.metadata("a-morning").unwrap(),
+            Some(json!({ "source": "a.gpx" }))
+        );
+        assert_eq!(store.metadata("b-evening").unwrap(), None);
+
+        assert!(store.delete("a-morning").unwrap());
+        assert!(!store.delete("a-morning").unwrap());
+        assert_eq!(store.metadata("a-morning").unwrap(), None);
+        assert_eq!(store.list().unwrap(), ["b-evening", "empty"]);
+        assert!(matches!(
+            store.put("../escape", &evening),
+            Err(StoreError::InvalidId(_))
+        ));
+    }
+
+    #[test]
+    fn test_thumbnails_and_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("trips.db");
+        let long = trajectory((0..500).map(|ts| ts * ts).collect());
+        SqliteStore::open(&path)
+            .unwrap()
+            .put("long", &long)
+            .unwrap();
+
+        let store = SqliteStore::open(&path).unwrap();
+        assert_eq!(store.get("long").unwrap(), Some(long.clone()));
+        assert_eq!(
+            store.thumbnail("long").unwrap(),
+            Some(thumbnail::thumbnail(&long, MAX_POINTS))
+        );
+        assert_eq!(store.thumbnail("missing").unwrap(), None);
+    }
+}
@@ -0,0 +1,342 @@
+//! Stay-point detection: places where a trajectory remained within a distance for a
+//! minimum time, such as homes, offices or shops.
+//!
+//! Follows Li et al., "Mining User Similarity Based on Location History", 2008: from an
+//! anchor point, the trajectory is followed until a point farther than `max_distance_m`
+//! from the anchor. If the points before it span `min_duration_s` or more, they form a
+//! stay point and the next anchor is that farther point; otherwise the next anchor is the
+//! point after the current one.
+//!
+//! Following the trajectory from every anchor is quadratic on slow, densely sampled
+//! stretches. `detect` instead finds the first point beyond the distance with a tree of
+//! bounding boxes over ranges of points: ranges whose box lies within the distance of the
+//! anchor are skipped whole.
+
+use crate::geo::{to_degrees, LocalProjection};
+use crate::rtree::Rect;
+use crate::trajectory::Trajectory;
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// Thresholds of the stay-point detection.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StayParams {
+    pub max_distance_m: f64,
+    pub min_duration_s: u64,
+}
+
+/// Points of a trajectory forming a stay.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StayPoint {
+    /// Mean position of the points, in scaled units
+    pub latitude: i64,
+    pub longitude: i64,
+    /// Timestamps of the first and last points
+    pub arrival: u64,
+    pub departure: u64,
+    /// Indices of the first and last points
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Projects the points to meters around the first one.
+fn project(trajectory: &Trajectory) -> Vec<(f64, f64)> {
+    let Some((&latitude, &longitude)) = trajectory
+        .latitudes
+        .first()
+        .zip(trajectory.longitudes.first())
+    else {
+        return Vec::new();
+    };
+    let projection = LocalProjection::new(to_degrees(latitude), to_degrees(longitude));
+    trajectory
+        .latitudes
+        .iter()
+        .zip(&trajectory.longitudes)
+        .map(|(&lat, &lon)| projection.project(to_degrees(lat), to_degrees(lon)))
+        .collect()
+}
+
+fn distance_squared(a: (f64, f64), b: (f64, f64)) -> f64 {
+    (a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)
+}
+
+/// Squared distance from a point to the farthest corner of a rectangle, which bounds the
+/// distance to every point inside.
+fn farthest_squared(rect: &Rect, (x, y): (f64, f64)) -> f64 {
+    let dx = (rect.min_x - x).abs().max((rect.max_x - x).abs());
+    let dy = (rect.min_y - y).abs().max((rect.max_y - y).abs());
+    dx * dx + dy * dy
+}
+
+/// Builds the stay point of the points `start..=end`.
+fn stay_point(trajectory: &Trajectory, start: usize, end: usize) -> StayPoint {
+    let mean = |values: &[i64]| {
+        let sum: i128 = values[start..=end].iter().map(|&v| v as i128).sum();
+        (sum / (end - start + 1) as i128) as i64
+    };
+    StayPoint {
+        latitude: mean(&trajectory.latitudes),
+        longitude: mean(&trajectory.longitudes),
+        arrival: trajectory.timestamps[start],
+        departure: trajectory.timestamps[end],
+        start,
+        end,
+    }
+}
+
+/// Runs the detection, with `first_beyond(anchor)` returning the first point after
+/// `anchor` farther than the distance from it, or the number of points.
+fn detect_with(
+    trajectory: &Trajectory,
+    params: StayParams,
+    mut first_beyond: impl FnMut(usize) -> usize,
+) -> Vec<StayPoint> {
+    let timestamps = &trajectory.timestamps;
+    let len = timestamps.len();
+    let mut stays = Vec::new();
+    let mut anchor = 0;
+    let span = |first: usize, last: usize| timestamps[last].saturating_sub(timestamps[first]);
+    while anchor < len && span(anchor, len - 1) >= params.min_duration_s {
+        let beyond = first_beyond(anchor);
+        if span(anchor, beyond - 1) >= params.min_duration_s {
+            stays.push(stay_point(trajectory, anchor, beyond - 1));
+            anchor = beyond;
+        } else {
+            anchor += 1;
+        }
+    }
+    stays
+}
+
+/// Detects the stay points by following the trajectory from every anchor, as in the
+/// original algorithm.
+///
+/// Timestamps must be sorted in non-decreasing order.
+pub fn detect_naive(trajectory: &Trajectory, params: StayParams) -> Vec<StayPoint> {
+    let points = project(trajectory);
+    let max_squared = params.max_distance_m * params.max_distance_m;
+    detect_with(trajectory, params, |anchor| {
+        (anchor + 1..points.len())
+            .find(|&j| distance_squared(points[anchor], points[j]) > max_squared)
+            .unwrap_or(points.len())
+    })
+}
+
+/// Bounding boxes of ranges of points, as a complete binary tree: node 1 covers every
+/// point, and node `i` covers the halves of its range as nodes `2i` and `2i + 1`.
+struct RangeTree {
+    /// Number of leaves, a power of two
+    size: usize,
+    nodes: Vec<Rect>,
+}
+
+impl RangeTree {
+    fn new(points: &[(f64, f64)]) -> Self {
+        let size = points.len().next_power_of_two();
+        let empty = Rect {
+            min_x: f64::INFINITY,
+            min_y: f64::INFINITY,
+            max_x: f64::NEG_INFINITY,
+            max_y: f64::NEG_INFINITY,
+        };
+        let mut nodes = vec![empty; 2 * size];
+        for (i, &point) in points.iter().enumerate() {
+            nodes[size + i] = Rect::of_segment(point, point);
+        }
+        for i in (1..size).rev() {
+            nodes[i] = nodes[2 * i].union(nodes[2 * i + 1]);
+        }
+        RangeTree { size, nodes }
+    }
+
+    /// Finds the first point at or after `from`, and before `len`, farther than
+    /// `sqrt(max_squared)` from `center`.
+    fn first_beyond(
+        &self,
+        node: usize,
+        (low, high): (usize, usize),
+        from: usize,
+        len: usize,
+        center: (f64, f64),
+        max_squared: f64,
+    ) -> Option<usize> {
+        if high <= from || low >= len {
+            return None;
+        }
+        let rect = &self.nodes[node];
+        if low >= from && high <= len && farthest_squared(rect, center) <= max_squared {
+            return None;
+        }
+        if high - low == 1 {
+            return Some(low);
+        }
+        let middle = (low + high) / 2;
+        self.first_beyond(2 * node, (low, middle), from, len, center, max_squared)
+            .or_else(|| {
+                self.first_beyond(2 * node + 1, (middle, high), from, len, center, max_squared)
+            })
+    }
+}
+
+/// Detects the stay points, with the same result as `detect_naive`.
+///
+/// Timestamps must be sorted in non-decreasing order.
+pub fn detect(trajectory: &Trajectory, params: StayParams) -> Vec<StayPoint> {
+    let points = project(trajectory);
+    let tree = RangeTree::new(&points);
+    let max_squared = params.max_distance_m * params.max_distance_m;
+    detect_with(trajectory, params, |anchor| {
+        tree.first_beyond(
+            1,
+            (0, tree.size),
+            anchor + 1,
+            points.len(),
+            points[anchor],
+            max_squared,
+        )
+        .unwrap_or(points.len())
+    })
+}
+
+/// Detects the stay points of many trajectories in parallel, like `simplify_batch`.
+///
+/// # Returns
+///
+/// The stay points of every trajectory, in the order of `trajectories`.
+///
+/// # Panics
+///
+/// This function will panic if `threads` is zero.
+pub fn detect_batch(
+    trajectories: &[Trajectory],
+    params: StayParams,
+    threads: usize,
+) -> Vec<Vec<StayPoint>> {
+    assert!(threads > 0, "threads > 0");
+
+    let next = AtomicUsize::new(0);
+    let mut stays = vec![Vec::new(); trajectories.len()];
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads.min(trajectories.len()))
+            .map(|_| {
+                let next = &next;
+                scope.spawn(move || {
+                    let mut found = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(trajectory) = trajectories.get(index) else {
+                            break;
+                        };
+                        found.push((index, detect(trajectory, params)));
+                    }
+                    found
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            let found = handle
+                .join()
+                .unwrap_or_else(|panic| panic::resume_unwind(panic));
+            for (index, trajectory_stays) in found {
+                stays[index] = trajectory_stays;
+            }
+        }
+    });
+    stays
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trajectory::CoordinateSystem;
+    use crate::bench::synthetic_trajectory;
+
+    const PARAMS: StayParams = StayParams {
+        max_distance_m: 200.0,
+        min_duration_s: 1_200,
+    };
+
+    #[test]
+    fn test_detect() {
+        // 30 minutes at one place with some jitter, a 3 km trip, then 10 minutes at another
+        let mut trajectory = Trajectory {
+            latitudes: vec![],
+            longitudes: vec![],
+            timestamps: vec![],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+        let mut push = |latitude: i64, longitude: i64, ts: u64| {
+            trajectory.latitudes.push(latitude);
+            trajectory.longitudes.push(longitude);
+            trajectory.timestamps.push(ts);
+        };
+        for i in 0..=30 {
+            push(39_900_000 + (i % 3) * 100, 116_400_000, 60 * i as u64);
+        }
+        for i in 1..=10 {
+            push(39_900_000 + i * 2_700, 116_400_000, 1_800 + 60 * i as u64);
+        }
+        for i in 1..=10 {
+            push(
+                39_927_000,
+                116_400_000 + (i % 2) * 100,
+                2_400 + 60 * i as u64,
+            );
+        }
+
+        let stays = detect(&trajectory, PARAMS);
+        assert_eq!(stays.len(), 1);
+        assert_eq!((stays[0].start, stays[0].end), (0, 30));
+        assert_eq!((stays[0].arrival, stays[0].departure), (0, 1_800));
+        assert_eq!(stays[0].longitude, 116_400_000);
+        assert_eq!(stays[0].latitude, 39_900_096);
+        assert_eq!(stays, detect_naive(&trajectory, PARAMS));
+
+        // The second place is a stay when 10 minutes are enough
+        let short = StayParams {
+            min_duration_s: 540,
+            ..PARAMS
+        };
+        assert_eq!(detect(&trajectory, short).len(), 2);
+    }
+
+    #[test]
+    fn test_detect_same_as_naive() {
+        // A slow random walk, about 1.4 m per second
+        let trajectory = synthetic_trajectory(20_000, 1);
+        for (max_distance_m, min_duration_s) in [(20.0, 60), (50.0, 300), (200.0, 1_200)] {
+            let params = StayParams {
+                max_distance_m,
+                min_duration_s,
+            };
+            let stays = detect(&trajectory, params);
+            assert_eq!(stays, detect_naive(&trajectory, params));
+        }
+    }
+
+    #[test]
+    fn test_detect_batch() {
+        let trajectories: Vec<Trajectory> = (0..5)
+            .map(|seed| synthetic_trajectory(5_000, seed))
+            .collect();
+        let batch = detect_batch(&trajectories, PARAMS, 3);
+        assert_eq!(batch.len(), 5);
+        for (trajectory, stays) in trajectories.iter().zip(&batch) {
+            assert_eq!(stays, &detect(trajectory, PARAMS));
+        }
+
+        let empty = Trajectory {
+            latitudes: vec![],
+            longitudes: vec![],
+            timestamps: vec![],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+        assert!(detect(&empty, PARAMS).is_empty());
+        assert!(detect_batch(&[], PARAMS, 2).is_empty());
+    }
+}
@@ -0,0 +1,573 @@
+//! Persistence of simplified trajectories behind the `TrajectoryStore` trait, so that every
+//! mode reads and writes them the same way whatever the backend.
+//!
+//! Trajectories are stored by id in the binary format, with optional JSON metadata such as
+//! where they come from, and a thumbnail of at most `thumbnail::MAX_POINTS` points to
+//! preview them without reading them. The filesystem backend keeps one `<id>.trjb` file per
+//! trajectory in a directory, its metadata in `<id>.json` and its thumbnail in `<id>.thumb`.
+//! Built with the `sqlite` or `s3` feature, stores may also be an SQLite database
+//! (`sqlite_store`) or a prefix of an S3 bucket (`s3_store`). A `NamespacedStore` isolates
+//! the trajectories of a tenant in a store shared by several.
+
+use crate::binary::{self, BinaryError};
+use crate::thumbnail::{self, MAX_POINTS};
+use crate::trajectory::Trajectory;
+use serde_json::Value;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Extension of the trajectory files of the filesystem backend
+const EXTENSION: &str = "trjb";
+
+/// Extension of the metadata files of the filesystem backend
+const METADATA_EXTENSION: &str = "json";
+
+/// Extension of the thumbnail files of the filesystem backend, in the binary format
+const THUMBNAIL_EXTENSION: &str = "thumb";
+
+#[derive(Error, Debug)]
+pub enum StoreError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Stored trajectory is invalid: {0}")]
+    Binary(#[from] BinaryError),
+    #[error("Stored metadata is invalid: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid trajectory id: {0}")]
+    InvalidId(String),
+    #[error("Store {location} needs a build with the `{feature}` feature")]
+    UnsupportedBackend {
+        location: String,
+        feature: &'static str,
+    },
+    #[cfg(feature = "sqlite")]
+    #[error("SQLite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[cfg(feature = "s3")]
+    #[error("S3 error: {0}")]
+    S3(String),
+}
+
+/// Storage of trajectories by id.
+///
+/// Ids are made of ASCII letters, digits, `-` and `_`, so that backends can use them as
+/// file names or keys as is.
+pub trait TrajectoryStore {
+    /// Stores a trajectory and its thumbnail, replacing the ones with the same id if any.
+    fn put(&self, id: &str, trajectory: &Trajectory) -> Result<(), StoreError>;
+
+    /// Returns the trajectory stored with `id`, if any.
+    fn get(&self, id: &str) -> Result<Option<Trajectory>, StoreError> {
+        self.get_encoded(id)?
+            .map(|bytes| binary::decode(&bytes))
+            .transpose()
+            .map_err(StoreError::from)
+    }
+
+    /// Returns the trajectory stored with `id` as encoded, in the version of the binary
+    /// format it was stored with, if any.
+    fn get_encoded(&self, id: &str) -> Result<Option<Vec<u8>>, StoreError>;
+
+    /// Ids of every stored trajectory, sorted.
+    fn list(&self) -> Result<Vec<String>, StoreError>;
+
+    /// Removes a trajectory, its metadata and its thumbnail, returning whether it was
+    /// stored.
+    fn delete(&self, id: &str) -> Result<bool, StoreError>;
+
+    /// Stores the metadata of a trajectory, replacing the previous one if any.
+    fn put_metadata(&self, id: &str, metadata: &Value) -> Result<(), StoreError>;
+
+    /// Returns the metadata of a trajectory, if any.
+    fn metadata(&self, id: &str) -> Result<Option<Value>, StoreError>;
+
+    /// Returns the thumbnail of the trajectory stored with `id`, if any (see `thumbnail`).
+    ///
+    /// Reads the whole trajectory; backends storing the thumbnails should override it.
+    fn thumbnail(&self, id: &str) -> Result<Option<Trajectory>, StoreError> {
+        Ok(self
+            .get(id)?
+            .map(|trajectory| thumbnail::thumbnail(&trajectory, MAX_POINTS)))
+    }
+
+    /// Ids of the stored trajectories with points between the `start` and `end` timestamps
+    /// (inclusive), sorted.
+    ///
+    /// Reads every trajectory; backends indexing the time ranges should override it.
+    fn list_between(&self, start: u64, end: u64) -> Result<Vec<String>, StoreError> {
+        let mut ids = Vec::new();
+        for id in self.list()? {
+            let Some(trajectory) = self.get(&id)? else {
+                continue;
+            };
+            let first = trajectory.timestamps.iter().min();
+            let last = trajectory.timestamps.iter().max();
+            if let (Some(&first), Some(&last)) = (first, last) {
+                if first <= end && last >= start {
+                    ids.push(id);
+                }
+            }
+        }
+        Ok(ids)
+    }
+}
+
+/// Checks that an id can be used as a file name.
+pub(crate) fn validate_id(id: &str) -> Result<(), StoreError> {
+    let valid = !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(StoreError::InvalidId(id.to_string()))
+    }
+}
+
+/// Store keeping one file per trajectory in a directory.
+#[derive(Debug, Clone)]
+pub struct FsStore {
+    root: PathBuf,
+}
+
+impl FsStore {
+    /// Opens the store in `root`, creating the directory if needed.
+    pub fn open(root: &Path) -> Result<Self, StoreError> {
+        fs::create_dir_all(root)?;
+        Ok(FsStore {
+            root: root.to_path_buf(),
+        })
+    }
+
+    fn path(&self, id: &str, extension: &str) -> Result<PathBuf, StoreError> {
+        validate_id(id)?;
+        Ok(self.root.join(format!("{id}.{extension}")))
+    }
+}
+
+/// Writes a file aside then renames it, so that readers never see a partial file.
+fn write_atomically(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".partial");
+    fs::write(&partial, bytes)?;
+    fs::rename(&partial, path)
+}
+
+/// Reads a file, or returns `None` if it does not exist.
+fn read_if_exists(path: &Path) -> io::Result<Option<Vec<u8>>> {
+    match fs::read(path) {
+        Ok(bytes) => Ok(Some(bytes)),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+impl TrajectoryStore for FsStore {
+    fn put(&self, id: &str, trajectory: &Trajectory) -> Result<(), StoreError> {
+        // The previous thumbnail goes first, for a failed write to never leave it next to the
+        // new trajectory: without one, `thumbnail` computes it from the trajectory
+        let thumbnail_path = self.path(id, THUMBNAIL_EXTENSION)?;
+        match fs::remove_file(&thumbnail_path) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => return Err(error.into()),
+            _ => {}
+        }
+        write_atomically(&self.path(id, EXTENSION)?, &binary::encode(trajectory))?;
+        // After the trajectory, for a thumbnail to never be left without it
+        let thumbnail = thumbnail::thumbnail(trajectory, MAX_POINTS);
+        write_atomically(&thumbnail_path, &binary::encode(&thumbnail))?;
+        Ok(())
+    }
+
+    fn get_encoded(&self, id: &str) -> Result<Option<Vec<u8>>, StoreError> {
+        Ok(read_if_exists(&self.path(id, EXTENSION)?)?)
+    }
+
+    fn list(&self) -> Result<Vec<String>, StoreError> {
+        let mut ids = Vec::new();
+        for entry in fs::read_dir(&self.root)? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == EXTENSION)
+            {
+                if let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) {
+                    ids.push(id.to_string());
+                }
+            }
+        }
+        ids.sort();
+        Ok(ids)
+    }
+
+    fn delete(&self, id: &str) -> Result<bool, StoreError> {
+        let removed = |path: PathBuf| match fs::remove_file(path) {
+            Ok(()) => Ok(true),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(error) => Err(error),
+        };
+        // The metadata and thumbnail go first, for a trajectory to never be left without them
+        removed(self.path(id, METADATA_EXTENSION)?)?;
+        removed(self.path(id, THUMBNAIL_EXTENSION)?)?;
+        Ok(removed(self.path(id, EXTENSION)?)?)
+    }
+
+    fn put_metadata(&self, id: &str, metadata: &Value) -> Result<(), StoreError> {
+        let path = self.path(id, METADATA_EXTENSION)?;
+        write_atomically(&path, &serde_json::to_vec(metadata)?)?;
+        Ok(())
+    }
+
+    fn metadata(&self, id: &str) -> Result<Option<Value>, StoreError> {
+        read_if_exists(&self.path(id, METADATA_EXTENSION)?)?
+            .map(|bytes| serde_json::from_slice(&bytes))
+            .transpose()
+            .map_err(StoreError::from)
+    }
+
+    fn thumbnail(&self, id: &str) -> Result<Option<Trajectory>, StoreError> {
+        match read_if_exists(&self.path(id, THUMBNAIL_EXTENSION)?)? {
+            Some(bytes) => Ok(Some(binary::decode(&bytes)?)),
+            // Stored before thumbnails were
+            None => Ok(self
+                .get(id)?
+                .map(|trajectory| thumbnail::thumbnail(&trajectory, MAX_POINTS))),
+        }
+    }
+}
+
+/// Store keeping its trajectories in a namespace of another store, such as the one of a
+/// tenant, by prefixing their ids.
+pub struct NamespacedStore {
+    inner: Box<dyn TrajectoryStore>,
+    prefix: String,
+}
+
+impl NamespacedStore {
+    /// Keeps the trajectories in the namespace `name` of `inner`.
+    ///
+    /// Names are made of the same characters as ids, so `_` is doubled in the prefix and
+    /// `_-` ends it, for no prefix to start another one.
+    pub fn new(inner: Box<dyn TrajectoryStore>, name: &str) -> Result<Self, StoreError> {
+        validate_id(name)?;
+        Ok(NamespacedStore {
+            inner,
+            prefix: format!("{}_-", name.replace('_', "__")),
+        })
+    }
+
+    fn key(&self, id: &str) -> Result<String, StoreError> {
+        validate_id(id)?;
+        Ok(format!("{}{id}", self.prefix))
+    }
+
+    /// Ids of the namespace among the keys of the inner store.
+    fn ids(&self, keys: Vec<String>) -> Vec<String> {
+        keys.iter()
+            .filter_map(|key| key.strip_prefix(&self.prefix))
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+impl TrajectoryStore for NamespacedStore {
+    fn put(&self, id: &str, trajectory: &Trajectory) -> Result<(), StoreError> {
+        self.inner.put(&self.key(id)?, trajectory)
+    }
+
+    fn get_encoded(&self, id: &str) -> Result<Option<Vec<u8>>, StoreError> {
+        self.inner.get_encoded(&self.key(id)?)
+    }
+
+    fn list(&self) -> Result<Vec<String>, StoreError> {
+        Ok(self.ids(self.inner.list()?))
+    }
+
+    fn delete(&self, id: &str) -> Result<bool, StoreError> {
+        self.inner.delete(&self.key(id)?)
+    }
+
+    fn put_metadata(&self, id: &str, metadata: &Value) -> Result<(), StoreError> {
+        self.inner.put_metadata(&self.key(id)?, metadata)
+    }
+
+    fn metadata(&self, id: &str) -> Result<Option<Value>, StoreError> {
+        self.inner.metadata(&self.key(id)?)
+    }
+
+    fn thumbnail(&self, id: &str) -> Result<Option<Trajectory>, StoreError> {
+        self.inner.thumbnail(&self.key(id)?)
+    }
+
+    fn list_between(&self, start: u64, end: u64) -> Result<Vec<String>, StoreError> {
+        Ok(self.ids(self.inner.list_between(start, end)?))
+    }
+}
+
+/// Outcome of a migration.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Migration {
+    /// Trajectories upgraded to the current version of the format
+    pub migrated: usize,
+    /// Trajectories already in the current version
+    pub current: usize,
+}
+
+/// Upgrades the trajectories stored in an older version of the binary format to the
+/// current one, in place, after copying each as it was to `backup/<id>.trjb`.
+///
+/// Trajectories in a newer version than this build knows fail the migration, leaving the
+/// ones after them untouched.
+pub fn migrate(store: &dyn TrajectoryStore, backup: &Path) -> Result<Migration, StoreError> {
+    let mut migration = Migration::default();
+    for id in store.list()? {
+        let Some(bytes) = store.get_encoded(&id)? else {
+            continue;
+        };
+        if binary::version(&bytes)? == binary::VERSION {
+            migration.current += 1;
+            continue;
+        }
+        // Decoded first, for the backup to only hold trajectories that could be read
+        let trajectory = binary::decode(&bytes)?;
+        fs::create_dir_all(backup)?;
+        write_atomically(&backup.join(format!("{id}.{EXTENSION}")), &bytes)?;
+        store.put(&id, &trajectory)?;
+        migration.migrated += 1;
+    }
+    Ok(migration)
+}
+
+/// Opens the store at `location`: an SQLite database as `sqlite:<path>`, a prefix of an S3
+/// bucket as `s3://<bucket>/<prefix>`, or else a directory.
+///
+/// SQLite and S3 stores fail with `StoreError::UnsupportedBackend` in builds without the
+/// `sqlite` or `s3` feature.
+pub fn open(location: &str) -> Result<Box<dyn TrajectoryStore>, StoreError> {
+    if let Some(path) = location.strip_prefix("sqlite:") {
+        return open_sqlite(path);
+    }
+    if let Some(bucket) = location.strip_prefix("s3://") {
+        return open_s3(bucket);
+    }
+    Ok(Box::new(FsStore::open(Path::new(location))?))
+}
+
+#[cfg(feature = "sqlite")]
+fn open_sqlite(path: &str) -> Result<Box<dyn TrajectoryStore>, StoreError> {
+    Ok(Box::new(crate::sqlite_store::SqliteStore::open(
+        Path::new(path),
+    )?))
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn open_sqlite(path: &str) -> Result<Box<dyn TrajectoryStore>, StoreError> {
+    Err(StoreError::UnsupportedBackend {
+        location: format!("sqlite:{path}"),
+        feature: "sqlite",
+    })
+}
+
+#[cfg(feature = "s3")]
+fn open_s3(bucket: &str) -> Result<Box<dyn TrajectoryStore>, StoreError> {
+    Ok(Box::new(crate::s3_store::S3Store::open(bucket)?))
+}
+
+#[cfg(not(feature = "s3"))]
+fn open_s3(bucket: &str) -> Result<Box<dyn TrajectoryStore>, StoreError> {
+    Err(StoreError::UnsupportedBackend {
+        location: format!("s3://{bucket}"),
+        feature: "s3",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trajectory::CoordinateSystem;
+    use serde_json::json;
+
+    fn trajectory(timestamps: Vec<u64>) -> Trajectory {
+        Trajectory {
+            latitudes: timestamps.iter().map(|&ts| ts as i64 * 10).collect(),
+            longitudes: timestamps.iter().map(|&ts| -(ts as i64)).collect(),
+            timestamps,
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        }
+    }
+
+    #[test]
+    fn test_fs_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FsStore::open(dir.path()).unwrap();
+        let morning = trajectory(vec![100, 200, 300]);
+        let evening = trajectory(vec![1000, 1100]);
+
+        store.put("b-evening", &evening).unwrap();
+        store.put("a-morning", &trajectory(vec![1])).unwrap();
+        store.put("a-morning", &morning).unwrap();
+        assert_eq!(store.list().unwrap(), ["a-morning", "b-evening"]);
+        assert_eq!(store.get("a-morning").unwrap(), Some(morning));
+        assert_eq!(store.get("missing").unwrap(), None);
+
+        assert_eq!(store.list_between(250, 999).unwrap(), ["a-morning"]);
+        assert_eq!(store.list_between(300, 1000).unwrap().len(), 2);
+        assert!(store.list_between(301, 999).unwrap().is_empty());
+
+        store
+            .put_metadata("a-morning", &json!({ "source": "a.gpx" }))
+            .unwrap();
+        assert_eq!(
+            store.metadata("a-morning").unwrap(),
+            Some(json!({ "source": "a.gpx" }))
+        );
+        assert_eq!(store.metadata("b-evening").unwrap(), None);
+        // Metadata files are not trajectories
+        assert_eq!(store.list().unwrap(), ["a-morning", "b-evening"]);
+
+        assert!(store.delete("a-morning").unwrap());
+        assert!(!store.delete("a-morning").unwrap());
+        assert_eq!(store.metadata("a-morning").unwrap(), None);
+        assert_eq!(store.list().unwrap(), ["b-evening"]);
+    }
+
+    #[test]
+    fn test_thumbnails() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FsStore::open(dir.path()).unwrap();
+        let long = trajectory((0..500).map(|ts| ts * ts).collect());
+        store.put("long", &long).unwrap();
+        let preview = store.thumbnail("long").unwrap().unwrap();
+        assert_eq!(preview, thumbnail::thumbnail(&long, MAX_POINTS));
+        assert!(preview.timestamps.len() <= MAX_POINTS);
+        // Thumbnails are not trajectories
+        assert_eq!(store.list().unwrap(), ["long"]);
+
+        // Stored before thumbnails were
+        fs::remove_file(dir.path().join("long.thumb")).unwrap();
+        assert_eq!(store.thumbnail("long").unwrap(), Some(preview));
+        assert_eq!(store.thumbnail("missing").unwrap(), None);
+
+        store.put("long", &long).unwrap();
+        assert!(store.delete("long").unwrap());
+        assert!(!dir.path().join("long.thumb").exists());
+    }
+
+    #[test]
+    fn test_failed_thumbnail_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FsStore::open(dir.path()).unwrap();
+        let short = trajectory(vec![1, 2, 3]);
+        let long = trajectory((0..500).map(|ts| ts * ts).collect());
+        store.put("trip", &short).unwrap();
+
+        // A directory in the way of the thumbnail written aside
+        fs::create_dir(dir.path().join("trip.thumb.partial")).unwrap();
+        assert!(matches!(store.put("trip", &long), Err(StoreError::Io(_))));
+        assert_eq!(store.get("trip").unwrap(), Some(long.clone()));
+        assert_eq!(
+            store.thumbnail("trip").unwrap(),
+            Some(thumbnail::thumbnail(&long, MAX_POINTS))
+        );
+    }
+
+    #[test]
+    fn test_namespaced_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let open = |name| NamespacedStore::new(Box::new(FsStore::open(dir.path()).unwrap()), name);
+        let (fleet, fleet_b) = (open("fleet").unwrap(), open("fleet_b").unwrap());
+        let morning = trajectory(vec![100, 200, 300]);
+        let evening = trajectory(vec![1000, 1100]);
+
+        fleet.put("b_trip", &morning).unwrap();
+        fleet_b.put("trip", &evening).unwrap();
+        fleet
+            .put_metadata("b_trip", &json!({ "user": "000" }))
+            .unwrap();
+        assert_eq!(fleet.list().unwrap(), ["b_trip"]);
+        assert_eq!(fleet_b.list().unwrap(), ["trip"]);
+        assert_eq!(fleet.get("b_trip").unwrap(), Some(morning.clone()));
+        assert_eq!(fleet.get("trip").unwrap(), None);
+        assert_eq!(fleet_b.metadata("trip").unwrap(), None);
+        assert_eq!(
+            fleet.thumbnail("b_trip").unwrap(),
+            Some(thumbnail::thumbnail(&morning, MAX_POINTS))
+        );
+        assert_eq!(fleet.list_between(0, 500).unwrap(), ["b_trip"]);
+        assert!(fleet_b.list_between(0, 500).unwrap().is_empty());
+        assert!(!fleet_b.delete("b_trip").unwrap());
+        assert!(fleet.delete("b_trip").unwrap());
+
+        let inner = FsStore::open(dir.path()).unwrap();
+        assert_eq!(inner.list().unwrap(), ["fleet__b_-trip"]);
+        for name in ["", "../etc", "a/b", ".."] {
+            assert!(matches!(open(name), Err(StoreError::InvalidId(_))));
+        }
+        assert!(matches!(
+            fleet.put("../trip", &evening),
+            Err(StoreError::InvalidId(_))
+        ));
+    }
+
+    #[test]
+    fn test_migrate() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FsStore::open(&dir.path().join("store")).unwrap();
+        let backup = dir.path().join("backup");
+        let morning = trajectory(vec![100, 200, 300]);
+        store.put("current", &morning).unwrap();
+        // Version 1 has no checksum after the version
+        let mut version_1 = binary::encode(&morning);
+        version_1.drain(5..13);
+        version_1[4] = 1;
+        fs::write(dir.path().join("store/old.trjb"), &version_1).unwrap();
+
+        assert_eq!(
+            migrate(&store, &backup).unwrap(),
+            Migration {
+                migrated: 1,
+                current: 1
+            }
+        );
+        assert_eq!(
+            store.get_encoded("old").unwrap().unwrap(),
+            binary::encode(&morning)
+        );
+        assert_eq!(fs::read(backup.join("old.trjb")).unwrap(), version_1);
+        assert!(!backup.join("current.trjb").exists());
+        assert_eq!(migrate(&store, &backup).unwrap().migrated, 0);
+
+        fs::write(dir.path().join("store/future.trjb"), b"TRJB\x09").unwrap();
+        assert!(matches!(
+            migrate(&store, &backup),
+            Err(StoreError::Binary(BinaryError::UnsupportedVersion(9)))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_locations() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FsStore::open(dir.path()).unwrap();
+        assert!(matches!(
+            store.put("../escape", &trajectory(vec![1])),
+            Err(StoreError::InvalidId(id)) if id == "../escape"
+        ));
+        assert!(matches!(store.get(""), Err(StoreError::InvalidId(_))));
+        #[cfg(not(feature = "s3"))]
+        assert!(matches!(
+            open("s3://bucket/prefix"),
+            Err(StoreError::UnsupportedBackend { feature: "s3", .. })
+        ));
+        #[cfg(not(feature = "sqlite"))]
+        assert!(matches!(
+            open("sqlite:trips.db"),
+            Err(StoreError::UnsupportedBackend {
+                feature: "sqlite",
+                ..
+            })
+        ));
+    }
+}
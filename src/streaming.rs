@@ -0,0 +1,279 @@
+//! Simplification of live GPS feeds, where points arrive in batches.
+//!
+//! Fixes reported with a poor accuracy (in tunnels or urban canyons) are simplified with a
+//! larger tolerance, so that their noise does not force extra points to be kept.
+
+use crate::simplify;
+
+/// Accuracy in meters of a good GPS fix, at which the tolerance is not scaled
+const REFERENCE_ACCURACY_M: f64 = 10.0;
+
+/// Largest factor the tolerance of an inaccurate fix is scaled by
+const MAX_EPSILON_SCALE: f64 = 4.0;
+
+/// Options of the streaming simplification.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StreamingOptions {
+    /// Tolerance in scaled units for fixes at least as accurate as `reference_accuracy_m`
+    pub epsilon: i64,
+    /// Accuracy in meters at which `epsilon` applies unchanged
+    pub reference_accuracy_m: f64,
+    /// Largest factor `epsilon` is scaled by for inaccurate fixes
+    pub max_epsilon_scale: f64,
+}
+
+impl StreamingOptions {
+    /// Options with the given tolerance and the default accuracy scaling.
+    pub fn new(epsilon: i64) -> Self {
+        StreamingOptions {
+            epsilon,
+            reference_accuracy_m: REFERENCE_ACCURACY_M,
+            max_epsilon_scale: MAX_EPSILON_SCALE,
+        }
+    }
+
+    /// Returns the tolerance of a fix with the given reported accuracy in meters.
+    ///
+    /// The tolerance grows proportionally to the accuracy beyond `reference_accuracy_m`,
+    /// up to `max_epsilon_scale` times `epsilon`. Fixes without a reported accuracy use
+    /// `epsilon`.
+    pub fn epsilon_for(&self, accuracy_m: Option<f64>) -> i64 {
+        let scale = accuracy_m.map_or(1.0, |accuracy_m| {
+            (accuracy_m / self.reference_accuracy_m).clamp(1.0, self.max_epsilon_scale)
+        });
+        (self.epsilon as f64 * scale).round() as i64
+    }
+
+    /// Updates the simplification of a feed after points were appended to it.
+    ///
+    /// `previous` is the result for the points before the append, and `accuracies` has
+    /// the reported accuracy of every point, if any.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `positions_x`, `positions_y` and `accuracies` have
+    /// different lengths, or if `previous` is longer than them.
+    pub fn update(
+        &self,
+        positions_x: &[i64],
+        positions_y: &[i64],
+        accuracies: &[Option<f64>],
+        previous: &[bool],
+    ) -> Vec<bool> {
+        assert_eq!(
+            positions_x.len(),
+            accuracies.len(),
+            "positions_x.len() == accuracies.len()"
+        );
+
+        // Without any accuracy, every point shares the tolerance and the faster
+        // uniform kernel applies
+        if accuracies.iter().all(Option::is_none) {
+            return simplify::simplify_appended(positions_x, positions_y, previous, self.epsilon);
+        }
+
+        let epsilons: Vec<i64> = accuracies
+            .iter()
+            .map(|&accuracy_m| self.epsilon_for(accuracy_m))
+            .collect();
+        simplify::simplify_appended_weighted(positions_x, positions_y, previous, &epsilons)
+    }
+}
+
+/// Number of leading points whose decision is final after an update: every point before
+/// the last kept one, as the next update only simplifies again from the second-to-last
+/// kept point, which stays kept.
+pub fn committed(keep: &[bool]) -> usize {
+    keep.iter().rposition(|&keep| keep).unwrap_or(0)
+}
+
+/// Point of a live feed, in scaled coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamPoint {
+    pub latitude: i64,
+    pub longitude: i64,
+    pub timestamp: u64,
+}
+
+/// Simplification of a live feed one point at a time, with bounded latency and memory,
+/// using the Opening Window algorithm (OPW).
+///
+/// The window opens at the last kept point, the anchor, and grows with every point pushed
+/// while the points in between stay within `epsilon` of the line from the anchor to the
+/// newest point. When a point breaks the tolerance, the point before it is kept and opens
+/// the next window. A window reaching `max_window` points is closed at its newest point,
+/// which bounds the work per point and how long a point is held back.
+///
+/// Unlike `StreamingOptions::update`, earlier points never need to be kept again, so the
+/// feed does not have to be buffered. It usually keeps more points than `simplify` on the
+/// whole feed would, within the same tolerance.
+#[derive(Debug, Clone)]
+pub struct StreamingSimplifier {
+    epsilon: i64,
+    max_window: usize,
+    /// Points since the anchor, the anchor first
+    latitudes: Vec<i64>,
+    longitudes: Vec<i64>,
+    timestamps: Vec<u64>,
+}
+
+impl StreamingSimplifier {
+    /// Creates a simplifier with a tolerance in scaled units, holding at most `max_window`
+    /// points.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `epsilon` is negative or `max_window` is less than 3.
+    pub fn new(epsilon: i64, max_window: usize) -> Self {
+        assert!(epsilon >= 0, "epsilon must be non-negative");
+        assert!(max_window >= 3, "max_window must be at least 3");
+        StreamingSimplifier {
+            epsilon,
+            max_window,
+            latitudes: Vec::with_capacity(max_window),
+            longitudes: Vec::with_capacity(max_window),
+            timestamps: Vec::with_capacity(max_window),
+        }
+    }
+
+    fn point(&self, i: usize) -> StreamPoint {
+        StreamPoint {
+            latitude: self.latitudes[i],
+            longitude: self.longitudes[i],
+            timestamp: self.timestamps[i],
+        }
+    }
+
+    /// Restarts the window at its point `i`, the new anchor.
+    fn reopen(&mut self, i: usize) {
+        self.latitudes.drain(..i);
+        self.longitudes.drain(..i);
+        self.timestamps.drain(..i);
+    }
+
+    /// Pushes the next point of the feed, returning the points whose decision became final
+    /// and that are kept, if any.
+    ///
+    /// The first point is kept at once. Other kept points are returned at the latest when
+    /// the window is full.
+    pub fn push(&mut self, point: StreamPoint) -> Option<Vec<StreamPoint>> {
+        self.latitudes.push(point.latitude);
+        self.longitudes.push(point.longitude);
+        self.timestamps.push(point.timestamp);
+        let len = self.latitudes.len();
+        if len == 1 {
+            return Some(vec![point]);
+        }
+
+        let (distance_squared, _) =
+            simplify::max_distance(&self.latitudes, &self.longitudes, 0, len - 1);
+        if len > 2 && distance_squared > self.epsilon * self.epsilon {
+            let kept = self.point(len - 2);
+            self.reopen(len - 2);
+            return Some(vec![kept]);
+        }
+        if len == self.max_window {
+            self.reopen(len - 1);
+            return Some(vec![point]);
+        }
+        None
+    }
+
+    /// Ends the feed, returning its last point if it was not returned yet.
+    pub fn finish(self) -> Option<Vec<StreamPoint>> {
+        (self.latitudes.len() > 1).then(|| vec![self.point(self.latitudes.len() - 1)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_epsilon_for() {
+        let options = StreamingOptions::new(100);
+
+        assert_eq!(options.epsilon_for(None), 100);
+        assert_eq!(options.epsilon_for(Some(3.0)), 100);
+        assert_eq!(options.epsilon_for(Some(25.0)), 250);
+        assert_eq!(options.epsilon_for(Some(500.0)), 400);
+    }
+
+    #[test]
+    fn test_update_ignores_inaccurate_fixes() {
+        // A 30-unit jump reported by a fix accurate to 50 m, i.e. 5 times the reference
+        let x: Vec<i64> = (0..10).collect();
+        let mut y = vec![0; 10];
+        y[5] = 30;
+        let mut accuracies = vec![Some(5.0); 10];
+        let options = StreamingOptions::new(10);
+
+        let keep = options.update(&x, &y, &accuracies, &[]);
+        assert!(keep[5]);
+
+        accuracies[5] = Some(50.0);
+        let keep = options.update(&x, &y, &accuracies, &[]);
+        assert_eq!(keep.iter().filter(|&&keep| keep).count(), 2);
+    }
+
+    #[test]
+    fn test_committed() {
+        // A zigzag, every point of which is kept
+        let x: Vec<i64> = (0..20).map(|i| i * 10).collect();
+        let y: Vec<i64> = (0..20).map(|i| (i % 2) * 100).collect();
+        let options = StreamingOptions::new(10);
+
+        let keep = options.update(&x[..10], &y[..10], &[None; 10], &[]);
+        let first = committed(&keep);
+        assert_eq!(first, 9);
+        let updated = options.update(&x, &y, &[None; 20], &keep);
+        assert_eq!(updated[..first], keep[..first]);
+        assert_eq!(committed(&updated), 19);
+        assert_eq!(committed(&[]), 0);
+    }
+
+    /// Pushes every point, returning the indices of the kept ones.
+    fn stream(mut simplifier: StreamingSimplifier, x: &[i64], y: &[i64]) -> Vec<usize> {
+        let mut kept = Vec::new();
+        for i in 0..x.len() {
+            let point = StreamPoint {
+                latitude: x[i],
+                longitude: y[i],
+                timestamp: i as u64,
+            };
+            kept.extend(simplifier.push(point).into_iter().flatten());
+        }
+        kept.extend(simplifier.finish().into_iter().flatten());
+        kept.iter().map(|point| point.timestamp as usize).collect()
+    }
+
+    #[test]
+    fn test_streaming_simplifier() {
+        // Along a straight line, then a right angle at 10
+        let x: Vec<i64> = (0..=20).map(|i| i.min(10) * 10).collect();
+        let y: Vec<i64> = (0..=20).map(|i| (i - 10).max(0) * 10).collect();
+        assert_eq!(
+            stream(StreamingSimplifier::new(1, 100), &x, &y),
+            [0, 10, 20]
+        );
+        // A full window is closed at its newest point
+        assert_eq!(
+            stream(StreamingSimplifier::new(1, 8), &x, &y),
+            [0, 7, 10, 17, 20]
+        );
+        assert!(stream(StreamingSimplifier::new(1, 8), &[], &[]).is_empty());
+        assert_eq!(stream(StreamingSimplifier::new(1, 8), &[5], &[5]), [0]);
+    }
+
+    #[test]
+    fn test_streaming_simplifier_tolerance() {
+        let x: Vec<i64> = (0..300).map(|i| i * 10).collect();
+        let y: Vec<i64> = (0..300).map(|i| (i * i * 7) % 101).collect();
+        let kept = stream(StreamingSimplifier::new(30, 50), &x, &y);
+        assert_eq!((kept[0], *kept.last().unwrap()), (0, 299));
+        for pair in kept.windows(2) {
+            let (distance_squared, _) = simplify::max_distance(&x, &y, pair[0], pair[1]);
+            assert!(distance_squared <= 30 * 30, "{pair:?}");
+        }
+    }
+}
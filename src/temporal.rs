@@ -0,0 +1,158 @@
+//! Trip counts and distances by day of the week and hour of the day, per user.
+//!
+//! Every trip counts once, in the hour it starts, with its whole distance. Times are in
+//! UTC, like the timestamps of GeoLife.
+
+use crate::trajectory::Trajectory;
+use chrono::{DateTime, Datelike, Timelike};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+/// Names of the days of the week, from Monday
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// Trips starting in one hour of the week.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct HourCell {
+    pub trips: usize,
+    pub distance_m: f64,
+}
+
+/// Table of the trips of one user, by day of the week from Monday and hour of the day.
+pub type WeekTable = [[HourCell; 24]; 7];
+
+/// Trips of every user by hour of the week.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TemporalReport {
+    pub users: BTreeMap<String, WeekTable>,
+}
+
+impl TemporalReport {
+    /// Counts a trip of `user`. Trips without points are ignored.
+    pub fn add_trip(&mut self, user: &str, trip: &Trajectory) {
+        let Some(start) = trip
+            .timestamps
+            .first()
+            .and_then(|&ts| DateTime::from_timestamp(ts as i64, 0))
+        else {
+            return;
+        };
+        let table = self
+            .users
+            .entry(user.to_string())
+            .or_insert_with(|| [[HourCell::default(); 24]; 7]);
+        let cell =
+            &mut table[start.weekday().num_days_from_monday() as usize][start.hour() as usize];
+        cell.trips += 1;
+        cell.distance_m += trip.length_m();
+    }
+
+    /// Writes one line per user and hour of the week with trips.
+    pub fn csv(&self) -> String {
+        let mut csv = String::from("user,weekday,hour,trips,distance_km\n");
+        for (user, table) in &self.users {
+            for (day, hours) in WEEKDAYS.iter().zip(table) {
+                for (hour, cell) in hours.iter().enumerate() {
+                    if cell.trips > 0 {
+                        csv.push_str(&format!(
+                            "{user},{day},{hour},{},{:.3}\n",
+                            cell.trips,
+                            cell.distance_m / 1000.0
+                        ));
+                    }
+                }
+            }
+        }
+        csv
+    }
+
+    /// Builds a heat table per user: for each day of the week, the number of trips and the
+    /// distance in kilometers at every hour of the day.
+    pub fn json(&self) -> Value {
+        let users: Map<String, Value> = self
+            .users
+            .iter()
+            .map(|(user, table)| {
+                let days: Map<String, Value> = WEEKDAYS
+                    .iter()
+                    .zip(table)
+                    .map(|(day, hours)| {
+                        let trips: Vec<usize> = hours.iter().map(|cell| cell.trips).collect();
+                        let distance_km: Vec<f64> =
+                            hours.iter().map(|cell| cell.distance_m / 1000.0).collect();
+                        (
+                            day.to_string(),
+                            json!({ "trips": trips, "distance_km": distance_km }),
+                        )
+                    })
+                    .collect();
+                (user.clone(), Value::Object(days))
+            })
+            .collect();
+        json!({ "users": users })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trajectory::CoordinateSystem;
+
+    /// A trip of about 1.1 km starting at `start`
+    fn trip(start: u64) -> Trajectory {
+        Trajectory {
+            latitudes: vec![0, 10_000],
+            longitudes: vec![0, 0],
+            timestamps: vec![start, start + 600],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        }
+    }
+
+    #[test]
+    fn test_temporal_report() {
+        // Thursday 2008/04/03 08:30 UTC, and the next Monday at 17:59
+        let thursday = 1_207_211_400;
+        let monday = thursday + 4 * 86_400 + 9 * 3_600 + 29 * 60;
+
+        let mut report = TemporalReport::default();
+        report.add_trip("000", &trip(thursday));
+        report.add_trip("000", &trip(thursday + 1_200));
+        report.add_trip("000", &trip(monday));
+        report.add_trip("001", &trip(monday));
+        report.add_trip(
+            "002",
+            &Trajectory {
+                latitudes: vec![],
+                longitudes: vec![],
+                timestamps: vec![],
+                altitudes: Vec::new(),
+                coordinates: CoordinateSystem::Wgs84,
+            },
+        );
+
+        assert_eq!(report.users.len(), 2);
+        assert_eq!(report.users["000"][3][8].trips, 2);
+        assert!((report.users["000"][3][8].distance_m - 2_223.9).abs() < 1.0);
+        assert_eq!(report.users["000"][0][17].trips, 1);
+
+        assert_eq!(
+            report.csv(),
+            "user,weekday,hour,trips,distance_km\n\
+             000,Mon,17,1,1.112\n\
+             000,Thu,8,2,2.224\n\
+             001,Mon,17,1,1.112\n"
+        );
+
+        let json = report.json();
+        assert_eq!(json["users"]["000"]["Thu"]["trips"][8], 2);
+        assert_eq!(json["users"]["001"]["Sun"]["trips"][0], 0);
+        assert_eq!(
+            json["users"]["001"]["Mon"]["trips"]
+                .as_array()
+                .unwrap()
+                .len(),
+            24
+        );
+    }
+}
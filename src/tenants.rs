@@ -0,0 +1,159 @@
+//! Tenants of a shared deployment of the servers, each with its own API keys and its own
+//! namespace of the storage, so that several apps are served in isolation.
+//!
+//! Tenants are read from a TOML file with one table per tenant:
+//!
+//! ```toml
+//! [fleet]
+//! api_keys = ["3f9c0e7d51a2", "a81b44c09e6f"]
+//!
+//! [bikes]
+//! api_keys = ["77d2e5b0c413"]
+//! ```
+//!
+//! The files of a tenant are read from the directory named after it, and its trajectories
+//! are stored in its namespace of a store (see `store::NamespacedStore`), so tenant names
+//! are restricted to ASCII letters, digits, `-` and `_`.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use toml_edit::DocumentMut;
+
+#[derive(Error, Debug)]
+pub enum TenantError {
+    #[error("Error while reading tenants file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid TOML: {0}")]
+    Toml(#[from] toml_edit::TomlError),
+    #[error("Invalid tenant name: {0}")]
+    InvalidName(String),
+    #[error("Invalid API keys for tenant {0}")]
+    InvalidKeys(String),
+    #[error("API key of tenant {0} is already used by another tenant")]
+    DuplicateKey(String),
+}
+
+/// API keys of every tenant.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Tenants {
+    /// Tenant of every API key
+    by_key: BTreeMap<String, String>,
+    names: Vec<String>,
+}
+
+fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+impl Tenants {
+    /// Parses the tenants from TOML.
+    pub fn parse(toml: &str) -> Result<Tenants, TenantError> {
+        let document: DocumentMut = toml.parse()?;
+        let mut tenants = Tenants::default();
+        for (name, item) in document.iter() {
+            if !valid_name(name) {
+                return Err(TenantError::InvalidName(name.to_string()));
+            }
+            let keys = item
+                .get("api_keys")
+                .and_then(|keys| keys.as_array())
+                .ok_or_else(|| TenantError::InvalidKeys(name.to_string()))?;
+            for key in keys {
+                let key = key
+                    .as_str()
+                    .filter(|key| !key.is_empty())
+                    .ok_or_else(|| TenantError::InvalidKeys(name.to_string()))?;
+                if tenants
+                    .by_key
+                    .insert(key.to_string(), name.to_string())
+                    .is_some()
+                {
+                    return Err(TenantError::DuplicateKey(name.to_string()));
+                }
+            }
+            tenants.names.push(name.to_string());
+        }
+        Ok(tenants)
+    }
+
+    /// Reads the tenants from a TOML file.
+    pub fn load(path: &Path) -> Result<Tenants, TenantError> {
+        Tenants::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Names of the tenants, in the order of the file.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Returns the tenant an API key belongs to, if any.
+    pub fn authenticate(&self, api_key: &str) -> Option<&str> {
+        self.by_key.get(api_key).map(String::as_str)
+    }
+}
+
+/// Directory under `root` holding the files of `tenant`, refusing names such as `..` or
+/// `a/b` that would lead out of it.
+pub fn namespace(root: &Path, tenant: &str) -> Result<PathBuf, TenantError> {
+    if !valid_name(tenant) {
+        return Err(TenantError::InvalidName(tenant.to_string()));
+    }
+    Ok(root.join(tenant))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let tenants = Tenants::parse(
+            r#"
+            [fleet]
+            api_keys = ["k1", "k2"]
+
+            [bikes]
+            api_keys = ["k3"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(tenants.names(), ["fleet", "bikes"]);
+        assert_eq!(tenants.authenticate("k2"), Some("fleet"));
+        assert_eq!(tenants.authenticate("k3"), Some("bikes"));
+        assert_eq!(tenants.authenticate("k4"), None);
+        assert_eq!(
+            namespace(Path::new("data"), "bikes").unwrap(),
+            PathBuf::from("data/bikes")
+        );
+        for name in ["..", "../etc", "a/b", ""] {
+            assert!(matches!(
+                namespace(Path::new("data"), name),
+                Err(TenantError::InvalidName(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(
+            Tenants::parse("[\"../etc\"]\napi_keys = [\"k\"]"),
+            Err(TenantError::InvalidName(name)) if name == "../etc"
+        ));
+        assert!(matches!(
+            Tenants::parse("[\"a/b\"]\napi_keys = [\"k\"]"),
+            Err(TenantError::InvalidName(name)) if name == "a/b"
+        ));
+        assert!(matches!(
+            Tenants::parse("[fleet]\napi_keys = \"k\""),
+            Err(TenantError::InvalidKeys(_))
+        ));
+        assert!(matches!(
+            Tenants::parse("[a]\napi_keys = [\"k\"]\n[b]\napi_keys = [\"k\"]"),
+            Err(TenantError::DuplicateKey(tenant)) if tenant == "b"
+        ));
+    }
+}
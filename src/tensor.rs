@@ -0,0 +1,162 @@
+//! Export of trajectories as flat `f32` tensors for neural trajectory models.
+//!
+//! Each trajectory is resampled to a fixed number of points and normalized: the
+//! coordinates are centered on their mean and divided by their largest absolute
+//! offset, so every value lies in `[-1, 1]` and the aspect ratio is preserved.
+//! The tensor has shape `[trajectories, points, 2]` with `(latitude, longitude)` pairs.
+
+use crate::resample::{resample_by_time, ResampleError};
+use crate::trajectory::Trajectory;
+use std::io::{self, Write};
+
+/// On-disk layout of the tensor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TensorFormat {
+    /// NumPy `.npy` file (version 1.0, little-endian `f32`)
+    Npy,
+    /// Raw little-endian `f32` values, without header
+    Raw,
+}
+
+/// Resamples the trajectory to `points` points and returns its normalized
+/// `(latitude, longitude)` pairs, flattened.
+///
+/// # Errors
+///
+/// Returns an error if the timestamps of the trajectory are not sorted.
+pub fn normalized_features(
+    trajectory: &Trajectory,
+    points: usize,
+) -> Result<Vec<f32>, ResampleError> {
+    let resampled = resample_by_time(trajectory, points)?;
+    let len = resampled.latitudes.len();
+    if len == 0 {
+        return Ok(vec![0.0; points * 2]);
+    }
+
+    let mean = |values: &[i64]| values.iter().map(|&v| v as f64).sum::<f64>() / len as f64;
+    let mean_latitude = mean(&resampled.latitudes);
+    let mean_longitude = mean(&resampled.longitudes);
+
+    let offsets: Vec<(f64, f64)> = resampled
+        .latitudes
+        .iter()
+        .zip(&resampled.longitudes)
+        .map(|(&lat, &lon)| (lat as f64 - mean_latitude, lon as f64 - mean_longitude))
+        .collect();
+    let scale = offsets
+        .iter()
+        .map(|&(lat, lon)| lat.abs().max(lon.abs()))
+        .fold(0.0, f64::max);
+    let scale = if scale > 0.0 { scale } else { 1.0 };
+
+    Ok(offsets
+        .into_iter()
+        .flat_map(|(lat, lon)| [(lat / scale) as f32, (lon / scale) as f32])
+        .collect())
+}
+
+/// Writes the NumPy `.npy` header for a little-endian `f32` array of the given shape.
+fn write_npy_header(writer: &mut impl Write, shape: &[usize]) -> io::Result<()> {
+    let shape = match shape {
+        [len] => format!("({len},)"),
+        _ => format!(
+            "({})",
+            shape.iter().map(usize::to_string).collect::<Vec<_>>().join(", ")
+        ),
+    };
+    let mut header = format!("{{'descr': '<f4', 'fortran_order': False, 'shape': {shape}, }}");
+
+    // Magic (6) + version (2) + header length (2) + header must be a multiple of 64 bytes,
+    // the header being terminated by a newline.
+    let unpadded = 10 + header.len() + 1;
+    header.push_str(&" ".repeat((64 - unpadded % 64) % 64));
+    header.push('\n');
+
+    writer.write_all(b"\x93NUMPY\x01\x00")?;
+    writer.write_all(&(header.len() as u16).to_le_bytes())?;
+    writer.write_all(header.as_bytes())
+}
+
+/// Writes the trajectories as a `[trajectories, points, 2]` tensor.
+///
+/// # Errors
+///
+/// Fails with `io::ErrorKind::InvalidInput` if the timestamps of a trajectory are not
+/// sorted, or with the error of the writer.
+pub fn write_tensor(
+    writer: &mut impl Write,
+    trajectories: &[Trajectory],
+    points: usize,
+    format: TensorFormat,
+) -> io::Result<()> {
+    if format == TensorFormat::Npy {
+        write_npy_header(writer, &[trajectories.len(), points, 2])?;
+    }
+    for trajectory in trajectories {
+        let features = normalized_features(trajectory, points)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+        for value in features {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trajectory::CoordinateSystem;
+
+    fn sample() -> Trajectory {
+        Trajectory {
+            latitudes: vec![0, 100, 100],
+            longitudes: vec![0, 0, 300],
+            timestamps: vec![0, 10, 40],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        }
+    }
+
+    #[test]
+    fn test_normalized_features_range() {
+        let features = normalized_features(&sample(), 5).unwrap();
+
+        assert_eq!(features.len(), 10);
+        assert!(features.iter().all(|v| (-1.0..=1.0).contains(v)));
+        assert!(features.iter().any(|&v| v == 1.0 || v == -1.0));
+    }
+
+    #[test]
+    fn test_normalized_features_stationary() {
+        let trajectory = Trajectory {
+            latitudes: vec![5, 5],
+            longitudes: vec![7, 7],
+            timestamps: vec![0, 10],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+        assert_eq!(normalized_features(&trajectory, 3).unwrap(), vec![0.0; 6]);
+    }
+
+    #[test]
+    fn test_write_npy() {
+        let mut output = Vec::new();
+        write_tensor(&mut output, &[sample(), sample()], 4, TensorFormat::Npy).unwrap();
+
+        let header_len = u16::from_le_bytes([output[8], output[9]]) as usize;
+        let header = std::str::from_utf8(&output[10..10 + header_len]).unwrap();
+        assert_eq!(&output[..8], b"\x93NUMPY\x01\x00");
+        assert_eq!((10 + header_len) % 64, 0);
+        assert!(header.contains("'shape': (2, 4, 2)"));
+        assert!(header.ends_with('\n'));
+        assert_eq!(output.len(), 10 + header_len + 2 * 4 * 2 * 4);
+    }
+
+    #[test]
+    fn test_write_raw() {
+        let mut output = Vec::new();
+        write_tensor(&mut output, &[sample()], 4, TensorFormat::Raw).unwrap();
+        assert_eq!(output.len(), 4 * 2 * 4);
+    }
+}
@@ -0,0 +1,66 @@
+//! Thumbnails of trajectories: extreme simplifications to a few dozen points, for list
+//! views and search results that render thousands of trip previews.
+//!
+//! A tolerance cannot bound the size of a thumbnail, so the most significant points are
+//! kept in the priority order of `simplify::simplify_top_k` whatever the length of the
+//! trajectory. Latitudes and longitudes are projected to Web Mercator pixels first (see
+//! `tile`), the plane previews are drawn in.
+
+use crate::simplify;
+use crate::tile;
+use crate::trajectory::{CoordinateSystem, Trajectory};
+
+/// Most points of a thumbnail
+pub const MAX_POINTS: usize = 32;
+
+/// Keeps the `max_points` most significant points of a trajectory, its endpoints
+/// included, or every point of a shorter trajectory. Timestamps and altitudes of the kept
+/// points are kept.
+pub fn thumbnail(trajectory: &Trajectory, max_points: usize) -> Trajectory {
+    let keep_points = match trajectory.coordinates {
+        CoordinateSystem::Wgs84 => {
+            let (xs, ys) = tile::project_trajectory(&trajectory.latitudes, &trajectory.longitudes);
+            simplify::simplify_top_k(&xs, &ys, max_points)
+        }
+        CoordinateSystem::Local { .. } => {
+            simplify::simplify_top_k(&trajectory.longitudes, &trajectory.latitudes, max_points)
+        }
+    };
+    trajectory.clone().filter(&keep_points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A zigzag walk of `len` points east from Beijing.
+    fn zigzag(len: usize) -> Trajectory {
+        Trajectory {
+            latitudes: (0..len)
+                .map(|i| 39_900_000 + (i % 2) as i64 * 500)
+                .collect(),
+            longitudes: (0..len as i64).map(|i| 116_400_000 + i * 1_000).collect(),
+            timestamps: (0..len as u64).map(|i| i * 10).collect(),
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        }
+    }
+
+    #[test]
+    fn test_thumbnail() {
+        let trajectory = zigzag(1_000);
+        let preview = thumbnail(&trajectory, MAX_POINTS);
+        assert_eq!(preview.latitudes.len(), MAX_POINTS);
+        assert_eq!(preview.timestamps.first(), Some(&0));
+        assert_eq!(preview.timestamps.last(), Some(&9_990));
+
+        let short = zigzag(10);
+        assert_eq!(thumbnail(&short, MAX_POINTS), short);
+
+        let local = Trajectory {
+            coordinates: CoordinateSystem::Local { scale: 3 },
+            ..zigzag(100)
+        };
+        assert_eq!(thumbnail(&local, 8).timestamps.len(), 8);
+    }
+}
@@ -0,0 +1,136 @@
+//! Projection to Web Mercator tile pixels, for simplifying trajectories per zoom level.
+//!
+//! Map renderers draw at a given zoom level, where a tolerance is naturally expressed in
+//! screen pixels rather than in meters or degrees.
+
+use crate::geo::to_degrees;
+use crate::simplify;
+
+/// Width and height in pixels of a map tile
+pub const TILE_SIZE: f64 = 256.0;
+
+/// Largest latitude in degrees that Web Mercator can represent
+pub const MAX_LATITUDE: f64 = 85.051_128_78;
+
+/// Highest supported zoom level
+pub const MAX_ZOOM: u8 = 18;
+
+/// Number of fixed-point subdivisions of a pixel used for simplification
+const SUBPIXELS: f64 = 256.0;
+
+/// Projects a scaled coordinate to global pixel coordinates `(x, y)` at the given zoom level.
+///
+/// `x` grows eastward and `y` southward from the top-left corner of the world map, which
+/// is `TILE_SIZE * 2^zoom` pixels wide.
+pub fn project_to_pixels(latitude: i64, longitude: i64, zoom: u8) -> (f64, f64) {
+    let world_size = TILE_SIZE * 2f64.powi(zoom.into());
+    let lat = to_degrees(latitude)
+        .clamp(-MAX_LATITUDE, MAX_LATITUDE)
+        .to_radians();
+    let lon = to_degrees(longitude);
+
+    let x = (lon + 180.0) / 360.0 * world_size;
+    let y = (1.0 - (lat.tan() + 1.0 / lat.cos()).ln() / std::f64::consts::PI) / 2.0 * world_size;
+    (x, y)
+}
+
+/// Projects a trajectory to fixed-point pixels at `MAX_ZOOM`.
+///
+/// Pixel coordinates at lower zoom levels are the same divided by a power of two, so a
+/// single projection serves every zoom level when paired with `zoom_epsilon`.
+///
+/// # Panics
+///
+/// This function will panic if `latitudes` and `longitudes` have different lengths.
+pub fn project_trajectory(latitudes: &[i64], longitudes: &[i64]) -> (Vec<i64>, Vec<i64>) {
+    assert_eq!(
+        latitudes.len(),
+        longitudes.len(),
+        "latitudes.len() == longitudes.len()"
+    );
+
+    latitudes
+        .iter()
+        .zip(longitudes)
+        .map(|(&lat, &lon)| {
+            let (x, y) = project_to_pixels(lat, lon, MAX_ZOOM);
+            ((x * SUBPIXELS).round() as i64, (y * SUBPIXELS).round() as i64)
+        })
+        .unzip()
+}
+
+/// Converts a tolerance of `epsilon_px` pixels at `zoom` to the units of `project_trajectory`.
+///
+/// # Panics
+///
+/// This function will panic if `zoom` is above `MAX_ZOOM`.
+pub fn zoom_epsilon(zoom: u8, epsilon_px: f64) -> i64 {
+    assert!(zoom <= MAX_ZOOM, "zoom <= MAX_ZOOM");
+    (epsilon_px * SUBPIXELS * 2f64.powi((MAX_ZOOM - zoom).into())).round() as i64
+}
+
+/// Simplifies a trajectory for rendering at the given zoom level.
+///
+/// The points are projected to tile pixels and simplified with a tolerance of
+/// `epsilon_px` pixels.
+///
+/// # Returns
+///
+/// A vector of booleans, one per point, telling whether the point is drawn.
+///
+/// # Panics
+///
+/// This function will panic if `latitudes` and `longitudes` have different lengths, or
+/// if `zoom` is above `MAX_ZOOM`.
+pub fn simplify_for_zoom(
+    latitudes: &[i64],
+    longitudes: &[i64],
+    zoom: u8,
+    epsilon_px: f64,
+) -> Vec<bool> {
+    let (xs, ys) = project_trajectory(latitudes, longitudes);
+    simplify::simplify(&xs, &ys, zoom_epsilon(zoom, epsilon_px))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project_to_pixels() {
+        assert_eq!(project_to_pixels(0, 0, 0), (128.0, 128.0));
+        assert_eq!(project_to_pixels(0, -180_000_000, 1), (0.0, 256.0));
+
+        let (x, y) = project_to_pixels(90_000_000, 180_000_000, 2);
+        assert_eq!(x, 1024.0);
+        assert!(y.abs() < 1e-6, "{y}");
+
+        // Tiananmen at zoom 10 lies in tile (843, 388)
+        let (x, y) = project_to_pixels(39_908_823, 116_397_470, 10);
+        assert_eq!(((x / TILE_SIZE) as u32, (y / TILE_SIZE) as u32), (843, 388));
+    }
+
+    #[test]
+    fn test_zoom_epsilon() {
+        assert_eq!(zoom_epsilon(MAX_ZOOM, 1.0), 256);
+        assert_eq!(zoom_epsilon(MAX_ZOOM - 1, 1.0), 512);
+        assert_eq!(zoom_epsilon(MAX_ZOOM - 1, 1.0), zoom_epsilon(MAX_ZOOM, 2.0));
+    }
+
+    #[test]
+    fn test_simplify_for_zoom() {
+        // A 100 m detour along a 2 km street in Beijing
+        let latitudes = vec![39_900_000, 39_900_900, 39_900_000, 39_900_000];
+        let longitudes = vec![116_400_000, 116_410_000, 116_420_000, 116_425_000];
+
+        // Less than a pixel at zoom 10, about 100 pixels at zoom 17
+        assert_eq!(
+            simplify_for_zoom(&latitudes, &longitudes, 10, 1.0),
+            vec![true, false, false, true]
+        );
+        assert_eq!(
+            simplify_for_zoom(&latitudes, &longitudes, 17, 1.0),
+            vec![true, true, true, true]
+        );
+    }
+}
@@ -1,20 +1,125 @@
+use crate::corridor::{Corridor, Polygon};
+use crate::delta::{delta_decode, delta_encode};
+use crate::geo::{haversine_m, segment_distance_m, to_degrees, METERS_PER_UNIT};
+use crate::geojson::{self, GeoJsonError};
+use crate::geom;
 use crate::point::Point;
 use crate::proto;
+use crate::proto::TimestampUnit;
+use serde_json::Value;
+use thiserror::Error;
+
+/// Errors that can occur while encoding a trajectory.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum EncodeError {
+    #[error("Timestamp at index {index} is earlier than the previous one")]
+    NonMonotonicTimestamp { index: usize },
+}
+
+/// Errors that can occur while decoding a trajectory from a protobuf message.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    #[error(
+        "Columns have different lengths: {latitudes} latitudes, {longitudes} longitudes, \
+         {timestamps} timestamps"
+    )]
+    ColumnLengths {
+        latitudes: usize,
+        longitudes: usize,
+        timestamps: usize,
+    },
+    #[error("Unknown timestamp unit: {0}")]
+    UnknownTimestampUnit(i32),
+}
 
 /// A trajectory represents a sequence of GPS points with their timestamps.
 /// The coordinates are stored as scaled integers for efficient storage and processing.
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Trajectory {
-    /// Latitude values scaled by 10^6
+    /// Latitude values scaled by 10^6, or north coordinates in a local system
     pub latitudes: Vec<i64>,
-    /// Longitude values scaled by 10^6
+    /// Longitude values scaled by 10^6, or east coordinates in a local system
     pub longitudes: Vec<i64>,
     /// Unix timestamps in seconds
     pub timestamps: Vec<u64>,
+    /// Altitudes in meters scaled by 10^`ALTITUDE_SCALE`, one per point, or empty when the
+    /// points have no altitude
+    pub altitudes: Vec<i64>,
+    /// How to read `latitudes` and `longitudes`
+    pub coordinates: CoordinateSystem,
 }
 
 /// Scale factor for coordinate precision (10^6 = 1 microdegree ≈ 11cm at equator)
-const SCALE: u32 = 6;
+pub const SCALE: u32 = 6;
+
+/// Scale factor for altitude precision (10^3 = 1 millimeter)
+pub const ALTITUDE_SCALE: u32 = 3;
+
+/// Coordinate system of the points of a trajectory.
+///
+/// Distance metrics measure trajectories in meters whatever their system, and tell when
+/// two trajectories cannot be compared because their systems differ.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CoordinateSystem {
+    /// GPS latitudes and longitudes in degrees, scaled by 10^`SCALE`
+    #[default]
+    Wgs84,
+    /// Local east-north coordinates in meters scaled by 10^`scale`, such as indoor
+    /// positioning or ENU frames: `longitudes` hold the east coordinates and `latitudes`
+    /// the north ones
+    Local { scale: u32 },
+}
+
+impl CoordinateSystem {
+    /// Number of scaled units per meter in a local system.
+    fn units_per_meter(scale: u32) -> f64 {
+        10f64.powi(scale as i32)
+    }
+
+    /// Distance in meters between two `(latitude, longitude)` points.
+    pub fn distance_m(self, (lat1, lon1): (i64, i64), (lat2, lon2): (i64, i64)) -> f64 {
+        match self {
+            CoordinateSystem::Wgs84 => haversine_m(lat1, lon1, lat2, lon2),
+            CoordinateSystem::Local { scale } => {
+                ((lat2 - lat1) as f64).hypot((lon2 - lon1) as f64) / Self::units_per_meter(scale)
+            }
+        }
+    }
+
+    /// Offset `(east, north)` in meters from the point `from` to the point `to`, in a flat
+    /// approximation around `from` for `Wgs84`.
+    pub fn offset_m(self, from: (i64, i64), to: (i64, i64)) -> (f64, f64) {
+        let (east, north) = ((to.1 - from.1) as f64, (to.0 - from.0) as f64);
+        match self {
+            CoordinateSystem::Wgs84 => {
+                let cos_lat = to_degrees(from.0).to_radians().cos();
+                (east * cos_lat * METERS_PER_UNIT, north * METERS_PER_UNIT)
+            }
+            CoordinateSystem::Local { scale } => {
+                let units_per_meter = Self::units_per_meter(scale);
+                (east / units_per_meter, north / units_per_meter)
+            }
+        }
+    }
+
+    /// Distance in meters from a point to the segment between two other points.
+    pub fn segment_distance_m(self, p: (i64, i64), a: (i64, i64), b: (i64, i64)) -> f64 {
+        match self {
+            CoordinateSystem::Wgs84 => segment_distance_m(p, a, b),
+            CoordinateSystem::Local { scale } => {
+                let (dx, dy) = ((b.1 - a.1) as f64, (b.0 - a.0) as f64);
+                let (px, py) = ((p.1 - a.1) as f64, (p.0 - a.0) as f64);
+                let length_squared = dx * dx + dy * dy;
+                let t = if length_squared == 0.0 {
+                    0.0
+                } else {
+                    ((px * dx + py * dy) / length_squared).clamp(0.0, 1.0)
+                };
+                (px - t * dx).hypot(py - t * dy) / Self::units_per_meter(scale)
+            }
+        }
+    }
+}
 
 impl Trajectory {
     /// Creates a new trajectory from a sequence of GPS points.
@@ -27,20 +132,36 @@ impl Trajectory {
     ///
     /// A new `Trajectory` instance with coordinates scaled to integers
     pub fn new(points: Vec<Point>) -> Self {
+        Self::with_coordinates(points, CoordinateSystem::Wgs84)
+    }
+
+    /// Creates a new trajectory from a sequence of points in the given coordinate system,
+    /// scaling their coordinates by 10^`SCALE` for `Wgs84` and by 10^`scale` for `Local`.
+    ///
+    /// Altitudes are kept when at least one point has one; the points without altitude then
+    /// take the altitude of the point before them (or of the first point with one).
+    pub fn with_coordinates(points: Vec<Point>, coordinates: CoordinateSystem) -> Self {
+        let scale = match coordinates {
+            CoordinateSystem::Wgs84 => SCALE,
+            CoordinateSystem::Local { scale } => scale,
+        };
         let capacity = points.len();
         let mut trajectory = Trajectory {
             latitudes: Vec::with_capacity(capacity),
             longitudes: Vec::with_capacity(capacity),
             timestamps: Vec::with_capacity(capacity),
+            altitudes: Vec::new(),
+            coordinates,
         };
+        let mut altitudes = Vec::with_capacity(capacity);
 
         for point in points {
             let ts: u64 = point.datetime.timestamp().try_into().unwrap();
             let mut latitude = point.latitude;
             let mut longitude = point.longitude;
 
-            latitude.rescale(SCALE);
-            longitude.rescale(SCALE);
+            latitude.rescale(scale);
+            longitude.rescale(scale);
 
             let latitude_i64: i64 = latitude.mantissa().try_into().unwrap();
             let longitude_i64: i64 = longitude.mantissa().try_into().unwrap();
@@ -48,46 +169,149 @@ impl Trajectory {
             trajectory.latitudes.push(latitude_i64);
             trajectory.longitudes.push(longitude_i64);
             trajectory.timestamps.push(ts);
+            altitudes.push(point.altitude.map(|mut altitude| {
+                altitude.rescale(ALTITUDE_SCALE);
+                i64::try_from(altitude.mantissa()).unwrap()
+            }));
+        }
+
+        // Points without altitude take the one of the previous point, or of the first point
+        // with one at the start
+        if let Some(first) = altitudes.iter().copied().flatten().next() {
+            let mut previous = first;
+            trajectory.altitudes = altitudes
+                .into_iter()
+                .map(|altitude| {
+                    previous = altitude.unwrap_or(previous);
+                    previous
+                })
+                .collect();
         }
 
         trajectory
     }
 
-    /// Converts the trajectory to a protobuf message using delta encoding.
-    /// Delta encoding stores the difference between consecutive values,
-    /// which can lead to better compression for smooth trajectories.
-    /// 
-    /// This function consumes the trajectory.
-    pub fn to_delta_proto(self) -> proto::Trajectory {
-        let latitudes: Vec<i64> = self.latitudes.into_iter()
-            .scan(0_i64, |last, lat| {
-                let delta = lat - *last;
-                *last = lat;
-                Some(delta)
-            })
-            .collect();
+    /// Keeps only the points whose entry in `keep` is `true`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `keep` does not have one entry per point.
+    pub fn filter(mut self, keep: &[bool]) -> Self {
+        assert_eq!(keep.len(), self.latitudes.len(), "keep.len() == len");
 
-        let longitudes: Vec<i64> = self.longitudes.into_iter()
-            .scan(0_i64, |last, lon| {
-                let delta = lon - *last;
-                *last = lon;
-                Some(delta)
-            })
-            .collect();
+        // Filter every column in a single pass
+        let mut i = 0;
+        self.latitudes.retain(|_| {
+            let keep = keep[i];
+            i += 1;
+            keep
+        });
 
-        let timestamps: Vec<u64> = self.timestamps.into_iter()
-            .scan(0_u64, |last, ts| {
-                let delta = ts - *last;
-                *last = ts;
-                Some(delta)
-            })
-            .collect();
+        i = 0;
+        self.longitudes.retain(|_| {
+            let keep = keep[i];
+            i += 1;
+            keep
+        });
 
-        proto::Trajectory {
-            latitudes,
-            longitudes,
-            timestamps,
+        i = 0;
+        self.timestamps.retain(|_| {
+            let keep = keep[i];
+            i += 1;
+            keep
+        });
+
+        i = 0;
+        self.altitudes.retain(|_| {
+            let keep = keep[i];
+            i += 1;
+            keep
+        });
+
+        self
+    }
+
+    /// Reverses the trajectory, as if the same path had been traveled backwards.
+    ///
+    /// The reversed trajectory starts at the same time as the original one and keeps
+    /// the time intervals between points, so its timestamps remain non-decreasing.
+    pub fn reverse(mut self) -> Self {
+        let first = self.timestamps.first().copied().unwrap_or_default();
+        let last = self.timestamps.last().copied().unwrap_or_default();
+
+        self.latitudes.reverse();
+        self.longitudes.reverse();
+        self.timestamps.reverse();
+        self.altitudes.reverse();
+        for ts in &mut self.timestamps {
+            *ts = first + (last - *ts);
         }
+
+        self
+    }
+
+    /// Length in meters of the path through every point.
+    pub fn length_m(&self) -> f64 {
+        (1..self.latitudes.len())
+            .map(|i| {
+                self.coordinates.distance_m(
+                    (self.latitudes[i - 1], self.longitudes[i - 1]),
+                    (self.latitudes[i], self.longitudes[i]),
+                )
+            })
+            .sum()
+    }
+
+    /// Computes the convex hull of the points, on the scaled coordinates with longitude as
+    /// `x` and latitude as `y` (see `geom::convex_hull`).
+    ///
+    /// # Returns
+    ///
+    /// The indices of the hull vertices, counter-clockwise on a map.
+    pub fn convex_hull(&self) -> Vec<usize> {
+        geom::convex_hull(&self.longitudes, &self.latitudes)
+    }
+
+    /// Computes the area within `radius_m` meters of the trajectory, in a flat-earth
+    /// approximation (see `Corridor::polygon`).
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `radius_m` is not positive.
+    pub fn buffer(&self, radius_m: f64) -> Polygon {
+        Corridor::new(self, radius_m).polygon()
+    }
+
+    /// Builds a GeoJSON `Feature` with the trajectory as a `LineString` and its timestamps
+    /// as a property (see `geojson`).
+    pub fn to_geojson(&self) -> Value {
+        geojson::to_geojson(self)
+    }
+
+    /// Reads the trajectories of a GeoJSON `LineString`, `MultiLineString`, `Feature` or
+    /// `FeatureCollection` (see `geojson::from_geojson`).
+    pub fn from_geojson(value: &Value) -> Result<Vec<Trajectory>, GeoJsonError> {
+        geojson::from_geojson(value)
+    }
+
+    /// Converts the trajectory to a protobuf message using delta encoding.
+    /// Delta encoding stores the difference between consecutive values,
+    /// which can lead to better compression for smooth trajectories.
+    ///
+    /// Timestamps are stored as unsigned deltas, so they must be sorted in
+    /// non-decreasing order. An `EncodeError::NonMonotonicTimestamp` is
+    /// returned otherwise.
+    ///
+    /// This function consumes the trajectory.
+    #[allow(clippy::wrong_self_convention)]
+    pub fn to_delta_proto(self) -> Result<proto::Trajectory, EncodeError> {
+        self.to_proto_with(
+            true,
+            TimestampFormat {
+                unit: TimestampUnit::Seconds,
+                encoding: TimestampEncoding::Delta,
+            },
+        )
     }
 
     /// Converts the trajectory to a protobuf message using absolute values.
@@ -95,20 +319,151 @@ impl Trajectory {
     /// or when random access to coordinates is needed.
     /// 
     /// This function consumes the trajectory.
+    #[allow(clippy::wrong_self_convention)]
     pub fn to_proto(self) -> proto::Trajectory {
         proto::Trajectory {
             latitudes: self.latitudes,
             longitudes: self.longitudes,
             timestamps: self.timestamps,
+            timestamp_unit: TimestampUnit::Seconds.into(),
+            reconstruction: None,
         }
     }
+
+    /// Converts the trajectory to a protobuf message, with coordinates delta-encoded if
+    /// `delta_coordinates` is set and timestamps written in the given format. The unit is
+    /// recorded in the message so readers can tell seconds from milliseconds.
+    ///
+    /// Delta-encoded timestamps must be sorted in non-decreasing order. An
+    /// `EncodeError::NonMonotonicTimestamp` is returned otherwise.
+    ///
+    /// This function consumes the trajectory.
+    #[allow(clippy::wrong_self_convention)]
+    pub fn to_proto_with(
+        self,
+        delta_coordinates: bool,
+        timestamps: TimestampFormat,
+    ) -> Result<proto::Trajectory, EncodeError> {
+        let (latitudes, longitudes) = if delta_coordinates {
+            (
+                delta_encode(self.latitudes).collect(),
+                delta_encode(self.longitudes).collect(),
+            )
+        } else {
+            (self.latitudes, self.longitudes)
+        };
+
+        let scale = match timestamps.unit {
+            TimestampUnit::Seconds => 1,
+            TimestampUnit::Milliseconds => 1000,
+        };
+        let scaled = self.timestamps.into_iter().map(|ts| ts * scale).collect();
+        let timestamps_values = match timestamps.encoding {
+            TimestampEncoding::Absolute => scaled,
+            TimestampEncoding::Delta => timestamp_deltas(scaled)?,
+        };
+
+        Ok(proto::Trajectory {
+            latitudes,
+            longitudes,
+            timestamps: timestamps_values,
+            timestamp_unit: timestamps.unit.into(),
+            reconstruction: None,
+        })
+    }
+
+    /// Reads a trajectory from a protobuf message with absolute values, as written by
+    /// `to_proto`.
+    pub fn from_proto(message: proto::Trajectory) -> Result<Trajectory, DecodeError> {
+        Self::from_proto_with(message, false, TimestampEncoding::Absolute)
+    }
+
+    /// Reads a trajectory from a protobuf message with delta-encoded values, as written by
+    /// `to_delta_proto`.
+    pub fn from_delta_proto(message: proto::Trajectory) -> Result<Trajectory, DecodeError> {
+        Self::from_proto_with(message, true, TimestampEncoding::Delta)
+    }
+
+    /// Reads a trajectory from a protobuf message written by `to_proto_with` with the same
+    /// `delta_coordinates` and timestamp encoding. The timestamp unit is read from the
+    /// message; milliseconds are truncated to seconds.
+    ///
+    /// Messages do not carry altitudes or their coordinate system, so the trajectory has
+    /// no altitudes and WGS 84 coordinates.
+    pub fn from_proto_with(
+        message: proto::Trajectory,
+        delta_coordinates: bool,
+        timestamps: TimestampEncoding,
+    ) -> Result<Trajectory, DecodeError> {
+        let unit = TimestampUnit::try_from(message.timestamp_unit)
+            .map_err(|_| DecodeError::UnknownTimestampUnit(message.timestamp_unit))?;
+        let (latitudes, longitudes, timestamps_values) =
+            (message.latitudes, message.longitudes, message.timestamps);
+        if latitudes.len() != longitudes.len() || latitudes.len() != timestamps_values.len() {
+            return Err(DecodeError::ColumnLengths {
+                latitudes: latitudes.len(),
+                longitudes: longitudes.len(),
+                timestamps: timestamps_values.len(),
+            });
+        }
+
+        let (latitudes, longitudes) = if delta_coordinates {
+            (
+                delta_decode(latitudes).collect(),
+                delta_decode(longitudes).collect(),
+            )
+        } else {
+            (latitudes, longitudes)
+        };
+
+        let scaled: Vec<u64> = match timestamps {
+            TimestampEncoding::Absolute => timestamps_values,
+            TimestampEncoding::Delta => delta_decode(timestamps_values).collect(),
+        };
+        let scale = match unit {
+            TimestampUnit::Seconds => 1,
+            TimestampUnit::Milliseconds => 1000,
+        };
+
+        Ok(Trajectory {
+            latitudes,
+            longitudes,
+            timestamps: scaled.into_iter().map(|ts| ts / scale).collect(),
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        })
+    }
+}
+
+/// Whether timestamps are written as they are or as differences with the previous one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimestampEncoding {
+    Absolute,
+    Delta,
+}
+
+/// How timestamps are written to protobuf messages.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimestampFormat {
+    pub unit: TimestampUnit,
+    pub encoding: TimestampEncoding,
+}
+
+/// Delta-encodes timestamps, failing on the first one earlier than its predecessor.
+fn timestamp_deltas(timestamps: Vec<u64>) -> Result<Vec<u64>, EncodeError> {
+    if let Some(index) = timestamps.windows(2).position(|pair| pair[1] < pair[0]) {
+        return Err(EncodeError::NonMonotonicTimestamp { index: index + 1 });
+    }
+    Ok(delta_encode(timestamps).collect())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::point::parse_plt_file;
     use rust_decimal::Decimal;
     use chrono::DateTime;
+    use prost::Message;
     use std::str::FromStr;
 
     fn create_test_point(lat: f64, lon: f64, timestamp: i64) -> Point {
@@ -116,6 +471,7 @@ mod tests {
             latitude: Decimal::from_str(&lat.to_string()).unwrap(),
             longitude: Decimal::from_str(&lon.to_string()).unwrap(),
             datetime: DateTime::from_timestamp(timestamp, 0).unwrap(),
+            altitude: None,
         }
     }
 
@@ -137,6 +493,66 @@ mod tests {
         assert_eq!(trajectory.timestamps[0], 1000);
     }
 
+    #[test]
+    fn test_trajectory_altitudes() {
+        let mut points: Vec<Point> = (0..4).map(|i| create_test_point(1.0, 2.0, i)).collect();
+        let flat = Trajectory::new(vec![create_test_point(1.0, 2.0, 0)]);
+        assert!(flat.altitudes.is_empty());
+
+        points[1].altitude = Some(Decimal::new(125, 1));
+        points[3].altitude = Some(Decimal::new(-2, 0));
+        let trajectory = Trajectory::new(points);
+        assert_eq!(trajectory.altitudes, [12_500, 12_500, 12_500, -2_000]);
+        let filtered = trajectory.filter(&[true, false, false, true]);
+        assert_eq!(filtered.altitudes, [12_500, -2_000]);
+        assert_eq!(filtered.reverse().altitudes, [-2_000, 12_500]);
+    }
+
+    #[test]
+    fn test_trajectory_length_m() {
+        let trajectory = Trajectory::new(vec![
+            create_test_point(0.0, 0.0, 0),
+            create_test_point(1.0, 0.0, 10),
+            create_test_point(1.0, 0.0, 20),
+            create_test_point(0.0, 0.0, 30),
+        ]);
+        let length_m = trajectory.length_m();
+        assert!((length_m - 2.0 * 111_195.0).abs() < 2.0, "{length_m}");
+        assert_eq!(Trajectory::new(vec![]).length_m(), 0.0);
+    }
+
+    #[test]
+    fn test_trajectory_local_coordinates() {
+        // A 3-4-5 triangle walked in a warehouse, in millimeters
+        let coordinates = CoordinateSystem::Local { scale: 3 };
+        let trajectory = Trajectory::with_coordinates(
+            vec![
+                create_test_point(0.0, 0.0, 0),
+                create_test_point(0.0, 3.0, 3),
+                create_test_point(4.0, 3.0, 7),
+            ],
+            coordinates,
+        );
+        assert_eq!(trajectory.latitudes, vec![0, 0, 4_000]);
+        assert_eq!(trajectory.longitudes, vec![0, 3_000, 3_000]);
+        assert_eq!(trajectory.length_m(), 7.0);
+        assert_eq!(coordinates.distance_m((0, 0), (4_000, 3_000)), 5.0);
+        assert_eq!(
+            coordinates.segment_distance_m((1_000, 1_500), (0, 0), (0, 3_000)),
+            1.0
+        );
+        assert_eq!(
+            coordinates.segment_distance_m((0, -4_000), (0, 0), (0, 3_000)),
+            4.0
+        );
+        assert_eq!(coordinates.offset_m((1_000, 0), (0, 3_000)), (3.0, -1.0));
+
+        // One microdegree north is about 11 cm, and east half as much at 60° north
+        let (east, north) = CoordinateSystem::Wgs84.offset_m((60_000_000, 0), (60_000_001, 1));
+        assert!((north - METERS_PER_UNIT).abs() < 1e-12);
+        assert!((east - METERS_PER_UNIT / 2.0).abs() < 1e-9);
+    }
+
     #[test]
     fn test_trajectory_to_proto() {
         let points = vec![
@@ -158,10 +574,177 @@ mod tests {
             create_test_point(2.0, 3.0, 2000),
         ];
         let trajectory = Trajectory::new(points);
-        let proto = trajectory.to_delta_proto();
+        let proto = trajectory.to_delta_proto().unwrap();
 
         assert_eq!(proto.latitudes, vec![1_000_000, 1_000_000]);
         assert_eq!(proto.longitudes, vec![2_000_000, 1_000_000]);
         assert_eq!(proto.timestamps, vec![1000, 1000]);
     }
+
+    #[test]
+    fn test_trajectory_to_proto_with() {
+        let trajectory = Trajectory {
+            latitudes: vec![10, 12, 11],
+            longitudes: vec![20, 20, 25],
+            timestamps: vec![1000, 1001, 1003],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+
+        let proto = trajectory
+            .clone()
+            .to_proto_with(
+                false,
+                TimestampFormat {
+                    unit: TimestampUnit::Milliseconds,
+                    encoding: TimestampEncoding::Delta,
+                },
+            )
+            .unwrap();
+        assert_eq!(proto.latitudes, vec![10, 12, 11]);
+        assert_eq!(proto.timestamps, vec![1_000_000, 1000, 2000]);
+        assert_eq!(proto.timestamp_unit(), TimestampUnit::Milliseconds);
+
+        let proto = trajectory
+            .clone()
+            .to_proto_with(
+                true,
+                TimestampFormat {
+                    unit: TimestampUnit::Seconds,
+                    encoding: TimestampEncoding::Absolute,
+                },
+            )
+            .unwrap();
+        assert_eq!(proto.latitudes, vec![10, 2, -1]);
+        assert_eq!(proto.longitudes, vec![20, 0, 5]);
+        assert_eq!(proto.timestamps, vec![1000, 1001, 1003]);
+        assert_eq!(proto.timestamp_unit(), TimestampUnit::Seconds);
+    }
+
+    #[test]
+    fn test_trajectory_proto_round_trip() {
+        let trajectory = Trajectory {
+            latitudes: vec![39_984_702, 39_984_683, i64::MIN, 0],
+            longitudes: vec![116_318_417, 116_318_450, i64::MAX, -5],
+            timestamps: vec![1_224_730_384, 1_224_730_389, 1_224_730_394, 1_224_730_394],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+
+        let proto = trajectory.clone().to_proto();
+        assert_eq!(Trajectory::from_proto(proto), Ok(trajectory.clone()));
+        let proto = trajectory.clone().to_delta_proto().unwrap();
+        assert_eq!(Trajectory::from_delta_proto(proto), Ok(trajectory.clone()));
+
+        let format = TimestampFormat {
+            unit: TimestampUnit::Milliseconds,
+            encoding: TimestampEncoding::Delta,
+        };
+        let proto = trajectory.clone().to_proto_with(false, format).unwrap();
+        assert_eq!(
+            Trajectory::from_proto_with(proto, false, TimestampEncoding::Delta),
+            Ok(trajectory.clone())
+        );
+
+        // Through the wire format
+        let bytes = trajectory.clone().to_delta_proto().unwrap().encode_to_vec();
+        let proto = proto::Trajectory::decode(bytes.as_slice()).unwrap();
+        assert_eq!(Trajectory::from_delta_proto(proto), Ok(trajectory));
+        assert_eq!(
+            Trajectory::from_proto(proto::Trajectory::default()).map(|t| t.timestamps),
+            Ok(Vec::new())
+        );
+    }
+
+    #[test]
+    fn test_trajectory_from_proto_invalid() {
+        let mut proto = proto::Trajectory {
+            latitudes: vec![1, 2],
+            longitudes: vec![3, 4],
+            timestamps: vec![5],
+            ..Default::default()
+        };
+        assert_eq!(
+            Trajectory::from_proto(proto.clone()),
+            Err(DecodeError::ColumnLengths {
+                latitudes: 2,
+                longitudes: 2,
+                timestamps: 1
+            })
+        );
+        proto.timestamps.push(6);
+        proto.timestamp_unit = 7;
+        assert_eq!(
+            Trajectory::from_proto(proto),
+            Err(DecodeError::UnknownTimestampUnit(7))
+        );
+    }
+
+    #[test]
+    fn test_trajectory_convex_hull() {
+        let trajectory = Trajectory {
+            latitudes: vec![0, 5, 10, 10, 0],
+            longitudes: vec![0, 5, 0, 10, 10],
+            timestamps: vec![0, 1, 2, 3, 4],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+        // Counter-clockwise with east as x: south-west, south-east, north-east, north-west
+        assert_eq!(trajectory.convex_hull(), vec![0, 4, 3, 2]);
+    }
+
+    #[test]
+    fn test_trajectory_reverse() {
+        let trajectory = Trajectory {
+            latitudes: vec![1, 2, 3],
+            longitudes: vec![4, 5, 6],
+            timestamps: vec![100, 110, 130],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+        let reversed = trajectory.clone().reverse();
+
+        assert_eq!(reversed.latitudes, vec![3, 2, 1]);
+        assert_eq!(reversed.longitudes, vec![6, 5, 4]);
+        assert_eq!(reversed.timestamps, vec![100, 120, 130]);
+        assert_eq!(reversed.reverse(), trajectory);
+    }
+
+    #[test]
+    fn test_trajectory_reverse_empty() {
+        let trajectory = Trajectory {
+            latitudes: vec![],
+            longitudes: vec![],
+            timestamps: vec![],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+        assert_eq!(trajectory.clone().reverse(), trajectory);
+    }
+
+    #[test]
+    fn test_trajectory_to_delta_proto_unsorted_geolife() {
+        // Excerpt of two GeoLife files concatenated without sorting: the
+        // second file starts before the last point of the first one.
+        let data = "\
+Geolife trajectory
+WGS 84
+Altitude is in Feet
+Reserved 3
+0,2,255,My Track,0,0,2,8421376
+0
+39.984702,116.318417,0,492,39744.1201851852,2008-10-23,02:53:04
+39.984683,116.31845,0,492,39744.1202546296,2008-10-23,02:53:10
+39.984686,116.318417,0,492,39744.1203125,2008-10-23,02:53:15
+39.984688,116.318385,0,492,39744.1201967593,2008-10-23,02:53:05
+39.984655,116.318263,0,492,39744.1202662037,2008-10-23,02:53:11
+";
+        let points = parse_plt_file(data.as_bytes()).unwrap();
+        let trajectory = Trajectory::new(points);
+
+        assert_eq!(
+            trajectory.to_delta_proto().unwrap_err(),
+            EncodeError::NonMonotonicTimestamp { index: 3 }
+        );
+    }
 }
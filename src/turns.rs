@@ -0,0 +1,282 @@
+//! Detection of the significant turns of a trajectory, labeled for turn-by-turn summaries.
+//!
+//! Turns are detected on the original points, and their points are then kept by the
+//! simplification, so that a compressed trajectory still carries every turn as the label
+//! of one of its points.
+
+use crate::geo::to_degrees;
+use crate::trajectory::Trajectory;
+use serde_json::{json, Value};
+
+/// Thresholds of the turn detection.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TurnParams {
+    /// Smallest heading change in degrees counted as a turn
+    pub min_angle_deg: f64,
+    /// Distance in meters the heading must hold before and after the turn, so that GPS
+    /// jitter and short wiggles are not reported
+    pub window_m: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TurnLabel {
+    SlightLeft,
+    Left,
+    SharpLeft,
+    SlightRight,
+    Right,
+    SharpRight,
+    UTurn,
+}
+
+impl TurnLabel {
+    /// Label of a heading change in degrees, positive clockwise.
+    pub fn of(angle_deg: f64) -> TurnLabel {
+        let (slight, turn, sharp) = if angle_deg > 0.0 {
+            (
+                TurnLabel::SlightRight,
+                TurnLabel::Right,
+                TurnLabel::SharpRight,
+            )
+        } else {
+            (TurnLabel::SlightLeft, TurnLabel::Left, TurnLabel::SharpLeft)
+        };
+        match angle_deg.abs() {
+            a if a < 45.0 => slight,
+            a if a < 120.0 => turn,
+            a if a < 160.0 => sharp,
+            _ => TurnLabel::UTurn,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TurnLabel::SlightLeft => "slight-left",
+            TurnLabel::Left => "left",
+            TurnLabel::SharpLeft => "sharp-left",
+            TurnLabel::SlightRight => "slight-right",
+            TurnLabel::Right => "right",
+            TurnLabel::SharpRight => "sharp-right",
+            TurnLabel::UTurn => "u-turn",
+        }
+    }
+}
+
+/// Turn at one original point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Turn {
+    /// Index of the original point where the heading changes the most
+    pub index: usize,
+    /// Heading change in degrees, positive clockwise (to the right), in `(-180, 180]`
+    pub angle_deg: f64,
+    /// Distance in meters from the start of the trajectory along its path
+    pub distance_m: f64,
+    pub label: TurnLabel,
+}
+
+/// Heading in degrees, clockwise from north, from the point `from` to the point `to`.
+fn heading_deg(trajectory: &Trajectory, from: usize, to: usize) -> f64 {
+    let point = |i: usize| (trajectory.latitudes[i], trajectory.longitudes[i]);
+    let (east, north) = trajectory.coordinates.offset_m(point(from), point(to));
+    east.atan2(north).to_degrees()
+}
+
+/// Detects the turns of a trajectory, ordered along it.
+///
+/// The turn at a point is the change between the heading from the point `window_m` meters
+/// before it and the heading to the point `window_m` meters after it. A turn spreads over
+/// the points around it, so only the sharpest point of each run of points turning the same
+/// way beyond `min_angle_deg` is reported.
+pub fn detect(trajectory: &Trajectory, params: TurnParams) -> Vec<Turn> {
+    let len = trajectory.latitudes.len();
+    let mut distances = Vec::with_capacity(len);
+    let mut distance = 0.0;
+    for i in 0..len {
+        if i > 0 {
+            distance += trajectory.coordinates.distance_m(
+                (trajectory.latitudes[i - 1], trajectory.longitudes[i - 1]),
+                (trajectory.latitudes[i], trajectory.longitudes[i]),
+            );
+        }
+        distances.push(distance);
+    }
+
+    let mut turns: Vec<Turn> = Vec::new();
+    // Index and angle of the last point turning beyond the threshold
+    let mut previous: Option<(usize, f64)> = None;
+    // Last point at least `window_m` before the current one, first one at least after
+    let (mut before, mut after) = (0, 0);
+    for i in 0..len {
+        while before + 1 < i && distances[i] - distances[before + 1] >= params.window_m {
+            before += 1;
+        }
+        while after < len && distances[after] - distances[i] < params.window_m {
+            after += 1;
+        }
+        if after == len || distances[i] - distances[before] < params.window_m {
+            continue;
+        }
+
+        let change = heading_deg(trajectory, i, after) - heading_deg(trajectory, before, i);
+        let angle_deg = 180.0 - (180.0 - change).rem_euclid(360.0);
+        if angle_deg.abs() < params.min_angle_deg {
+            continue;
+        }
+        let turn = Turn {
+            index: i,
+            angle_deg,
+            distance_m: distances[i],
+            label: TurnLabel::of(angle_deg),
+        };
+        let same_turn =
+            previous.is_some_and(|(index, angle)| index + 1 == i && angle * angle_deg > 0.0);
+        previous = Some((i, angle_deg));
+        match turns.last_mut() {
+            Some(last) if same_turn => {
+                if angle_deg.abs() > last.angle_deg.abs() {
+                    *last = turn;
+                }
+            }
+            _ => turns.push(turn),
+        }
+    }
+    turns
+}
+
+/// Marks the points of the turns as kept.
+///
+/// # Panics
+///
+/// This function will panic if a turn is beyond the end of `keep`.
+pub fn keep_turns(turns: &[Turn], keep: &mut [bool]) {
+    for turn in turns {
+        keep[turn.index] = true;
+    }
+}
+
+/// Builds a GeoJSON `FeatureCollection` with the simplified trajectory as a `LineString`,
+/// followed by every turn as a `Point`.
+///
+/// Each turn has its label, angle, distance from the start and timestamp, and the index of
+/// its point in the simplified trajectory as `point`, which requires its point to be kept
+/// (see `keep_turns`).
+///
+/// # Panics
+///
+/// This function will panic if `keep` does not have one entry per point of `original`, or
+/// if the point of a turn is not kept.
+pub fn turns_geojson(original: &Trajectory, keep: &[bool], turns: &[Turn]) -> Value {
+    assert_eq!(keep.len(), original.latitudes.len(), "keep.len() == len");
+
+    let position = |i: usize| {
+        json!([
+            to_degrees(original.longitudes[i]),
+            to_degrees(original.latitudes[i])
+        ])
+    };
+    let kept: Vec<usize> = (0..keep.len()).filter(|&i| keep[i]).collect();
+
+    let mut features = vec![json!({
+        "type": "Feature",
+        "geometry": {
+            "type": "LineString",
+            "coordinates": kept.iter().map(|&i| position(i)).collect::<Vec<_>>(),
+        },
+        "properties": {},
+    })];
+    features.extend(turns.iter().map(|turn| {
+        let point = kept
+            .binary_search(&turn.index)
+            .expect("the point of a turn is kept");
+        json!({
+            "type": "Feature",
+            "geometry": { "type": "Point", "coordinates": position(turn.index) },
+            "properties": {
+                "turn": turn.label.name(),
+                "angle_deg": turn.angle_deg,
+                "distance_m": turn.distance_m,
+                "timestamp": original.timestamps[turn.index],
+                "point": point,
+            },
+        })
+    }));
+    json!({ "type": "FeatureCollection", "features": features })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simplify;
+    use crate::trajectory::CoordinateSystem;
+
+    const PARAMS: TurnParams = TurnParams {
+        min_angle_deg: 30.0,
+        window_m: 30.0,
+    };
+
+    /// Walks through the given `(east, north)` positions in meters, one per second.
+    fn walk(positions: &[(i64, i64)]) -> Trajectory {
+        Trajectory {
+            latitudes: positions.iter().map(|&(_, north)| north).collect(),
+            longitudes: positions.iter().map(|&(east, _)| east).collect(),
+            timestamps: (0..positions.len() as u64).collect(),
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Local { scale: 0 },
+        }
+    }
+
+    /// 100 m north, 100 m east, then 100 m back west, in 10 m steps, with a 1 m wiggle.
+    fn trip() -> Trajectory {
+        let mut positions: Vec<(i64, i64)> = (0..10).map(|i| (i % 2, 10 * i)).collect();
+        positions.extend((0..10).map(|i| (10 * i, 100)));
+        positions.extend((0..=10).map(|i| (100 - 10 * i, 110)));
+        walk(&positions)
+    }
+
+    #[test]
+    fn test_turn_label() {
+        assert_eq!(TurnLabel::of(90.0), TurnLabel::Right);
+        assert_eq!(TurnLabel::of(-20.0), TurnLabel::SlightLeft);
+        assert_eq!(TurnLabel::of(-150.0), TurnLabel::SharpLeft);
+        assert_eq!(TurnLabel::of(180.0), TurnLabel::UTurn);
+        assert_eq!(TurnLabel::of(-170.0).name(), "u-turn");
+    }
+
+    #[test]
+    fn test_detect() {
+        let trajectory = trip();
+        let turns = detect(&trajectory, PARAMS);
+
+        let labels: Vec<TurnLabel> = turns.iter().map(|turn| turn.label).collect();
+        assert_eq!(labels, [TurnLabel::Right, TurnLabel::UTurn]);
+        assert_eq!(turns[0].index, 10);
+        assert!((turns[0].angle_deg - 90.0).abs() < 10.0, "{turns:?}");
+        assert!(turns[1].index == 19 || turns[1].index == 20, "{turns:?}");
+        assert!(turns[1].distance_m > 190.0);
+
+        // The wiggle turns at every step over a narrower window
+        let narrow = TurnParams {
+            min_angle_deg: 10.0,
+            window_m: 5.0,
+        };
+        assert!(detect(&trajectory, narrow).len() > 2);
+        assert!(detect(&walk(&[(0, 0), (0, 10)]), PARAMS).is_empty());
+    }
+
+    #[test]
+    fn test_turns_geojson() {
+        let trajectory = trip();
+        let turns = detect(&trajectory, PARAMS);
+        let mut keep = simplify::simplify(&trajectory.latitudes, &trajectory.longitudes, 50);
+        keep_turns(&turns, &mut keep);
+
+        let geojson = turns_geojson(&trajectory, &keep, &turns);
+        let features = geojson["features"].as_array().unwrap();
+        assert_eq!(features.len(), 3);
+        let line = features[0]["geometry"]["coordinates"].as_array().unwrap();
+        let right = &features[1]["properties"];
+        assert_eq!(right["turn"], "right");
+        let point = right["point"].as_u64().unwrap() as usize;
+        assert_eq!(line[point], features[1]["geometry"]["coordinates"]);
+    }
+}
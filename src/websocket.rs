@@ -0,0 +1,549 @@
+//! WebSocket live feed (RFC 6455) of the points committed by the streaming simplifier, to
+//! update live maps as points arrive.
+//!
+//! Clients connect to `/` to follow every trajectory, or to `/<trajectory>` to follow one.
+//! When the feed has tenants, clients authenticate with the API key of their tenant, as an
+//! `Authorization: Bearer <key>` header or an `api_key` query parameter for browsers, and
+//! only receive the trajectories of their tenant.
+//! The feed only sends text messages and never reads from clients after the handshake.
+//! Every client has its own writer thread behind a bounded queue, so that a slow client
+//! never delays the others: a client whose queue is full or whose connection fails is
+//! dropped.
+
+use crate::geo;
+use crate::streaming::{self, StreamingOptions};
+use crate::tenants::Tenants;
+use crate::trajectory::Trajectory;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use sha1::{Digest, Sha1};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// GUID appended to the client key to compute the accept key
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// Time a client has to send its handshake
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Longest handshake accepted, request line and headers included
+const MAX_HANDSHAKE_BYTES: u64 = 16 * 1024;
+
+/// Time a client has to read a message before being dropped
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Messages waiting to be sent to a client before it is dropped as too slow
+const QUEUE_LENGTH: usize = 256;
+
+/// Key the server answers a handshake with, from the `Sec-WebSocket-Key` of the client.
+fn accept_key(client_key: &str) -> String {
+    BASE64.encode(Sha1::digest(
+        format!("{client_key}{WEBSOCKET_GUID}").as_bytes(),
+    ))
+}
+
+/// Frames a text message, unmasked as sent by a server.
+fn text_frame(message: &str) -> Vec<u8> {
+    // FIN bit and text opcode
+    let mut frame = vec![0x81];
+    match message.len() {
+        len @ 0..=125 => frame.push(len as u8),
+        len @ 126..=0xFFFF => {
+            frame.push(126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(message.as_bytes());
+    frame
+}
+
+/// Client of the feed, following every trajectory of its tenant or only one.
+struct Subscriber {
+    /// Queue of the frames to send, drained by the writer thread of the client
+    frames: SyncSender<Arc<Vec<u8>>>,
+    tenant: Option<String>,
+    trajectory: Option<String>,
+}
+
+/// Connected clients of the feed.
+#[derive(Default)]
+pub struct LiveFeed {
+    /// Tenants allowed to connect, or `None` to accept every client
+    tenants: Option<Tenants>,
+    subscribers: Mutex<Vec<Subscriber>>,
+}
+
+/// Starts the writer thread sending the queued frames to `stream` until its connection
+/// fails or the feed drops it, and returns the queue.
+fn spawn_writer(mut stream: TcpStream) -> io::Result<SyncSender<Arc<Vec<u8>>>> {
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    let (frames, queued) = mpsc::sync_channel::<Arc<Vec<u8>>>(QUEUE_LENGTH);
+    thread::spawn(move || {
+        for frame in queued {
+            if stream.write_all(&frame).is_err() {
+                break;
+            }
+        }
+    });
+    Ok(frames)
+}
+
+/// Reads a line of the handshake, failing if it is cut by the size limit.
+fn read_handshake_line(reader: &mut impl BufRead, line: &mut String) -> io::Result<()> {
+    line.clear();
+    reader.read_line(line)?;
+    if !line.ends_with('\n') {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "handshake too long or cut",
+        ));
+    }
+    Ok(())
+}
+
+/// Returns the value of the `api_key` parameter of a query string.
+fn api_key_param(query: &str) -> Option<&str> {
+    query
+        .split('&')
+        .find_map(|param| param.strip_prefix("api_key="))
+}
+
+impl LiveFeed {
+    /// Feed only accepting the clients of `tenants`.
+    pub fn with_tenants(tenants: Tenants) -> Self {
+        LiveFeed {
+            tenants: Some(tenants),
+            subscribers: Mutex::default(),
+        }
+    }
+
+    /// Completes the handshake of a new connection and subscribes it to the trajectory of
+    /// the requested path.
+    pub fn accept(&self, mut stream: TcpStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+        let mut reader = BufReader::new((&stream).take(MAX_HANDSHAKE_BYTES));
+        let (mut request_line, mut header) = (String::new(), String::new());
+        let mut handshake = read_handshake_line(&mut reader, &mut request_line);
+        let target = request_line.split_whitespace().nth(1).unwrap_or("/");
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let trajectory = Some(path.trim_start_matches('/'))
+            .filter(|trajectory| !trajectory.is_empty())
+            .map(str::to_string);
+        let mut api_key = api_key_param(query).map(str::to_string);
+
+        let mut client_key = None;
+        while handshake.is_ok() {
+            handshake = read_handshake_line(&mut reader, &mut header);
+            if handshake.is_err() || header.trim_end().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                let name = name.trim();
+                if name.eq_ignore_ascii_case("sec-websocket-key") {
+                    client_key = Some(value.trim().to_string());
+                } else if name.eq_ignore_ascii_case("authorization") {
+                    if let Some(key) = value.trim().strip_prefix("Bearer ") {
+                        api_key = Some(key.trim().to_string());
+                    }
+                }
+            }
+        }
+        drop(reader);
+
+        if let Err(error) = handshake {
+            stream.write_all(b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\n\r\n")?;
+            return Err(error);
+        }
+        let tenant = match &self.tenants {
+            None => None,
+            Some(tenants) => {
+                let Some(tenant) = api_key.and_then(|key| tenants.authenticate(&key)) else {
+                    stream.write_all(b"HTTP/1.1 401 Unauthorized\r\nContent-Length: 0\r\n\r\n")?;
+                    return Err(io::Error::new(
+                        io::ErrorKind::PermissionDenied,
+                        "missing or unknown API key",
+                    ));
+                };
+                Some(tenant.to_string())
+            }
+        };
+        let Some(client_key) = client_key else {
+            stream.write_all(b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\n\r\n")?;
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a WebSocket handshake",
+            ));
+        };
+        stream.write_all(
+            format!(
+                "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n\
+                 Connection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+                accept_key(&client_key)
+            )
+            .as_bytes(),
+        )?;
+        self.subscribers.lock().unwrap().push(Subscriber {
+            frames: spawn_writer(stream)?,
+            tenant,
+            trajectory,
+        });
+        Ok(())
+    }
+
+    /// Accepts the clients connecting to `listener` on a thread of its own, logging the
+    /// rejected ones, until the process ends.
+    pub fn serve(self: &Arc<Self>, listener: TcpListener) {
+        let feed = Arc::clone(self);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if let Err(error) = feed.accept(stream) {
+                    eprintln!("Rejected a client: {error}");
+                }
+            }
+        });
+    }
+
+    /// Replays `trajectory` as a live feed through the streaming simplifier, in batches of
+    /// `batch_size` points one `interval` apart, and broadcasts the points committed by
+    /// each batch as `{"trajectory": <name>, "points": [[latitude, longitude, timestamp],
+    /// ...]}`.
+    ///
+    /// # Returns
+    ///
+    /// The number of points committed and of messages queued for clients.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `batch_size` is zero.
+    pub fn replay(
+        &self,
+        tenant: Option<&str>,
+        name: &str,
+        trajectory: &Trajectory,
+        options: StreamingOptions,
+        batch_size: usize,
+        interval: Duration,
+    ) -> (usize, usize) {
+        let len = trajectory.timestamps.len();
+        // GeoLife files do not report the accuracy of their fixes
+        let accuracies = vec![None; len];
+
+        let (mut points, mut deliveries) = (0, 0);
+        let mut keep_points = Vec::new();
+        let mut sent = 0;
+        for end in (batch_size..len + batch_size).step_by(batch_size) {
+            let end = end.min(len);
+            keep_points = options.update(
+                &trajectory.latitudes[..end],
+                &trajectory.longitudes[..end],
+                &accuracies[..end],
+                &keep_points,
+            );
+            // Every decision is final once the trajectory ends
+            let committed = if end == len {
+                len
+            } else {
+                streaming::committed(&keep_points)
+            };
+            let batch: Vec<serde_json::Value> = (sent..committed)
+                .filter(|&i| keep_points[i])
+                .map(|i| {
+                    serde_json::json!([
+                        geo::to_degrees(trajectory.latitudes[i]),
+                        geo::to_degrees(trajectory.longitudes[i]),
+                        trajectory.timestamps[i]
+                    ])
+                })
+                .collect();
+            sent = committed;
+            if !batch.is_empty() {
+                points += batch.len();
+                let message = serde_json::json!({ "trajectory": name, "points": batch });
+                deliveries += self.broadcast(tenant, name, &message.to_string());
+            }
+            thread::sleep(interval);
+        }
+        (points, deliveries)
+    }
+
+    /// Queues a message about `trajectory` of `tenant` for the clients following it,
+    /// dropping the ones whose connection failed or which are too far behind.
+    ///
+    /// # Returns
+    ///
+    /// The number of clients the message was queued for.
+    pub fn broadcast(&self, tenant: Option<&str>, trajectory: &str, message: &str) -> usize {
+        let frame = Arc::new(text_frame(message));
+        let mut subscribers = self.subscribers.lock().unwrap();
+        let mut sent = 0;
+        subscribers.retain_mut(|subscriber| {
+            if subscriber.tenant.as_deref() != tenant
+                || subscriber
+                    .trajectory
+                    .as_ref()
+                    .is_some_and(|followed| followed != trajectory)
+            {
+                return true;
+            }
+            match subscriber.frames.try_send(Arc::clone(&frame)) {
+                Ok(()) => {
+                    sent += 1;
+                    true
+                }
+                Err(TrySendError::Full(_) | TrySendError::Disconnected(_)) => false,
+            }
+        });
+        sent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+
+    #[test]
+    fn test_accept_key() {
+        // Example of RFC 6455, section 1.3
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    #[test]
+    fn test_text_frame() {
+        assert_eq!(text_frame("hi"), [0x81, 2, b'h', b'i']);
+        let long = "x".repeat(300);
+        assert_eq!(text_frame(&long)[..4], [0x81, 126, 1, 44]);
+    }
+
+    /// Sends a handshake for `path` with the given extra headers, and returns the client
+    /// stream and the result of accepting it.
+    fn connect(
+        feed: &LiveFeed,
+        listener: &TcpListener,
+        path: &str,
+        headers: &str,
+    ) -> (TcpStream, io::Result<()>) {
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        write!(
+            client,
+            "GET {path} HTTP/1.1\r\nHost: x\r\nUpgrade: websocket\r\n{headers}\
+             Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n"
+        )
+        .unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        (client, feed.accept(stream))
+    }
+
+    /// Connects a client following `path` and returns its stream after the handshake.
+    fn subscribe(feed: &LiveFeed, listener: &TcpListener, path: &str) -> TcpStream {
+        subscribe_with(feed, listener, path, "")
+    }
+
+    fn subscribe_with(
+        feed: &LiveFeed,
+        listener: &TcpListener,
+        path: &str,
+        headers: &str,
+    ) -> TcpStream {
+        let (client, accepted) = connect(feed, listener, path, headers);
+        accepted.unwrap();
+
+        let mut response = BufReader::new(client.try_clone().unwrap());
+        let mut line = String::new();
+        let mut lines = Vec::new();
+        while response.read_line(&mut line).unwrap() > 2 {
+            lines.push(line.trim_end().to_string());
+            line.clear();
+        }
+        assert_eq!(lines[0], "HTTP/1.1 101 Switching Protocols");
+        assert!(lines.contains(&"Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=".into()));
+        client
+    }
+
+    #[test]
+    fn test_broadcast() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let feed = LiveFeed::default();
+        let mut all = subscribe(&feed, &listener, "/");
+        let mut one = subscribe(&feed, &listener, "/b.plt");
+
+        assert_eq!(feed.broadcast(None, "a.plt", "first"), 1);
+        assert_eq!(feed.broadcast(None, "b.plt", "second"), 2);
+
+        let mut received = [0; 15];
+        all.read_exact(&mut received).unwrap();
+        assert_eq!(received[..7], [0x81, 5, b'f', b'i', b'r', b's', b't']);
+        assert_eq!(received[7..], [0x81, 6, b's', b'e', b'c', b'o', b'n', b'd']);
+        let mut received = [0; 8];
+        one.read_exact(&mut received).unwrap();
+        assert_eq!(received[2..], *b"second");
+
+        // Plain HTTP requests are refused
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        client.write_all(b"GET / HTTP/1.1\r\n\r\n").unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        let handle = thread::spawn(move || {
+            let mut response = String::new();
+            client.read_to_string(&mut response).unwrap();
+            response
+        });
+        assert!(feed.accept(stream).is_err());
+        assert!(handle.join().unwrap().starts_with("HTTP/1.1 400"));
+    }
+
+    /// Reads a text frame shorter than 126 bytes.
+    fn read_text(client: &mut TcpStream) -> String {
+        let mut header = [0; 2];
+        client.read_exact(&mut header).unwrap();
+        assert_eq!(header[0], 0x81);
+        let mut text = vec![0; header[1] as usize];
+        client.read_exact(&mut text).unwrap();
+        String::from_utf8(text).unwrap()
+    }
+
+    #[test]
+    fn test_replay() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let feed = LiveFeed::default();
+        let mut client = subscribe(&feed, &listener, "/a.plt");
+
+        // Straight north, then east: the corner and the endpoints are kept
+        let trajectory = Trajectory {
+            latitudes: vec![0, 1_000, 2_000, 2_000, 2_000],
+            longitudes: vec![0, 0, 0, 1_000, 2_000],
+            timestamps: vec![1, 2, 3, 4, 5],
+            altitudes: Vec::new(),
+            coordinates: crate::trajectory::CoordinateSystem::Wgs84,
+        };
+        let options = StreamingOptions::new(10);
+        let (points, deliveries) =
+            feed.replay(None, "a.plt", &trajectory, options, 2, Duration::ZERO);
+        assert_eq!(points, 3);
+        assert!(deliveries >= 1);
+
+        let mut received = Vec::new();
+        for _ in 0..deliveries {
+            let message: serde_json::Value = serde_json::from_str(&read_text(&mut client)).unwrap();
+            assert_eq!(message["trajectory"], "a.plt");
+            received.extend(message["points"].as_array().unwrap().clone());
+        }
+        assert_eq!(
+            received,
+            [
+                serde_json::json!([0.0, 0.0, 1]),
+                serde_json::json!([0.002, 0.0, 3]),
+                serde_json::json!([0.002, 0.002, 5]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_serve() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let feed = Arc::new(LiveFeed::default());
+        feed.serve(listener);
+
+        let mut client = TcpStream::connect(address).unwrap();
+        write!(
+            client,
+            "GET / HTTP/1.1\r\nHost: x\r\nUpgrade: websocket\r\n\
+             Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n"
+        )
+        .unwrap();
+        let mut response = BufReader::new(client.try_clone().unwrap());
+        let mut line = String::new();
+        response.read_line(&mut line).unwrap();
+        assert_eq!(line, "HTTP/1.1 101 Switching Protocols\r\n");
+        while response.read_line(&mut line).unwrap() > 2 {
+            line.clear();
+        }
+        // Subscribed right after the handshake is answered
+        for _ in 0..100 {
+            if feed.broadcast(None, "a.plt", "hi") == 1 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(read_text(&mut client), "hi");
+    }
+
+    #[test]
+    fn test_broadcast_tenants() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let tenants =
+            Tenants::parse("[fleet]\napi_keys = [\"k1\"]\n[bikes]\napi_keys = [\"k2\"]").unwrap();
+        let feed = LiveFeed::with_tenants(tenants);
+        let mut fleet = subscribe_with(&feed, &listener, "/", "Authorization: Bearer k1\r\n");
+        let mut bikes = subscribe_with(&feed, &listener, "/?api_key=k2", "");
+
+        // Without a key, or with an unknown one, clients are refused
+        let (_, accepted) = connect(&feed, &listener, "/", "");
+        assert_eq!(
+            accepted.unwrap_err().kind(),
+            io::ErrorKind::PermissionDenied
+        );
+        let (_, accepted) = connect(&feed, &listener, "/?api_key=k3", "");
+        assert!(accepted.is_err());
+
+        assert_eq!(feed.broadcast(Some("bikes"), "a.plt", "bike"), 1);
+        assert_eq!(feed.broadcast(Some("fleet"), "a.plt", "van"), 1);
+        assert_eq!(feed.broadcast(None, "a.plt", "none"), 0);
+        let mut received = [0; 5];
+        fleet.read_exact(&mut received).unwrap();
+        assert_eq!(received[2..], *b"van");
+        let mut received = [0; 6];
+        bikes.read_exact(&mut received).unwrap();
+        assert_eq!(received[2..], *b"bike");
+    }
+
+    #[test]
+    fn test_slow_subscriber() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let feed = LiveFeed::default();
+        // Never reads, so its queue fills once the socket buffers are full
+        let _slow = subscribe(&feed, &listener, "/");
+        let message = "x".repeat(64 * 1024);
+        let dropped = (0..10_000).any(|_| feed.broadcast(None, "a.plt", &message) == 0);
+        assert!(dropped);
+
+        // Followers connecting after it are still served
+        let mut fast = subscribe(&feed, &listener, "/");
+        assert_eq!(feed.broadcast(None, "a.plt", "next"), 1);
+        let mut received = [0; 6];
+        fast.read_exact(&mut received).unwrap();
+        assert_eq!(received[2..], *b"next");
+    }
+
+    #[test]
+    fn test_handshake_too_long() {
+        let long = format!(
+            "X-Padding: {}\r\n",
+            "x".repeat(MAX_HANDSHAKE_BYTES as usize)
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let feed = LiveFeed::default();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        // The server may close the connection before the whole header is sent
+        let handle = thread::spawn(move || write!(client, "GET / HTTP/1.1\r\n{long}\r\n"));
+        assert_eq!(
+            feed.accept(stream).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        let _ = handle.join().unwrap();
+        assert!(feed.subscribers.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,281 @@
+//! Distance and time spent by trajectories inside polygon zones, for tolling and zone
+//! billing.
+//!
+//! Zones are read from a GeoJSON `FeatureCollection` of `Polygon` and `MultiPolygon`
+//! features, named by their `name` property. Every segment of a trajectory is cut where it
+//! crosses the boundary of a zone, and the parts inside count with their share of the
+//! segment length and duration.
+
+use crate::geo::{haversine_m, UNITS_PER_DEGREE};
+use crate::trajectory::Trajectory;
+use serde_json::Value;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ZoneError {
+    #[error("Invalid zones: {0}")]
+    InvalidZones(&'static str),
+    #[error("Invalid zones: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Polygons of one zone, as rings of scaled `(latitude, longitude)` points. Holes are rings
+/// like the others: a point is inside when it is inside an odd number of rings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Zone {
+    pub name: String,
+    rings: Vec<Vec<(i64, i64)>>,
+}
+
+/// Distance and time a trajectory spent inside a zone.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ZoneUsage {
+    pub distance_m: f64,
+    pub duration_s: f64,
+}
+
+fn parse_position(value: &Value) -> Result<(i64, i64), ZoneError> {
+    let coordinate = |index: usize| {
+        value
+            .get(index)
+            .and_then(Value::as_f64)
+            .map(|degrees| (degrees * UNITS_PER_DEGREE).round() as i64)
+            .ok_or(ZoneError::InvalidZones(
+                "position is not [longitude, latitude]",
+            ))
+    };
+    Ok((coordinate(1)?, coordinate(0)?))
+}
+
+fn parse_rings(value: &Value) -> Result<Vec<Vec<(i64, i64)>>, ZoneError> {
+    value
+        .as_array()
+        .ok_or(ZoneError::InvalidZones("coordinates are not an array"))?
+        .iter()
+        .map(|ring| {
+            ring.as_array()
+                .ok_or(ZoneError::InvalidZones("coordinates are not an array"))?
+                .iter()
+                .map(parse_position)
+                .collect()
+        })
+        .collect()
+}
+
+impl Zone {
+    /// Reads the zones of a GeoJSON `FeatureCollection`. Features without a `name` are
+    /// named by their index, and features of other geometry types are skipped.
+    pub fn from_geojson(geojson: &str) -> Result<Vec<Zone>, ZoneError> {
+        let value: Value = serde_json::from_str(geojson)?;
+        let features = value
+            .get("features")
+            .and_then(Value::as_array)
+            .ok_or(ZoneError::InvalidZones("not a FeatureCollection"))?;
+
+        let mut zones = Vec::new();
+        for (index, feature) in features.iter().enumerate() {
+            let Some(geometry) = feature.get("geometry") else {
+                continue;
+            };
+            let coordinates = geometry
+                .get("coordinates")
+                .ok_or(ZoneError::InvalidZones("geometry has no coordinates"))?;
+            let rings = match geometry.get("type").and_then(Value::as_str) {
+                Some("Polygon") => parse_rings(coordinates)?,
+                Some("MultiPolygon") => {
+                    let mut rings = Vec::new();
+                    for polygon in coordinates
+                        .as_array()
+                        .ok_or(ZoneError::InvalidZones("coordinates are not an array"))?
+                    {
+                        rings.extend(parse_rings(polygon)?);
+                    }
+                    rings
+                }
+                _ => continue,
+            };
+            let name = feature
+                .pointer("/properties/name")
+                .and_then(Value::as_str)
+                .map_or_else(|| index.to_string(), str::to_string);
+            zones.push(Zone { name, rings });
+        }
+        Ok(zones)
+    }
+
+    /// Edges of every ring, as pairs of `(longitude, latitude)` points.
+    fn edges(&self) -> impl Iterator<Item = ((f64, f64), (f64, f64))> + '_ {
+        let point = |&(lat, lon): &(i64, i64)| (lon as f64, lat as f64);
+        self.rings.iter().flat_map(move |ring| {
+            ring.windows(2)
+                .map(move |pair| (point(&pair[0]), point(&pair[1])))
+        })
+    }
+
+    /// Tells whether a `(longitude, latitude)` point is inside, by counting the edges
+    /// crossed by a ray going east.
+    fn contains(&self, (x, y): (f64, f64)) -> bool {
+        self.edges()
+            .filter(|&((x1, y1), (x2, y2))| {
+                (y1 > y) != (y2 > y) && x < x1 + (y - y1) / (y2 - y1) * (x2 - x1)
+            })
+            .count()
+            % 2
+            == 1
+    }
+
+    /// Tells whether a point, in scaled units, is inside.
+    pub fn contains_point(&self, latitude: i64, longitude: i64) -> bool {
+        self.contains((longitude as f64, latitude as f64))
+    }
+
+    /// Measures the distance and time `trajectory` spent inside.
+    pub fn usage(&self, trajectory: &Trajectory) -> ZoneUsage {
+        let mut usage = ZoneUsage::default();
+        for i in 1..trajectory.latitudes.len() {
+            let p = (
+                trajectory.longitudes[i - 1] as f64,
+                trajectory.latitudes[i - 1] as f64,
+            );
+            let r = (
+                trajectory.longitudes[i] as f64 - p.0,
+                trajectory.latitudes[i] as f64 - p.1,
+            );
+
+            // Fractions of the segment where it crosses an edge
+            let mut cuts = vec![0.0, 1.0];
+            for (q, q_end) in self.edges() {
+                let s = (q_end.0 - q.0, q_end.1 - q.1);
+                let denominator = r.0 * s.1 - r.1 * s.0;
+                if denominator == 0.0 {
+                    continue;
+                }
+                let qp = (q.0 - p.0, q.1 - p.1);
+                let t = (qp.0 * s.1 - qp.1 * s.0) / denominator;
+                let u = (qp.0 * r.1 - qp.1 * r.0) / denominator;
+                if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) {
+                    cuts.push(t);
+                }
+            }
+            cuts.sort_by(f64::total_cmp);
+
+            let inside: f64 = cuts
+                .windows(2)
+                .filter(|pair| pair[1] > pair[0])
+                .filter(|pair| {
+                    let middle = (pair[0] + pair[1]) / 2.0;
+                    self.contains((p.0 + middle * r.0, p.1 + middle * r.1))
+                })
+                .map(|pair| pair[1] - pair[0])
+                .sum();
+            if inside > 0.0 {
+                let length_m = haversine_m(
+                    trajectory.latitudes[i - 1],
+                    trajectory.longitudes[i - 1],
+                    trajectory.latitudes[i],
+                    trajectory.longitudes[i],
+                );
+                let duration_s =
+                    trajectory.timestamps[i].saturating_sub(trajectory.timestamps[i - 1]);
+                usage.distance_m += inside * length_m;
+                usage.duration_s += inside * duration_s as f64;
+            }
+        }
+        usage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trajectory::CoordinateSystem;
+    use crate::geo::METERS_PER_UNIT;
+
+    /// A 2 x 2 km square at the equator with a 1 x 1 km hole in its south-east corner, and
+    /// a separate triangle
+    const ZONES: &str = r#"{
+        "type": "FeatureCollection",
+        "features": [
+            {
+                "type": "Feature",
+                "properties": { "name": "center" },
+                "geometry": {
+                    "type": "Polygon",
+                    "coordinates": [
+                        [[0, 0], [0.02, 0], [0.02, 0.02], [0, 0.02], [0, 0]],
+                        [[0.01, 0], [0.02, 0], [0.02, 0.01], [0.01, 0.01], [0.01, 0]]
+                    ]
+                }
+            },
+            {
+                "type": "Feature",
+                "properties": {},
+                "geometry": {
+                    "type": "MultiPolygon",
+                    "coordinates": [[[[1, 1], [1.01, 1], [1.01, 1.01], [1, 1]]]]
+                }
+            },
+            { "type": "Feature", "geometry": { "type": "Point", "coordinates": [0, 0] } }
+        ]
+    }"#;
+
+    #[test]
+    fn test_from_geojson() {
+        let zones = Zone::from_geojson(ZONES).unwrap();
+        assert_eq!(zones.len(), 2);
+        assert_eq!(zones[0].name, "center");
+        assert_eq!(zones[0].rings.len(), 2);
+        assert_eq!(zones[0].rings[1][1], (0, 20_000));
+        assert_eq!(zones[1].name, "1");
+
+        assert!(matches!(
+            Zone::from_geojson("{}"),
+            Err(ZoneError::InvalidZones(_))
+        ));
+        assert!(matches!(
+            Zone::from_geojson("not json"),
+            Err(ZoneError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn test_contains() {
+        let zone = &Zone::from_geojson(ZONES).unwrap()[0];
+        assert!(zone.contains((5_000.0, 5_000.0)));
+        assert!(zone.contains((15_000.0, 15_000.0)));
+        assert!(!zone.contains((15_000.0, 5_000.0)));
+        assert!(!zone.contains((25_000.0, 5_000.0)));
+        assert!(zone.contains_point(15_000, 5_000));
+        assert!(!zone.contains_point(5_000, 15_000));
+    }
+
+    #[test]
+    fn test_usage() {
+        let zone = &Zone::from_geojson(ZONES).unwrap()[0];
+        // West to east across the hole from 1 km outside to 1 km past the zone, then north
+        // 3 km out of it, 1 m/s
+        let trajectory = Trajectory {
+            latitudes: vec![5_000, 5_000, 35_000],
+            longitudes: vec![-10_000, 30_000, 30_000],
+            timestamps: vec![0, 4_448, 7_784],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+        let usage = zone.usage(&trajectory);
+        let expected_m = 10_000.0 * METERS_PER_UNIT;
+        assert!((usage.distance_m - expected_m).abs() < 1.0, "{usage:?}");
+        assert!((usage.duration_s - 1_112.0).abs() < 1.0, "{usage:?}");
+
+        // Entering from the west, then staying inside
+        let trajectory = Trajectory {
+            latitudes: vec![15_000, 15_000, 10_000],
+            longitudes: vec![-5_000, 5_000, 5_000],
+            timestamps: vec![0, 100, 200],
+            altitudes: Vec::new(),
+            coordinates: CoordinateSystem::Wgs84,
+        };
+        let usage = zone.usage(&trajectory);
+        assert!((usage.distance_m - expected_m).abs() < 1.0, "{usage:?}");
+        assert_eq!(usage.duration_s, 150.0);
+    }
+}
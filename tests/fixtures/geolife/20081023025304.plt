@@ -0,0 +1,406 @@
+Geolife trajectory
+WGS 84
+Altitude is in Feet
+Reserved 3
+0,2,255,My Track,0,0,2,8421376
+0
+39.980000,116.310000,0,119,39744.1201851852,2008-10-23,02:53:04
+39.980052,116.310010,0,244,39744.1202430556,2008-10-23,02:53:09
+39.980133,116.310030,0,181,39744.1203009259,2008-10-23,02:53:14
+39.980206,116.310074,0,231,39744.1203587963,2008-10-23,02:53:19
+39.980245,116.310096,0,252,39744.1204166667,2008-10-23,02:53:24
+39.980311,116.310152,0,207,39744.1204282407,2008-10-23,02:53:25
+39.980386,116.310199,0,245,39744.1204861111,2008-10-23,02:53:30
+39.980443,116.310260,0,282,39744.1205092593,2008-10-23,02:53:32
+39.980494,116.310330,0,114,39744.1205324074,2008-10-23,02:53:34
+39.980536,116.310363,0,227,39744.1205902778,2008-10-23,02:53:39
+39.980574,116.310422,0,110,39744.1206018518,2008-10-23,02:53:40
+39.980622,116.310475,0,293,39744.1206250000,2008-10-23,02:53:42
+39.980674,116.310523,0,154,39744.1206365741,2008-10-23,02:53:43
+39.980721,116.310570,0,190,39744.1206944444,2008-10-23,02:53:48
+39.980769,116.310613,0,129,39744.1207523148,2008-10-23,02:53:53
+39.980815,116.310651,0,256,39744.1207754630,2008-10-23,02:53:55
+39.980834,116.310671,0,240,39744.1207986111,2008-10-23,02:53:57
+39.980901,116.310726,0,251,39744.1208564815,2008-10-23,02:54:02
+39.980933,116.310753,0,283,39744.1209143518,2008-10-23,02:54:07
+39.980952,116.310758,0,298,39744.1209259259,2008-10-23,02:54:08
+39.980972,116.310763,0,113,39744.1209490741,2008-10-23,02:54:10
+39.981002,116.310765,0,205,39744.1209722222,2008-10-23,02:54:12
+39.981091,116.310760,0,163,39744.1210300926,2008-10-23,02:54:17
+39.981181,116.310737,0,237,39744.1210532407,2008-10-23,02:54:19
+39.981266,116.310728,0,229,39744.1211111111,2008-10-23,02:54:24
+39.981307,116.310728,0,106,39744.1211226852,2008-10-23,02:54:25
+39.981384,116.310708,0,122,39744.1211458333,2008-10-23,02:54:27
+39.981416,116.310698,0,162,39744.1211574074,2008-10-23,02:54:28
+39.981443,116.310671,0,264,39744.1211805556,2008-10-23,02:54:30
+39.981498,116.310623,0,183,39744.1211921296,2008-10-23,02:54:31
+39.981551,116.310569,0,153,39744.1212037037,2008-10-23,02:54:32
+39.981574,116.310537,0,107,39744.1212268519,2008-10-23,02:54:34
+39.981606,116.310509,0,275,39744.1212500000,2008-10-23,02:54:36
+39.981658,116.310468,0,164,39744.1212731482,2008-10-23,02:54:38
+39.981726,116.310421,0,103,39744.1213310185,2008-10-23,02:54:43
+39.981740,116.310406,0,107,39744.1213541667,2008-10-23,02:54:45
+39.981794,116.310366,0,244,39744.1214120370,2008-10-23,02:54:50
+39.981846,116.310312,0,153,39744.1214699074,2008-10-23,02:54:55
+39.981901,116.310269,0,223,39744.1214814815,2008-10-23,02:54:56
+39.981919,116.310259,0,145,39744.1215046296,2008-10-23,02:54:58
+39.981944,116.310246,0,248,39744.1215162037,2008-10-23,02:54:59
+39.981980,116.310218,0,264,39744.1215393519,2008-10-23,02:55:01
+39.982037,116.310171,0,148,39744.1215972222,2008-10-23,02:55:06
+39.982095,116.310115,0,124,39744.1216203704,2008-10-23,02:55:08
+39.982142,116.310084,0,168,39744.1216435185,2008-10-23,02:55:10
+39.982159,116.310071,0,266,39744.1217013889,2008-10-23,02:55:15
+39.982215,116.310029,0,130,39744.1217592593,2008-10-23,02:55:20
+39.982273,116.309991,0,269,39744.1218171296,2008-10-23,02:55:25
+39.982346,116.309932,0,180,39744.1218402778,2008-10-23,02:55:27
+39.982377,116.309910,0,133,39744.1218634259,2008-10-23,02:55:29
+39.982398,116.309895,0,151,39744.1218750000,2008-10-23,02:55:30
+39.982446,116.309831,0,107,39744.1218865741,2008-10-23,02:55:31
+39.982495,116.309768,0,251,39744.1219097222,2008-10-23,02:55:33
+39.982536,116.309715,0,177,39744.1219212963,2008-10-23,02:55:34
+39.982564,116.309677,0,271,39744.1219444444,2008-10-23,02:55:36
+39.982588,116.309652,0,154,39744.1220023148,2008-10-23,02:55:41
+39.982637,116.309603,0,243,39744.1220601852,2008-10-23,02:55:46
+39.982649,116.309571,0,233,39744.1220717593,2008-10-23,02:55:47
+39.982678,116.309513,0,165,39744.1221296296,2008-10-23,02:55:52
+39.982707,116.309448,0,239,39744.1221527778,2008-10-23,02:55:54
+39.982749,116.309373,0,129,39744.1222106482,2008-10-23,02:55:59
+39.982792,116.309321,0,156,39744.1222337963,2008-10-23,02:56:01
+39.982842,116.309248,0,184,39744.1222453704,2008-10-23,02:56:02
+39.982852,116.309216,0,289,39744.1222685185,2008-10-23,02:56:04
+39.982887,116.309153,0,277,39744.1222916667,2008-10-23,02:56:06
+39.982900,116.309131,0,262,39744.1223032407,2008-10-23,02:56:07
+39.982912,116.309105,0,217,39744.1223263889,2008-10-23,02:56:09
+39.982937,116.309055,0,144,39744.1223495370,2008-10-23,02:56:11
+39.982954,116.309019,0,280,39744.1224074074,2008-10-23,02:56:16
+39.982981,116.308961,0,145,39744.1224305556,2008-10-23,02:56:18
+39.983031,116.308887,0,218,39744.1224884259,2008-10-23,02:56:23
+39.983068,116.308813,0,180,39744.1225462963,2008-10-23,02:56:28
+39.983086,116.308747,0,133,39744.1225694444,2008-10-23,02:56:30
+39.983104,116.308704,0,170,39744.1226273148,2008-10-23,02:56:35
+39.983135,116.308637,0,233,39744.1226851852,2008-10-23,02:56:40
+39.983146,116.308581,0,193,39744.1227430556,2008-10-23,02:56:45
+39.983151,116.308560,0,273,39744.1227546296,2008-10-23,02:56:46
+39.983162,116.308539,0,239,39744.1228125000,2008-10-23,02:56:51
+39.983176,116.308517,0,196,39744.1228356482,2008-10-23,02:56:53
+39.983210,116.308475,0,146,39744.1228935185,2008-10-23,02:56:58
+39.983232,116.308415,0,157,39744.1229166667,2008-10-23,02:57:00
+39.983250,116.308373,0,277,39744.1229398148,2008-10-23,02:57:02
+39.983277,116.308321,0,229,39744.1229629630,2008-10-23,02:57:04
+39.983318,116.308257,0,191,39744.1229861111,2008-10-23,02:57:06
+39.983397,116.308197,0,106,39744.1230439815,2008-10-23,02:57:11
+39.983473,116.308136,0,160,39744.1230671296,2008-10-23,02:57:13
+39.983527,116.308072,0,144,39744.1230787037,2008-10-23,02:57:14
+39.983583,116.308033,0,225,39744.1231018519,2008-10-23,02:57:16
+39.983614,116.308011,0,221,39744.1231597222,2008-10-23,02:57:21
+39.983674,116.307953,0,135,39744.1232175926,2008-10-23,02:57:26
+39.983688,116.307939,0,225,39744.1232754630,2008-10-23,02:57:31
+39.983714,116.307912,0,232,39744.1232870370,2008-10-23,02:57:32
+39.983775,116.307849,0,200,39744.1232986111,2008-10-23,02:57:33
+39.983818,116.307806,0,105,39744.1233101852,2008-10-23,02:57:34
+39.983903,116.307759,0,242,39744.1233680556,2008-10-23,02:57:39
+39.983937,116.307749,0,214,39744.1234259259,2008-10-23,02:57:44
+39.984018,116.307731,0,140,39744.1234375000,2008-10-23,02:57:45
+39.984071,116.307721,0,179,39744.1234490741,2008-10-23,02:57:46
+39.984141,116.307716,0,269,39744.1234606481,2008-10-23,02:57:47
+39.984185,116.307705,0,286,39744.1235185185,2008-10-23,02:57:52
+39.984219,116.307691,0,259,39744.1235416667,2008-10-23,02:57:54
+39.984300,116.307671,0,274,39744.1235995370,2008-10-23,02:57:59
+39.984322,116.307666,0,282,39744.1236111111,2008-10-23,02:58:00
+39.984352,116.307667,0,202,39744.1236226852,2008-10-23,02:58:01
+39.984450,116.307665,0,238,39744.1236805556,2008-10-23,02:58:06
+39.984492,116.307663,0,118,39744.1237037037,2008-10-23,02:58:08
+39.984528,116.307658,0,268,39744.1237615741,2008-10-23,02:58:13
+39.984560,116.307645,0,214,39744.1238194444,2008-10-23,02:58:18
+39.984653,116.307617,0,207,39744.1238773148,2008-10-23,02:58:23
+39.984687,116.307599,0,175,39744.1238888889,2008-10-23,02:58:24
+39.984733,116.307572,0,204,39744.1239467593,2008-10-23,02:58:29
+39.984806,116.307510,0,295,39744.1239583333,2008-10-23,02:58:30
+39.984826,116.307496,0,244,39744.1240162037,2008-10-23,02:58:35
+39.984865,116.307468,0,102,39744.1240393519,2008-10-23,02:58:37
+39.984926,116.307439,0,239,39744.1240972222,2008-10-23,02:58:42
+39.984997,116.307421,0,155,39744.1241203704,2008-10-23,02:58:44
+39.985025,116.307416,0,295,39744.1241435185,2008-10-23,02:58:46
+39.985045,116.307408,0,269,39744.1242013889,2008-10-23,02:58:51
+39.985133,116.307384,0,161,39744.1242592593,2008-10-23,02:58:56
+39.985194,116.307371,0,168,39744.1243171296,2008-10-23,02:59:01
+39.985279,116.307352,0,220,39744.1243402778,2008-10-23,02:59:03
+39.985344,116.307338,0,153,39744.1243981481,2008-10-23,02:59:08
+39.985392,116.307330,0,120,39744.1244097222,2008-10-23,02:59:09
+39.985460,116.307313,0,228,39744.1244212963,2008-10-23,02:59:10
+39.985551,116.307296,0,112,39744.1244444444,2008-10-23,02:59:12
+39.985599,116.307286,0,119,39744.1244560185,2008-10-23,02:59:13
+39.985694,116.307286,0,237,39744.1245138889,2008-10-23,02:59:18
+39.985736,116.307283,0,297,39744.1245370370,2008-10-23,02:59:20
+39.985817,116.307279,0,192,39744.1245486111,2008-10-23,02:59:21
+39.985890,116.307289,0,199,39744.1246064815,2008-10-23,02:59:26
+39.985921,116.307302,0,152,39744.1246643519,2008-10-23,02:59:31
+39.985979,116.307334,0,261,39744.1246759259,2008-10-23,02:59:32
+39.986056,116.307396,0,148,39744.1246990741,2008-10-23,02:59:34
+39.986077,116.307416,0,137,39744.1247222222,2008-10-23,02:59:36
+39.986151,116.307456,0,275,39744.1247800926,2008-10-23,02:59:41
+39.986184,116.307483,0,242,39744.1247916667,2008-10-23,02:59:42
+39.986256,116.307522,0,220,39744.1248495370,2008-10-23,02:59:47
+39.986288,116.307533,0,108,39744.1248611111,2008-10-23,02:59:48
+39.986346,116.307556,0,193,39744.1248726852,2008-10-23,02:59:49
+39.986368,116.307559,0,179,39744.1249305556,2008-10-23,02:59:54
+39.986459,116.307577,0,139,39744.1249537037,2008-10-23,02:59:56
+39.986541,116.307582,0,235,39744.1249768519,2008-10-23,02:59:58
+39.986563,116.307586,0,297,39744.1249884259,2008-10-23,02:59:59
+39.986626,116.307605,0,233,39744.1250115741,2008-10-23,03:00:01
+39.986651,116.307611,0,194,39744.1250347222,2008-10-23,03:00:03
+39.986711,116.307615,0,146,39744.1250578704,2008-10-23,03:00:05
+39.986771,116.307618,0,295,39744.1250810185,2008-10-23,03:00:07
+39.986853,116.307629,0,149,39744.1251041667,2008-10-23,03:00:09
+39.986932,116.307648,0,146,39744.1251157407,2008-10-23,03:00:10
+39.986986,116.307667,0,230,39744.1251388889,2008-10-23,03:00:12
+39.987047,116.307695,0,115,39744.1251504630,2008-10-23,03:00:13
+39.987072,116.307705,0,104,39744.1251736111,2008-10-23,03:00:15
+39.987112,116.307714,0,247,39744.1251851852,2008-10-23,03:00:16
+39.987135,116.307720,0,139,39744.1251967593,2008-10-23,03:00:17
+39.987169,116.307722,0,278,39744.1252083333,2008-10-23,03:00:18
+39.987253,116.307712,0,147,39744.1252199074,2008-10-23,03:00:19
+39.987329,116.307721,0,114,39744.1252430556,2008-10-23,03:00:21
+39.987428,116.307728,0,137,39744.1252546296,2008-10-23,03:00:22
+39.987490,116.307731,0,210,39744.1253125000,2008-10-23,03:00:27
+39.987580,116.307731,0,146,39744.1253356481,2008-10-23,03:00:29
+39.987649,116.307740,0,244,39744.1253587963,2008-10-23,03:00:31
+39.987698,116.307736,0,298,39744.1253819444,2008-10-23,03:00:33
+39.987792,116.307718,0,262,39744.1254050926,2008-10-23,03:00:35
+39.987872,116.307711,0,108,39744.1254282407,2008-10-23,03:00:37
+39.987929,116.307707,0,215,39744.1254861111,2008-10-23,03:00:42
+39.988002,116.307690,0,250,39744.1255092593,2008-10-23,03:00:44
+39.988057,116.307688,0,224,39744.1255671296,2008-10-23,03:00:49
+39.988079,116.307690,0,253,39744.1255902778,2008-10-23,03:00:51
+39.988156,116.307693,0,131,39744.1256481481,2008-10-23,03:00:56
+39.988187,116.307688,0,216,39744.1257060185,2008-10-23,03:01:01
+39.988218,116.307673,0,238,39744.1257291667,2008-10-23,03:01:03
+39.988291,116.307639,0,264,39744.1257870370,2008-10-23,03:01:08
+39.988371,116.307603,0,258,39744.1258449074,2008-10-23,03:01:13
+39.988398,116.307594,0,174,39744.1258564815,2008-10-23,03:01:14
+39.988440,116.307575,0,215,39744.1258680556,2008-10-23,03:01:15
+39.988471,116.307563,0,250,39744.1258796296,2008-10-23,03:01:16
+39.988540,116.307528,0,117,39744.1259375000,2008-10-23,03:01:21
+39.988596,116.307490,0,291,39744.1259490741,2008-10-23,03:01:22
+39.988641,116.307449,0,145,39744.1259722222,2008-10-23,03:01:24
+39.988702,116.307387,0,247,39744.1259837963,2008-10-23,03:01:25
+39.988762,116.307316,0,126,39744.1259953704,2008-10-23,03:01:26
+39.988795,116.307268,0,241,39744.1260185185,2008-10-23,03:01:28
+39.988822,116.307239,0,176,39744.1260416667,2008-10-23,03:01:30
+39.988855,116.307210,0,176,39744.1260995370,2008-10-23,03:01:35
+39.988923,116.307173,0,233,39744.1261111111,2008-10-23,03:01:36
+39.988965,116.307158,0,265,39744.1261226852,2008-10-23,03:01:37
+39.989028,116.307141,0,262,39744.1261805556,2008-10-23,03:01:42
+39.989055,116.307137,0,264,39744.1261921296,2008-10-23,03:01:43
+39.989135,116.307124,0,207,39744.1262037037,2008-10-23,03:01:44
+39.989232,116.307122,0,177,39744.1262152778,2008-10-23,03:01:45
+39.989299,116.307132,0,262,39744.1262731481,2008-10-23,03:01:50
+39.989380,116.307126,0,221,39744.1262847222,2008-10-23,03:01:51
+39.989470,116.307130,0,200,39744.1263078704,2008-10-23,03:01:53
+39.989529,116.307143,0,289,39744.1263310185,2008-10-23,03:01:55
+39.989608,116.307164,0,229,39744.1263888889,2008-10-23,03:02:00
+39.989632,116.307165,0,246,39744.1264467593,2008-10-23,03:02:05
+39.989728,116.307192,0,149,39744.1264699074,2008-10-23,03:02:07
+39.989803,116.307209,0,298,39744.1264930556,2008-10-23,03:02:09
+39.989842,116.307224,0,199,39744.1265162037,2008-10-23,03:02:11
+39.989926,116.307247,0,103,39744.1265277778,2008-10-23,03:02:12
+39.990012,116.307270,0,275,39744.1265393519,2008-10-23,03:02:13
+39.990074,116.307287,0,180,39744.1265509259,2008-10-23,03:02:14
+39.990102,116.307298,0,266,39744.1265740741,2008-10-23,03:02:16
+39.990126,116.307307,0,208,39744.1265972222,2008-10-23,03:02:18
+39.990156,116.307317,0,100,39744.1266550926,2008-10-23,03:02:23
+39.990184,116.307324,0,181,39744.1267129630,2008-10-23,03:02:28
+39.990218,116.307333,0,258,39744.1267708333,2008-10-23,03:02:33
+39.990244,116.307334,0,207,39744.1268287037,2008-10-23,03:02:38
+39.990292,116.307327,0,189,39744.1268865741,2008-10-23,03:02:43
+39.990375,116.307296,0,247,39744.1269444444,2008-10-23,03:02:48
+39.990395,116.307288,0,255,39744.1269560185,2008-10-23,03:02:49
+39.990455,116.307274,0,110,39744.1269791667,2008-10-23,03:02:51
+39.990497,116.307261,0,260,39744.1269907407,2008-10-23,03:02:52
+39.990547,116.307236,0,256,39744.1270023148,2008-10-23,03:02:53
+39.990632,116.307199,0,146,39744.1270254630,2008-10-23,03:02:55
+39.990719,116.307160,0,221,39744.1270833333,2008-10-23,03:03:00
+39.990791,116.307122,0,281,39744.1271064815,2008-10-23,03:03:02
+39.990842,116.307076,0,123,39744.1271180556,2008-10-23,03:03:03
+39.990872,116.307036,0,235,39744.1271412037,2008-10-23,03:03:05
+39.990909,116.306998,0,204,39744.1271527778,2008-10-23,03:03:06
+39.990964,116.306939,0,284,39744.1272106481,2008-10-23,03:03:11
+39.991025,116.306865,0,137,39744.1272685185,2008-10-23,03:03:16
+39.991049,116.306838,0,273,39744.1273263889,2008-10-23,03:03:21
+39.991094,116.306777,0,296,39744.1273495370,2008-10-23,03:03:23
+39.991153,116.306723,0,293,39744.1274074074,2008-10-23,03:03:28
+39.991230,116.306674,0,105,39744.1274652778,2008-10-23,03:03:33
+39.991290,116.306648,0,146,39744.1274884259,2008-10-23,03:03:35
+39.991327,116.306637,0,157,39744.1275000000,2008-10-23,03:03:36
+39.991362,116.306633,0,109,39744.1275578704,2008-10-23,03:03:41
+39.991415,116.306620,0,191,39744.1275694444,2008-10-23,03:03:42
+39.991443,116.306614,0,222,39744.1276273148,2008-10-23,03:03:47
+39.991477,116.306610,0,237,39744.1276851852,2008-10-23,03:03:52
+39.991549,116.306612,0,100,39744.1277430556,2008-10-23,03:03:57
+39.991574,116.306613,0,109,39744.1277662037,2008-10-23,03:03:59
+39.991633,116.306622,0,104,39744.1277893518,2008-10-23,03:04:01
+39.991699,116.306625,0,209,39744.1278009259,2008-10-23,03:04:02
+39.991724,116.306626,0,240,39744.1278125000,2008-10-23,03:04:03
+39.991813,116.306635,0,274,39744.1278240741,2008-10-23,03:04:04
+39.991842,116.306631,0,228,39744.1278356481,2008-10-23,03:04:05
+39.991936,116.306600,0,164,39744.1278935185,2008-10-23,03:04:10
+39.991967,116.306585,0,225,39744.1279166667,2008-10-23,03:04:12
+39.992025,116.306558,0,213,39744.1279282407,2008-10-23,03:04:13
+39.992103,116.306540,0,119,39744.1279861111,2008-10-23,03:04:18
+39.992141,116.306539,0,263,39744.1280439815,2008-10-23,03:04:23
+39.992220,116.306548,0,234,39744.1280555556,2008-10-23,03:04:24
+39.992258,116.306557,0,203,39744.1280787037,2008-10-23,03:04:26
+39.992285,116.306562,0,160,39744.1280902778,2008-10-23,03:04:27
+39.992338,116.306575,0,220,39744.1281481481,2008-10-23,03:04:32
+39.992397,116.306594,0,120,39744.1281712963,2008-10-23,03:04:34
+39.992459,116.306614,0,261,39744.1282291667,2008-10-23,03:04:39
+39.992514,116.306620,0,254,39744.1282407407,2008-10-23,03:04:40
+39.992565,116.306621,0,193,39744.1282986111,2008-10-23,03:04:45
+39.992662,116.306603,0,238,39744.1283101852,2008-10-23,03:04:46
+39.992744,116.306578,0,117,39744.1283217593,2008-10-23,03:04:47
+39.992821,116.306550,0,251,39744.1283449074,2008-10-23,03:04:49
+39.992856,116.306537,0,273,39744.1284027778,2008-10-23,03:04:54
+39.992940,116.306496,0,137,39744.1284606481,2008-10-23,03:04:59
+39.993003,116.306477,0,159,39744.1284837963,2008-10-23,03:05:01
+39.993039,116.306462,0,300,39744.1284953704,2008-10-23,03:05:02
+39.993075,116.306451,0,117,39744.1285185185,2008-10-23,03:05:04
+39.993147,116.306405,0,167,39744.1285763889,2008-10-23,03:05:09
+39.993179,116.306389,0,298,39744.1286342593,2008-10-23,03:05:14
+39.993265,116.306367,0,169,39744.1286574074,2008-10-23,03:05:16
+39.993294,116.306360,0,178,39744.1286805556,2008-10-23,03:05:18
+39.993336,116.306357,0,194,39744.1287384259,2008-10-23,03:05:23
+39.993401,116.306342,0,117,39744.1287615741,2008-10-23,03:05:25
+39.993431,116.306340,0,161,39744.1287731481,2008-10-23,03:05:26
+39.993516,116.306329,0,171,39744.1287962963,2008-10-23,03:05:28
+39.993567,116.306325,0,185,39744.1288541667,2008-10-23,03:05:33
+39.993629,116.306304,0,222,39744.1288773148,2008-10-23,03:05:35
+39.993703,116.306290,0,178,39744.1288888889,2008-10-23,03:05:36
+39.993780,116.306279,0,134,39744.1289467593,2008-10-23,03:05:41
+39.993827,116.306276,0,294,39744.1289583333,2008-10-23,03:05:42
+39.993854,116.306273,0,225,39744.1290162037,2008-10-23,03:05:47
+39.993935,116.306248,0,121,39744.1290393519,2008-10-23,03:05:49
+39.993966,116.306243,0,160,39744.1290509259,2008-10-23,03:05:50
+39.994022,116.306229,0,252,39744.1290625000,2008-10-23,03:05:51
+39.994052,116.306216,0,146,39744.1291203704,2008-10-23,03:05:56
+39.994114,116.306188,0,119,39744.1291319444,2008-10-23,03:05:57
+39.994196,116.306173,0,266,39744.1291550926,2008-10-23,03:05:59
+39.994246,116.306182,0,136,39744.1291666667,2008-10-23,03:06:00
+39.994291,116.306188,0,136,39744.1292245370,2008-10-23,03:06:05
+39.994322,116.306202,0,211,39744.1292824074,2008-10-23,03:06:10
+39.994342,116.306206,0,285,39744.1293055556,2008-10-23,03:06:12
+39.994389,116.306213,0,278,39744.1293634259,2008-10-23,03:06:17
+39.994471,116.306243,0,240,39744.1293865741,2008-10-23,03:06:19
+39.994543,116.306270,0,125,39744.1294097222,2008-10-23,03:06:21
+39.994628,116.306316,0,197,39744.1294328704,2008-10-23,03:06:23
+39.994681,116.306335,0,234,39744.1294444444,2008-10-23,03:06:24
+39.994722,116.306349,0,106,39744.1294560185,2008-10-23,03:06:25
+39.994796,116.306376,0,295,39744.1294675926,2008-10-23,03:06:26
+39.994845,116.306403,0,250,39744.1295254630,2008-10-23,03:06:31
+39.994931,116.306429,0,237,39744.1295370370,2008-10-23,03:06:32
+39.994966,116.306441,0,193,39744.1295949074,2008-10-23,03:06:37
+39.995031,116.306437,0,298,39744.1296180556,2008-10-23,03:06:39
+39.995078,116.306425,0,215,39744.1296759259,2008-10-23,03:06:44
+39.995143,116.306413,0,202,39744.1297337963,2008-10-23,03:06:49
+39.995236,116.306390,0,222,39744.1297916667,2008-10-23,03:06:54
+39.995314,116.306379,0,100,39744.1298148148,2008-10-23,03:06:56
+39.995352,116.306377,0,129,39744.1298263889,2008-10-23,03:06:57
+39.995408,116.306381,0,215,39744.1298379630,2008-10-23,03:06:58
+39.995486,116.306395,0,206,39744.1298611111,2008-10-23,03:07:00
+39.995514,116.306396,0,278,39744.1298842593,2008-10-23,03:07:02
+39.995596,116.306405,0,238,39744.1299074074,2008-10-23,03:07:04
+39.995672,116.306424,0,298,39744.1299305556,2008-10-23,03:07:06
+39.995707,116.306435,0,201,39744.1299421296,2008-10-23,03:07:07
+39.995749,116.306444,0,232,39744.1299537037,2008-10-23,03:07:08
+39.995793,116.306454,0,125,39744.1300115741,2008-10-23,03:07:13
+39.995867,116.306482,0,298,39744.1300347222,2008-10-23,03:07:15
+39.995899,116.306489,0,206,39744.1300925926,2008-10-23,03:07:20
+39.995943,116.306477,0,171,39744.1301157407,2008-10-23,03:07:22
+39.996010,116.306450,0,231,39744.1301273148,2008-10-23,03:07:23
+39.996052,116.306434,0,298,39744.1301388889,2008-10-23,03:07:24
+39.996133,116.306404,0,230,39744.1301504630,2008-10-23,03:07:25
+39.996228,116.306397,0,155,39744.1302083333,2008-10-23,03:07:30
+39.996267,116.306400,0,175,39744.1302314815,2008-10-23,03:07:32
+39.996312,116.306396,0,180,39744.1302893519,2008-10-23,03:07:37
+39.996411,116.306379,0,150,39744.1303472222,2008-10-23,03:07:42
+39.996473,116.306357,0,209,39744.1304050926,2008-10-23,03:07:47
+39.996546,116.306345,0,297,39744.1304166667,2008-10-23,03:07:48
+39.996570,116.306338,0,228,39744.1304282407,2008-10-23,03:07:49
+39.996618,116.306333,0,167,39744.1304513889,2008-10-23,03:07:51
+39.996651,116.306328,0,171,39744.1304629630,2008-10-23,03:07:52
+39.996718,116.306314,0,243,39744.1304745370,2008-10-23,03:07:53
+39.996758,116.306305,0,285,39744.1304976852,2008-10-23,03:07:55
+39.996852,116.306278,0,218,39744.1305092593,2008-10-23,03:07:56
+39.996941,116.306234,0,133,39744.1305671296,2008-10-23,03:08:01
+39.996968,116.306225,0,261,39744.1306250000,2008-10-23,03:08:06
+39.997021,116.306215,0,286,39744.1306365741,2008-10-23,03:08:07
+39.997040,116.306209,0,297,39744.1306597222,2008-10-23,03:08:09
+39.997117,116.306183,0,103,39744.1306712963,2008-10-23,03:08:10
+39.997167,116.306169,0,227,39744.1307291667,2008-10-23,03:08:15
+39.997212,116.306156,0,163,39744.1307407407,2008-10-23,03:08:16
+39.997234,116.306137,0,113,39744.1307638889,2008-10-23,03:08:18
+39.997306,116.306091,0,198,39744.1307870370,2008-10-23,03:08:20
+39.997335,116.306075,0,235,39744.1308101852,2008-10-23,03:08:22
+39.997400,116.306047,0,281,39744.1308680556,2008-10-23,03:08:27
+39.997422,116.306037,0,154,39744.1308796296,2008-10-23,03:08:28
+39.997445,116.306028,0,259,39744.1308912037,2008-10-23,03:08:29
+39.997537,116.306015,0,178,39744.1309027778,2008-10-23,03:08:30
+39.997602,116.306016,0,232,39744.1309606481,2008-10-23,03:08:35
+39.997624,116.306014,0,268,39744.1309837963,2008-10-23,03:08:37
+39.997687,116.306002,0,210,39744.1310069444,2008-10-23,03:08:39
+39.997747,116.305989,0,168,39744.1310300926,2008-10-23,03:08:41
+39.997834,116.305947,0,253,39744.1310532407,2008-10-23,03:08:43
+39.997914,116.305921,0,132,39744.1311111111,2008-10-23,03:08:48
+39.997997,116.305884,0,164,39744.1311226852,2008-10-23,03:08:49
+39.998049,116.305838,0,284,39744.1311342593,2008-10-23,03:08:50
+39.998062,116.305821,0,106,39744.1311574074,2008-10-23,03:08:52
+39.998080,116.305788,0,247,39744.1311689815,2008-10-23,03:08:53
+39.998109,116.305741,0,111,39744.1311921296,2008-10-23,03:08:55
+39.998143,116.305690,0,261,39744.1312500000,2008-10-23,03:09:00
+39.998159,116.305666,0,174,39744.1312615741,2008-10-23,03:09:01
+39.998191,116.305577,0,218,39744.1313194444,2008-10-23,03:09:06
+39.998201,116.305559,0,151,39744.1313425926,2008-10-23,03:09:08
+39.998241,116.305495,0,244,39744.1313541667,2008-10-23,03:09:09
+39.998264,116.305462,0,116,39744.1313657407,2008-10-23,03:09:10
+39.998277,116.305442,0,131,39744.1313773148,2008-10-23,03:09:11
+39.998293,116.305416,0,235,39744.1314351852,2008-10-23,03:09:16
+39.998332,116.305374,0,228,39744.1314467593,2008-10-23,03:09:17
+39.998368,116.305286,0,172,39744.1315046296,2008-10-23,03:09:22
+39.998408,116.305204,0,252,39744.1315625000,2008-10-23,03:09:27
+39.998448,116.305132,0,248,39744.1315740741,2008-10-23,03:09:28
+39.998493,116.305084,0,144,39744.1316319444,2008-10-23,03:09:33
+39.998513,116.305065,0,155,39744.1316898148,2008-10-23,03:09:38
+39.998574,116.304992,0,113,39744.1317129630,2008-10-23,03:09:40
+39.998597,116.304974,0,115,39744.1317708333,2008-10-23,03:09:45
+39.998638,116.304921,0,231,39744.1318287037,2008-10-23,03:09:50
+39.998691,116.304851,0,180,39744.1318518519,2008-10-23,03:09:52
+39.998711,116.304813,0,129,39744.1318750000,2008-10-23,03:09:54
+39.998720,116.304788,0,228,39744.1318981481,2008-10-23,03:09:56
+39.998749,116.304708,0,197,39744.1319097222,2008-10-23,03:09:57
+39.998774,116.304635,0,257,39744.1319212963,2008-10-23,03:09:58
+39.998797,116.304551,0,102,39744.1319328704,2008-10-23,03:09:59
+39.998823,116.304493,0,271,39744.1319444444,2008-10-23,03:10:00
+39.998841,116.304426,0,200,39744.1319560185,2008-10-23,03:10:01
+39.998864,116.304332,0,253,39744.1320138889,2008-10-23,03:10:06
+39.998882,116.304294,0,207,39744.1320717593,2008-10-23,03:10:11
+39.998924,116.304216,0,223,39744.1321296296,2008-10-23,03:10:16
+39.998946,116.304185,0,177,39744.1321527778,2008-10-23,03:10:18
+39.998958,116.304158,0,281,39744.1321643519,2008-10-23,03:10:19
+39.998993,116.304096,0,223,39744.1321759259,2008-10-23,03:10:20
+39.999011,116.304041,0,263,39744.1321990741,2008-10-23,03:10:22
+39.999024,116.304003,0,108,39744.1322222222,2008-10-23,03:10:24
+39.999033,116.303931,0,181,39744.1322453704,2008-10-23,03:10:26
+39.999040,116.303881,0,204,39744.1322569444,2008-10-23,03:10:27
+39.999050,116.303822,0,204,39744.1322685185,2008-10-23,03:10:28
+39.999036,116.303729,0,117,39744.1322916667,2008-10-23,03:10:30
+39.999025,116.303665,0,139,39744.1323032407,2008-10-23,03:10:31
+39.999015,116.303628,0,169,39744.1323611111,2008-10-23,03:10:36
+39.999003,116.303573,0,193,39744.1324189815,2008-10-23,03:10:41
+39.999000,116.303483,0,149,39744.1324768519,2008-10-23,03:10:46
+39.999000,116.303444,0,199,39744.1325000000,2008-10-23,03:10:48
+39.998993,116.303383,0,273,39744.1325578704,2008-10-23,03:10:53
+39.998989,116.303324,0,122,39744.1325810185,2008-10-23,03:10:55
+39.998986,116.303244,0,236,39744.1326041667,2008-10-23,03:10:57
+39.998990,116.303205,0,181,39744.1326273148,2008-10-23,03:10:59
+39.998990,116.303120,0,288,39744.1326388889,2008-10-23,03:11:00
+39.999000,116.303058,0,155,39744.1326967593,2008-10-23,03:11:05
+39.999031,116.302969,0,262,39744.1327083333,2008-10-23,03:11:06
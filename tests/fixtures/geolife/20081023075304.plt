@@ -0,0 +1,306 @@
+Geolife trajectory
+WGS 84
+Altitude is in Feet
+Reserved 3
+0,2,255,My Track,0,0,2,8421376
+0
+39.990000,116.320000,0,131,39744.3285185185,2008-10-23,07:53:04
+39.990008,116.319904,0,170,39744.3285763889,2008-10-23,07:53:09
+39.990005,116.319872,0,106,39744.3285995370,2008-10-23,07:53:11
+39.989983,116.319792,0,192,39744.3286574074,2008-10-23,07:53:16
+39.989979,116.319754,0,269,39744.3286805556,2008-10-23,07:53:18
+39.989962,116.319662,0,223,39744.3286921296,2008-10-23,07:53:19
+39.989959,116.319562,0,270,39744.3287152778,2008-10-23,07:53:21
+39.989972,116.319502,0,290,39744.3287384259,2008-10-23,07:53:23
+39.989974,116.319443,0,214,39744.3287615741,2008-10-23,07:53:25
+39.989968,116.319373,0,263,39744.3287847222,2008-10-23,07:53:27
+39.989965,116.319340,0,192,39744.3288078704,2008-10-23,07:53:29
+39.989957,116.319282,0,124,39744.3288194444,2008-10-23,07:53:30
+39.989944,116.319235,0,108,39744.3288310185,2008-10-23,07:53:31
+39.989922,116.319157,0,128,39744.3288425926,2008-10-23,07:53:32
+39.989907,116.319086,0,293,39744.3289004630,2008-10-23,07:53:37
+39.989896,116.319056,0,235,39744.3289583333,2008-10-23,07:53:42
+39.989874,116.318998,0,110,39744.3290162037,2008-10-23,07:53:47
+39.989844,116.318910,0,156,39744.3290277778,2008-10-23,07:53:48
+39.989833,116.318886,0,140,39744.3290509259,2008-10-23,07:53:50
+39.989793,116.318833,0,161,39744.3290740741,2008-10-23,07:53:52
+39.989741,116.318790,0,260,39744.3291319444,2008-10-23,07:53:57
+39.989703,116.318755,0,128,39744.3291435185,2008-10-23,07:53:58
+39.989664,116.318722,0,239,39744.3291550926,2008-10-23,07:53:59
+39.989621,116.318684,0,108,39744.3292129630,2008-10-23,07:54:04
+39.989554,116.318622,0,271,39744.3292245370,2008-10-23,07:54:05
+39.989479,116.318574,0,124,39744.3292361111,2008-10-23,07:54:06
+39.989448,116.318553,0,254,39744.3292592593,2008-10-23,07:54:08
+39.989393,116.318520,0,133,39744.3292708333,2008-10-23,07:54:09
+39.989367,116.318499,0,228,39744.3293287037,2008-10-23,07:54:14
+39.989286,116.318459,0,256,39744.3293518519,2008-10-23,07:54:16
+39.989264,116.318450,0,164,39744.3293634259,2008-10-23,07:54:17
+39.989223,116.318415,0,288,39744.3293750000,2008-10-23,07:54:18
+39.989208,116.318399,0,150,39744.3293865741,2008-10-23,07:54:19
+39.989151,116.318335,0,194,39744.3294097222,2008-10-23,07:54:21
+39.989129,116.318304,0,291,39744.3294328704,2008-10-23,07:54:23
+39.989070,116.318259,0,137,39744.3294907407,2008-10-23,07:54:28
+39.989009,116.318229,0,176,39744.3295138889,2008-10-23,07:54:30
+39.988944,116.318183,0,192,39744.3295717593,2008-10-23,07:54:35
+39.988869,116.318136,0,265,39744.3295949074,2008-10-23,07:54:37
+39.988826,116.318116,0,140,39744.3296180556,2008-10-23,07:54:39
+39.988753,116.318090,0,231,39744.3296412037,2008-10-23,07:54:41
+39.988708,116.318082,0,274,39744.3296527778,2008-10-23,07:54:42
+39.988622,116.318055,0,209,39744.3296643519,2008-10-23,07:54:43
+39.988550,116.318043,0,170,39744.3297222222,2008-10-23,07:54:48
+39.988466,116.318000,0,116,39744.3297800926,2008-10-23,07:54:53
+39.988432,116.317976,0,230,39744.3298032407,2008-10-23,07:54:55
+39.988371,116.317915,0,197,39744.3298263889,2008-10-23,07:54:57
+39.988356,116.317890,0,282,39744.3298495370,2008-10-23,07:54:59
+39.988316,116.317802,0,238,39744.3298726852,2008-10-23,07:55:01
+39.988295,116.317751,0,180,39744.3298958333,2008-10-23,07:55:03
+39.988296,116.317713,0,257,39744.3299537037,2008-10-23,07:55:08
+39.988290,116.317690,0,278,39744.3300115741,2008-10-23,07:55:13
+39.988288,116.317645,0,120,39744.3300694444,2008-10-23,07:55:18
+39.988283,116.317602,0,181,39744.3300810185,2008-10-23,07:55:19
+39.988284,116.317577,0,118,39744.3300925926,2008-10-23,07:55:20
+39.988276,116.317547,0,253,39744.3301504630,2008-10-23,07:55:25
+39.988262,116.317465,0,177,39744.3302083333,2008-10-23,07:55:30
+39.988244,116.317386,0,171,39744.3302199074,2008-10-23,07:55:31
+39.988235,116.317305,0,173,39744.3302430556,2008-10-23,07:55:33
+39.988224,116.317236,0,176,39744.3302662037,2008-10-23,07:55:35
+39.988215,116.317181,0,166,39744.3303240741,2008-10-23,07:55:40
+39.988198,116.317114,0,285,39744.3303472222,2008-10-23,07:55:42
+39.988186,116.317021,0,181,39744.3303703704,2008-10-23,07:55:44
+39.988177,116.316971,0,125,39744.3303935185,2008-10-23,07:55:46
+39.988167,116.316921,0,266,39744.3304166667,2008-10-23,07:55:48
+39.988165,116.316884,0,266,39744.3304282407,2008-10-23,07:55:49
+39.988166,116.316836,0,264,39744.3304861111,2008-10-23,07:55:54
+39.988173,116.316797,0,196,39744.3305092593,2008-10-23,07:55:56
+39.988189,116.316723,0,211,39744.3305324074,2008-10-23,07:55:58
+39.988185,116.316634,0,271,39744.3305902778,2008-10-23,07:56:03
+39.988180,116.316604,0,188,39744.3306018518,2008-10-23,07:56:04
+39.988187,116.316510,0,129,39744.3306134259,2008-10-23,07:56:05
+39.988173,116.316434,0,133,39744.3306712963,2008-10-23,07:56:10
+39.988169,116.316406,0,125,39744.3306828704,2008-10-23,07:56:11
+39.988166,116.316385,0,187,39744.3306944444,2008-10-23,07:56:12
+39.988146,116.316293,0,182,39744.3307523148,2008-10-23,07:56:17
+39.988110,116.316217,0,152,39744.3308101852,2008-10-23,07:56:22
+39.988077,116.316141,0,203,39744.3308333333,2008-10-23,07:56:24
+39.988056,116.316099,0,266,39744.3308449074,2008-10-23,07:56:25
+39.988045,116.316078,0,242,39744.3308680556,2008-10-23,07:56:27
+39.988014,116.315995,0,242,39744.3308912037,2008-10-23,07:56:29
+39.987996,116.315953,0,124,39744.3309143518,2008-10-23,07:56:31
+39.987953,116.315889,0,213,39744.3309375000,2008-10-23,07:56:33
+39.987898,116.315820,0,121,39744.3309953704,2008-10-23,07:56:38
+39.987833,116.315766,0,126,39744.3310532407,2008-10-23,07:56:43
+39.987792,116.315739,0,275,39744.3310648148,2008-10-23,07:56:44
+39.987719,116.315694,0,232,39744.3310763889,2008-10-23,07:56:45
+39.987679,116.315659,0,190,39744.3310879630,2008-10-23,07:56:46
+39.987655,116.315648,0,110,39744.3311458333,2008-10-23,07:56:51
+39.987577,116.315601,0,121,39744.3311574074,2008-10-23,07:56:52
+39.987519,116.315577,0,251,39744.3311805556,2008-10-23,07:56:54
+39.987486,116.315559,0,237,39744.3311921296,2008-10-23,07:56:55
+39.987445,116.315530,0,262,39744.3312152778,2008-10-23,07:56:57
+39.987383,116.315492,0,144,39744.3312384259,2008-10-23,07:56:59
+39.987333,116.315439,0,199,39744.3312962963,2008-10-23,07:57:04
+39.987310,116.315416,0,143,39744.3313194444,2008-10-23,07:57:06
+39.987272,116.315359,0,295,39744.3313773148,2008-10-23,07:57:11
+39.987230,116.315314,0,114,39744.3314351852,2008-10-23,07:57:16
+39.987178,116.315237,0,170,39744.3314583333,2008-10-23,07:57:18
+39.987152,116.315162,0,105,39744.3315162037,2008-10-23,07:57:23
+39.987130,116.315066,0,148,39744.3315277778,2008-10-23,07:57:24
+39.987135,116.314995,0,250,39744.3315509259,2008-10-23,07:57:26
+39.987136,116.314967,0,230,39744.3315740741,2008-10-23,07:57:28
+39.987152,116.314934,0,257,39744.3315972222,2008-10-23,07:57:30
+39.987171,116.314899,0,177,39744.3316550926,2008-10-23,07:57:35
+39.987220,116.314821,0,286,39744.3316666667,2008-10-23,07:57:36
+39.987290,116.314750,0,294,39744.3317245370,2008-10-23,07:57:41
+39.987337,116.314700,0,111,39744.3317361111,2008-10-23,07:57:42
+39.987372,116.314650,0,230,39744.3317592593,2008-10-23,07:57:44
+39.987393,116.314622,0,148,39744.3317708333,2008-10-23,07:57:45
+39.987444,116.314547,0,249,39744.3317939815,2008-10-23,07:57:47
+39.987472,116.314514,0,205,39744.3318518518,2008-10-23,07:57:52
+39.987497,116.314490,0,128,39744.3318750000,2008-10-23,07:57:54
+39.987531,116.314447,0,287,39744.3319328704,2008-10-23,07:57:59
+39.987557,116.314402,0,228,39744.3319560185,2008-10-23,07:58:01
+39.987594,116.314345,0,204,39744.3319791667,2008-10-23,07:58:03
+39.987610,116.314316,0,172,39744.3320023148,2008-10-23,07:58:05
+39.987620,116.314295,0,194,39744.3320138889,2008-10-23,07:58:06
+39.987639,116.314234,0,104,39744.3320717593,2008-10-23,07:58:11
+39.987654,116.314184,0,288,39744.3321296296,2008-10-23,07:58:16
+39.987659,116.314160,0,122,39744.3321527778,2008-10-23,07:58:18
+39.987666,116.314124,0,171,39744.3322106482,2008-10-23,07:58:23
+39.987666,116.314077,0,131,39744.3322337963,2008-10-23,07:58:25
+39.987666,116.314052,0,127,39744.3322916667,2008-10-23,07:58:30
+39.987664,116.314021,0,103,39744.3323032407,2008-10-23,07:58:31
+39.987656,116.313981,0,267,39744.3323611111,2008-10-23,07:58:36
+39.987640,116.313933,0,132,39744.3324189815,2008-10-23,07:58:41
+39.987628,116.313891,0,219,39744.3324305556,2008-10-23,07:58:42
+39.987624,116.313871,0,122,39744.3324884259,2008-10-23,07:58:47
+39.987617,116.313830,0,254,39744.3325000000,2008-10-23,07:58:48
+39.987616,116.313795,0,232,39744.3325231482,2008-10-23,07:58:50
+39.987601,116.313702,0,118,39744.3325347222,2008-10-23,07:58:51
+39.987595,116.313668,0,143,39744.3325925926,2008-10-23,07:58:56
+39.987588,116.313583,0,186,39744.3326041667,2008-10-23,07:58:57
+39.987588,116.313512,0,142,39744.3326620370,2008-10-23,07:59:02
+39.987582,116.313447,0,133,39744.3327199074,2008-10-23,07:59:07
+39.987572,116.313409,0,225,39744.3327777778,2008-10-23,07:59:12
+39.987548,116.313321,0,268,39744.3327893519,2008-10-23,07:59:13
+39.987542,116.313300,0,201,39744.3328125000,2008-10-23,07:59:15
+39.987534,116.313262,0,258,39744.3328240741,2008-10-23,07:59:16
+39.987537,116.313232,0,131,39744.3328356482,2008-10-23,07:59:17
+39.987538,116.313191,0,172,39744.3328472222,2008-10-23,07:59:18
+39.987557,116.313112,0,263,39744.3329050926,2008-10-23,07:59:23
+39.987579,116.313057,0,295,39744.3329166667,2008-10-23,07:59:24
+39.987602,116.313001,0,182,39744.3329398148,2008-10-23,07:59:26
+39.987619,116.312948,0,268,39744.3329513889,2008-10-23,07:59:27
+39.987643,116.312898,0,196,39744.3329745370,2008-10-23,07:59:29
+39.987652,116.312814,0,265,39744.3329861111,2008-10-23,07:59:30
+39.987651,116.312750,0,128,39744.3330092593,2008-10-23,07:59:32
+39.987645,116.312722,0,272,39744.3330208333,2008-10-23,07:59:33
+39.987632,116.312653,0,132,39744.3330787037,2008-10-23,07:59:38
+39.987621,116.312605,0,262,39744.3331365741,2008-10-23,07:59:43
+39.987582,116.312526,0,232,39744.3331944444,2008-10-23,07:59:48
+39.987571,116.312498,0,130,39744.3332060185,2008-10-23,07:59:49
+39.987537,116.312417,0,292,39744.3332638889,2008-10-23,07:59:54
+39.987497,116.312328,0,281,39744.3332870370,2008-10-23,07:59:56
+39.987467,116.312282,0,213,39744.3332986111,2008-10-23,07:59:57
+39.987446,116.312237,0,180,39744.3333217593,2008-10-23,07:59:59
+39.987413,116.312150,0,262,39744.3333333333,2008-10-23,08:00:00
+39.987404,116.312131,0,184,39744.3333912037,2008-10-23,08:00:05
+39.987393,116.312051,0,219,39744.3334143519,2008-10-23,08:00:07
+39.987370,116.311993,0,275,39744.3334259259,2008-10-23,08:00:08
+39.987316,116.311916,0,178,39744.3334490741,2008-10-23,08:00:10
+39.987303,116.311900,0,270,39744.3334606482,2008-10-23,08:00:11
+39.987269,116.311861,0,238,39744.3335185185,2008-10-23,08:00:16
+39.987242,116.311828,0,273,39744.3335416667,2008-10-23,08:00:18
+39.987216,116.311799,0,255,39744.3335995370,2008-10-23,08:00:23
+39.987186,116.311756,0,251,39744.3336226852,2008-10-23,08:00:25
+39.987165,116.311716,0,230,39744.3336805556,2008-10-23,08:00:30
+39.987151,116.311693,0,167,39744.3336921296,2008-10-23,08:00:31
+39.987096,116.311613,0,278,39744.3337037037,2008-10-23,08:00:32
+39.987077,116.311560,0,198,39744.3337152778,2008-10-23,08:00:33
+39.987068,116.311488,0,244,39744.3337384259,2008-10-23,08:00:35
+39.987059,116.311444,0,238,39744.3337962963,2008-10-23,08:00:40
+39.987061,116.311409,0,137,39744.3338078704,2008-10-23,08:00:41
+39.987071,116.311380,0,110,39744.3338657407,2008-10-23,08:00:46
+39.987097,116.311315,0,221,39744.3339236111,2008-10-23,08:00:51
+39.987135,116.311223,0,218,39744.3339351852,2008-10-23,08:00:52
+39.987154,116.311133,0,240,39744.3339583333,2008-10-23,08:00:54
+39.987175,116.311035,0,168,39744.3340162037,2008-10-23,08:00:59
+39.987191,116.310974,0,127,39744.3340277778,2008-10-23,08:01:00
+39.987194,116.310948,0,287,39744.3340856481,2008-10-23,08:01:05
+39.987192,116.310906,0,226,39744.3340972222,2008-10-23,08:01:06
+39.987200,116.310866,0,128,39744.3341203704,2008-10-23,08:01:08
+39.987207,116.310826,0,133,39744.3341319444,2008-10-23,08:01:09
+39.987222,116.310739,0,136,39744.3341898148,2008-10-23,08:01:14
+39.987240,116.310706,0,167,39744.3342013889,2008-10-23,08:01:15
+39.987270,116.310649,0,156,39744.3342245370,2008-10-23,08:01:17
+39.987281,116.310605,0,161,39744.3342361111,2008-10-23,08:01:18
+39.987288,116.310573,0,249,39744.3342939815,2008-10-23,08:01:23
+39.987301,116.310513,0,173,39744.3343171296,2008-10-23,08:01:25
+39.987300,116.310465,0,292,39744.3343402778,2008-10-23,08:01:27
+39.987315,116.310415,0,249,39744.3343518519,2008-10-23,08:01:28
+39.987337,116.310347,0,254,39744.3344097222,2008-10-23,08:01:33
+39.987346,116.310308,0,154,39744.3344675926,2008-10-23,08:01:38
+39.987342,116.310240,0,188,39744.3344907407,2008-10-23,08:01:40
+39.987341,116.310218,0,224,39744.3345023148,2008-10-23,08:01:41
+39.987350,116.310161,0,138,39744.3345601852,2008-10-23,08:01:46
+39.987371,116.310067,0,246,39744.3345833333,2008-10-23,08:01:48
+39.987382,116.310032,0,185,39744.3345949074,2008-10-23,08:01:49
+39.987398,116.309983,0,178,39744.3346180556,2008-10-23,08:01:51
+39.987433,116.309893,0,253,39744.3346412037,2008-10-23,08:01:53
+39.987454,116.309837,0,244,39744.3346527778,2008-10-23,08:01:54
+39.987476,116.309798,0,290,39744.3347106481,2008-10-23,08:01:59
+39.987496,116.309709,0,258,39744.3347685185,2008-10-23,08:02:04
+39.987512,116.309612,0,111,39744.3347800926,2008-10-23,08:02:05
+39.987518,116.309558,0,101,39744.3348379630,2008-10-23,08:02:10
+39.987524,116.309525,0,187,39744.3348611111,2008-10-23,08:02:12
+39.987538,116.309464,0,219,39744.3348842593,2008-10-23,08:02:14
+39.987540,116.309428,0,173,39744.3348958333,2008-10-23,08:02:15
+39.987554,116.309375,0,222,39744.3349189815,2008-10-23,08:02:17
+39.987570,116.309308,0,131,39744.3349768519,2008-10-23,08:02:22
+39.987580,116.309255,0,172,39744.3350000000,2008-10-23,08:02:24
+39.987584,116.309213,0,286,39744.3350115741,2008-10-23,08:02:25
+39.987588,116.309115,0,146,39744.3350694444,2008-10-23,08:02:30
+39.987587,116.309023,0,124,39744.3350810185,2008-10-23,08:02:31
+39.987588,116.308959,0,223,39744.3351041667,2008-10-23,08:02:33
+39.987595,116.308863,0,205,39744.3351273148,2008-10-23,08:02:35
+39.987600,116.308807,0,181,39744.3351504630,2008-10-23,08:02:37
+39.987617,116.308750,0,248,39744.3352083333,2008-10-23,08:02:42
+39.987629,116.308682,0,199,39744.3352199074,2008-10-23,08:02:43
+39.987649,116.308634,0,228,39744.3352777778,2008-10-23,08:02:48
+39.987674,116.308550,0,146,39744.3353009259,2008-10-23,08:02:50
+39.987687,116.308469,0,189,39744.3353125000,2008-10-23,08:02:51
+39.987689,116.308432,0,290,39744.3353703704,2008-10-23,08:02:56
+39.987705,116.308358,0,202,39744.3354282407,2008-10-23,08:03:01
+39.987713,116.308298,0,271,39744.3354398148,2008-10-23,08:03:02
+39.987725,116.308269,0,261,39744.3354513889,2008-10-23,08:03:03
+39.987737,116.308231,0,130,39744.3354629630,2008-10-23,08:03:04
+39.987759,116.308188,0,244,39744.3354745370,2008-10-23,08:03:05
+39.987784,116.308139,0,196,39744.3354976852,2008-10-23,08:03:07
+39.987801,116.308100,0,142,39744.3355092593,2008-10-23,08:03:08
+39.987819,116.308035,0,225,39744.3355324074,2008-10-23,08:03:10
+39.987831,116.307971,0,178,39744.3355902778,2008-10-23,08:03:15
+39.987849,116.307914,0,118,39744.3356018519,2008-10-23,08:03:16
+39.987872,116.307853,0,200,39744.3356250000,2008-10-23,08:03:18
+39.987901,116.307775,0,280,39744.3356481481,2008-10-23,08:03:20
+39.987924,116.307700,0,138,39744.3357060185,2008-10-23,08:03:25
+39.987931,116.307667,0,128,39744.3357291667,2008-10-23,08:03:27
+39.987938,116.307604,0,227,39744.3357407407,2008-10-23,08:03:28
+39.987957,116.307541,0,276,39744.3357638889,2008-10-23,08:03:30
+39.987978,116.307498,0,181,39744.3357754630,2008-10-23,08:03:31
+39.987994,116.307477,0,208,39744.3358333333,2008-10-23,08:03:36
+39.988025,116.307435,0,109,39744.3358449074,2008-10-23,08:03:37
+39.988041,116.307408,0,205,39744.3359027778,2008-10-23,08:03:42
+39.988091,116.307346,0,243,39744.3359143519,2008-10-23,08:03:43
+39.988109,116.307314,0,279,39744.3359259259,2008-10-23,08:03:44
+39.988133,116.307282,0,206,39744.3359837963,2008-10-23,08:03:49
+39.988172,116.307225,0,240,39744.3359953704,2008-10-23,08:03:50
+39.988210,116.307147,0,134,39744.3360532407,2008-10-23,08:03:55
+39.988239,116.307075,0,200,39744.3360648148,2008-10-23,08:03:56
+39.988274,116.307014,0,284,39744.3361226852,2008-10-23,08:04:01
+39.988303,116.306970,0,264,39744.3361342593,2008-10-23,08:04:02
+39.988344,116.306914,0,142,39744.3361458333,2008-10-23,08:04:03
+39.988369,116.306853,0,241,39744.3361574074,2008-10-23,08:04:04
+39.988382,116.306828,0,137,39744.3361689815,2008-10-23,08:04:05
+39.988424,116.306749,0,258,39744.3361921296,2008-10-23,08:04:07
+39.988453,116.306707,0,217,39744.3362152778,2008-10-23,08:04:09
+39.988492,116.306652,0,270,39744.3362268519,2008-10-23,08:04:10
+39.988512,116.306621,0,215,39744.3362500000,2008-10-23,08:04:12
+39.988540,116.306547,0,124,39744.3363078704,2008-10-23,08:04:17
+39.988577,116.306482,0,139,39744.3363657407,2008-10-23,08:04:22
+39.988588,116.306448,0,230,39744.3364236111,2008-10-23,08:04:27
+39.988601,116.306390,0,260,39744.3364814815,2008-10-23,08:04:32
+39.988621,116.306295,0,173,39744.3365393519,2008-10-23,08:04:37
+39.988628,116.306236,0,261,39744.3365972222,2008-10-23,08:04:42
+39.988624,116.306193,0,252,39744.3366550926,2008-10-23,08:04:47
+39.988631,116.306114,0,186,39744.3367129630,2008-10-23,08:04:52
+39.988640,116.306076,0,169,39744.3367361111,2008-10-23,08:04:54
+39.988667,116.305998,0,251,39744.3367476852,2008-10-23,08:04:55
+39.988675,116.305934,0,276,39744.3368055556,2008-10-23,08:05:00
+39.988683,116.305907,0,159,39744.3368634259,2008-10-23,08:05:05
+39.988695,116.305884,0,205,39744.3368750000,2008-10-23,08:05:06
+39.988728,116.305800,0,250,39744.3369328704,2008-10-23,08:05:11
+39.988736,116.305735,0,276,39744.3369907407,2008-10-23,08:05:16
+39.988740,116.305648,0,186,39744.3370023148,2008-10-23,08:05:17
+39.988760,116.305576,0,225,39744.3370138889,2008-10-23,08:05:18
+39.988773,116.305507,0,193,39744.3370717593,2008-10-23,08:05:23
+39.988785,116.305476,0,220,39744.3371296296,2008-10-23,08:05:28
+39.988812,116.305407,0,105,39744.3371527778,2008-10-23,08:05:30
+39.988835,116.305348,0,122,39744.3372106481,2008-10-23,08:05:35
+39.988861,116.305261,0,200,39744.3372685185,2008-10-23,08:05:40
+39.988879,116.305212,0,253,39744.3373263889,2008-10-23,08:05:45
+39.988900,116.305155,0,239,39744.3373842593,2008-10-23,08:05:50
+39.988919,116.305059,0,161,39744.3374421296,2008-10-23,08:05:55
+39.988953,116.304993,0,203,39744.3375000000,2008-10-23,08:06:00
+39.988969,116.304961,0,152,39744.3375115741,2008-10-23,08:06:01
+39.988993,116.304895,0,279,39744.3375694444,2008-10-23,08:06:06
+39.989003,116.304829,0,172,39744.3375925926,2008-10-23,08:06:08
+39.988998,116.304773,0,164,39744.3376504630,2008-10-23,08:06:13
+39.988971,116.304677,0,193,39744.3376736111,2008-10-23,08:06:15
+39.988955,116.304622,0,156,39744.3376851852,2008-10-23,08:06:16
+39.988919,116.304555,0,233,39744.3376967593,2008-10-23,08:06:17
+39.988884,116.304498,0,180,39744.3377083333,2008-10-23,08:06:18
+39.988839,116.304431,0,201,39744.3377199074,2008-10-23,08:06:19
+39.988824,116.304410,0,269,39744.3377777778,2008-10-23,08:06:24
+39.988760,116.304345,0,192,39744.3378356481,2008-10-23,08:06:29
+39.988730,116.304309,0,199,39744.3378935185,2008-10-23,08:06:34
+39.988704,116.304276,0,157,39744.3379166667,2008-10-23,08:06:36
+39.988663,116.304212,0,169,39744.3379745370,2008-10-23,08:06:41
@@ -0,0 +1,506 @@
+Geolife trajectory
+WGS 84
+Altitude is in Feet
+Reserved 3
+0,2,255,My Track,0,0,2,8421376
+0
+40.000000,116.330000,0,255,39744.5368518519,2008-10-23,12:53:04
+40.000033,116.330010,0,156,39744.5368750000,2008-10-23,12:53:06
+40.000053,116.330018,0,273,39744.5369328704,2008-10-23,12:53:11
+40.000108,116.330040,0,282,39744.5369560185,2008-10-23,12:53:13
+40.000135,116.330048,0,148,39744.5370138889,2008-10-23,12:53:18
+40.000210,116.330081,0,185,39744.5370370370,2008-10-23,12:53:20
+40.000303,116.330115,0,155,39744.5370601852,2008-10-23,12:53:22
+40.000365,116.330133,0,285,39744.5371180556,2008-10-23,12:53:27
+40.000428,116.330152,0,106,39744.5371412037,2008-10-23,12:53:29
+40.000516,116.330160,0,157,39744.5371643519,2008-10-23,12:53:31
+40.000567,116.330158,0,274,39744.5371759259,2008-10-23,12:53:32
+40.000637,116.330152,0,167,39744.5371990741,2008-10-23,12:53:34
+40.000733,116.330144,0,299,39744.5372106481,2008-10-23,12:53:35
+40.000762,116.330136,0,210,39744.5372222222,2008-10-23,12:53:36
+40.000857,116.330110,0,238,39744.5372800926,2008-10-23,12:53:41
+40.000925,116.330089,0,150,39744.5372916667,2008-10-23,12:53:42
+40.000954,116.330076,0,240,39744.5373032407,2008-10-23,12:53:43
+40.001012,116.330057,0,276,39744.5373263889,2008-10-23,12:53:45
+40.001045,116.330045,0,259,39744.5373495370,2008-10-23,12:53:47
+40.001100,116.330021,0,129,39744.5373726852,2008-10-23,12:53:49
+40.001136,116.330005,0,286,39744.5373958333,2008-10-23,12:53:51
+40.001211,116.329950,0,188,39744.5374537037,2008-10-23,12:53:56
+40.001234,116.329934,0,253,39744.5374768519,2008-10-23,12:53:58
+40.001290,116.329891,0,176,39744.5375347222,2008-10-23,12:54:03
+40.001361,116.329839,0,250,39744.5375925926,2008-10-23,12:54:08
+40.001397,116.329785,0,255,39744.5376041667,2008-10-23,12:54:09
+40.001414,116.329760,0,239,39744.5376620370,2008-10-23,12:54:14
+40.001429,116.329729,0,168,39744.5376736111,2008-10-23,12:54:15
+40.001454,116.329662,0,145,39744.5377314815,2008-10-23,12:54:20
+40.001468,116.329611,0,207,39744.5377430556,2008-10-23,12:54:21
+40.001497,116.329525,0,268,39744.5377662037,2008-10-23,12:54:23
+40.001519,116.329451,0,262,39744.5377893519,2008-10-23,12:54:25
+40.001544,116.329378,0,266,39744.5378472222,2008-10-23,12:54:30
+40.001572,116.329293,0,186,39744.5378587963,2008-10-23,12:54:31
+40.001603,116.329242,0,193,39744.5379166667,2008-10-23,12:54:36
+40.001618,116.329212,0,169,39744.5379398148,2008-10-23,12:54:38
+40.001641,116.329167,0,227,39744.5379976852,2008-10-23,12:54:43
+40.001652,116.329147,0,103,39744.5380208333,2008-10-23,12:54:45
+40.001678,116.329093,0,163,39744.5380439815,2008-10-23,12:54:47
+40.001691,116.329060,0,183,39744.5380671296,2008-10-23,12:54:49
+40.001711,116.329026,0,192,39744.5380902778,2008-10-23,12:54:51
+40.001722,116.329008,0,104,39744.5381018519,2008-10-23,12:54:52
+40.001744,116.328974,0,175,39744.5381250000,2008-10-23,12:54:54
+40.001793,116.328900,0,111,39744.5381828704,2008-10-23,12:54:59
+40.001805,116.328878,0,219,39744.5381944444,2008-10-23,12:55:00
+40.001848,116.328800,0,195,39744.5382060185,2008-10-23,12:55:01
+40.001865,116.328772,0,253,39744.5382175926,2008-10-23,12:55:02
+40.001896,116.328705,0,177,39744.5382291667,2008-10-23,12:55:03
+40.001917,116.328635,0,287,39744.5382407407,2008-10-23,12:55:04
+40.001923,116.328614,0,184,39744.5382986111,2008-10-23,12:55:09
+40.001938,116.328558,0,145,39744.5383101852,2008-10-23,12:55:10
+40.001951,116.328495,0,170,39744.5383217593,2008-10-23,12:55:11
+40.001953,116.328466,0,257,39744.5383449074,2008-10-23,12:55:13
+40.001960,116.328392,0,216,39744.5383680556,2008-10-23,12:55:15
+40.001964,116.328363,0,112,39744.5383912037,2008-10-23,12:55:17
+40.001977,116.328264,0,108,39744.5384143519,2008-10-23,12:55:19
+40.001985,116.328241,0,174,39744.5384375000,2008-10-23,12:55:21
+40.001994,116.328182,0,251,39744.5384606481,2008-10-23,12:55:23
+40.002010,116.328110,0,236,39744.5385185185,2008-10-23,12:55:28
+40.002025,116.328026,0,114,39744.5385763889,2008-10-23,12:55:33
+40.002027,116.328004,0,175,39744.5386342593,2008-10-23,12:55:38
+40.002041,116.327910,0,215,39744.5386921296,2008-10-23,12:55:43
+40.002046,116.327883,0,228,39744.5387037037,2008-10-23,12:55:44
+40.002071,116.327837,0,192,39744.5387615741,2008-10-23,12:55:49
+40.002086,116.327821,0,280,39744.5387731481,2008-10-23,12:55:50
+40.002134,116.327754,0,241,39744.5387847222,2008-10-23,12:55:51
+40.002166,116.327726,0,284,39744.5388425926,2008-10-23,12:55:56
+40.002180,116.327711,0,239,39744.5389004630,2008-10-23,12:56:01
+40.002222,116.327665,0,228,39744.5389236111,2008-10-23,12:56:03
+40.002281,116.327622,0,220,39744.5389814815,2008-10-23,12:56:08
+40.002304,116.327605,0,159,39744.5390393519,2008-10-23,12:56:13
+40.002358,116.327529,0,264,39744.5390509259,2008-10-23,12:56:14
+40.002383,116.327503,0,209,39744.5391087963,2008-10-23,12:56:19
+40.002403,116.327479,0,173,39744.5391666667,2008-10-23,12:56:24
+40.002421,116.327460,0,102,39744.5391898148,2008-10-23,12:56:26
+40.002446,116.327431,0,229,39744.5392476852,2008-10-23,12:56:31
+40.002481,116.327403,0,190,39744.5392708333,2008-10-23,12:56:33
+40.002527,116.327361,0,178,39744.5393287037,2008-10-23,12:56:38
+40.002595,116.327311,0,148,39744.5393518519,2008-10-23,12:56:40
+40.002635,116.327287,0,151,39744.5394097222,2008-10-23,12:56:45
+40.002702,116.327254,0,259,39744.5394212963,2008-10-23,12:56:46
+40.002787,116.327218,0,173,39744.5394444444,2008-10-23,12:56:48
+40.002811,116.327201,0,159,39744.5395023148,2008-10-23,12:56:53
+40.002835,116.327179,0,190,39744.5395601852,2008-10-23,12:56:58
+40.002853,116.327159,0,196,39744.5395717593,2008-10-23,12:56:59
+40.002871,116.327135,0,170,39744.5395833333,2008-10-23,12:57:00
+40.002933,116.327059,0,282,39744.5395949074,2008-10-23,12:57:01
+40.002960,116.327012,0,181,39744.5396064815,2008-10-23,12:57:02
+40.002984,116.326980,0,184,39744.5396643519,2008-10-23,12:57:07
+40.003017,116.326914,0,127,39744.5396759259,2008-10-23,12:57:08
+40.003051,116.326853,0,251,39744.5396990741,2008-10-23,12:57:10
+40.003087,116.326780,0,245,39744.5397222222,2008-10-23,12:57:12
+40.003101,116.326750,0,290,39744.5397800926,2008-10-23,12:57:17
+40.003121,116.326682,0,264,39744.5398379630,2008-10-23,12:57:22
+40.003139,116.326630,0,159,39744.5398958333,2008-10-23,12:57:27
+40.003181,116.326573,0,188,39744.5399189815,2008-10-23,12:57:29
+40.003206,116.326533,0,196,39744.5399421296,2008-10-23,12:57:31
+40.003232,116.326494,0,152,39744.5399652778,2008-10-23,12:57:33
+40.003263,116.326452,0,258,39744.5400231481,2008-10-23,12:57:38
+40.003286,116.326423,0,209,39744.5400347222,2008-10-23,12:57:39
+40.003349,116.326370,0,183,39744.5400925926,2008-10-23,12:57:44
+40.003374,116.326350,0,214,39744.5401041667,2008-10-23,12:57:45
+40.003432,116.326312,0,134,39744.5401157407,2008-10-23,12:57:46
+40.003471,116.326278,0,182,39744.5401273148,2008-10-23,12:57:47
+40.003515,116.326236,0,259,39744.5401851852,2008-10-23,12:57:52
+40.003544,116.326222,0,267,39744.5402430556,2008-10-23,12:57:57
+40.003591,116.326194,0,229,39744.5402662037,2008-10-23,12:57:59
+40.003662,116.326146,0,268,39744.5403240741,2008-10-23,12:58:04
+40.003730,116.326107,0,256,39744.5403472222,2008-10-23,12:58:06
+40.003786,116.326082,0,109,39744.5404050926,2008-10-23,12:58:11
+40.003834,116.326066,0,243,39744.5404282407,2008-10-23,12:58:13
+40.003853,116.326058,0,133,39744.5404398148,2008-10-23,12:58:14
+40.003926,116.326030,0,210,39744.5404629630,2008-10-23,12:58:16
+40.003999,116.325992,0,196,39744.5404745370,2008-10-23,12:58:17
+40.004066,116.325978,0,152,39744.5404976852,2008-10-23,12:58:19
+40.004122,116.325969,0,157,39744.5405208333,2008-10-23,12:58:21
+40.004201,116.325949,0,112,39744.5405324074,2008-10-23,12:58:22
+40.004223,116.325944,0,257,39744.5405555556,2008-10-23,12:58:24
+40.004260,116.325932,0,191,39744.5405787037,2008-10-23,12:58:26
+40.004280,116.325928,0,159,39744.5405902778,2008-10-23,12:58:27
+40.004315,116.325916,0,160,39744.5406018519,2008-10-23,12:58:28
+40.004349,116.325909,0,154,39744.5406134259,2008-10-23,12:58:29
+40.004440,116.325890,0,289,39744.5406365741,2008-10-23,12:58:31
+40.004509,116.325890,0,216,39744.5406481481,2008-10-23,12:58:32
+40.004584,116.325882,0,290,39744.5406712963,2008-10-23,12:58:34
+40.004606,116.325884,0,283,39744.5406828704,2008-10-23,12:58:35
+40.004643,116.325893,0,173,39744.5406944444,2008-10-23,12:58:36
+40.004681,116.325912,0,101,39744.5407060185,2008-10-23,12:58:37
+40.004755,116.325929,0,252,39744.5407291667,2008-10-23,12:58:39
+40.004799,116.325936,0,202,39744.5407870370,2008-10-23,12:58:44
+40.004871,116.325960,0,300,39744.5408449074,2008-10-23,12:58:49
+40.004903,116.325973,0,199,39744.5408564815,2008-10-23,12:58:50
+40.004937,116.325984,0,298,39744.5408796296,2008-10-23,12:58:52
+40.004969,116.325993,0,246,39744.5408912037,2008-10-23,12:58:53
+40.005028,116.326010,0,293,39744.5409027778,2008-10-23,12:58:54
+40.005068,116.326028,0,255,39744.5409143519,2008-10-23,12:58:55
+40.005137,116.326070,0,210,39744.5409259259,2008-10-23,12:58:56
+40.005196,116.326100,0,130,39744.5409837963,2008-10-23,12:59:01
+40.005284,116.326130,0,251,39744.5410069444,2008-10-23,12:59:03
+40.005347,116.326137,0,208,39744.5410648148,2008-10-23,12:59:08
+40.005392,116.326143,0,172,39744.5411226852,2008-10-23,12:59:13
+40.005483,116.326154,0,260,39744.5411805556,2008-10-23,12:59:18
+40.005535,116.326141,0,155,39744.5411921296,2008-10-23,12:59:19
+40.005613,116.326125,0,116,39744.5412037037,2008-10-23,12:59:20
+40.005661,116.326101,0,215,39744.5412615741,2008-10-23,12:59:25
+40.005724,116.326061,0,164,39744.5412731481,2008-10-23,12:59:26
+40.005802,116.326015,0,236,39744.5412962963,2008-10-23,12:59:28
+40.005846,116.325992,0,191,39744.5413078704,2008-10-23,12:59:29
+40.005901,116.325951,0,289,39744.5413310185,2008-10-23,12:59:31
+40.005942,116.325920,0,295,39744.5413888889,2008-10-23,12:59:36
+40.005983,116.325894,0,272,39744.5414120370,2008-10-23,12:59:38
+40.006006,116.325870,0,149,39744.5414351852,2008-10-23,12:59:40
+40.006025,116.325841,0,111,39744.5414930556,2008-10-23,12:59:45
+40.006062,116.325767,0,169,39744.5415509259,2008-10-23,12:59:50
+40.006072,116.325735,0,270,39744.5415625000,2008-10-23,12:59:51
+40.006093,116.325678,0,231,39744.5415740741,2008-10-23,12:59:52
+40.006125,116.325621,0,287,39744.5416319444,2008-10-23,12:59:57
+40.006138,116.325591,0,109,39744.5416550926,2008-10-23,12:59:59
+40.006152,116.325538,0,166,39744.5416666667,2008-10-23,13:00:00
+40.006161,116.325489,0,128,39744.5417245370,2008-10-23,13:00:05
+40.006155,116.325417,0,161,39744.5417824074,2008-10-23,13:00:10
+40.006164,116.325370,0,276,39744.5418402778,2008-10-23,13:00:15
+40.006172,116.325277,0,209,39744.5418981481,2008-10-23,13:00:20
+40.006167,116.325202,0,157,39744.5419097222,2008-10-23,13:00:21
+40.006174,116.325112,0,183,39744.5419212963,2008-10-23,13:00:22
+40.006182,116.325058,0,170,39744.5419791667,2008-10-23,13:00:27
+40.006204,116.324979,0,252,39744.5419907407,2008-10-23,13:00:28
+40.006214,116.324940,0,170,39744.5420023148,2008-10-23,13:00:29
+40.006227,116.324900,0,298,39744.5420138889,2008-10-23,13:00:30
+40.006261,116.324817,0,141,39744.5420254630,2008-10-23,13:00:31
+40.006265,116.324795,0,229,39744.5420833333,2008-10-23,13:00:36
+40.006268,116.324774,0,175,39744.5421412037,2008-10-23,13:00:41
+40.006294,116.324682,0,228,39744.5421527778,2008-10-23,13:00:42
+40.006306,116.324628,0,192,39744.5422106481,2008-10-23,13:00:47
+40.006322,116.324589,0,120,39744.5422337963,2008-10-23,13:00:49
+40.006335,116.324557,0,195,39744.5422916667,2008-10-23,13:00:54
+40.006369,116.324467,0,213,39744.5423032407,2008-10-23,13:00:55
+40.006374,116.324416,0,223,39744.5423611111,2008-10-23,13:01:00
+40.006383,116.324333,0,249,39744.5423842593,2008-10-23,13:01:02
+40.006400,116.324269,0,257,39744.5424421296,2008-10-23,13:01:07
+40.006418,116.324222,0,208,39744.5425000000,2008-10-23,13:01:12
+40.006441,116.324128,0,211,39744.5425578704,2008-10-23,13:01:17
+40.006465,116.324068,0,180,39744.5425810185,2008-10-23,13:01:19
+40.006515,116.324011,0,114,39744.5426041667,2008-10-23,13:01:21
+40.006563,116.323965,0,155,39744.5426273148,2008-10-23,13:01:23
+40.006627,116.323902,0,129,39744.5426851852,2008-10-23,13:01:28
+40.006669,116.323858,0,127,39744.5427083333,2008-10-23,13:01:30
+40.006694,116.323819,0,280,39744.5427314815,2008-10-23,13:01:32
+40.006742,116.323735,0,260,39744.5427893518,2008-10-23,13:01:37
+40.006769,116.323695,0,249,39744.5428125000,2008-10-23,13:01:39
+40.006816,116.323608,0,181,39744.5428356481,2008-10-23,13:01:41
+40.006835,116.323587,0,111,39744.5428472222,2008-10-23,13:01:42
+40.006868,116.323556,0,166,39744.5428587963,2008-10-23,13:01:43
+40.006922,116.323485,0,174,39744.5429166667,2008-10-23,13:01:48
+40.006973,116.323434,0,154,39744.5429745370,2008-10-23,13:01:53
+40.007020,116.323399,0,142,39744.5429861111,2008-10-23,13:01:54
+40.007085,116.323353,0,270,39744.5429976852,2008-10-23,13:01:55
+40.007158,116.323295,0,239,39744.5430555556,2008-10-23,13:02:00
+40.007218,116.323244,0,204,39744.5430787037,2008-10-23,13:02:02
+40.007290,116.323182,0,103,39744.5430902778,2008-10-23,13:02:03
+40.007330,116.323141,0,230,39744.5431134259,2008-10-23,13:02:05
+40.007367,116.323100,0,171,39744.5431365741,2008-10-23,13:02:07
+40.007425,116.323026,0,282,39744.5431944444,2008-10-23,13:02:12
+40.007462,116.322974,0,283,39744.5432175926,2008-10-23,13:02:14
+40.007482,116.322943,0,231,39744.5432291667,2008-10-23,13:02:15
+40.007508,116.322864,0,182,39744.5432523148,2008-10-23,13:02:17
+40.007513,116.322840,0,253,39744.5432754630,2008-10-23,13:02:19
+40.007527,116.322799,0,136,39744.5432986111,2008-10-23,13:02:21
+40.007567,116.322730,0,227,39744.5433101852,2008-10-23,13:02:22
+40.007600,116.322688,0,132,39744.5433333333,2008-10-23,13:02:24
+40.007625,116.322643,0,148,39744.5433564815,2008-10-23,13:02:26
+40.007657,116.322557,0,268,39744.5433796296,2008-10-23,13:02:28
+40.007668,116.322477,0,146,39744.5434375000,2008-10-23,13:02:33
+40.007660,116.322416,0,273,39744.5434490741,2008-10-23,13:02:34
+40.007665,116.322340,0,195,39744.5435069444,2008-10-23,13:02:39
+40.007672,116.322298,0,207,39744.5435648148,2008-10-23,13:02:44
+40.007678,116.322209,0,111,39744.5436226852,2008-10-23,13:02:49
+40.007677,116.322164,0,152,39744.5436458333,2008-10-23,13:02:51
+40.007666,116.322112,0,116,39744.5436574074,2008-10-23,13:02:52
+40.007647,116.322047,0,236,39744.5436805556,2008-10-23,13:02:54
+40.007631,116.322018,0,299,39744.5436921296,2008-10-23,13:02:55
+40.007598,116.321971,0,270,39744.5437500000,2008-10-23,13:03:00
+40.007538,116.321900,0,182,39744.5437731482,2008-10-23,13:03:02
+40.007515,116.321876,0,279,39744.5437962963,2008-10-23,13:03:04
+40.007457,116.321817,0,281,39744.5438078704,2008-10-23,13:03:05
+40.007430,116.321795,0,282,39744.5438657407,2008-10-23,13:03:10
+40.007356,116.321741,0,276,39744.5438888889,2008-10-23,13:03:12
+40.007294,116.321705,0,285,39744.5439120370,2008-10-23,13:03:14
+40.007219,116.321659,0,173,39744.5439699074,2008-10-23,13:03:19
+40.007141,116.321621,0,257,39744.5439930556,2008-10-23,13:03:21
+40.007097,116.321603,0,286,39744.5440162037,2008-10-23,13:03:23
+40.007073,116.321597,0,194,39744.5440740741,2008-10-23,13:03:28
+40.006995,116.321584,0,170,39744.5440972222,2008-10-23,13:03:30
+40.006973,116.321578,0,285,39744.5441203704,2008-10-23,13:03:32
+40.006931,116.321567,0,105,39744.5441319444,2008-10-23,13:03:33
+40.006886,116.321554,0,295,39744.5441435185,2008-10-23,13:03:34
+40.006866,116.321548,0,243,39744.5442013889,2008-10-23,13:03:39
+40.006829,116.321545,0,274,39744.5442592593,2008-10-23,13:03:44
+40.006790,116.321543,0,243,39744.5443171296,2008-10-23,13:03:49
+40.006743,116.321541,0,174,39744.5443287037,2008-10-23,13:03:50
+40.006657,116.321535,0,111,39744.5443402778,2008-10-23,13:03:51
+40.006633,116.321538,0,136,39744.5443518519,2008-10-23,13:03:52
+40.006578,116.321562,0,124,39744.5443634259,2008-10-23,13:03:53
+40.006510,116.321570,0,159,39744.5444212963,2008-10-23,13:03:58
+40.006490,116.321577,0,121,39744.5444328704,2008-10-23,13:03:59
+40.006440,116.321591,0,266,39744.5444907407,2008-10-23,13:04:04
+40.006353,116.321596,0,226,39744.5445138889,2008-10-23,13:04:06
+40.006256,116.321592,0,210,39744.5445254630,2008-10-23,13:04:07
+40.006224,116.321586,0,104,39744.5445370370,2008-10-23,13:04:08
+40.006157,116.321566,0,211,39744.5445949074,2008-10-23,13:04:13
+40.006106,116.321546,0,277,39744.5446064815,2008-10-23,13:04:14
+40.006032,116.321522,0,248,39744.5446643519,2008-10-23,13:04:19
+40.005955,116.321500,0,300,39744.5446875000,2008-10-23,13:04:21
+40.005885,116.321459,0,157,39744.5447453704,2008-10-23,13:04:26
+40.005826,116.321422,0,203,39744.5447569444,2008-10-23,13:04:27
+40.005761,116.321378,0,250,39744.5447685185,2008-10-23,13:04:28
+40.005671,116.321339,0,260,39744.5447916667,2008-10-23,13:04:30
+40.005643,116.321327,0,155,39744.5448495370,2008-10-23,13:04:35
+40.005592,116.321293,0,215,39744.5449074074,2008-10-23,13:04:40
+40.005550,116.321254,0,160,39744.5449652778,2008-10-23,13:04:45
+40.005492,116.321187,0,226,39744.5449768519,2008-10-23,13:04:46
+40.005473,116.321167,0,249,39744.5449884259,2008-10-23,13:04:47
+40.005456,116.321152,0,235,39744.5450115741,2008-10-23,13:04:49
+40.005423,116.321122,0,239,39744.5450694444,2008-10-23,13:04:54
+40.005389,116.321088,0,105,39744.5450810185,2008-10-23,13:04:55
+40.005339,116.321025,0,224,39744.5450925926,2008-10-23,13:04:56
+40.005322,116.321003,0,122,39744.5451157407,2008-10-23,13:04:58
+40.005296,116.320956,0,211,39744.5451388889,2008-10-23,13:05:00
+40.005279,116.320934,0,102,39744.5451504630,2008-10-23,13:05:01
+40.005256,116.320896,0,257,39744.5452083333,2008-10-23,13:05:06
+40.005199,116.320837,0,171,39744.5452662037,2008-10-23,13:05:11
+40.005162,116.320782,0,100,39744.5453240741,2008-10-23,13:05:16
+40.005159,116.320762,0,109,39744.5453819444,2008-10-23,13:05:21
+40.005159,116.320715,0,150,39744.5454050926,2008-10-23,13:05:23
+40.005176,116.320627,0,198,39744.5454629630,2008-10-23,13:05:28
+40.005185,116.320553,0,135,39744.5454745370,2008-10-23,13:05:29
+40.005175,116.320469,0,282,39744.5454861111,2008-10-23,13:05:30
+40.005159,116.320374,0,209,39744.5455092593,2008-10-23,13:05:32
+40.005153,116.320334,0,133,39744.5455208333,2008-10-23,13:05:33
+40.005140,116.320242,0,287,39744.5455324074,2008-10-23,13:05:34
+40.005121,116.320170,0,278,39744.5455902778,2008-10-23,13:05:39
+40.005109,116.320145,0,257,39744.5456134259,2008-10-23,13:05:41
+40.005106,116.320118,0,194,39744.5456250000,2008-10-23,13:05:42
+40.005106,116.320084,0,246,39744.5456365741,2008-10-23,13:05:43
+40.005079,116.320011,0,105,39744.5456597222,2008-10-23,13:05:45
+40.005058,116.319922,0,239,39744.5457175926,2008-10-23,13:05:50
+40.005053,116.319889,0,132,39744.5457407407,2008-10-23,13:05:52
+40.005050,116.319859,0,223,39744.5457638889,2008-10-23,13:05:54
+40.005036,116.319816,0,192,39744.5458217593,2008-10-23,13:05:59
+40.005027,116.319761,0,242,39744.5458333333,2008-10-23,13:06:00
+40.005025,116.319701,0,256,39744.5458564815,2008-10-23,13:06:02
+40.005029,116.319612,0,105,39744.5458680556,2008-10-23,13:06:03
+40.005032,116.319591,0,122,39744.5458796296,2008-10-23,13:06:04
+40.005031,116.319563,0,204,39744.5458912037,2008-10-23,13:06:05
+40.005020,116.319487,0,192,39744.5459143519,2008-10-23,13:06:07
+40.005014,116.319467,0,104,39744.5459375000,2008-10-23,13:06:09
+40.005012,116.319386,0,212,39744.5459606481,2008-10-23,13:06:11
+40.005008,116.319356,0,182,39744.5460185185,2008-10-23,13:06:16
+40.004983,116.319267,0,100,39744.5460416667,2008-10-23,13:06:18
+40.004958,116.319194,0,230,39744.5460532407,2008-10-23,13:06:19
+40.004916,116.319106,0,188,39744.5460648148,2008-10-23,13:06:20
+40.004895,116.319085,0,274,39744.5461226852,2008-10-23,13:06:25
+40.004880,116.319070,0,178,39744.5461458333,2008-10-23,13:06:27
+40.004815,116.319019,0,166,39744.5461689815,2008-10-23,13:06:29
+40.004790,116.319004,0,222,39744.5461921296,2008-10-23,13:06:31
+40.004760,116.318979,0,261,39744.5462037037,2008-10-23,13:06:32
+40.004695,116.318911,0,165,39744.5462152778,2008-10-23,13:06:33
+40.004661,116.318869,0,214,39744.5462268519,2008-10-23,13:06:34
+40.004620,116.318838,0,296,39744.5462847222,2008-10-23,13:06:39
+40.004592,116.318814,0,268,39744.5463078704,2008-10-23,13:06:41
+40.004564,116.318797,0,109,39744.5463310185,2008-10-23,13:06:43
+40.004496,116.318777,0,117,39744.5463425926,2008-10-23,13:06:44
+40.004427,116.318748,0,203,39744.5463541667,2008-10-23,13:06:45
+40.004397,116.318726,0,126,39744.5463657407,2008-10-23,13:06:46
+40.004336,116.318663,0,212,39744.5463773148,2008-10-23,13:06:47
+40.004303,116.318637,0,143,39744.5464004630,2008-10-23,13:06:49
+40.004245,116.318581,0,151,39744.5464583333,2008-10-23,13:06:54
+40.004229,116.318558,0,234,39744.5465162037,2008-10-23,13:06:59
+40.004195,116.318485,0,101,39744.5465277778,2008-10-23,13:07:00
+40.004155,116.318406,0,138,39744.5465393519,2008-10-23,13:07:01
+40.004130,116.318320,0,247,39744.5465509259,2008-10-23,13:07:02
+40.004090,116.318248,0,122,39744.5465625000,2008-10-23,13:07:03
+40.004074,116.318207,0,117,39744.5465740741,2008-10-23,13:07:04
+40.004044,116.318151,0,189,39744.5465972222,2008-10-23,13:07:06
+40.004024,116.318107,0,264,39744.5466203704,2008-10-23,13:07:08
+40.004014,116.318032,0,114,39744.5466319444,2008-10-23,13:07:09
+40.004001,116.317985,0,251,39744.5466550926,2008-10-23,13:07:11
+40.003997,116.317960,0,155,39744.5467129630,2008-10-23,13:07:16
+40.003982,116.317912,0,122,39744.5467245370,2008-10-23,13:07:17
+40.003987,116.317851,0,134,39744.5467824074,2008-10-23,13:07:22
+40.004000,116.317766,0,113,39744.5468402778,2008-10-23,13:07:27
+40.004025,116.317705,0,139,39744.5468634259,2008-10-23,13:07:29
+40.004039,116.317672,0,151,39744.5469212963,2008-10-23,13:07:34
+40.004068,116.317642,0,116,39744.5469444444,2008-10-23,13:07:36
+40.004084,116.317623,0,148,39744.5469675926,2008-10-23,13:07:38
+40.004103,116.317583,0,222,39744.5469907407,2008-10-23,13:07:40
+40.004118,116.317509,0,140,39744.5470486111,2008-10-23,13:07:45
+40.004124,116.317442,0,208,39744.5470717593,2008-10-23,13:07:47
+40.004127,116.317413,0,192,39744.5471296296,2008-10-23,13:07:52
+40.004136,116.317371,0,196,39744.5471875000,2008-10-23,13:07:57
+40.004194,116.317290,0,139,39744.5471990741,2008-10-23,13:07:58
+40.004204,116.317265,0,252,39744.5472569444,2008-10-23,13:08:03
+40.004217,116.317194,0,139,39744.5472685185,2008-10-23,13:08:04
+40.004224,116.317139,0,128,39744.5472916667,2008-10-23,13:08:06
+40.004225,116.317099,0,255,39744.5473032407,2008-10-23,13:08:07
+40.004231,116.317056,0,155,39744.5473148148,2008-10-23,13:08:08
+40.004243,116.316998,0,107,39744.5473726852,2008-10-23,13:08:13
+40.004266,116.316932,0,297,39744.5473958333,2008-10-23,13:08:15
+40.004277,116.316886,0,290,39744.5474537037,2008-10-23,13:08:20
+40.004302,116.316835,0,258,39744.5475115741,2008-10-23,13:08:25
+40.004295,116.316765,0,197,39744.5475231481,2008-10-23,13:08:26
+40.004303,116.316675,0,207,39744.5475462963,2008-10-23,13:08:28
+40.004316,116.316601,0,292,39744.5476041667,2008-10-23,13:08:33
+40.004316,116.316508,0,171,39744.5476273148,2008-10-23,13:08:35
+40.004308,116.316433,0,197,39744.5476504630,2008-10-23,13:08:37
+40.004304,116.316375,0,172,39744.5476736111,2008-10-23,13:08:39
+40.004311,116.316299,0,251,39744.5477314815,2008-10-23,13:08:44
+40.004315,116.316252,0,255,39744.5477430556,2008-10-23,13:08:45
+40.004308,116.316179,0,132,39744.5477546296,2008-10-23,13:08:46
+40.004283,116.316134,0,231,39744.5478125000,2008-10-23,13:08:51
+40.004253,116.316065,0,207,39744.5478703704,2008-10-23,13:08:56
+40.004237,116.316035,0,102,39744.5478819444,2008-10-23,13:08:57
+40.004207,116.315978,0,174,39744.5479050926,2008-10-23,13:08:59
+40.004176,116.315930,0,114,39744.5479629630,2008-10-23,13:09:04
+40.004150,116.315865,0,188,39744.5479745370,2008-10-23,13:09:05
+40.004126,116.315799,0,158,39744.5480324074,2008-10-23,13:09:10
+40.004081,116.315735,0,101,39744.5480439815,2008-10-23,13:09:11
+40.004050,116.315663,0,150,39744.5481018519,2008-10-23,13:09:16
+40.004041,116.315637,0,209,39744.5481250000,2008-10-23,13:09:18
+40.004021,116.315573,0,109,39744.5481481481,2008-10-23,13:09:20
+40.004005,116.315533,0,286,39744.5481712963,2008-10-23,13:09:22
+40.003994,116.315502,0,197,39744.5482291667,2008-10-23,13:09:27
+40.003977,116.315466,0,253,39744.5482870370,2008-10-23,13:09:32
+40.003966,116.315445,0,280,39744.5482986111,2008-10-23,13:09:33
+40.003955,116.315419,0,246,39744.5483217593,2008-10-23,13:09:35
+40.003907,116.315343,0,197,39744.5483333333,2008-10-23,13:09:36
+40.003849,116.315291,0,251,39744.5483564815,2008-10-23,13:09:38
+40.003786,116.315252,0,173,39744.5483680556,2008-10-23,13:09:39
+40.003747,116.315228,0,179,39744.5483796296,2008-10-23,13:09:40
+40.003710,116.315207,0,166,39744.5484027778,2008-10-23,13:09:42
+40.003659,116.315176,0,169,39744.5484606481,2008-10-23,13:09:47
+40.003597,116.315137,0,278,39744.5484722222,2008-10-23,13:09:48
+40.003549,116.315109,0,298,39744.5484837963,2008-10-23,13:09:49
+40.003464,116.315080,0,174,39744.5485069444,2008-10-23,13:09:51
+40.003387,116.315057,0,270,39744.5485648148,2008-10-23,13:09:56
+40.003315,116.315024,0,164,39744.5485763889,2008-10-23,13:09:57
+40.003252,116.315002,0,279,39744.5486342593,2008-10-23,13:10:02
+40.003176,116.314974,0,140,39744.5486574074,2008-10-23,13:10:04
+40.003105,116.314951,0,223,39744.5486689815,2008-10-23,13:10:05
+40.003014,116.314911,0,162,39744.5487268519,2008-10-23,13:10:10
+40.002949,116.314887,0,272,39744.5487384259,2008-10-23,13:10:11
+40.002920,116.314880,0,245,39744.5487962963,2008-10-23,13:10:16
+40.002897,116.314882,0,199,39744.5488078704,2008-10-23,13:10:17
+40.002809,116.314903,0,210,39744.5488310185,2008-10-23,13:10:19
+40.002760,116.314923,0,223,39744.5488888889,2008-10-23,13:10:24
+40.002727,116.314944,0,150,39744.5489467593,2008-10-23,13:10:29
+40.002663,116.314990,0,195,39744.5490046296,2008-10-23,13:10:34
+40.002583,116.315042,0,109,39744.5490625000,2008-10-23,13:10:39
+40.002519,116.315076,0,102,39744.5491203704,2008-10-23,13:10:44
+40.002472,116.315098,0,144,39744.5491435185,2008-10-23,13:10:46
+40.002384,116.315134,0,118,39744.5491550926,2008-10-23,13:10:47
+40.002359,116.315144,0,150,39744.5491782407,2008-10-23,13:10:49
+40.002304,116.315188,0,122,39744.5491898148,2008-10-23,13:10:50
+40.002254,116.315228,0,185,39744.5492129630,2008-10-23,13:10:52
+40.002191,116.315278,0,294,39744.5492245370,2008-10-23,13:10:53
+40.002155,116.315314,0,165,39744.5492476852,2008-10-23,13:10:55
+40.002096,116.315369,0,125,39744.5492708333,2008-10-23,13:10:57
+40.002058,116.315425,0,300,39744.5493287037,2008-10-23,13:11:02
+40.002029,116.315461,0,231,39744.5493402778,2008-10-23,13:11:03
+40.001986,116.315499,0,157,39744.5493981481,2008-10-23,13:11:08
+40.001913,116.315536,0,255,39744.5494097222,2008-10-23,13:11:09
+40.001840,116.315598,0,167,39744.5494675926,2008-10-23,13:11:14
+40.001809,116.315626,0,205,39744.5494791667,2008-10-23,13:11:15
+40.001779,116.315657,0,259,39744.5495370370,2008-10-23,13:11:20
+40.001752,116.315676,0,171,39744.5495601852,2008-10-23,13:11:22
+40.001699,116.315719,0,252,39744.5495833333,2008-10-23,13:11:24
+40.001665,116.315756,0,273,39744.5496064815,2008-10-23,13:11:26
+40.001623,116.315800,0,227,39744.5496180556,2008-10-23,13:11:27
+40.001589,116.315859,0,268,39744.5496759259,2008-10-23,13:11:32
+40.001538,116.315931,0,120,39744.5496875000,2008-10-23,13:11:33
+40.001510,116.315978,0,254,39744.5497453704,2008-10-23,13:11:38
+40.001458,116.316057,0,162,39744.5497685185,2008-10-23,13:11:40
+40.001445,116.316116,0,138,39744.5497916667,2008-10-23,13:11:42
+40.001445,116.316186,0,113,39744.5498495370,2008-10-23,13:11:47
+40.001448,116.316205,0,120,39744.5498611111,2008-10-23,13:11:48
+40.001443,116.316260,0,235,39744.5499189815,2008-10-23,13:11:53
+40.001435,116.316324,0,192,39744.5499305556,2008-10-23,13:11:54
+40.001430,116.316349,0,125,39744.5499421296,2008-10-23,13:11:55
+40.001411,116.316423,0,128,39744.5499537037,2008-10-23,13:11:56
+40.001370,116.316509,0,270,39744.5499768518,2008-10-23,13:11:58
+40.001363,116.316549,0,147,39744.5500000000,2008-10-23,13:12:00
+40.001349,116.316609,0,252,39744.5500231481,2008-10-23,13:12:02
+40.001345,116.316641,0,144,39744.5500462963,2008-10-23,13:12:04
+40.001328,116.316719,0,257,39744.5501041667,2008-10-23,13:12:09
+40.001304,116.316795,0,183,39744.5501620370,2008-10-23,13:12:14
+40.001297,116.316840,0,136,39744.5501736111,2008-10-23,13:12:15
+40.001285,116.316900,0,277,39744.5501851852,2008-10-23,13:12:16
+40.001269,116.316994,0,223,39744.5502083333,2008-10-23,13:12:18
+40.001246,116.317075,0,299,39744.5502314815,2008-10-23,13:12:20
+40.001197,116.317155,0,268,39744.5502546296,2008-10-23,13:12:22
+40.001182,116.317202,0,296,39744.5503125000,2008-10-23,13:12:27
+40.001168,116.317231,0,121,39744.5503703704,2008-10-23,13:12:32
+40.001145,116.317289,0,203,39744.5503819444,2008-10-23,13:12:33
+40.001109,116.317381,0,230,39744.5504050926,2008-10-23,13:12:35
+40.001100,116.317405,0,292,39744.5504282407,2008-10-23,13:12:37
+40.001094,116.317433,0,225,39744.5504513889,2008-10-23,13:12:39
+40.001086,116.317479,0,231,39744.5504629630,2008-10-23,13:12:40
+40.001073,116.317512,0,143,39744.5505208333,2008-10-23,13:12:45
+40.001050,116.317560,0,141,39744.5505324074,2008-10-23,13:12:46
+40.001019,116.317606,0,145,39744.5505902778,2008-10-23,13:12:51
+40.000995,116.317639,0,266,39744.5506018518,2008-10-23,13:12:52
+40.000946,116.317720,0,146,39744.5506597222,2008-10-23,13:12:57
+40.000893,116.317782,0,103,39744.5506828704,2008-10-23,13:12:59
+40.000840,116.317846,0,269,39744.5506944444,2008-10-23,13:13:00
+40.000790,116.317901,0,133,39744.5507175926,2008-10-23,13:13:02
+40.000774,116.317914,0,275,39744.5507291667,2008-10-23,13:13:03
+40.000747,116.317939,0,207,39744.5507407407,2008-10-23,13:13:04
+40.000729,116.317953,0,219,39744.5507638889,2008-10-23,13:13:06
+40.000645,116.318002,0,119,39744.5508217593,2008-10-23,13:13:11
+40.000616,116.318015,0,131,39744.5508796296,2008-10-23,13:13:16
+40.000564,116.318025,0,112,39744.5509375000,2008-10-23,13:13:21
+40.000525,116.318025,0,168,39744.5509606482,2008-10-23,13:13:23
+40.000493,116.318032,0,234,39744.5510185185,2008-10-23,13:13:28
+40.000443,116.318046,0,175,39744.5510763889,2008-10-23,13:13:33
+40.000346,116.318057,0,239,39744.5510879630,2008-10-23,13:13:34
+40.000287,116.318074,0,109,39744.5510995370,2008-10-23,13:13:35
+40.000212,116.318124,0,227,39744.5511574074,2008-10-23,13:13:40
+40.000158,116.318158,0,278,39744.5511805556,2008-10-23,13:13:42
+40.000124,116.318191,0,120,39744.5511921296,2008-10-23,13:13:43
+40.000062,116.318227,0,196,39744.5512500000,2008-10-23,13:13:48
+40.000015,116.318254,0,149,39744.5512731482,2008-10-23,13:13:50
+39.999977,116.318274,0,269,39744.5512847222,2008-10-23,13:13:51
+39.999897,116.318328,0,300,39744.5513425926,2008-10-23,13:13:56
+39.999802,116.318347,0,247,39744.5514004630,2008-10-23,13:14:01
+39.999770,116.318360,0,238,39744.5514236111,2008-10-23,13:14:03
+39.999677,116.318385,0,168,39744.5514467593,2008-10-23,13:14:05
+39.999647,116.318399,0,144,39744.5514583333,2008-10-23,13:14:06
+39.999561,116.318413,0,228,39744.5514814815,2008-10-23,13:14:08
+39.999487,116.318421,0,154,39744.5514930556,2008-10-23,13:14:09
+39.999390,116.318444,0,269,39744.5515162037,2008-10-23,13:14:11
+39.999361,116.318461,0,297,39744.5515393519,2008-10-23,13:14:13
+39.999334,116.318488,0,178,39744.5515625000,2008-10-23,13:14:15
+39.999269,116.318560,0,182,39744.5516203704,2008-10-23,13:14:20
+39.999247,116.318649,0,218,39744.5516435185,2008-10-23,13:14:22
+39.999248,116.318687,0,257,39744.5517013889,2008-10-23,13:14:27
+39.999258,116.318742,0,265,39744.5517592593,2008-10-23,13:14:32
+39.999263,116.318771,0,139,39744.5517708333,2008-10-23,13:14:33
+39.999277,116.318850,0,230,39744.5517824074,2008-10-23,13:14:34
+39.999285,116.318919,0,220,39744.5518055556,2008-10-23,13:14:36
+39.999289,116.318950,0,276,39744.5518171296,2008-10-23,13:14:37
+39.999295,116.319033,0,112,39744.5518287037,2008-10-23,13:14:38
+39.999302,116.319082,0,134,39744.5518518519,2008-10-23,13:14:40
+39.999329,116.319147,0,296,39744.5518750000,2008-10-23,13:14:42
+39.999378,116.319228,0,249,39744.5518865741,2008-10-23,13:14:43
+39.999437,116.319281,0,245,39744.5519097222,2008-10-23,13:14:45
+39.999483,116.319330,0,127,39744.5519212963,2008-10-23,13:14:46
+39.999509,116.319350,0,114,39744.5519328704,2008-10-23,13:14:47
+39.999522,116.319369,0,209,39744.5519444444,2008-10-23,13:14:48
+39.999564,116.319438,0,279,39744.5519560185,2008-10-23,13:14:49
+39.999622,116.319520,0,282,39744.5519791667,2008-10-23,13:14:51
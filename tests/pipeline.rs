@@ -0,0 +1,484 @@
+//! End-to-end tests running the binary on GeoLife data.
+//!
+//! By default the tiny fixture set in `tests/fixtures/geolife/` is used. Set
+//! `GEOLIFE_DIR` to run the same invariants against a real GeoLife directory.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Number of points in the bundled fixture files
+const FIXTURE_POINTS: u64 = 1_200;
+
+/// Returns the data directory under test and whether it is the bundled fixture set.
+fn data_dir() -> (PathBuf, bool) {
+    match std::env::var_os("GEOLIFE_DIR") {
+        Some(dir) => (PathBuf::from(dir), false),
+        None => (
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/geolife"),
+            true,
+        ),
+    }
+}
+
+/// Runs the binary with the given arguments and returns its standard output.
+fn run(args: &[&str]) -> String {
+    run_in(&data_dir().0, args)
+}
+
+/// Runs the binary on the data in `dir` and returns its standard output.
+fn run_in(dir: &Path, args: &[&str]) -> String {
+    run_with_env(dir, args, &[])
+}
+
+/// Runs the binary on the data in `dir` with extra environment variables and returns its
+/// standard output.
+fn run_with_env(dir: &Path, args: &[&str], env: &[(&str, &str)]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_trajectory-rs"))
+        .args(args)
+        .env("GEOLIFE_DIR", dir)
+        .envs(env.iter().copied())
+        .output()
+        .expect("failed to run the binary");
+    assert!(
+        output.status.success(),
+        "binary failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).expect("stdout is not UTF-8")
+}
+
+/// Parses the `label: value unit` lines of the report into numbers.
+fn parse_report(stdout: &str) -> HashMap<String, f64> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let (label, value) = line.split_once(':')?;
+            let value = value.split_whitespace().next()?.replace(',', "");
+            Some((label.trim().to_lowercase(), value.parse().ok()?))
+        })
+        .collect()
+}
+
+#[test]
+fn test_pipeline_invariants() {
+    let report = parse_report(&run(&[]));
+
+    let total_points = report["total points"];
+    let simplified_points = report["simplified points"];
+    let original_size = report["original size"];
+
+    if data_dir().1 {
+        assert_eq!(total_points as u64, FIXTURE_POINTS);
+    }
+    assert!(simplified_points >= 2.0);
+    assert!(simplified_points <= total_points);
+    assert!(report["size after simplification"] < original_size);
+    assert!(report["serialized delta size"] < original_size);
+
+    // Delta encoding must pay off on GPS data, and the simplified output must
+    // be a small fraction of the text input.
+    assert!(report["ratio bytes delta vs non-delta"] < 100.0);
+    assert!(report["ratio bytes delta vs original"] < 10.0);
+    assert!(report["ratio points"] <= 100.0);
+}
+
+#[test]
+fn test_bench_formats_lists_every_backend() {
+    let stdout = run(&["bench-formats"]);
+
+    for format in ["proto", "proto-delta", "polyline", "binary"] {
+        assert!(
+            stdout.lines().any(|line| line.starts_with(&format!("{format} "))),
+            "missing {format} in:\n{stdout}"
+        );
+    }
+}
+
+#[test]
+fn test_compressed_input_matches_plain() {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let plain = parse_report(&run_in(&fixtures.join("geolife"), &[]));
+    let compressed = parse_report(&run_in(&fixtures.join("compressed"), &[]));
+
+    assert_eq!(compressed, plain);
+}
+
+#[test]
+fn test_encrypted_output_decrypts_to_plain() {
+    let (dir, _) = data_dir();
+    let output = tempfile::tempdir().unwrap();
+    let (plain, encrypted) = (output.path().join("plain"), output.path().join("encrypted"));
+    let key = run_in(&dir, &["keygen"]);
+    let env = [("TRAJECTORY_KEY", key.trim())];
+
+    run_in(&dir, &["split", "time", "binary", plain.to_str().unwrap()]);
+    run_with_env(&dir, &["split", "time", "binary", encrypted.to_str().unwrap()], &env);
+
+    let (plain, encrypted) = (plain.join("train.trjb"), encrypted.join("train.trjb"));
+    let decrypted = output.path().join("decrypted.trjb");
+    run_with_env(
+        &dir,
+        &["decrypt", encrypted.to_str().unwrap(), decrypted.to_str().unwrap()],
+        &env,
+    );
+
+    let plain = std::fs::read(plain).unwrap();
+    assert_ne!(std::fs::read(encrypted).unwrap(), plain);
+    assert_eq!(std::fs::read(decrypted).unwrap(), plain);
+}
+
+#[test]
+fn test_options_override_defaults() {
+    let (dir, _) = data_dir();
+    let output = tempfile::tempdir().unwrap();
+    let path = output.path().join("out.pb");
+    let default = parse_report(&run(&[]));
+    // --input takes precedence over GEOLIFE_DIR
+    let finer = parse_report(&run_in(
+        &output.path().join("missing"),
+        &[
+            "--input",
+            dir.to_str().unwrap(),
+            "--epsilon",
+            "500",
+            "--output",
+            path.to_str().unwrap(),
+        ],
+    ));
+
+    assert_eq!(finer["total points"], default["total points"]);
+    assert!(finer["simplified points"] >= default["simplified points"]);
+    assert_eq!(
+        std::fs::metadata(&path).unwrap().len() as f64,
+        finer["serialized delta size"]
+    );
+
+    let invalid = Command::new(env!("CARGO_BIN_EXE_trajectory-rs"))
+        .args(["--epsilon", "-1"])
+        .output()
+        .expect("failed to run the binary");
+    assert!(!invalid.status.success());
+}
+
+#[test]
+fn test_target_ratio() {
+    let report = parse_report(&run(&["--target-ratio", "0.1"]));
+    let ratio = report["simplified points"] / report["total points"];
+    assert!((ratio - 0.1).abs() <= 0.01, "kept {ratio}");
+    assert!(report["epsilon for a ratio of 0.1"] > 0.0);
+}
+
+#[test]
+fn test_gpx_input() {
+    let dir = tempfile::tempdir().unwrap();
+    let points: String = (0..5)
+        .map(|i| {
+            format!(
+                "<trkpt lat=\"39.9{i}\" lon=\"116.4\"><time>2020-01-01T00:00:0{i}Z</time></trkpt>"
+            )
+        })
+        .collect();
+    std::fs::write(
+        dir.path().join("track.gpx"),
+        format!("<gpx><trk><trkseg>{points}</trkseg></trk></gpx>"),
+    )
+    .unwrap();
+
+    let report = parse_report(&run_in(dir.path(), &[]));
+    assert_eq!(report["total points"], 5.0);
+}
+
+#[test]
+fn test_geojson_output() {
+    let output = tempfile::tempdir().unwrap();
+    let path = output.path().join("simplified.geojson");
+    let report = parse_report(&run(&["--geojson", path.to_str().unwrap()]));
+
+    let geojson: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(geojson["geometry"]["type"], "MultiLineString");
+    let points: usize = geojson["properties"]["timestamps"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|timestamps| timestamps.as_array().unwrap().len())
+        .sum();
+    assert_eq!(points as f64, report["simplified points"]);
+
+    let report = parse_report(&run(&["--merge", "--geojson", path.to_str().unwrap()]));
+    let geojson: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(geojson["geometry"]["type"], "LineString");
+    let timestamps = geojson["properties"]["timestamps"].as_array().unwrap();
+    assert_eq!(timestamps.len() as f64, report["simplified points"]);
+}
+
+#[test]
+fn test_explain_kept_points() {
+    let output = tempfile::tempdir().unwrap();
+    let path = output.path().join("reasons.json");
+    let report = parse_report(&run(&["--explain", path.to_str().unwrap()]));
+
+    let reasons: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    let trajectories = reasons["trajectories"].as_array().unwrap();
+    if data_dir().1 {
+        assert_eq!(trajectories.len(), 3);
+    }
+    let count = |member: &str| -> usize {
+        trajectories
+            .iter()
+            .map(|reasons| reasons[member].as_array().unwrap().len())
+            .sum()
+    };
+    assert_eq!(count("points") as f64, report["simplified points"]);
+    assert_eq!(count("keep") as f64, report["total points"]);
+    let points = trajectories[0]["points"].as_array().unwrap();
+    assert_eq!(points[0]["reason"], "endpoint");
+    assert!(points
+        .iter()
+        .any(|point| point["reason"] == "split" && point["depth"] == 1));
+}
+
+#[test]
+fn test_options_apply_to_each_file() {
+    let output = tempfile::tempdir().unwrap();
+    let config = output.path().join("config.toml");
+    std::fs::write(&config, "epsilon_m = 50\nmin_points = 40\n").unwrap();
+    let path = output.path().join("reasons.json");
+    let report = parse_report(&run(&[
+        "--config",
+        config.to_str().unwrap(),
+        "--explain",
+        path.to_str().unwrap(),
+    ]));
+
+    let reasons: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    let trajectories = reasons["trajectories"].as_array().unwrap();
+    // Every file keeps its minimum number of points, not the dataset as a whole
+    for reasons in trajectories {
+        let kept = reasons["points"].as_array().unwrap().len();
+        let points = reasons["keep"].as_array().unwrap().len();
+        assert!(kept >= points.min(40), "kept {kept} of {points}");
+    }
+    if data_dir().1 {
+        assert!(report["simplified points"] >= 120.0);
+    }
+    assert_eq!(report["epsilon"], 50.0);
+}
+
+#[test]
+fn test_batch_writes_one_file_per_trajectory() {
+    let output = tempfile::tempdir().unwrap();
+    let stdout = run(&[
+        "batch",
+        "binary",
+        output.path().to_str().unwrap(),
+        "--threads",
+        "simplify=2",
+    ]);
+    assert!(stdout.contains("simplify (2)"));
+
+    let files: Vec<PathBuf> = std::fs::read_dir(output.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    if data_dir().1 {
+        assert_eq!(files.len(), 3);
+    }
+    assert!(!files.is_empty());
+    assert!(files
+        .iter()
+        .all(|file| std::fs::metadata(file).unwrap().len() > 0));
+}
+
+#[test]
+fn test_dry_run_writes_nothing() {
+    let output = tempfile::tempdir().unwrap();
+    let path = output.path().join("out.pb");
+    let geojson = output.path().join("out.geojson");
+    let default = parse_report(&run(&[]));
+    let dry_run = parse_report(&run(&[
+        "--dry-run",
+        "--output",
+        path.to_str().unwrap(),
+        "--geojson",
+        geojson.to_str().unwrap(),
+    ]));
+
+    assert!(!path.exists());
+    assert!(!geojson.exists());
+    assert_eq!(
+        dry_run["serialized delta size"],
+        default["serialized delta size"]
+    );
+    assert_eq!(dry_run["simplified points"], default["simplified points"]);
+}
+
+#[test]
+fn test_per_file_collection() {
+    use prost::Message;
+    use trajectory_rs::collection::TrajectoryCollection;
+    use trajectory_rs::proto;
+
+    let output = tempfile::tempdir().unwrap();
+    let path = output.path().join("collection.pb");
+    let merged = parse_report(&run(&["--merge"]));
+    let report = parse_report(&run(&["--output", path.to_str().unwrap()]));
+    assert_eq!(report["total points"], merged["total points"]);
+
+    let message = proto::TrajectoryCollection::decode(std::fs::read(&path).unwrap().as_slice());
+    let collection = TrajectoryCollection::from_delta_proto(message.unwrap()).unwrap();
+    if data_dir().1 {
+        assert_eq!(collection.len(), 3);
+    }
+    assert_eq!(collection.points() as f64, report["simplified points"]);
+    for trajectory in &collection.trajectories {
+        assert!(trajectory
+            .timestamps
+            .windows(2)
+            .all(|pair| pair[0] <= pair[1]));
+    }
+}
+
+#[test]
+fn test_duplicate_file_warning() {
+    let dir = tempfile::tempdir().unwrap();
+    let fixture =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/geolife/20081023025304.plt");
+    std::fs::copy(&fixture, dir.path().join("a.plt")).unwrap();
+    std::fs::copy(&fixture, dir.path().join("b.plt")).unwrap();
+
+    let stdout = run_in(dir.path(), &[]);
+    let warnings: Vec<&str> = stdout
+        .lines()
+        .filter(|line| line.starts_with("Warning:"))
+        .collect();
+    assert_eq!(warnings.len(), 1, "{stdout}");
+    assert!(warnings[0].contains("b.plt holds the same points as"));
+}
+
+#[test]
+fn test_datum_conversion() {
+    let output = tempfile::tempdir().unwrap();
+    let wgs84 = output.path().join("wgs84.geojson");
+    let gcj02 = output.path().join("gcj02.geojson");
+    run(&["--geojson", wgs84.to_str().unwrap()]);
+    let stdout = run(&["--datum", "gcj02", "--geojson", gcj02.to_str().unwrap()]);
+    assert!(stdout.contains("Converting points from gcj02 to WGS 84"));
+
+    let first = |path: &Path| {
+        let geojson: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        let position = &geojson["geometry"]["coordinates"][0][0];
+        (position[0].as_f64().unwrap(), position[1].as_f64().unwrap())
+    };
+    // GeoLife is in Beijing, where GCJ-02 is shifted north-east of WGS 84
+    let ((wgs84_lon, wgs84_lat), (shifted_lon, shifted_lat)) = (first(&wgs84), first(&gcj02));
+    assert!(shifted_lon < wgs84_lon && shifted_lat < wgs84_lat);
+
+    let invalid = Command::new(env!("CARGO_BIN_EXE_trajectory-rs"))
+        .args(["--datum", "etrs89"])
+        .output()
+        .expect("failed to run the binary");
+    assert!(!invalid.status.success());
+}
+
+#[test]
+fn test_split_files_into_trips() {
+    let output = tempfile::tempdir().unwrap();
+    let path = output.path().join("trips.geojson");
+    let per_file = parse_report(&run(&[]));
+    // Every second is a gap: each point is its own trip
+    let stdout = run(&["--max-gap", "0", "--geojson", path.to_str().unwrap()]);
+    let report = parse_report(&stdout);
+    assert_eq!(report["total points"], per_file["total points"]);
+    assert_eq!(report["simplified points"], report["total points"]);
+
+    let geojson: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    let trips = geojson["geometry"]["coordinates"].as_array().unwrap();
+    assert!(stdout
+        .replace(',', "")
+        .contains(&format!("into {} trips", trips.len())));
+}
+
+#[test]
+fn test_generalize_preset() {
+    let output = tempfile::tempdir().unwrap();
+    let out = output.path().to_str().unwrap();
+    let stdout = run(&["generalize", "--preset", "web-z14", "--out", out]);
+    assert!(stdout.contains("for zoom 14 (0.5 px)"));
+    let files: Vec<PathBuf> = std::fs::read_dir(output.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert!(!files.is_empty());
+    assert!(files
+        .iter()
+        .all(|file| file.extension().unwrap() == "polyline"));
+
+    let overview = tempfile::tempdir().unwrap();
+    run(&[
+        "generalize",
+        "--preset",
+        "web-z6",
+        "--out",
+        overview.path().to_str().unwrap(),
+    ]);
+    // Every trajectory, with at least 3 points each
+    let overviews: Vec<PathBuf> = std::fs::read_dir(overview.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(overviews.len(), files.len());
+    for path in overviews {
+        let geojson = std::fs::read_to_string(path).unwrap();
+        assert!(geojson.matches("],[").count() >= 2);
+    }
+}
+
+#[test]
+fn test_store_thumbnails() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = dir.path().join("store");
+    let store = store.to_str().unwrap();
+    run(&["store", store, "put"]);
+    let list = run(&["store", store, "list"]);
+    let id = list.lines().next().unwrap();
+
+    let full = dir.path().join("full.polyline");
+    let preview = dir.path().join("preview.polyline");
+    for (command, path) in [("get", &full), ("thumbnail", &preview)] {
+        let path = path.to_str().unwrap();
+        run(&["store", store, command, id, "polyline", path]);
+    }
+    let (full, preview) = (
+        std::fs::read(full).unwrap(),
+        std::fs::read(preview).unwrap(),
+    );
+    assert!(!preview.is_empty());
+    assert!(preview.len() <= full.len());
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn test_store_sqlite_matches_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    let directory = dir.path().join("store");
+    let database = format!("sqlite:{}", dir.path().join("trips.db").display());
+    for store in [directory.to_str().unwrap(), &database] {
+        run(&["store", store, "put"]);
+    }
+    let list = run(&["store", &database, "list"]);
+    assert!(!list.is_empty());
+    assert_eq!(list, run(&["store", directory.to_str().unwrap(), "list"]));
+
+    let id = list.lines().next().unwrap();
+    let output = dir.path().join("trip.polyline");
+    let path = output.to_str().unwrap();
+    run(&["store", &database, "get", id, "polyline", path]);
+    assert!(!std::fs::read(path).unwrap().is_empty());
+}